@@ -0,0 +1,152 @@
+// Read-only access to images inside ZIP archives (grouping `scan_archives`).
+// An entry is addressed by a virtual path, the archive path followed by the entry
+// name (e.g. /backup/2009.zip/DCIM/IMG_0001.JPG), so it flows through scanning and
+// the GUI like a file. Entries are decompressed whole into memory; nothing inside
+// an archive is ever deleted, moved or renamed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::scanner::is_image_ext;
+
+/// Whether `path` names a ZIP archive (by extension).
+pub fn is_zip(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+}
+
+/// Split a virtual path into its archive and the entry name inside it.
+/// None for ordinary paths: no ancestor is an existing ZIP file.
+fn split(path: &Path) -> Option<(&Path, String)> {
+    let archive = path.ancestors().skip(1).find(|a| is_zip(a) && a.is_file())?;
+    let entry = path.strip_prefix(archive).ok()?;
+    let name: Vec<String> =
+        entry.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+    Some((archive, name.join("/")))
+}
+
+/// Whether `path` is an entry inside a ZIP archive, hence read-only.
+pub fn is_archive_entry(path: &Path) -> bool {
+    !path.exists() && split(path).is_some()
+}
+
+/// Virtual paths of the image entries of an archive. Directories, encrypted
+/// entries, unsafe names (absolute, `..`) and entries larger than `max_size`
+/// bytes uncompressed are left out.
+pub fn image_entries(archive: &Path, max_size: u64) -> Vec<PathBuf> {
+    let file = match fs::File::open(archive) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("[ZIP] {}: {}", archive.display(), e);
+            return Vec::new();
+        }
+    };
+    let mut zip = match zip::ZipArchive::new(file) {
+        Ok(z) => z,
+        Err(e) => {
+            eprintln!("[ZIP] {}: not a readable archive: {}", archive.display(), e);
+            return Vec::new();
+        }
+    };
+    let mut entries = Vec::new();
+    let mut too_large = 0;
+    for i in 0..zip.len() {
+        let Ok(entry) = zip.by_index_raw(i) else { continue };
+        if entry.is_dir() || entry.encrypted() {
+            continue;
+        }
+        let Some(name) = entry.enclosed_name() else { continue };
+        if !is_image_ext(&name) {
+            continue;
+        }
+        if entry.size() > max_size {
+            too_large += 1;
+            continue;
+        }
+        entries.push(archive.join(name));
+    }
+    if too_large > 0 {
+        eprintln!(
+            "[ZIP] {}: skipped {} entries larger than {} MiB",
+            archive.display(),
+            too_large,
+            max_size / (1024 * 1024)
+        );
+    }
+    entries
+}
+
+/// Bytes of a file, or of a ZIP entry when `path` points inside an archive.
+pub fn read(path: &Path) -> std::io::Result<Vec<u8>> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(bytes),
+        Err(e) => match split(path) {
+            Some((archive, name)) => read_entry(archive, &name),
+            None => Err(e),
+        },
+    }
+}
+
+/// An archive opened once: its central directory parsed into (name → size,
+/// index), and the reader kept for decompressing entries.
+struct OpenArchive {
+    mtime: SystemTime,
+    key: Option<u128>,
+    entries: HashMap<String, (u64, usize)>,
+    zip: Mutex<zip::ZipArchive<fs::File>>,
+}
+
+/// Archives opened so far, dropped and reopened when the file's mtime changes.
+fn open_archive(archive: &Path) -> std::io::Result<Arc<OpenArchive>> {
+    static OPEN: OnceLock<Mutex<HashMap<PathBuf, Arc<OpenArchive>>>> = OnceLock::new();
+    let mtime = fs::metadata(archive)?.modified()?;
+    let open = OPEN.get_or_init(Default::default);
+    if let Some(a) = open.lock().unwrap().get(archive).filter(|a| a.mtime == mtime) {
+        return Ok(a.clone());
+    }
+
+    let mut zip = zip::ZipArchive::new(fs::File::open(archive)?)?;
+    let mut entries = HashMap::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        let Ok(entry) = zip.by_index_raw(i) else { continue };
+        entries.insert(entry.name().to_string(), (entry.size(), i));
+    }
+    let key = crate::fileops::get_file_key(archive);
+    let a = Arc::new(OpenArchive { mtime, key, entries, zip: Mutex::new(zip) });
+    open.lock().unwrap().insert(archive.to_path_buf(), a.clone());
+    Ok(a)
+}
+
+fn not_found(name: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} not in archive", name))
+}
+
+fn read_entry(archive: &Path, name: &str) -> std::io::Result<Vec<u8>> {
+    let a = open_archive(archive)?;
+    let &(size, index) = a.entries.get(name).ok_or_else(|| not_found(name))?;
+    let mut zip = a.zip.lock().unwrap();
+    let mut entry = zip.by_index(index)?;
+    let mut bytes = Vec::with_capacity(size as usize);
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Size, modification time and a synthetic unique_file_id of an archive entry.
+/// The time is the archive's (entry times are local and coarse), and the id mixes
+/// the archive's own file key with the entry name, so it is stable across scans.
+pub fn entry_stat(path: &Path) -> Option<(u64, SystemTime, u128)> {
+    let (archive, name) = split(path)?;
+    let a = open_archive(archive).ok()?;
+    let &(size, _) = a.entries.get(&name)?;
+    let archive_key = a.key?;
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&archive_key.to_le_bytes());
+    hasher.update(name.as_bytes());
+    let mut id = [0u8; 16];
+    id.copy_from_slice(&hasher.finalize().as_bytes()[..16]);
+    Some((size, a.mtime, u128::from_le_bytes(id)))
+}
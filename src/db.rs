@@ -1,3 +1,4 @@
+use crate::debug_log;
 use crate::image_features::ImageFeatures;
 use crate::scanner::RAW_EXTS;
 use chacha20poly1305::{
@@ -12,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fs;
 use std::io::Write;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{
@@ -23,11 +24,20 @@ use zeroize::Zeroize;
 
 const CONFIG_FILE_NAME: &str = "phdupes.conf";
 const DB_FILE_NAME_PDQHASH: &str = "phdupes_pdqhash";
+const DB_FILE_NAME_WHASH: &str = "phdupes_whash";
 const DB_FILE_NAME_FEATURES: &str = "phdupes_features";
 const DB_FILE_NAME_PIXELHASH: &str = "phdupes_pixelhash";
+const DB_FILE_NAME_ORIENTED_PIXELHASH: &str = "phdupes_pixelhash_oriented";
 const DB_FILE_NAME_COEFFICIENTS: &str = "phdupes_coefficients";
 const DB_FILE_NAME_IGNORED: &str = "phdupes_ignored";
 const DB_FILE_NAME_IGNORED_PDQMAP: &str = "phdupes_ignored_pdqmap";
+const DB_FILE_NAME_SCAN_BASELINE: &str = "phdupes_scan_baseline";
+const DB_FILE_NAME_PROTECTED: &str = "phdupes_protected";
+const DB_FILE_NAME_PREFIXHASH: &str = "phdupes_prefixhash";
+const DB_FILE_NAME_RATINGS: &str = "phdupes_ratings";
+
+/// Stored rating of a rejected file; 1-5 are stars
+pub const REJECTED: u8 = 0;
 
 // Encryption overhead: 24-byte nonce + 16-byte Poly1305 tag
 const ENCRYPTION_OVERHEAD: usize = 24 + 16;
@@ -43,6 +53,160 @@ const DEFAULT_DB_SIZE_MB: u32 = 2048;
 pub enum HashAlgorithm {
     #[default]
     PdqHash,
+    /// 64-bit Haar wavelet hash, kept in its own database
+    WHash,
+}
+
+impl HashAlgorithm {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pdq" => Some(Self::PdqHash),
+            "whash" => Some(Self::WHash),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PdqHash => "PDQ hash",
+            Self::WHash => "wavelet hash",
+        }
+    }
+}
+
+/// JPEG decoders tried in order by `jpeg_decoders` under `[gui]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JpegTier {
+    Zune,
+    JpegDecoder,
+    Image,
+}
+
+/// What the viewer shows behind the image (cycled with Shift+K)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewerBackground {
+    /// The panel's own dark fill
+    #[default]
+    Panel,
+    Black,
+    White,
+    /// Checkerboard behind the image, showing where it is transparent
+    Checkerboard,
+}
+
+impl ViewerBackground {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Panel => Self::Black,
+            Self::Black => Self::White,
+            Self::White => Self::Checkerboard,
+            Self::Checkerboard => Self::Panel,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Panel => "panel",
+            Self::Black => "black",
+            Self::White => "white",
+            Self::Checkerboard => "checkerboard",
+        }
+    }
+}
+
+/// How the viewer samples 8-bit images when scaling them (cycled with Shift+Z)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFilter {
+    /// Smooth in the fit modes, hard pixels in manual zoom from 1:1 up
+    #[default]
+    Auto,
+    /// Always linear
+    Smooth,
+    /// Always nearest-neighbour
+    Pixel,
+}
+
+impl ImageFilter {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Auto => Self::Smooth,
+            Self::Smooth => Self::Pixel,
+            Self::Pixel => Self::Auto,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Smooth => "smooth",
+            Self::Pixel => "pixel",
+        }
+    }
+}
+
+/// What double-clicking a file in the list does (single click always just selects)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DoubleClickAction {
+    #[default]
+    None,
+    /// Toggle fullscreen, same as F
+    Fullscreen,
+    /// Open the file with `external_editor`
+    Editor,
+}
+
+/// Where file system changes in the watched folder are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FsNotifications {
+    /// The status line, unless it already shows a message
+    #[default]
+    Status,
+    /// The file system event panel (Ctrl+J), which keeps every event
+    Panel,
+    /// Not reported
+    Off,
+}
+
+/// What duplicate mode selects when a scan has finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AfterScan {
+    /// Keep the selection where it was (clamped to the new list)
+    #[default]
+    Stay,
+    /// The first file of the first group
+    First,
+    /// The first file of the first group, in fullscreen
+    Fullscreen,
+}
+
+/// Unit for GPS distances (status line, map tooltips, path length)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceUnit {
+    /// Kilometres, metres below 1 km
+    #[default]
+    Km,
+    /// Statute miles, feet below 1 mi
+    Mi,
+    /// Nautical miles
+    Nmi,
+}
+
+/// How bearings are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BearingStyle {
+    /// Degrees plus compass direction ("88.00° E")
+    #[default]
+    Cardinal,
+    /// Degrees only ("88.00°")
+    Degrees,
 }
 
 /// Palette sort order for dominant color display
@@ -55,7 +219,7 @@ pub enum PaletteSort {
 }
 
 /// Bundled palette extraction configuration
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PaletteConfig {
     pub dominant_colors: usize,
     pub saturation_bias: f32,
@@ -73,7 +237,7 @@ impl PaletteConfig {
 }
 
 /// Bundled HDR→SDR tone-mapping configuration
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct HdrConfig {
     pub sdr_peak_nits: f32,
 }
@@ -84,10 +248,135 @@ impl HdrConfig {
     }
 }
 
+/// Bundled image decoding configuration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecodeConfig {
+    /// Decode JPEGs only down to this longest edge (DCT scaling); None = full size
+    pub jpeg_preview_max: Option<u32>,
+    /// Show the embedded EXIF thumbnail while the full decode runs
+    pub exif_placeholder: bool,
+    /// Give up on a single decode after this long; None = wait forever
+    pub timeout: Option<std::time::Duration>,
+}
+
+impl DecodeConfig {
+    pub fn from_gui_config(gui: &GuiConfig) -> Self {
+        Self {
+            jpeg_preview_max: gui.jpeg_preview_max.filter(|&m| m > 0),
+            exif_placeholder: gui.exif_placeholder.unwrap_or(true),
+            timeout: match gui.decode_timeout_secs.unwrap_or(30) {
+                0 => None,
+                secs => Some(std::time::Duration::from_secs(secs)),
+            },
+        }
+    }
+}
+
+/// How files classified as screenshots (`scanner::is_screenshot`) are grouped
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenshotGrouping {
+    /// Grouped together with everything else
+    #[default]
+    Mixed,
+    /// Left out of perceptual grouping
+    Exclude,
+    /// Grouped only among themselves
+    Separate,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct GroupingConfig {
     pub ignore_same_stem: bool,
     pub extensions: Vec<String>,
+    /// Live photo companion extensions, e.g. ["mov", "jpg"]. A file with one of these
+    /// next to a same-stem image (IMG_1234.HEIC + IMG_1234.MOV) is treated as part of
+    /// that image: it is not grouped on its own and is offered for deletion with it.
+    #[serde(default)]
+    pub live_photo_exts: Vec<String>,
+    /// List files under their canonical path (default). With false, paths reached
+    /// through symlinked folders are kept as given; identity still uses unique_file_id.
+    #[serde(default = "default_resolve_symlinks")]
+    pub resolve_symlinks: bool,
+    /// Keep files whose path can't be canonicalized (e.g. a network share that went
+    /// away mid-scan) but that still exist, listed under their absolute path with a
+    /// warning (default). With false they are dropped from the listing.
+    #[serde(default = "default_keep_unresolved")]
+    pub keep_unresolved: bool,
+    /// Within each similarity group, files whose 64x64 grayscale downsamples differ by
+    /// at most this mean squared error (0-255 scale) form a near-identical subgroup.
+    /// Decodes every grouped file again; 0 (default) disables the check.
+    #[serde(default)]
+    pub near_identical_mse: f32,
+    /// Within each similarity group, a grayscale file whose 64x64 luminance downsample
+    /// differs from a color member's by at most this mean squared error is labelled as
+    /// its black-and-white version. Shares the near-identical decode; 0 (default) disables.
+    #[serde(default)]
+    pub luma_variant_mse: f32,
+    /// A group member with the same aspect ratio as another but at least this many
+    /// times fewer pixels is flagged as a smaller copy of it (list badge, --json).
+    /// Default 2.0; 0 disables.
+    #[serde(default = "default_smaller_copy_ratio")]
+    pub smaller_copy_ratio: f32,
+    /// How the file to keep in a group is chosen ("Mark all but keeper", --clean
+    /// --keep best): an ordered list of "criterion [asc|desc]", later rules breaking
+    /// ties of earlier ones. Criteria: ext_priority (order of `extensions`),
+    /// resolution, size, mtime, raw, path_length. Protected files always win.
+    /// Empty means the default ["resolution desc", "ext_priority", "size desc"].
+    #[serde(default)]
+    pub keep_rules: Vec<String>,
+    /// Per-extension similarity thresholds, e.g. { png = 24, jpg = 48 }, overriding the
+    /// global --similarity for files of that type (see `ScanConfig::similarity_for`)
+    #[serde(default)]
+    pub similarity_by_ext: HashMap<String, u32>,
+    /// Split groups whose members are more than this far apart (pairwise) into tighter
+    /// subclusters, so a chain of small steps doesn't make one giant group. Unset keeps
+    /// plain transitive grouping.
+    #[serde(default)]
+    pub cluster_split_dist: Option<u32>,
+    /// Screenshots (screenshot tool in Software, or no camera EXIF and an exact
+    /// screen size): "mixed" (default), "exclude" or "separate"
+    #[serde(default)]
+    pub screenshots: ScreenshotGrouping,
+    /// Write freedesktop.org thumbnails (~/.cache/thumbnails) for files decoded while
+    /// scanning, so file managers don't have to make their own. Valid ones are kept.
+    #[serde(default)]
+    pub os_thumbnails: bool,
+    /// Treat .zip archives as folders in duplicate mode: their image entries are read
+    /// into memory and hashed like files, read-only (they can't be deleted or moved)
+    #[serde(default)]
+    pub scan_archives: bool,
+    /// Read the embedded ICC profile of every hashed file (cached with its features), so
+    /// copies with the same pixels but another color profile are told apart in the list
+    /// ("C1 (sRGB)")
+    #[serde(default)]
+    pub icc_profiles: bool,
+    /// Largest image, in pixels (width x height from the file header), that is decoded
+    /// in full; larger ones are skipped when hashing and shown from a scaled JPEG decode
+    /// or the embedded preview in the viewer. Guards against decompression bombs.
+    /// 0 = no limit, default 500000000.
+    #[serde(default = "default_max_decode_pixels")]
+    pub max_decode_pixels: u64,
+    /// Directories scanned together in duplicate mode when no paths are given on the
+    /// command line; missing ones are skipped with a warning.
+    #[serde(default)]
+    pub watch_dirs: Vec<PathBuf>,
+}
+
+fn default_resolve_symlinks() -> bool {
+    true
+}
+
+fn default_keep_unresolved() -> bool {
+    true
+}
+
+fn default_smaller_copy_ratio() -> f32 {
+    2.0
+}
+
+fn default_max_decode_pixels() -> u64 {
+    crate::scanner::DEFAULT_MAX_DECODE_PIXELS
 }
 
 impl Default for GroupingConfig {
@@ -96,7 +385,29 @@ impl Default for GroupingConfig {
         let mut extensions = vec!["jpg".to_string(), "jpeg".to_string()];
         // Dynamically add all raw extensions from the const list
         extensions.extend(RAW_EXTS.iter().map(|s| s.to_string()));
-        Self { ignore_same_stem: true, extensions }
+        Self {
+            ignore_same_stem: true,
+            extensions,
+            live_photo_exts: Vec::new(),
+            resolve_symlinks: true,
+            keep_unresolved: true,
+            near_identical_mse: 0.0,
+            luma_variant_mse: 0.0,
+            smaller_copy_ratio: 2.0,
+            keep_rules: vec![
+                "resolution desc".to_string(),
+                "ext_priority".to_string(),
+                "size desc".to_string(),
+            ],
+            similarity_by_ext: HashMap::new(),
+            cluster_split_dist: None,
+            screenshots: ScreenshotGrouping::Mixed,
+            os_thumbnails: false,
+            scan_archives: false,
+            icc_profiles: false,
+            max_decode_pixels: crate::scanner::DEFAULT_MAX_DECODE_PIXELS,
+            watch_dirs: Vec::new(),
+        }
     }
 }
 
@@ -115,11 +426,122 @@ pub struct GuiConfig {
     pub height: Option<u32>,
     pub panel_width: Option<f32>,
     pub decimal_coords: Option<bool>,
+    /// GPS distances in "km" (default), "mi" or "nmi"
+    pub distance_units: Option<DistanceUnit>,
+    /// Report changes in the watched folder on the status line ("status", default),
+    /// in the event panel (Ctrl+J, "panel"), or not at all ("off")
+    pub fs_notifications: Option<FsNotifications>,
+    /// Duplicate mode, once a scan is done: keep the selection ("stay", default), select
+    /// the first file of the first group ("first"), or do so in fullscreen ("fullscreen")
+    pub after_scan: Option<AfterScan>,
+    /// Decimal places of distances (above 1 km / 1 mi) and bearings (default 2)
+    pub distance_decimals: Option<usize>,
+    /// Bearings as "cardinal" (degrees + compass direction, default) or plain "degrees"
+    pub bearing_style: Option<BearingStyle>,
     /// Target display peak luminance (nits) for HDR→SDR tone mapping.
     /// 100.0 = strict SDR reference; 203.0 = BT.2408 HDR reference white (default).
     pub sdr_peak_nits: Option<f32>,
+    /// Repaint polling interval (ms) while background work is in flight.
+    /// When idle the GUI only redraws on input events.
+    pub repaint_poll_ms: Option<u64>,
+    /// Read GPS + EXIF date for uncached files during the view-mode directory scan,
+    /// so geotags and date sort work on first paint (costs extra I/O).
+    pub dir_scan_exif: Option<bool>,
+    /// Show list resolutions after EXIF rotation (false = stored sensor W x H)
+    pub oriented_resolution: Option<bool>,
+    /// Share of the preload window placed ahead of the current file (0..1, 0.5 = even)
+    pub preload_forward_bias: Option<f32>,
+    /// Shift the preload window towards the direction of recent navigation
+    pub preload_auto_bias: Option<bool>,
+    /// Move "deleted" files into dated subfolders here instead of the OS trash
+    /// (for filesystems without a working trash). Restore with --restore-staged.
+    pub staging_dir: Option<PathBuf>,
+    /// Folder that Shift+D moves marked files into, below their path relative to the
+    /// scan root, for a look before deleting. The action bar's "Restore review" (or
+    /// --restore-staged <dir>) moves them back.
+    pub review_dir: Option<PathBuf>,
+    /// Decode JPEGs at 1/2, 1/4 or 1/8 scale when that still covers this longest edge
+    /// in pixels (much faster on large files, but zooming past it shows the lower
+    /// resolution). Unset = always decode at full size.
+    pub jpeg_preview_max: Option<u32>,
+    /// Show the embedded EXIF thumbnail as a low-res placeholder while an image decodes
+    pub exif_placeholder: Option<bool>,
+    /// JPEG decoder order, e.g. ["jpeg-decoder", "zune"]; decoders left out are not
+    /// tried. Default ["zune", "jpeg-decoder", "image"]. Also used when hashing.
+    pub jpeg_decoders: Option<Vec<JpegTier>>,
+    /// Background behind the image: panel, black, white or checkerboard
+    pub viewer_background: Option<ViewerBackground>,
+    /// Image sampling when zoomed: auto, smooth or pixel
+    pub image_filter: Option<ImageFilter>,
+    /// Duplicate mode: show the action bar (counts, bulk mark/delete); toggled with Ctrl+B
+    pub action_bar: Option<bool>,
+    /// Duplicate mode: sort marked files to the top of each group so the keeper sits last
+    pub keeper_last: Option<bool>,
+    /// Duplicate mode display filter (action bar): hide groups with fewer files than
+    /// this, more than `max_group_size` (0 = no limit), or a larger distance than
+    /// `max_group_dist`. Filters the found groups only; nothing is rescanned.
+    pub min_group_size: Option<usize>,
+    pub max_group_size: Option<usize>,
+    pub max_group_dist: Option<u32>,
+    /// Badge list rows with the key tags a file lacks ("no-date", "no-gps", "no-camera")
+    /// and with "screenshot" (default off)
+    pub missing_tag_badges: Option<bool>,
+    /// Show the aspect ratio (width / height, EXIF rotation applied) after the
+    /// resolution in list rows
+    pub show_aspect_ratio: Option<bool>,
+    /// Next/Prev wrap around at the end and start of the list instead of stopping.
+    /// Crossing from one group into the next happens either way.
+    pub nav_wrap: Option<bool>,
+    /// Use the window manager's title bar instead of the built-in one (non-Windows);
+    /// for tiling window managers. Needs a restart.
+    pub native_decorations: Option<bool>,
+    /// Double-clicking a file row: "none", "fullscreen" or "editor"
+    pub double_click_action: Option<DoubleClickAction>,
+    /// Command for double_click_action = "editor", e.g. "gimp" or "darktable --"; the
+    /// file path is passed as the last argument
+    pub external_editor: Option<String>,
+    /// View mode: directory rows select on click and open on double-click (default:
+    /// a single click opens them)
+    pub dir_double_click: Option<bool>,
+    /// Timezone for EXIF capture times in the list and EXIF overlay: "photo" (as shot,
+    /// default), "local", "UTC" or a fixed offset like "+09:00". Files without an
+    /// OffsetTimeOriginal tag are taken to be local time.
+    pub display_timezone: Option<String>,
+    /// Print debug output, same as `--verbose`
+    pub verbose: Option<bool>,
+    /// Seconds before a stuck image decode is abandoned and shown as failed (default 30,
+    /// 0 = no limit)
+    pub decode_timeout_secs: Option<u64>,
+    /// View mode: PDQ distance for the "similar" badge on images with a near-duplicate
+    /// in the same directory, e.g. 30 (default unset = don't hash the directory)
+    pub similar_threshold: Option<u32>,
+    /// Contact sheet (Ctrl+E): thumbnails per row (default 6)
+    pub contact_sheet_cols: Option<u32>,
+    /// Contact sheet thumbnail size in pixels (default 256)
+    pub contact_sheet_cell: Option<u32>,
+    /// Contact sheet file type: "png" (default) or "jpg"
+    pub contact_sheet_format: Option<String>,
+    /// Contact sheet folder; default the user's Pictures folder
+    pub contact_sheet_dir: Option<PathBuf>,
+    /// Web export (Ctrl+Shift+E): longest edge in pixels (default 2048)
+    pub web_export_max: Option<u32>,
+    /// Web export JPEG quality, 1-100 (default 85)
+    pub web_export_quality: Option<u8>,
+    /// Web export folder; default `web_export_<date>_<time>` in the working directory
+    pub web_export_dir: Option<PathBuf>,
     #[serde(default = "default_exif_tags")]
     pub exif_tags: Vec<String>,
+    /// "Open with" context menu entries (`[[gui.open_with]]` tables)
+    #[serde(default)]
+    pub open_with: Vec<OpenWithEntry>,
+}
+
+/// One "Open with" launcher. `command` is split on whitespace; `{path}` in it is
+/// replaced by the file path, which is appended as the last argument when absent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OpenWithEntry {
+    pub name: String,
+    pub command: String,
 }
 
 fn default_exif_tags() -> Vec<String> {
@@ -152,8 +574,49 @@ impl Default for GuiConfig {
             height: Some(720),
             panel_width: Some(450.0),
             decimal_coords: Some(true),
+            distance_units: None,
+            fs_notifications: None,
+            after_scan: None,
+            distance_decimals: Some(2),
+            bearing_style: None,
             sdr_peak_nits: Some(203.0),
+            repaint_poll_ms: Some(100),
+            dir_scan_exif: Some(false),
+            oriented_resolution: Some(true),
+            preload_forward_bias: Some(0.5),
+            preload_auto_bias: Some(false),
+            staging_dir: None,
+            review_dir: None,
+            jpeg_preview_max: None,
+            exif_placeholder: Some(true),
+            jpeg_decoders: None,
+            viewer_background: None,
+            image_filter: None,
+            action_bar: Some(true),
+            keeper_last: Some(false),
+            min_group_size: None,
+            max_group_size: None,
+            max_group_dist: None,
+            missing_tag_badges: Some(false),
+            show_aspect_ratio: Some(false),
+            nav_wrap: Some(false),
+            native_decorations: Some(false),
+            double_click_action: None,
+            external_editor: None,
+            dir_double_click: Some(false),
+            display_timezone: None,
+            verbose: None,
+            decode_timeout_secs: Some(30),
+            similar_threshold: None,
+            contact_sheet_cols: Some(6),
+            contact_sheet_cell: Some(256),
+            contact_sheet_format: None,
+            contact_sheet_dir: None,
+            web_export_max: Some(2048),
+            web_export_quality: Some(85),
+            web_export_dir: None,
             exif_tags: default_exif_tags(),
+            open_with: Vec::new(),
         }
     }
 }
@@ -235,6 +698,24 @@ impl IgnoredEntry {
     }
 }
 
+/// Snapshot of a scan used by `--changes` to report what changed between runs.
+/// Key: keyed blake3 of the sorted scan roots, so each root set has its own baseline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanBaseline {
+    pub timestamp: u64, // Unix epoch seconds when the baseline was saved
+    pub files: Vec<(String, [u8; 32])>, // (path, content_hash)
+}
+
+impl ScanBaseline {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, postcard::Error> {
+        postcard::to_stdvec(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+}
+
 /// Result of background enrichment for a file.
 /// This struct is designed to be extensible - add new fields as needed.
 #[derive(Debug, Clone)]
@@ -246,6 +727,24 @@ pub struct EnrichmentResult {
     pub features: Option<ImageFeatures>,
 }
 
+/// Cache statistics shown in the cache panel (`AppContext::db_stats`).
+#[derive(Debug, Clone, Default)]
+pub struct DbStats {
+    /// Files in the metadata cache
+    pub files: usize,
+    pub hashes: usize,
+    pub features: usize,
+    pub coefficients: usize,
+    pub pixel_hashes: usize,
+    /// Bytes in pages holding data, over all databases
+    pub used_bytes: u64,
+    /// Bytes up to the highest page ever written: the size of the data file. The gap
+    /// to `used_bytes` is free pages LMDB reuses before growing the file.
+    pub file_bytes: u64,
+    /// Configured map size (db_size_mb), the upper limit of the file
+    pub map_bytes: u64,
+}
+
 pub struct AppContext {
     pub env: Arc<Environment>,
     pub hash_db: Database,
@@ -253,13 +752,19 @@ pub struct AppContext {
     pub feature_db: Database,
     pub coeff_db: Database, // Separate DB for PDQ coefficients
     pub pixel_db: Database,
-    pub ignored_db: Database, // Registered/ignored files (duplicate finder)
+    pub oriented_pixel_db: Database, // Pixel hash after applying EXIF orientation
+    pub ignored_db: Database,        // Registered/ignored files (duplicate finder)
     pub ignored_pdqmap_db: Database, // Maps pdqhash → UUID for cross-session stability
+    pub baseline_db: Database,       // Scan root set → ScanBaseline (--changes)
+    pub protected_db: Database,      // content_hash → protection timestamp
+    pub prefix_db: Database,         // meta_key → prefix length, prefix hash, timestamp
+    pub ratings_db: Database,        // content_hash → rating (see `REJECTED`), timestamp
     pub content_key: [u8; 32],
     pub meta_key: [u8; 32],
+    pub algorithm: HashAlgorithm, // Selects the database and what hash_db holds
     pub grouping_config: GroupingConfig,
     pub gui_config: GuiConfig,
-    pub locations: HashMap<String, Point<f64>>,
+    pub locations: RwLock<HashMap<String, Point<f64>>>, // Grows via add_location
     pub map_providers: HashMap<String, String>,
     pub selected_provider: String,
     pub tile_cache_path: PathBuf, // Path for walkers to store images
@@ -269,15 +774,17 @@ pub struct AppContext {
 /// Database update type
 pub enum HashValue {
     PdqHash([u8; 32]),
+    WHash(u64),
 }
 
-// (Meta Update, Hash Update, Feature Update, Coefficients Update, Pixel Hash)
+// (Meta Update, Hash Update, Feature Update, Coefficients Update, Pixel Hash, Oriented Pixel Hash)
 pub type DbUpdate = (
     Option<([u8; 32], [u8; 32])>,           // Meta: meta_key -> content_hash
     Option<([u8; 32], HashValue)>,          // Hash: content_hash -> pdqhash
     Option<([u8; 32], ImageFeatures)>,      // Features: content_hash -> ImageFeatures
     Option<([u8; 32], CachedCoefficients)>, // Coefficients: content_hash -> coefficients (dupe mode only)
     Option<([u8; 32], [u8; 32])>,           // Pixel Hash
+    Option<([u8; 32], [u8; 32])>,           // Oriented Pixel Hash
 );
 
 /// Compute the meta_key from file metadata.
@@ -328,7 +835,7 @@ pub fn create_feature_update(
     let metadata = std::fs::metadata(path).ok()?;
     let meta_key = compute_meta_key_from_metadata(meta_key_secret, &metadata, unique_file_id);
 
-    Some((Some((meta_key, content_hash)), None, Some((content_hash, features)), None, None))
+    Some((Some((meta_key, content_hash)), None, Some((content_hash, features)), None, None, None))
 }
 
 impl AppContext {
@@ -367,21 +874,31 @@ impl AppContext {
     pub fn with_algorithm(algorithm: HashAlgorithm) -> Result<Self, Box<dyn std::error::Error>> {
         let config_dir = dirs::config_dir().ok_or("No config dir found")?;
         let cache_dir = dirs::cache_dir().ok_or("No cache dir found")?;
+        Self::with_dirs(algorithm, &config_dir, &cache_dir)
+    }
 
-        fs::create_dir_all(&config_dir)?;
-        fs::create_dir_all(&cache_dir)?;
+    /// Open the config file in `config_dir` and the databases in `cache_dir`
+    /// instead of the user's config and cache folders.
+    pub fn with_dirs(
+        algorithm: HashAlgorithm,
+        config_dir: &Path,
+        cache_dir: &Path,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(config_dir)?;
+        fs::create_dir_all(cache_dir)?;
         let tile_cache_path = cache_dir.join("phdupes_tiles");
         fs::create_dir_all(&tile_cache_path)?;
         let config_path = config_dir.join(CONFIG_FILE_NAME);
 
         let db_file_name = match algorithm {
             HashAlgorithm::PdqHash => DB_FILE_NAME_PDQHASH,
+            HashAlgorithm::WHash => DB_FILE_NAME_WHASH,
         };
         let db_path = cache_dir.join(db_file_name);
 
         let mut config = if config_path.exists() {
             let content = fs::read_to_string(&config_path)?;
-            eprintln!("[DEBUG-DB] Loading config from {:?}", config_path);
+            debug_log!("[DEBUG-DB] Loading config from {:?}", config_path);
 
             let mut cfg: Config = toml::from_str(&content)
                 .map_err(|_| "Failed to parse config. Format might have changed.")?;
@@ -392,7 +909,7 @@ impl AppContext {
             cfg.gui.dominant_colors = Some(clamped_val);
             let colors_clamped = original_colors != cfg.gui.dominant_colors;
 
-            eprintln!(
+            debug_log!(
                 "[DEBUG-DB] Loaded gui config: width={:?}, height={:?}, panel_width={:?}, dominant_colors={:?}, saturation_bias={:?}, palette_sort={:?}, sdr_peak_nits={:?}",
                 cfg.gui.width,
                 cfg.gui.height,
@@ -402,8 +919,8 @@ impl AppContext {
                 cfg.gui.palette_sort,
                 cfg.gui.sdr_peak_nits
             );
-            eprintln!("[DEBUG-DB] LMDB map size: {} MiB", cfg.db_size_mb);
-            eprintln!("[DEBUG-DB] Locations loaded: {}", cfg.locations.len());
+            debug_log!("[DEBUG-DB] LMDB map size: {} MiB", cfg.db_size_mb);
+            debug_log!("[DEBUG-DB] Locations loaded: {}", cfg.locations.len());
 
             // Check for missing sections to write back defaults
             let raw_value: toml::Value =
@@ -436,7 +953,7 @@ impl AppContext {
                 || missing_locations
                 || colors_clamped
             {
-                eprintln!(
+                debug_log!(
                     "[DEBUG-DB] Writing back defaults/fixes (grouping={}, gui={}, db_size={}, locations={}, colors_clamped={})",
                     missing_grouping,
                     missing_gui,
@@ -448,7 +965,7 @@ impl AppContext {
             }
             cfg
         } else {
-            eprintln!(
+            debug_log!(
                 "[DEBUG-DB] Config file does not exist, creating new one at {:?}",
                 config_path
             );
@@ -484,7 +1001,7 @@ impl AppContext {
         let mut master_key_bytes = match Self::decode_master_key(&config.master_key) {
             Ok(bytes) => bytes,
             Err(e) => {
-                eprintln!("[DEBUG-DB] Invalid master_key: {}. Generating new key.", e);
+                debug_log!("[DEBUG-DB] Invalid master_key: {}. Generating new key.", e);
 
                 let mut new_key = [0u8; 32];
                 getrandom::fill(&mut new_key)?;
@@ -492,7 +1009,7 @@ impl AppContext {
 
                 // Save updated config with new key
                 Self::write_config(&config_path, &config)?;
-                eprintln!(
+                debug_log!(
                     "[DEBUG-DB] Saved new master_key to config file. Cache will be invalidated."
                 );
 
@@ -517,14 +1034,15 @@ impl AppContext {
 
         // Calculate map size from config (convert MiB to bytes)
         let map_size = (config.db_size_mb as usize) * 1024 * 1024;
-        eprintln!(
+        debug_log!(
             "[DEBUG-DB] Setting LMDB map size to {} bytes ({} MiB)",
-            map_size, config.db_size_mb
+            map_size,
+            config.db_size_mb
         );
 
         let env = Environment::new()
             .set_map_size(map_size)
-            .set_max_dbs(10)
+            .set_max_dbs(12)
             .set_max_readers(512)
             .open(&db_path)?;
 
@@ -572,10 +1090,24 @@ impl AppContext {
         let feature_db = env.create_db(Some(DB_FILE_NAME_FEATURES), DatabaseFlags::empty())?;
         let coeff_db = env.create_db(Some(DB_FILE_NAME_COEFFICIENTS), DatabaseFlags::empty())?;
         let pixel_db = env.create_db(Some(DB_FILE_NAME_PIXELHASH), DatabaseFlags::empty())?;
+        let oriented_pixel_db =
+            env.create_db(Some(DB_FILE_NAME_ORIENTED_PIXELHASH), DatabaseFlags::empty())?;
         let ignored_db = env.create_db(Some(DB_FILE_NAME_IGNORED), DatabaseFlags::empty())?;
         let ignored_pdqmap_db =
             env.create_db(Some(DB_FILE_NAME_IGNORED_PDQMAP), DatabaseFlags::empty())?;
+        let baseline_db =
+            env.create_db(Some(DB_FILE_NAME_SCAN_BASELINE), DatabaseFlags::empty())?;
+        let protected_db = env.create_db(Some(DB_FILE_NAME_PROTECTED), DatabaseFlags::empty())?;
+        let prefix_db = env.create_db(Some(DB_FILE_NAME_PREFIXHASH), DatabaseFlags::empty())?;
+        let ratings_db = env.create_db(Some(DB_FILE_NAME_RATINGS), DatabaseFlags::empty())?;
         // Convert the locations into runtime usable Points
+        if config.gui.verbose == Some(true) {
+            crate::scanner::set_verbose(true);
+        }
+        crate::scanner::set_jpeg_tiers(config.gui.jpeg_decoders.as_deref().unwrap_or_default());
+        crate::scanner::set_max_decode_pixels(config.grouping.max_decode_pixels);
+        crate::scanner::set_resolve_symlinks(config.grouping.resolve_symlinks);
+        crate::scanner::set_keep_unresolved(config.grouping.keep_unresolved);
         let locations: HashMap<String, Point<f64>> =
             config.locations.into_iter().map(|(name, option)| (name, option.into())).collect();
 
@@ -586,13 +1118,19 @@ impl AppContext {
             feature_db,
             coeff_db,
             pixel_db,
+            oriented_pixel_db,
             ignored_db,
             ignored_pdqmap_db,
+            baseline_db,
+            protected_db,
+            prefix_db,
+            ratings_db,
             content_key,
             meta_key,
+            algorithm,
             grouping_config: config.grouping,
             gui_config: config.gui,
-            locations,
+            locations: RwLock::new(locations),
             map_providers: config.map_providers,
             selected_provider: config
                 .selected_provider
@@ -675,6 +1213,24 @@ impl AppContext {
         }
     }
 
+    /// Get wavelet hash (64-bit) from database (only in the whash database)
+    pub fn get_whash(&self, content_hash: &[u8; 32]) -> Result<Option<u64>, lmdb::Error> {
+        let txn = self.env.begin_ro_txn()?;
+        match txn.get(self.hash_db, content_hash) {
+            Ok(encrypted_bytes) => {
+                if let Some(decrypted) = self.decrypt_value(content_hash, encrypted_bytes) {
+                    let arr: [u8; 8] = decrypted.try_into().map_err(|_| lmdb::Error::Corrupted)?;
+                    Ok(Some(u64::from_le_bytes(arr)))
+                } else {
+                    eprintln!("[ERROR-DB] get_whash Corrupted content_hash={:x?}", content_hash);
+                    Err(lmdb::Error::Corrupted)
+                }
+            }
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Get cached features from database
     pub fn get_features(
         &self,
@@ -747,8 +1303,24 @@ impl AppContext {
     }
 
     pub fn get_pixel_hash(&self, content_hash: &[u8; 32]) -> Result<Option<[u8; 32]>, lmdb::Error> {
+        self.get_pixel_hash_from(self.pixel_db, content_hash)
+    }
+
+    /// Pixel hash computed after applying EXIF orientation (see --oriented-pixel-hash).
+    pub fn get_oriented_pixel_hash(
+        &self,
+        content_hash: &[u8; 32],
+    ) -> Result<Option<[u8; 32]>, lmdb::Error> {
+        self.get_pixel_hash_from(self.oriented_pixel_db, content_hash)
+    }
+
+    fn get_pixel_hash_from(
+        &self,
+        db: Database,
+        content_hash: &[u8; 32],
+    ) -> Result<Option<[u8; 32]>, lmdb::Error> {
         let txn = self.env.begin_ro_txn()?;
-        match txn.get(self.pixel_db, content_hash) {
+        match txn.get(db, content_hash) {
             Ok(encrypted_bytes) => {
                 if let Some(decrypted) = self.decrypt_value(content_hash, encrypted_bytes) {
                     let arr: [u8; 32] = decrypted.try_into().map_err(|_| lmdb::Error::Corrupted)?;
@@ -772,7 +1344,7 @@ impl AppContext {
         // CHANGED return type
         let meta_key = compute_meta_key_from_metadata(&self.meta_key, metadata, unique_file_id);
 
-        eprintln!(
+        debug_log!(
             "[DEBUG-CACHE] lookup_cached_features: size={}, unique_file_id={}, meta_key={:?}",
             metadata.len(),
             unique_file_id,
@@ -785,11 +1357,11 @@ impl AppContext {
                 return None;
             }
 
-            eprintln!("[DEBUG-CACHE]   Found content_hash: {:?}", hex::encode(&content_hash[..8]));
+            debug_log!("[DEBUG-CACHE]   Found content_hash: {:?}", hex::encode(&content_hash[..8]));
 
             // Look up cached features from content_hash
             if let Ok(Some(features)) = self.get_features(&content_hash) {
-                eprintln!(
+                debug_log!(
                     "[DEBUG-CACHE]   Found features: resolution={:?}, orientation={}, gps_pos={:#?}, exif_timestamp={:?}",
                     features.resolution(),
                     features.orientation(),
@@ -798,11 +1370,11 @@ impl AppContext {
                 );
                 return Some(features);
             } else {
-                eprintln!("[DEBUG-CACHE]   No features found for content_hash");
+                debug_log!("[DEBUG-CACHE]   No features found for content_hash");
             }
         }
 
-        eprintln!("[DEBUG-CACHE]   Not found in database");
+        debug_log!("[DEBUG-CACHE]   Not found in database");
         None
     }
 
@@ -945,16 +1517,36 @@ impl AppContext {
             }
         }
 
-        // 5. Sweep PixelDB
-        if txn.stat(self.pixel_db)?.entries() > 0 {
-            let mut cursor = txn.open_rw_cursor(self.pixel_db)?;
+        // 5. Sweep PixelDB and OrientedPixelDB
+        for db in [self.pixel_db, self.oriented_pixel_db] {
+            if txn.stat(db)?.entries() > 0 {
+                let mut cursor = txn.open_rw_cursor(db)?;
+                for iter in cursor.iter_start() {
+                    if let Ok((key, _)) = iter
+                        && key.len() == 32
+                    {
+                        let mut k = [0u8; 32];
+                        k.copy_from_slice(key);
+                        if !valid_content_hashes.contains(&k) {
+                            cursor.del(WriteFlags::empty())?;
+                        }
+                    }
+                }
+            }
+        }
+        // 6. Expire PrefixDB entries by their own timestamp (they need no meta entry)
+        if txn.stat(self.prefix_db)?.entries() > 0 {
+            let mut cursor = txn.open_rw_cursor(self.prefix_db)?;
             for iter in cursor.iter_start() {
-                if let Ok((key, _)) = iter
-                    && key.len() == 32
-                {
-                    let mut k = [0u8; 32];
-                    k.copy_from_slice(key);
-                    if !valid_content_hashes.contains(&k) {
+                if let Ok((key, val_bytes)) = iter {
+                    let expired = match self.decrypt_value(key, val_bytes) {
+                        Some(decrypted) if decrypted.len() == 44 => {
+                            let ts_bytes: [u8; 8] = decrypted[36..44].try_into().unwrap();
+                            u64::from_le_bytes(ts_bytes) < cutoff
+                        }
+                        _ => true,
+                    };
+                    if expired {
                         cursor.del(WriteFlags::empty())?;
                     }
                 }
@@ -964,6 +1556,103 @@ impl AppContext {
         Ok((meta_remove_count, hash_remove_count))
     }
 
+    /// Delete metadata-cache entries by meta key (used by `--verify --purge-stale`).
+    /// Content-keyed data is left for `--prune` to sweep once nothing refers to it.
+    pub fn remove_meta_entries(&self, meta_keys: &[[u8; 32]]) -> Result<usize, lmdb::Error> {
+        let mut txn = self.env.begin_rw_txn()?;
+        let mut removed = 0;
+        for key in meta_keys {
+            match txn.del(self.meta_db, key, None) {
+                Ok(()) => removed += 1,
+                Err(lmdb::Error::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        txn.commit()?;
+        Ok(removed)
+    }
+
+    /// Entry counts and sizes of the cache, for the cache panel (Ctrl+I).
+    pub fn db_stats(&self) -> Result<DbStats, lmdb::Error> {
+        let txn = self.env.begin_ro_txn()?;
+        let page_bytes = |s: &lmdb::Stat| {
+            (s.branch_pages() + s.leaf_pages() + s.overflow_pages()) as u64 * s.page_size() as u64
+        };
+        let mut stats = DbStats::default();
+        for (db, count) in [
+            (self.meta_db, &mut stats.files),
+            (self.feature_db, &mut stats.features),
+            (self.coeff_db, &mut stats.coefficients),
+            (self.pixel_db, &mut stats.pixel_hashes),
+        ] {
+            let stat = txn.stat(db)?;
+            *count = stat.entries();
+            stats.used_bytes += page_bytes(&stat);
+        }
+        for db in [
+            self.oriented_pixel_db,
+            self.ignored_db,
+            self.ignored_pdqmap_db,
+            self.baseline_db,
+            self.protected_db,
+            self.prefix_db,
+            self.ratings_db,
+        ] {
+            stats.used_bytes += page_bytes(&txn.stat(db)?);
+        }
+
+        // The main database also holds the names of the others; count only hashes
+        let main = txn.stat(self.hash_db)?;
+        stats.used_bytes += page_bytes(&main);
+        if main.entries() > 0 {
+            let mut cursor = txn.open_ro_cursor(self.hash_db)?;
+            stats.hashes = cursor.iter_start().flatten().filter(|(key, _)| key.len() == 32).count();
+        }
+
+        let info = self.env.info()?;
+        let page_size = self.env.stat()?.page_size() as u64;
+        stats.file_bytes = (info.last_pgno() as u64 + 1) * page_size;
+        stats.map_bytes = info.map_size() as u64;
+        Ok(stats)
+    }
+
+    /// Drop content-keyed entries (hashes, features, coefficients, pixel hashes) that no
+    /// cached file refers to any more; returns the number of hashes dropped. LMDB reuses
+    /// the freed pages, the data file itself only shrinks with `mdb_copy -c`.
+    pub fn compact(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        // Nothing is old enough to expire, so this only sweeps unreferenced entries
+        self.prune(u64::MAX).map(|(_, hashes)| hashes)
+    }
+
+    /// Empty the file cache (metadata, hashes, features, coefficients, pixel and prefix
+    /// hashes), so the next scan reads and hashes every file again. Ignored, protected
+    /// and rated files and the --changes baselines are kept.
+    pub fn clear_cache(&self) -> Result<(), lmdb::Error> {
+        let mut txn = self.env.begin_rw_txn()?;
+        for db in [
+            self.meta_db,
+            self.feature_db,
+            self.coeff_db,
+            self.pixel_db,
+            self.oriented_pixel_db,
+            self.prefix_db,
+        ] {
+            txn.clear_db(db)?;
+        }
+        // The main database also holds the names of the others: delete only hashes
+        if txn.stat(self.hash_db)?.entries() > 0 {
+            let mut cursor = txn.open_rw_cursor(self.hash_db)?;
+            for iter in cursor.iter_start() {
+                if let Ok((key, _)) = iter
+                    && key.len() == 32
+                {
+                    cursor.del(WriteFlags::empty())?;
+                }
+            }
+        }
+        txn.commit()
+    }
+
     pub fn start_db_writer(&self, rx: Receiver<DbUpdate>) -> thread::JoinHandle<()> {
         let env = self.env.clone();
         let meta_db = self.meta_db;
@@ -971,6 +1660,7 @@ impl AppContext {
         let feature_db = self.feature_db;
         let coeff_db = self.coeff_db;
         let pixel_db = self.pixel_db;
+        let oriented_pixel_db = self.oriented_pixel_db;
         let cipher = self.cipher.clone();
 
         thread::spawn(move || {
@@ -979,6 +1669,7 @@ impl AppContext {
             let mut feature_updates = Vec::new();
             let mut coeff_updates = Vec::new();
             let mut pixel_updates = Vec::new();
+            let mut oriented_pixel_updates = Vec::new();
 
             let mut last_flush = Instant::now();
             let flush_interval = Duration::from_secs(1);
@@ -994,7 +1685,7 @@ impl AppContext {
             loop {
                 let msg = rx.recv_timeout(Duration::from_millis(100));
                 match msg {
-                    Ok((m, h, f, c, p)) => {
+                    Ok((m, h, f, c, p, o)) => {
                         if let Some(up) = m {
                             meta_updates.push(up);
                         }
@@ -1010,6 +1701,9 @@ impl AppContext {
                         if let Some(up) = p {
                             pixel_updates.push(up);
                         }
+                        if let Some(up) = o {
+                            oriented_pixel_updates.push(up);
+                        }
                     }
                     Err(RecvTimeoutError::Disconnected) => {
                         // Channel closed: attempt a final flush. Retry a few times
@@ -1025,11 +1719,13 @@ impl AppContext {
                                 feature_db,
                                 coeff_db,
                                 pixel_db,
+                                oriented_pixel_db,
                                 &meta_updates,
                                 &hash_updates,
                                 &feature_updates,
                                 &coeff_updates,
                                 &pixel_updates,
+                                &oriented_pixel_updates,
                             ) {
                                 Ok(()) => break,
                                 Err(e) => {
@@ -1043,7 +1739,8 @@ impl AppContext {
                                             + hash_updates.len()
                                             + feature_updates.len()
                                             + coeff_updates.len()
-                                            + pixel_updates.len();
+                                            + pixel_updates.len()
+                                            + oriented_pixel_updates.len();
                                         eprintln!(
                                             "[ERROR-DB] Giving up on final flush; {} cache updates lost",
                                             lost
@@ -1063,12 +1760,14 @@ impl AppContext {
                     || !hash_updates.is_empty()
                     || !feature_updates.is_empty()
                     || !coeff_updates.is_empty()
-                    || !pixel_updates.is_empty();
+                    || !pixel_updates.is_empty()
+                    || !oriented_pixel_updates.is_empty();
                 let buffer_full = meta_updates.len() >= max_buffer
                     || hash_updates.len() >= max_buffer
                     || feature_updates.len() >= max_buffer
                     || coeff_updates.len() >= max_buffer
-                    || pixel_updates.len() >= max_buffer;
+                    || pixel_updates.len() >= max_buffer
+                    || oriented_pixel_updates.len() >= max_buffer;
                 let time_elapsed = last_flush.elapsed() >= flush_interval;
 
                 // After a failure, suppress the buffer-full trigger so we retry at
@@ -1086,11 +1785,13 @@ impl AppContext {
                         feature_db,
                         coeff_db,
                         pixel_db,
+                        oriented_pixel_db,
                         &meta_updates,
                         &hash_updates,
                         &feature_updates,
                         &coeff_updates,
                         &pixel_updates,
+                        &oriented_pixel_updates,
                     ) {
                         Ok(()) => {
                             meta_updates.clear();
@@ -1098,6 +1799,7 @@ impl AppContext {
                             feature_updates.clear();
                             coeff_updates.clear();
                             pixel_updates.clear();
+                            oriented_pixel_updates.clear();
                             last_write_failed = false;
                         }
                         Err(e) => {
@@ -1111,7 +1813,8 @@ impl AppContext {
                                 + hash_updates.len()
                                 + feature_updates.len()
                                 + coeff_updates.len()
-                                + pixel_updates.len();
+                                + pixel_updates.len()
+                                + oriented_pixel_updates.len();
                             if buffered > hard_cap {
                                 eprintln!(
                                     "[ERROR-DB] Dropping {} buffered cache updates after repeated write failures",
@@ -1122,6 +1825,7 @@ impl AppContext {
                                 feature_updates.clear();
                                 coeff_updates.clear();
                                 pixel_updates.clear();
+                                oriented_pixel_updates.clear();
                             }
                         }
                     }
@@ -1139,11 +1843,13 @@ impl AppContext {
         feature_db: Database,
         coeff_db: Database,
         pixel_db: Database,
+        oriented_pixel_db: Database,
         meta_updates: &Vec<([u8; 32], [u8; 32])>,
         hash_updates: &Vec<([u8; 32], HashValue)>,
         feature_updates: &Vec<([u8; 32], ImageFeatures)>,
         coeff_updates: &Vec<([u8; 32], CachedCoefficients)>,
         pixel_updates: &Vec<([u8; 32], [u8; 32])>,
+        oriented_pixel_updates: &Vec<([u8; 32], [u8; 32])>,
     ) -> Result<(), lmdb::Error> {
         let mut txn = env.begin_rw_txn()?;
 
@@ -1167,6 +1873,10 @@ impl AppContext {
                     let encrypted = Self::encrypt_value(cipher, key, pdqhash);
                     txn.put(hash_db, key, &encrypted, WriteFlags::empty())?;
                 }
+                HashValue::WHash(whash) => {
+                    let encrypted = Self::encrypt_value(cipher, key, &whash.to_le_bytes());
+                    txn.put(hash_db, key, &encrypted, WriteFlags::empty())?;
+                }
             }
         }
 
@@ -1190,6 +1900,12 @@ impl AppContext {
             txn.put(pixel_db, key, &encrypted, WriteFlags::empty())?;
         }
 
+        // 6. Oriented Pixel Updates
+        for (key, val) in oriented_pixel_updates {
+            let encrypted = Self::encrypt_value(cipher, key, val);
+            txn.put(oriented_pixel_db, key, &encrypted, WriteFlags::empty())?;
+        }
+
         txn.commit()
     }
 
@@ -1225,6 +1941,156 @@ impl AppContext {
         Ok(count)
     }
 
+    // --- Scan Baselines ---
+
+    /// Key for a set of scan roots; order-independent so `a b` and `b a` share a baseline.
+    fn baseline_key(&self, roots: &[String]) -> [u8; 32] {
+        let mut sorted: Vec<&String> = roots.iter().collect();
+        sorted.sort();
+        let mut h = blake3::Hasher::new_keyed(&self.meta_key);
+        h.update(b"scan_baseline");
+        for root in sorted {
+            h.update(&(root.len() as u64).to_le_bytes());
+            h.update(root.as_bytes());
+        }
+        *h.finalize().as_bytes()
+    }
+
+    /// Get the stored baseline for these scan roots, if any.
+    pub fn get_scan_baseline(&self, roots: &[String]) -> Result<Option<ScanBaseline>, lmdb::Error> {
+        let key = self.baseline_key(roots);
+        let txn = self.env.begin_ro_txn()?;
+        match txn.get(self.baseline_db, &key) {
+            Ok(encrypted) => match self.decrypt_value(&key, encrypted) {
+                Some(decrypted) => Ok(ScanBaseline::from_bytes(&decrypted).ok()),
+                None => Err(lmdb::Error::Corrupted),
+            },
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replace the baseline for these scan roots.
+    pub fn save_scan_baseline(
+        &self,
+        roots: &[String],
+        baseline: &ScanBaseline,
+    ) -> Result<(), lmdb::Error> {
+        let key = self.baseline_key(roots);
+        let bytes = baseline.to_bytes().expect("ScanBaseline serialization failed");
+        let encrypted = Self::encrypt_value(&self.cipher, &key, &bytes);
+        let mut txn = self.env.begin_rw_txn()?;
+        txn.put(self.baseline_db, &key, &encrypted, WriteFlags::empty())?;
+        txn.commit()
+    }
+
+    // --- Prefix Hash Database (--prefix-hash) ---
+
+    /// Cached keyed blake3 of the first `len` bytes of the file behind `meta_key`.
+    /// Stored as [len (4) || hash (32) || timestamp (8)]; a different `len` is a miss.
+    pub fn get_prefix_hash(&self, meta_key: &[u8; 32], len: u32) -> Option<[u8; 32]> {
+        let txn = self.env.begin_ro_txn().ok()?;
+        let encrypted = txn.get(self.prefix_db, meta_key).ok()?;
+        let decrypted = self.decrypt_value(meta_key, encrypted)?;
+        if decrypted.len() != 44 || decrypted[0..4] != len.to_le_bytes() {
+            return None;
+        }
+        decrypted[4..36].try_into().ok()
+    }
+
+    /// Store prefix hashes as `(meta_key, prefix length, hash)` in one transaction.
+    pub fn put_prefix_hashes(
+        &self,
+        entries: &[([u8; 32], u32, [u8; 32])],
+    ) -> Result<(), lmdb::Error> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut txn = self.env.begin_rw_txn()?;
+        for (meta_key, len, hash) in entries {
+            let mut value = Vec::with_capacity(44);
+            value.extend_from_slice(&len.to_le_bytes());
+            value.extend_from_slice(hash);
+            value.extend_from_slice(&now.to_le_bytes());
+            let encrypted = Self::encrypt_value(&self.cipher, meta_key, &value);
+            txn.put(self.prefix_db, meta_key, &encrypted, WriteFlags::empty())?;
+        }
+        txn.commit()
+    }
+
+    // --- Protected Files Database ---
+
+    /// Check if a file's content is protected against deletion.
+    pub fn is_protected(&self, content_hash: &[u8; 32]) -> bool {
+        let Ok(txn) = self.env.begin_ro_txn() else { return false };
+        match txn.get(self.protected_db, content_hash) {
+            Ok(encrypted) => self.decrypt_value(content_hash, encrypted).is_some(),
+            _ => false,
+        }
+    }
+
+    /// Protect or unprotect a content hash. Protection follows the content,
+    /// so every copy (hardlinked or identical) is covered.
+    pub fn set_protected(
+        &self,
+        content_hash: &[u8; 32],
+        protected: bool,
+    ) -> Result<(), lmdb::Error> {
+        let mut txn = self.env.begin_rw_txn()?;
+        if protected {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            let encrypted =
+                Self::encrypt_value(&self.cipher, content_hash, &timestamp.to_le_bytes());
+            txn.put(self.protected_db, content_hash, &encrypted, WriteFlags::empty())?;
+        } else {
+            match txn.del(self.protected_db, content_hash, None) {
+                Ok(()) | Err(lmdb::Error::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        txn.commit()
+    }
+
+    // --- Ratings Database ---
+
+    /// Rating of a file's content: 1-5 stars or `REJECTED`; None when unrated.
+    pub fn get_rating(&self, content_hash: &[u8; 32]) -> Option<u8> {
+        let txn = self.env.begin_ro_txn().ok()?;
+        let encrypted = txn.get(self.ratings_db, content_hash).ok()?;
+        let decrypted = self.decrypt_value(content_hash, encrypted)?;
+        decrypted.first().copied().filter(|&r| r <= 5)
+    }
+
+    /// Rate a content hash (None clears the rating). Like protection, the rating
+    /// follows the content, so identical copies share it.
+    pub fn set_rating(
+        &self,
+        content_hash: &[u8; 32],
+        rating: Option<u8>,
+    ) -> Result<(), lmdb::Error> {
+        let mut txn = self.env.begin_rw_txn()?;
+        if let Some(rating) = rating {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            let mut value = vec![rating];
+            value.extend_from_slice(&timestamp.to_le_bytes());
+            let encrypted = Self::encrypt_value(&self.cipher, content_hash, &value);
+            txn.put(self.ratings_db, content_hash, &encrypted, WriteFlags::empty())?;
+        } else {
+            match txn.del(self.ratings_db, content_hash, None) {
+                Ok(()) | Err(lmdb::Error::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        txn.commit()
+    }
+
     // --- Ignored Files Database ---
 
     /// Check if a file has been explicitly ignored (ignored flag == true).
@@ -1348,7 +2214,7 @@ impl AppContext {
         }
 
         txn.commit()?;
-        eprintln!(
+        debug_log!(
             "[DEBUG-IGNORE] Registered {} files across {} groups",
             total_registered,
             groups.len()
@@ -1463,7 +2329,7 @@ impl AppContext {
                         && let Ok(entry) = IgnoredEntry::from_bytes(&decrypted)
                         && entry.group_uuid == *uuid
                     {
-                        eprintln!(
+                        debug_log!(
                             "[DEBUG-UNIGNORE] Removing blake3={} ignored={}",
                             hex::encode(content_hash),
                             entry.ignored,
@@ -1611,7 +2477,62 @@ impl AppContext {
 
             Self::write_config(&config_path, &cfg)?;
         } else {
-            eprintln!("[DEBUG-DB] Config file does not exist at {:?}", config_path);
+            debug_log!("[DEBUG-DB] Config file does not exist at {:?}", config_path);
+        }
+        Ok(())
+    }
+
+    /// Re-read the config file for a live reload. `locations` is replaced in place;
+    /// the `[gui]` section and map providers are returned for the GUI to apply.
+    /// Keys, `[grouping]` and the database settings stay as loaded at startup.
+    pub fn reload_config(
+        &self,
+    ) -> Result<(GuiConfig, HashMap<String, String>), Box<dyn std::error::Error>> {
+        let config_dir = dirs::config_dir().ok_or("No config dir found")?;
+        let config_path = config_dir.join(CONFIG_FILE_NAME);
+        let content = fs::read_to_string(&config_path)?;
+        let mut cfg: Config = toml::from_str(&content)?;
+        cfg.gui.dominant_colors = Some(cfg.gui.dominant_colors.unwrap_or(5).clamp(1, 25));
+
+        if let Ok(mut locations) = self.locations.write() {
+            *locations =
+                cfg.locations.into_iter().map(|(name, option)| (name, option.into())).collect();
+        }
+        Ok((cfg.gui, cfg.map_providers))
+    }
+
+    /// Add a named location (`[locations]` in the config) and persist it.
+    /// Rejects empty or duplicate names and out-of-range coordinates.
+    /// `point` is x = longitude, y = latitude, like the rest of `locations`.
+    pub fn add_location(
+        &self,
+        name: &str,
+        point: Point<f64>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Location name is empty".into());
+        }
+        if self.locations.read().map(|l| l.contains_key(name)).unwrap_or(false) {
+            return Err(format!("Location '{}' already exists", name).into());
+        }
+        let (lat, lon) = (point.y(), point.x());
+        if !lat.is_finite() || !lon.is_finite() || lat.abs() > 90.0 || lon.abs() > 180.0 {
+            return Err(format!("Invalid coordinates: {}, {}", lat, lon).into());
+        }
+
+        let config_dir = dirs::config_dir().ok_or("No config dir found")?;
+        let config_path = config_dir.join(CONFIG_FILE_NAME);
+        let content = fs::read_to_string(&config_path)?;
+        let mut cfg: Config = toml::from_str(&content)?;
+        if cfg.locations.contains_key(name) {
+            return Err(format!("Location '{}' already exists in config", name).into());
+        }
+        cfg.locations.insert(name.to_string(), LocationOption::Named { lat, lon });
+        Self::write_config(&config_path, &cfg)?;
+
+        if let Ok(mut locations) = self.locations.write() {
+            locations.insert(name.to_string(), point);
         }
         Ok(())
     }
@@ -2,9 +2,10 @@
 // Integrates functionality from helper_exif.rs.
 // Filters out thumbnails and large binary blobs.
 use crate::exif_types::{
-    ExifValue, MAX_TAG_SIZE, TAG_DERIVED_COUNTRY, TAG_DERIVED_SUBDIVISION,
-    TAG_DERIVED_SUN_ALTITUDE, TAG_DERIVED_SUN_AZIMUTH, TAG_DERIVED_TIMESTAMP, TAG_DERIVED_TIMEZONE,
-    TAG_GPS_ALTITUDE, TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE, TAG_ORIENTATION, is_excluded_tag,
+    ExifValue, MAX_TAG_SIZE, TAG_DERIVED_COUNTRY, TAG_DERIVED_LENS, TAG_DERIVED_SUBDIVISION,
+    TAG_DERIVED_SUBSEC_NANOS, TAG_DERIVED_SUN_ALTITUDE, TAG_DERIVED_SUN_AZIMUTH,
+    TAG_DERIVED_TIMESTAMP, TAG_DERIVED_TIMEZONE, TAG_DERIVED_TZ_OFFSET, TAG_GPS_ALTITUDE,
+    TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE, TAG_LENS_MODEL, TAG_ORIENTATION, is_excluded_tag,
 };
 use crate::image_features::ImageFeatures;
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
@@ -168,6 +169,143 @@ fn parse_exif_datetime_tag(exif: &exif::Exif, tag: Tag) -> Option<i64> {
     None
 }
 
+/// Sub-second nanoseconds and UTC offset (seconds east) belonging to the same
+/// DateTime tag that `get_exif_timestamp` picks.
+pub fn get_exif_time_refinement(exif: &exif::Exif) -> (Option<u32>, Option<i32>) {
+    let (subsec_tag, offset_tag) = if parse_exif_datetime_tag(exif, Tag::DateTimeOriginal).is_some()
+    {
+        (Tag::SubSecTimeOriginal, Tag::OffsetTimeOriginal)
+    } else {
+        (Tag::SubSecTimeDigitized, Tag::OffsetTimeDigitized)
+    };
+    let subsec = ascii_field(exif, subsec_tag).and_then(parse_subsec_nanos);
+    let offset = ascii_field(exif, offset_tag).and_then(parse_tz_offset);
+    (subsec, offset)
+}
+
+fn ascii_field(exif: &exif::Exif, tag: Tag) -> Option<&[u8]> {
+    match exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(ref vec) if !vec.is_empty() => Some(&vec[0]),
+        _ => None,
+    }
+}
+
+/// Parse SubSecTime* ("123" means .123 s) into nanoseconds.
+/// Cameras pad with spaces; digits beyond nanosecond precision are dropped.
+fn parse_subsec_nanos(raw: &[u8]) -> Option<u32> {
+    let digits: Vec<u8> = raw.iter().copied().take_while(|b| *b != 0).collect();
+    let digits = std::str::from_utf8(&digits).ok()?.trim();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut nanos = 0u32;
+    for i in 0..9 {
+        let d = digits.as_bytes().get(i).map_or(0, |b| (b - b'0') as u32);
+        nanos = nanos * 10 + d;
+    }
+    Some(nanos)
+}
+
+/// Parse OffsetTime* ("+HH:MM" / "-HH:MM") into seconds east of UTC.
+fn parse_tz_offset(raw: &[u8]) -> Option<i32> {
+    let s = std::str::from_utf8(raw).ok()?.trim_end_matches('\0').trim();
+    let (sign, rest) = match s.as_bytes().first()? {
+        b'+' => (1, &s[1..]),
+        b'-' => (-1, &s[1..]),
+        _ => return None,
+    };
+    let (h, m) = rest.split_once(':')?;
+    let h: i32 = h.parse().ok()?;
+    let m: i32 = m.parse().ok()?;
+    if h > 14 || m > 59 {
+        return None;
+    }
+    Some(sign * (h * 3600 + m * 60))
+}
+
+/// Timezone EXIF capture times are shown in (`display_timezone` in `[gui]`).
+/// EXIF DateTime values are naive wall-clock times; files without an
+/// OffsetTime* tag are taken to be in the local timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayTz {
+    /// Wall clock at capture, with the recorded offset appended when known
+    #[default]
+    Photo,
+    Local,
+    Utc,
+    /// Fixed offset, seconds east of UTC
+    Fixed(i32),
+}
+
+impl DisplayTz {
+    /// Parse "photo", "local", "UTC" or a "+HH:MM" / "-HH:MM" offset.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "photo" => Some(Self::Photo),
+            "local" => Some(Self::Local),
+            "utc" | "z" => Some(Self::Utc),
+            other => parse_tz_offset(other.as_bytes()).map(Self::Fixed),
+        }
+    }
+
+    /// Format a naive EXIF time (`naive_ts`, wall clock read as UTC seconds)
+    /// with its optional OffsetTime* value.
+    pub fn format(self, naive_ts: i64, offset: Option<i32>) -> String {
+        use jiff::tz::{Offset, TimeZone};
+
+        let Ok(wall) = jiff::Timestamp::from_second(naive_ts) else {
+            return naive_ts.to_string();
+        };
+        let wall = wall.to_zoned(TimeZone::UTC).datetime();
+        let plain = || wall.strftime("%Y-%m-%d %H:%M:%S").to_string();
+
+        let zone = match (self, offset) {
+            (Self::Photo, None) | (Self::Local, None) => return plain(),
+            (Self::Photo, Some(off)) => match Offset::from_seconds(off) {
+                Ok(o) => TimeZone::fixed(o),
+                Err(_) => return plain(),
+            },
+            (Self::Local, Some(_)) => TimeZone::system(),
+            (Self::Utc, _) => TimeZone::UTC,
+            (Self::Fixed(off), _) => match Offset::from_seconds(off) {
+                Ok(o) => TimeZone::fixed(o),
+                Err(_) => return plain(),
+            },
+        };
+        // The actual instant: recorded offset, or the local zone when there is none
+        let instant = match offset {
+            Some(off) => jiff::Timestamp::from_second(naive_ts - off as i64).ok(),
+            None => wall.to_zoned(TimeZone::system()).ok().map(|z| z.timestamp()),
+        };
+        let Some(instant) = instant else {
+            return plain();
+        };
+        let zoned = instant.to_zoned(zone);
+        match self {
+            Self::Local => zoned.strftime("%Y-%m-%d %H:%M:%S").to_string(),
+            Self::Utc => zoned.strftime("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            _ => zoned.strftime("%Y-%m-%d %H:%M:%S %:z").to_string(),
+        }
+    }
+}
+
+/// Reformat the DateTime tags of `exif` named in `tags` (overlay name, value)
+/// for `tz`, using each tag's own OffsetTime* companion.
+pub fn apply_display_tz(exif: &exif::Exif, tags: &mut [(String, String)], tz: DisplayTz) {
+    for (name, value) in tags.iter_mut() {
+        let (tag, offset_tag) = match name.to_ascii_lowercase().as_str() {
+            "datetimeoriginal" => (Tag::DateTimeOriginal, Tag::OffsetTimeOriginal),
+            "datetimedigitized" => (Tag::DateTimeDigitized, Tag::OffsetTimeDigitized),
+            "datetime" => (Tag::DateTime, Tag::OffsetTime),
+            _ => continue,
+        };
+        if let Some(ts) = parse_exif_datetime_tag(exif, tag) {
+            let offset = ascii_field(exif, offset_tag).and_then(parse_tz_offset);
+            *value = tz.format(ts, offset);
+        }
+    }
+}
+
 /// Gets altitude from EXIF tags
 pub fn get_altitude(exif: &exif::Exif) -> Option<f64> {
     let val_field = exif.get_field(Tag::GPSAltitude, In::PRIMARY)?;
@@ -371,6 +509,13 @@ pub fn build_image_features(
     // Extract all standard EXIF tags
     features.tags = extract_all_exif(exif_data);
 
+    // Normalized lens name next to the raw LensModel
+    if let Some(ExifValue::String(lens)) = features.tags.get(&TAG_LENS_MODEL)
+        && let Some(normalized) = normalize_lens_model(lens)
+    {
+        features.insert_tag(TAG_DERIVED_LENS, ExifValue::String(normalized));
+    }
+
     // Store GPS as decimal degrees for easier querying
     if let Some((lat, lon)) = extract_gps_lat_lon(exif_data) {
         features.insert_tag(TAG_GPS_LATITUDE, ExifValue::Float(lat));
@@ -390,6 +535,13 @@ pub fn build_image_features(
     // Store EXIF timestamp as derived value
     if let Some(ts) = get_exif_timestamp(exif_data) {
         features.insert_tag(TAG_DERIVED_TIMESTAMP, ExifValue::Long64(ts));
+        let (subsec, offset) = get_exif_time_refinement(exif_data);
+        if let Some(nanos) = subsec {
+            features.insert_tag(TAG_DERIVED_SUBSEC_NANOS, ExifValue::Long(nanos));
+        }
+        if let Some(off) = offset {
+            features.insert_tag(TAG_DERIVED_TZ_OFFSET, ExifValue::Signed(off));
+        }
     }
 
     // Ensure orientation is stored
@@ -473,6 +625,122 @@ pub fn derive_subdivision(lat: f64, lon: f64) -> Option<String> {
     None
 }
 
+/// Brand implied by a mount prefix, for lens names that leave the brand out.
+/// The canonical brand spelling is also used when a name does include it.
+const LENS_MOUNT_BRANDS: &[(&str, &str)] = &[
+    ("Canon", "Canon"),
+    ("EF-S", "Canon"),
+    ("EF-M", "Canon"),
+    ("EF", "Canon"),
+    ("RF-S", "Canon"),
+    ("RF", "Canon"),
+    ("TS-E", "Canon"),
+    ("MP-E", "Canon"),
+    ("Nikon", "Nikon"),
+    ("NIKKOR", "Nikon"),
+    ("Sony", "Sony"),
+    ("FE", "Sony"),
+    ("Fujifilm", "Fujifilm"),
+    ("XF", "Fujifilm"),
+    ("XC", "Fujifilm"),
+    ("GF", "Fujifilm"),
+    ("Olympus", "Olympus"),
+    ("M.Zuiko", "Olympus"),
+];
+
+/// Focus motor designations. Bodies disagree on whether they record them, and
+/// they never tell two otherwise identical lenses apart.
+const LENS_MOTOR_TOKENS: &[&str] = &["USM", "STM", "HSM", "SSM", "SWM", "LM", "SDM", "PZ"];
+
+/// Normalize a LensModel string so the spellings different bodies and converters
+/// write for one lens compare equal, e.g. "EF24-70mm f/2.8L USM" and
+/// "Canon EF 24-70mm F2.8L" both become "Canon EF 24-70mm f/2.8L".
+/// Version markers (II, III) are kept: those are different optics.
+pub fn normalize_lens_model(raw: &str) -> Option<String> {
+    let trimmed = raw.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    // Bodies without lens communication write placeholders like "----" or "0"
+    if trimmed.is_empty() || trimmed.chars().all(|c| c == '-' || c == '0') {
+        return None;
+    }
+
+    let mut tokens: Vec<String> = Vec::new();
+    let mut words = trimmed.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        if LENS_MOTOR_TOKENS.iter().any(|m| m.eq_ignore_ascii_case(word)) {
+            continue;
+        }
+        // "EF24-70mm" -> "EF" "24-70mm"
+        let word = match word.find(|c: char| c.is_ascii_digit()) {
+            Some(i) if i > 0 && lens_mount_brand(&word[..i]).is_some() => {
+                tokens.push(word[..i].to_string());
+                &word[i..]
+            }
+            _ => word,
+        };
+        // "18-55mmF2.8-4" -> "18-55mm" "f/2.8-4"
+        if let Some(i) = word.to_ascii_lowercase().find("mm").map(|i| i + 2)
+            && i < word.len()
+            && let (Some(focal), Some(aperture)) =
+                (normalize_focal_token(&word[..i]), normalize_aperture_token(&word[i..]))
+        {
+            tokens.push(focal);
+            tokens.push(aperture);
+        } else if let Some(focal) = normalize_focal_token(word) {
+            tokens.push(focal);
+        } else if let Some(focal) = words
+            .next_if(|w| w.eq_ignore_ascii_case("mm"))
+            .and_then(|_| normalize_focal_token(&format!("{}mm", word)))
+        {
+            tokens.push(focal);
+        } else if let Some(aperture) = normalize_aperture_token(word) {
+            tokens.push(aperture);
+        } else {
+            tokens.push(word.to_string());
+        }
+    }
+
+    if let Some(brand) = tokens.first().and_then(|t| lens_mount_brand(t)) {
+        if tokens[0].eq_ignore_ascii_case(brand) {
+            tokens[0] = brand.to_string();
+        } else {
+            tokens.insert(0, brand.to_string());
+        }
+    }
+    Some(tokens.join(" "))
+}
+
+fn lens_mount_brand(token: &str) -> Option<&'static str> {
+    LENS_MOUNT_BRANDS.iter().find(|(m, _)| m.eq_ignore_ascii_case(token)).map(|(_, b)| *b)
+}
+
+/// Parse "24", "24.0" or "24-70" into numbers without trailing zeros ("24", "24-70").
+fn normalize_number_range(s: &str) -> Option<String> {
+    let parts: Vec<f64> = s.split('-').map(|p| p.parse::<f64>().ok()).collect::<Option<_>>()?;
+    if parts.is_empty() || parts.len() > 2 {
+        return None;
+    }
+    Some(parts.iter().map(|p| p.to_string()).collect::<Vec<_>>().join("-"))
+}
+
+/// "24.0-70.0mm" -> "24-70mm"
+fn normalize_focal_token(token: &str) -> Option<String> {
+    let lower = token.to_ascii_lowercase();
+    let numbers = lower.strip_suffix("mm")?;
+    Some(format!("{}mm", normalize_number_range(numbers)?))
+}
+
+/// "F2.8", "1:2.8", "f/2.8L" -> "f/2.8", "f/2.8L"; anything else -> None
+fn normalize_aperture_token(token: &str) -> Option<String> {
+    let rest = ["f/", "F/", "1:", "f", "F"].iter().find_map(|p| token.strip_prefix(p))?;
+    let end =
+        rest.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-')).unwrap_or(rest.len());
+    let (numbers, suffix) = rest.split_at(end);
+    if !numbers.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("f/{}{}", normalize_number_range(numbers)?, suffix))
+}
+
 /// Derive sun position from GPS coordinates and EXIF timestamp
 fn derive_sun_position(
     lat: f64,
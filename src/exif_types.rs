@@ -70,6 +70,24 @@ pub const TAG_DERIVED_SUN_ALTITUDE: u16 = 0xF004;
 pub const TAG_DERIVED_TIMEZONE: u16 = 0xF005;
 /// Derived: EXIF timestamp as Unix epoch seconds
 pub const TAG_DERIVED_TIMESTAMP: u16 = 0xF006;
+/// Derived: Sub-second part of the EXIF timestamp in nanoseconds
+pub const TAG_DERIVED_SUBSEC_NANOS: u16 = 0xF007;
+/// Derived: EXIF OffsetTimeOriginal as seconds east of UTC
+pub const TAG_DERIVED_TZ_OFFSET: u16 = 0xF008;
+/// Derived: LensModel normalized so different spellings of one lens compare equal
+pub const TAG_DERIVED_LENS: u16 = 0xF009;
+/// Derived: JPEG XL coded losslessly (1) or lossy (0)
+pub const TAG_DERIVED_LOSSLESS: u16 = 0xF00A;
+/// Derived: JPEG XL displayed frame count (more than 1 = animated)
+pub const TAG_DERIVED_FRAMES: u16 = 0xF00B;
+/// Derived: HEIC/HEIF carries a depth map next to its primary image (1) or not (0)
+pub const TAG_DERIVED_DEPTH_MAP: u16 = 0xF00C;
+/// Derived: first 8 bytes of the blake3 of the embedded ICC profile (empty = no profile)
+pub const TAG_DERIVED_ICC_HASH: u16 = 0xF00D;
+/// Derived: description of the embedded ICC profile, when it has one
+pub const TAG_DERIVED_ICC_DESCRIPTION: u16 = 0xF00E;
+/// Derived: why the file was not decoded for hashing (over `max_decode_pixels`)
+pub const TAG_DERIVED_DECODE_SKIPPED: u16 = 0xF00F;
 
 // =============================================================================
 // Common EXIF Tag IDs (for reference and name mapping)
@@ -194,6 +212,15 @@ pub fn tag_id_to_name(tag_id: u16) -> Option<&'static str> {
         TAG_DERIVED_SUN_ALTITUDE => "SunAltitude",
         TAG_DERIVED_TIMEZONE => "Timezone",
         TAG_DERIVED_TIMESTAMP => "Timestamp",
+        TAG_DERIVED_SUBSEC_NANOS => "SubSecNanos",
+        TAG_DERIVED_TZ_OFFSET => "TzOffset",
+        TAG_DERIVED_LENS => "Lens",
+        TAG_DERIVED_LOSSLESS => "Lossless",
+        TAG_DERIVED_FRAMES => "Frames",
+        TAG_DERIVED_DEPTH_MAP => "DepthMap",
+        TAG_DERIVED_ICC_HASH => "IccHash",
+        TAG_DERIVED_ICC_DESCRIPTION => "IccProfile",
+        TAG_DERIVED_DECODE_SKIPPED => "DecodeSkipped",
         _ => return None,
     })
 }
@@ -232,7 +259,7 @@ pub fn name_to_tag_id(name: &str) -> Option<u16> {
         "sharpness" => TAG_SHARPNESS,
         "subjectdistance" => TAG_SUBJECT_DISTANCE,
         "lensmake" => TAG_LENS_MAKE,
-        "lensmodel" | "lens" => TAG_LENS_MODEL,
+        "lensmodel" => TAG_LENS_MODEL,
         "gpslatituderef" => TAG_GPS_LATITUDE_REF,
         "gpslatitude" => TAG_GPS_LATITUDE,
         "gpslongituderef" => TAG_GPS_LONGITUDE_REF,
@@ -248,6 +275,15 @@ pub fn name_to_tag_id(name: &str) -> Option<u16> {
         "sunaltitude" | "derivedsunaltitude" => TAG_DERIVED_SUN_ALTITUDE,
         "timezone" | "tz" | "derivedtimezone" => TAG_DERIVED_TIMEZONE,
         "timestamp" | "derivedtimestamp" => TAG_DERIVED_TIMESTAMP,
+        "subsecnanos" | "derivedsubsecnanos" => TAG_DERIVED_SUBSEC_NANOS,
+        "tzoffset" | "derivedtzoffset" => TAG_DERIVED_TZ_OFFSET,
+        "lens" | "derivedlens" => TAG_DERIVED_LENS,
+        "lossless" | "derivedlossless" => TAG_DERIVED_LOSSLESS,
+        "frames" | "derivedframes" => TAG_DERIVED_FRAMES,
+        "depthmap" | "deriveddepthmap" => TAG_DERIVED_DEPTH_MAP,
+        "icchash" | "derivedicchash" => TAG_DERIVED_ICC_HASH,
+        "iccprofile" | "icc" | "derivediccprofile" => TAG_DERIVED_ICC_DESCRIPTION,
+        "decodeskipped" | "deriveddecodeskipped" => TAG_DERIVED_DECODE_SKIPPED,
         _ => return None,
     })
 }
@@ -261,12 +297,15 @@ pub fn get_searchable_tags() -> Vec<(u16, &'static str, &'static str, bool)> {
         (TAG_MODEL, "Model", "Camera model", false),
         (TAG_LENS_MAKE, "LensMake", "Lens manufacturer", false),
         (TAG_LENS_MODEL, "LensModel", "Lens model name", false),
+        (TAG_DERIVED_LENS, "Lens", "Lens model, normalized", false),
         (TAG_SOFTWARE, "Software", "Software used", false),
         (TAG_ARTIST, "Artist", "Artist/creator", false),
         (TAG_COPYRIGHT, "Copyright", "Copyright information", false),
         (TAG_DERIVED_COUNTRY, "Country", "Country from GPS", false),
         (TAG_DERIVED_SUBDIVISION, "Subdivision", "State/province from GPS", false),
         (TAG_DERIVED_TIMEZONE, "Timezone", "Timezone at GPS location", false),
+        (TAG_DERIVED_ICC_DESCRIPTION, "IccProfile", "Embedded color profile", false),
+        (TAG_DERIVED_DECODE_SKIPPED, "DecodeSkipped", "Why hashing skipped the file", false),
         // Numeric tags (range search)
         (TAG_ISO, "ISO", "ISO sensitivity", true),
         (TAG_FNUMBER, "FNumber", "Aperture f-number", true),
@@ -279,5 +318,9 @@ pub fn get_searchable_tags() -> Vec<(u16, &'static str, &'static str, bool)> {
         (TAG_DERIVED_SUN_AZIMUTH, "SunAzimuth", "Sun azimuth angle (degrees)", true),
         (TAG_DERIVED_SUN_ALTITUDE, "SunAltitude", "Sun altitude angle (degrees)", true),
         (TAG_DERIVED_TIMESTAMP, "Timestamp", "EXIF timestamp (Unix epoch)", true),
+        (TAG_DERIVED_TZ_OFFSET, "TzOffset", "EXIF time offset (seconds east of UTC)", true),
+        (TAG_DERIVED_LOSSLESS, "Lossless", "JPEG XL coded losslessly (1/0)", true),
+        (TAG_DERIVED_FRAMES, "Frames", "JPEG XL frame count (>1 = animated)", true),
+        (TAG_DERIVED_DEPTH_MAP, "DepthMap", "HEIC/HEIF has a depth map (1/0)", true),
     ]
 }
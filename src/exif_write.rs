@@ -0,0 +1,747 @@
+// In-place rewriting of EXIF date tags for JPEG and TIFF files.
+// DateTime values are fixed-size ASCII ("YYYY:MM:DD HH:MM:SS\0"), so shifting
+// them only overwrites 19 bytes at their existing offsets; the file layout,
+// segment sizes and every other tag stay untouched. GPS stripping works the same
+// way: the GPS IFD is zeroed and its pointer dropped from IFD0, nothing moves, and
+// the `exif:GPS*` properties of an embedded XMP packet are blanked with spaces.
+// Writing GPS and adding a missing DateTimeOriginal are the edits that add
+// bytes; see `set_gps_ifd` and `set_date_original`.
+
+use chrono::{Duration, NaiveDateTime};
+use std::fs;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+const TAG_DATETIME: u16 = 0x0132;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_GPS_IFD: u16 = 0x8825;
+const TAG_DATETIME_ORIGINAL: u16 = 0x9003;
+const TAG_DATETIME_DIGITIZED: u16 = 0x9004;
+const EXIF_DATE_FORMAT: &str = "%Y:%m:%d %H:%M:%S";
+const EXIF_DATE_LEN: usize = 19;
+
+/// One date tag to be rewritten.
+#[derive(Debug, Clone)]
+pub struct DateEdit {
+    pub tag: u16,
+    /// Absolute byte offset of the ASCII value in the file
+    pub offset: u64,
+    pub old: NaiveDateTime,
+    pub new: NaiveDateTime,
+}
+
+/// Formats whose EXIF block we know how to patch safely.
+/// RAW containers are excluded even when TIFF-based: vendors keep private
+/// offsets and checksums that we would not update.
+pub fn supports_exif_write(path: &Path) -> bool {
+    if crate::scanner::is_raw_ext(path) {
+        return false;
+    }
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| matches!(e.to_lowercase().as_str(), "jpg" | "jpeg" | "tif" | "tiff"))
+        .unwrap_or(false)
+}
+
+/// Parse a shift like "+2h -3m", "1d", "-90s" or "+1h30m" into seconds.
+pub fn parse_time_shift(input: &str) -> Result<i64, String> {
+    let mut total: i64 = 0;
+    let mut sign: i64 = 1;
+    let mut num = String::new();
+    let mut any = false;
+    for c in input.chars() {
+        match c {
+            '+' | '-' if num.is_empty() => sign = if c == '-' { -1 } else { 1 },
+            '0'..='9' => num.push(c),
+            'd' | 'h' | 'm' | 's' => {
+                let n: i64 = num.parse().map_err(|_| format!("missing number before '{}'", c))?;
+                let unit = match c {
+                    'd' => 86400,
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                total = n
+                    .checked_mul(sign * unit)
+                    .and_then(|v| total.checked_add(v))
+                    .ok_or("shift out of range")?;
+                num.clear();
+                any = true;
+            }
+            c if c.is_whitespace() => {
+                if !num.is_empty() {
+                    return Err(format!("missing unit after {}", num));
+                }
+                sign = 1;
+            }
+            _ => return Err(format!("unexpected character '{}'", c)),
+        }
+    }
+    if !num.is_empty() {
+        return Err(format!("missing unit after {}", num));
+    }
+    if !any {
+        return Err("empty shift (use e.g. +2h -3m)".to_string());
+    }
+    Ok(total)
+}
+
+/// Find the date tags of `path` and compute their shifted values without writing.
+pub fn plan_date_shift(path: &Path, delta_secs: i64) -> Result<Vec<DateEdit>, String> {
+    if !supports_exif_write(path) {
+        return Err("unsupported format".to_string());
+    }
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let tiff = find_tiff_start(&data).ok_or("no EXIF block")?;
+    let mut edits = Vec::new();
+    let delta = Duration::try_seconds(delta_secs).ok_or("shift out of range")?;
+    for (tag, offset) in find_date_tags(&data, tiff)? {
+        let raw = &data[offset..offset + EXIF_DATE_LEN];
+        let Ok(old) =
+            NaiveDateTime::parse_from_str(&String::from_utf8_lossy(raw), EXIF_DATE_FORMAT)
+        else {
+            continue; // Blank ("    :  :     :  :  ") or garbage; leave it alone
+        };
+        let new = old.checked_add_signed(delta).ok_or("shift out of range")?;
+        edits.push(DateEdit { tag, offset: offset as u64, old, new });
+    }
+    if edits.is_empty() {
+        return Err("no DateTime tags".to_string());
+    }
+    Ok(edits)
+}
+
+/// Write planned edits back to the file, in place.
+pub fn apply_date_edits(path: &Path, edits: &[DateEdit]) -> Result<(), String> {
+    // Validate everything first so a bad value can't leave the file half-edited
+    let texts: Vec<String> =
+        edits.iter().map(|e| e.new.format(EXIF_DATE_FORMAT).to_string()).collect();
+    if let Some(bad) = texts.iter().find(|t| t.len() != EXIF_DATE_LEN) {
+        return Err(format!("shifted date out of EXIF range: {}", bad));
+    }
+    let mut file = fs::OpenOptions::new().write(true).open(path).map_err(|e| e.to_string())?;
+    for (edit, text) in edits.iter().zip(&texts) {
+        file.seek(SeekFrom::Start(edit.offset)).map_err(|e| e.to_string())?;
+        file.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    file.sync_all().map_err(|e| e.to_string())
+}
+
+/// New `exif_timestamp` after the edits, using the same tag preference as
+/// `get_exif_timestamp` (DateTimeOriginal, then DateTimeDigitized).
+pub fn shifted_timestamp(edits: &[DateEdit]) -> Option<i64> {
+    [TAG_DATETIME_ORIGINAL, TAG_DATETIME_DIGITIZED]
+        .iter()
+        .find_map(|t| edits.iter().find(|e| e.tag == *t))
+        .map(|e| e.new.and_utc().timestamp())
+}
+
+pub fn date_tag_name(tag: u16) -> &'static str {
+    match tag {
+        TAG_DATETIME_ORIGINAL => "DateTimeOriginal",
+        TAG_DATETIME_DIGITIZED => "DateTimeDigitized",
+        _ => "DateTime",
+    }
+}
+
+/// Remove the GPS IFD from a JPEG or TIFF file image in memory. The GPS entries and
+/// their out-of-line values are zeroed and the GPS pointer is removed from IFD0.
+/// Returns false when there was no GPS IFD.
+pub fn strip_gps_ifd(data: &mut [u8]) -> Result<bool, String> {
+    let tiff = find_tiff_start(data).ok_or("no EXIF block")?;
+    let le = match data.get(tiff..tiff + 2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return Err("bad TIFF header".to_string()),
+    };
+    let rd16 = |data: &[u8], off: usize| -> Result<usize, String> {
+        let b = data.get(off..off + 2).ok_or("truncated IFD")?;
+        let v =
+            if le { u16::from_le_bytes([b[0], b[1]]) } else { u16::from_be_bytes([b[0], b[1]]) };
+        Ok(v as usize)
+    };
+    let rd32 = |data: &[u8], off: usize| -> Result<usize, String> {
+        let b = data.get(off..off + 4).ok_or("truncated IFD")?;
+        let a = [b[0], b[1], b[2], b[3]];
+        let v = if le { u32::from_le_bytes(a) } else { u32::from_be_bytes(a) };
+        Ok(v as usize)
+    };
+
+    let ifd0 = tiff + rd32(data, tiff + 4)?;
+    let count = rd16(data, ifd0)?;
+    let ifd0_end = ifd0 + 2 + count * 12 + 4; // Entries plus the next-IFD offset
+    if ifd0_end > data.len() {
+        return Err("truncated IFD".to_string());
+    }
+    let Some(ptr_entry) = (0..count)
+        .map(|i| ifd0 + 2 + i * 12)
+        .find(|&e| rd16(data, e).is_ok_and(|tag| tag == TAG_GPS_IFD as usize))
+    else {
+        return Ok(false);
+    };
+
+    // Zero the GPS IFD: out-of-line values first, then the directory itself
+    let gps_off = rd32(data, ptr_entry + 8)?;
+    if gps_off < 8 {
+        return Err("bad GPS IFD offset".to_string());
+    }
+    let gps = tiff + gps_off;
+    let gps_count = rd16(data, gps)?;
+    let gps_end = gps + 2 + gps_count * 12 + 4;
+    if gps_end > data.len() {
+        return Err("truncated GPS IFD".to_string());
+    }
+    for i in 0..gps_count {
+        let entry = gps + 2 + i * 12;
+        let unit = match rd16(data, entry + 2)? {
+            1 | 2 | 6 | 7 => 1, // BYTE, ASCII, SBYTE, UNDEFINED
+            3 | 8 => 2,         // SHORT, SSHORT
+            4 | 9 | 11 => 4,    // LONG, SLONG, FLOAT
+            5 | 10 | 12 => 8,   // RATIONAL, SRATIONAL, DOUBLE
+            _ => continue,
+        };
+        let size = unit * rd32(data, entry + 4)?;
+        if size > 4 {
+            let value = tiff + rd32(data, entry + 8)?;
+            if let Some(bytes) = data.get_mut(value..value + size) {
+                bytes.fill(0);
+            }
+        }
+    }
+    data[gps..gps_end].fill(0);
+
+    // Drop the pointer: shift the later entries and the next-IFD offset up by one slot
+    data.copy_within(ptr_entry + 12..ifd0_end, ptr_entry);
+    data[ifd0_end - 12..ifd0_end].fill(0);
+    let new_count = (count - 1) as u16;
+    let bytes = if le { new_count.to_le_bytes() } else { new_count.to_be_bytes() };
+    data[ifd0..ifd0 + 2].copy_from_slice(&bytes);
+    Ok(true)
+}
+
+/// Blank the `exif:GPS*` properties (attributes and elements alike) of the XMP packet
+/// embedded in a file image with spaces, which XML reads as plain whitespace, so the
+/// packet keeps its size and stays well-formed. Returns false when there were none.
+pub fn strip_xmp_gps(data: &mut [u8]) -> bool {
+    const PROP: &[u8] = b"exif:GPS";
+    let find = |hay: &[u8], needle: &[u8]| hay.windows(needle.len()).position(|w| w == needle);
+    let Some(start) = find(data, b"<x:xmpmeta") else { return false };
+    let end = find(&data[start..], b"</x:xmpmeta>").map_or(data.len(), |e| start + e);
+    let mut stripped = false;
+    let mut pos = start;
+    while let Some(rel) = find(&data[pos..end], PROP) {
+        let at = pos + rel;
+        let name_len = data[at..end]
+            .iter()
+            .position(|&b| b == b'=' || b == b'>' || b == b'/' || b.is_ascii_whitespace())
+            .unwrap_or(end - at);
+        let name = &data[at..at + name_len];
+        let span = match data[at - 1] {
+            // <exif:GPSLatitude>..</exif:GPSLatitude>, or self-closing
+            b'<' => find(&data[at..end], b">")
+                .and_then(|gt| {
+                    let open_end = at + gt + 1;
+                    if data[open_end - 2] == b'/' {
+                        return Some(open_end);
+                    }
+                    let close = [&b"</"[..], name, b">"].concat();
+                    find(&data[open_end..end], &close).map(|c| open_end + c + close.len())
+                })
+                .map(|e| (at - 1, e)),
+            // exif:GPSLatitude="..." inside a start tag
+            b if b.is_ascii_whitespace() && data.get(at + name_len) == Some(&b'=') => {
+                let quote_at = at + name_len + 1;
+                match data.get(quote_at) {
+                    Some(&q) if q == b'"' || q == b'\'' => {
+                        find(&data[quote_at + 1..end], &[q]).map(|e| (at, quote_at + 1 + e + 1))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match span {
+            Some((s, e)) => {
+                data[s..e].fill(b' ');
+                stripped = true;
+                pos = e;
+            }
+            None => pos = at + PROP.len(),
+        }
+    }
+    stripped
+}
+
+/// Give a JPEG or TIFF file image in memory a GPS IFD holding `lat`/`lon` (decimal
+/// degrees). Any old GPS IFD is stripped first. The new GPS IFD and a copy of IFD0
+/// that points to it are appended to the end of the TIFF block (the APP1 segment
+/// grows for JPEG) and the header is pointed at the copy. Nothing before the end
+/// of the block moves, so every existing offset stays valid.
+pub fn set_gps_ifd(data: &mut Vec<u8>, lat: f64, lon: f64) -> Result<(), String> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(format!("coordinates out of range: {}, {}", lat, lon));
+    }
+    if find_tiff_start(data).is_none() && data.starts_with(&[0xFF, 0xD8]) {
+        insert_exif_segment(data);
+    }
+    strip_gps_ifd(data)?;
+    let tiff = find_tiff_start(data).ok_or("no EXIF block")?;
+    let le = data[tiff] == b'I'; // Header already checked by strip_gps_ifd
+    let rd16 = |data: &[u8], off: usize| -> Result<usize, String> {
+        let b = data.get(off..off + 2).ok_or("truncated IFD")?;
+        let v =
+            if le { u16::from_le_bytes([b[0], b[1]]) } else { u16::from_be_bytes([b[0], b[1]]) };
+        Ok(v as usize)
+    };
+    let rd32 = |data: &[u8], off: usize| -> Result<usize, String> {
+        let b = data.get(off..off + 4).ok_or("truncated IFD")?;
+        let a = [b[0], b[1], b[2], b[3]];
+        let v = if le { u32::from_le_bytes(a) } else { u32::from_be_bytes(a) };
+        Ok(v as usize)
+    };
+    let w16 = |v: u16| if le { v.to_le_bytes() } else { v.to_be_bytes() };
+    let w32 = |v: u32| if le { v.to_le_bytes() } else { v.to_be_bytes() };
+    let entry = |tag: u16, typ: u16, n: u32, value: [u8; 4]| {
+        let mut e = w16(tag).to_vec();
+        e.extend_from_slice(&w16(typ));
+        e.extend_from_slice(&w32(n));
+        e.extend_from_slice(&value);
+        e
+    };
+
+    // End of the TIFF block: the file end for TIFF, the APP1 segment end for JPEG
+    let end = if tiff == 0 { data.len() } else { tiff - 8 + rd_be16(data, tiff - 8) };
+    if end > data.len() {
+        return Err("truncated EXIF segment".to_string());
+    }
+    let ifd0 = tiff + rd32(data, tiff + 4)?;
+    let count = rd16(data, ifd0)?;
+    let entries_end = ifd0 + 2 + count * 12;
+    if entries_end + 4 > data.len() {
+        return Err("truncated IFD".to_string());
+    }
+
+    // Offsets are relative to the TIFF header and must be word aligned
+    let pad = (end - tiff) % 2;
+    let new_ifd0 = end - tiff + pad;
+    let gps_ifd = new_ifd0 + 2 + (count + 1) * 12 + 4;
+    let values = gps_ifd + 2 + 5 * 12 + 4;
+    if values + 48 > u32::MAX as usize {
+        return Err("TIFF too large".to_string());
+    }
+
+    let mut block = vec![0u8; pad];
+    block.extend_from_slice(&w16(count as u16 + 1));
+    let mut ptr = Some(entry(TAG_GPS_IFD, 4, 1, w32(gps_ifd as u32)));
+    for e in data[ifd0 + 2..entries_end].chunks(12) {
+        // Entries stay sorted by tag
+        if rd16(e, 0)? > TAG_GPS_IFD as usize
+            && let Some(p) = ptr.take()
+        {
+            block.extend(p);
+        }
+        block.extend_from_slice(e);
+    }
+    if let Some(p) = ptr {
+        block.extend(p);
+    }
+    block.extend_from_slice(&data[entries_end..entries_end + 4]); // Next-IFD offset
+
+    let lat_ref = if lat < 0.0 { b'S' } else { b'N' };
+    let lon_ref = if lon < 0.0 { b'W' } else { b'E' };
+    block.extend_from_slice(&w16(5));
+    block.extend(entry(0x0000, 1, 4, [2, 3, 0, 0])); // GPSVersionID
+    block.extend(entry(0x0001, 2, 2, [lat_ref, 0, 0, 0]));
+    block.extend(entry(0x0002, 5, 3, w32(values as u32)));
+    block.extend(entry(0x0003, 2, 2, [lon_ref, 0, 0, 0]));
+    block.extend(entry(0x0004, 5, 3, w32(values as u32 + 24)));
+    block.extend_from_slice(&w32(0));
+    for (num, den) in dms_rationals(lat).into_iter().chain(dms_rationals(lon)) {
+        block.extend_from_slice(&w32(num));
+        block.extend_from_slice(&w32(den));
+    }
+
+    if tiff > 0 {
+        let seg_len = end - (tiff - 8) + block.len();
+        let seg_len = u16::try_from(seg_len).map_err(|_| "EXIF segment would exceed 64 KiB")?;
+        data[tiff - 8..tiff - 6].copy_from_slice(&seg_len.to_be_bytes());
+    }
+    data.splice(end..end, block);
+    data[tiff + 4..tiff + 8].copy_from_slice(&w32(new_ifd0 as u32));
+    Ok(())
+}
+
+/// Give a JPEG or TIFF file image in memory the capture time `when` as
+/// DateTimeOriginal. A DateTimeOriginal slot already there (blank or garbage, as
+/// some cameras leave it) is overwritten in place. Otherwise a copy of the Exif IFD
+/// with the tag added, its value and a copy of IFD0 pointing to the new Exif IFD
+/// are appended to the TIFF block, the same way `set_gps_ifd` adds its IFD.
+pub fn set_date_original(data: &mut Vec<u8>, when: NaiveDateTime) -> Result<(), String> {
+    let text = when.format(EXIF_DATE_FORMAT).to_string();
+    if text.len() != EXIF_DATE_LEN {
+        return Err(format!("date out of EXIF range: {}", text));
+    }
+    if find_tiff_start(data).is_none() && data.starts_with(&[0xFF, 0xD8]) {
+        insert_exif_segment(data);
+    }
+    let tiff = find_tiff_start(data).ok_or("no EXIF block")?;
+    let slots = find_date_tags(data, tiff)?;
+    if let Some(&(_, offset)) = slots.iter().find(|(tag, _)| *tag == TAG_DATETIME_ORIGINAL) {
+        data[offset..offset + EXIF_DATE_LEN].copy_from_slice(text.as_bytes());
+        return Ok(());
+    }
+    let le = data[tiff] == b'I'; // Header already checked by find_date_tags
+    let rd16 = |data: &[u8], off: usize| -> Result<usize, String> {
+        let b = data.get(off..off + 2).ok_or("truncated IFD")?;
+        let v =
+            if le { u16::from_le_bytes([b[0], b[1]]) } else { u16::from_be_bytes([b[0], b[1]]) };
+        Ok(v as usize)
+    };
+    let rd32 = |data: &[u8], off: usize| -> Result<usize, String> {
+        let b = data.get(off..off + 4).ok_or("truncated IFD")?;
+        let a = [b[0], b[1], b[2], b[3]];
+        let v = if le { u32::from_le_bytes(a) } else { u32::from_be_bytes(a) };
+        Ok(v as usize)
+    };
+    let w16 = |v: u16| if le { v.to_le_bytes() } else { v.to_be_bytes() };
+    let w32 = |v: u32| if le { v.to_le_bytes() } else { v.to_be_bytes() };
+    let entry = |tag: u16, typ: u16, n: u32, value: u32| {
+        let mut e = w16(tag).to_vec();
+        e.extend_from_slice(&w16(typ));
+        e.extend_from_slice(&w32(n));
+        e.extend_from_slice(&w32(value));
+        e
+    };
+    // Entries of the IFD at `ifd` (absolute) with `new` added in tag order,
+    // replacing an entry of the same tag, and the IFD's next-IFD offset
+    let with_entry = |data: &[u8], ifd: Option<usize>, new: Vec<u8>| {
+        let tag = rd16(&new[..], 0)?;
+        let mut entries = Vec::new();
+        let mut next = w32(0).to_vec();
+        if let Some(ifd) = ifd {
+            let count = rd16(data, ifd)?;
+            let end = ifd + 2 + count * 12;
+            if end + 4 > data.len() {
+                return Err("truncated IFD".to_string());
+            }
+            for e in data[ifd + 2..end].chunks(12) {
+                if rd16(e, 0)? != tag {
+                    entries.push(e.to_vec());
+                }
+            }
+            next = data[end..end + 4].to_vec();
+        }
+        let at = entries.iter().position(|e| rd16(&e[..], 0).is_ok_and(|t| t > tag));
+        entries.insert(at.unwrap_or(entries.len()), new);
+        Ok::<_, String>((entries, next))
+    };
+
+    // End of the TIFF block: the file end for TIFF, the APP1 segment end for JPEG
+    let end = if tiff == 0 { data.len() } else { tiff - 8 + rd_be16(data, tiff - 8) };
+    if end > data.len() {
+        return Err("truncated EXIF segment".to_string());
+    }
+    let ifd0 = tiff + rd32(data, tiff + 4)?;
+    let count = rd16(data, ifd0)?;
+    let old_exif = (0..count)
+        .map(|i| ifd0 + 2 + i * 12)
+        .find(|&e| rd16(data, e).is_ok_and(|tag| tag == TAG_EXIF_IFD as usize))
+        .map(|e| rd32(data, e + 8))
+        .transpose()?
+        .map(|off| tiff + off);
+
+    // Layout (offsets relative to the TIFF header, word aligned): new Exif IFD,
+    // the date value, new IFD0
+    let pad = (end - tiff) % 2;
+    let new_exif = end - tiff + pad;
+    let date_entry = entry(TAG_DATETIME_ORIGINAL, 2, EXIF_DATE_LEN as u32 + 1, 0);
+    let (mut exif_entries, exif_next) = with_entry(data, old_exif, date_entry)?;
+    let value = new_exif + 2 + exif_entries.len() * 12 + 4;
+    let new_ifd0 = value + EXIF_DATE_LEN + 1;
+    if new_ifd0 + 2 + (count + 1) * 12 + 4 > u32::MAX as usize {
+        return Err("TIFF too large".to_string());
+    }
+    for e in exif_entries
+        .iter_mut()
+        .filter(|e| rd16(&e[..], 0).is_ok_and(|t| t == TAG_DATETIME_ORIGINAL as usize))
+    {
+        e[8..12].copy_from_slice(&w32(value as u32));
+    }
+    let exif_ptr = entry(TAG_EXIF_IFD, 4, 1, new_exif as u32);
+    let (ifd0_entries, ifd0_next) = with_entry(data, Some(ifd0), exif_ptr)?;
+
+    let mut block = vec![0u8; pad];
+    block.extend_from_slice(&w16(exif_entries.len() as u16));
+    block.extend(exif_entries.iter().flatten());
+    block.extend_from_slice(&exif_next);
+    block.extend_from_slice(text.as_bytes());
+    block.push(0);
+    block.extend_from_slice(&w16(ifd0_entries.len() as u16));
+    block.extend(ifd0_entries.iter().flatten());
+    block.extend_from_slice(&ifd0_next);
+
+    if tiff > 0 {
+        let seg_len = end - (tiff - 8) + block.len();
+        let seg_len = u16::try_from(seg_len).map_err(|_| "EXIF segment would exceed 64 KiB")?;
+        data[tiff - 8..tiff - 6].copy_from_slice(&seg_len.to_be_bytes());
+    }
+    data.splice(end..end, block);
+    data[tiff + 4..tiff + 8].copy_from_slice(&w32(new_ifd0 as u32));
+    Ok(())
+}
+
+/// Degrees, minutes and seconds (to 1/10000 s) of a coordinate, as EXIF rationals.
+fn dms_rationals(deg: f64) -> [(u32, u32); 3] {
+    const SEC_DEN: u64 = 10_000;
+    let total = (deg.abs() * 3600.0 * SEC_DEN as f64).round() as u64;
+    let d = total / (3600 * SEC_DEN);
+    let m = total % (3600 * SEC_DEN) / (60 * SEC_DEN);
+    let s = total % (60 * SEC_DEN);
+    [(d as u32, 1), (m as u32, 1), (s as u32, SEC_DEN as u32)]
+}
+
+fn rd_be16(data: &[u8], off: usize) -> usize {
+    data.get(off..off + 2).map(|b| u16::from_be_bytes([b[0], b[1]]) as usize).unwrap_or(0)
+}
+
+/// Add an empty big-endian EXIF APP1 segment to a JPEG that has none. It goes
+/// after SOI, or after the APP0 segment when there is one (JFIF wants APP0 first).
+fn insert_exif_segment(data: &mut Vec<u8>) {
+    let mut pos = 2;
+    if data.get(pos..pos + 2) == Some(&[0xFF, 0xE0]) {
+        pos = (pos + 2 + rd_be16(data, pos + 2)).min(data.len());
+    }
+    let mut segment = vec![0xFF, 0xE1, 0, 22];
+    segment.extend_from_slice(b"Exif\0\0MM\0*\0\0\0\x08\0\0\0\0\0\0");
+    data.splice(pos..pos, segment);
+}
+
+/// Offset of the TIFF header: file start for TIFF, APP1 "Exif" payload for JPEG.
+fn find_tiff_start(data: &[u8]) -> Option<usize> {
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        return Some(0);
+    }
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= data.len() && data[pos] == 0xFF {
+        let marker = data[pos + 1];
+        if marker == 0xDA || marker == 0xD9 {
+            break; // Start of scan / end of image: no more metadata segments
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let payload = pos + 4;
+        if marker == 0xE1 && data.get(payload..payload + 6).is_some_and(|h| h == b"Exif\0\0") {
+            return Some(payload + 6);
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// Walk IFD0 and the Exif sub-IFD and return (tag, absolute offset) of every
+/// ASCII date tag that is long enough to hold a full date.
+fn find_date_tags(data: &[u8], tiff: usize) -> Result<Vec<(u16, usize)>, String> {
+    let le = match data.get(tiff..tiff + 2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return Err("bad TIFF header".to_string()),
+    };
+    let rd16 = |off: usize| -> Result<u16, String> {
+        let b = data.get(off..off + 2).ok_or("truncated IFD")?;
+        Ok(if le { u16::from_le_bytes([b[0], b[1]]) } else { u16::from_be_bytes([b[0], b[1]]) })
+    };
+    let rd32 = |off: usize| -> Result<u32, String> {
+        let b = data.get(off..off + 4).ok_or("truncated IFD")?;
+        let a = [b[0], b[1], b[2], b[3]];
+        Ok(if le { u32::from_le_bytes(a) } else { u32::from_be_bytes(a) })
+    };
+
+    let mut found = Vec::new();
+    let mut ifds = vec![rd32(tiff + 4)? as usize];
+    let mut visited = 0;
+    while let Some(ifd_off) = ifds.pop() {
+        visited += 1;
+        if visited > 4 {
+            break; // IFD0 + Exif IFD is all we look at; guard against loops
+        }
+        let ifd = tiff + ifd_off;
+        let count = rd16(ifd)? as usize;
+        for i in 0..count {
+            let entry = ifd + 2 + i * 12;
+            let tag = rd16(entry)?;
+            let typ = rd16(entry + 2)?;
+            let n = rd32(entry + 4)? as usize;
+            let value = rd32(entry + 8)? as usize;
+            match tag {
+                TAG_EXIF_IFD => ifds.push(value),
+                TAG_DATETIME | TAG_DATETIME_ORIGINAL | TAG_DATETIME_DIGITIZED
+                    if typ == 2 && n >= EXIF_DATE_LEN =>
+                {
+                    let abs = tiff + value;
+                    if abs + EXIF_DATE_LEN <= data.len() {
+                        found.push((tag, abs));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_shift() {
+        assert_eq!(parse_time_shift("+2h -3m"), Ok(2 * 3600 - 180));
+        assert_eq!(parse_time_shift("-1d"), Ok(-86400));
+        assert_eq!(parse_time_shift("+1h30m"), Ok(5400));
+        assert_eq!(parse_time_shift("90s"), Ok(90));
+        assert!(parse_time_shift("").is_err());
+        assert!(parse_time_shift("2").is_err());
+        assert!(parse_time_shift("2x").is_err());
+        assert_eq!(parse_time_shift("+9999999999999999d"), Err("shift out of range".to_string()));
+    }
+
+    #[test]
+    fn test_find_date_tags_tiff() {
+        // Little-endian TIFF, IFD0 at 8 with one DateTime entry pointing at 26
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&TAG_DATETIME.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&20u32.to_le_bytes());
+        data.extend_from_slice(&26u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(b"2024:01:02 03:04:05\0");
+        assert_eq!(find_tiff_start(&data), Some(0));
+        assert_eq!(find_date_tags(&data, 0), Ok(vec![(TAG_DATETIME, 26)]));
+    }
+
+    #[test]
+    fn test_strip_gps_ifd() {
+        // IFD0 at 8: DateTime (-> 38) and GPS pointer (-> 58); GPS IFD with one
+        // GPSLatitude RATIONAL[3] entry whose 24-byte value sits at 76
+        let entry = |tag: u16, typ: u16, n: u32, value: u32| {
+            let mut e = tag.to_le_bytes().to_vec();
+            e.extend_from_slice(&typ.to_le_bytes());
+            e.extend_from_slice(&n.to_le_bytes());
+            e.extend_from_slice(&value.to_le_bytes());
+            e
+        };
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend(entry(TAG_DATETIME, 2, 20, 38));
+        data.extend(entry(TAG_GPS_IFD, 4, 1, 58));
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(b"2024:01:02 03:04:05\0");
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend(entry(0x0002, 5, 3, 76));
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&[0xAB; 24]);
+        assert_eq!(data.len(), 100);
+
+        assert_eq!(strip_gps_ifd(&mut data), Ok(true));
+        assert!(data[58..].iter().all(|&b| b == 0));
+        assert_eq!(find_date_tags(&data, 0), Ok(vec![(TAG_DATETIME, 50)]));
+        assert_eq!(strip_gps_ifd(&mut data), Ok(false));
+    }
+
+    #[test]
+    fn test_strip_xmp_gps() {
+        let xmp = concat!(
+            "<x:xmpmeta><rdf:Description exif:GPSLatitude=\"60,10.5N\" ",
+            "tiff:Make='X' exif:GPSVersionID='2.3'>",
+            "<exif:GPSLongitude>24,56.1E</exif:GPSLongitude><exif:GPSAltitude/>",
+            "</rdf:Description></x:xmpmeta>"
+        );
+        let mut data = xmp.as_bytes().to_vec();
+        assert!(strip_xmp_gps(&mut data));
+        assert_eq!(data.len(), xmp.len());
+        let text = String::from_utf8(data.clone()).unwrap();
+        assert!(!text.contains("GPS") && !text.contains("60,10") && !text.contains("24,56"));
+        assert!(text.contains("tiff:Make='X'") && text.contains("</rdf:Description>"));
+        assert!(!strip_xmp_gps(&mut data));
+        assert!(!strip_xmp_gps(&mut b"no packet exif:GPSLatitude=\"1\"".to_vec()));
+    }
+
+    #[test]
+    fn test_set_gps_ifd() {
+        // TIFF with one DateTime entry, as in test_find_date_tags_tiff
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&TAG_DATETIME.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&20u32.to_le_bytes());
+        data.extend_from_slice(&26u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(b"2024:01:02 03:04:05\0");
+
+        let read_gps = |tiff: &[u8]| {
+            let exif = exif::Reader::new().read_raw(tiff.to_vec()).unwrap();
+            crate::exif_extract::extract_gps_lat_lon(&exif).unwrap()
+        };
+        set_gps_ifd(&mut data, 60.1699, -24.9384).unwrap();
+        let (lat, lon) = read_gps(&data);
+        assert!((lat - 60.1699).abs() < 1e-6 && (lon + 24.9384).abs() < 1e-6);
+        assert_eq!(find_date_tags(&data, 0), Ok(vec![(TAG_DATETIME, 26)]));
+
+        // Writing again replaces the location instead of adding a second GPS IFD
+        set_gps_ifd(&mut data, -33.8568, 151.2153).unwrap();
+        let (lat, lon) = read_gps(&data);
+        assert!((lat + 33.8568).abs() < 1e-6 && (lon - 151.2153).abs() < 1e-6);
+
+        // A JPEG without EXIF gets a new APP1 segment after APP0
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0, 4, 0, 0, 0xFF, 0xD9];
+        set_gps_ifd(&mut jpeg, 1.5, 2.5).unwrap();
+        assert_eq!(&jpeg[8..10], &[0xFF, 0xE1]);
+        let tiff = find_tiff_start(&jpeg).unwrap();
+        let seg_end = tiff - 8 + rd_be16(&jpeg, tiff - 8);
+        assert_eq!(&jpeg[seg_end..], &[0xFF, 0xD9]);
+        assert_eq!(read_gps(&jpeg[tiff..seg_end]), (1.5, 2.5));
+        assert!(set_gps_ifd(&mut jpeg, 91.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_set_date_original() {
+        // TIFF with one DateTime entry, as in test_find_date_tags_tiff
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&TAG_DATETIME.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&20u32.to_le_bytes());
+        data.extend_from_slice(&26u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(b"2024:01:02 03:04:05\0");
+
+        let read_ts = |tiff: &[u8]| {
+            let exif = exif::Reader::new().read_raw(tiff.to_vec()).unwrap();
+            crate::exif_extract::get_exif_timestamp(&exif)
+        };
+        let when = |s: &str| NaiveDateTime::parse_from_str(s, EXIF_DATE_FORMAT).unwrap();
+        assert_eq!(read_ts(&data), None);
+        set_date_original(&mut data, when("2023:06:07 08:09:10")).unwrap();
+        assert_eq!(read_ts(&data), Some(when("2023:06:07 08:09:10").and_utc().timestamp()));
+        assert!(find_date_tags(&data, 0).unwrap().contains(&(TAG_DATETIME, 26)));
+
+        // The second write reuses the slot, and a later GPS write keeps the Exif IFD
+        let len = data.len();
+        set_date_original(&mut data, when("2023:06:07 08:09:11")).unwrap();
+        assert_eq!(data.len(), len);
+        set_gps_ifd(&mut data, 60.1699, 24.9384).unwrap();
+        assert_eq!(read_ts(&data), Some(when("2023:06:07 08:09:11").and_utc().timestamp()));
+
+        // A JPEG without EXIF gets a new APP1 segment
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xD9];
+        set_date_original(&mut jpeg, when("2020:01:01 00:00:00")).unwrap();
+        let tiff = find_tiff_start(&jpeg).unwrap();
+        let seg_end = tiff - 8 + rd_be16(&jpeg, tiff - 8);
+        assert_eq!(&jpeg[seg_end..], &[0xFF, 0xD9]);
+        let expected = when("2020:01:01 00:00:00").and_utc().timestamp();
+        assert_eq!(read_ts(&jpeg[tiff..seg_end]), Some(expected));
+    }
+}
@@ -1,6 +1,10 @@
+use crate::debug_log;
 use file_id::FileId;
 use filetime::FileTime;
-use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::io::{BufRead, Write};
+use std::path::{Component, Path, PathBuf};
 use std::time::SystemTime;
 
 #[cfg(unix)]
@@ -9,6 +13,71 @@ use std::os::fd::OwnedFd;
 // Standard filename limit for most filesystems
 const MAX_FILENAME_BYTES: usize = 255;
 
+/// Written into every dated subfolder of the staging directory
+pub const STAGING_MANIFEST: &str = "manifest.jsonl";
+
+/// Numbered names tried when staging meets a file of the same name
+const MAX_NAME_NUMBER: usize = 1000;
+
+/// Where deleted files go: the staging folder (`gui.staging_dir`), the trash, or
+/// nowhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeleteMode {
+    Stage,
+    Trash,
+    Permanent,
+}
+
+impl DeleteMode {
+    /// Staging wins over the trash when a staging folder is configured.
+    pub fn new(use_trash: bool, staging_dir: Option<&Path>) -> Self {
+        if staging_dir.is_some() {
+            DeleteMode::Stage
+        } else if use_trash {
+            DeleteMode::Trash
+        } else {
+            DeleteMode::Permanent
+        }
+    }
+
+    /// The action in prompts: "stage", "trash" or "delete"
+    pub fn verb(self) -> &'static str {
+        match self {
+            DeleteMode::Stage => "stage",
+            DeleteMode::Trash => "trash",
+            DeleteMode::Permanent => "delete",
+        }
+    }
+}
+
+/// Entries inside ZIP archives (`grouping.scan_archives`) are read-only: refuse to
+/// delete, move or rename them with a clear error instead of a raw "not found".
+pub fn ensure_not_archived(path: &Path) -> std::io::Result<()> {
+    if crate::archive::is_archive_entry(path) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "file is inside a ZIP archive (read-only)",
+        ));
+    }
+    Ok(())
+}
+
+/// Delete `path` the configured way: into the staging folder, to the trash, or for good.
+pub fn remove_file(
+    path: &Path,
+    use_trash: bool,
+    staging_dir: Option<&Path>,
+) -> std::io::Result<()> {
+    ensure_not_archived(path)?;
+    match (DeleteMode::new(use_trash, staging_dir), staging_dir) {
+        (DeleteMode::Stage, Some(dir)) => stage_file(dir, path).map(|_| ()),
+        (DeleteMode::Trash, _) => {
+            trash::delete(path).map_err(|e| std::io::Error::other(e.to_string()))
+        }
+        _ => std::fs::remove_file(path),
+    }
+}
+
 /// Holds an open handle to the destination directory plus cached metadata.
 ///
 /// On Unix the OwnedFd is kept alive for the entire confirm+move sequence,
@@ -98,6 +167,13 @@ pub fn move_files_into(dest: &DestinationDir, sources: &[PathBuf]) -> Vec<MoveRe
 }
 
 fn move_one(dest: &DestinationDir, src: &Path) -> MoveResult {
+    if let Err(e) = ensure_not_archived(src) {
+        return MoveResult {
+            source: src.to_path_buf(),
+            destination: dest.path.clone(),
+            outcome: Err(e),
+        };
+    }
     // Extract the destination filename from the source.
     let Some(dst_name_os) = src.file_name() else {
         return MoveResult {
@@ -124,13 +200,271 @@ fn move_one(dest: &DestinationDir, src: &Path) -> MoveResult {
     MoveResult { source: src.to_path_buf(), destination: dest.path.join(&final_name), outcome }
 }
 
+/// One line of a staging manifest.
+#[derive(Serialize, Deserialize)]
+struct StagedEntry {
+    #[serde(with = "manifest_path_repr")]
+    original: PathBuf,
+    #[serde(with = "manifest_path_repr")]
+    staged: PathBuf,
+    time: i64,
+}
+
+/// Manifest paths are plain strings; one that isn't valid UTF-8 is written as
+/// `{"hex": "..."}` of its raw bytes (UTF-16 units on Windows), so it restores exactly.
+mod manifest_path_repr {
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use std::ffi::OsString;
+    use std::path::{Path, PathBuf};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Text(PathBuf),
+        Hex { hex: String },
+    }
+
+    pub fn serialize<S: Serializer>(path: &Path, s: S) -> Result<S::Ok, S::Error> {
+        if let Some(text) = path.to_str() {
+            return s.serialize_str(text);
+        }
+        let mut map = s.serialize_map(Some(1))?;
+        map.serialize_entry("hex", &hex::encode(to_bytes(path)))?;
+        map.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<PathBuf, D::Error> {
+        match Repr::deserialize(d)? {
+            Repr::Text(path) => Ok(path),
+            Repr::Hex { hex } => {
+                let bytes = hex::decode(hex).map_err(D::Error::custom)?;
+                from_bytes(bytes)
+                    .map(PathBuf::from)
+                    .ok_or_else(|| D::Error::custom("odd UTF-16 length"))
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn to_bytes(path: &Path) -> Vec<u8> {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes().to_vec()
+    }
+
+    #[cfg(unix)]
+    fn from_bytes(bytes: Vec<u8>) -> Option<OsString> {
+        use std::os::unix::ffi::OsStringExt;
+        Some(OsString::from_vec(bytes))
+    }
+
+    #[cfg(windows)]
+    fn to_bytes(path: &Path) -> Vec<u8> {
+        use std::os::windows::ffi::OsStrExt;
+        path.as_os_str().encode_wide().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[cfg(windows)]
+    fn from_bytes(bytes: Vec<u8>) -> Option<OsString> {
+        use std::os::windows::ffi::OsStringExt;
+        if bytes.len() % 2 != 0 {
+            return None;
+        }
+        let wide: Vec<u16> =
+            bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        Some(OsString::from_wide(&wide))
+    }
+}
+
+/// Move `src` into `<staging_root>/<YYYY-MM-DD>/`, mirroring its absolute directory
+/// path below that, so equal filenames from different folders never collide.
+/// The move is appended to the dated folder's manifest for `restore_staged`.
+pub fn stage_file(staging_root: &Path, src: &Path) -> std::io::Result<PathBuf> {
+    let abs = std::path::absolute(src)?;
+    let day_dir = staging_root.join(chrono::Local::now().format("%Y-%m-%d").to_string());
+    let target_dir = mirror_dir(&day_dir, abs.parent().unwrap_or(Path::new("")));
+    move_recorded(&day_dir, &target_dir, abs)
+}
+
+/// Move `src` into `review_root`, recreating its directory relative to `base` (the
+/// scan root), so a reviewed folder looks like the tree it came from. Files outside
+/// `base` mirror their absolute directory like `stage_file`. The move goes into
+/// `review_root`'s manifest, so `restore_staged(review_root)` puts everything back.
+pub fn move_to_review(
+    review_root: &Path,
+    base: Option<&Path>,
+    src: &Path,
+) -> std::io::Result<PathBuf> {
+    let abs = std::path::absolute(src)?;
+    let parent = abs.parent().unwrap_or(Path::new(""));
+    let rel = base.and_then(|b| parent.strip_prefix(b).ok()).unwrap_or(parent);
+    let target_dir = mirror_dir(review_root, rel);
+    move_recorded(review_root, &target_dir, abs)
+}
+
+/// `root` joined with the plain components of `dir`: drops the root and (on Windows)
+/// the drive prefix, and any `..`, so the result always stays below `root`.
+fn mirror_dir(root: &Path, dir: &Path) -> PathBuf {
+    let mut target = root.to_path_buf();
+    target.extend(dir.components().filter_map(|c| match c {
+        Component::Normal(s) => Some(s),
+        _ => None,
+    }));
+    target
+}
+
+/// Move `abs` into `target_dir` (created if needed) and append the move to the
+/// manifest in `manifest_dir`. A name already taken there (the same file name
+/// staged twice on one day) gets a number: "a (2).jpg", and a name that isn't valid
+/// UTF-8 is staged under its lossy form; the manifest keeps the original exactly.
+/// When the manifest can't be written the file is moved back, since nothing would
+/// restore it. Returns the new path.
+fn move_recorded(manifest_dir: &Path, target_dir: &Path, abs: PathBuf) -> std::io::Result<PathBuf> {
+    ensure_not_archived(&abs)?;
+    let original_name = abs.file_name().map(OsStr::to_os_string).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "source path has no filename component",
+        )
+    })?;
+    let name = original_name.to_string_lossy().into_owned();
+    std::fs::create_dir_all(target_dir)?;
+    let dest = DestinationDir::open(target_dir)?;
+    let mut moved = None;
+    for n in 1..=MAX_NAME_NUMBER {
+        let candidate = if n == 1 { name.clone() } else { numbered_name(&name, n) };
+        let (final_name, outcome) = try_move_with_retry(&dest, &abs, &candidate);
+        match outcome {
+            Ok(()) => {
+                moved = Some(dest.path.join(final_name));
+                break;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    let staged = moved.ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::AlreadyExists, "no free name in target folder")
+    })?;
+
+    let entry = StagedEntry { original: abs, staged, time: chrono::Utc::now().timestamp() };
+    if let Err(e) = append_manifest(manifest_dir, &entry) {
+        let back = entry
+            .original
+            .parent()
+            .ok_or_else(|| std::io::Error::other("no parent directory"))
+            .and_then(DestinationDir::open)
+            .and_then(|dir| try_move(&dir, &entry.staged, &original_name));
+        if let Err(back_err) = back {
+            eprintln!(
+                "[ERROR] {} left at {} without a manifest entry: {}",
+                entry.original.display(),
+                entry.staged.display(),
+                back_err
+            );
+        }
+        return Err(e);
+    }
+    Ok(entry.staged)
+}
+
+fn append_manifest(manifest_dir: &Path, entry: &StagedEntry) -> std::io::Result<()> {
+    let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    let mut manifest = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(manifest_dir.join(STAGING_MANIFEST))?;
+    writeln!(manifest, "{}", line)?;
+    manifest.sync_all()
+}
+
+/// `name` with " (n)" before its extension, shortened to fit the filename limit.
+fn numbered_name(name: &str, n: usize) -> String {
+    let (base, ext) = match name.rfind('.') {
+        Some(dot) if dot > 0 => name.split_at(dot),
+        _ => (name, ""),
+    };
+    let suffix = format!(" ({})", n);
+    let room = MAX_FILENAME_BYTES.saturating_sub(suffix.len() + ext.len());
+    format!("{}{}{}", truncate_str_to_byte_limit(base, room), suffix, ext)
+}
+
+fn manifest_path(dir: &Path) -> PathBuf {
+    if dir.is_dir() { dir.join(STAGING_MANIFEST) } else { dir.to_path_buf() }
+}
+
+/// Number of entries in a staging manifest, i.e. the files `restore_staged` would
+/// try to move back.
+pub fn staged_count(dir: &Path) -> std::io::Result<usize> {
+    let reader = std::io::BufReader::new(std::fs::File::open(manifest_path(dir))?);
+    let mut count = 0;
+    for line in reader.lines() {
+        if !line?.trim().is_empty() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Move every file listed in a staging manifest back to its original location.
+/// `dir` is a dated staging folder or the manifest itself. Entries that could not
+/// be restored stay in the manifest; it is removed once everything is back.
+pub fn restore_staged(dir: &Path) -> std::io::Result<Vec<MoveResult>> {
+    let manifest_path = manifest_path(dir);
+    let reader = std::io::BufReader::new(std::fs::File::open(&manifest_path)?);
+
+    let mut results = Vec::new();
+    let mut remaining = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: StagedEntry = match serde_json::from_str(&line) {
+            Ok(e) => e,
+            Err(e) => {
+                eprintln!("[WARN] Skipping bad manifest line ({}): {}", e, line);
+                remaining.push(line);
+                continue;
+            }
+        };
+        let (Some(parent), Some(name)) = (entry.original.parent(), entry.original.file_name())
+        else {
+            remaining.push(line);
+            continue;
+        };
+        // The staged name may be truncated or numbered; restore the original one
+        let outcome = std::fs::create_dir_all(parent)
+            .and_then(|_| DestinationDir::open(parent))
+            .and_then(|dest| try_move(&dest, &entry.staged, name));
+        let result = MoveResult {
+            source: entry.staged.clone(),
+            destination: entry.original.clone(),
+            outcome,
+        };
+        if result.outcome.is_err() {
+            remaining.push(line);
+        }
+        results.push(result);
+    }
+
+    if remaining.is_empty() {
+        std::fs::remove_file(&manifest_path)?;
+    } else {
+        let mut text = remaining.join("\n");
+        text.push('\n');
+        std::fs::write(&manifest_path, text)?;
+    }
+    Ok(results)
+}
+
 /// Run the move; on ENAMETOOLONG, truncate the filename and retry once.
 fn try_move_with_retry(
     dest: &DestinationDir,
     src: &Path,
     dst_name: &str,
 ) -> (String, std::io::Result<()>) {
-    let outcome = try_move(dest, src, dst_name);
+    let outcome = try_move(dest, src, OsStr::new(dst_name));
 
     if let Err(ref e) = outcome
         && is_name_too_long(e)
@@ -138,7 +472,7 @@ fn try_move_with_retry(
         let truncated = truncate_filename_to_limit(dst_name);
         if truncated != dst_name {
             eprintln!("Filename too long, retrying with: {}", truncated);
-            let retry = try_move(dest, src, &truncated);
+            let retry = try_move(dest, src, OsStr::new(&truncated));
             return (truncated, retry);
         }
     }
@@ -148,7 +482,7 @@ fn try_move_with_retry(
 
 /// Single move attempt: rename within the same fs, copy+delete across fs
 /// boundaries. Always uses NOREPLACE / O_EXCL so we never overwrite.
-fn try_move(dest: &DestinationDir, src: &Path, dst_name: &str) -> std::io::Result<()> {
+fn try_move(dest: &DestinationDir, src: &Path, dst_name: &OsStr) -> std::io::Result<()> {
     #[cfg(any(target_os = "linux", target_os = "android"))]
     {
         // 1. Try atomic renameat2 with RENAME_NOREPLACE on the kept-open dirfd.
@@ -162,9 +496,10 @@ fn try_move(dest: &DestinationDir, src: &Path, dst_name: &str) -> std::io::Resul
                 }
                 // EXDEV (cross-device), ENOSYS (old kernel), EINVAL (FAT/Android
                 // rejecting the flag), EPERM, EOPNOTSUPP -> fall through to copy.
-                eprintln!(
+                debug_log!(
                     "[DEBUG] renameat2 failed: {} (errno: {:?}). Trying copy fallback...",
-                    e, raw
+                    e,
+                    raw
                 );
             }
         }
@@ -186,7 +521,7 @@ fn try_move(dest: &DestinationDir, src: &Path, dst_name: &str) -> std::io::Resul
         let dst_path = dest.path.join(dst_name);
         if let Err(e) = std::fs::hard_link(src, &dst_path) {
             // Cross-volume or unsupported -> copy with create_new + delete.
-            eprintln!("[DEBUG] hard_link failed: {}. Trying copy fallback...", e);
+            debug_log!("[DEBUG] hard_link failed: {}. Trying copy fallback...", e);
             let mut reader = std::fs::File::open(src)?;
             let mut writer =
                 std::fs::OpenOptions::new().write(true).create_new(true).open(&dst_path)?;
@@ -205,7 +540,7 @@ fn try_move(dest: &DestinationDir, src: &Path, dst_name: &str) -> std::io::Resul
 fn try_renameat_noreplace(
     dest: &DestinationDir,
     src: &Path,
-    dst_name: &str,
+    dst_name: &OsStr,
 ) -> std::io::Result<()> {
     use rustix::fs::{CWD, RenameFlags, renameat_with};
     use std::os::fd::AsFd;
@@ -219,7 +554,7 @@ fn try_renameat_noreplace(
 /// Copy + delete via the kept-open dirfd. Uses O_EXCL so we never overwrite,
 /// and restores permissions / timestamps / xattrs on a best-effort basis.
 #[cfg(unix)]
-fn copy_move_into(dest: &DestinationDir, src: &Path, dst_name: &str) -> std::io::Result<()> {
+fn copy_move_into(dest: &DestinationDir, src: &Path, dst_name: &OsStr) -> std::io::Result<()> {
     use rustix::fs::{Mode, OFlags, openat};
     use std::os::fd::AsFd;
     use xattr::FileExt;
@@ -404,6 +739,84 @@ fn truncate_str_to_byte_limit(s: &str, max_bytes: usize) -> &str {
     &s[..end]
 }
 
+/// Remove the GPS tags of a JPEG or TIFF file: the EXIF GPS IFD and the `exif:GPS*`
+/// properties of its XMP packet. The stripped copy is written to a temp file next to
+/// it and renamed over the original, so a crash never leaves a half-written image.
+/// Returns false (file untouched) when it had neither. When only the XMP part could
+/// be removed the file is rewritten but the EXIF error is returned, as the file
+/// still carries its EXIF location.
+pub fn strip_gps(path: &Path) -> Result<bool, String> {
+    if !crate::exif_write::supports_exif_write(path) {
+        return Err("unsupported format".to_string());
+    }
+    let mut data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let exif = crate::exif_write::strip_gps_ifd(&mut data);
+    let xmp = crate::exif_write::strip_xmp_gps(&mut data);
+    if !xmp && !exif.as_ref().is_ok_and(|&stripped| stripped) {
+        return exif;
+    }
+    replace_file(path, &data, None)?;
+    match exif {
+        Ok(_) => Ok(true),
+        Err(e) => Err(format!("only XMP GPS removed, EXIF left as is: {}", e)),
+    }
+}
+
+/// Write GPS coordinates (decimal degrees) into a JPEG or TIFF file, replacing any
+/// location it had. Rewritten like `strip_gps`, but the original mtime is kept so
+/// date-based sorting and syncing tools don't see the file as new.
+pub fn write_gps(path: &Path, lat: f64, lon: f64) -> Result<(), String> {
+    if !crate::exif_write::supports_exif_write(path) {
+        return Err("unsupported format".to_string());
+    }
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    let mut data = std::fs::read(path).map_err(|e| e.to_string())?;
+    crate::exif_write::set_gps_ifd(&mut data, lat, lon)?;
+    replace_file(path, &data, Some(FileTime::from_last_modification_time(&metadata)))
+}
+
+/// Add a missing capture time (DateTimeOriginal) and/or location to a JPEG or TIFF
+/// file in one rewrite, keeping the mtime like `write_gps`.
+pub fn fill_exif(
+    path: &Path,
+    when: Option<chrono::NaiveDateTime>,
+    gps: Option<(f64, f64)>,
+) -> Result<(), String> {
+    if !crate::exif_write::supports_exif_write(path) {
+        return Err("unsupported format".to_string());
+    }
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    let mut data = std::fs::read(path).map_err(|e| e.to_string())?;
+    if let Some(when) = when {
+        crate::exif_write::set_date_original(&mut data, when)?;
+    }
+    if let Some((lat, lon)) = gps {
+        crate::exif_write::set_gps_ifd(&mut data, lat, lon)?;
+    }
+    replace_file(path, &data, Some(FileTime::from_last_modification_time(&metadata)))
+}
+
+/// Replace `path` with `data` via a temp file next to it and a rename, so a crash
+/// never leaves a half-written file. Permissions are kept, and the mtime too when given.
+fn replace_file(path: &Path, data: &[u8], mtime: Option<FileTime>) -> Result<(), String> {
+    let name = path.file_name().ok_or("no file name")?.to_string_lossy();
+    let tmp = path.with_file_name(format!(".{}.phdupes-tmp", name));
+    let write = || -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(data)?;
+        file.set_permissions(std::fs::metadata(path)?.permissions())?;
+        if let Some(mtime) = mtime {
+            filetime::set_file_handle_times(&file, None, Some(mtime))?;
+        }
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)
+    };
+    write().map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        e.to_string()
+    })
+}
+
 pub fn get_file_key(path: &Path) -> Option<u128> {
     // 1. Fallback for non-Unix/Windows: Return truncated blake3 of path
     #[cfg(not(any(unix, windows)))]
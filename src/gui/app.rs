@@ -5,7 +5,7 @@ use geo::Point;
 use jiff::Timestamp;
 use notify::{Event, RecommendedWatcher, RecursiveMode, Result as NotifyResult, Watcher};
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -14,21 +14,32 @@ use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use super::gps_map::GpsMapState;
+use super::cache_panel::CachePanel;
+use super::cull::CullSession;
+use super::dialogs::{ReviewMove, StripGpsPlan, TimeShiftPreview, WriteGpsPlan};
+use super::fill_metadata::FillMetadata;
+use super::gps_map::{DistanceFormat, GpsMapState};
+use super::group_review::GroupReview;
 use super::image::{GroupViewState, ViewMode};
+use super::web_export::WebExport;
 use crate::GroupStatus;
-use crate::db::{AppContext, EnrichmentResult};
+use crate::db::{AfterScan, AppContext, EnrichmentResult, FsNotifications};
+use crate::debug_log;
 use crate::format_relative_time;
 use crate::gui::APP_TITLE;
-use crate::gui::image::{ImageLoadResult, MAX_TEXTURE_SIDE};
+use crate::gui::image::{CROP_GUIDES, ImageLoadResult, MAX_TEXTURE_SIDE};
 use crate::img_debug;
 use crate::position;
 use crate::scanner::{self, ScanConfig};
 use crate::state::{
-    AppState, InputIntent, format_path_depth, get_bit_identical_counts, get_content_subgroups,
-    get_hardlink_groups,
+    AppState, InputIntent, get_bit_identical_counts, get_content_subgroups, get_hardlink_groups,
+    get_luma_variant_subgroups, get_mixed_profile_subgroups, get_near_identical_subgroups,
+    get_smaller_copies,
 };
-use crate::{FileMetadata, GroupInfo};
+use crate::{FileMetadata, GroupInfo, ScanSummary};
+
+/// Entries kept in the file system event panel; the oldest are dropped first
+const FS_EVENT_LOG_LEN: usize = 500;
 
 // Define a cache struct to hold the data we previously fetched every frame
 #[derive(Clone)]
@@ -78,32 +89,287 @@ fn truncate_to_width(
     ("…".to_string(), true)
 }
 
+/// Like `truncate_to_width`, but drops characters from the start ("…dir/file.jpg").
+fn truncate_to_width_left(
+    text: &str,
+    max_width: f32,
+    font_id: &egui::FontId,
+    ui: &egui::Ui,
+) -> (String, bool) {
+    let full_galley =
+        ui.painter().layout_no_wrap(text.to_string(), font_id.clone(), egui::Color32::WHITE);
+    let full_width = full_galley.rect.width();
+    if full_width <= max_width {
+        return (text.to_string(), false);
+    }
+
+    let estimated_ellipsis_width = font_id.size * 0.8;
+    if let Some(row) = full_galley.rows.first() {
+        let mut byte_offset = 0;
+        for glyph in &row.glyphs {
+            // Keep everything from this glyph on if it fits behind the ellipsis
+            if full_width - glyph.pos.x + estimated_ellipsis_width <= max_width {
+                return (format!("…{}", &text[byte_offset..]), true);
+            }
+            byte_offset += glyph.chr.len_utf8();
+        }
+    }
+
+    ("…".to_string(), true)
+}
+
+/// Duplicate mode display filter over the found groups (see `GuiApp::apply_group_filter`).
+#[derive(Clone, Copy, PartialEq)]
+pub(super) struct GroupFilter {
+    pub(super) min_size: usize,
+    /// 0 = no limit
+    pub(super) max_size: usize,
+    pub(super) max_dist: Option<u32>,
+}
+
+impl GroupFilter {
+    fn from_config(cfg: &crate::db::GuiConfig) -> Self {
+        Self {
+            min_size: cfg.min_group_size.unwrap_or(2).max(2),
+            max_size: cfg.max_group_size.unwrap_or(0),
+            max_dist: cfg.max_group_dist,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.min_size > 2 || self.max_size > 0 || self.max_dist.is_some()
+    }
+
+    fn admits(&self, len: usize, info: &GroupInfo) -> bool {
+        len >= self.min_size
+            && (self.max_size == 0 || len <= self.max_size)
+            && self.max_dist.is_none_or(|d| info.max_dist <= d)
+    }
+}
+
+/// List badges: key metadata a file lacks, or that it looks like a screenshot.
+/// Each is filterable with Shift+E.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum ListBadge {
+    Date,
+    Gps,
+    Camera,
+    Screenshot,
+}
+
+impl ListBadge {
+    pub(super) const ALL: [ListBadge; 4] =
+        [ListBadge::Date, ListBadge::Gps, ListBadge::Camera, ListBadge::Screenshot];
+
+    pub(super) fn label(self) -> &'static str {
+        match self {
+            ListBadge::Date => "no-date",
+            ListBadge::Gps => "no-gps",
+            ListBadge::Camera => "no-camera",
+            ListBadge::Screenshot => "screenshot",
+        }
+    }
+
+    /// Shift+E order: off -> date -> gps -> camera -> screenshot -> off
+    fn next(current: Option<ListBadge>) -> Option<ListBadge> {
+        match current {
+            None => Some(ListBadge::Date),
+            Some(ListBadge::Date) => Some(ListBadge::Gps),
+            Some(ListBadge::Gps) => Some(ListBadge::Camera),
+            Some(ListBadge::Camera) => Some(ListBadge::Screenshot),
+            Some(ListBadge::Screenshot) => None,
+        }
+    }
+}
+
+/// Final scan result: groups, their infos, view mode subdirectories, and the live
+/// photo companions of the listed files (looked up on the scan thread)
+type ScanResult =
+    (Vec<Vec<FileMetadata>>, Vec<GroupInfo>, Vec<PathBuf>, HashMap<PathBuf, Vec<PathBuf>>);
+
+/// Shift+S: one group narrowed to a single content subgroup (C1, C2, ...)
+pub(super) struct SubgroupFilter {
+    /// Number of the listed subgroup
+    id: usize,
+    /// Its files; the rest of the group containing them is hidden
+    shown: HashSet<PathBuf>,
+}
+
+impl SubgroupFilter {
+    /// Whether `group` is the narrowed one
+    fn lists_group(&self, group: &[FileMetadata]) -> bool {
+        group.iter().any(|f| self.shown.contains(&f.path))
+    }
+}
+
+/// Ctrl+Shift+L: one group being hashed and grouped again in the background
+pub(super) struct GroupRescan {
+    group_idx: usize,
+    /// Paths of the group when the re-scan started, to check it is still the same group
+    paths: Vec<PathBuf>,
+    rx: Receiver<(Vec<Vec<FileMetadata>>, Vec<GroupInfo>)>,
+}
+
+/// What a file's cached EXIF features say about its badges
+#[derive(Clone)]
+pub(super) struct CachedTags {
+    date: bool,
+    /// Camera identity, see `camera_name`
+    camera: Option<String>,
+    screenshot: bool,
+    /// JPEG XL coding (list label "LL"/"A"); None for other formats
+    jxl: Option<crate::scanner::JxlInfo>,
+    /// HEIC/HEIF with a depth map (list label "D")
+    depth_map: bool,
+}
+
+impl CachedTags {
+    pub(super) fn from_features(features: &crate::image_features::ImageFeatures) -> Self {
+        use crate::exif_types::{TAG_DATETIME, TAG_DATETIME_ORIGINAL, TAG_DERIVED_TIMESTAMP};
+        Self {
+            date: [TAG_DERIVED_TIMESTAMP, TAG_DATETIME_ORIGINAL, TAG_DATETIME]
+                .iter()
+                .any(|&t| features.has_tag(t)),
+            camera: camera_name(features),
+            screenshot: crate::scanner::is_screenshot_features(features),
+            jxl: crate::scanner::JxlInfo::from_features(features),
+            depth_map: matches!(
+                features.get_tag(crate::exif_types::TAG_DERIVED_DEPTH_MAP),
+                Some(crate::exif_types::ExifValue::Short(1))
+            ),
+        }
+    }
+}
+
+/// Camera identity from EXIF features: "Make Model", with the make left out when the
+/// model already starts with it. None when neither is recorded.
+fn camera_name(features: &crate::image_features::ImageFeatures) -> Option<String> {
+    use crate::exif_types::{TAG_MAKE, TAG_MODEL};
+    let make = features.get_tag_string(TAG_MAKE).map(|s| s.trim().to_string());
+    let model = features.get_tag_string(TAG_MODEL).map(|s| s.trim().to_string());
+    match (make, model) {
+        (Some(make), Some(model)) => {
+            let first_word = make.split_whitespace().next().unwrap_or_default();
+            if model.to_lowercase().starts_with(&first_word.to_lowercase()) {
+                Some(model)
+            } else {
+                Some(format!("{} {}", make, model))
+            }
+        }
+        (Some(only), None) | (None, Some(only)) => Some(only),
+        (None, None) => None,
+    }
+}
+
+/// Read the badge facts of `files` (content hashes) from the feature cache on a
+/// background thread; a file without cached features yet comes back as None.
+fn spawn_cached_tags_load(
+    files: Vec<[u8; 32]>,
+    ctx: Arc<AppContext>,
+    result_tx: Sender<([u8; 32], Option<CachedTags>)>,
+) {
+    std::thread::spawn(move || {
+        for content_hash in files {
+            let tags = ctx
+                .get_features(&content_hash)
+                .ok()
+                .flatten()
+                .map(|features| CachedTags::from_features(&features));
+            if result_tx.send((content_hash, tags)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Whether `file` gets `badge`. GPS comes from the listing itself; the others also
+/// need the cached EXIF features, so files not loaded into `cache` yet are never flagged.
+pub(super) fn shows_badge(
+    cache: &HashMap<[u8; 32], CachedTags>,
+    file: &FileMetadata,
+    badge: ListBadge,
+) -> bool {
+    if badge == ListBadge::Gps {
+        return file.gps_pos.is_none();
+    }
+    if badge == ListBadge::Date && file.exif_timestamp.is_some() {
+        return false;
+    }
+    match (cache.get(&file.content_hash), badge) {
+        (Some(t), ListBadge::Date) => !t.date,
+        (Some(t), ListBadge::Camera) => t.camera.is_none(),
+        (Some(t), ListBadge::Screenshot) => t.screenshot,
+        _ => false,
+    }
+}
+
+/// Duplicate mode order within a group: content subgroups contiguous.
+/// We sort primarily by pixel_hash (oriented, if computed), secondarily by path.
+/// This keeps "C1" files together, "C2" together, etc.
+/// Near-identical clusters ("N1") and grayscale/color variants ("L1") come before
+/// that, so they stay contiguous too.
+fn subgroup_order(a: &FileMetadata, b: &FileMetadata) -> std::cmp::Ordering {
+    if a.near_identical != b.near_identical {
+        return a.near_identical.cmp(&b.near_identical);
+    }
+    if a.luma_variant != b.luma_variant {
+        return a.luma_variant.cmp(&b.luma_variant);
+    }
+    // Standard Option ordering puts files without a pixel hash (None) first;
+    // all "Some(hash)" files still end up grouped.
+    // Within a subgroup, files with the same color profile sit together
+    a.subgroup_pixel_hash()
+        .cmp(&b.subgroup_pixel_hash())
+        .then_with(|| {
+            let profile = |f: &FileMetadata| f.icc_profile.as_ref().map(|p| p.hash);
+            profile(a).cmp(&profile(b))
+        })
+        .then_with(|| a.path.cmp(&b.path))
+}
+
 pub struct GuiApp {
     pub(super) state: AppState,
     pub(super) group_views: HashMap<usize, GroupViewState>,
+    /// Set by the renderer when FitWindowNoUpscale is holding the image at native size
+    pub(super) fit_capped: bool,
     pub(super) initial_scale_applied: bool,
     pub(super) initial_panel_width_applied: bool,
     pub(super) ctx: Arc<AppContext>,
+    /// `[gui]` settings, starting as `ctx.gui_config`; replaced by a config reload (Ctrl+R)
+    pub(super) gui_config: crate::db::GuiConfig,
+    /// Map providers by name; replaced by a config reload
+    pub(super) map_providers: HashMap<String, String>,
     pub(super) scan_config: ScanConfig,
-    pub(super) scan_rx:
-        Option<Receiver<(Vec<Vec<FileMetadata>>, Vec<GroupInfo>, Vec<std::path::PathBuf>)>>,
+    pub(super) scan_rx: Option<Receiver<ScanResult>>,
     pub(super) scan_progress_rx: Option<Receiver<(usize, usize)>>,
     pub(super) scan_progress: (usize, usize),
+    /// A duplicate scan has streamed its first preliminary group (see
+    /// `ingest_preview_groups`)
+    pub(super) scan_preview_started: bool,
+    /// Running re-scan of a single group (Ctrl+Shift+L)
+    pub(super) group_rescan: Option<GroupRescan>,
     pub(super) rename_input: String,
     pub(super) show_move_input: bool,
     pub(super) move_input: String,
     pub(super) move_completion_candidates: Vec<String>,
     pub(super) move_completion_index: usize,
     pub(super) last_preload_pos: Option<(usize, usize)>,
+    /// Consecutive navigation steps in one direction (+ forward, - backward)
+    pub(super) preload_momentum: i32,
     pub(super) slideshow_last_advance: Option<std::time::Instant>,
     // View mode: if Some, use scan_for_view with this sort order instead of scan_and_group
     pub(super) view_mode_sort: Option<String>,
+    /// View mode: sort chosen in each directory, restored when it is entered again.
+    /// Directories without an entry use the startup sort (`scan_config.group_by`).
+    pub(super) dir_sorts: HashMap<PathBuf, String>,
     // View mode: if true, recursive scanning is enabled (flatten mode)
     pub(super) view_mode_flatten: bool,
 
     // --- Raw Preloading ---
     // Cache for raw images (Path -> Texture)
     pub(super) raw_cache: HashMap<std::path::PathBuf, egui::TextureHandle>,
+    // EXIF thumbnails shown while the full decode is pending (Path -> (Texture, orientation))
+    pub(super) placeholder_cache: HashMap<std::path::PathBuf, (egui::TextureHandle, u8)>,
     // Set of paths currently being processed by the worker to avoid dupes
     pub(super) raw_loading: HashSet<std::path::PathBuf>,
     // Channel to send paths to the worker
@@ -135,6 +401,37 @@ pub struct GuiApp {
     // Directory browsing (view mode only)
     pub(super) current_dir: Option<std::path::PathBuf>,
     pub(super) show_dir_picker: bool,
+    // Go-to-path quick-open (Ctrl+P, view mode)
+    pub(super) show_goto_input: bool,
+    pub(super) goto_input: String,
+    pub(super) goto_focus_requested: bool,
+    /// File to select once the directory scan started by go-to-path delivers it
+    pub(super) goto_pending_file: Option<std::path::PathBuf>,
+    // Jump to the n-th file overall (`:`)
+    pub(super) show_jump_input: bool,
+    pub(super) jump_input: String,
+    pub(super) jump_focus_requested: bool,
+    // Bulk EXIF date shift (Ctrl+T)
+    pub(super) show_time_shift: bool,
+    pub(super) time_shift_input: String,
+    pub(super) time_shift_focus_requested: bool,
+    pub(super) time_shift_preview: Option<TimeShiftPreview>,
+    // Pin a map point as a named location (GPS map panel)
+    pub(super) pin_location: Option<Point<f64>>,
+    pub(super) pin_location_input: String,
+    pub(super) pin_location_focus_requested: bool,
+    // Strip GPS tags from marked files (Ctrl+G), confirmed before writing
+    pub(super) strip_gps: Option<StripGpsPlan>,
+    pub(super) write_gps: Option<WriteGpsPlan>,
+    /// Missing capture times and locations proposed from neighbours (Ctrl+Shift+T)
+    pub(super) fill_metadata: Option<FillMetadata>,
+    pub(super) group_review: Option<GroupReview>,
+    pub(super) cache_panel: Option<CachePanel>,
+    pub(super) web_export: Option<WebExport>,
+    /// Review folder move waiting for confirmation
+    pub(super) review_move: Option<ReviewMove>,
+    /// Groups confirmed in the review window, keyed by `group_review::group_key`
+    pub(super) reviewed_groups: HashSet<Vec<u128>>,
     pub(super) dir_list: Vec<std::path::PathBuf>,
     /// Cached modification times for `dir_list`, populated alongside it in `open_dir_picker`.
     pub(super) dir_list_mtime: Vec<Option<chrono::DateTime<chrono::Utc>>>,
@@ -148,10 +445,38 @@ pub struct GuiApp {
     pub(super) completion_index: usize,
     // Histogram display
     pub(super) histogram_mode: u8,
+    /// Aspect crop guide: 0 = off, otherwise 1-based index into CROP_GUIDES
+    pub(super) crop_guide: usize,
+    /// Shift+O: draw the previous file at half opacity over the current one
+    pub(super) ghost_overlay: bool,
+    /// Fill behind the image (Shift+K), saved to the config on exit
+    pub(super) viewer_background: crate::db::ViewerBackground,
+    pub(super) checker_texture: Option<egui::TextureHandle>,
+    /// Texture sampling (Shift+Z), saved to the config on exit
+    pub(super) image_filter: crate::db::ImageFilter,
+    /// (egui texture, nearest-sampled view of it) for the 8-bit image being painted;
+    /// egui textures are uploaded with linear sampling
+    pub(super) nearest_view: Option<(egui::TextureId, egui::TextureId)>,
+    /// RAWs are shown via their embedded previews (--raw-thumbnails)
+    pub(super) raw_thumbnails: bool,
+    /// Shift+W white balance comparison for the current RAW file
+    pub(super) raw_wb: super::image::RawWbPreview,
+    /// Duplicate mode: counts and bulk-action buttons above the list (Ctrl+B)
+    pub(super) show_action_bar: bool,
+    /// (marks generation, listed groups, listed files) the reclaimable figure was
+    /// computed for, and the bytes
+    pub(super) action_bar_stats: Option<((u64, usize, usize), u64)>,
+    // Duplicate mode: marked files first in each group, so the keeper sits last
+    pub(super) keeper_last: bool,
+    // Marks the groups were last sorted for; None = sort again
+    pub(super) keeper_sorted_marks: Option<Vec<PathBuf>>,
     // Shared flag so worker threads skip histogram+palette when disabled
     pub(super) histogram_enabled: Arc<AtomicBool>,
     // EXIF info display
     pub(super) show_exif: bool,
+    /// Post-scan summary dialog (duplicate mode); shown once per session
+    pub(super) scan_summary: Option<ScanSummary>,
+    pub(super) scan_summary_shown: bool,
     // Cache for histogram and palette data, keyed by path (lifecycle matches raw_cache)
     pub(super) cached_histogram: HashMap<
         std::path::PathBuf,
@@ -170,6 +495,7 @@ pub struct GuiApp {
 
     // UI Virtualization State
     pub(super) group_y_offsets: Vec<f32>, // Cached Y position of every group
+    pub(super) collapsed_groups: HashSet<usize>, // Duplicate mode: groups showing only their header
     pub(super) total_content_height: f32, // Total scrollable height
     pub(super) cache_dirty: bool,         // Flag to rebuild offsets
     //
@@ -184,10 +510,57 @@ pub struct GuiApp {
     pub(super) fs_rem_files: HashSet<String>,
     pub(super) fs_rem_dirs: HashSet<String>,
     pub(super) last_fs_refresh: Instant,
+    /// Shift+L: the list keeps its order; FS refreshes and background re-sorts wait
+    pub(super) list_frozen: bool,
+    /// A location re-sort from enrichment was skipped while the list was frozen
+    pub(super) frozen_resort: bool,
+    /// File system events logged for the event panel (fs_notifications = "panel"),
+    /// as (local time, message), oldest first
+    pub(super) fs_events: VecDeque<(String, String)>,
+    pub(super) show_fs_events: bool,
     // View mode: Channel to receive enrichment results (content_hash, GPS, etc.)
     pub(super) enrichment_rx: Option<Receiver<EnrichmentResult>>,
+    pub(super) enrichment_progress_rx: Option<Receiver<(usize, usize)>>,
+    pub(super) enrichment_progress: (usize, usize),
+    // View mode: (unique_file_id, resolution) read from headers for files the cache had none for
+    pub(super) resolution_rx: Option<Receiver<(u128, (u32, u32))>>,
     // View mode: Maps unique_file_id -> file_idx within the single group
     pub(super) file_index: HashMap<u128, usize>,
+    // Shift+G / Shift+E filter: Some while only files showing that badge are listed
+    pub(super) badge_filter: Option<ListBadge>,
+    /// Shift+S: the current group narrowed to one content subgroup
+    pub(super) subgroup_filter: Option<SubgroupFilter>,
+    /// Files queued by `reenrich_selection`; their enrichment results overwrite the
+    /// listed GPS position and EXIF date even when the new value is missing
+    pub(super) reenrich_ids: HashSet<u128>,
+    /// Content hash -> stored rating of the listed files (1-5, or `db::REJECTED`)
+    pub(super) ratings: HashMap<[u8; 32], u8>,
+    /// Shift+X cull pass, running or showing its summary
+    pub(super) cull: Option<CullSession>,
+    /// Content hash -> badge facts from its cached features, filled in the background
+    /// (`request_cached_tags`) and from enrichment results
+    pub(super) cached_tags: HashMap<[u8; 32], CachedTags>,
+    /// Content hashes being read by the running `cached_tags` load
+    pub(super) cached_tags_pending: HashSet<[u8; 32]>,
+    pub(super) cached_tags_rx: Option<Receiver<([u8; 32], Option<CachedTags>)>>,
+    pub(super) group_filter: GroupFilter,
+    /// Listed file indices of each group when a list filter hides some (`state.hidden`),
+    /// rebuilt with `group_y_offsets`; None lists every file
+    pub(super) list_rows: Option<Vec<Vec<usize>>>,
+    /// Files in `state.groups` when the layout cache was last built
+    pub(super) layout_file_count: usize,
+    // Cull assistant: hashes of the --reference folder, streamed in the background
+    pub(super) reference: Option<crate::reference::ReferenceSet>,
+    pub(super) reference_rx: Option<Receiver<crate::reference::ReferenceUpdate>>,
+    // Pixel hashes of browsed files, computed on demand (unique_file_id, path)
+    pub(super) reference_pixel_tx: Option<Sender<(u128, PathBuf)>>,
+    pub(super) reference_pixel_rx: Option<Receiver<(u128, Option<[u8; 32]>)>>,
+    // View mode: near-duplicates within the browsed directory, hashed in the background
+    pub(super) similar: Option<crate::similar::SimilarIndex>,
+    pub(super) similar_rx: Option<Receiver<crate::similar::SimilarUpdate>>,
+    /// Installed font definitions, kept for laying out contact sheet labels
+    pub(super) label_fonts: egui::FontDefinitions,
+    pub(super) contact_sheet_rx: Option<Receiver<Result<PathBuf, String>>>,
     // View mode: Map of images that failed to load -> error message
     failed_images: HashMap<PathBuf, String>,
     // Animation state for animated images (e.g. animated WebP)
@@ -207,6 +580,10 @@ pub struct GuiApp {
     pub(super) dir_scan_rx: Option<Receiver<Vec<FileMetadata>>>,
     // Total file count from directory (for progress display)
     pub(super) dir_total_count: Option<usize>,
+    // Polling interval while background work is in flight (idle = event-driven only)
+    pub(super) repaint_poll: Duration,
+    // Handle for waking the UI from background threads (FS watcher). Set in run().
+    pub(super) egui_ctx: Option<egui::Context>,
 }
 
 impl GuiApp {
@@ -221,30 +598,407 @@ impl GuiApp {
         self.gps_map.optimize_path();
 
         // 2. Save current selection to restore it after sorting
-        let current_path = self.state.get_current_image_path().cloned();
+        let current_id = self.state.current_file().map(|f| f.unique_file_id);
 
-        // 3. Sort groups using the map's optimized path_to_marker indices
-        // Files without GPS data are pushed to the end (usize::MAX)
+        // 3. Sort groups by distance from the selected map location when one is chosen,
+        // otherwise along the map's optimized path. Files without GPS data go last.
+        let origin = self.gps_map.selected_location.as_ref().map(|(_, p)| (p.y(), p.x()));
         for group in &mut self.state.groups {
-            group.sort_by_key(|f| {
-                self.gps_map.path_to_marker.get(&f.path).copied().unwrap_or(usize::MAX)
-            });
+            match origin {
+                Some(origin) => group.sort_by_cached_key(|f| {
+                    self.gps_map.get_marker_by_path(&f.path).map_or(u64::MAX, |m| {
+                        position::distance(origin, (m.lat, m.lon)).round() as u64
+                    })
+                }),
+                None => group.sort_by_key(|f| {
+                    self.gps_map.path_to_marker.get(&f.path).copied().unwrap_or(usize::MAX)
+                }),
+            }
         }
 
         // 4. Restore selection index
-        if let Some(path) = current_path
-            && let Some(group) = self.state.groups.get(self.state.current_group_idx)
-            && let Some(new_idx) = group.iter().position(|f| f.path == path)
+        if let Some(id) = current_id {
+            self.state.select_file_id(id);
+        }
+
+        self.rebuild_file_index();
+        self.cache_dirty = true;
+        self.state.selection_changed = true;
+    }
+
+    /// Re-sort the loaded list in memory (no rescan), keeping the current file selected.
+    pub(super) fn apply_sort(&mut self, sort: String) {
+        // Update stored preference for future scans
+        self.view_mode_sort = Some(sort.clone());
+        if self.state.view_mode
+            && let Some(dir) = &self.current_dir
         {
-            self.state.current_file_idx = new_idx;
+            self.dir_sorts.insert(dir.clone(), sort.clone());
         }
 
+        if sort == "location" {
+            self.state.show_sort_selection = false;
+            self.apply_location_sort();
+            self.state.set_status("Sorted by: location".to_string(), false);
+        } else {
+            // Explicitly sort subdirectories (AppState only handles files)
+            scanner::sort_directories(&mut self.subdirs, &sort);
+            // Update GPS map sort mode based on sort order
+            self.gps_map.sort_by_exif_timestamp = sort == "exif-date" || sort == "exif-date-desc";
+            self.gps_map.markers_needs_sort = true; // Force re-sort with new mode
+            self.state.handle_input(InputIntent::ChangeSortOrder(sort));
+            self.rebuild_file_index();
+        }
+        self.cache_dirty = true;
+    }
+
+    /// Shift+G: show only the files without GPS coordinates (to geotag them), or bring
+    /// the geotagged ones back. The selection stays on the same file when it is still
+    /// listed.
+    pub(super) fn toggle_missing_gps_filter(&mut self) {
+        self.set_badge_filter(if self.badge_filter == Some(ListBadge::Gps) {
+            None
+        } else {
+            Some(ListBadge::Gps)
+        });
+    }
+
+    /// Shift+E: cycle the badge filter through no-date, no-GPS, no-camera and screenshot.
+    pub(super) fn cycle_badge_filter(&mut self) {
+        self.set_badge_filter(ListBadge::next(self.badge_filter));
+    }
+
+    /// List only files showing `tag` (None shows all files again).
+    fn set_badge_filter(&mut self, tag: Option<ListBadge>) {
+        self.badge_filter = tag;
+        self.refresh_list_filters();
+        let msg = match tag {
+            Some(tag) => {
+                let total: usize = self.state.groups.iter().map(|g| g.len()).sum();
+                format!(
+                    "Showing {} files with {} ({} hidden, Shift+E for the next badge)",
+                    total - self.state.hidden.len(),
+                    tag.label(),
+                    self.state.hidden.len()
+                )
+            }
+            None => "Showing all files".to_string(),
+        };
+        self.set_status(msg, false);
+    }
+
+    /// Recompute `state.hidden` from the badge, subgroup and group filters. The files
+    /// stay in `state.groups`: the list is laid out from the listed rows only and
+    /// navigation skips the rest, so deleting or ignoring while a filter is on can't
+    /// misplace anything. The selection moves off a file that got hidden.
+    pub(super) fn refresh_list_filters(&mut self) {
+        let mut hidden = HashSet::new();
+        if !self.state.view_mode && self.group_filter.is_active() {
+            for (group, info) in self.state.groups.iter().zip(&self.state.group_infos) {
+                if !self.group_filter.admits(group.len(), info) {
+                    hidden.extend(group.iter().map(|f| f.path.clone()));
+                }
+            }
+        }
+        if let Some(filter) = &self.subgroup_filter
+            && let Some(group) = self.state.groups.iter().find(|g| filter.lists_group(g))
+        {
+            hidden.extend(
+                group.iter().filter(|f| !filter.shown.contains(&f.path)).map(|f| f.path.clone()),
+            );
+        }
+        if let Some(tag) = self.badge_filter {
+            for f in self.state.groups.iter().flatten() {
+                if !shows_badge(&self.cached_tags, f, tag) {
+                    hidden.insert(f.path.clone());
+                }
+            }
+        }
+        self.state.hidden = hidden;
+        self.state.select_listed();
         self.cache_dirty = true;
         self.state.selection_changed = true;
     }
 
+    /// Start loading the badge facts of listed files that have a content hash but none
+    /// in `cached_tags` yet. One load runs at a time; the next layout rebuild picks up
+    /// what it didn't cover.
+    pub(super) fn request_cached_tags(&mut self) {
+        if self.cached_tags_rx.is_some() {
+            return;
+        }
+        let mut files = Vec::new();
+        for f in self.state.groups.iter().flatten() {
+            if f.content_hash != [0u8; 32]
+                && !self.cached_tags.contains_key(&f.content_hash)
+                && self.cached_tags_pending.insert(f.content_hash)
+            {
+                files.push(f.content_hash);
+            }
+        }
+        if files.is_empty() {
+            return;
+        }
+        let (tx, rx) = unbounded();
+        spawn_cached_tags_load(files, self.ctx.clone(), tx);
+        self.cached_tags_rx = Some(rx);
+    }
+
+    /// Shift+S (duplicate mode): list only content subgroup C1 of the current group,
+    /// then C2 and so on, then the whole group again. The selection moves to the
+    /// first file of the listed subgroup.
+    pub(super) fn cycle_subgroup_filter(&mut self) {
+        if self.state.view_mode {
+            return;
+        }
+        let g_idx = self.state.current_group_idx;
+        let Some(group) = self.state.groups.get(g_idx) else { return };
+        let ids = get_content_subgroups(group);
+        let count = ids.values().copied().max().unwrap_or(0);
+        let next = match &self.subgroup_filter {
+            Some(filter) if filter.lists_group(group) => filter.id + 1,
+            _ => 1,
+        };
+
+        let (filter, msg) = if count == 0 {
+            (None, format!("Group {} has no content subgroups", g_idx + 1))
+        } else if next > count {
+            (None, format!("Showing all {} files of group {}", group.len(), g_idx + 1))
+        } else {
+            let shown: HashSet<PathBuf> = group
+                .iter()
+                .filter(|f| f.subgroup_pixel_hash().and_then(|ph| ids.get(&ph)) == Some(&next))
+                .map(|f| f.path.clone())
+                .collect();
+            let msg = format!(
+                "Showing C{} of group {}: {} files ({} hidden, Shift+S for the next)",
+                next,
+                g_idx + 1,
+                shown.len(),
+                group.len() - shown.len()
+            );
+            (Some(SubgroupFilter { id: next, shown }), msg)
+        };
+        let narrowed = filter.is_some();
+        self.subgroup_filter = filter;
+        self.refresh_list_filters();
+        if narrowed && let Some(&first) = self.state.listed_indices(g_idx).first() {
+            self.state.current_group_idx = g_idx;
+            self.state.current_file_idx = first;
+        }
+        self.set_status(msg, false);
+    }
+
+    /// Ctrl+Shift+L (duplicate mode): hash the files of the current group again,
+    /// ignoring their cached hashes and features, and regroup just those files. Run
+    /// after editing files outside the app, when the group may no longer hold.
+    pub(super) fn start_group_rescan(&mut self) {
+        if self.state.view_mode || self.state.is_loading || self.group_rescan.is_some() {
+            return;
+        }
+        // The whole group is re-scanned, also the files a filter hides
+        let g_idx = self.state.current_group_idx;
+        let Some(group) = self.state.groups.get(g_idx) else { return };
+        let paths: Vec<PathBuf> = group.iter().map(|f| f.path.clone()).collect();
+        if paths.iter().any(|p| crate::archive::is_archive_entry(p)) {
+            self.set_status(
+                "Groups with archived files can't be re-scanned alone".to_string(),
+                true,
+            );
+            return;
+        }
+
+        let mut cfg = self.scan_config.clone();
+        cfg.paths = paths.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+        cfg.rehash = true;
+        cfg.dedupe_scan_roots = true;
+        cfg.scan_archives = false;
+        let ctx = self.ctx.clone();
+        let (tx, rx) = unbounded();
+        thread::spawn(move || {
+            let _ = tx.send(scanner::scan_and_group(&cfg, &ctx, None, None));
+        });
+        self.group_rescan = Some(GroupRescan { group_idx: g_idx, paths, rx });
+        self.set_status(format!("Re-scanning group {}…", g_idx + 1), false);
+    }
+
+    /// Put the result of a group re-scan in place of the group: files that still
+    /// match form one or more groups there, the others leave the list.
+    fn check_group_rescan(&mut self, ctx: &egui::Context) {
+        let Some(rescan) = &self.group_rescan else { return };
+        let Ok((mut new_groups, mut new_infos)) = rescan.rx.try_recv() else {
+            ctx.request_repaint_after(self.repaint_poll);
+            return;
+        };
+        let Some(rescan) = self.group_rescan.take() else { return };
+        let g_idx = rescan.group_idx;
+
+        // The list may have changed meanwhile (reload, deletions, filters)
+        let unchanged = self.state.groups.get(g_idx).is_some_and(|group| {
+            group.len() == rescan.paths.len()
+                && group.iter().zip(&rescan.paths).all(|(f, p)| f.path == *p)
+        });
+        if !unchanged || self.state.is_loading {
+            self.set_status("Group changed during the re-scan; result discarded".to_string(), true);
+            return;
+        }
+
+        for group in &mut new_groups {
+            group.sort_by(subgroup_order);
+        }
+        self.filter_ignored_groups(&mut new_groups, &mut new_infos);
+        let kept: HashSet<&PathBuf> = new_groups.iter().flatten().map(|f| &f.path).collect();
+        let dropped: Vec<PathBuf> =
+            rescan.paths.iter().filter(|p| !kept.contains(p)).cloned().collect();
+        self.state.marked_for_deletion.retain(|p| !dropped.contains(p));
+        self.state.marks_generation += 1;
+
+        let split = new_groups.len();
+        let files = rescan.paths.len();
+        self.state.groups.splice(g_idx..=g_idx, new_groups);
+        self.state.group_infos.splice(g_idx..=g_idx, new_infos);
+        self.keeper_sorted_marks = None;
+        self.collapsed_groups.clear();
+        self.group_review = None;
+        self.cached_tags.clear();
+        self.state.sync_protected(|ch| self.ctx.is_protected(ch));
+        self.sync_ratings();
+        self.state.index_live_siblings();
+        self.rebuild_file_index();
+        self.state.last_file_count = self.state.groups.iter().map(|g| g.len()).sum();
+
+        if self.state.groups.is_empty() {
+            self.state.current_group_idx = 0;
+        } else {
+            self.state.current_group_idx = g_idx.min(self.state.groups.len() - 1);
+        }
+        self.state.current_file_idx = 0;
+        // The new groups may fall outside the group filter
+        self.refresh_list_filters();
+        self.set_status(
+            format!(
+                "Re-scanned group {}: {} files, now {} group(s), {} dropped",
+                g_idx + 1,
+                files,
+                split,
+                dropped.len()
+            ),
+            false,
+        );
+        ctx.request_repaint();
+    }
+
+    /// Duplicate mode: re-apply `group_filter`, hiding the groups outside its range.
+    /// Marks are left alone; bulk actions only reach listed files. The selection stays
+    /// on the same file when it is still listed.
+    pub(super) fn apply_group_filter(&mut self) {
+        if self.state.view_mode {
+            return;
+        }
+        self.refresh_list_filters();
+        self.collapsed_groups.clear();
+        self.group_review = None;
+        let total = self.state.groups.len();
+        let listed = (0..total).filter(|&g| !self.state.listed_indices(g).is_empty()).count();
+        let msg = if listed == total {
+            format!("Showing all {} groups", total)
+        } else {
+            format!("Showing {} of {} groups", listed, total)
+        };
+        self.set_status(msg, false);
+    }
+
+    /// View mode: map unique_file_id -> index in the single group after the order changed
+    pub(super) fn rebuild_file_index(&mut self) {
+        if !self.state.view_mode {
+            return;
+        }
+        self.file_index = self
+            .state
+            .groups
+            .first()
+            .map(|g| g.iter().enumerate().map(|(idx, f)| (f.unique_file_id, idx)).collect())
+            .unwrap_or_default();
+    }
+
+    /// Camera of a listed file from `cached_tags`: Some(None) when its features record
+    /// none, None when they aren't loaded yet.
+    fn camera_name(&self, content_hash: &[u8; 32]) -> Option<Option<String>> {
+        self.cached_tags.get(content_hash).map(|t| t.camera.clone())
+    }
+
+    /// Look up the camera of every map marker that hasn't been checked yet, for
+    /// coloring markers by camera. Files not hashed yet, or whose features aren't
+    /// loaded, stay unchecked and are looked up again on a later frame.
+    fn resolve_marker_cameras(&mut self) {
+        if self.gps_map.markers.iter().all(|m| m.camera_checked) {
+            return;
+        }
+        let hashes: HashMap<&std::path::Path, [u8; 32]> = self
+            .state
+            .groups
+            .iter()
+            .flatten()
+            .map(|f| (f.path.as_path(), f.content_hash))
+            .collect();
+        let cameras: Vec<Option<Option<String>>> = self
+            .gps_map
+            .markers
+            .iter()
+            .map(|m| {
+                if m.camera_checked {
+                    return None;
+                }
+                let content_hash = hashes.get(m.path.as_path()).filter(|ch| **ch != [0u8; 32])?;
+                self.camera_name(content_hash)
+            })
+            .collect();
+        for (marker, camera) in self.gps_map.markers.iter_mut().zip(cameras) {
+            if let Some(camera) = camera {
+                marker.camera = camera;
+                marker.camera_checked = true;
+            }
+        }
+    }
+
+    /// `]` / `[`: select the next (or previous) file in the current group shot with a
+    /// different camera than the current one. Files without camera info count as one
+    /// "unknown" camera; files whose features aren't loaded yet are skipped.
+    pub(super) fn jump_to_other_camera(&mut self, forward: bool) {
+        let Some(group) = self.state.groups.get(self.state.current_group_idx) else {
+            return;
+        };
+        let start = self.state.current_file_idx;
+        let Some(current) = group.get(start) else {
+            return;
+        };
+        let Some(current_camera) = self.camera_name(&current.content_hash) else {
+            self.set_status("Camera info of this file isn't loaded yet".to_string(), false);
+            return;
+        };
+        let candidates: Box<dyn Iterator<Item = usize>> =
+            if forward { Box::new(start + 1..group.len()) } else { Box::new((0..start).rev()) };
+        let found = candidates
+            .filter(|&idx| self.state.is_listed(self.state.current_group_idx, idx))
+            .filter_map(|idx| Some((idx, self.camera_name(&group[idx].content_hash)?)))
+            .find(|(_, camera)| *camera != current_camera);
+        match found {
+            Some((idx, camera)) => {
+                self.state.current_file_idx = idx;
+                self.state.manual_rotation = 0;
+                self.state.selection_changed = true;
+                let name = camera.unwrap_or_else(|| "unknown".to_string());
+                self.set_status(format!("Camera: {}", name), false);
+            }
+            None => {
+                let dir = if forward { "after" } else { "before" };
+                self.set_status(format!("No other camera {} this file", dir), false);
+            }
+        }
+    }
+
     pub fn get_point(&self, name: &str) -> Option<Point<f64>> {
-        self.ctx.locations.get(name).cloned()
+        self.ctx.locations.read().ok()?.get(name).cloned()
     }
 
     pub fn build_search_index(&mut self) {
@@ -319,6 +1073,24 @@ impl GuiApp {
             ext_priorities,
         );
         state.is_loading = true;
+        state.staging_dir = ctx.gui_config.staging_dir.clone();
+        state.review_dir = ctx.gui_config.review_dir.clone();
+        state.nav_wrap = ctx.gui_config.nav_wrap.unwrap_or(false);
+        state.live_photo_exts = ctx.grouping_config.live_photo_exts.clone();
+        match crate::state::KeepRule::parse_all(&ctx.grouping_config.keep_rules) {
+            Ok(rules) => state.keep_rules = rules,
+            // keep_rules stays at the defaults from AppState::new
+            Err(e) => {
+                state.error_popup = Some(format!(
+                    "{} in [grouping] keep_rules.\nUsing the default rules.\n\n(Press any key to dismiss)",
+                    e
+                ))
+            }
+        }
+        state.scan_root = crate::state::common_scan_root(&scan_config.paths);
+        if !scan_config.dedupe_scan_roots {
+            state.scan_roots = crate::state::scan_root_dirs(&scan_config.paths);
+        }
 
         let active_window = Arc::new(RwLock::new(HashSet::new()));
 
@@ -335,34 +1107,51 @@ impl GuiApp {
             ctx.content_key,
             palette_config,
             hdr_config,
+            crate::db::DecodeConfig::from_gui_config(&ctx.gui_config),
             Arc::clone(&histogram_enabled),
             Arc::clone(&deep_caps),
         );
 
         // panel_width is saved in logical points (after font_scale applied)
         let panel_width = ctx.gui_config.panel_width.unwrap_or(450.0);
+        let viewer_background = ctx.gui_config.viewer_background.unwrap_or_default();
+        let image_filter = ctx.gui_config.image_filter.unwrap_or_default();
+        let show_action_bar = ctx.gui_config.action_bar.unwrap_or(true);
+        let keeper_last = ctx.gui_config.keeper_last.unwrap_or(false);
+        let group_filter = GroupFilter::from_config(&ctx.gui_config);
+        let gui_config = ctx.gui_config.clone();
+        let map_providers = ctx.map_providers.clone();
         // Initialize with configured size so we have a fallback if window size isn't captured
         let initial_window_size =
             Some((ctx.gui_config.width.unwrap_or(1280), ctx.gui_config.height.unwrap_or(720)));
+        let repaint_poll = Duration::from_millis(ctx.gui_config.repaint_poll_ms.unwrap_or(100));
 
-        eprintln!(
+        debug_log!(
             "[DEBUG-CONFIG] new() - config values: width={:?}, height={:?}, panel_width={:?}",
-            ctx.gui_config.width, ctx.gui_config.height, ctx.gui_config.panel_width
+            ctx.gui_config.width,
+            ctx.gui_config.height,
+            ctx.gui_config.panel_width
         );
 
         // Extract values before moving ctx to Arc
         let tile_cache_path = ctx.tile_cache_path.clone();
         let selected_provider = ctx.selected_provider.clone();
         let provider_url = ctx.map_providers.get(&selected_provider).cloned().unwrap_or_default();
+        let distance_format = DistanceFormat::from_gui_config(&ctx.gui_config);
 
         Self {
             state,
             group_views: HashMap::new(),
+            fit_capped: false,
             initial_scale_applied: false,
             initial_panel_width_applied: false,
             ctx: Arc::new(ctx),
+            gui_config,
+            map_providers,
             scan_config,
             scan_rx: None,
+            scan_preview_started: false,
+            group_rescan: None,
             scan_progress_rx: None,
             scan_progress: (0, 0),
             rename_input: String::new(),
@@ -371,10 +1160,13 @@ impl GuiApp {
             move_completion_candidates: Vec::new(),
             move_completion_index: 0,
             last_preload_pos: None,
+            preload_momentum: 0,
             slideshow_last_advance: None,
             view_mode_sort: None,
+            dir_sorts: HashMap::new(),
             view_mode_flatten: false,
             raw_cache: HashMap::new(),
+            placeholder_cache: HashMap::new(),
             raw_loading: HashSet::new(),
             scan_batch_rx: None,
             image_preload_tx: tx,
@@ -389,6 +1181,28 @@ impl GuiApp {
             last_row_height: 0.0,
             current_dir: None,
             show_dir_picker: false,
+            show_goto_input: false,
+            goto_input: String::new(),
+            goto_focus_requested: false,
+            goto_pending_file: None,
+            show_jump_input: false,
+            jump_input: String::new(),
+            jump_focus_requested: false,
+            show_time_shift: false,
+            time_shift_input: String::new(),
+            time_shift_focus_requested: false,
+            time_shift_preview: None,
+            pin_location: None,
+            pin_location_input: String::new(),
+            pin_location_focus_requested: false,
+            strip_gps: None,
+            write_gps: None,
+            fill_metadata: None,
+            group_review: None,
+            cache_panel: None,
+            web_export: None,
+            review_move: None,
+            reviewed_groups: HashSet::new(),
             dir_list: Vec::new(),
             dir_list_mtime: Vec::new(),
             dir_picker_selection: 0,
@@ -399,9 +1213,23 @@ impl GuiApp {
             completion_candidates: Vec::new(),
             completion_index: 0,
             histogram_mode: 0,
+            crop_guide: 0,
+            ghost_overlay: false,
+            viewer_background,
+            checker_texture: None,
+            image_filter,
+            nearest_view: None,
+            raw_thumbnails: use_raw_thumbnails,
+            raw_wb: Default::default(),
+            show_action_bar,
+            action_bar_stats: None,
+            keeper_last,
+            keeper_sorted_marks: None,
             histogram_channel: 0,
             histogram_enabled,
             show_exif: false,
+            scan_summary: None,
+            scan_summary_shown: false,
             cached_histogram: HashMap::new(),
             cached_exif: None,
             search_input: String::new(),
@@ -410,6 +1238,7 @@ impl GuiApp {
             move_focus_requested: false,
             exif_search_cache: HashMap::new(),
             group_y_offsets: Vec::new(),
+            collapsed_groups: HashSet::new(),
             total_content_height: 0.0,
             cache_dirty: true,
             watcher: None,
@@ -421,9 +1250,38 @@ impl GuiApp {
             fs_rem_files: HashSet::new(),
             fs_rem_dirs: HashSet::new(),
             last_fs_refresh: Instant::now(),
-            gps_map: GpsMapState::new(tile_cache_path, selected_provider, provider_url),
+            list_frozen: false,
+            frozen_resort: false,
+            fs_events: VecDeque::new(),
+            show_fs_events: false,
+            gps_map: GpsMapState {
+                distance_format,
+                ..GpsMapState::new(tile_cache_path, selected_provider, provider_url)
+            },
             enrichment_rx: None,
+            enrichment_progress_rx: None,
+            resolution_rx: None,
+            enrichment_progress: (0, 0),
             file_index: HashMap::new(),
+            badge_filter: None,
+            subgroup_filter: None,
+            reenrich_ids: HashSet::new(),
+            ratings: HashMap::new(),
+            cull: None,
+            cached_tags: HashMap::new(),
+            cached_tags_pending: HashSet::new(),
+            cached_tags_rx: None,
+            group_filter,
+            list_rows: None,
+            layout_file_count: 0,
+            reference: None,
+            reference_rx: None,
+            reference_pixel_tx: None,
+            reference_pixel_rx: None,
+            similar: None,
+            similar_rx: None,
+            label_fonts: egui::FontDefinitions::empty(),
+            contact_sheet_rx: None,
             failed_images: HashMap::new(),
             animation_cache: HashMap::new(),
             last_failed_images_len: 0,
@@ -435,6 +1293,8 @@ impl GuiApp {
             db_tx: None,
             dir_scan_rx: None,
             dir_total_count: None,
+            repaint_poll,
+            egui_ctx: None,
         }
     }
 
@@ -448,6 +1308,7 @@ impl GuiApp {
         slideshow_interval: Option<f32>,
         use_raw_thumbnails: bool,
         view_flatten: bool,
+        max_depth: Option<usize>,
     ) -> Self {
         let mut state = AppState::new(
             Vec::new(),
@@ -467,7 +1328,7 @@ impl GuiApp {
             .iter()
             .filter_map(|p| {
                 let path = std::path::Path::new(p);
-                path.canonicalize().ok().map(|c| c.to_string_lossy().to_string())
+                crate::scanner::resolve_path(path).ok().map(|c| c.to_string_lossy().to_string())
             })
             .collect();
 
@@ -488,11 +1349,31 @@ impl GuiApp {
             extensions: Vec::new(),
             ignore_same_stem: false,
             calc_pixel_hash: false,
+            calc_oriented_pixel_hash: false,
+            fast_resume: true,
+            name_dupes: None,
+            resolution_tolerance: None,
+            live_photo_exts: Vec::new(),
+            max_depth,
+            prefix_hash_bytes: None,
+            near_identical_mse: None,
+            dedupe_scan_roots: true,
+            similarity_by_ext: HashMap::new(),
+            cluster_split_dist: None,
+            screenshots: crate::db::ScreenshotGrouping::Mixed,
+            os_thumbnails: false,
+            scan_archives: false,
+            icc_profiles: false,
+            luma_variant_mse: None,
         };
 
         let active_window = Arc::new(RwLock::new(HashSet::new()));
 
         let ctx = crate::db::AppContext::new().expect("Failed to create context");
+        state.scan_root = crate::state::common_scan_root(&scan_config.paths);
+        state.staging_dir = ctx.gui_config.staging_dir.clone();
+        state.review_dir = ctx.gui_config.review_dir.clone();
+        state.nav_wrap = ctx.gui_config.nav_wrap.unwrap_or(false);
 
         // Initialize memory limits early, before any parallel image work
         scanner::init_smart_limits();
@@ -507,13 +1388,22 @@ impl GuiApp {
             ctx.content_key,
             palette_config,
             hdr_config,
+            crate::db::DecodeConfig::from_gui_config(&ctx.gui_config),
             Arc::clone(&histogram_enabled),
             Arc::clone(&deep_caps),
         );
 
         let panel_width = ctx.gui_config.panel_width.unwrap_or(450.0);
+        let viewer_background = ctx.gui_config.viewer_background.unwrap_or_default();
+        let image_filter = ctx.gui_config.image_filter.unwrap_or_default();
+        let show_action_bar = ctx.gui_config.action_bar.unwrap_or(true);
+        let keeper_last = ctx.gui_config.keeper_last.unwrap_or(false);
+        let group_filter = GroupFilter::from_config(&ctx.gui_config);
+        let gui_config = ctx.gui_config.clone();
+        let map_providers = ctx.map_providers.clone();
         let initial_window_size =
             Some((ctx.gui_config.width.unwrap_or(1280), ctx.gui_config.height.unwrap_or(720)));
+        let repaint_poll = Duration::from_millis(ctx.gui_config.repaint_poll_ms.unwrap_or(100));
 
         // Start database writer for view mode
         let (db_tx_send, db_rx) = unbounded::<crate::db::DbUpdate>();
@@ -533,13 +1423,19 @@ impl GuiApp {
                 &ctx,
                 batch_tx,
                 Some(progress_tx),
+                max_depth,
             );
             // In flatten mode, subdirs is empty and directory navigation is disabled
             (Vec::new(), Some(count), Some(batch_rx), Some(progress_rx))
         } else if let Some(ref dir) = current_dir {
             let (batch_tx, batch_rx) = unbounded::<Vec<FileMetadata>>();
-            let (subdirs, count) =
-                scanner::spawn_background_dir_scan(dir.clone(), sort_order.clone(), &ctx, batch_tx);
+            let (subdirs, count) = scanner::spawn_background_dir_scan(
+                dir.clone(),
+                sort_order.clone(),
+                &ctx,
+                batch_tx,
+                ctx.gui_config.dir_scan_exif.unwrap_or(false),
+            );
             (subdirs, Some(count), Some(batch_rx), None)
         } else {
             (Vec::new(), None, None, None)
@@ -574,15 +1470,21 @@ impl GuiApp {
         let mut gps_map = GpsMapState::new(tile_cache_path, selected_provider, provider_url);
         gps_map.sort_by_exif_timestamp =
             sort_order == "exif-date" || sort_order == "exif-date-desc";
+        gps_map.distance_format = DistanceFormat::from_gui_config(&ctx.gui_config);
 
         Self {
             state,
             group_views: HashMap::new(),
+            fit_capped: false,
             initial_scale_applied: false,
             initial_panel_width_applied: false,
             ctx: Arc::new(ctx),
+            gui_config,
+            map_providers,
             scan_config,
             scan_rx: None,
+            scan_preview_started: false,
+            group_rescan: None,
             scan_progress_rx,
             scan_progress: (0, 0),
             rename_input: String::new(),
@@ -591,10 +1493,13 @@ impl GuiApp {
             move_completion_candidates: Vec::new(),
             move_completion_index: 0,
             last_preload_pos: None,
+            preload_momentum: 0,
             slideshow_last_advance: None,
             view_mode_sort: Some(sort_order),
+            dir_sorts: HashMap::new(),
             view_mode_flatten: view_flatten,
             raw_cache: HashMap::new(),
+            placeholder_cache: HashMap::new(),
             raw_loading: HashSet::new(),
             scan_batch_rx: None,
             image_preload_tx: tx,
@@ -609,6 +1514,28 @@ impl GuiApp {
             last_row_height: 0.0,
             current_dir,
             show_dir_picker: false,
+            show_goto_input: false,
+            goto_input: String::new(),
+            goto_focus_requested: false,
+            goto_pending_file: None,
+            show_jump_input: false,
+            jump_input: String::new(),
+            jump_focus_requested: false,
+            show_time_shift: false,
+            time_shift_input: String::new(),
+            time_shift_focus_requested: false,
+            time_shift_preview: None,
+            pin_location: None,
+            pin_location_input: String::new(),
+            pin_location_focus_requested: false,
+            strip_gps: None,
+            write_gps: None,
+            fill_metadata: None,
+            group_review: None,
+            cache_panel: None,
+            web_export: None,
+            review_move: None,
+            reviewed_groups: HashSet::new(),
             dir_list: Vec::new(),
             dir_list_mtime: Vec::new(),
             dir_picker_selection: 0,
@@ -619,9 +1546,23 @@ impl GuiApp {
             completion_candidates: Vec::new(),
             completion_index: 0,
             histogram_mode: 0,
+            crop_guide: 0,
+            ghost_overlay: false,
+            viewer_background,
+            checker_texture: None,
+            image_filter,
+            nearest_view: None,
+            raw_thumbnails: use_raw_thumbnails,
+            raw_wb: Default::default(),
+            show_action_bar,
+            action_bar_stats: None,
+            keeper_last,
+            keeper_sorted_marks: None,
             histogram_channel: 0,
             histogram_enabled,
             show_exif: false,
+            scan_summary: None,
+            scan_summary_shown: false,
             cached_histogram: HashMap::new(),
             cached_exif: None,
             search_input: String::new(),
@@ -630,6 +1571,7 @@ impl GuiApp {
             move_focus_requested: false,
             exif_search_cache: HashMap::new(),
             group_y_offsets: Vec::new(),
+            collapsed_groups: HashSet::new(),
             total_content_height: 0.0,
             cache_dirty: true,
             watcher: None,
@@ -641,9 +1583,35 @@ impl GuiApp {
             fs_rem_files: HashSet::new(),
             fs_rem_dirs: HashSet::new(),
             last_fs_refresh: Instant::now(),
+            list_frozen: false,
+            frozen_resort: false,
+            fs_events: VecDeque::new(),
+            show_fs_events: false,
             gps_map,
             enrichment_rx: None,
+            enrichment_progress_rx: None,
+            resolution_rx: None,
+            enrichment_progress: (0, 0),
             file_index: HashMap::new(),
+            badge_filter: None,
+            subgroup_filter: None,
+            reenrich_ids: HashSet::new(),
+            ratings: HashMap::new(),
+            cull: None,
+            cached_tags: HashMap::new(),
+            cached_tags_pending: HashSet::new(),
+            cached_tags_rx: None,
+            group_filter,
+            list_rows: None,
+            layout_file_count: 0,
+            reference: None,
+            reference_rx: None,
+            reference_pixel_tx: None,
+            reference_pixel_rx: None,
+            similar: None,
+            similar_rx: None,
+            label_fonts: egui::FontDefinitions::empty(),
+            contact_sheet_rx: None,
             failed_images: HashMap::new(),
             animation_cache: HashMap::new(),
             last_failed_images_len: 0,
@@ -655,6 +1623,8 @@ impl GuiApp {
             db_tx,
             dir_scan_rx,
             dir_total_count,
+            repaint_poll,
+            egui_ctx: None,
         }
     }
 
@@ -663,11 +1633,565 @@ impl GuiApp {
         self
     }
 
+    /// Badge files that already exist (bit- or pixel-identical) in `dir`.
+    pub fn with_reference_dir(mut self, dir: Option<std::path::PathBuf>) -> Self {
+        let Some(dir) = dir else { return self };
+        let root = dir.canonicalize().unwrap_or(dir);
+        let (tx, rx) = unbounded();
+        crate::reference::spawn_reference_scan(root.clone(), Arc::clone(&self.ctx), tx);
+        let (pixel_tx, pixel_rx) = crate::reference::spawn_pixel_hasher(Arc::clone(&self.ctx));
+        self.reference = Some(crate::reference::ReferenceSet::new(root));
+        self.reference_rx = Some(rx);
+        self.reference_pixel_tx = Some(pixel_tx);
+        self.reference_pixel_rx = Some(pixel_rx);
+        self
+    }
+
+    /// Drain reference scan results and request the current file's pixel hash
+    /// when the reference has pixel hashes and the content hash didn't match.
+    fn update_reference(&mut self, ctx: &egui::Context) {
+        let Some(reference) = self.reference.as_mut() else { return };
+
+        if let Some(rx) = &self.reference_rx {
+            let mut got_any = false;
+            while let Ok(update) = rx.try_recv() {
+                reference.apply(update);
+                got_any = true;
+            }
+            if got_any {
+                ctx.request_repaint();
+            }
+            if reference.done {
+                self.reference_rx = None;
+            }
+        }
+
+        if let Some(rx) = &self.reference_pixel_rx {
+            while let Ok((unique_file_id, pixel_hash)) = rx.try_recv() {
+                reference.browsed_pixel.insert(unique_file_id, pixel_hash);
+                reference.pixel_pending = reference.pixel_pending.saturating_sub(1);
+                ctx.request_repaint();
+            }
+        }
+
+        let Some(file) = self
+            .state
+            .groups
+            .get(self.state.current_group_idx)
+            .and_then(|g| g.get(self.state.current_file_idx))
+        else {
+            return;
+        };
+        if !reference.pixel.is_empty()
+            && file.content_hash != [0u8; 32]
+            && !reference.content.contains(&file.content_hash)
+            && !reference.browsed_pixel.contains_key(&file.unique_file_id)
+            && let Some(tx) = &self.reference_pixel_tx
+            && tx.send((file.unique_file_id, file.path.clone())).is_ok()
+        {
+            // Pending marker so the request is sent once; the worker's answer replaces it
+            reference.browsed_pixel.insert(file.unique_file_id, None);
+            reference.pixel_pending += 1;
+        }
+    }
+
+    /// View mode: hash the listed directory in the background for the "similar" badge.
+    fn start_similar_scan(&mut self) {
+        let Some(threshold) = self.gui_config.similar_threshold.filter(|&t| t > 0) else {
+            return;
+        };
+        let Some(group) = self.state.groups.first() else { return };
+        if group.len() < 2 {
+            return;
+        }
+        let files: Vec<(PathBuf, u128)> =
+            group.iter().map(|f| (f.path.clone(), f.unique_file_id)).collect();
+        let (tx, rx) = unbounded();
+        crate::similar::spawn_similar_scan(files, Arc::clone(&self.ctx), threshold, tx);
+        self.similar = Some(crate::similar::SimilarIndex::default());
+        self.similar_rx = Some(rx);
+    }
+
+    fn update_similar(&mut self, ctx: &egui::Context) {
+        let (Some(similar), Some(rx)) = (self.similar.as_mut(), &self.similar_rx) else {
+            return;
+        };
+        let mut got_any = false;
+        while let Ok(update) = rx.try_recv() {
+            similar.apply(update);
+            got_any = true;
+        }
+        if got_any {
+            ctx.request_repaint();
+        }
+        if similar.done {
+            self.similar_rx = None;
+        }
+    }
+
+    /// The current file's nearest similar image in the directory: (file index, distance)
+    fn nearest_similar(&self) -> Option<(usize, u32)> {
+        let similar = self.similar.as_ref()?;
+        let file = self.state.current_file()?;
+        let &(other, dist) = similar.nearest.get(&file.unique_file_id)?;
+        self.file_index.get(&other).map(|&idx| (idx, dist))
+    }
+
+    /// View mode J: jump to the nearest similar image in the directory.
+    pub(super) fn jump_to_similar(&mut self) {
+        let Some(similar) = &self.similar else {
+            self.set_status("No similarity pass for this directory".to_string(), false);
+            return;
+        };
+        let progress = (similar.loaded, similar.total, similar.done);
+        match self.nearest_similar() {
+            Some((idx, dist)) => {
+                self.state.current_file_idx = idx;
+                self.state.manual_rotation = 0;
+                self.state.selection_changed = true;
+                self.set_status(format!("Similar image (distance {})", dist), false);
+            }
+            None if !progress.2 => self.set_status(
+                format!("No similar image found yet ({}/{} hashed)", progress.0, progress.1),
+                false,
+            ),
+            None => self.set_status("No similar image in this directory".to_string(), false),
+        }
+    }
+
+    /// `:` input: select the `number`-th file counted across all groups (1-based,
+    /// as in the `[n/total]` status). Out-of-range numbers are clamped.
+    pub(super) fn jump_to_file_number(&mut self, number: usize) {
+        let (_, total) = self.state.file_position();
+        if total == 0 {
+            self.set_status("No files to jump to".to_string(), true);
+            return;
+        }
+        let clamped = number.clamp(1, total);
+        let Some((group_idx, file_idx)) = self.state.locate_file_number(clamped) else {
+            return;
+        };
+        self.state.current_group_idx = group_idx;
+        self.state.current_file_idx = file_idx;
+        self.state.manual_rotation = 0;
+        self.state.selection_changed = true;
+        if clamped != number {
+            self.set_status(
+                format!("{} is out of range (1-{}), went to {}", number, total, clamped),
+                true,
+            );
+        }
+    }
+
+    /// The marked files in list order, or the files of the current group when none
+    /// are marked: what the contact sheet and the web export work on.
+    pub(super) fn marked_or_group_files(&self) -> Vec<PathBuf> {
+        if self.state.marked_for_deletion.is_empty() {
+            self.state
+                .groups
+                .get(self.state.current_group_idx)
+                .map(|g| g.iter().map(|f| f.path.clone()).collect())
+                .unwrap_or_default()
+        } else {
+            // List order rather than the order they were marked in
+            let marked: HashSet<&PathBuf> = self.state.marked_for_deletion.iter().collect();
+            self.state
+                .groups
+                .iter()
+                .flatten()
+                .filter(|f| marked.contains(&f.path))
+                .map(|f| f.path.clone())
+                .collect()
+        }
+    }
+
+    /// Ctrl+E: write the marked files (or the current group) as a contact sheet
+    /// into `contact_sheet_dir` (default the Pictures folder), in the background.
+    pub(super) fn start_contact_sheet(&mut self) {
+        if self.contact_sheet_rx.is_some() {
+            self.set_status("A contact sheet is already being written".to_string(), false);
+            return;
+        }
+        let files = self.marked_or_group_files();
+        if files.is_empty() {
+            self.set_status("Nothing to put on a contact sheet".to_string(), false);
+            return;
+        }
+
+        let cfg = &self.gui_config;
+        let cols = cfg.contact_sheet_cols.unwrap_or(6);
+        let cell = cfg.contact_sheet_cell.unwrap_or(256).clamp(32, 2048);
+        let ext = cfg.contact_sheet_format.as_deref().unwrap_or("png").to_lowercase();
+        let Some(format) = image::ImageFormat::from_extension(&ext)
+            .filter(|f| matches!(f, image::ImageFormat::Png | image::ImageFormat::Jpeg))
+        else {
+            self.set_status(format!("contact_sheet_format must be png or jpg, not {}", ext), true);
+            return;
+        };
+        // Too large for JPEG: write a PNG rather than fail after decoding everything
+        let (width, height) = super::contact_sheet::sheet_size(files.len(), cols, cell);
+        let too_big = format == image::ImageFormat::Jpeg
+            && width.max(height) > super::contact_sheet::JPEG_MAX_SIDE;
+        let (format, ext) =
+            if too_big { (image::ImageFormat::Png, "png".to_string()) } else { (format, ext) };
+        let dir = match &cfg.contact_sheet_dir {
+            Some(dir) => dir.clone(),
+            None => {
+                dirs::picture_dir().or_else(|| std::env::current_dir().ok()).unwrap_or_default()
+            }
+        };
+        let dir = std::path::absolute(&dir).unwrap_or(dir);
+        let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let out = dir.join(format!("contact_sheet_{}.{}", stamp, ext));
+        let fonts = self.label_fonts.clone();
+        let count = files.len();
+
+        let (tx, rx) = unbounded();
+        thread::spawn(move || {
+            let result = fs::create_dir_all(&dir)
+                .and_then(|()| fs::File::create(&out))
+                .map_err(|e| format!("{}: {}", out.display(), e))
+                .and_then(|file| {
+                    let mut writer = std::io::BufWriter::new(file);
+                    super::contact_sheet::export_contact_sheet(
+                        &files,
+                        cols,
+                        cell,
+                        fonts,
+                        format,
+                        &mut writer,
+                    )
+                });
+            let result = match result {
+                Ok(()) => Ok(out),
+                Err(e) => {
+                    let _ = fs::remove_file(&out);
+                    Err(e)
+                }
+            };
+            let _ = tx.send(result);
+        });
+        self.contact_sheet_rx = Some(rx);
+        let note = if too_big { " (PNG: too large for JPEG)" } else { "" };
+        self.set_status(format!("Writing contact sheet of {} files{}...", count, note), false);
+    }
+
+    fn poll_contact_sheet(&mut self) {
+        let Some(result) = self.contact_sheet_rx.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return;
+        };
+        self.contact_sheet_rx = None;
+        match result {
+            Ok(path) => self.set_status(format!("Contact sheet: {}", path.display()), false),
+            Err(e) => self.set_status(format!("Contact sheet failed: {}", e), true),
+        }
+    }
+
+    /// Duplicate mode bar: totals, reclaimable bytes of the marked files, and bulk actions.
+    fn render_action_bar(&mut self, ui: &mut egui::Ui) {
+        // Counts cover the listed files only, like the bulk actions
+        let (groups, files) = match &self.list_rows {
+            Some(rows) => {
+                (rows.iter().filter(|r| !r.is_empty()).count(), rows.iter().map(Vec::len).sum())
+            }
+            None => (self.state.groups.len(), self.state.groups.iter().map(|g| g.len()).sum()),
+        };
+        let marked = self.state.listed_marks().len();
+        let key = (self.state.marks_generation, groups, files);
+        let reclaimable = match self.action_bar_stats {
+            Some((k, bytes)) if k == key => bytes,
+            _ => {
+                let bytes = self.state.marked_reclaimable_bytes();
+                self.action_bar_stats = Some((key, bytes));
+                bytes
+            }
+        };
+
+        let mut mark_all = false;
+        let mut delete = false;
+        let mut clear = false;
+        let mut restore_review = false;
+        let mut filter = self.group_filter;
+        egui::Panel::top("action_bar").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} groups, {} files, {} marked, {} reclaimable",
+                    groups,
+                    files,
+                    marked,
+                    crate::format_size(reclaimable)
+                ));
+                ui.separator();
+                mark_all = ui.button("Mark all but keeper in all groups").clicked();
+                delete = ui.add_enabled(marked > 0, egui::Button::new("Delete marked")).clicked();
+                clear = ui.add_enabled(marked > 0, egui::Button::new("Clear marks")).clicked();
+                if self.state.review_dir.is_some() {
+                    ui.separator();
+                    if ui
+                        .add_enabled(marked > 0, egui::Button::new("Move marked to review"))
+                        .on_hover_text("Move the marked files into review_dir (Shift+D)")
+                        .clicked()
+                    {
+                        self.review_move = Some(ReviewMove::Marked);
+                    }
+                    restore_review = ui
+                        .button("Restore review")
+                        .on_hover_text("Move everything in review_dir back where it came from")
+                        .clicked();
+                }
+                ui.separator();
+                if ui
+                    .checkbox(&mut self.keeper_last, "Keeper last")
+                    .on_hover_text("Sort marked files to the top of each group")
+                    .changed()
+                {
+                    self.keeper_sorted_marks = None;
+                }
+                ui.separator();
+                ui.label("Show groups of");
+                ui.add(egui::DragValue::new(&mut filter.min_size).range(2..=99_999));
+                ui.label("to");
+                ui.add(egui::DragValue::new(&mut filter.max_size).range(0..=99_999))
+                    .on_hover_text("Largest group shown, 0 = no limit");
+                ui.label("files");
+                let mut dist_on = filter.max_dist.is_some();
+                ui.checkbox(&mut dist_on, "dist ≤")
+                    .on_hover_text("Only groups whose largest distance is at most this");
+                let mut dist = filter.max_dist.unwrap_or(self.scan_config.similarity);
+                ui.add_enabled(dist_on, egui::DragValue::new(&mut dist).range(0..=255));
+                filter.max_dist = dist_on.then_some(dist);
+            });
+        });
+
+        if mark_all {
+            let added = self.state.mark_all_but_keepers();
+            self.set_status(format!("Marked {} files, keeping one per group", added), false);
+        }
+        if delete {
+            self.state.handle_input(InputIntent::ExecuteDelete);
+        }
+        if clear {
+            self.state.marked_for_deletion.clear();
+            self.state.marks_generation += 1;
+            self.set_status("Cleared all marks".to_string(), false);
+        }
+        if restore_review && let Some(dir) = &self.state.review_dir {
+            match crate::fileops::staged_count(dir) {
+                Ok(n) if n > 0 => self.review_move = Some(ReviewMove::Restore(n)),
+                _ => self.set_status("Nothing to restore in review_dir".to_string(), false),
+            }
+        }
+        if filter != self.group_filter {
+            self.group_filter = filter;
+            self.apply_group_filter();
+        }
+    }
+
+    /// Duplicate mode with "Keeper last": move marked files to the top of each group and
+    /// the unmarked keeper(s) to the bottom, redone whenever the marks change. Turning it
+    /// off restores the subgroup order. The selection stays on the same file.
+    fn apply_keeper_last_sort(&mut self) {
+        if self.state.view_mode
+            || (!self.keeper_last && self.keeper_sorted_marks.is_none())
+            || self.keeper_sorted_marks.as_ref() == Some(&self.state.marked_for_deletion)
+        {
+            return;
+        }
+        let current = self.state.get_current_image_path().cloned();
+        if self.keeper_last {
+            let marked: HashSet<&PathBuf> = self.state.marked_for_deletion.iter().collect();
+            for group in &mut self.state.groups {
+                group.sort_by(|a, b| {
+                    marked
+                        .contains(&b.path)
+                        .cmp(&marked.contains(&a.path))
+                        .then_with(|| subgroup_order(a, b))
+                });
+            }
+            self.keeper_sorted_marks = Some(self.state.marked_for_deletion.clone());
+        } else {
+            for group in &mut self.state.groups {
+                group.sort_by(subgroup_order);
+            }
+            self.keeper_sorted_marks = None;
+        }
+        if let Some(path) = current
+            && let Some(idx) = self
+                .state
+                .groups
+                .get(self.state.current_group_idx)
+                .and_then(|g| g.iter().position(|f| f.path == path))
+        {
+            self.state.current_file_idx = idx;
+        }
+        // Rows moved: rebuild the layout cache
+        self.cache_dirty = true;
+    }
+
     /// Set status message with automatic 5-second timeout
     pub(super) fn set_status(&mut self, msg: String, is_error: bool) {
         self.state.set_status(msg, is_error);
     }
 
+    /// Copy the current group's paths to the clipboard, one per line; with
+    /// `details` each line also gets the size in bytes and the resolution.
+    pub(super) fn copy_group_paths(&mut self, ctx: &egui::Context, details: bool) {
+        let oriented = self.gui_config.oriented_resolution.unwrap_or(true);
+        let g_idx = self.state.current_group_idx;
+        let text = self.state.group_paths_text(g_idx, details, oriented);
+        let count = text.lines().count();
+        if count == 0 {
+            return;
+        }
+        ctx.copy_text(text);
+        self.set_status(format!("Copied {} paths of group {}", count, g_idx + 1), false);
+    }
+
+    /// Double-click on a file row (the click before it already selected the file).
+    fn run_double_click_action(&mut self) {
+        use crate::db::DoubleClickAction;
+        match self.gui_config.double_click_action.unwrap_or_default() {
+            DoubleClickAction::None => {}
+            DoubleClickAction::Fullscreen => {
+                self.state.handle_input(InputIntent::ToggleFullscreen);
+            }
+            DoubleClickAction::Editor => self.open_in_external_editor(),
+        }
+    }
+
+    /// Launch `external_editor` on the current file, detached.
+    fn open_in_external_editor(&mut self) {
+        let command = self.gui_config.external_editor.clone().unwrap_or_default();
+        if command.trim().is_empty() {
+            self.set_status("Set external_editor under [gui] to open files".to_string(), true);
+            return;
+        }
+        self.open_current_with("editor", &command);
+    }
+
+    /// "Open with" context menu entry `idx` on the current file.
+    fn run_open_with(&mut self, idx: usize) {
+        if let Some(entry) = self.gui_config.open_with.get(idx).cloned() {
+            self.open_current_with(&entry.name, &entry.command);
+        }
+    }
+
+    /// Spawn `command` (see `OpenWithEntry`) on the current file without waiting for it;
+    /// a failure to start is reported in the status line under `name`.
+    fn open_current_with(&mut self, name: &str, command: &str) {
+        let Some(path) = self.state.get_current_image_path().cloned() else {
+            return;
+        };
+        let path_str = path.to_string_lossy();
+        let mut words = command.split_whitespace();
+        let Some(program) = words.next() else {
+            self.set_status(format!("{}: empty command", name), true);
+            return;
+        };
+        let mut args: Vec<String> = words.map(|w| w.replace("{path}", &path_str)).collect();
+        if !command.contains("{path}") {
+            args.push(path_str.into_owned());
+        }
+        match std::process::Command::new(program).args(&args).spawn() {
+            Ok(mut child) => {
+                // Reap the tool when it exits so it doesn't linger as a zombie
+                thread::spawn(move || child.wait());
+                self.set_status(
+                    format!(
+                        "Opened {} with {}",
+                        path.file_name().unwrap_or_default().to_string_lossy(),
+                        name
+                    ),
+                    false,
+                );
+            }
+            Err(e) => self.set_status(format!("{}: failed to run {}: {}", name, program, e), true),
+        }
+    }
+
+    /// Copy the paths of all marked files (across groups) to the clipboard.
+    pub(super) fn copy_marked_paths(&mut self, ctx: &egui::Context) {
+        let text = self.state.marked_paths_text();
+        let count = text.lines().count();
+        if count == 0 {
+            self.set_status("No marked files to copy".to_string(), true);
+            return;
+        }
+        ctx.copy_text(text);
+        self.set_status(format!("Copied {} marked paths", count), false);
+    }
+
+    /// Load the stored ratings of the listed files (see `cull`).
+    fn sync_ratings(&mut self) {
+        self.ratings = self
+            .state
+            .groups
+            .iter()
+            .flatten()
+            .filter(|f| f.content_hash != [0u8; 32])
+            .filter_map(|f| self.ctx.get_rating(&f.content_hash).map(|r| (f.content_hash, r)))
+            .collect();
+    }
+
+    /// Shift+R: re-read the metadata of the marked files (or the current file) after
+    /// an external edit. Their metadata-cache entries are dropped and the files go
+    /// through background enrichment again; results arrive on `enrichment_rx` and
+    /// replace GPS position, EXIF date and map marker, also when they were removed.
+    pub(super) fn reenrich_selection(&mut self) {
+        if self.enrichment_rx.is_some() {
+            self.set_status(
+                "Metadata enrichment still running; try again when it finishes".to_string(),
+                true,
+            );
+            return;
+        }
+        let selection: HashSet<PathBuf> = if self.state.marked_for_deletion.is_empty() {
+            self.state.get_current_image_path().cloned().into_iter().collect()
+        } else {
+            self.state.marked_for_deletion.iter().cloned().collect()
+        };
+        let files: Vec<_> = self
+            .state
+            .groups
+            .iter()
+            .flatten()
+            .filter(|f| selection.contains(&f.path))
+            .map(|f| (f.path.clone(), f.unique_file_id, f.resolution, f.orientation))
+            .collect();
+        if files.is_empty() {
+            self.set_status("No file selected to re-read".to_string(), true);
+            return;
+        }
+
+        let meta_keys: Vec<[u8; 32]> = files
+            .iter()
+            .filter_map(|(path, uid, _, _)| {
+                let md = fs::metadata(path).ok()?;
+                Some(crate::db::compute_meta_key_from_metadata(&self.ctx.meta_key, &md, *uid))
+            })
+            .collect();
+        if let Err(e) = self.ctx.remove_meta_entries(&meta_keys) {
+            eprintln!("[REENRICH] Failed to drop cache entries: {}", e);
+        }
+
+        self.reenrich_ids = files.iter().map(|(_, uid, _, _)| *uid).collect();
+        let count = files.len();
+        let (result_tx, result_rx) = unbounded::<EnrichmentResult>();
+        let (progress_tx, progress_rx) = unbounded::<(usize, usize)>();
+        self.enrichment_progress = (0, count);
+        scanner::spawn_background_enrichment(
+            files,
+            self.ctx.content_key,
+            self.ctx.meta_key,
+            self.db_tx.clone(),
+            result_tx,
+            Some(progress_tx),
+        );
+        self.enrichment_rx = Some(result_rx);
+        self.enrichment_progress_rx = Some(progress_rx);
+        self.set_status(format!("Re-reading metadata of {} files", count), false);
+    }
+
     #[inline]
     fn enqueue_image_load(&mut self, path: &std::path::Path, g_idx: usize, f_idx: usize) {
         if self.failed_images.contains_key(path) {
@@ -678,7 +2202,7 @@ impl GuiApp {
         {
             return;
         }
-        eprintln!("[DEBUG] enqueue_image_load sending to preload: {:?}", path);
+        debug_log!("[DEBUG] enqueue_image_load sending to preload: {:?}", path);
         let _ = self.image_preload_tx.send((path.to_path_buf(), g_idx, f_idx));
     }
 
@@ -709,7 +2233,7 @@ impl GuiApp {
             let before = group.len();
             group.retain(|f| {
                 if f.content_hash != [0u8; 32] && self.ctx.is_ignored(&f.content_hash) {
-                    eprintln!(
+                    debug_log!(
                         "[DEBUG-IGNORE] Filtering ignored file: {:?}",
                         f.path.file_name().unwrap_or_default()
                     );
@@ -733,7 +2257,7 @@ impl GuiApp {
                     i += 1;
                 }
             }
-            eprintln!(
+            debug_log!(
                 "[DEBUG-IGNORE] Filtered {} ignored files, {} groups remain",
                 total_removed,
                 groups.len()
@@ -817,8 +2341,8 @@ impl GuiApp {
         };
 
         // Format the result
-        let dist_str = super::gps_map::format_distance(distance);
-        let bearing_str = super::gps_map::format_bearing(bearing);
+        let dist_str = self.gps_map.distance_format.distance(distance);
+        let bearing_str = self.gps_map.distance_format.bearing(bearing);
         let direction_str = if self.gps_map.direction_to_image {
             format!("{} to image", loc_name)
         } else {
@@ -867,7 +2391,15 @@ impl GuiApp {
         let Some(dir) = &self.current_dir else { return };
 
         let (tx, rx) = channel();
-        match notify::recommended_watcher(tx) {
+        // Wake the UI on each event so FS changes are noticed while idle.
+        let egui_ctx = self.egui_ctx.clone();
+        let handler = move |res: NotifyResult<Event>| {
+            let _ = tx.send(res);
+            if let Some(ctx) = &egui_ctx {
+                ctx.request_repaint();
+            }
+        };
+        match notify::recommended_watcher(handler) {
             Ok(mut watcher) => {
                 if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
                     eprintln!("Notify watch error: {:?}", e);
@@ -879,16 +2411,32 @@ impl GuiApp {
         }
     }
 
+    /// Shift+Z: next sampling mode. The sampler is picked per frame in
+    /// `render_image_texture`, so nothing is decoded or uploaded again.
+    pub(super) fn cycle_image_filter(&mut self) {
+        self.image_filter = self.image_filter.next();
+        self.set_status(format!("Image filter: {}", self.image_filter.name()), false);
+    }
+
     // Setup watcher and populate cache
     pub(super) fn change_directory(&mut self, new_dir: std::path::PathBuf) {
         if !self.state.view_mode {
             return;
         }
 
-        if let Ok(canonical) = new_dir.canonicalize() {
+        if let Ok(canonical) = crate::scanner::resolve_path(&new_dir) {
             self.current_dir = Some(canonical.clone());
             self.scan_config.paths = vec![canonical.to_string_lossy().to_string()];
 
+            // Each directory keeps the sort last chosen in it
+            let sort = self
+                .dir_sorts
+                .get(&canonical)
+                .cloned()
+                .unwrap_or_else(|| self.scan_config.group_by.clone());
+            self.gps_map.sort_by_exif_timestamp = sort == "exif-date" || sort == "exif-date-desc";
+            self.view_mode_sort = Some(sort);
+
             // Change process working directory so relative paths work
             let _ = std::env::set_current_dir(&canonical);
 
@@ -896,6 +2444,7 @@ impl GuiApp {
 
             // Clear caches first
             self.raw_cache.clear();
+            self.placeholder_cache.clear();
             self.gpu_cache.clear();
             self.animation_cache.clear();
             self.cached_histogram.clear();
@@ -907,13 +2456,30 @@ impl GuiApp {
             }
             self.last_preload_pos = None;
             self.file_index.clear();
+            self.badge_filter = None;
+            self.subgroup_filter = None;
+            self.state.hidden.clear();
+            self.cached_tags.clear();
+            self.cached_tags_pending.clear();
+            self.cached_tags_rx = None;
             self.enrichment_rx = None;
+            self.reenrich_ids.clear();
+            self.enrichment_progress_rx = None;
+            self.enrichment_progress = (0, 0);
+            self.resolution_rx = None;
+            self.similar = None;
+            self.similar_rx = None;
 
             // Background directory scanning with batch database lookups
             let sort_order = self.view_mode_sort.clone().unwrap_or_else(|| "name".to_string());
             let (batch_tx, batch_rx) = unbounded::<Vec<FileMetadata>>();
-            let (subdirs, count) =
-                scanner::spawn_background_dir_scan(canonical, sort_order, &self.ctx, batch_tx);
+            let (subdirs, count) = scanner::spawn_background_dir_scan(
+                canonical,
+                sort_order,
+                &self.ctx,
+                batch_tx,
+                self.gui_config.dir_scan_exif.unwrap_or(false),
+            );
 
             self.subdirs = subdirs;
             self.dir_total_count = Some(count);
@@ -985,7 +2551,9 @@ impl GuiApp {
                 for entry in entries.flatten() {
                     let entry_path = entry.path();
                     // Canonicalize each entry path to ensure absolute paths
-                    if let Ok(canonical) = entry_path.canonicalize() {
+                    if let Ok(canonical) = crate::scanner::canonicalize(&entry_path) {
+                        // Logical path instead when resolve_symlinks = false
+                        let canonical = crate::scanner::listed_path(&entry_path, canonical);
                         if canonical.is_dir() {
                             self.subdirs.push(canonical);
                         } else if self.state.view_mode
@@ -1010,7 +2578,7 @@ impl GuiApp {
 
                                     // Extract fields from ImageFeatures if found
                                     let (resolution, orientation, gps_pos, exif_timestamp) =
-                                        if let Some(feats) = cached {
+                                        if let Some(feats) = &cached {
                                             (
                                                 feats.resolution(),
                                                 feats.orientation(),
@@ -1029,10 +2597,20 @@ impl GuiApp {
                                         resolution,
                                         content_hash: [0u8; 32],
                                         pixel_hash: None,
+                                        oriented_pixel_hash: None,
+                                        near_identical: None,
+                                        luma_variant: None,
+                                        icc_profile: None,
                                         orientation,
                                         gps_pos,
                                         unique_file_id,
                                         exif_timestamp,
+                                        exif_subsec_nanos: cached
+                                            .as_ref()
+                                            .and_then(|f| f.exif_subsec_nanos()),
+                                        exif_tz_offset: cached
+                                            .as_ref()
+                                            .and_then(|f| f.exif_tz_offset()),
                                     });
                                 }
                             }
@@ -1041,7 +2619,8 @@ impl GuiApp {
                 }
             }
 
-            crate::scanner::sort_directories(&mut self.subdirs, &self.state.group_by);
+            let dir_sort = self.view_mode_sort.as_deref().unwrap_or(&self.state.group_by);
+            crate::scanner::sort_directories(&mut self.subdirs, dir_sort);
 
             if self.state.view_mode {
                 if let Some(sort_order) = &self.view_mode_sort {
@@ -1099,7 +2678,7 @@ impl GuiApp {
 
         let current = self.failed_images.len();
         if current != self.last_failed_images_len {
-            eprintln!("[DEBUG] failed_images size now {}", current);
+            debug_log!("[DEBUG] failed_images size now {}", current);
             self.last_failed_images_len = current;
         }
 
@@ -1186,7 +2765,7 @@ impl GuiApp {
                             self.failed_images.remove(path);
                             self.retry_after.remove(path);
                             self.raw_loading.remove(path);
-                            eprintln!("[DEBUG-NOTIFY] CloseWrite — loading image: {:?}", path);
+                            debug_log!("[DEBUG-NOTIFY] CloseWrite — loading image: {:?}", path);
                             self.enqueue_image_load(path, 0, 0);
                             if let Some(name) = path.file_name() {
                                 self.fs_mod_files.insert(name.to_string_lossy().to_string());
@@ -1233,7 +2812,8 @@ impl GuiApp {
             || !self.fs_rem_files.is_empty()
             || !self.fs_rem_dirs.is_empty();
 
-        if has_pending {
+        // A frozen list keeps collecting changes until it is unfrozen
+        if has_pending && !self.list_frozen {
             let debounce_dur = Duration::from_millis(500);
             let time_since = self.last_fs_refresh.elapsed();
 
@@ -1283,8 +2863,8 @@ impl GuiApp {
                     parts.push(format!("removed {} dirs ({}{})", count, display.join(", "), extra));
                 }
 
-                if !parts.is_empty() && self.state.status_message.is_none() {
-                    self.set_status(format!("FS: {}", parts.join("; ")), false);
+                if !parts.is_empty() {
+                    self.report_fs_event(parts.join("; "));
                 }
             } else {
                 let remaining = debounce_dur - time_since;
@@ -1293,7 +2873,87 @@ impl GuiApp {
         }
     }
 
+    /// Route a file system change summary per `fs_notifications`.
+    fn report_fs_event(&mut self, msg: String) {
+        match self.gui_config.fs_notifications.unwrap_or_default() {
+            FsNotifications::Status => {
+                if self.state.status_message.is_none() {
+                    self.set_status(format!("FS: {}", msg), false);
+                }
+            }
+            FsNotifications::Panel => {
+                if self.fs_events.len() >= FS_EVENT_LOG_LEN {
+                    self.fs_events.pop_front();
+                }
+                let time = chrono::Local::now().format("%H:%M:%S").to_string();
+                self.fs_events.push_back((time, msg));
+            }
+            FsNotifications::Off => {}
+        }
+    }
+
     // Handles streaming batches for instant feedback
+    /// Duplicate mode scan in progress: list the preliminary groups streamed by
+    /// `scan_and_group`. A group replaces every listed group it shares a file with
+    /// (it grew or merged them); the final result replaces them all.
+    fn ingest_preview_groups(&mut self) -> bool {
+        let Some(batch_rx) = self.scan_batch_rx.clone() else { return false };
+        let mut got_any = false;
+        while let Ok(group) = batch_rx.try_recv() {
+            if !self.scan_preview_started {
+                // First early result: clear the list of the previous scan
+                self.badge_filter = None;
+                self.subgroup_filter = None;
+                self.state.hidden.clear();
+                self.collapsed_groups.clear();
+                self.group_review = None;
+                self.state.groups.clear();
+                self.state.group_infos.clear();
+                self.state.current_group_idx = 0;
+                self.state.current_file_idx = 0;
+                self.scan_preview_started = true;
+            }
+            let ids: HashSet<u128> = group.iter().map(|f| f.unique_file_id).collect();
+            // Ignored files stay out of the list, as they do for the final result
+            let mut kept = vec![group];
+            self.filter_ignored_groups(&mut kept, &mut Vec::new());
+            // A grown group keeps its place in the list
+            let mut at = None;
+            let mut g_idx = 0;
+            while g_idx < self.state.groups.len() {
+                if self.state.groups[g_idx].iter().any(|f| ids.contains(&f.unique_file_id)) {
+                    self.state.groups.remove(g_idx);
+                    self.state.group_infos.remove(g_idx);
+                    at.get_or_insert(g_idx);
+                } else {
+                    g_idx += 1;
+                }
+            }
+            if let Some(mut group) = kept.pop() {
+                group.sort_by(subgroup_order);
+                let at = at.unwrap_or(self.state.groups.len());
+                self.state.groups.insert(at, group);
+                self.state
+                    .group_infos
+                    .insert(at, GroupInfo { max_dist: 0, status: GroupStatus::None });
+            }
+            got_any = true;
+        }
+        if got_any {
+            let groups = self.state.groups.len();
+            if self.state.current_group_idx >= groups {
+                self.state.current_group_idx = groups.saturating_sub(1);
+                self.state.current_file_idx = 0;
+            }
+            let group_len = self.state.groups.get(self.state.current_group_idx).map_or(0, Vec::len);
+            self.state.current_file_idx =
+                self.state.current_file_idx.min(group_len.saturating_sub(1));
+            self.state.last_file_count = self.state.groups.iter().map(|g| g.len()).sum();
+            self.cache_dirty = true;
+        }
+        got_any
+    }
+
     pub(super) fn check_reload(&mut self, ctx: &egui::Context) {
         // 1. Start Scan if needed
         if self.state.is_loading && self.scan_rx.is_none() {
@@ -1312,24 +2972,33 @@ impl GuiApp {
                 let sort = sort_order.clone();
                 let paths = cfg.paths.clone();
                 thread::spawn(move || {
-                    let res = scanner::scan_for_view(&paths, &sort, Some(prog_tx), Some(batch_tx));
-                    let _ = tx.send(res);
+                    let (groups, infos, subdirs) =
+                        scanner::scan_for_view(&paths, &sort, Some(prog_tx), Some(batch_tx));
+                    let _ = tx.send((groups, infos, subdirs, HashMap::new()));
                 });
             } else {
-                // Duplicate Finder Mode
+                // Duplicate Finder Mode: preliminary groups arrive on the batch channel
                 let ctx_clone = self.ctx.clone();
                 thread::spawn(move || {
-                    // Note: scan_and_group doesn't use batch_tx yet, but progress will work
-                    let (groups, infos) = scanner::scan_and_group(&cfg, &ctx_clone, Some(prog_tx));
-                    let _ = tx.send((groups, infos, Vec::new()));
+                    let (groups, infos) =
+                        scanner::scan_and_group(&cfg, &ctx_clone, Some(prog_tx), Some(batch_tx));
+                    let live_siblings = scanner::live_photo_sibling_index(
+                        groups.iter().flatten().map(|f| f.path.as_path()),
+                        &cfg.live_photo_exts,
+                    );
+                    let _ = tx.send((groups, infos, Vec::new(), live_siblings));
                 });
+                // The previous list stays until the first preview group comes in
+                self.scan_preview_started = false;
             }
         }
 
         let mut needs_repaint = false;
 
         // 2. Process Partial Batches (Streaming View)
-        if let Some(batch_rx) = &self.scan_batch_rx.clone() {
+        if self.view_mode_sort.is_none() {
+            needs_repaint |= self.ingest_preview_groups();
+        } else if let Some(batch_rx) = &self.scan_batch_rx.clone() {
             while let Ok(new_files) = batch_rx.try_recv() {
                 for file in &new_files {
                     // If file already has cached features, index it immediately
@@ -1368,50 +3037,27 @@ impl GuiApp {
 
         // 4. Process Final Result
         if let Some(rx) = &self.scan_rx
-            && let Ok((mut new_groups, mut new_infos, new_subdirs)) = rx.try_recv()
+            && let Ok((mut new_groups, mut new_infos, new_subdirs, live_siblings)) = rx.try_recv()
         {
-            eprintln!(
+            debug_log!(
                 "[DEBUG-RELOAD] Replacing groups! Old groups count: {}, New groups count: {}",
                 self.state.groups.len(),
                 new_groups.len()
             );
 
-            // SORTING LOGIC: Ensure content subgroups are contiguous.
-            // We sort primarily by pixel_hash, secondarily by path.
-            // This keeps "C1" files together, "C2" together, etc.
+            // SORTING LOGIC: Ensure content subgroups are contiguous (see subgroup_order).
             // Only apply hardcoded pixel-hash sort in Duplicate Finder mode
             if !self.state.view_mode {
                 for group in &mut new_groups {
-                    group.sort_by(|a, b| {
-                        match (a.pixel_hash, b.pixel_hash) {
-                            (Some(ha), Some(hb)) => {
-                                if ha == hb {
-                                    a.path.cmp(&b.path)
-                                } else {
-                                    ha.cmp(&hb)
-                                }
-                            }
-                            // Put files WITH pixel hash (potential content matches) before those without?
-                            // Or standard Option ordering: None < Some.
-                            // Let's use standard Ord which puts None first.
-                            // This means "Unmatched" files might appear at top/bottom,
-                            // but all "Some(hash)" will be grouped.
-                            (h1, h2) => {
-                                let ord = h1.cmp(&h2);
-                                if ord == std::cmp::Ordering::Equal {
-                                    a.path.cmp(&b.path)
-                                } else {
-                                    ord
-                                }
-                            }
-                        }
-                    });
+                    group.sort_by(subgroup_order);
                 }
+                // Marked files move up again once the new groups are shown
+                self.keeper_sorted_marks = None;
             }
 
             if let Some(first_group) = new_groups.first() {
                 for (i, file) in first_group.iter().enumerate().take(5) {
-                    eprintln!(
+                    debug_log!(
                         "[DEBUG-RELOAD]   new_groups[0][{}]: {:?}, orientation={}",
                         i,
                         file.path.file_name().unwrap_or_default(),
@@ -1448,6 +3094,12 @@ impl GuiApp {
 
             // Only replace if we have results (duplicate mode) or finished view mode
             self.state.groups = new_groups;
+            self.badge_filter = None;
+            self.subgroup_filter = None;
+            self.cached_tags.clear();
+            self.state.sync_protected(|ch| self.ctx.is_protected(ch));
+            self.sync_ratings();
+            self.state.live_siblings = live_siblings;
             if let Some(ref sort) = self.view_mode_sort
                 && sort == "location"
             {
@@ -1456,6 +3108,10 @@ impl GuiApp {
             self.cache_dirty = true;
             self.state.group_infos = new_infos;
             self.subdirs = new_subdirs;
+            self.state.hidden.clear();
+            if self.group_filter.is_active() {
+                self.apply_group_filter();
+            }
 
             if let Some(ref sort) = self.view_mode_sort
                 && sort == "location"
@@ -1483,9 +3139,31 @@ impl GuiApp {
                 }
             }
 
+            // Start reviewing right away (`after_scan`); with no groups the list just
+            // says so
+            let after_scan = self.gui_config.after_scan.unwrap_or_default();
+            if !self.state.view_mode
+                && after_scan != AfterScan::Stay
+                && !self.state.groups.is_empty()
+            {
+                self.state.current_group_idx = 0;
+                self.state.current_file_idx = 0;
+                self.state.selection_changed = true;
+                if after_scan == AfterScan::Fullscreen {
+                    self.state.is_fullscreen = true;
+                }
+            }
+
             self.state.is_loading = false;
             self.build_search_index();
             self.scan_rx = None;
+
+            if !self.state.view_mode && !self.scan_summary_shown {
+                let summary = ScanSummary::from_groups(&self.state.groups, &self.state.group_infos);
+                debug_log!("[SCAN-SUMMARY] {}", summary.describe());
+                self.scan_summary = Some(summary);
+                self.scan_summary_shown = true;
+            }
             self.scan_progress_rx = None;
             self.scan_batch_rx = None;
             needs_repaint = true;
@@ -1499,8 +3177,75 @@ impl GuiApp {
         // Crucial: If we are still loading, request another frame soon
         // to keep polling the channels even if the user isn't moving the mouse.
         if self.state.is_loading {
-            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            ctx.request_repaint_after(self.repaint_poll);
+        }
+    }
+
+    /// Fit the GPS map to the geotagged files of the current group (A key).
+    pub(super) fn fit_map_to_current_group(&mut self) {
+        if let Some(group) = self.state.groups.get(self.state.current_group_idx) {
+            let positions: Vec<_> = group
+                .iter()
+                .filter_map(|f| self.gps_map.get_marker_by_path(&f.path))
+                .map(|m| m.position())
+                .collect();
+
+            if !positions.is_empty() {
+                self.gps_map.visible = true;
+                self.gps_map.fit_positions(&positions);
+                self.set_status(format!("Map fitted to {} group markers", positions.len()), false);
+            } else {
+                self.set_status("No GPS markers found in current group".to_string(), true);
+            }
+        }
+    }
+
+    /// True while any background work may deliver results over a channel that
+    /// has no way to wake the UI by itself. When this is false the app stops
+    /// requesting repaints and relies on egui's event-driven redraw.
+    fn has_background_work(&self) -> bool {
+        self.state.is_loading
+            || self.scan_rx.is_some()
+            || self.dir_scan_rx.is_some()
+            || self.enrichment_rx.is_some()
+            || self.resolution_rx.is_some()
+            || self.cached_tags_rx.is_some()
+            || self.contact_sheet_rx.is_some()
+            || self.similar_rx.is_some()
+            || self.reference_rx.is_some()
+            || self.reference.as_ref().is_some_and(|r| r.pixel_pending > 0)
+            || self.raw_wb.is_pending()
+            || !self.raw_loading.is_empty()
+            || !self.retry_after.is_empty()
+            || (!self.list_frozen && self.frozen_pending_count() > 0)
+    }
+
+    /// Changes waiting for the list to be unfrozen: queued file system changes
+    /// plus a skipped location re-sort.
+    pub(super) fn frozen_pending_count(&self) -> usize {
+        self.fs_mod_files.len()
+            + self.fs_mod_dirs.len()
+            + self.fs_rem_files.len()
+            + self.fs_rem_dirs.len()
+            + usize::from(self.frozen_resort)
+    }
+
+    /// Shift+L: freeze the list so FS events and enrichment don't reorder it under
+    /// the selection. Unfreezing applies what was queued meanwhile.
+    pub(super) fn toggle_list_freeze(&mut self) {
+        self.list_frozen = !self.list_frozen;
+        if self.list_frozen {
+            self.set_status("List frozen, changes wait for Shift+L".to_string(), false);
+            return;
+        }
+        let pending = self.frozen_pending_count();
+        if std::mem::take(&mut self.frozen_resort)
+            && self.view_mode_sort.as_deref() == Some("location")
+        {
+            self.apply_location_sort();
         }
+        // Queued FS changes go through the usual debounce in check_fs_events
+        self.set_status(format!("List unfrozen, applying {} pending changes", pending), false);
     }
 
     pub(super) fn get_title_string(&self) -> String {
@@ -1515,6 +3260,14 @@ impl GuiApp {
             } else {
                 format!("{} | Files: {}", APP_TITLE, self.state.last_file_count)
             }
+        } else if self.scan_config.paths.len() > 1 {
+            format!(
+                "{} | Roots: {} | Groups: {} | Files: {}",
+                APP_TITLE,
+                self.scan_config.paths.len(),
+                self.state.groups.len(),
+                self.state.last_file_count
+            )
         } else {
             format!(
                 "{} | Groups: {} | Files: {}",
@@ -1534,6 +3287,50 @@ impl GuiApp {
         f(entry);
     }
 
+    /// Fraction of the preload window to place ahead of the current position.
+    /// With `preload_auto_bias`, two or more steps in the same direction push the
+    /// window that way (forward cull passes rarely need the files already seen).
+    fn preload_forward_bias(&mut self, current_g: usize, current_f: usize) -> f32 {
+        let configured = self.gui_config.preload_forward_bias.unwrap_or(0.5).clamp(0.0, 1.0);
+
+        let groups = &self.state.groups;
+        let is_last = |g: usize, f: usize| {
+            g + 1 == groups.len() && groups.get(g).is_some_and(|grp| f + 1 == grp.len())
+        };
+        let step = match self.last_preload_pos {
+            // A wrap-around (nav_wrap) keeps the direction it was made in
+            Some((lg, lf))
+                if self.state.nav_wrap && is_last(lg, lf) && (current_g, current_f) == (0, 0) =>
+            {
+                1
+            }
+            Some((lg, lf))
+                if self.state.nav_wrap && (lg, lf) == (0, 0) && is_last(current_g, current_f) =>
+            {
+                -1
+            }
+            Some((lg, lf)) if self.state.groups.len() == 1 || lg == current_g => {
+                (current_f as i64 - lf as i64).signum() as i32
+            }
+            Some((lg, _)) => (current_g as i64 - lg as i64).signum() as i32,
+            None => 0,
+        };
+        self.preload_momentum = if step == 0 || step.signum() != self.preload_momentum.signum() {
+            step
+        } else {
+            (self.preload_momentum + step).clamp(-3, 3)
+        };
+
+        if !self.gui_config.preload_auto_bias.unwrap_or(false) {
+            return configured;
+        }
+        match self.preload_momentum {
+            m if m >= 2 => configured.max(0.8),
+            m if m <= -2 => configured.min(0.2),
+            _ => configured,
+        }
+    }
+
     /// Handles both standard image preloading (via egui) and Raw preloading (via worker pool)
     /// In duplicate mode (multiple groups), preloads files from current and nearby groups.
     pub(super) fn perform_preload(&mut self, _ctx: &egui::Context) {
@@ -1550,9 +3347,10 @@ impl GuiApp {
         {
             return;
         }
+        let forward_bias = self.preload_forward_bias(current_g, current_f);
         self.last_preload_pos = Some((current_g, current_f));
 
-        let preload_limit = self.ctx.gui_config.preload_count.unwrap_or(10);
+        let preload_limit = self.gui_config.preload_count.unwrap_or(10);
         let mut active_window_paths = HashSet::new();
 
         // Collect paths to preload, respecting preload_limit across all groups
@@ -1562,14 +3360,25 @@ impl GuiApp {
         if self.state.groups.len() == 1 {
             // Original behavior: preload within the single group
             let group = &self.state.groups[0];
-            let half = preload_limit / 2;
-            let start = current_f.saturating_sub(half);
-            let end = (start + preload_limit).min(group.len());
-            let start =
-                if end - start < preload_limit { end.saturating_sub(preload_limit) } else { start };
+            let behind = ((preload_limit as f32) * (1.0 - forward_bias)).round() as usize;
+            if self.state.nav_wrap && group.len() > preload_limit {
+                // The window wraps around the list ends like the navigation does
+                let first = current_f + group.len() - behind.min(group.len());
+                for i in (first..first + preload_limit).map(|i| i % group.len()) {
+                    paths_to_preload.push((group[i].path.clone(), i == current_f, 0, i));
+                }
+            } else {
+                let start = current_f.saturating_sub(behind);
+                let end = (start + preload_limit).min(group.len());
+                let start = if end - start < preload_limit {
+                    end.saturating_sub(preload_limit)
+                } else {
+                    start
+                };
 
-            for i in start..end {
-                paths_to_preload.push((group[i].path.clone(), i == current_f, 0, i));
+                for i in start..end {
+                    paths_to_preload.push((group[i].path.clone(), i == current_f, 0, i));
+                }
             }
         } else {
             // Multiple groups: preload current group + files from nearby groups
@@ -1589,7 +3398,8 @@ impl GuiApp {
 
                 // Next group(s)
                 let mut next_g = current_g + 1;
-                let mut slots_left = remaining / 2 + remaining % 2; // Give slightly more to next
+                let ahead = ((remaining as f32) * forward_bias).ceil() as usize;
+                let mut slots_left = ahead; // Rounded up: the next group wins ties
                 while next_g < self.state.groups.len() && slots_left > 0 {
                     let group = &self.state.groups[next_g];
                     for (i, file) in group.iter().enumerate().take(slots_left) {
@@ -1600,7 +3410,7 @@ impl GuiApp {
                 }
 
                 // Previous group(s)
-                slots_left = remaining / 2;
+                slots_left = remaining - ahead;
                 let mut prev_g = current_g.saturating_sub(1);
                 while prev_g < current_g && slots_left > 0 {
                     let group = &self.state.groups[prev_g];
@@ -1675,9 +3485,13 @@ impl GuiApp {
             }
         }
 
+        // The preload window itself may wrap around the list ends (nav_wrap)
+        retention_paths.extend(active_window_paths.iter().cloned());
+
         // Evict from memory only if it falls completely outside the wider retention window.
         // Dropping a GpuImage releases its wgpu::Texture, and with it the VRAM.
         self.raw_cache.retain(|k, _| retention_paths.contains(k));
+        self.placeholder_cache.retain(|k, _| retention_paths.contains(k));
         self.gpu_cache.retain(|k, _| retention_paths.contains(k));
         self.animation_cache.retain(|k, _| retention_paths.contains(k));
         self.cached_histogram.retain(|k, _| retention_paths.contains(k));
@@ -1727,20 +3541,180 @@ impl GuiApp {
         if let Some(ref current) = self.current_dir.clone()
             && let Some(parent) = current.parent()
         {
-            self.change_directory(parent.to_path_buf());
+            self.change_directory(parent.to_path_buf());
+        }
+    }
+
+    /// Ctrl+R: re-read the config file and apply what can change while running.
+    /// Window layout and toggles stay as they are (they are saved on exit); settings
+    /// baked into the image loader threads are reported as needing a restart.
+    pub(super) fn reload_config(&mut self, egui_ctx: &egui::Context) {
+        let (mut new, map_providers) = match self.ctx.reload_config() {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.set_status(format!("Config reload failed: {}", e), true);
+                return;
+            }
+        };
+        let old = std::mem::take(&mut self.gui_config);
+
+        // Live UI state wins over what is on disk
+        new.width = old.width;
+        new.height = old.height;
+        new.panel_width = old.panel_width;
+        new.viewer_background = old.viewer_background;
+        new.image_filter = old.image_filter;
+        new.action_bar = old.action_bar;
+        new.keeper_last = old.keeper_last;
+        new.min_group_size = old.min_group_size;
+        new.max_group_size = old.max_group_size;
+        new.max_group_dist = old.max_group_dist;
+
+        let mut restart = Vec::new();
+        if crate::db::PaletteConfig::from_gui_config(&old)
+            != crate::db::PaletteConfig::from_gui_config(&new)
+        {
+            restart.push("palette");
+        }
+        if crate::db::HdrConfig::from_gui_config(&old)
+            != crate::db::HdrConfig::from_gui_config(&new)
+        {
+            restart.push("sdr_peak_nits");
+        }
+        if crate::db::DecodeConfig::from_gui_config(&old)
+            != crate::db::DecodeConfig::from_gui_config(&new)
+        {
+            restart.push("jpeg_preview_max/exif_placeholder/decode_timeout_secs");
+        }
+        // Read per decode, so unlike the rest of the decoding settings this applies now
+        crate::scanner::set_jpeg_tiers(new.jpeg_decoders.as_deref().unwrap_or_default());
+        if old.native_decorations != new.native_decorations {
+            restart.push("native_decorations");
+            // The window was created with the old setting; keep the title bar matching it
+            new.native_decorations = old.native_decorations;
+        }
+
+        if old.font_ui != new.font_ui
+            || old.font_monospace != new.font_monospace
+            || old.font_orthography != new.font_orthography
+        {
+            self.gui_config = new.clone();
+            self.install_fonts(egui_ctx);
+        }
+        let (old_scale, new_scale) = (old.font_scale.unwrap_or(1.0), new.font_scale.unwrap_or(1.0));
+        if old_scale != new_scale && old_scale > 0.0 {
+            egui_ctx.set_pixels_per_point(egui_ctx.pixels_per_point() / old_scale * new_scale);
+        }
+        self.repaint_poll = Duration::from_millis(new.repaint_poll_ms.unwrap_or(100));
+        self.gps_map.distance_format = DistanceFormat::from_gui_config(&new);
+        self.state.staging_dir = new.staging_dir.clone();
+        self.state.review_dir = new.review_dir.clone();
+        self.state.nav_wrap = new.nav_wrap.unwrap_or(false);
+        if old.exif_tags != new.exif_tags
+            || old.decimal_coords != new.decimal_coords
+            || old.display_timezone != new.display_timezone
+        {
+            self.cached_exif = None;
+        }
+        let bad_tz = new
+            .display_timezone
+            .as_deref()
+            .filter(|tz| crate::exif_extract::DisplayTz::parse(tz).is_none())
+            .map(str::to_string);
+        self.gui_config = new;
+        // Pick up a changed URL for the provider in use
+        if let Some(url) = map_providers.get(&self.gps_map.provider_name)
+            && *url != self.gps_map.provider_url
+        {
+            self.gps_map.set_provider(self.gps_map.provider_name.clone(), url.clone(), egui_ctx);
+        }
+        self.map_providers = map_providers;
+
+        let mut msg = if restart.is_empty() {
+            "Config reloaded".to_string()
+        } else {
+            format!("Config reloaded; restart to apply: {}", restart.join(", "))
+        };
+        if let Some(tz) = &bad_tz {
+            msg.push_str(&format!(
+                "; display_timezone must be photo, local, UTC or +HH:MM, not {}",
+                tz
+            ));
+        }
+        self.set_status(msg, bad_tz.is_some());
+    }
+
+    /// Build the font set from the embedded fonts and the configured `font_ui` /
+    /// `font_monospace` / `font_orthography`, and install it. Run at startup and on
+    /// a config reload.
+    fn install_fonts(&mut self, egui_ctx: &egui::Context) {
+        let mut fonts = egui::FontDefinitions::default();
+
+        // Orthography preference, e.g. font_orthography = "j,sc" in the config.
+        let orth_owned: Vec<String> = self
+            .gui_config
+            .font_orthography
+            .as_deref()
+            .map(|s| s.split(',').map(|t| t.trim().to_ascii_lowercase()).collect())
+            .unwrap_or_default();
+        let orthography: Vec<&str> = if orth_owned.is_empty() {
+            super::fonts::DEFAULT_ORTHOGRAPHY.to_vec()
+        } else {
+            orth_owned.iter().map(String::as_str).collect()
+        };
+
+        #[cfg(feature = "embed-fonts")]
+        {
+            const SARASA_TTC: &[u8] = include_bytes!("../../assets/fonts/Sarasa-Regular.ttc");
+            eprintln!("[INFO] Compiling with embedded Sarasa fonts.");
+            super::fonts::install_font_file(
+                &mut fonts,
+                "sarasa-embedded",
+                SARASA_TTC,
+                &orthography,
+            );
+        }
+
+        // User-specified fonts win over the embedded one (installed later => inserted in front).
+        for (role, cfg_path) in [
+            (super::fonts::FontRole::Proportional, self.gui_config.font_ui.as_deref()),
+            (super::fonts::FontRole::Monospace, self.gui_config.font_monospace.as_deref()),
+        ] {
+            if let Some(path) = cfg_path
+                && let Some(data) = super::fonts::load_font_data(std::path::Path::new(path))
+            {
+                super::fonts::install_role(&mut fonts, path, data, role, &orthography);
+            }
         }
+
+        self.label_fonts = fonts.clone();
+        egui_ctx.set_fonts(fonts);
+    }
+
+    /// Timezone for EXIF capture times; unparsable values show them as shot.
+    pub(super) fn display_tz(&self) -> crate::exif_extract::DisplayTz {
+        self.gui_config
+            .display_timezone
+            .as_deref()
+            .and_then(crate::exif_extract::DisplayTz::parse)
+            .unwrap_or_default()
+    }
+
+    /// Borderless window with our own title bar (drag, minimize, maximize, close).
+    /// Windows always gets native decorations; elsewhere `native_decorations = true`
+    /// opts out, e.g. for tiling window managers.
+    fn custom_title_bar(&self) -> bool {
+        !cfg!(target_os = "windows") && !self.gui_config.native_decorations.unwrap_or(false)
     }
 
     pub fn run(self) -> Result<(), eframe::Error> {
         // Config stores physical pixels (screen_rect * ppp after font_scale applied)
         // with_inner_size is called BEFORE font_scale, when ppp=1.0
         // So physical pixels = logical points at that moment
-        let width = self.ctx.gui_config.width.unwrap_or(1280) as f32;
-        let height = self.ctx.gui_config.height.unwrap_or(720) as f32;
-
-        //eprintln!("[DEBUG-RUN] Setting window size to {}x{} (physical pixels = logical points at ppp=1)", width, height);
+        let width = self.gui_config.width.unwrap_or(1280) as f32;
+        let height = self.gui_config.height.unwrap_or(720) as f32;
 
-        let is_windows = cfg!(target_os = "windows");
+        //debug_log!("[DEBUG-RUN] Setting window size to {}x{} (physical pixels = logical points at ppp=1)", width, height);
 
         // Ask for 16-bit normalized textures when the adapter offers them. They
         // back the Rgba16Unorm path used for images with real transparency,
@@ -1763,7 +3737,7 @@ impl GuiApp {
             wgpu_options,
             viewport: egui::ViewportBuilder::default()
                 .with_inner_size([width, height])
-                .with_decorations(is_windows)
+                .with_decorations(!self.custom_title_bar())
                 .with_resizable(true),
             ..Default::default()
         };
@@ -1775,54 +3749,11 @@ impl GuiApp {
                 // `self` is moved into this FnOnce; rebind so the wgpu setup below
                 // can mutate it before it becomes the boxed App.
                 let mut app = self;
+                app.egui_ctx = Some(cc.egui_ctx.clone());
 
                 egui_extras::install_image_loaders(&cc.egui_ctx);
 
-                let mut fonts = egui::FontDefinitions::default();
-
-                // Orthography preference, e.g. font_orthography = "j,sc" in the config.
-                let orth_owned: Vec<String> = app
-                    .ctx
-                    .gui_config
-                    .font_orthography
-                    .as_deref()
-                    .map(|s| s.split(',').map(|t| t.trim().to_ascii_lowercase()).collect())
-                    .unwrap_or_default();
-                let orthography: Vec<&str> = if orth_owned.is_empty() {
-                    super::fonts::DEFAULT_ORTHOGRAPHY.to_vec()
-                } else {
-                    orth_owned.iter().map(String::as_str).collect()
-                };
-
-                #[cfg(feature = "embed-fonts")]
-                {
-                    const SARASA_TTC: &[u8] =
-                        include_bytes!("../../assets/fonts/Sarasa-Regular.ttc");
-                    eprintln!("[INFO] Compiling with embedded Sarasa fonts.");
-                    super::fonts::install_font_file(
-                        &mut fonts,
-                        "sarasa-embedded",
-                        SARASA_TTC,
-                        &orthography,
-                    );
-                }
-
-                // User-specified fonts win over the embedded one (installed later => inserted in front).
-                for (role, cfg_path) in [
-                    (super::fonts::FontRole::Proportional, app.ctx.gui_config.font_ui.as_deref()),
-                    (
-                        super::fonts::FontRole::Monospace,
-                        app.ctx.gui_config.font_monospace.as_deref(),
-                    ),
-                ] {
-                    if let Some(path) = cfg_path
-                        && let Some(data) = super::fonts::load_font_data(std::path::Path::new(path))
-                    {
-                        super::fonts::install_role(&mut fonts, path, data, role, &orthography);
-                    }
-                }
-
-                cc.egui_ctx.set_fonts(fonts);
+                app.install_fonts(&cc.egui_ctx);
 
                 // --- 10-bit image path ---
                 // Only worth taking when the swapchain can actually carry 10 bits.
@@ -1856,7 +3787,7 @@ impl GuiApp {
 impl Drop for GuiApp {
     fn drop(&mut self) {
         // Save window size and panel width to config
-        let mut gui_config = self.ctx.gui_config.clone();
+        let mut gui_config = self.gui_config.clone();
 
         if let Some((w, h)) = self.last_window_size {
             gui_config.width = Some(w);
@@ -1866,16 +3797,25 @@ impl Drop for GuiApp {
         // panel_width is in current logical points (after font_scale)
         // Save it directly - we'll scale when loading
         gui_config.panel_width = Some(self.panel_width);
-
-        eprintln!(
+        gui_config.viewer_background = Some(self.viewer_background);
+        gui_config.image_filter = Some(self.image_filter);
+        gui_config.action_bar = Some(self.show_action_bar);
+        gui_config.keeper_last = Some(self.keeper_last);
+        gui_config.min_group_size = Some(self.group_filter.min_size);
+        gui_config.max_group_size = Some(self.group_filter.max_size);
+        gui_config.max_group_dist = self.group_filter.max_dist;
+
+        debug_log!(
             "[DEBUG-EXIT] Calling save_gui_config with width={:?}, height={:?}, panel_width={:?}",
-            gui_config.width, gui_config.height, gui_config.panel_width
+            gui_config.width,
+            gui_config.height,
+            gui_config.panel_width
         );
 
         if let Err(e) = self.ctx.save_gui_config(&gui_config) {
             eprintln!("Error saving config: {}", e);
         } else {
-            eprintln!("[DEBUG-EXIT] save_gui_config succeeded");
+            debug_log!("[DEBUG-EXIT] save_gui_config succeeded");
         }
     }
 }
@@ -1904,6 +3844,13 @@ impl eframe::App for GuiApp {
         // 1. Determine what the title SHOULD be
         let current_title = if self.state.is_loading {
             format!("{} | Scanning... {}/{}", APP_TITLE, self.scan_progress.0, self.scan_progress.1)
+        } else if self.enrichment_rx.is_some() {
+            format!(
+                "{} | Enriching {}/{}",
+                self.get_title_string(),
+                self.enrichment_progress.0,
+                self.enrichment_progress.1
+            )
         } else {
             self.get_title_string()
         };
@@ -1915,7 +3862,7 @@ impl eframe::App for GuiApp {
         }
 
         // 3. Use the title string for the internal label (doesn't trigger OS events)
-        if !cfg!(target_os = "windows") && !self.state.is_fullscreen {
+        if self.custom_title_bar() && !self.state.is_fullscreen {
             egui::Panel::top("custom_title_bar").show(ui, |ui| {
                 ui.horizontal(|ui| {
                     let height = 12.0;
@@ -1954,7 +3901,7 @@ impl eframe::App for GuiApp {
         let mut force_panel_resize = false;
 
         if !self.initial_scale_applied {
-            let user_scale = self.ctx.gui_config.font_scale.unwrap_or(1.0);
+            let user_scale = self.gui_config.font_scale.unwrap_or(1.0);
             ctx.set_pixels_per_point(ctx.pixels_per_point() * user_scale);
             self.initial_scale_applied = true;
         }
@@ -1966,6 +3913,11 @@ impl eframe::App for GuiApp {
             self.state.status_set_time = None;
         }
 
+        self.update_reference(ctx);
+        self.update_similar(ctx);
+        self.poll_contact_sheet();
+        super::image::poll_raw_wb(self, ctx);
+
         // Receive finished raw images from worker thread pool
         // Use try_recv() which returns Err on empty OR disconnected channel
         loop {
@@ -2094,6 +4046,14 @@ impl eframe::App for GuiApp {
                                 },
                             );
                         }
+                        ImageLoadResult::Placeholder(color_image, orientation) => {
+                            // Full decode still running: keep the path in raw_loading
+                            let name = format!("thumb_{}", path.display());
+                            let texture = ctx.load_texture(name, color_image, Default::default());
+                            self.placeholder_cache.insert(path, (texture, orientation));
+                            ctx.request_repaint();
+                            continue;
+                        }
                         ImageLoadResult::Failed(err_msg) => {
                             let lower = err_msg.to_lowercase();
                             let is_transient = lower.contains("premature")
@@ -2104,9 +4064,10 @@ impl eframe::App for GuiApp {
                                 || lower.contains("truncated");
 
                             if is_transient {
-                                eprintln!(
+                                debug_log!(
                                     "[DEBUG-RETRY] transient failure for {:?}: {}",
-                                    path, err_msg
+                                    path,
+                                    err_msg
                                 );
                                 self.retry_after.insert(
                                     path.clone(),
@@ -2120,6 +4081,7 @@ impl eframe::App for GuiApp {
 
                     // Always remove from loading set
                     self.raw_loading.remove(&path);
+                    self.placeholder_cache.remove(&path);
                     ctx.request_repaint();
                 }
                 Err(crossbeam_channel::TryRecvError::Empty) => {
@@ -2150,6 +4112,14 @@ impl eframe::App for GuiApp {
                             for (i, file) in group[start_idx..].iter().enumerate() {
                                 self.file_index.insert(file.unique_file_id, start_idx + i);
                             }
+                            if let Some(target) = &self.goto_pending_file
+                                && let Some(pos) = group.iter().position(|f| &f.path == target)
+                            {
+                                self.state.current_file_idx = pos;
+                                self.state.selection_changed = true;
+                                self.dir_selection_idx = None;
+                                self.goto_pending_file = None;
+                            }
                         }
                         self.cache_dirty = true;
                     }
@@ -2158,6 +4128,7 @@ impl eframe::App for GuiApp {
                         // Scan complete - start enrichment for files missing GPS
                         self.state.is_loading = false;
                         self.dir_scan_rx = None;
+                        self.goto_pending_file = None;
                         self.state.last_file_count =
                             self.state.groups.first().map_or(0, |g| g.len());
 
@@ -2179,18 +4150,42 @@ impl eframe::App for GuiApp {
 
                             if !files_to_enrich.is_empty() {
                                 let (result_tx, result_rx) = unbounded::<EnrichmentResult>();
+                                let (progress_tx, progress_rx) = unbounded::<(usize, usize)>();
+                                self.enrichment_progress = (0, files_to_enrich.len());
                                 scanner::spawn_background_enrichment(
                                     files_to_enrich,
                                     self.ctx.content_key,
                                     self.ctx.meta_key,
                                     self.db_tx.clone(),
                                     result_tx,
+                                    Some(progress_tx),
                                 );
                                 self.enrichment_rx = Some(result_rx);
+                                self.enrichment_progress_rx = Some(progress_rx);
+                            }
+
+                            // Cheap header-only pass for files the cache had no resolution for
+                            let missing_resolution: Vec<_> = group
+                                .iter()
+                                .filter(|f| f.resolution.is_none())
+                                .map(|f| (f.path.clone(), f.unique_file_id))
+                                .collect();
+                            if !missing_resolution.is_empty() {
+                                let (res_tx, res_rx) = unbounded::<(u128, (u32, u32))>();
+                                scanner::spawn_resolution_fill(
+                                    missing_resolution,
+                                    self.ctx.clone(),
+                                    self.db_tx.clone(),
+                                    res_tx,
+                                );
+                                self.resolution_rx = Some(res_rx);
                             }
+                            self.start_similar_scan();
                         }
 
                         self.dir_scan_rx = None;
+                        // Explicit wake-up: the final state must be drawn even if idle.
+                        received_any = true;
                         break;
                     }
                 }
@@ -2204,6 +4199,11 @@ impl eframe::App for GuiApp {
         // This updates FileMetadata with computed content_hash and GPS coordinates
         // Database writing is handled by scanner::spawn_background_enrichment
         let mut enrichment_done = false;
+        if let Some(prog_rx) = &self.enrichment_progress_rx {
+            while let Ok(progress) = prog_rx.try_recv() {
+                self.enrichment_progress = progress;
+            }
+        }
         if let Some(ref rx) = self.enrichment_rx {
             let mut got_new_gps = false;
 
@@ -2220,12 +4220,45 @@ impl eframe::App for GuiApp {
                             }
                         }
 
-                        // O(1) lookup using file_index
-                        if let Some(&file_idx) = self.file_index.get(&result.unique_file_id)
-                            && let Some(group) = self.state.groups.first_mut()
+                        // O(1) lookup using file_index; a re-read file (Shift+R) may
+                        // also sit in any duplicate group
+                        let forced = self.reenrich_ids.remove(&result.unique_file_id);
+                        let location = self
+                            .file_index
+                            .get(&result.unique_file_id)
+                            .map(|&file_idx| (0, file_idx))
+                            .or_else(|| {
+                                if !forced {
+                                    return None;
+                                }
+                                self.state.groups.iter().enumerate().find_map(|(g, group)| {
+                                    group
+                                        .iter()
+                                        .position(|f| f.unique_file_id == result.unique_file_id)
+                                        .map(|f_idx| (g, f_idx))
+                                })
+                            });
+                        if let Some((group_idx, file_idx)) = location
+                            && let Some(group) = self.state.groups.get_mut(group_idx)
                             && let Some(file) = group.get_mut(file_idx)
                         {
                             file.content_hash = result.content_hash;
+                            if self.ctx.is_protected(&result.content_hash) {
+                                self.state.protected.insert(result.unique_file_id);
+                            }
+                            if let Some(rating) = self.ctx.get_rating(&result.content_hash) {
+                                self.ratings.insert(result.content_hash, rating);
+                            }
+                            if forced {
+                                // Drop what the old metadata left behind; the marker
+                                // comes back below if the file still has a position
+                                self.gps_map.remove_marker(&file.path);
+                                if self.cached_exif.as_ref().is_some_and(|(p, _)| *p == file.path) {
+                                    self.cached_exif = None;
+                                }
+                                file.gps_pos = result.gps_pos;
+                                file.exif_timestamp = result.exif_timestamp;
+                            }
                             if result.gps_pos.is_some() {
                                 file.gps_pos = result.gps_pos;
                                 got_new_gps = true;
@@ -2233,6 +4266,14 @@ impl eframe::App for GuiApp {
                             if result.exif_timestamp.is_some() {
                                 file.exif_timestamp = result.exif_timestamp;
                             }
+                            if let Some(features) = &result.features {
+                                file.exif_subsec_nanos = features.exif_subsec_nanos();
+                                file.exif_tz_offset = features.exif_tz_offset();
+                                self.cached_tags.insert(
+                                    result.content_hash,
+                                    CachedTags::from_features(features),
+                                );
+                            }
 
                             // Add GPS marker if we found coordinates
                             if let Some(pos) = result.gps_pos {
@@ -2267,7 +4308,9 @@ impl eframe::App for GuiApp {
             if got_new_gps && self.view_mode_sort.as_deref() == Some("location") {
                 // Sort immediately if enrichment finished, otherwise debounce to once/sec
                 let time_since_last = self.last_fs_refresh.elapsed();
-                if enrichment_done || time_since_last > Duration::from_millis(1000) {
+                if self.list_frozen {
+                    self.frozen_resort = true;
+                } else if enrichment_done || time_since_last > Duration::from_millis(1000) {
                     eprintln!("[GPS] apply_location_sort (Debounced)");
                     self.apply_location_sort();
                     self.last_fs_refresh = Instant::now(); // Reset timer
@@ -2278,9 +4321,78 @@ impl eframe::App for GuiApp {
         // Clean up the channel handle once fully processed
         if enrichment_done {
             self.enrichment_rx = None;
+            self.reenrich_ids.clear();
+            self.enrichment_progress_rx = None;
+            self.enrichment_progress = (0, 0);
+            ctx.request_repaint();
+        }
+
+        // Badge facts read from the feature cache
+        if let Some(ref rx) = self.cached_tags_rx {
+            let mut got_any = false;
+            let mut done = false;
+            loop {
+                match rx.try_recv() {
+                    Ok((content_hash, tags)) => {
+                        // A miss is asked for again on a later layout rebuild
+                        self.cached_tags_pending.remove(&content_hash);
+                        if let Some(tags) = tags {
+                            self.cached_tags.entry(content_hash).or_insert(tags);
+                            got_any = true;
+                        }
+                    }
+                    Err(crossbeam_channel::TryRecvError::Empty) => break,
+                    Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                        done = true;
+                        break;
+                    }
+                }
+            }
+            if done {
+                self.cached_tags_rx = None;
+                self.cached_tags_pending.clear();
+            }
+            if got_any {
+                if self.badge_filter.is_some() {
+                    self.refresh_list_filters();
+                }
+                ctx.request_repaint();
+            }
+        }
+
+        // Resolutions read from headers after the dir scan (view mode)
+        if let Some(ref rx) = self.resolution_rx {
+            let mut got_any = false;
+            let mut done = false;
+            loop {
+                match rx.try_recv() {
+                    Ok((unique_file_id, dims)) => {
+                        if let Some(&file_idx) = self.file_index.get(&unique_file_id)
+                            && let Some(group) = self.state.groups.first_mut()
+                            && let Some(file) = group.get_mut(file_idx)
+                            && file.resolution.is_none()
+                        {
+                            file.resolution = Some(dims);
+                            got_any = true;
+                        }
+                    }
+                    Err(crossbeam_channel::TryRecvError::Empty) => break,
+                    Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                        done = true;
+                        break;
+                    }
+                }
+            }
+            if done {
+                self.resolution_rx = None;
+            }
+            if got_any || done {
+                ctx.request_repaint();
+            }
         }
 
         self.check_reload(ctx);
+        self.check_group_rescan(ctx);
         self.perform_preload(ctx);
 
         let intent = RefCell::new(None::<InputIntent>);
@@ -2299,11 +4411,22 @@ impl eframe::App for GuiApp {
             } else {
                 // Duplicate mode: trigger full rescan
                 self.state.marked_for_deletion.clear();
+                self.state.marks_generation += 1;
                 self.state.is_loading = true;
             }
         }
 
+        self.apply_keeper_last_sort();
+
         // --- RENDER ---
+        if !self.state.view_mode
+            && self.show_action_bar
+            && !self.state.is_fullscreen
+            && !self.state.groups.is_empty()
+        {
+            self.render_action_bar(ui);
+        }
+
         let current_image_path = self.state.get_current_image_path().cloned();
         let current_group_idx = self.state.current_group_idx;
         let current_view_mode =
@@ -2319,6 +4442,7 @@ impl eframe::App for GuiApp {
                 } else {
                     let mode_str = match current_view_mode.mode {
                         ViewMode::FitWindow => "Fit Window",
+                        ViewMode::FitWindowNoUpscale => "Fit Window (no upscale)",
                         ViewMode::FitWidth => "Fit Width",
                         ViewMode::FitHeight => "Fit Height",
                         ViewMode::ManualZoom(_) => "Zoom",
@@ -2333,6 +4457,7 @@ impl eframe::App for GuiApp {
                             }
                         }
                         ViewMode::ManualZoom(z) => format!(" {:.0}x", z),
+                        ViewMode::FitWindowNoUpscale if self.fit_capped => " 1:1".to_string(),
                         _ => "".to_string(),
                     };
 
@@ -2365,19 +4490,15 @@ impl eframe::App for GuiApp {
 
                     let sort_str = if self.state.view_mode { " | [T] Sort" } else { "" };
                     let hist_str = if self.histogram_mode > 0 { " | [I] Hist" } else { "" };
+                    let crop_str = match self.crop_guide.checked_sub(1) {
+                        Some(i) => format!(" | [K] Crop {}", CROP_GUIDES[i].0),
+                        None => String::new(),
+                    };
                     let exif_str = if self.show_exif { " | [E] EXIF" } else { "" };
+                    let ghost_str = if self.ghost_overlay { " | [Shift+O] Ghost" } else { "" };
 
                     let pos_str = if !self.state.groups.is_empty() {
-                        let total: usize = self.state.groups.iter().map(|g| g.len()).sum();
-                        let current: usize = self
-                            .state
-                            .groups
-                            .iter()
-                            .take(self.state.current_group_idx)
-                            .map(|g| g.len())
-                            .sum::<usize>()
-                            + self.state.current_file_idx
-                            + 1;
+                        let (current, total) = self.state.file_position();
                         format!(" [{}/{}]", current, total)
                     } else {
                         "".to_string()
@@ -2391,7 +4512,7 @@ impl eframe::App for GuiApp {
 
                     ui.horizontal(|ui| {
                         ui.label(format!(
-                            "W: {}{} | Z: Zoom{}{}{}{}{}{}{}{}{}",
+                            "W: {}{} | Z: Zoom{}{}{}{}{}{}{}{}{}{}{}",
                             mode_str,
                             extra,
                             rel_tag,
@@ -2401,7 +4522,9 @@ impl eframe::App for GuiApp {
                             sort_str,
                             rot_str,
                             hist_str,
+                            crop_str,
                             exif_str,
+                            ghost_str,
                             gps_map_str
                         ));
                         ui.separator();
@@ -2464,9 +4587,10 @@ impl eframe::App for GuiApp {
                 self.initial_panel_width_applied = true;
                 // On the very first ready frame, install saved_panel_width as the live width
                 self.panel_width = self.saved_panel_width;
-                eprintln!(
+                debug_log!(
                     "[PANEL-DBG] initial apply: panel_width={:.1}, window_width={:.1}",
-                    self.panel_width, window_width
+                    self.panel_width,
+                    window_width
                 );
             }
 
@@ -2527,9 +4651,12 @@ impl eframe::App for GuiApp {
             }
 
             if force_panel_resize {
-                eprintln!(
+                debug_log!(
                     "[PANEL-DBG] force_panel_resize=true, applying panel_width={:.1} (window_ready={}, window_width={:.1}, max={:.1})",
-                    self.panel_width, window_ready, window_width, panel_max_width
+                    self.panel_width,
+                    window_ready,
+                    window_width,
+                    panel_max_width
                 );
             }
 
@@ -2571,6 +4698,18 @@ impl eframe::App for GuiApp {
                     ui.separator();
                 }
 
+                if self.list_frozen {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "\u{2744} List frozen, {} pending changes [Shift+L]",
+                            self.frozen_pending_count()
+                        ))
+                        .size(11.0)
+                        .color(egui::Color32::LIGHT_BLUE),
+                    );
+                    ui.separator();
+                }
+
                 // Calculate target scroll offset if we need to scroll to selected item
                 let scroll_to_file =
                     self.state.selection_changed && self.dir_selection_idx.is_none();
@@ -2599,6 +4738,7 @@ impl eframe::App for GuiApp {
                         }
 
                         let mut dir_to_open: Option<std::path::PathBuf> = None;
+                        let dir_double_click = self.gui_config.dir_double_click.unwrap_or(false);
 
                         // Calculate offset caused by directories
                         // In flatten mode, skip directory rendering entirely
@@ -2661,7 +4801,14 @@ impl eframe::App for GuiApp {
                                     egui::Color32::GRAY,
                                 );
 
-                                if resp.clicked() {
+                                if dir_double_click {
+                                    if resp.clicked() {
+                                        self.dir_selection_idx = Some(dir_idx);
+                                    }
+                                    if resp.double_clicked() {
+                                        dir_to_open = Some(entry.path.clone());
+                                    }
+                                } else if resp.clicked() {
                                     dir_to_open = Some(entry.path.clone());
                                 }
                                 if is_selected && scroll_to_dir {
@@ -2787,7 +4934,14 @@ impl eframe::App for GuiApp {
                                     egui::Color32::GRAY,
                                 );
 
-                                if resp.clicked() {
+                                if dir_double_click {
+                                    if resp.clicked() {
+                                        self.dir_selection_idx = Some(dir_idx);
+                                    }
+                                    if resp.double_clicked() {
+                                        dir_to_open = Some(entry.path.clone());
+                                    }
+                                } else if resp.clicked() {
                                     dir_to_open = Some(entry.path.clone());
                                 }
                                 if is_selected && scroll_to_dir {
@@ -2851,21 +5005,53 @@ impl eframe::App for GuiApp {
 
                         let show_headers = !self.state.view_mode;
 
+                        // Keyboard navigation into a collapsed group expands it
+                        if self.state.selection_changed
+                            && self.collapsed_groups.remove(&self.state.current_group_idx)
+                        {
+                            self.cache_dirty = true;
+                        }
+
                         // --- 2. REBUILD LAYOUT CACHE (Once per update if dirty) ---
-                        if self.cache_dirty || self.group_y_offsets.len() != self.state.groups.len()
+                        let file_count: usize = self.state.groups.iter().map(|g| g.len()).sum();
+                        if self.cache_dirty
+                            || self.group_y_offsets.len() != self.state.groups.len()
+                            || self.layout_file_count != file_count
                         {
+                            self.request_cached_tags();
+                            // Group indices shift when groups are added or removed
+                            if self.group_y_offsets.len() != self.state.groups.len() {
+                                self.collapsed_groups.clear();
+                            }
+                            self.list_rows = (!self.state.hidden.is_empty()).then(|| {
+                                (0..self.state.groups.len())
+                                    .map(|g| self.state.listed_indices(g))
+                                    .collect()
+                            });
                             self.group_y_offsets.clear();
                             self.group_y_offsets.reserve(self.state.groups.len());
                             let mut y = 0.0;
 
-                            for group in &self.state.groups {
+                            for (g_idx, group) in self.state.groups.iter().enumerate() {
                                 self.group_y_offsets.push(y);
+                                let rows =
+                                    self.list_rows.as_ref().map_or(group.len(), |r| r[g_idx].len());
+                                // A group whose files are all hidden takes no space
+                                if rows == 0 {
+                                    continue;
+                                }
                                 let header = if show_headers { header_height } else { 0.0 };
-                                let body = group.len() as f32 * file_row_total_h;
+                                let body = if show_headers && self.collapsed_groups.contains(&g_idx)
+                                {
+                                    0.0
+                                } else {
+                                    rows as f32 * file_row_total_h
+                                };
                                 let sep = if show_headers { separator_h } else { 0.0 };
                                 y += header + body + sep;
                             }
                             self.total_content_height = y;
+                            self.layout_file_count = file_count;
                             self.cache_dirty = false;
                         }
 
@@ -2875,7 +5061,15 @@ impl eframe::App for GuiApp {
                                 self.group_y_offsets.get(self.state.current_group_idx)
                         {
                             let header_offset = if show_headers { header_height } else { 0.0 };
-                            let file_offset = self.state.current_file_idx as f32 * file_row_total_h;
+                            let current = self.state.current_file_idx;
+                            let row = match &self.list_rows {
+                                Some(rows) => rows
+                                    .get(self.state.current_group_idx)
+                                    .and_then(|r| r.iter().position(|&f| f == current))
+                                    .unwrap_or(0),
+                                None => current,
+                            };
+                            let file_offset = row as f32 * file_row_total_h;
 
                             // Offset relative to content top (including directories)
                             let target_y_offset =
@@ -2945,14 +5139,31 @@ impl eframe::App for GuiApp {
                             }
                         };
 
+                        let mut toggle_collapse: Option<usize> = None;
                         let mut action_rename = false;
                         let mut action_delete = false;
                         let mut copy_path_target: Option<String> = None;
                         let mut copy_extended_target: Option<String> = None;
+                        let mut copy_group_target: Option<bool> = None;
+                        let mut copy_marked_target = false;
+                        let mut file_double_clicked = false;
+                        let mut open_with_target: Option<usize> = None;
+                        let mut reenrich_target = false;
+                        let open_with_entries = self.gui_config.open_with.clone();
 
                         // --- 6. RENDER LOOP ---
                         // Base absolute Y uses our safe captured coordinate
                         let start_y = files_start_pos.y;
+                        let oriented_resolution =
+                            self.gui_config.oriented_resolution.unwrap_or(true);
+                        let missing_tag_badges =
+                            self.gui_config.missing_tag_badges.unwrap_or(false);
+                        let show_aspect_ratio = self.gui_config.show_aspect_ratio.unwrap_or(false);
+                        let smaller_copy_ratio = if self.state.view_mode {
+                            0.0
+                        } else {
+                            self.ctx.grouping_config.smaller_copy_ratio
+                        };
 
                         for (g_idx, group) in self.state.groups.iter().enumerate().skip(start_idx) {
                             let group_y = self.group_y_offsets[g_idx];
@@ -2961,8 +5172,15 @@ impl eframe::App for GuiApp {
                             if current_y > clip_rect.max.y {
                                 break;
                             }
+                            // Listed rows: file indices when a filter hides some
+                            let listed = self.list_rows.as_ref().and_then(|r| r.get(g_idx));
+                            let row_count = listed.map_or(group.len(), |r| r.len());
+                            if row_count == 0 {
+                                continue;
+                            }
 
                             // Render Header
+                            let collapsed = show_headers && self.collapsed_groups.contains(&g_idx);
                             if show_headers {
                                 let info = &self.state.group_infos[g_idx];
                                 let header_rect = egui::Rect::from_min_size(
@@ -2989,13 +5207,33 @@ impl eframe::App for GuiApp {
                                             egui::Color32::YELLOW,
                                         ),
                                     };
-                                    ui.put(
+                                    let txt = if collapsed {
+                                        format!("[+] {} ({} files)", txt, row_count)
+                                    } else {
+                                        format!("[-] {}", txt)
+                                    };
+                                    let txt = if self
+                                        .reviewed_groups
+                                        .contains(&super::group_review::group_key(group))
+                                    {
+                                        format!("{} ✔ reviewed", txt)
+                                    } else {
+                                        txt
+                                    };
+                                    let resp = ui.put(
                                         header_rect,
-                                        egui::Label::new(egui::RichText::new(txt).color(col)),
+                                        egui::Label::new(egui::RichText::new(txt).color(col))
+                                            .sense(egui::Sense::click()),
                                     );
+                                    if resp.clicked() {
+                                        toggle_collapse = Some(g_idx);
+                                    }
                                 }
                                 current_y += header_height;
                             }
+                            if collapsed {
+                                continue;
+                            }
 
                             // --- JUMP TO FIRST VISIBLE FILE ---
                             let group_content_start_y = current_y;
@@ -3012,8 +5250,14 @@ impl eframe::App for GuiApp {
 
                             // Pre-calculate subgroups for this group
                             let content_subgroups = get_content_subgroups(group);
-
-                            for (f_idx, file) in group.iter().enumerate().skip(start_f_idx) {
+                            let near_subgroups = get_near_identical_subgroups(group);
+                            let luma_subgroups = get_luma_variant_subgroups(group);
+                            let mixed_profiles = get_mixed_profile_subgroups(group);
+                            let smaller_copies = get_smaller_copies(group, smaller_copy_ratio);
+
+                            for row in start_f_idx..row_count {
+                                let f_idx = listed.map_or(row, |r| r[row]);
+                                let Some(file) = group.get(f_idx) else { break };
                                 // 1. Calculate Rects
                                 let file_rect = egui::Rect::from_min_size(
                                     egui::pos2(files_start_pos.x, current_y),
@@ -3038,9 +5282,15 @@ impl eframe::App for GuiApp {
                                         hardlink_groups.contains_key(&file.unique_file_id);
 
                                     // Content Group ID
-                                    let content_id =
-                                        file.pixel_hash.and_then(|ph| content_subgroups.get(&ph));
+                                    let content_id = file
+                                        .subgroup_pixel_hash()
+                                        .and_then(|ph| content_subgroups.get(&ph));
                                     let is_content_identical = content_id.is_some();
+                                    let near_id = file
+                                        .near_identical
+                                        .and_then(|key| near_subgroups.get(&key));
+                                    let luma_id =
+                                        file.luma_variant.and_then(|key| luma_subgroups.get(&key));
 
                                     // --- LAYOUT ---
                                     // Two main rects: header_rect (marker + filename) and meta_rect (details)
@@ -3061,22 +5311,46 @@ impl eframe::App for GuiApp {
                                     let c_label = if self.state.view_mode {
                                         String::new()
                                     } else if let Some(id) = content_id {
-                                        format!("C{:<2} ", id) // e.g., "C4  "
+                                        match file.subgroup_pixel_hash() {
+                                            // Same pixels, other color profile: "C1 (sRGB) "
+                                            Some(ph) if mixed_profiles.contains(&ph) => format!(
+                                                "C{} ({}) ",
+                                                id,
+                                                file.icc_profile
+                                                    .as_ref()
+                                                    .map_or("no profile".to_string(), |p| p
+                                                        .label())
+                                            ),
+                                            _ => format!("C{:<2} ", id), // e.g., "C4  "
+                                        }
+                                    } else if let Some(id) = near_id {
+                                        format!("N{:<2} ", id) // near-identical, e.g. "N1  "
+                                    } else if let Some(id) = luma_id {
+                                        format!("L{:<2} ", id) // grayscale/color, e.g. "L1  "
                                     } else {
                                         "    ".to_string()
                                     };
 
+                                    let is_live_pair =
+                                        self.state.live_siblings.contains_key(&file.path);
+                                    let is_protected =
+                                        self.state.protected.contains(&file.unique_file_id);
+                                    let rating = self
+                                        .ratings
+                                        .get(&file.content_hash)
+                                        .map(|&r| format!("{} ", super::cull::rating_label(r)))
+                                        .unwrap_or_default();
                                     let marker_text = format!(
-                                        "{} {} {} ",
+                                        "{} {} {}{}{}{} ",
                                         if is_marked { "M" } else { " " },
                                         if is_hardlinked { "L" } else { " " },
+                                        if is_protected { "🔒 " } else { "" },
+                                        if is_live_pair { "P " } else { "" },
+                                        rating,
                                         c_label
                                     );
 
-                                    let filename_text = format_path_depth(
-                                        &file.path,
-                                        self.state.path_display_depth,
-                                    );
+                                    let filename_text = self.state.display_path(&file.path);
 
                                     // --- COLORS ---
                                     let (marker_color, filename_color) = if is_selected {
@@ -3092,6 +5366,8 @@ impl eframe::App for GuiApp {
                                         (Some(egui::Color32::GREEN), Some(egui::Color32::GREEN))
                                     } else if is_content_identical {
                                         (Some(egui::Color32::GOLD), Some(egui::Color32::GOLD))
+                                    } else if near_id.is_some() && !self.state.view_mode {
+                                        (Some(egui::Color32::KHAKI), Some(egui::Color32::KHAKI))
                                     } else {
                                         (None, None)
                                     };
@@ -3115,8 +5391,14 @@ impl eframe::App for GuiApp {
                                     let available_filename_width =
                                         (header_rect.width() - marker_width - padding).max(20.0);
 
-                                    // Truncate filename if needed
-                                    let (display_filename, was_truncated) = truncate_to_width(
+                                    // Truncate filename if needed; root-relative paths lose
+                                    // their start instead so the file name stays visible
+                                    let truncate = if self.state.path_relative_to_root {
+                                        truncate_to_width_left
+                                    } else {
+                                        truncate_to_width
+                                    };
+                                    let (display_filename, was_truncated) = truncate(
                                         &filename_text,
                                         available_filename_width,
                                         &font_id,
@@ -3133,8 +5415,9 @@ impl eframe::App for GuiApp {
                                     if let Some(current_file) =
                                         group.get(self.state.current_file_idx)
                                         && !is_selected
-                                        && current_file.pixel_hash.is_some()
-                                        && current_file.pixel_hash == file.pixel_hash
+                                        && current_file.subgroup_pixel_hash().is_some()
+                                        && current_file.subgroup_pixel_hash()
+                                            == file.subgroup_pixel_hash()
                                     {
                                         let bg = egui::Color32::from_black_alpha(40);
                                         marker_rich = marker_rich.strong().background_color(bg);
@@ -3203,9 +5486,11 @@ impl eframe::App for GuiApp {
                                                     if let Some(current_file) =
                                                         group.get(self.state.current_file_idx)
                                                         && !is_selected
-                                                        && current_file.pixel_hash.is_some()
-                                                        && current_file.pixel_hash
-                                                            == file.pixel_hash
+                                                        && current_file
+                                                            .subgroup_pixel_hash()
+                                                            .is_some()
+                                                        && current_file.subgroup_pixel_hash()
+                                                            == file.subgroup_pixel_hash()
                                                     {
                                                         let bg =
                                                             egui::Color32::from_black_alpha(40);
@@ -3246,6 +5531,10 @@ impl eframe::App for GuiApp {
                                         header_resp.scroll_to_me(Some(egui::Align::Center));
                                     }
 
+                                    if header_resp.double_clicked() || meta_resp.double_clicked() {
+                                        file_double_clicked = true;
+                                    }
+
                                     if any_clicked || any_sec_clicked {
                                         self.state.current_group_idx = g_idx;
                                         self.state.current_file_idx = f_idx;
@@ -3283,12 +5572,8 @@ impl eframe::App for GuiApp {
                                             "exif_timestamp: {}",
                                             file.exif_timestamp
                                                 .map(|ts| {
-                                                    chrono::DateTime::from_timestamp(ts, 0)
-                                                        .map(|dt| {
-                                                            dt.format("%Y-%m-%d %H:%M:%S")
-                                                                .to_string()
-                                                        })
-                                                        .unwrap_or_else(|| format!("{}", ts))
+                                                    self.display_tz()
+                                                        .format(ts, file.exif_tz_offset)
                                                 })
                                                 .unwrap_or_else(|| "None".to_string())
                                         ));
@@ -3331,12 +5616,18 @@ impl eframe::App for GuiApp {
 
                                     // Context Menu (Shared)
                                     let ctx_arc = self.ctx.clone();
+                                    let view_mode = self.state.view_mode;
+                                    let has_marks = !self.state.marked_for_deletion.is_empty();
                                     let context_menu_logic =
                                     |ui: &mut egui::Ui,
                                      action_rename: &mut bool,
                                      action_delete: &mut bool,
                                      copy_target: &mut Option<String>,
                                      copy_extended: &mut Option<String>,
+                                     copy_group: &mut Option<bool>,
+                                     copy_marked: &mut bool,
+                                     open_with: &mut Option<usize>,
+                                     reenrich: &mut bool,
                                      path: &std::path::Path,
                                      content_hash: &[u8; 32]| {
                                         if ui.button("Rename (R)").clicked() {
@@ -3363,6 +5654,45 @@ impl eframe::App for GuiApp {
                                                 b3
                                             ));
                                         }
+                                        if !view_mode {
+                                            if ui.button("Copy group paths (Shift+C)").clicked() {
+                                                ui.close();
+                                                *copy_group = Some(false);
+                                            }
+                                            if ui
+                                                .button("Copy group paths + size/resolution")
+                                                .clicked()
+                                            {
+                                                ui.close();
+                                                *copy_group = Some(true);
+                                            }
+                                            if has_marks && ui.button("Copy marked paths").clicked()
+                                            {
+                                                ui.close();
+                                                *copy_marked = true;
+                                            }
+                                        }
+                                        if !open_with_entries.is_empty() {
+                                            ui.menu_button("Open with", |ui| {
+                                                for (idx, entry) in
+                                                    open_with_entries.iter().enumerate()
+                                                {
+                                                    if ui.button(&entry.name).clicked() {
+                                                        ui.close();
+                                                        *open_with = Some(idx);
+                                                    }
+                                                }
+                                            });
+                                        }
+                                        let reenrich_label = if has_marks {
+                                            "Re-read metadata of marked (Shift+R)"
+                                        } else {
+                                            "Re-read metadata (Shift+R)"
+                                        };
+                                        if ui.button(reenrich_label).clicked() {
+                                            ui.close();
+                                            *reenrich = true;
+                                        }
                                         if ui.button("Delete (Del)").clicked() {
                                             ui.close();
                                             *action_delete = true;
@@ -3377,6 +5707,10 @@ impl eframe::App for GuiApp {
                                             &mut action_delete,
                                             &mut copy_path_target,
                                             &mut copy_extended_target,
+                                            &mut copy_group_target,
+                                            &mut copy_marked_target,
+                                            &mut open_with_target,
+                                            &mut reenrich_target,
                                             &file.path,
                                             &file.content_hash,
                                         )
@@ -3389,6 +5723,10 @@ impl eframe::App for GuiApp {
                                             &mut action_delete,
                                             &mut copy_path_target,
                                             &mut copy_extended_target,
+                                            &mut copy_group_target,
+                                            &mut copy_marked_target,
+                                            &mut open_with_target,
+                                            &mut reenrich_target,
                                             &file.path,
                                             &file.content_hash,
                                         )
@@ -3417,7 +5755,8 @@ impl eframe::App for GuiApp {
                                         file.exif_timestamp
                                             .and_then(|ts| {
                                                 chrono::DateTime::<chrono::Utc>::from_timestamp(
-                                                    ts, 0,
+                                                    ts - file.exif_tz_offset.unwrap_or(0) as i64,
+                                                    0,
                                                 )
                                             })
                                             .unwrap_or(file.modified)
@@ -3433,12 +5772,14 @@ impl eframe::App for GuiApp {
                                             ))
                                             .unwrap();
                                         format_relative_time(ts)
+                                    } else if use_exif_time && let Some(ts) = file.exif_timestamp {
+                                        self.display_tz().format(ts, file.exif_tz_offset)
                                     } else {
                                         display_time.format("%Y-%m-%d %H:%M:%S").to_string()
                                     };
 
                                     let res_str = file
-                                        .resolution
+                                        .display_resolution(oriented_resolution)
                                         .map(|(w, h)| {
                                             if w > MAX_TEXTURE_SIDE.try_into().unwrap()
                                                 || h > MAX_TEXTURE_SIDE.try_into().unwrap()
@@ -3448,7 +5789,71 @@ impl eframe::App for GuiApp {
                                                 format!(" {}x{}  ", w, h)
                                             }
                                         })
-                                        .unwrap_or_default();
+                                        .unwrap_or_else(|| {
+                                            // Nothing decoded (e.g. --name-dupes): show content hash
+                                            if self.state.view_mode {
+                                                String::new()
+                                            } else {
+                                                format!("{}  ", crate::short_content_hash(file))
+                                            }
+                                        });
+
+                                    // HEIC/HEIF depth map (portrait shots), from the
+                                    // cached features
+                                    let has_depth_map = self
+                                        .cached_tags
+                                        .get(&file.content_hash)
+                                        .is_some_and(|t| t.depth_map);
+                                    let res_str = if has_depth_map {
+                                        format!("{} D", res_str.trim_end())
+                                    } else {
+                                        res_str
+                                    };
+                                    let res_str = match file.aspect_ratio() {
+                                        Some(ratio) if show_aspect_ratio => {
+                                            format!("{} {:.2}", res_str.trim_end(), ratio)
+                                        }
+                                        _ => res_str,
+                                    };
+                                    // JXL: "LL" lossless, "A" animated
+                                    let jxl_label = self
+                                        .cached_tags
+                                        .get(&file.content_hash)
+                                        .and_then(|t| t.jxl)
+                                        .and_then(|info| info.list_label());
+                                    let res_str = match jxl_label {
+                                        Some(label) => format!("{} {}", res_str.trim_end(), label),
+                                        None => res_str,
+                                    };
+
+                                    let mut missing_badge = if missing_tag_badges {
+                                        ListBadge::ALL
+                                            .into_iter()
+                                            .filter(|&tag| {
+                                                shows_badge(&self.cached_tags, file, tag)
+                                            })
+                                            .map(ListBadge::label)
+                                            .collect::<Vec<_>>()
+                                            .join(" ")
+                                    } else {
+                                        String::new()
+                                    };
+                                    // Lower-resolution copy of another member of the group
+                                    if let Some(larger) =
+                                        smaller_copies.get(&f_idx).and_then(|&j| group.get(j))
+                                    {
+                                        if !missing_badge.is_empty() {
+                                            missing_badge.push(' ');
+                                        }
+                                        missing_badge.push_str(&format!(
+                                            "smaller copy of {}",
+                                            larger
+                                                .path
+                                                .file_name()
+                                                .unwrap_or_default()
+                                                .to_string_lossy()
+                                        ));
+                                    }
 
                                     let w_meta = meta_rect.width();
                                     let h_meta = meta_rect.height();
@@ -3490,6 +5895,17 @@ impl eframe::App for GuiApp {
                                         ),
                                         |ui| {
                                             ui.label(make_text(time_str));
+                                            if !missing_badge.is_empty() {
+                                                ui.label(
+                                                    egui::RichText::new(format!(
+                                                        " {}",
+                                                        missing_badge
+                                                    ))
+                                                    .size(10.0)
+                                                    .family(egui::FontFamily::Monospace)
+                                                    .color(egui::Color32::from_rgb(220, 150, 60)),
+                                                );
+                                            }
                                         },
                                     );
 
@@ -3516,12 +5932,34 @@ impl eframe::App for GuiApp {
                             }
                         }
 
+                        if let Some(g_idx) = toggle_collapse {
+                            if !self.collapsed_groups.remove(&g_idx) {
+                                self.collapsed_groups.insert(g_idx);
+                            }
+                            self.cache_dirty = true;
+                        }
+
                         // Execute Context Menu Actions (Outside Loop)
                         if let Some(text) = copy_extended_target {
                             ctx.copy_text(text);
                         } else if let Some(path) = copy_path_target {
                             ctx.copy_text(path);
                         }
+                        if let Some(details) = copy_group_target {
+                            self.copy_group_paths(ctx, details);
+                        }
+                        if copy_marked_target {
+                            self.copy_marked_paths(ctx);
+                        }
+                        if file_double_clicked {
+                            self.run_double_click_action();
+                        }
+                        if let Some(idx) = open_with_target {
+                            self.run_open_with(idx);
+                        }
+                        if reenrich_target {
+                            self.reenrich_selection();
+                        }
 
                         if action_rename {
                             if let Some(path) = self.state.get_current_image_path() {
@@ -3550,9 +5988,10 @@ impl eframe::App for GuiApp {
             if window_width > 400.0 {
                 let egui_reported = panel_response.response.rect.width();
                 if (egui_reported - self.panel_width).abs() > 1.0 {
-                    eprintln!(
+                    debug_log!(
                         "[PANEL-DBG] frame end: our panel_width={:.1}, egui reported={:.1} (DIVERGED)",
-                        self.panel_width, egui_reported
+                        self.panel_width,
+                        egui_reported
                     );
                 }
                 // NOTE: Do NOT overwrite self.panel_width from the response in egui 0.34.2.
@@ -3564,6 +6003,7 @@ impl eframe::App for GuiApp {
 
         // GPS Map Panel (right side, when visible)
         let mut map_clicked_path: Option<std::path::PathBuf> = None;
+        let mut fit_group_clicked = false;
         if self.gps_map.visible {
             egui::Panel::right("gps_map_panel")
                 .resizable(true)
@@ -3580,7 +6020,7 @@ impl eframe::App for GuiApp {
                         egui::ComboBox::from_id_salt("provider_selector")
                             .selected_text(&current_provider)
                             .show_ui(ui, |ui| {
-                                for (name, url) in &self.ctx.map_providers {
+                                for (name, url) in &self.map_providers {
                                     let is_selected = current_provider == *name;
                                     if ui.selectable_label(is_selected, name).clicked() {
                                         self.gps_map.set_provider(name.clone(), url.clone(), ctx);
@@ -3589,6 +6029,26 @@ impl eframe::App for GuiApp {
                                     }
                                 }
                             });
+                        // Tile download activity for the visible region
+                        if self.gps_map.tiles_paused {
+                            if ui
+                                .small_button("Retry")
+                                .on_hover_text("Download the visible map tiles again")
+                                .clicked()
+                            {
+                                self.gps_map.retry_tiles(ctx);
+                            }
+                        } else if self.gps_map.tiles_in_flight > 0 {
+                            ui.spinner();
+                            ui.label(format!("{} tiles", self.gps_map.tiles_in_flight));
+                            if ui
+                                .small_button("Cancel")
+                                .on_hover_text("Stop downloading map tiles")
+                                .clicked()
+                            {
+                                self.gps_map.cancel_tiles();
+                            }
+                        }
                     });
 
                     // Location selector dropdown
@@ -3606,7 +6066,11 @@ impl eframe::App for GuiApp {
                                 if ui.selectable_label(current_loc == "None", "None").clicked() {
                                     self.gps_map.selected_location = None;
                                 }
-                                for (name, point) in &self.ctx.locations {
+                                let locations = match self.ctx.locations.read() {
+                                    Ok(l) => l,
+                                    Err(_) => return,
+                                };
+                                for (name, point) in locations.iter() {
                                     let is_selected = self
                                         .gps_map
                                         .selected_location
@@ -3621,6 +6085,113 @@ impl eframe::App for GuiApp {
                             });
                     });
 
+                    // Pin current image's GPS or a clicked map point as a named location
+                    ui.horizontal(|ui| {
+                        let image_point = self
+                            .state
+                            .groups
+                            .get(self.state.current_group_idx)
+                            .and_then(|g| g.get(self.state.current_file_idx))
+                            .and_then(|f| {
+                                f.gps_pos.or_else(|| {
+                                    self.gps_map
+                                        .get_marker_by_path(&f.path)
+                                        .map(|m| Point::new(m.lon, m.lat))
+                                })
+                            });
+                        let picked_point =
+                            self.gps_map.picked_point.map(|p| Point::new(p.x(), p.y()));
+                        if ui
+                            .add_enabled(image_point.is_some(), egui::Button::new("📌 Pin image"))
+                            .on_hover_text("Save the current image's GPS position as a location")
+                            .clicked()
+                        {
+                            self.pin_location = image_point;
+                            self.pin_location_input.clear();
+                            self.pin_location_focus_requested = false;
+                        }
+                        if ui
+                            .add_enabled(picked_point.is_some(), egui::Button::new("📌 Pin point"))
+                            .on_hover_text("Save the point last clicked on the map as a location")
+                            .clicked()
+                        {
+                            self.pin_location = picked_point;
+                            self.pin_location_input.clear();
+                            self.pin_location_focus_requested = false;
+                        }
+                    });
+
+                    // Write the clicked point or the selected location into the EXIF
+                    // of the marked files (or the current file)
+                    ui.horizontal(|ui| {
+                        let picked_point =
+                            self.gps_map.picked_point.map(|p| Point::new(p.x(), p.y()));
+                        let location = self.gps_map.selected_location.clone();
+                        if ui
+                            .add_enabled(picked_point.is_some(), egui::Button::new("✏ Write point"))
+                            .on_hover_text(
+                                "Write the point last clicked on the map into the GPS tags of the marked files",
+                            )
+                            .clicked()
+                            && let Some(point) = picked_point
+                        {
+                            self.write_gps = Some(super::dialogs::plan_write_gps(
+                                self,
+                                point,
+                                "map point".to_string(),
+                            ));
+                        }
+                        if ui
+                            .add_enabled(location.is_some(), egui::Button::new("✏ Write location"))
+                            .on_hover_text(
+                                "Write the selected saved location into the GPS tags of the marked files",
+                            )
+                            .clicked()
+                            && let Some((name, point)) = location
+                        {
+                            self.write_gps = Some(super::dialogs::plan_write_gps(
+                                self,
+                                point,
+                                format!("location \"{}\"", name),
+                            ));
+                        }
+                    });
+
+                    // Fit controls
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Fit all")
+                            .on_hover_text("Zoom to show every geotagged file")
+                            .clicked()
+                        {
+                            let n = self.gps_map.fit_all_markers();
+                            if n == 0 {
+                                self.set_status("No GPS markers to fit".to_string(), true);
+                            }
+                        }
+                        if ui
+                            .button("Fit group (A)")
+                            .on_hover_text("Zoom to the geotagged files of the current group")
+                            .clicked()
+                        {
+                            fit_group_clicked = true;
+                        }
+                        egui::ComboBox::from_id_salt("marker_coloring")
+                            .selected_text(self.gps_map.coloring.name())
+                            .show_ui(ui, |ui| {
+                                for mode in super::gps_map::MarkerColoring::ALL {
+                                    ui.selectable_value(
+                                        &mut self.gps_map.coloring,
+                                        mode,
+                                        mode.name(),
+                                    );
+                                }
+                            });
+                    });
+                    if self.gps_map.coloring == super::gps_map::MarkerColoring::Camera {
+                        self.resolve_marker_cameras();
+                    }
+
                     ui.separator();
 
                     // Display movement info from previous image
@@ -3663,6 +6234,10 @@ impl eframe::App for GuiApp {
                 });
         }
 
+        if fit_group_clicked {
+            self.fit_map_to_current_group();
+        }
+
         // Handle map click navigation
         if let Some(clicked_path) = map_clicked_path {
             // Find the file in our groups and navigate to it
@@ -3721,6 +6296,15 @@ impl eframe::App for GuiApp {
                     let since_last = Instant::now().duration_since(last_frame_time);
                     let remaining = next_duration.saturating_sub(since_last);
                     ctx.request_repaint_after(remaining);
+                } else if let Some(src) = super::image::raw_wb_source(self, &path) {
+                    // RAW shown with a non-camera white balance (Shift+W)
+                    super::image::render_image_texture(
+                        self,
+                        ui,
+                        src,
+                        available_rect,
+                        current_group_idx,
+                    );
                 } else if let Some(src) = self.gpu_cache.get(&path).map(|gpu| {
                     // Cloned out of the map so the borrow of self ends here and
                     // render_image_texture can take &mut self.
@@ -3776,10 +6360,28 @@ impl eframe::App for GuiApp {
                     });
                 } else {
                     // 3. Not in cache and not failed? It's loading.
-                    ui.centered_and_justified(|ui| {
-                        ui.spinner();
-                        ui.label("Loading...");
-                    });
+                    if let Some((texture, orientation)) = self.placeholder_cache.get(&path) {
+                        // Low-res EXIF thumbnail until the full decode arrives
+                        super::image::render_placeholder(
+                            self,
+                            ui,
+                            texture,
+                            *orientation,
+                            available_rect,
+                        );
+                        ui.put(
+                            egui::Rect::from_min_size(
+                                available_rect.min + egui::vec2(10.0, 10.0),
+                                egui::vec2(20.0, 20.0),
+                            ),
+                            egui::Spinner::new(),
+                        );
+                    } else {
+                        ui.centered_and_justified(|ui| {
+                            ui.spinner();
+                            ui.label("Loading...");
+                        });
+                    }
 
                     // Trigger load if missed (failsafe)
                     if !self.raw_loading.contains(&path) {
@@ -3795,6 +6397,10 @@ impl eframe::App for GuiApp {
                 // Filename Overlay
                 if self.state.is_fullscreen {
                     let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    let name = match &self.cull {
+                        Some(cull) if !cull.finished => super::cull::footer(self, &name),
+                        _ => name.into_owned(),
+                    };
                     let overlay_rect = egui::Rect::from_min_size(
                         egui::pos2(available_rect.min.x + 10.0, available_rect.max.y - 25.0),
                         egui::vec2(available_rect.width() - 20.0, 20.0),
@@ -3810,6 +6416,61 @@ impl eframe::App for GuiApp {
                     );
                 }
 
+                // Reference folder badge (--reference)
+                if let Some(reference) = &self.reference {
+                    let current = self
+                        .state
+                        .groups
+                        .get(current_group_idx)
+                        .and_then(|g| g.get(self.state.current_file_idx))
+                        .map(|f| reference.lookup(&f.content_hash, f.unique_file_id));
+                    let badge = match current {
+                        Some(Some(crate::reference::ReferenceMatch::BitIdentical)) => {
+                            Some(("★ In reference".to_string(), egui::Color32::GREEN))
+                        }
+                        Some(Some(crate::reference::ReferenceMatch::PixelIdentical)) => {
+                            Some(("★ In reference (same pixels)".to_string(), egui::Color32::GOLD))
+                        }
+                        _ if !reference.done => Some((
+                            format!("Reference {}/{}…", reference.loaded, reference.total),
+                            egui::Color32::GRAY,
+                        )),
+                        _ => None,
+                    };
+                    if let Some((text, color)) = badge {
+                        let painter = ui.painter();
+                        let galley =
+                            painter.layout_no_wrap(text, egui::FontId::proportional(16.0), color);
+                        let rect = egui::Align2::CENTER_TOP
+                            .anchor_size(
+                                egui::pos2(available_rect.center().x, available_rect.min.y + 10.0),
+                                galley.size(),
+                            )
+                            .expand(4.0);
+                        painter.rect_filled(rect, 4.0, egui::Color32::from_black_alpha(150));
+                        painter.galley(rect.min + egui::vec2(4.0, 4.0), galley, color);
+                    }
+                }
+
+                // Near-duplicate in the same directory (view mode), below the reference badge
+                if let Some((idx, dist)) = self.nearest_similar()
+                    && let Some(other) = self.state.groups.first().and_then(|g| g.get(idx))
+                {
+                    let name = other.path.file_name().unwrap_or_default().to_string_lossy();
+                    let text = format!("dup? {} (distance {}, J)", name, dist);
+                    let color = egui::Color32::LIGHT_BLUE;
+                    let top =
+                        available_rect.min.y + if self.reference.is_some() { 40.0 } else { 10.0 };
+                    let painter = ui.painter();
+                    let galley =
+                        painter.layout_no_wrap(text, egui::FontId::proportional(14.0), color);
+                    let rect = egui::Align2::CENTER_TOP
+                        .anchor_size(egui::pos2(available_rect.center().x, top), galley.size())
+                        .expand(4.0);
+                    painter.rect_filled(rect, 4.0, egui::Color32::from_black_alpha(150));
+                    painter.galley(rect.min + egui::vec2(4.0, 4.0), galley, color);
+                }
+
                 // Histogram Overlay (toggle with 'I' key)
                 if self.histogram_mode > 0 {
                     super::image::render_histogram(self, ui, available_rect, &path);
@@ -3844,5 +6505,14 @@ impl eframe::App for GuiApp {
         if size.0 > 100 && size.1 > 100 && ppp > 0.0 && ppp.is_finite() && !is_maximized {
             self.last_window_size = Some(size);
         }
+
+        // Idle repaint throttle: keep polling only while background work is in
+        // flight. Otherwise wait for input, except to clear a pending status message.
+        if self.has_background_work() {
+            ctx.request_repaint_after(self.repaint_poll);
+        } else if let Some(set_time) = self.state.status_set_time {
+            let timeout = Duration::from_secs(2);
+            ctx.request_repaint_after(timeout.saturating_sub(set_time.elapsed()));
+        }
     }
 }
@@ -0,0 +1,121 @@
+//! Cache panel (Ctrl+I): what the feature database holds and how large it is, how
+//! much of the last scan came from it, and the two maintenance actions: compacting
+//! (sweeping entries no cached file refers to) and clearing it altogether, which
+//! makes the next scan read and hash every file again.
+
+use eframe::egui;
+
+use super::app::GuiApp;
+use crate::db::DbStats;
+use crate::format_size;
+
+/// State of the open cache panel.
+pub(super) struct CachePanel {
+    stats: Result<DbStats, String>,
+    /// "Clear cache…" was clicked and awaits its confirmation
+    confirm_clear: bool,
+}
+
+impl CachePanel {
+    pub(super) fn open(app: &GuiApp) -> Self {
+        Self { stats: app.ctx.db_stats().map_err(|e| e.to_string()), confirm_clear: false }
+    }
+}
+
+/// The panel window. Esc or Close closes it.
+pub(super) fn show(app: &mut GuiApp, ctx: &egui::Context) {
+    let Some(panel) = app.cache_panel.as_mut() else { return };
+    let mut refresh = false;
+    let mut compact = false;
+    let mut clear = false;
+    let mut close = false;
+
+    egui::Window::new("Cache").collapsible(false).resizable(false).show(ctx, |ui| {
+        match &panel.stats {
+            Ok(stats) => {
+                egui::Grid::new("cache_stats").num_columns(2).striped(true).show(ui, |ui| {
+                    for (label, value) in [
+                        ("Cached files", stats.files.to_string()),
+                        ("Content hashes", stats.hashes.to_string()),
+                        ("Features", stats.features.to_string()),
+                        ("Coefficients", stats.coefficients.to_string()),
+                        ("Pixel hashes", stats.pixel_hashes.to_string()),
+                        ("Data", format_size(stats.used_bytes)),
+                        ("File on disk", format_size(stats.file_bytes)),
+                        ("Map size (db_size_mb)", format_size(stats.map_bytes)),
+                    ] {
+                        ui.label(label);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                    ui.label("Last scan");
+                    ui.label(match crate::scanner::last_scan_cache() {
+                        Some((files, cached)) => format!(
+                            "{} of {} files from the cache ({:.1}%)",
+                            cached,
+                            files,
+                            cached as f64 * 100.0 / files as f64
+                        ),
+                        None => "no duplicate scan in this session".to_string(),
+                    });
+                    ui.end_row();
+                });
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, format!("Cannot read the cache: {}", e));
+            }
+        }
+        ui.separator();
+
+        if panel.confirm_clear {
+            ui.label("Clear the whole cache? The next scan reads and hashes every file again.");
+            ui.label("Ignored, protected and rated files are kept.");
+            ui.horizontal(|ui| {
+                clear = ui.button("Clear cache").clicked();
+                if ui.button("Cancel").clicked() {
+                    panel.confirm_clear = false;
+                }
+            });
+        } else {
+            ui.horizontal(|ui| {
+                refresh = ui.button("Refresh").clicked();
+                compact = ui
+                    .button("Compact")
+                    .on_hover_text(
+                        "Drop hashes and features no cached file refers to any more. The \
+                         freed space is reused; the file only shrinks with `mdb_copy -c`.",
+                    )
+                    .clicked();
+                if ui.button("Clear cache…").clicked() {
+                    panel.confirm_clear = true;
+                }
+                close = ui.button("Close").clicked();
+            });
+        }
+    });
+
+    if compact {
+        match app.ctx.compact() {
+            Ok(n) => {
+                app.set_status(format!("Compacted the cache: {} unused hashes dropped", n), false)
+            }
+            Err(e) => app.set_status(format!("Compacting the cache failed: {}", e), true),
+        }
+        refresh = true;
+    }
+    if clear {
+        match app.ctx.clear_cache() {
+            Ok(()) => app.set_status(
+                "Cache cleared: the next scan hashes every file again".to_string(),
+                false,
+            ),
+            Err(e) => app.set_status(format!("Clearing the cache failed: {}", e), true),
+        }
+        refresh = true;
+    }
+    if close {
+        app.cache_panel = None;
+    } else if refresh {
+        app.cache_panel = Some(CachePanel::open(app));
+    }
+}
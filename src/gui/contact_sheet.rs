@@ -0,0 +1,252 @@
+//! Contact sheet export: a grid of thumbnails with the file names underneath,
+//! written as a single PNG or JPEG for sharing a selection.
+//!
+//! Thumbnails come from the cheap paths (embedded RAW previews, scaled JPEG
+//! decodes). Labels are laid out by epaint with the GUI's font definitions and
+//! copied from its glyph atlas, so they look exactly like the names on screen.
+
+use eframe::egui;
+use egui::epaint::text::{Fonts, FontsView, Galley};
+use egui::epaint::{AlphaFromCoverage, Color32, ColorImage};
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+use rayon::prelude::*;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::scanner;
+
+const PADDING: u32 = 8;
+const LABEL_HEIGHT: u32 = 20;
+const LABEL_PX: f32 = 13.0;
+const BACKGROUND: Rgb<u8> = Rgb([32, 32, 32]);
+const FOREGROUND: Rgb<u8> = Rgb([220, 220, 220]);
+const PLACEHOLDER: Rgb<u8> = Rgb([64, 64, 64]);
+/// Largest glyph atlas the label fonts may grow to
+const ATLAS_SIDE: usize = 8192;
+/// Largest width or height a baseline JPEG can store
+pub(super) const JPEG_MAX_SIDE: u32 = 65535;
+
+/// Pixel size of the sheet for `count` files in a `cols`-wide grid.
+pub(super) fn sheet_size(count: usize, cols: u32, cell_size: u32) -> (u32, u32) {
+    let cols = cols.clamp(1, count.max(1) as u32);
+    let rows = (count as u32).div_ceil(cols);
+    (cols * (cell_size + PADDING) + PADDING, rows * (cell_size + LABEL_HEIGHT + PADDING) + PADDING)
+}
+
+/// Render `files` as a `cols`-wide grid of `cell_size` px thumbnails, each with its
+/// file name below, and encode it to `writer` as `format`. Files that can't be
+/// decoded get a crossed-out placeholder cell so the grid stays aligned.
+pub(super) fn export_contact_sheet<W: Write + Seek>(
+    files: &[PathBuf],
+    cols: u32,
+    cell_size: u32,
+    fonts: egui::FontDefinitions,
+    format: ImageFormat,
+    writer: &mut W,
+) -> Result<(), String> {
+    if files.is_empty() {
+        return Err("No files to export".to_string());
+    }
+    let cols = cols.clamp(1, files.len() as u32);
+    let cell_w = cell_size + PADDING;
+    let cell_h = cell_size + LABEL_HEIGHT + PADDING;
+    let (width, height) = sheet_size(files.len(), cols, cell_size);
+    let mut sheet = RgbImage::from_pixel(width, height, BACKGROUND);
+
+    let thumbs: Vec<Option<RgbImage>> =
+        files.par_iter().map(|path| load_thumbnail(path, cell_size)).collect();
+
+    // Light text on a dark background, like the dark theme
+    let mut fonts = Fonts::new(ATLAS_SIDE, AlphaFromCoverage::DARK_MODE_DEFAULT, fonts);
+    let mut view = fonts.with_pixels_per_point(1.0);
+    let labels: Vec<Arc<Galley>> = files
+        .iter()
+        .map(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            layout_label(&mut view, &name, cell_size as f32)
+        })
+        .collect();
+    let atlas = view.image();
+
+    for (i, (thumb, label)) in thumbs.iter().zip(&labels).enumerate() {
+        let x = PADDING + (i as u32 % cols) * cell_w;
+        let y = PADDING + (i as u32 / cols) * cell_h;
+        match thumb {
+            // Centered in the square cell
+            Some(t) => image::imageops::overlay(
+                &mut sheet,
+                t,
+                (x + (cell_size - t.width()) / 2) as i64,
+                (y + (cell_size - t.height()) / 2) as i64,
+            ),
+            None => draw_placeholder(&mut sheet, x, y, cell_size),
+        }
+        draw_label(&mut sheet, &atlas, label, x, y + cell_size, cell_size);
+    }
+
+    DynamicImage::ImageRgb8(sheet).write_to(writer, format).map_err(|e| e.to_string())
+}
+
+/// Decode a thumbnail no larger than `size` x `size`, upright. RAWs use their
+/// embedded preview, JPEGs a scaled decode; the EXIF thumbnail is the last resort.
+pub(super) fn load_thumbnail(path: &Path, size: u32) -> Option<RgbImage> {
+    let img = decode_upright(path, size).or_else(|| {
+        let (img, orientation) = super::image::load_exif_thumbnail(path)?;
+        let mut img = color_image_to_dynamic(&img);
+        if let Some(o) = Orientation::from_exif(orientation) {
+            img.apply_orientation(o);
+        }
+        Some(img)
+    })?;
+    Some(flatten_alpha(&img.thumbnail(size, size), BACKGROUND))
+}
+
+/// Decode an image upright, cheaply where its longest edge can stay at `size` or
+/// more: RAWs give their embedded preview, JPEGs a scaled decode. None when it
+/// can't be decoded at all.
+pub(super) fn decode_upright(path: &Path, size: u32) -> Option<DynamicImage> {
+    let bytes = crate::archive::read(path).ok()?;
+    decode_upright_from(path, &bytes, size)
+}
+
+/// `decode_upright` for a file already read into `bytes`.
+pub(super) fn decode_upright_from(path: &Path, bytes: &[u8], size: u32) -> Option<DynamicImage> {
+    let (mut img, orientation) = if scanner::is_raw_ext(path) {
+        rsraw::RawImage::open(bytes)
+            .ok()
+            .and_then(|mut raw| super::image::extract_best_thumbnail(&mut raw))
+            .map(|(img, orientation)| (color_image_to_dynamic(&img), orientation))
+            .or_else(|| {
+                let jpeg = crate::raw_exif::extract_embedded_jpeg(bytes)?;
+                let img = image::load_from_memory(jpeg).ok()?;
+                Some((img, scanner::get_orientation(path, Some(bytes))))
+            })?
+    } else {
+        let img = scanner::load_jpeg_scaled(path, bytes, size)
+            .map(|(img, _)| img)
+            .or_else(|| scanner::load_image_fast(path, bytes).ok())?;
+        let orientation = if scanner::orientation_baked_into_pixels(path) {
+            1
+        } else {
+            scanner::get_orientation(path, Some(bytes))
+        };
+        (img, orientation)
+    };
+    if let Some(o) = Orientation::from_exif(orientation) {
+        img.apply_orientation(o);
+    }
+    Some(img)
+}
+
+/// Composite a possibly transparent image over `bg`. Dropping the alpha channel
+/// instead would show whatever colour the encoder left in transparent areas.
+pub(super) fn flatten_alpha(img: &DynamicImage, bg: Rgb<u8>) -> RgbImage {
+    if !img.color().has_alpha() {
+        return img.to_rgb8();
+    }
+    let rgba = img.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let p = rgba.get_pixel(x, y).0;
+        let a = p[3] as u32;
+        Rgb(std::array::from_fn(|ch| {
+            ((p[ch] as u32 * a + bg.0[ch] as u32 * (255 - a) + 127) / 255) as u8
+        }))
+    })
+}
+
+fn color_image_to_dynamic(img: &egui::ColorImage) -> DynamicImage {
+    let [w, h] = img.size;
+    let rgb = RgbImage::from_fn(w as u32, h as u32, |x, y| {
+        let c = img.pixels[y as usize * w + x as usize];
+        Rgb([c.r(), c.g(), c.b()])
+    });
+    DynamicImage::ImageRgb8(rgb)
+}
+
+/// Filled square with both diagonals, standing in for an undecodable file.
+fn draw_placeholder(sheet: &mut RgbImage, x: u32, y: u32, size: u32) {
+    for dy in 0..size {
+        for dx in 0..size {
+            let on_diagonal = dx.abs_diff(dy) <= 1 || dx.abs_diff(size - 1 - dy) <= 1;
+            let color = if on_diagonal { BACKGROUND } else { PLACEHOLDER };
+            sheet.put_pixel(x + dx, y + dy, color);
+        }
+    }
+}
+
+/// Lay out `text` on one line, shortened in the middle (keeping the extension)
+/// when it is wider than `max_width`.
+fn layout_label(view: &mut FontsView, text: &str, max_width: f32) -> Arc<Galley> {
+    let font = egui::FontId::proportional(LABEL_PX);
+    let full = view.layout_no_wrap(text.to_string(), font.clone(), Color32::WHITE);
+    if full.size().x <= max_width {
+        return full;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    // Keep `keep` characters around the ellipsis, the odd one at the end
+    let shortened = |keep: usize| -> String {
+        let head = keep / 2;
+        let tail = chars.len() - (keep - head);
+        chars[..head].iter().chain(['…'].iter()).chain(&chars[tail..]).collect()
+    };
+    // Widths grow with `keep`: find the most that still fits
+    let (mut fits, mut too_wide) = (0, chars.len());
+    while too_wide - fits > 1 {
+        let mid = (fits + too_wide) / 2;
+        let galley = view.layout_no_wrap(shortened(mid), font.clone(), Color32::WHITE);
+        if galley.size().x <= max_width {
+            fits = mid;
+        } else {
+            too_wide = mid;
+        }
+    }
+    view.layout_no_wrap(shortened(fits), font, Color32::WHITE)
+}
+
+/// Draw a laid-out label centered below the cell, blending glyph coverage from
+/// the font atlas. Only pixels inside the label area are touched.
+fn draw_label(
+    sheet: &mut RgbImage,
+    atlas: &ColorImage,
+    galley: &Galley,
+    x: u32,
+    y: u32,
+    max_width: u32,
+) {
+    let left = x as f32 + ((max_width as f32 - galley.size().x) / 2.0).max(0.0);
+    let top = y as f32 + ((LABEL_HEIGHT as f32 - galley.size().y) / 2.0).max(0.0);
+    let x_end = (x + max_width).min(sheet.width());
+    let y_end = (y + LABEL_HEIGHT).min(sheet.height());
+    for placed in &galley.rows {
+        for glyph in &placed.row.glyphs {
+            let uv = glyph.uv_rect;
+            if uv.is_nothing() {
+                continue;
+            }
+            // At one pixel per point, atlas texels map 1:1 to sheet pixels
+            let pos = placed.pos + glyph.pos.to_vec2() + uv.offset;
+            let gx = (left + pos.x).round() as i64 - uv.min[0] as i64;
+            let gy = (top + pos.y).round() as i64 - uv.min[1] as i64;
+            for v in uv.min[1]..uv.max[1] {
+                for u in uv.min[0]..uv.max[0] {
+                    let (px, py) = (gx + u as i64, gy + v as i64);
+                    if px < x as i64 || py < y as i64 || px >= x_end as i64 || py >= y_end as i64 {
+                        continue;
+                    }
+                    let alpha = atlas[(u as usize, v as usize)].a() as u32;
+                    if alpha == 0 {
+                        continue;
+                    }
+                    let p = sheet.get_pixel_mut(px as u32, py as u32);
+                    for ch in 0..3 {
+                        let blended =
+                            p.0[ch] as u32 * (255 - alpha) + FOREGROUND.0[ch] as u32 * alpha;
+                        p.0[ch] = ((blended + 127) / 255) as u8;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Cull mode (Shift+X): a keyboard-only pass over the list. 1-5 rate the current
+//! file, X rejects it and U clears its rating, each moving straight on to the next
+//! file. The image gets the whole window with a one-line footer showing progress.
+//!
+//! Ratings are stored per content hash (`AppContext::set_rating`), so an
+//! interrupted cull resumes at the first unrated file, and identical copies share
+//! a rating. At the end the rejected files can be deleted or moved in one batch
+//! through the usual confirmations; one copy of each rejected content is kept.
+
+use std::collections::HashMap;
+
+use eframe::egui;
+
+use super::app::GuiApp;
+use crate::FileMetadata;
+use crate::db::REJECTED;
+use crate::state::InputIntent;
+
+/// A running cull pass.
+pub(super) struct CullSession {
+    /// Fullscreen state to restore when the pass ends
+    was_fullscreen: bool,
+    /// The pass ended and the summary window is open
+    pub(super) finished: bool,
+}
+
+/// Short label of a rating for the list and the footer.
+pub(super) fn rating_label(rating: u8) -> String {
+    if rating == REJECTED { "✖".to_string() } else { format!("★{}", rating) }
+}
+
+/// Start culling, resuming at the first unrated file of the list.
+pub(super) fn start(app: &mut GuiApp) {
+    if app.state.groups.iter().all(|g| g.is_empty()) {
+        app.set_status("Nothing to cull: the list is empty".to_string(), true);
+        return;
+    }
+    let first_unrated = app.state.groups.iter().enumerate().find_map(|(g, group)| {
+        group.iter().position(|f| !app.ratings.contains_key(&f.content_hash)).map(|f| (g, f))
+    });
+    if let Some((g, f)) = first_unrated {
+        app.state.current_group_idx = g;
+        app.state.current_file_idx = f;
+        app.state.selection_changed = true;
+    }
+    app.cull = Some(CullSession { was_fullscreen: app.state.is_fullscreen, finished: false });
+    app.state.is_fullscreen = true;
+    let (reviewed, total) = progress(app);
+    let msg = if first_unrated.is_some() {
+        format!("Culling: {}/{} reviewed", reviewed, total)
+    } else {
+        format!("Culling: all {} files are rated already", total)
+    };
+    app.set_status(msg, false);
+}
+
+/// Files of the list that carry a rating (rejects included), and all files.
+pub(super) fn progress(app: &GuiApp) -> (usize, usize) {
+    let files = app.state.groups.iter().flatten();
+    let reviewed = files.clone().filter(|f| app.ratings.contains_key(&f.content_hash)).count();
+    (reviewed, files.count())
+}
+
+/// Footer line drawn under the image while culling.
+pub(super) fn footer(app: &GuiApp, name: &str) -> String {
+    let rating = app
+        .state
+        .groups
+        .get(app.state.current_group_idx)
+        .and_then(|g| g.get(app.state.current_file_idx))
+        .and_then(|f| app.ratings.get(&f.content_hash))
+        .map(|&r| format!("  {}", rating_label(r)))
+        .unwrap_or_default();
+    let (reviewed, total) = progress(app);
+    format!(
+        "{}{}   {}/{} reviewed   1-5 rate · X reject · U unrate · Esc finish",
+        name, rating, reviewed, total
+    )
+}
+
+/// Cull keys; true when one was handled, so the normal bindings of X and U
+/// don't fire as well.
+pub(super) fn handle_keys(app: &mut GuiApp, ctx: &egui::Context) -> bool {
+    const STARS: [(egui::Key, u8); 5] = [
+        (egui::Key::Num1, 1),
+        (egui::Key::Num2, 2),
+        (egui::Key::Num3, 3),
+        (egui::Key::Num4, 4),
+        (egui::Key::Num5, 5),
+    ];
+    let pressed =
+        |key| ctx.input(|i| i.key_pressed(key) && !i.modifiers.command && !i.modifiers.shift);
+    let rating = if let Some(&(_, stars)) = STARS.iter().find(|(key, _)| pressed(*key)) {
+        Some(Some(stars))
+    } else if pressed(egui::Key::X) {
+        Some(Some(REJECTED))
+    } else if pressed(egui::Key::U) {
+        Some(None)
+    } else {
+        None
+    };
+    match rating {
+        Some(rating) => {
+            rate_and_advance(app, rating);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Store the rating of the current file, then move to the next one; rating the
+/// last file of the list ends the pass.
+fn rate_and_advance(app: &mut GuiApp, rating: Option<u8>) {
+    let (g, f) = (app.state.current_group_idx, app.state.current_file_idx);
+    let Some(content_hash) =
+        app.state.groups.get(g).and_then(|grp| grp.get(f)).map(|f| f.content_hash)
+    else {
+        return;
+    };
+    if content_hash == [0u8; 32] {
+        app.set_status("Not hashed yet, can't rate this file; try again shortly".to_string(), true);
+        return;
+    }
+    if let Err(e) = app.ctx.set_rating(&content_hash, rating) {
+        app.set_status(format!("Failed to save rating: {}", e), true);
+        return;
+    }
+    match rating {
+        Some(r) => app.ratings.insert(content_hash, r),
+        None => app.ratings.remove(&content_hash),
+    };
+    app.cache_dirty = true;
+
+    let is_last = g + 1 >= app.state.groups.len()
+        && f + 1 >= app.state.groups.get(g).map_or(0, |grp| grp.len());
+    if is_last {
+        finish(app);
+    } else {
+        app.state.next_item();
+        app.state.selection_changed = true;
+    }
+}
+
+/// End the pass and open the summary.
+pub(super) fn finish(app: &mut GuiApp) {
+    if let Some(cull) = app.cull.as_mut()
+        && !cull.finished
+    {
+        cull.finished = true;
+        app.state.is_fullscreen = cull.was_fullscreen;
+    }
+}
+
+/// Listed files whose content was rejected, except protected ones and entries
+/// inside ZIP archives. Ratings are per content hash, so rejecting one copy rejects
+/// its twins too; one copy of each rejected content always stays off the list
+/// unless a protected or archived copy survives anyway.
+fn rejected_paths(app: &GuiApp) -> Vec<std::path::PathBuf> {
+    let survives = |f: &FileMetadata| {
+        app.state.protected.contains(&f.unique_file_id) || crate::archive::is_archive_entry(&f.path)
+    };
+    // Rejected copies per content hash, in list order
+    let mut order = Vec::new();
+    let mut copies: HashMap<[u8; 32], Vec<&FileMetadata>> = HashMap::new();
+    for f in app.state.groups.iter().flatten() {
+        if app.ratings.get(&f.content_hash) == Some(&REJECTED) {
+            copies
+                .entry(f.content_hash)
+                .or_insert_with(|| {
+                    order.push(f.content_hash);
+                    Vec::new()
+                })
+                .push(f);
+        }
+    }
+    order
+        .iter()
+        .flat_map(|ch| {
+            let all = &copies[ch];
+            // Without a protected or archived copy, the first one in list order stays
+            let keep = usize::from(!all.iter().any(|&f| survives(f)));
+            all.iter().filter(|&&f| !survives(f)).skip(keep).map(|f| f.path.clone())
+        })
+        .collect()
+}
+
+/// Summary after a pass: progress, and deleting or moving the rejected files.
+/// Both mark the rejects and hand over to the normal confirmation dialogs.
+pub(super) fn show_summary(app: &mut GuiApp, ctx: &egui::Context) {
+    let (reviewed, total) = progress(app);
+    let rejected = rejected_paths(app);
+    let has_review_dir = app.state.review_dir.is_some();
+    let mut delete = false;
+    let mut move_out = false;
+    let mut review = false;
+    let mut close = ctx.input(|i| i.key_pressed(egui::Key::Enter));
+
+    egui::Window::new("Cull finished").collapsible(false).resizable(false).show(ctx, |ui| {
+        ui.label(format!("{}/{} files reviewed", reviewed, total));
+        if reviewed < total {
+            ui.label("Shift+X resumes at the first unrated file.");
+        }
+        ui.label(format!(
+            "{} rejected (protected and archived files and one copy of each image are left out)",
+            rejected.len()
+        ));
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!rejected.is_empty(), |ui| {
+                delete = ui.button("Delete rejected…").clicked();
+                move_out = ui.button("Move rejected…").clicked();
+                if has_review_dir {
+                    review = ui.button("Move rejected to review…").clicked();
+                }
+            });
+            if ui.button("Keep all (Enter)").clicked() {
+                close = true;
+            }
+        });
+    });
+
+    if delete || move_out || review {
+        app.cull = None;
+        app.state.marked_for_deletion = rejected;
+        app.state.marks_generation += 1;
+        app.cache_dirty = true;
+        if delete {
+            app.state.handle_input(InputIntent::ExecuteDelete);
+        } else if review {
+            app.review_move = Some(super::dialogs::ReviewMove::Marked);
+        } else if app.state.move_target.is_some() {
+            app.state.handle_input(InputIntent::MoveMarked);
+        } else {
+            app.show_move_input = true;
+            app.move_focus_requested = false;
+            app.move_input.clear();
+        }
+    } else if close {
+        app.cull = None;
+    }
+}
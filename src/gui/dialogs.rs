@@ -1,5 +1,9 @@
+use crate::debug_log;
+use crate::exif_types::{ExifValue, TAG_DERIVED_TIMESTAMP, TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE};
+use crate::exif_write;
+use crate::fileops::DeleteMode;
 use crate::format_relative_time;
-use crate::scanner;
+use crate::image_features::ImageFeatures;
 use crate::search_index::{SearchCriterion, parse_search_query};
 use crate::state::InputIntent;
 use eframe::egui;
@@ -7,10 +11,11 @@ use jiff::Timestamp;
 use regex::RegexBuilder;
 use std::cell::RefCell;
 use std::fs;
-use std::path::Path;
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
 
 use super::app::GuiApp;
-use super::image::ViewMode;
+use super::image::{CROP_GUIDES, ViewMode};
 
 struct GeoDistanceFilter {
     target_point: geo::Point<f64>,
@@ -33,10 +38,74 @@ pub(super) fn handle_input(
 
     // Input handling
     if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+        if app.scan_summary.is_some() {
+            app.scan_summary = None;
+            return;
+        }
         if app.show_move_input {
             app.show_move_input = false;
             return;
         }
+        if app.show_goto_input {
+            app.show_goto_input = false;
+            return;
+        }
+        if app.show_jump_input {
+            app.show_jump_input = false;
+            return;
+        }
+        if app.show_time_shift {
+            app.show_time_shift = false;
+            return;
+        }
+        if app.pin_location.is_some() {
+            app.pin_location = None;
+            return;
+        }
+        if app.strip_gps.is_some() {
+            app.strip_gps = None;
+            return;
+        }
+        if app.write_gps.is_some() {
+            app.write_gps = None;
+            return;
+        }
+        if app.fill_metadata.is_some() {
+            app.fill_metadata = None;
+            return;
+        }
+        if app.group_review.is_some() {
+            app.group_review = None;
+            return;
+        }
+        if app.cache_panel.is_some() {
+            app.cache_panel = None;
+            return;
+        }
+        if app.web_export.as_ref().is_some_and(|e| !e.is_running()) {
+            app.web_export = None;
+            return;
+        }
+        if app.show_fs_events {
+            app.show_fs_events = false;
+            return;
+        }
+        if app.review_move.is_some() {
+            app.review_move = None;
+            return;
+        }
+        if let Some(cull) = &app.cull
+            && !app.state.is_any_dialog_open()
+            && !app.show_dir_picker
+        {
+            // First Esc ends the pass, the second closes its summary
+            if cull.finished {
+                app.cull = None;
+            } else {
+                super::cull::finish(app);
+            }
+            return;
+        }
         if app.show_dir_picker {
             app.show_dir_picker = false;
         } else if app.state.show_search {
@@ -54,7 +123,134 @@ pub(super) fn handle_input(
             *intent.borrow_mut() = Some(InputIntent::Quit);
         }
     }
-    if app.show_move_input {
+    if app.show_move_input
+        || app.show_goto_input
+        || app.show_jump_input
+        || app.show_time_shift
+        || app.pin_location.is_some()
+        || app.strip_gps.is_some()
+        || app.write_gps.is_some()
+        || app.fill_metadata.is_some()
+        || app.group_review.is_some()
+        || app.cache_panel.is_some()
+        || app.web_export.is_some()
+        || app.review_move.is_some()
+        || app.cull.as_ref().is_some_and(|c| c.finished)
+    {
+        return;
+    }
+
+    // Cull mode: 1-5 rate, X rejects, U unrates, each moving on to the next file
+    if app.cull.is_some()
+        && !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && super::cull::handle_keys(app, ctx)
+    {
+        return;
+    }
+
+    // Fill missing capture times and locations from the neighbouring files
+    if !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && ctx.input(|i| i.key_pressed(egui::Key::T) && i.modifiers.command && i.modifiers.shift)
+    {
+        app.fill_metadata = Some(super::fill_metadata::FillMetadata::open(app));
+        return;
+    }
+
+    // Shift EXIF dates of marked files (or the current file)
+    if !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && ctx.input(|i| i.key_pressed(egui::Key::T) && i.modifiers.command)
+    {
+        app.show_time_shift = true;
+        app.time_shift_focus_requested = false;
+        app.time_shift_preview = None;
+        return;
+    }
+
+    // Strip GPS tags from marked files (or the current file)
+    if !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && ctx.input(|i| i.key_pressed(egui::Key::G) && i.modifiers.command)
+    {
+        app.strip_gps = Some(plan_strip_gps(app));
+        return;
+    }
+
+    // File system event panel
+    if !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && ctx.input(|i| i.key_pressed(egui::Key::J) && i.modifiers.command)
+    {
+        app.show_fs_events = !app.show_fs_events;
+        return;
+    }
+
+    // Cache statistics and maintenance
+    if !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && ctx.input(|i| i.key_pressed(egui::Key::I) && i.modifiers.command)
+    {
+        app.cache_panel = Some(super::cache_panel::CachePanel::open(app));
+        return;
+    }
+
+    // Export the marked files (or the current group) as web-ready JPEGs
+    if !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && ctx.input(|i| i.key_pressed(egui::Key::E) && i.modifiers.command && i.modifiers.shift)
+    {
+        let export = super::web_export::WebExport::open(app);
+        app.web_export = Some(export);
+        return;
+    }
+
+    // Review the current group pair by pair (duplicate mode)
+    if !app.state.view_mode
+        && !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && ctx.input(|i| i.key_pressed(egui::Key::D) && i.modifiers.command)
+    {
+        let group_idx = app.state.current_group_idx;
+        let len = app.state.groups.get(group_idx).map_or(0, |g| g.len());
+        app.group_review = super::group_review::GroupReview::new(group_idx, len);
+        if app.group_review.is_none() {
+            app.set_status(
+                "Nothing to review: the group has fewer than two files".to_string(),
+                true,
+            );
+        }
+        return;
+    }
+
+    // Go to path (view mode)
+    if app.state.view_mode
+        && !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && ctx.input(|i| i.key_pressed(egui::Key::P) && i.modifiers.command)
+    {
+        app.show_goto_input = true;
+        app.goto_focus_requested = false;
+        app.completion_candidates.clear();
+        app.completion_index = 0;
+        app.goto_input = app
+            .current_dir
+            .as_ref()
+            .map(|d| format!("{}{}", d.display(), std::path::MAIN_SEPARATOR))
+            .unwrap_or_default();
+        return;
+    }
+
+    // Jump to the n-th file overall
+    if !app.state.is_any_dialog_open()
+        && !app.show_dir_picker
+        && !app.state.groups.is_empty()
+        && ctx.input(|i| i.key_pressed(egui::Key::Colon))
+    {
+        app.show_jump_input = true;
+        app.jump_focus_requested = false;
+        app.jump_input.clear();
         return;
     }
 
@@ -106,6 +302,7 @@ pub(super) fn handle_input(
         && !app.state.show_delete_immediate_confirmation
         && !app.state.show_ignore_group_confirmation
         && app.state.error_popup.is_none()
+        && app.scan_summary.is_none()
     {
         // Calculate total directory count (parent + subdirs) for view mode navigation
         // In flatten mode, there are no directories to navigate
@@ -332,33 +529,72 @@ pub(super) fn handle_input(
         if ctx.input(|i| i.key_pressed(egui::Key::Space)) {
             *intent.borrow_mut() = Some(InputIntent::ToggleMark);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::D)) {
+        if ctx.input(|i| i.key_pressed(egui::Key::D) && i.modifiers.shift) {
+            if app.state.review_dir.is_none() {
+                app.set_status(
+                    "Set review_dir under [gui] to move files for review".to_string(),
+                    true,
+                );
+            } else if app.state.listed_marks().is_empty() {
+                app.set_status("No files marked.".to_string(), false);
+            } else {
+                app.review_move = Some(ReviewMove::Marked);
+            }
+        } else if ctx.input(|i| i.key_pressed(egui::Key::D) && !i.modifiers.command) {
             *intent.borrow_mut() = Some(InputIntent::ExecuteDelete);
         }
         if ctx.input(|i| i.key_pressed(egui::Key::H)) {
             *intent.borrow_mut() = Some(InputIntent::ToggleRelativeTime);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::W)) {
+        if ctx.input(|i| i.key_pressed(egui::Key::W) && !i.modifiers.shift) {
             *intent.borrow_mut() = Some(InputIntent::CycleViewMode);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::Z)) {
+        if ctx.input(|i| i.key_pressed(egui::Key::W) && i.modifiers.shift) {
+            super::image::cycle_raw_wb(app);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Z) && !i.modifiers.shift) {
             *intent.borrow_mut() = Some(InputIntent::CycleZoom);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::R)) {
+        if ctx.input(|i| i.key_pressed(egui::Key::Z) && i.modifiers.shift) {
+            app.cycle_image_filter();
+        }
+        // Shift+R: re-read metadata of the marked files (or the current file)
+        if ctx.input(|i| i.key_pressed(egui::Key::R) && i.modifiers.shift && !i.modifiers.command) {
+            app.reenrich_selection();
+        } else if ctx.input(|i| i.key_pressed(egui::Key::R) && !i.modifiers.command) {
             *intent.borrow_mut() = Some(InputIntent::StartRename);
         }
-        if ctx.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::L)) {
-            *intent.borrow_mut() = Some(InputIntent::RefreshDirCache);
+        // Ctrl+R: re-read the config file
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::R)) {
+            app.reload_config(ctx);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::X)) {
+        // Ctrl+Shift+L: re-scan the current group only
+        if ctx.input(|i| i.modifiers.ctrl && i.modifiers.shift && i.key_pressed(egui::Key::L)) {
+            app.start_group_rescan();
+        } else if ctx.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::L)) {
+            *intent.borrow_mut() = Some(InputIntent::RefreshDirCache);
+        } else if ctx.input(|i| i.modifiers.shift && i.key_pressed(egui::Key::L)) {
+            // Shift+L: freeze/unfreeze the list order
+            app.toggle_list_freeze();
+        }
+        // Shift+X: start (or resume) a cull pass
+        if ctx.input(|i| i.key_pressed(egui::Key::X) && i.modifiers.shift) {
+            if app.cull.is_none() {
+                super::cull::start(app);
+            }
+        } else if ctx.input(|i| i.key_pressed(egui::Key::X)) {
             *intent.borrow_mut() = Some(InputIntent::ToggleZoomRelative);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::P)) {
+        if ctx.input(|i| i.key_pressed(egui::Key::P) && !i.modifiers.command) {
             *intent.borrow_mut() = Some(InputIntent::TogglePathVisibility);
         }
         if ctx.input(|i| i.key_pressed(egui::Key::Delete)) {
             *intent.borrow_mut() = Some(InputIntent::DeleteImmediate);
         }
+        // ! key: protect/unprotect current file against deletion
+        if ctx.input(|i| i.key_pressed(egui::Key::Exclamationmark)) {
+            *intent.borrow_mut() = Some(InputIntent::ToggleProtect);
+        }
         // Q key: Ignore files (duplicate mode only)
         // Plain Q: ignore marked files (or current file if none marked)
         // Shift+Q: ignore all files in current group (with confirmation)
@@ -378,7 +614,7 @@ pub(super) fn handle_input(
         if ctx.input(|i| i.key_pressed(egui::Key::M)) {
             // Check if there is anything to move at all.
             // We need either Marked Files OR a Current File (fallback).
-            let has_marked = !app.state.marked_for_deletion.is_empty();
+            let has_marked = !app.state.listed_marks().is_empty();
             let has_current = app.state.get_current_image_path().is_some();
 
             if !has_marked && !has_current {
@@ -404,13 +640,21 @@ pub(super) fn handle_input(
                 }
             }
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::S)) {
+        // Shift+S: list one content subgroup (C1, C2, ...) of the group at a time
+        if ctx.input(|i| i.key_pressed(egui::Key::S) && i.modifiers.shift) {
+            app.cycle_subgroup_filter();
+        } else if ctx.input(|i| i.key_pressed(egui::Key::S)) {
             *intent.borrow_mut() = Some(InputIntent::ToggleSlideshow);
         }
         if ctx.input(|i| i.key_pressed(egui::Key::F)) {
             *intent.borrow_mut() = Some(InputIntent::ToggleFullscreen);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::O)) {
+        // Shift+O: ghost of the previous file over the current one (bursts, stacks)
+        if ctx.input(|i| i.key_pressed(egui::Key::O) && i.modifiers.shift) {
+            app.ghost_overlay = !app.ghost_overlay;
+            let msg = if app.ghost_overlay { "Ghost overlay: on" } else { "Ghost overlay: off" };
+            app.set_status(msg.to_string(), false);
+        } else if ctx.input(|i| i.key_pressed(egui::Key::O)) {
             *intent.borrow_mut() = Some(InputIntent::RotateCW);
         }
         if ctx.input(|i| i.key_pressed(egui::Key::Y)) {
@@ -422,15 +666,49 @@ pub(super) fn handle_input(
         if ctx.input(|i| i.key_pressed(egui::Key::Backspace)) {
             *intent.borrow_mut() = Some(InputIntent::ResetTransform);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::I)) {
+        if ctx.input(|i| i.key_pressed(egui::Key::CloseBracket)) {
+            *intent.borrow_mut() = Some(InputIntent::NextCamera);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::OpenBracket)) {
+            *intent.borrow_mut() = Some(InputIntent::PrevCamera);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::J) && i.modifiers.shift) {
+            *intent.borrow_mut() = Some(InputIntent::NextPeer);
+        } else if app.state.view_mode && ctx.input(|i| i.key_pressed(egui::Key::J)) {
+            app.jump_to_similar();
+        } else if !app.state.view_mode && ctx.input(|i| i.key_pressed(egui::Key::J)) {
+            // Collapse all groups to their headers, or expand all if already collapsed
+            if app.collapsed_groups.len() < app.state.groups.len() {
+                app.collapsed_groups = (0..app.state.groups.len()).collect();
+            } else {
+                app.collapsed_groups.clear();
+            }
+            app.cache_dirty = true;
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::K) && !i.modifiers.shift) {
+            // Cycle: Off -> 1:1 -> 4:5 -> 16:9 -> Off
+            app.crop_guide = (app.crop_guide + 1) % (CROP_GUIDES.len() + 1);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::K) && i.modifiers.shift) {
+            // Cycle: Panel -> Black -> White -> Checkerboard -> Panel
+            app.viewer_background = app.viewer_background.next();
+            app.set_status(format!("Background: {}", app.viewer_background.name()), false);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::I) && !i.modifiers.command) {
             // Cycle: 0 (Off) -> 1 (Standard Grid) -> 2 (Proportional Strip) -> 0 (Off)
             app.histogram_mode = (app.histogram_mode + 1) % 3;
             app.histogram_enabled
                 .store(app.histogram_mode > 0, std::sync::atomic::Ordering::Relaxed);
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::E)) {
+        // Shift+E: list only files with a badge (date -> GPS -> camera -> screenshot -> all)
+        if ctx.input(|i| i.key_pressed(egui::Key::E) && i.modifiers.shift && !i.modifiers.command) {
+            app.cycle_badge_filter();
+        } else if ctx.input(|i| i.key_pressed(egui::Key::E) && !i.modifiers.command) {
             app.show_exif = !app.show_exif;
         }
+        if ctx.input(|i| i.key_pressed(egui::Key::E) && i.modifiers.command && !i.modifiers.shift) {
+            app.start_contact_sheet();
+        }
 
         // N key: Toggle GPS Map panel
         // Logic: Off -> Map Only -> Map + Lines (Optimized) -> Off
@@ -449,7 +727,8 @@ pub(super) fn handle_input(
 
                 // (Existing auto-center logic...)
                 if app.gps_map.selected_location.is_none()
-                    && let Some((name, point)) = app.ctx.locations.iter().next()
+                    && let Ok(locations) = app.ctx.locations.read()
+                    && let Some((name, point)) = locations.iter().next()
                 {
                     app.gps_map.selected_location = Some((name.clone(), *point));
                 }
@@ -489,7 +768,7 @@ pub(super) fn handle_input(
                 // State 2: Lines ON
                 app.gps_map.show_path_lines = true;
                 let dist = app.gps_map.optimize_path();
-                let dist_str = crate::gui::gps_map::format_distance(dist);
+                let dist_str = app.gps_map.distance_format.distance(dist);
                 app.set_status(
                     format!("GPS Map: Path lines enabled. Total distance: {}", dist_str),
                     false,
@@ -503,7 +782,12 @@ pub(super) fn handle_input(
             }
         }
 
-        if ctx.input(|i| i.key_pressed(egui::Key::G)) {
+        // Shift+G: list only files without GPS coordinates
+        if ctx.input(|i| i.key_pressed(egui::Key::G) && i.modifiers.shift && !i.modifiers.command) {
+            app.toggle_missing_gps_filter();
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::G) && !i.modifiers.shift && !i.modifiers.command)
+        {
             // Toggle Time Source
             app.state.use_gps_utc = !app.state.use_gps_utc;
             app.cached_exif = None;
@@ -526,6 +810,14 @@ pub(super) fn handle_input(
             }
         }
 
+        // Shift+C: copy the current group's paths (duplicate mode)
+        if !app.state.view_mode
+            && ctx
+                .input(|i| i.key_pressed(egui::Key::C) && i.modifiers.shift && !i.modifiers.command)
+        {
+            app.copy_group_paths(ctx, false);
+        }
+
         // View Mode Only
         // Directory navigation is disabled in flatten mode (--view-flatten)
         if app.state.view_mode && !app.state.view_mode_flatten {
@@ -552,7 +844,8 @@ pub(super) fn handle_input(
         }
 
         // Sort selection is available in all view modes
-        if app.state.view_mode && ctx.input(|i| i.key_pressed(egui::Key::T)) {
+        if app.state.view_mode && ctx.input(|i| i.key_pressed(egui::Key::T) && !i.modifiers.command)
+        {
             *intent.borrow_mut() = Some(InputIntent::ShowSortSelection);
         }
 
@@ -566,19 +859,29 @@ pub(super) fn handle_input(
         if ctx.input(|i| i.key_pressed(egui::Key::V)) {
             let old = app.panel_width;
             app.panel_width = (app.panel_width - delta).max(160.0);
-            eprintln!(
+            debug_log!(
                 "[PANEL-DBG] V pressed: window_width={:.1}, delta={:.1}, panel_width {:.1} -> {:.1}",
-                window_width, delta, old, app.panel_width
+                window_width,
+                delta,
+                old,
+                app.panel_width
             );
             *force_panel_resize = true;
         }
+        // Ctrl+B: toggle the duplicate-mode action bar
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::B)) {
+            app.show_action_bar = !app.show_action_bar;
+        }
         // B to Expand
-        if ctx.input(|i| i.key_pressed(egui::Key::B)) {
+        if ctx.input(|i| !i.modifiers.command && i.key_pressed(egui::Key::B)) {
             let old = app.panel_width;
             app.panel_width = (app.panel_width + delta).min(window_width * 0.8);
-            eprintln!(
+            debug_log!(
                 "[PANEL-DBG] B pressed: window_width={:.1}, delta={:.1}, panel_width {:.1} -> {:.1}",
-                window_width, delta, old, app.panel_width
+                window_width,
+                delta,
+                old,
+                app.panel_width
             );
             *force_panel_resize = true;
         }
@@ -626,7 +929,8 @@ pub(super) fn handle_dialogs(
             InputIntent::CycleViewMode => {
                 app.update_view_state(|v| {
                     v.mode = match v.mode {
-                        ViewMode::FitWindow => ViewMode::FitWidth,
+                        ViewMode::FitWindow => ViewMode::FitWindowNoUpscale,
+                        ViewMode::FitWindowNoUpscale => ViewMode::FitWidth,
                         ViewMode::FitWidth => ViewMode::FitHeight,
                         _ => ViewMode::FitWindow,
                     };
@@ -661,41 +965,57 @@ pub(super) fn handle_dialogs(
                 }
             }
             InputIntent::FindInMap => {
-                if let Some(group) = app.state.groups.get(app.state.current_group_idx) {
-                    let positions: Vec<_> = group
-                        .iter()
-                        .filter_map(|f| app.gps_map.get_marker_by_path(&f.path))
-                        .map(|m| m.position())
-                        .collect();
-
-                    if !positions.is_empty() {
-                        app.gps_map.visible = true;
-                        app.gps_map.fit_positions(&positions);
-                        app.set_status(
-                            format!("Map fitted to {} group markers", positions.len()),
-                            false,
-                        );
-                    } else {
-                        app.set_status("No GPS markers found in current group".to_string(), true);
+                app.fit_map_to_current_group();
+            }
+            InputIntent::NextCamera => app.jump_to_other_camera(true),
+            InputIntent::PrevCamera => app.jump_to_other_camera(false),
+            InputIntent::ToggleProtect => {
+                let current = app
+                    .state
+                    .groups
+                    .get(app.state.current_group_idx)
+                    .and_then(|g| g.get(app.state.current_file_idx))
+                    .map(|f| f.content_hash);
+                match current {
+                    Some(ch) if ch != [0u8; 32] => {
+                        let protect = !app.state.is_current_protected();
+                        match app.ctx.set_protected(&ch, protect) {
+                            Ok(()) => {
+                                let n = app.state.set_content_protected(&ch, protect);
+                                let verb = if protect { "Protected" } else { "Unprotected" };
+                                app.set_status(format!("{} {} file(s)", verb, n), false);
+                                app.cache_dirty = true;
+                            }
+                            Err(e) => {
+                                app.set_status(format!("Failed to save protection: {}", e), true)
+                            }
+                        }
                     }
+                    Some(_) => app.set_status(
+                        "Cannot protect: content hash not computed yet".to_string(),
+                        true,
+                    ),
+                    None => {}
                 }
             }
             InputIntent::IgnoreCurrent => {
                 // Q key in duplicate finder mode:
-                // If files are marked → ignore all marked files
+                // If files are marked → ignore all listed marked files
                 // If nothing marked → ignore just the current file
                 //
                 // Groups are pre-registered with UUIDs on scan completion,
                 // so we just flip the `ignored` flag to true.
                 if !app.state.view_mode {
-                    let has_marked = !app.state.marked_for_deletion.is_empty();
+                    // Marks on files a list filter hides are left alone
+                    let listed_marks = app.state.listed_marks();
+                    let has_marked = !listed_marks.is_empty();
 
                     // Collect content_hashes to ignore
                     let mut hashes_to_ignore: Vec<[u8; 32]> = Vec::new();
 
                     if has_marked {
                         let marked_set: std::collections::HashSet<_> =
-                            app.state.marked_for_deletion.iter().cloned().collect();
+                            listed_marks.iter().cloned().collect();
                         for group in &app.state.groups {
                             for f in group {
                                 if marked_set.contains(&f.path) && f.content_hash != [0u8; 32] {
@@ -724,7 +1044,7 @@ pub(super) fn handle_dialogs(
                                         .get_group_uuid(ch)
                                         .map(|u| crate::db::AppContext::format_uuid(&u))
                                         .unwrap_or_else(|| "unknown".to_string());
-                                    eprintln!(
+                                    debug_log!(
                                         "[DEBUG-IGNORE] Set ignored=true: blake3={} uuid={}",
                                         hex::encode(ch),
                                         uuid_str,
@@ -762,7 +1082,10 @@ pub(super) fn handle_dialogs(
                                         app.state.current_file_idx = glen.saturating_sub(1);
                                     }
                                 }
-                                app.state.marked_for_deletion.clear();
+                                let remaining: std::collections::HashSet<&PathBuf> =
+                                    app.state.groups.iter().flatten().map(|f| &f.path).collect();
+                                app.state.marked_for_deletion.retain(|p| remaining.contains(p));
+                                app.state.marks_generation += 1;
                                 app.state.selection_changed = true;
                                 app.cache_dirty = true;
                                 app.state.last_file_count =
@@ -790,13 +1113,20 @@ pub(super) fn handle_dialogs(
             app.state.handle_input(InputIntent::Cancel);
         }
         egui::Window::new("Confirm Deletion").collapsible(false).show(ctx, |ui| {
-            let marked_count = app.state.marked_for_deletion.len();
-            let use_trash = app.state.use_trash;
-            ui.label(format!(
-                "Are you sure you want to {} {} files?",
-                if use_trash { "trash" } else { "permanently delete" },
-                marked_count
-            ));
+            let marked = app.state.listed_marks();
+            let marked_count = marked.len();
+            let action = match app.state.delete_mode() {
+                DeleteMode::Permanent => "permanently delete",
+                mode => mode.verb(),
+            };
+            ui.label(format!("Are you sure you want to {} {} files?", action, marked_count));
+            let live_count = app.state.live_sibling_count(&marked);
+            if live_count > 0 {
+                ui.checkbox(
+                    &mut app.state.delete_live_siblings,
+                    format!("Also {} {} live photo companion files", action, live_count),
+                );
+            }
             ui.horizontal(|ui| {
                 if ui.button("Yes (y)").clicked() {
                     app.state.handle_input(InputIntent::ConfirmDelete);
@@ -823,6 +1153,23 @@ pub(super) fn handle_dialogs(
                 .map(|p| p.file_name().unwrap_or_default().to_string_lossy().to_string())
                 .unwrap_or_default();
             ui.label(format!("Delete current file?\n{}", filename));
+            if app.state.is_current_protected() {
+                let warning = if app.state.protected_delete_armed {
+                    "🔒 This file is PROTECTED. Press y again to delete it anyway."
+                } else {
+                    "🔒 This file is PROTECTED."
+                };
+                ui.colored_label(egui::Color32::from_rgb(255, 160, 0), warning);
+            }
+            let live_count =
+                app.state.get_current_image_path().map_or(0, |p| app.state.live_sibling_count([p]));
+            if live_count > 0 {
+                let verb = app.state.delete_mode().verb();
+                ui.checkbox(
+                    &mut app.state.delete_live_siblings,
+                    format!("Also {} {} live photo companion files", verb, live_count),
+                );
+            }
             ui.horizontal(|ui| {
                 if ui.button("Yes (y)").clicked() {
                     app.state.handle_input(InputIntent::ConfirmDeleteImmediate);
@@ -871,7 +1218,8 @@ pub(super) fn handle_dialogs(
             let target =
                 app.state.move_target.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
 
-            let msg = if app.state.marked_for_deletion.is_empty() {
+            let marked_count = app.state.listed_marks().len();
+            let msg = if marked_count == 0 {
                 if let Some(p) = app.state.get_current_image_path() {
                     let name = p.file_name().unwrap_or_default().to_string_lossy();
                     format!("Move current file '{}' to:\n{}", name, target)
@@ -879,7 +1227,7 @@ pub(super) fn handle_dialogs(
                     format!("Move 0 files to:\n{}", target)
                 }
             } else {
-                format!("Move {} marked files to:\n{}", app.state.marked_for_deletion.len(), target)
+                format!("Move {} marked files to:\n{}", marked_count, target)
             };
 
             // Append destination directory metadata read from the kept-open
@@ -927,6 +1275,224 @@ pub(super) fn handle_dialogs(
         }
     }
 
+    // Go To Path Dialog
+    if app.show_goto_input {
+        let mut submit = false;
+        let mut cancel = false;
+        let mut request_focus_back = false;
+
+        egui::Window::new("Go to Path").collapsible(false).show(ctx, |ui| {
+            ui.label("Directory or file (Tab completes):");
+
+            let res = ui.add(egui::TextEdit::singleline(&mut app.goto_input).desired_width(400.0));
+            if !app.goto_focus_requested {
+                res.request_focus();
+                app.goto_focus_requested = true;
+            }
+
+            if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
+                request_focus_back = true;
+                complete_path_input(
+                    &mut app.goto_input,
+                    &mut app.completion_candidates,
+                    &mut app.completion_index,
+                    None,
+                    false,
+                );
+            }
+            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                submit = true;
+            }
+            if request_focus_back {
+                res.request_focus();
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Go").clicked() {
+                    submit = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+        if submit {
+            let input_path = std::path::PathBuf::from(app.goto_input.trim());
+            let target = if input_path.is_absolute() {
+                input_path
+            } else if let Some(ref current) = app.current_dir {
+                current.join(&input_path)
+            } else {
+                input_path
+            };
+            if target.is_dir() {
+                app.show_goto_input = false;
+                app.change_directory(target);
+            } else if target.is_file()
+                && let Ok(canonical) = crate::scanner::resolve_path(&target)
+                && let Some(parent) = canonical.parent()
+            {
+                app.show_goto_input = false;
+                app.goto_pending_file = Some(canonical.clone());
+                app.change_directory(parent.to_path_buf());
+            } else {
+                // Keep the input open so the path can be corrected
+                app.set_status(format!("No such file or directory: {}", target.display()), true);
+                app.goto_focus_requested = false;
+            }
+        }
+        if cancel {
+            app.show_goto_input = false;
+        }
+    }
+
+    // Jump To File Number Dialog
+    if app.show_jump_input {
+        let mut submit = false;
+        let mut cancel = false;
+        let (current, total) = app.state.file_position();
+
+        egui::Window::new("Jump to File").collapsible(false).show(ctx, |ui| {
+            ui.label(format!("File number (1-{}, now at {}):", total, current));
+
+            let res = ui.add(egui::TextEdit::singleline(&mut app.jump_input).desired_width(120.0));
+            if !app.jump_focus_requested {
+                res.request_focus();
+                app.jump_focus_requested = true;
+            }
+            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                submit = true;
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Go").clicked() {
+                    submit = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+        if submit {
+            match app.jump_input.trim().trim_start_matches(':').trim().parse::<usize>() {
+                Ok(number) => {
+                    app.show_jump_input = false;
+                    app.jump_to_file_number(number);
+                }
+                Err(_) => {
+                    // Keep the input open so the number can be corrected
+                    app.set_status(format!("Not a file number: {}", app.jump_input.trim()), true);
+                    app.jump_focus_requested = false;
+                }
+            }
+        }
+        if cancel {
+            app.show_jump_input = false;
+        }
+    }
+
+    // Pin Location Dialog
+    if let Some(point) = app.pin_location {
+        let mut submit = false;
+        let mut cancel = false;
+
+        egui::Window::new("Pin Location").collapsible(false).show(ctx, |ui| {
+            ui.label(format!("Position: {:.6}, {:.6}", point.y(), point.x()));
+            ui.label("Name:");
+
+            let res = ui
+                .add(egui::TextEdit::singleline(&mut app.pin_location_input).desired_width(300.0));
+            if !app.pin_location_focus_requested {
+                res.request_focus();
+                app.pin_location_focus_requested = true;
+            }
+            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                submit = true;
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    submit = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+        if submit {
+            let name = app.pin_location_input.trim().to_string();
+            match app.ctx.add_location(&name, point) {
+                Ok(()) => {
+                    app.pin_location = None;
+                    app.gps_map.picked_point = None;
+                    app.gps_map.selected_location = Some((name.clone(), point));
+                    app.set_status(format!("Saved location '{}'", name), false);
+                }
+                Err(e) => {
+                    // Keep the dialog open so the name can be corrected
+                    app.set_status(format!("Cannot save location: {}", e), true);
+                    app.pin_location_focus_requested = false;
+                }
+            }
+        }
+        if cancel {
+            app.pin_location = None;
+        }
+    }
+
+    // Strip GPS Dialog
+    if app.strip_gps.is_some() {
+        show_strip_gps_dialog(app, ctx);
+    }
+
+    // Write GPS Dialog
+    if app.write_gps.is_some() {
+        show_write_gps_dialog(app, ctx);
+    }
+
+    // Fill Missing Metadata Window
+    if app.fill_metadata.is_some() {
+        super::fill_metadata::show(app, ctx);
+    }
+
+    // Group Review Window
+    if app.group_review.is_some() {
+        super::group_review::show_group_review(app, ctx);
+    }
+
+    // Cache Panel
+    if app.cache_panel.is_some() {
+        super::cache_panel::show(app, ctx);
+    }
+
+    // Web Export
+    if app.web_export.is_some() {
+        super::web_export::show(app, ctx);
+    }
+
+    // File System Event Panel
+    if app.show_fs_events {
+        show_fs_events_panel(app, ctx);
+    }
+
+    // Cull Summary
+    if app.cull.as_ref().is_some_and(|c| c.finished) {
+        super::cull::show_summary(app, ctx);
+    }
+
+    // Move To Review Folder Dialog
+    if let Some(review_move) = app.review_move {
+        show_review_move_dialog(app, ctx, review_move);
+    }
+
+    // EXIF Date Shift Dialog
+    if app.show_time_shift {
+        show_time_shift_dialog(app, ctx);
+    }
+
     // Move Input Dialog
     if app.show_move_input {
         let mut submit = false;
@@ -961,57 +1527,13 @@ pub(super) fn handle_dialogs(
             // Tab Completion (DIRECTORIES ONLY)
             if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
                 request_focus_back = true;
-                let path_buf = std::path::PathBuf::from(&app.move_input);
-                let (parent, prefix) = if app.move_input.ends_with(std::path::MAIN_SEPARATOR) {
-                    (Some(path_buf.as_path()), "".to_string())
-                } else {
-                    (
-                        path_buf.parent(),
-                        path_buf.file_name().unwrap_or_default().to_string_lossy().to_string(),
-                    )
-                };
-
-                if let Some(parent_dir) = parent {
-                    // Check if we need to refresh candidates
-                    let prev_idx = if !app.move_completion_candidates.is_empty() {
-                        (app.move_completion_index + app.move_completion_candidates.len() - 1)
-                            % app.move_completion_candidates.len()
-                    } else {
-                        0
-                    };
-
-                    let input_matches_candidate = !app.move_completion_candidates.is_empty()
-                        && app.move_completion_candidates[prev_idx] == app.move_input;
-
-                    if app.move_completion_candidates.is_empty() || !input_matches_candidate {
-                        app.move_completion_candidates.clear();
-                        app.move_completion_index = 0;
-                        if let Ok(entries) = fs::read_dir(parent_dir) {
-                            for entry in entries.flatten() {
-                                // Filter: ONLY DIRECTORIES
-                                if let Ok(ft) = entry.file_type()
-                                    && ft.is_dir()
-                                {
-                                    let name = entry.path().to_string_lossy().to_string();
-                                    if name.starts_with(&app.move_input)
-                                        || entry.file_name().to_string_lossy().starts_with(&prefix)
-                                    {
-                                        // Store full path for convenience
-                                        app.move_completion_candidates.push(name);
-                                    }
-                                }
-                            }
-                            app.move_completion_candidates.sort();
-                        }
-                    }
-
-                    if !app.move_completion_candidates.is_empty() {
-                        app.move_input =
-                            app.move_completion_candidates[app.move_completion_index].clone();
-                        app.move_completion_index =
-                            (app.move_completion_index + 1) % app.move_completion_candidates.len();
-                    }
-                }
+                complete_path_input(
+                    &mut app.move_input,
+                    &mut app.move_completion_candidates,
+                    &mut app.move_completion_index,
+                    None,
+                    true,
+                );
             }
 
             if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
@@ -1157,6 +1679,7 @@ pub(super) fn handle_dialogs(
                         ui.monospace("  Country:Sweden         → Derived country");
                         ui.monospace("  SunAzimuth:170-190     → Sun azimuth range");
                         ui.monospace("  SunAltitude:-3-3       → Sun near horizon (golden hour)");
+                        ui.monospace("  aspect:>2              → Panoramas (width / height)");
 
                         ui.add_space(4.0);
                         ui.label(egui::RichText::new("Available Tags:").strong());
@@ -1245,48 +1768,19 @@ pub(super) fn handle_dialogs(
 
             if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
                 request_focus_back = true;
-                let parent = if let Some(state) = &app.state.renaming {
-                    state.original_path.parent().map(|p| p.to_path_buf())
-                } else {
-                    None
-                };
-
+                let parent = app
+                    .state
+                    .renaming
+                    .as_ref()
+                    .and_then(|r| r.original_path.parent().map(Path::to_path_buf));
                 if let Some(parent_dir) = parent {
-                    // Calculate previous index to check if input matches what we last auto-completed
-                    let prev_idx = if !app.completion_candidates.is_empty() {
-                        (app.completion_index + app.completion_candidates.len() - 1)
-                            % app.completion_candidates.len()
-                    } else {
-                        0
-                    };
-
-                    // Check if the current input matches the candidate we just showed.
-                    // This confirms the user hasn't typed something new manually.
-                    let input_matches_candidate = !app.completion_candidates.is_empty()
-                        && app.completion_candidates[prev_idx] == app.rename_input;
-
-                    // If empty or user typed something new, scan for new candidates
-                    if app.completion_candidates.is_empty() || !input_matches_candidate {
-                        app.completion_candidates.clear();
-                        app.completion_index = 0;
-                        if let Ok(entries) = fs::read_dir(&parent_dir) {
-                            let prefix = app.rename_input.clone();
-                            for entry in entries.flatten() {
-                                let name = entry.file_name().to_string_lossy().to_string();
-                                if name.starts_with(&prefix) {
-                                    app.completion_candidates.push(name);
-                                }
-                            }
-                            app.completion_candidates.sort();
-                        }
-                    }
-
-                    // Apply the next completion
-                    if !app.completion_candidates.is_empty() {
-                        app.rename_input = app.completion_candidates[app.completion_index].clone();
-                        app.completion_index =
-                            (app.completion_index + 1) % app.completion_candidates.len();
-                    }
+                    complete_path_input(
+                        &mut app.rename_input,
+                        &mut app.completion_candidates,
+                        &mut app.completion_index,
+                        Some(&parent_dir),
+                        false,
+                    );
                 }
             }
 
@@ -1325,7 +1819,7 @@ pub(super) fn handle_dialogs(
         let mut selected_sort = None;
 
         egui::Window::new("Sort Order").collapsible(false).show(ctx, |ui| {
-            ui.label("Select sort order (or press 1-9, 0, -, L):");
+            ui.label("Select sort order (or press 1-9, 0, -, N, M, A, Z, L):");
             ui.separator();
 
             let options = [
@@ -1340,6 +1834,10 @@ pub(super) fn handle_dialogs(
                 ("9. Random", "random", egui::Key::Num9),
                 ("0. EXIF Date (Oldest First)", "exif-date", egui::Key::Num0),
                 ("-. EXIF Date (Newest First)", "exif-date-desc", egui::Key::Minus),
+                ("N. Name Natural, ignore case (A-Z)", "name-natural-ci", egui::Key::N),
+                ("M. Name Natural, ignore case (Z-A)", "name-natural-ci-desc", egui::Key::M),
+                ("A. Aspect Ratio (Portrait First)", "aspect", egui::Key::A),
+                ("Z. Aspect Ratio (Panoramas First)", "aspect-desc", egui::Key::Z),
                 ("L. Location (Spatial)", "location", egui::Key::L),
             ];
 
@@ -1365,21 +1863,7 @@ pub(super) fn handle_dialogs(
             if sort == "CANCEL" {
                 app.state.handle_input(InputIntent::Cancel);
             } else {
-                // Update stored preference for future scans
-                app.view_mode_sort = Some(sort.clone());
-
-                if sort == "location" {
-                    app.apply_location_sort();
-                } else {
-                    // Explicitly sort subdirectories (AppState only handles files)
-                    scanner::sort_directories(&mut app.subdirs, &sort);
-                    // Update GPS map sort mode based on sort order
-                    app.gps_map.sort_by_exif_timestamp =
-                        sort == "exif-date" || sort == "exif-date-desc";
-                    app.gps_map.markers_needs_sort = true; // Force re-sort with new mode
-                    app.state.handle_input(InputIntent::ChangeSortOrder(sort));
-                }
-                app.cache_dirty = true;
+                app.apply_sort(sort);
             }
         }
     }
@@ -1541,8 +2025,21 @@ pub(super) fn handle_dialogs(
     // which both burns CPU (the scroll_to_rect smooth-scroll triggered by
     // `selection_changed = true` repaints at full frame rate for a few hundred
     // ms after each advance) and changes the file the user is about to confirm.
-    let dialog_blocking_slideshow =
-        app.state.is_any_dialog_open() || app.show_move_input || app.show_dir_picker;
+    let dialog_blocking_slideshow = app.state.is_any_dialog_open()
+        || app.show_move_input
+        || app.show_goto_input
+        || app.show_jump_input
+        || app.show_time_shift
+        || app.pin_location.is_some()
+        || app.strip_gps.is_some()
+        || app.write_gps.is_some()
+        || app.fill_metadata.is_some()
+        || app.group_review.is_some()
+        || app.cache_panel.is_some()
+        || app.web_export.is_some()
+        || app.review_move.is_some()
+        || app.cull.is_some()
+        || app.show_dir_picker;
 
     if let Some(interval) = app.state.slideshow_interval
         && !app.state.slideshow_paused
@@ -1566,6 +2063,32 @@ pub(super) fn handle_dialogs(
         app.slideshow_last_advance = Some(std::time::Instant::now());
     }
 
+    if let Some(summary) = app.scan_summary.clone() {
+        if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+            app.scan_summary = None;
+        }
+        egui::Window::new("Scan Summary").collapsible(false).resizable(false).show(ctx, |ui| {
+            egui::Grid::new("scan_summary_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Groups:");
+                ui.label(summary.groups.to_string());
+                ui.end_row();
+                ui.label("Files:");
+                ui.label(summary.files.to_string());
+                ui.end_row();
+                ui.label("Identical groups:");
+                ui.label(summary.identical_groups.to_string());
+                ui.end_row();
+                ui.label("Reclaimable:");
+                ui.label(crate::format_size(summary.reclaimable_bytes));
+                ui.end_row();
+            });
+            ui.small("Keeping the largest file of each fully identical group.");
+            if ui.button("OK (Enter)").clicked() {
+                app.scan_summary = None;
+            }
+        });
+    }
+
     if let Some(err_text) = app.state.error_popup.clone() {
         egui::Window::new("Error").max_width(400.0).show(ctx, |ui| {
             egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
@@ -1578,6 +2101,648 @@ pub(super) fn handle_dialogs(
     }
 }
 
+/// Planned EXIF date shift, shown for review before anything is written.
+#[derive(Clone)]
+pub(super) struct TimeShiftPreview {
+    /// Input text the plan was computed for; Apply is only offered while it matches
+    input: String,
+    delta_secs: i64,
+    planned: Vec<(PathBuf, Vec<exif_write::DateEdit>)>,
+    skipped: Vec<(PathBuf, String)>,
+}
+
+fn plan_time_shift(app: &GuiApp, input: &str) -> Result<TimeShiftPreview, String> {
+    let delta_secs = exif_write::parse_time_shift(input)?;
+    let targets: Vec<PathBuf> = if app.state.marked_for_deletion.is_empty() {
+        app.state.get_current_image_path().cloned().into_iter().collect()
+    } else {
+        app.state.marked_for_deletion.clone()
+    };
+    let mut preview = TimeShiftPreview {
+        input: input.to_string(),
+        delta_secs,
+        planned: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for path in targets {
+        if !exif_write::supports_exif_write(&path) {
+            preview.skipped.push((path, "no safe in-place EXIF write for this format".into()));
+            continue;
+        }
+        match exif_write::plan_date_shift(&path, delta_secs) {
+            Ok(edits) => preview.planned.push((path, edits)),
+            Err(e) => preview.skipped.push((path, e)),
+        }
+    }
+    Ok(preview)
+}
+
+fn show_time_shift_dialog(app: &mut GuiApp, ctx: &egui::Context) {
+    let mut do_preview = false;
+    let mut do_apply = false;
+    let mut cancel = false;
+
+    egui::Window::new("Shift EXIF Date").collapsible(false).default_width(520.0).show(ctx, |ui| {
+        let count = app.state.marked_for_deletion.len();
+        ui.label(if count > 0 {
+            format!("Shift DateTimeOriginal/Digitized/DateTime of {} marked files by:", count)
+        } else {
+            "Shift DateTimeOriginal/Digitized/DateTime of the current file by:".to_string()
+        });
+        let res = ui.add(
+            egui::TextEdit::singleline(&mut app.time_shift_input)
+                .hint_text("+2h -3m")
+                .desired_width(200.0),
+        );
+        if !app.time_shift_focus_requested {
+            res.request_focus();
+            app.time_shift_focus_requested = true;
+        }
+        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            do_preview = true;
+        }
+
+        let current =
+            app.time_shift_preview.as_ref().filter(|p| p.input == app.time_shift_input.trim());
+        if let Some(preview) = current {
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for (path, edits) in &preview.planned {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    for e in edits {
+                        ui.monospace(format!(
+                            "{}  {}: {} -> {}",
+                            name,
+                            exif_write::date_tag_name(e.tag),
+                            e.old.format("%Y-%m-%d %H:%M:%S"),
+                            e.new.format("%Y-%m-%d %H:%M:%S")
+                        ));
+                    }
+                }
+                for (path, reason) in &preview.skipped {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!(
+                            "skipped {}: {}",
+                            path.file_name().unwrap_or_default().to_string_lossy(),
+                            reason
+                        ),
+                    );
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Preview (Enter)").clicked() {
+                do_preview = true;
+            }
+            let can_apply = current.is_some_and(|p| !p.planned.is_empty());
+            if ui.add_enabled(can_apply, egui::Button::new("Write to files")).clicked() {
+                do_apply = true;
+            }
+            if ui.button("Cancel").clicked() {
+                cancel = true;
+            }
+        });
+    });
+
+    if do_preview {
+        match plan_time_shift(app, app.time_shift_input.trim()) {
+            Ok(preview) => app.time_shift_preview = Some(preview),
+            Err(e) => app.set_status(format!("Invalid shift: {}", e), true),
+        }
+    }
+    if do_apply && let Some(preview) = app.time_shift_preview.take() {
+        apply_time_shift(app, preview);
+        app.show_time_shift = false;
+    }
+    if cancel {
+        app.show_time_shift = false;
+    }
+}
+
+fn apply_time_shift(app: &mut GuiApp, preview: TimeShiftPreview) {
+    let mut written = 0;
+    let mut failed = Vec::new();
+    for (path, edits) in &preview.planned {
+        match exif_write::apply_date_edits(path, edits) {
+            Ok(()) => {
+                written += 1;
+                if let Some(new_ts) = exif_write::shifted_timestamp(edits) {
+                    refresh_after_exif_write(app, path, Some(new_ts), None);
+                }
+            }
+            Err(e) => failed.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    eprintln!(
+        "[EXIF-SHIFT] {:+}s written to {} files, {} skipped, {} failed",
+        preview.delta_secs,
+        written,
+        preview.skipped.len(),
+        failed.len()
+    );
+    for (path, reason) in &preview.skipped {
+        eprintln!("[EXIF-SHIFT]   skipped {}: {}", path.display(), reason);
+    }
+
+    let sort = app.view_mode_sort.clone().unwrap_or_else(|| app.scan_config.group_by.clone());
+    if sort == "exif-date" || sort == "exif-date-desc" {
+        app.state.perform_sort(sort);
+        app.rebuild_file_index();
+        app.gps_map.markers_needs_sort = true;
+    }
+    app.cache_dirty = true;
+    app.cached_exif = None;
+
+    if failed.is_empty() {
+        app.set_status(
+            format!("Shifted EXIF date of {} files ({} skipped)", written, preview.skipped.len()),
+            false,
+        );
+    } else {
+        app.state.error_popup = Some(format!("EXIF date write failed:\n{}", failed.join("\n")));
+    }
+}
+
+/// Files a GPS strip would rewrite, and those whose format can't be rewritten.
+pub(super) struct StripGpsPlan {
+    targets: Vec<PathBuf>,
+    unsupported: Vec<PathBuf>,
+    /// The files come from the delete marks, not the current file
+    marked: bool,
+}
+
+fn plan_strip_gps(app: &GuiApp) -> StripGpsPlan {
+    let (targets, unsupported) = exif_write_selection(app);
+    StripGpsPlan { targets, unsupported, marked: !app.state.marked_for_deletion.is_empty() }
+}
+
+/// Marked files (or the current file), split into those whose EXIF we can
+/// rewrite and those we can't.
+fn exif_write_selection(app: &GuiApp) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let selection: Vec<PathBuf> = if app.state.marked_for_deletion.is_empty() {
+        app.state.get_current_image_path().cloned().into_iter().collect()
+    } else {
+        app.state.marked_for_deletion.clone()
+    };
+    selection.into_iter().partition(|p| exif_write::supports_exif_write(p))
+}
+
+fn show_strip_gps_dialog(app: &mut GuiApp, ctx: &egui::Context) {
+    let Some(plan) = &app.strip_gps else { return };
+    let mut confirm = false;
+    let mut cancel = false;
+
+    egui::Window::new("Strip GPS").collapsible(false).default_width(520.0).show(ctx, |ui| {
+        ui.label(if plan.marked {
+            format!(
+                "Remove the GPS tags (EXIF and XMP) of the {} JPEG/TIFF files marked for \
+                 deletion? They are rewritten on disk, and stay marked.",
+                plan.targets.len()
+            )
+        } else {
+            "Remove the GPS tags (EXIF and XMP) of the current file? It is rewritten on disk."
+                .to_string()
+        });
+        if !plan.unsupported.is_empty() {
+            ui.separator();
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "{} files can't be stripped and keep any location they carry:",
+                    plan.unsupported.len()
+                ),
+            );
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                for path in &plan.unsupported {
+                    ui.monospace(path.file_name().unwrap_or_default().to_string_lossy());
+                }
+            });
+        }
+        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            confirm = true;
+        }
+        ui.horizontal(|ui| {
+            let can_strip = !plan.targets.is_empty();
+            if ui.add_enabled(can_strip, egui::Button::new("Strip GPS (Enter)")).clicked() {
+                confirm = true;
+            }
+            if ui.button("Cancel").clicked() {
+                cancel = true;
+            }
+        });
+    });
+
+    if confirm && let Some(plan) = app.strip_gps.take() {
+        apply_strip_gps(app, plan);
+    }
+    if cancel {
+        app.strip_gps = None;
+    }
+}
+
+fn apply_strip_gps(app: &mut GuiApp, plan: StripGpsPlan) {
+    let mut stripped = 0;
+    let mut no_gps = 0;
+    let mut failed = Vec::new();
+    for path in &plan.targets {
+        match crate::fileops::strip_gps(path) {
+            Ok(true) => {
+                stripped += 1;
+                // New content hash, so the cached features (with GPS) no longer apply
+                let content_hash = fs::read(path)
+                    .map(|bytes| *blake3::keyed_hash(&app.ctx.content_key, &bytes).as_bytes())
+                    .unwrap_or([0u8; 32]);
+                for file in app.state.groups.iter_mut().flatten().filter(|f| &f.path == path) {
+                    file.gps_pos = None;
+                    file.content_hash = content_hash;
+                }
+                app.gps_map.remove_marker(path);
+            }
+            Ok(false) => no_gps += 1,
+            Err(e) => failed.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    eprintln!(
+        "[STRIP-GPS] {} stripped, {} without GPS, {} unsupported, {} failed",
+        stripped,
+        no_gps,
+        plan.unsupported.len(),
+        failed.len()
+    );
+    app.cache_dirty = true;
+    app.cached_exif = None;
+
+    if failed.is_empty() && plan.unsupported.is_empty() {
+        app.set_status(
+            format!("Stripped GPS from {} files ({} had none)", stripped, no_gps),
+            false,
+        );
+        return;
+    }
+    let mut report = format!("Stripped GPS from {} files ({} had none).\n", stripped, no_gps);
+    if !plan.unsupported.is_empty() {
+        report.push_str("\nNot stripped, format not supported (may still carry location):\n");
+        for path in &plan.unsupported {
+            report.push_str(&format!("{}\n", path.display()));
+        }
+    }
+    if !failed.is_empty() {
+        report.push_str("\nFailed (still carry location):\n");
+        report.push_str(&failed.join("\n"));
+    }
+    app.state.error_popup = Some(report);
+}
+
+/// Which way a review folder move goes, waiting for confirmation.
+#[derive(Clone, Copy)]
+pub(super) enum ReviewMove {
+    /// The marked files into `review_dir`
+    Marked,
+    /// Everything in the review manifest (this many entries) back out
+    Restore(usize),
+}
+
+fn show_review_move_dialog(app: &mut GuiApp, ctx: &egui::Context, review_move: ReviewMove) {
+    let Some(review_dir) = app.state.review_dir.clone() else {
+        app.review_move = None;
+        return;
+    };
+    let mut confirm = false;
+    let mut cancel = false;
+
+    egui::Window::new("Move to review folder").collapsible(false).default_width(520.0).show(
+        ctx,
+        |ui| {
+            match review_move {
+                ReviewMove::Marked => {
+                    ui.label(format!(
+                        "Move {} marked files into {}?",
+                        app.state.listed_marks().len(),
+                        review_dir.display()
+                    ));
+                    match &app.state.scan_root {
+                        Some(root) => ui.label(format!(
+                            "Their folders relative to {} are recreated there.",
+                            root.display()
+                        )),
+                        None => ui.label("Their absolute folders are recreated there."),
+                    };
+                    ui.label("\"Restore review\" in the action bar moves them back.");
+                }
+                ReviewMove::Restore(count) => {
+                    ui.label(format!(
+                        "Move {} files in {} back where they came from?",
+                        count,
+                        review_dir.display()
+                    ));
+                    ui.label("Files that can't be moved back stay in the review folder.");
+                }
+            }
+            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                confirm = true;
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Move (Enter)").clicked() {
+                    confirm = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        },
+    );
+
+    if confirm {
+        app.review_move = None;
+        match review_move {
+            ReviewMove::Marked => {
+                app.state.move_marked_to_review();
+                app.cache_dirty = true;
+                app.rebuild_file_index();
+            }
+            ReviewMove::Restore(_) => app.state.restore_review(),
+        }
+    }
+    if cancel {
+        app.review_move = None;
+    }
+}
+
+/// A location to write into the EXIF of the target files.
+pub(super) struct WriteGpsPlan {
+    /// Where the point came from ("map point", a saved location name, ...)
+    source: String,
+    lat: f64,
+    lon: f64,
+    targets: Vec<PathBuf>,
+    unsupported: Vec<PathBuf>,
+}
+
+/// Plan writing `point` (x = lon, y = lat) to the marked files or the current file.
+pub(super) fn plan_write_gps(app: &GuiApp, point: geo::Point<f64>, source: String) -> WriteGpsPlan {
+    let (targets, unsupported) = exif_write_selection(app);
+    WriteGpsPlan { source, lat: point.y(), lon: point.x(), targets, unsupported }
+}
+
+fn show_write_gps_dialog(app: &mut GuiApp, ctx: &egui::Context) {
+    let Some(plan) = &app.write_gps else { return };
+    let mut confirm = false;
+    let mut cancel = false;
+
+    egui::Window::new("Write GPS").collapsible(false).default_width(520.0).show(ctx, |ui| {
+        ui.label(format!(
+            "Write {} ({:.6}, {:.6}) into {} JPEG/TIFF files?",
+            plan.source,
+            plan.lat,
+            plan.lon,
+            plan.targets.len()
+        ));
+        ui.label(
+            "Any location they carry is replaced. Files are rewritten on disk, mtime is kept.",
+        );
+        egui::ScrollArea::vertical().id_salt("write_gps_targets").max_height(200.0).show(
+            ui,
+            |ui| {
+                for path in &plan.targets {
+                    ui.monospace(path.file_name().unwrap_or_default().to_string_lossy());
+                }
+            },
+        );
+        if !plan.unsupported.is_empty() {
+            ui.separator();
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "{} files are RAW or another unsupported format and are skipped:",
+                    plan.unsupported.len()
+                ),
+            );
+            egui::ScrollArea::vertical().id_salt("write_gps_unsupported").max_height(120.0).show(
+                ui,
+                |ui| {
+                    for path in &plan.unsupported {
+                        ui.monospace(path.file_name().unwrap_or_default().to_string_lossy());
+                    }
+                },
+            );
+        }
+        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            confirm = true;
+        }
+        ui.horizontal(|ui| {
+            let can_write = !plan.targets.is_empty();
+            if ui.add_enabled(can_write, egui::Button::new("Write GPS (Enter)")).clicked() {
+                confirm = true;
+            }
+            if ui.button("Cancel").clicked() {
+                cancel = true;
+            }
+        });
+    });
+
+    if confirm && let Some(plan) = app.write_gps.take() {
+        apply_write_gps(app, plan);
+    }
+    if cancel {
+        app.write_gps = None;
+    }
+}
+
+fn apply_write_gps(app: &mut GuiApp, plan: WriteGpsPlan) {
+    let mut written = 0;
+    let mut failed = Vec::new();
+    for path in &plan.targets {
+        if let Err(e) = crate::fileops::write_gps(path, plan.lat, plan.lon) {
+            failed.push(format!("{}: {}", path.display(), e));
+            continue;
+        }
+        written += 1;
+        refresh_after_exif_write(app, path, None, Some((plan.lat, plan.lon)));
+    }
+    eprintln!(
+        "[WRITE-GPS] ({:.6}, {:.6}) written to {} files, {} unsupported, {} failed",
+        plan.lat,
+        plan.lon,
+        written,
+        plan.unsupported.len(),
+        failed.len()
+    );
+    app.gps_map.markers_needs_sort = true;
+    app.cache_dirty = true;
+    app.cached_exif = None;
+
+    if failed.is_empty() && plan.unsupported.is_empty() {
+        app.set_status(format!("Wrote GPS to {} files", written), false);
+        return;
+    }
+    let mut report = format!("Wrote GPS to {} files.\n", written);
+    if !plan.unsupported.is_empty() {
+        report.push_str("\nNot written, format not supported:\n");
+        for path in &plan.unsupported {
+            report.push_str(&format!("{}\n", path.display()));
+        }
+    }
+    if !failed.is_empty() {
+        report.push_str("\nFailed:\n");
+        report.push_str(&failed.join("\n"));
+    }
+    app.state.error_popup = Some(report);
+}
+
+/// After `path` got a new capture time `ts` and/or location `gps` (lat, lon) written
+/// into its EXIF: hash it again, update the listed copies and their map marker, and
+/// cache the features under the new content hash with the new values.
+pub(super) fn refresh_after_exif_write(
+    app: &mut GuiApp,
+    path: &Path,
+    ts: Option<i64>,
+    gps: Option<(f64, f64)>,
+) {
+    let Ok(bytes) = fs::read(path) else { return };
+    let content_hash = *blake3::keyed_hash(&app.ctx.content_key, &bytes).as_bytes();
+    let mut file_info = None;
+    for file in app.state.groups.iter_mut().flatten().filter(|f| f.path == path) {
+        let old_hash = std::mem::replace(&mut file.content_hash, content_hash);
+        if ts.is_some() {
+            file.exif_timestamp = ts;
+        }
+        if let Some((lat, lon)) = gps {
+            file.gps_pos = Some(geo::Point::new(lon, lat));
+        }
+        file_info = Some((old_hash, file.unique_file_id, file.exif_timestamp, file.resolution));
+    }
+    let Some((old_hash, unique_file_id, exif_timestamp, resolution)) = file_info else {
+        return;
+    };
+
+    if let Some((lat, lon)) = gps {
+        app.gps_map.remove_marker(path);
+        app.gps_map.add_marker(path.to_path_buf(), lat, lon, exif_timestamp);
+    } else if let Some(&i) = app.gps_map.path_to_marker.get(path) {
+        app.gps_map.markers[i].exif_timestamp = exif_timestamp;
+    }
+
+    let mut features = app.ctx.get_features(&old_hash).ok().flatten().unwrap_or_else(|| {
+        let (w, h) = resolution.unwrap_or((0, 0));
+        ImageFeatures::new(w, h)
+    });
+    if let Some(ts) = ts {
+        features.insert_tag(TAG_DERIVED_TIMESTAMP, ExifValue::Long64(ts));
+    }
+    if let Some((lat, lon)) = gps {
+        features.insert_tag(TAG_GPS_LATITUDE, ExifValue::Float(lat));
+        features.insert_tag(TAG_GPS_LONGITUDE, ExifValue::Float(lon));
+    }
+    if let Some(db_tx) = &app.db_tx
+        && let Some(update) = crate::db::create_feature_update(
+            &app.ctx.meta_key,
+            path,
+            unique_file_id,
+            content_hash,
+            features,
+        )
+    {
+        let _ = db_tx.send(update);
+    }
+}
+
+/// Log of the changes seen in the watched folder (fs_notifications = "panel"),
+/// newest at the bottom. Non-modal: browsing goes on while it is open.
+fn show_fs_events_panel(app: &mut GuiApp, ctx: &egui::Context) {
+    let mut open = true;
+    let mut clear = false;
+    egui::Window::new("File system events").open(&mut open).default_size([480.0, 260.0]).show(
+        ctx,
+        |ui| {
+            if app.gui_config.fs_notifications != Some(crate::db::FsNotifications::Panel) {
+                ui.label("Events are logged here with fs_notifications = \"panel\" in [gui].");
+            }
+            ui.horizontal(|ui| {
+                ui.label(format!("{} events", app.fs_events.len()));
+                clear = ui.button("Clear").clicked();
+            });
+            ui.separator();
+            egui::ScrollArea::vertical().stick_to_bottom(true).auto_shrink(false).show(ui, |ui| {
+                for (time, msg) in &app.fs_events {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.monospace(time);
+                        ui.label(msg);
+                    });
+                }
+            });
+        },
+    );
+    if clear {
+        app.fs_events.clear();
+    }
+    if !open {
+        app.show_fs_events = false;
+    }
+}
+
+/// Tab completion for a typed path: cycles through entries of the parent directory
+/// whose name starts with the typed prefix. Candidates are rescanned whenever the
+/// input no longer matches the completion applied last. Directories get a trailing
+/// separator so the next Tab descends into them. With `base` the input is a bare
+/// name in that directory and completes to bare names (rename); `dirs_only` leaves
+/// files out (move target).
+fn complete_path_input(
+    input: &mut String,
+    candidates: &mut Vec<String>,
+    index: &mut usize,
+    base: Option<&Path>,
+    dirs_only: bool,
+) {
+    let path_buf = std::path::PathBuf::from(input.as_str());
+    let (parent, prefix) = if let Some(dir) = base {
+        (Some(dir), input.clone())
+    } else if input.ends_with(std::path::MAIN_SEPARATOR) {
+        (Some(path_buf.as_path()), String::new())
+    } else {
+        (path_buf.parent(), path_buf.file_name().unwrap_or_default().to_string_lossy().to_string())
+    };
+    let Some(parent_dir) = parent else {
+        return;
+    };
+
+    let prev_idx =
+        if !candidates.is_empty() { (*index + candidates.len() - 1) % candidates.len() } else { 0 };
+    let input_matches_candidate = !candidates.is_empty() && candidates[prev_idx] == *input;
+
+    if candidates.is_empty() || !input_matches_candidate {
+        candidates.clear();
+        *index = 0;
+        if let Ok(entries) = fs::read_dir(parent_dir) {
+            for entry in entries.flatten() {
+                if !entry.file_name().to_string_lossy().starts_with(&prefix) {
+                    continue;
+                }
+                let is_dir = entry.file_type().is_ok_and(|ft| ft.is_dir());
+                if dirs_only && !is_dir {
+                    continue;
+                }
+                if base.is_some() {
+                    candidates.push(entry.file_name().to_string_lossy().to_string());
+                    continue;
+                }
+                let mut name = entry.path().to_string_lossy().to_string();
+                if is_dir {
+                    name.push(std::path::MAIN_SEPARATOR);
+                }
+                candidates.push(name);
+            }
+            candidates.sort();
+        }
+    }
+
+    if !candidates.is_empty() {
+        *input = candidates[*index].clone();
+        *index = (*index + 1) % candidates.len();
+    }
+}
+
 /// Ignore all files in the current group (Ctrl+Q confirmation).
 /// Sets ignored=true for all files in the group, then removes the group from display.
 fn perform_ignore_group(app: &mut GuiApp) {
@@ -1612,16 +2777,19 @@ fn perform_ignore_group(app: &mut GuiApp) {
                 .get_group_uuid(&hashes[0])
                 .map(|u| crate::db::AppContext::format_uuid(&u))
                 .unwrap_or_else(|| "unknown".to_string());
-            eprintln!(
+            debug_log!(
                 "[DEBUG-IGNORE] Ignored group {}: {} files (uuid={})",
-                g_idx, count, uuid_str,
+                g_idx,
+                count,
+                uuid_str,
             );
             for (path_display, ch) in &file_info {
-                eprintln!("[DEBUG-IGNORE]   {} blake3={}", path_display, hex::encode(ch),);
+                debug_log!("[DEBUG-IGNORE]   {} blake3={}", path_display, hex::encode(ch),);
             }
 
             // Clear marks belonging to this group
             app.state.marked_for_deletion.retain(|p| !group_paths.contains(p));
+            app.state.marks_generation += 1;
 
             // Remove the group from display
             app.state.groups.remove(g_idx);
@@ -1659,6 +2827,7 @@ fn perform_advanced_search(app: &mut GuiApp) {
 
     let mut standard_query_parts = Vec::new();
     let mut geo_filters: Vec<GeoDistanceFilter> = Vec::new();
+    let mut aspect_filters: Vec<(Bound<f64>, Bound<f64>)> = Vec::new();
     let mut search_errors = Vec::new(); // General list for all parse errors
 
     let raw_terms = exif_query_raw.split_whitespace();
@@ -1724,6 +2893,15 @@ fn perform_advanced_search(app: &mut GuiApp) {
                         .push(format!("Invalid coordinates in '{}'. Expected numbers.", term));
                 }
             }
+        } else if let Some(spec) = term_lc.strip_prefix("aspect:") {
+            // Parse "aspect:>2", "aspect:<=1", "aspect:1.3-1.4" or "aspect:1.5"
+            match parse_aspect_filter(spec) {
+                Some(bounds) => aspect_filters.push(bounds),
+                None => search_errors.push(format!(
+                    "Invalid aspect filter '{}'. Expected e.g. aspect:>2 or aspect:1.3-1.4",
+                    term
+                )),
+            }
         } else {
             standard_query_parts.push(term);
         }
@@ -1822,7 +3000,12 @@ fn perform_advanced_search(app: &mut GuiApp) {
                 false
             };
 
-            if exif_matches && matches_geo {
+            let matches_aspect = aspect_filters.is_empty()
+                || file
+                    .aspect_ratio()
+                    .is_some_and(|r| aspect_filters.iter().all(|b| b.contains(&r)));
+
+            if exif_matches && matches_geo && matches_aspect {
                 let match_source = if !clean_exif_query.is_empty() && filename_regex.is_some() {
                     format!("Filename + {}", clean_exif_query)
                 } else if !clean_exif_query.is_empty() {
@@ -1866,6 +3049,26 @@ fn perform_advanced_search(app: &mut GuiApp) {
     }
 }
 
+/// Bounds of an `aspect:` search term: ">2", ">=2", "<1", "<=1", a range "1.3-1.4"
+/// (open-ended "2-"), or a single ratio, matched within 1%.
+fn parse_aspect_filter(spec: &str) -> Option<(Bound<f64>, Bound<f64>)> {
+    let number = |s: &str| s.trim().parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0);
+    if let Some(v) = spec.strip_prefix(">=") {
+        Some((Bound::Included(number(v)?), Bound::Unbounded))
+    } else if let Some(v) = spec.strip_prefix("<=") {
+        Some((Bound::Unbounded, Bound::Included(number(v)?)))
+    } else if let Some(v) = spec.strip_prefix('>') {
+        Some((Bound::Excluded(number(v)?), Bound::Unbounded))
+    } else if let Some(v) = spec.strip_prefix('<') {
+        Some((Bound::Unbounded, Bound::Excluded(number(v)?)))
+    } else if let Some((min, max)) = crate::search_index::parse_range_value(spec) {
+        Some((Bound::Included(number(&min)?), Bound::Included(number(&max)?)))
+    } else {
+        let v = number(spec)?;
+        Some((Bound::Included(v * 0.99), Bound::Included(v * 1.01)))
+    }
+}
+
 // Helper to avoid duplication between DistanceFrom and DistanceLonLat
 // Returns true if filter was successfully added, false if range was invalid
 fn parse_and_add_geo_filter(
@@ -1920,6 +3123,7 @@ fn check_exif_criteria_fallback(
     if criteria.iter().any(|c| c.tag_id >= 0xF000) {
         tag_names.push("DerivedSunPosition".to_string());
         tag_names.push("DerivedCountry".to_string());
+        tag_names.push("DerivedLens".to_string());
     }
 
     // Get or fetch EXIF data
@@ -0,0 +1,260 @@
+//! Fill metadata gaps (Ctrl+Shift+T): the marked files (or the current file) that
+//! lack a capture time or a location get values from their neighbours in list
+//! order. A missing time is interpolated between the nearest timestamped files
+//! before and after it (or copied when there is only one side), a missing location
+//! is copied from the nearest geotagged file. Every proposal is listed and can be
+//! unticked before anything is written; JPEG/TIFF only, like the other EXIF edits.
+
+use chrono::{DateTime, NaiveDateTime};
+use eframe::egui;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::app::GuiApp;
+use crate::FileMetadata;
+use crate::exif_write;
+
+/// A value proposed for one file and where it came from.
+struct Proposal<T> {
+    value: T,
+    /// "from a.jpg" or "between a.jpg and c.jpg"
+    source: String,
+    apply: bool,
+}
+
+/// What would be written into one file.
+struct FileFill {
+    path: PathBuf,
+    time: Option<Proposal<NaiveDateTime>>,
+    /// (lat, lon)
+    gps: Option<Proposal<(f64, f64)>>,
+}
+
+/// State of the open fill window.
+pub(super) struct FillMetadata {
+    fills: Vec<FileFill>,
+    /// Selected files that get nothing, and why
+    skipped: Vec<(PathBuf, &'static str)>,
+}
+
+impl FillMetadata {
+    pub(super) fn open(app: &GuiApp) -> Self {
+        let files: Vec<&FileMetadata> = app.state.groups.iter().flatten().collect();
+        let position: HashMap<&PathBuf, usize> =
+            files.iter().enumerate().map(|(i, f)| (&f.path, i)).collect();
+        let targets: Vec<PathBuf> = if app.state.marked_for_deletion.is_empty() {
+            app.state.get_current_image_path().cloned().into_iter().collect()
+        } else {
+            app.state.marked_for_deletion.clone()
+        };
+
+        let mut fills = Vec::new();
+        let mut skipped = Vec::new();
+        for path in targets {
+            let Some(&pos) = position.get(&path) else { continue };
+            let file = files[pos];
+            if file.exif_timestamp.is_some() && file.gps_pos.is_some() {
+                skipped.push((path, "has a capture time and a location"));
+                continue;
+            }
+            if !exif_write::supports_exif_write(&path) {
+                skipped.push((path, "no safe EXIF write for this format"));
+                continue;
+            }
+            let time =
+                if file.exif_timestamp.is_none() { interpolate_time(&files, pos) } else { None };
+            let gps = if file.gps_pos.is_none() { nearest_gps(&files, pos) } else { None };
+            if time.is_none() && gps.is_none() {
+                skipped.push((path, "no neighbour has the missing values"));
+                continue;
+            }
+            fills.push(FileFill { path, time, gps });
+        }
+        Self { fills, skipped }
+    }
+}
+
+fn file_name(file: &FileMetadata) -> String {
+    file.path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// Capture time for the file at `pos`: linear in list position between the nearest
+/// timestamped files on either side, or a copy of the only one found.
+fn interpolate_time(files: &[&FileMetadata], pos: usize) -> Option<Proposal<NaiveDateTime>> {
+    let before = files[..pos]
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, f)| f.exif_timestamp.map(|ts| (i, ts, *f)));
+    let after = files[pos + 1..]
+        .iter()
+        .enumerate()
+        .find_map(|(i, f)| f.exif_timestamp.map(|ts| (pos + 1 + i, ts, *f)));
+    let (ts, source) = match (before, after) {
+        (Some((i, a, fa)), Some((j, b, fb))) => (
+            a + (b - a) * (pos - i) as i64 / (j - i) as i64,
+            format!("between {} and {}", file_name(fa), file_name(fb)),
+        ),
+        (Some((_, ts, f)), None) | (None, Some((_, ts, f))) => {
+            (ts, format!("from {}", file_name(f)))
+        }
+        (None, None) => return None,
+    };
+    // Timestamps hold the EXIF wall-clock time as if it were UTC
+    let value = DateTime::from_timestamp(ts, 0)?.naive_utc();
+    Some(Proposal { value, source, apply: true })
+}
+
+/// Location of the nearest geotagged file in list order; the earlier one on a tie.
+fn nearest_gps(files: &[&FileMetadata], pos: usize) -> Option<Proposal<(f64, f64)>> {
+    (1..files.len())
+        .find_map(|d| {
+            let before = pos.checked_sub(d).map(|i| files[i]);
+            let after = files.get(pos + d).copied();
+            before.into_iter().chain(after).find_map(|f| f.gps_pos.map(|p| (p, f)))
+        })
+        .map(|(p, f)| Proposal {
+            value: (p.y(), p.x()),
+            source: format!("from {}", file_name(f)),
+            apply: true,
+        })
+}
+
+/// The fill window: the proposals with a checkbox each, then Write or Cancel.
+pub(super) fn show(app: &mut GuiApp, ctx: &egui::Context) {
+    let Some(fill) = app.fill_metadata.as_mut() else { return };
+    let mut write = false;
+    let mut cancel = false;
+
+    egui::Window::new("Fill missing metadata").collapsible(false).default_width(640.0).show(
+        ctx,
+        |ui| {
+            if fill.fills.is_empty() {
+                ui.label("Nothing to fill in.");
+            } else {
+                ui.label(format!(
+                    "Proposed values for {} files, taken from their neighbours in the list. \
+                     Hover a value to see its source.",
+                    fill.fills.len()
+                ));
+                egui::ScrollArea::vertical().id_salt("fill_metadata").max_height(300.0).show(
+                    ui,
+                    |ui| {
+                        egui::Grid::new("fill_metadata_grid").num_columns(3).striped(true).show(
+                            ui,
+                            |ui| {
+                                ui.strong("File");
+                                ui.strong("Capture time");
+                                ui.strong("Location");
+                                ui.end_row();
+                                for f in &mut fill.fills {
+                                    ui.monospace(
+                                        f.path.file_name().unwrap_or_default().to_string_lossy(),
+                                    );
+                                    match &mut f.time {
+                                        Some(p) => {
+                                            let text = p.value.format("%Y-%m-%d %H:%M:%S");
+                                            ui.checkbox(&mut p.apply, text.to_string())
+                                                .on_hover_text(p.source.as_str());
+                                        }
+                                        None => {
+                                            ui.label("-");
+                                        }
+                                    }
+                                    match &mut f.gps {
+                                        Some(p) => {
+                                            let (lat, lon) = p.value;
+                                            ui.checkbox(
+                                                &mut p.apply,
+                                                format!("{:.6}, {:.6}", lat, lon),
+                                            )
+                                            .on_hover_text(p.source.as_str());
+                                        }
+                                        None => {
+                                            ui.label("-");
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            },
+                        );
+                    },
+                );
+                ui.label("Files are rewritten on disk, mtime is kept.");
+            }
+            if !fill.skipped.is_empty() {
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .id_salt("fill_metadata_skipped")
+                    .max_height(120.0)
+                    .show(ui, |ui| {
+                        for (path, reason) in &fill.skipped {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                format!(
+                                    "skipped {}: {}",
+                                    path.file_name().unwrap_or_default().to_string_lossy(),
+                                    reason
+                                ),
+                            );
+                        }
+                    });
+            }
+            ui.horizontal(|ui| {
+                let any = fill.fills.iter().any(|f| {
+                    f.time.as_ref().is_some_and(|p| p.apply)
+                        || f.gps.as_ref().is_some_and(|p| p.apply)
+                });
+                write = ui.add_enabled(any, egui::Button::new("Write to files")).clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        },
+    );
+
+    if write && let Some(fill) = app.fill_metadata.take() {
+        apply(app, fill);
+    }
+    if cancel {
+        app.fill_metadata = None;
+    }
+}
+
+fn apply(app: &mut GuiApp, fill: FillMetadata) {
+    let mut written = 0;
+    let mut failed = Vec::new();
+    for f in &fill.fills {
+        let when = f.time.as_ref().filter(|p| p.apply).map(|p| p.value);
+        let gps = f.gps.as_ref().filter(|p| p.apply).map(|p| p.value);
+        if when.is_none() && gps.is_none() {
+            continue;
+        }
+        if let Err(e) = crate::fileops::fill_exif(&f.path, when, gps) {
+            failed.push(format!("{}: {}", f.path.display(), e));
+            continue;
+        }
+        written += 1;
+        let ts = when.map(|w| w.and_utc().timestamp());
+        super::dialogs::refresh_after_exif_write(app, &f.path, ts, gps);
+    }
+    eprintln!(
+        "[FILL-EXIF] {} files written, {} skipped, {} failed",
+        written,
+        fill.skipped.len(),
+        failed.len()
+    );
+
+    let sort = app.view_mode_sort.clone().unwrap_or_else(|| app.scan_config.group_by.clone());
+    if sort == "exif-date" || sort == "exif-date-desc" {
+        app.state.perform_sort(sort);
+        app.rebuild_file_index();
+    }
+    app.gps_map.markers_needs_sort = true;
+    app.cache_dirty = true;
+    app.cached_exif = None;
+
+    if failed.is_empty() {
+        app.set_status(format!("Filled in metadata of {} files", written), false);
+    } else {
+        app.state.error_popup = Some(format!("Writing metadata failed:\n{}", failed.join("\n")));
+    }
+}
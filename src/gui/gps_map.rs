@@ -1,4 +1,5 @@
 // GPS Map widget using walkers crate for displaying image locations on a map
+use crate::db::{BearingStyle, DistanceUnit, GuiConfig};
 use eframe::egui;
 use geo::Point;
 use once_cell::sync::Lazy;
@@ -239,6 +240,10 @@ pub struct GpsMarker {
     pub sun_elevation: Option<f64>,
     /// EXIF timestamp (Unix epoch seconds) for chronological sorting
     pub exif_timestamp: Option<i64>,
+    /// Camera name for `MarkerColoring::Camera`, filled in on demand
+    pub camera: Option<String>,
+    /// Whether `camera` has been looked up (None can mean "no camera info")
+    pub camera_checked: bool,
 }
 
 impl GpsMarker {
@@ -247,6 +252,137 @@ impl GpsMarker {
     }
 }
 
+/// How markers other than the current one are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarkerColoring {
+    #[default]
+    Plain,
+    /// Gradient from the oldest (blue) to the newest (red) EXIF timestamp
+    Age,
+    /// One palette color per camera Make/Model
+    Camera,
+}
+
+impl MarkerColoring {
+    pub const ALL: [Self; 3] = [Self::Plain, Self::Age, Self::Camera];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Plain => "Plain",
+            Self::Age => "By age",
+            Self::Camera => "By camera",
+        }
+    }
+}
+
+/// Categorical colors for `MarkerColoring::Camera`; reused in order past the tenth camera
+const CAMERA_PALETTE: [egui::Color32; 10] = [
+    egui::Color32::from_rgb(31, 119, 180),
+    egui::Color32::from_rgb(255, 127, 14),
+    egui::Color32::from_rgb(44, 160, 44),
+    egui::Color32::from_rgb(214, 39, 40),
+    egui::Color32::from_rgb(148, 103, 189),
+    egui::Color32::from_rgb(140, 86, 75),
+    egui::Color32::from_rgb(227, 119, 194),
+    egui::Color32::from_rgb(188, 189, 34),
+    egui::Color32::from_rgb(23, 190, 207),
+    egui::Color32::from_rgb(255, 215, 0),
+];
+
+/// Age gradient: t = 0 (oldest) is blue, t = 1 (newest) is red
+fn age_color(t: f32) -> egui::Color32 {
+    let hue = (1.0 - t.clamp(0.0, 1.0)) * (2.0 / 3.0);
+    egui::ecolor::Hsva::new(hue, 0.85, 0.95, 1.0).into()
+}
+
+/// Per-marker colors for the chosen coloring, plus the legend entries explaining them.
+fn marker_colors(
+    markers: &[GpsMarker],
+    coloring: MarkerColoring,
+) -> (Vec<egui::Color32>, Vec<(egui::Color32, String)>) {
+    match coloring {
+        MarkerColoring::Plain => (vec![egui::Color32::GRAY; markers.len()], Vec::new()),
+        MarkerColoring::Age => {
+            let (min, max) = markers
+                .iter()
+                .filter_map(|m| m.exif_timestamp)
+                .fold((i64::MAX, i64::MIN), |(lo, hi), ts| (lo.min(ts), hi.max(ts)));
+            if min > max {
+                return (vec![egui::Color32::GRAY; markers.len()], Vec::new());
+            }
+            let span = (max - min).max(1) as f32;
+            let colors = markers
+                .iter()
+                .map(|m| match m.exif_timestamp {
+                    Some(ts) => age_color((ts - min) as f32 / span),
+                    None => egui::Color32::GRAY,
+                })
+                .collect();
+            let date = |ts: i64| {
+                chrono::DateTime::from_timestamp(ts, 0)
+                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default()
+            };
+            let legend = vec![
+                (age_color(0.0), format!("Oldest {}", date(min))),
+                (age_color(0.5), format!("Middle {}", date(min + (max - min) / 2))),
+                (age_color(1.0), format!("Newest {}", date(max))),
+                (egui::Color32::GRAY, "No date".to_string()),
+            ];
+            (colors, legend)
+        }
+        MarkerColoring::Camera => {
+            let mut cameras: Vec<&str> =
+                markers.iter().filter_map(|m| m.camera.as_deref()).collect();
+            cameras.sort_unstable();
+            cameras.dedup();
+            let color_of = |name: &str| {
+                let idx = cameras.binary_search(&name).unwrap_or(0);
+                CAMERA_PALETTE[idx % CAMERA_PALETTE.len()]
+            };
+            let colors = markers
+                .iter()
+                .map(|m| m.camera.as_deref().map(color_of).unwrap_or(egui::Color32::GRAY))
+                .collect();
+            let mut legend: Vec<(egui::Color32, String)> =
+                cameras.iter().map(|name| (color_of(name), name.to_string())).collect();
+            legend.push((egui::Color32::GRAY, "Unknown camera".to_string()));
+            (colors, legend)
+        }
+    }
+}
+
+/// Color key in the top-left corner of the map
+fn draw_legend(ui: &egui::Ui, map_rect: egui::Rect, entries: &[(egui::Color32, String)]) {
+    const ROW: f32 = 16.0;
+    const MAX_ROWS: usize = 12;
+    let font = egui::FontId::proportional(11.0);
+    let shown = entries.len().min(MAX_ROWS);
+    let width = entries
+        .iter()
+        .take(shown)
+        .map(|(_, label)| {
+            ui.painter().layout_no_wrap(label.clone(), font.clone(), egui::Color32::WHITE).size().x
+        })
+        .fold(0.0_f32, f32::max)
+        + 30.0;
+    let origin = map_rect.min + egui::vec2(8.0, 8.0);
+    let bg = egui::Rect::from_min_size(origin, egui::vec2(width, shown as f32 * ROW + 6.0));
+    let painter = ui.painter().with_clip_rect(map_rect);
+    painter.rect_filled(bg, 4.0, egui::Color32::from_black_alpha(170));
+    for (row, (color, label)) in entries.iter().take(shown).enumerate() {
+        let y = origin.y + 3.0 + ROW * (row as f32 + 0.5);
+        painter.circle_filled(egui::pos2(origin.x + 10.0, y), 5.0, *color);
+        painter.text(
+            egui::pos2(origin.x + 20.0, y),
+            egui::Align2::LEFT_CENTER,
+            label,
+            font.clone(),
+            egui::Color32::WHITE,
+        );
+    }
+}
+
 /// GPS Map state for the application
 pub struct GpsMapState {
     /// Whether the GPS map panel is visible
@@ -277,12 +413,24 @@ pub struct GpsMapState {
     pub direction_to_image: bool,
     /// Error message if tile provider failed to initialize
     pub tile_error: Option<String>,
+    /// Tile downloads still running, refreshed every frame the map is drawn
+    pub tiles_in_flight: usize,
+    /// Downloads were cancelled: the map draws without tiles until Retry
+    pub tiles_paused: bool,
     /// Sort mode for markers: true = sort by EXIF timestamp, false = sort by distance
     pub sort_by_exif_timestamp: bool,
     /// Last viewed position for movement calculation
     pub last_pos: Option<(f64, f64)>,
     /// Movement text display string
     pub move_text: Option<String>,
+    /// Size of the map widget on the last frame (used to fit markers into view)
+    pub last_map_size: egui::Vec2,
+    /// Point clicked on the map away from any marker (candidate for pinning as a location)
+    pub picked_point: Option<Position>,
+    /// Marker coloring scheme (selector in the map panel)
+    pub coloring: MarkerColoring,
+    /// Units and precision for distances and bearings
+    pub distance_format: DistanceFormat,
 }
 
 impl Default for GpsMapState {
@@ -302,60 +450,94 @@ impl Default for GpsMapState {
             initial_center: None,
             direction_to_image: false,
             tile_error: None,
+            tiles_in_flight: 0,
+            tiles_paused: false,
             sort_by_exif_timestamp: false,
             last_pos: None,
             move_text: None,
+            last_map_size: egui::vec2(400.0, 400.0),
+            picked_point: None,
+            coloring: MarkerColoring::default(),
+            distance_format: DistanceFormat::default(),
         }
     }
 }
 
+/// Zoom used when fitting a single marker (or markers at the same spot):
+/// close enough to see the street, instead of zooming in without limit.
+const FIT_SINGLE_ZOOM: f64 = 15.0;
+/// Fraction of the map viewport left empty around the fitted markers.
+const FIT_MARGIN: f64 = 0.15;
+/// Web mercator tile size in logical points, as rendered by walkers.
+const TILE_SIZE: f64 = 256.0;
+
+/// Project to normalized web mercator coordinates (0..1 on both axes).
+fn mercator(lat: f64, lon: f64) -> (f64, f64) {
+    let lat = lat.clamp(-85.05112878, 85.05112878).to_radians();
+    let x = (lon + 180.0) / 360.0;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0;
+    (x, y)
+}
+
+/// Inverse of `mercator`, returns (lat, lon).
+fn inverse_mercator(x: f64, y: f64) -> (f64, f64) {
+    let n = std::f64::consts::PI * (1.0 - 2.0 * y);
+    (n.sinh().atan().to_degrees(), x * 360.0 - 180.0)
+}
+
+/// Compute the (center lat, center lon, zoom) that fits all positions in a
+/// viewport of `size` points, leaving FIT_MARGIN free around the markers.
+pub fn fit_view(positions: &[(f64, f64)], size: egui::Vec2) -> Option<(f64, f64, f64)> {
+    let (first_lat, first_lon) = *positions.first()?;
+    let (mut min_x, mut min_y) = mercator(first_lat, first_lon);
+    let (mut max_x, mut max_y) = (min_x, min_y);
+    for &(lat, lon) in &positions[1..] {
+        let (x, y) = mercator(lat, lon);
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+
+    let (center_lat, center_lon) = inverse_mercator((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+
+    let dx = max_x - min_x;
+    let dy = max_y - min_y;
+    // ~1 m apart: treat as a single marker
+    if dx < 1e-8 && dy < 1e-8 {
+        return Some((center_lat, center_lon, FIT_SINGLE_ZOOM));
+    }
+
+    let usable_w = (size.x.max(1.0) as f64) * (1.0 - 2.0 * FIT_MARGIN);
+    let usable_h = (size.y.max(1.0) as f64) * (1.0 - 2.0 * FIT_MARGIN);
+    // world width in points at zoom z is TILE_SIZE * 2^z
+    let zoom_x = if dx > 0.0 { (usable_w / (TILE_SIZE * dx)).log2() } else { f64::MAX };
+    let zoom_y = if dy > 0.0 { (usable_h / (TILE_SIZE * dy)).log2() } else { f64::MAX };
+    let zoom = zoom_x.min(zoom_y).clamp(1.0, FIT_SINGLE_ZOOM + 2.0);
+
+    Some((center_lat, center_lon, zoom))
+}
+
 impl GpsMapState {
     pub fn new(_cache_path: PathBuf, provider_name: String, provider_url: String) -> Self {
         Self { provider_name, provider_url, last_pos: None, move_text: None, ..Default::default() }
     }
 
+    /// Center and zoom the map so all positions are visible.
+    /// A single position gets a fixed street-level zoom.
     pub fn fit_positions(&mut self, positions: &[walkers::Position]) {
-        if positions.is_empty() {
-            return;
-        }
-
-        // 1. Calculate the bounding box
-        let mut min_lat = f64::MAX;
-        let mut max_lat = f64::MIN;
-        let mut min_lon = f64::MAX;
-        let mut max_lon = f64::MIN;
-
-        for pos in positions {
-            min_lat = min_lat.min(pos.y());
-            max_lat = max_lat.max(pos.y());
-            min_lon = min_lon.min(pos.x());
-            max_lon = max_lon.max(pos.x());
+        let coords: Vec<(f64, f64)> = positions.iter().map(|p| (p.y(), p.x())).collect();
+        if let Some((lat, lon, zoom)) = fit_view(&coords, self.last_map_size) {
+            self.map_memory.center_at(walkers::lat_lon(lat, lon));
+            let _ = self.map_memory.set_zoom(zoom);
         }
+    }
 
-        // 2. Center at the middle of the box
-        let center = walkers::lat_lon((min_lat + max_lat) / 2.0, (min_lon + max_lon) / 2.0);
-        self.map_memory.center_at(center);
-
-        // 3. Heuristic for zoom level (simple version)
-        let lat_diff = (max_lat - min_lat).abs();
-        let lon_diff = (max_lon - min_lon).abs();
-        let max_diff = lat_diff.max(lon_diff);
-
-        let zoom = if max_diff < 0.001 {
-            17.0
-        } else if max_diff < 0.01 {
-            14.0
-        } else if max_diff < 0.1 {
-            11.0
-        } else if max_diff < 1.0 {
-            8.0
-        } else if max_diff < 10.0 {
-            5.0
-        } else {
-            2.0
-        };
-
-        let _ = self.map_memory.set_zoom(zoom);
+    /// Fit every marker on the map into view. Returns the number of markers fitted.
+    pub fn fit_all_markers(&mut self) -> usize {
+        let positions: Vec<_> = self.markers.iter().map(|m| m.position()).collect();
+        self.fit_positions(&positions);
+        positions.len()
     }
 
     /// Remove a marker by path and mark the list for sorting
@@ -411,6 +593,8 @@ impl GpsMapState {
             sun_azimuth: None,
             sun_elevation: None,
             exif_timestamp,
+            camera: None,
+            camera_checked: false,
         });
         self.path_to_marker.insert(path, idx);
         self.markers_needs_sort = true;
@@ -458,7 +642,7 @@ impl GpsMapState {
                 "[GPS] Chronological Sort Complete. {} of {} markers have EXIF timestamps. Path Length: {}",
                 with_ts,
                 count,
-                format_distance(total_dist)
+                self.distance_format.distance(total_dist)
             );
             self.markers_needs_sort = false;
             return total_dist;
@@ -492,7 +676,10 @@ impl GpsMapState {
             let dist = crate::position::distance(p1, p2);
             total_dist += dist;
         }
-        eprintln!("[GPS] Spatial Sort Complete. Path Length: {}", format_distance(total_dist));
+        eprintln!(
+            "[GPS] Spatial Sort Complete. Path Length: {}",
+            self.distance_format.distance(total_dist)
+        );
         self.markers_needs_sort = false;
         total_dist
     }
@@ -578,18 +765,34 @@ impl GpsMapState {
         self.tile_error = None;
     }
 
-    /// Initialize tiles if not already done
+    /// Initialize tiles if not already done (and not cancelled by the user)
     pub fn ensure_tiles(&mut self, ctx: &egui::Context) {
-        if self.tiles.is_none() {
+        if self.tiles.is_none() && !self.tiles_paused {
             self.init_tiles(ctx);
         }
     }
 
+    /// Abort the running tile downloads. Dropping the tile source drops its
+    /// pending requests; the map keeps drawing markers on a blank background.
+    pub fn cancel_tiles(&mut self) {
+        self.tiles = None;
+        self.tiles_paused = true;
+        self.tiles_in_flight = 0;
+    }
+
+    /// Fetch the visible region again with a fresh tile source, which also
+    /// retries tiles whose download failed earlier.
+    pub fn retry_tiles(&mut self, ctx: &egui::Context) {
+        self.tiles_paused = false;
+        self.init_tiles(ctx);
+    }
+
     /// Change map provider - recreates tiles with new source
     pub fn set_provider(&mut self, name: String, url: String, ctx: &egui::Context) {
         self.provider_name = name;
         self.provider_url = url;
         self.tile_error = None;
+        self.tiles_paused = false;
         // Recreate tiles with new provider
         self.init_tiles(ctx);
     }
@@ -611,6 +814,13 @@ pub struct GpsMarkersPlugin {
     pub draw_lines: bool,
     // Store the position of the currently selected image to calculate distance/bearing
     pub current_image_pos: Option<Position>,
+    /// Previously picked map point, drawn as a crosshair
+    pub picked_point: Option<Position>,
+    /// Index into `markers` of the current image, drawn last with a heavier outline
+    pub current_marker: Option<usize>,
+    /// Receives the clicked map position when the click did not land near a marker
+    pub picked_out: std::sync::Arc<std::sync::Mutex<Option<Position>>>,
+    pub distance_format: DistanceFormat,
 }
 
 impl Plugin for GpsMarkersPlugin {
@@ -672,6 +882,7 @@ impl Plugin for GpsMarkersPlugin {
         let mut closest_dist = f32::MAX;
         let mut closest_idx = -1;
         let mut hovered_marker: Option<(Position, String)> = None;
+        let mut current_screen: Option<(egui::Pos2, f32, egui::Color32)> = None;
 
         for (pos, color, radius, idx, name) in &self.markers {
             let p_lat = pos.y();
@@ -697,12 +908,16 @@ impl Plugin for GpsMarkersPlugin {
             let screen_vec = projector.project(*pos);
             let screen_pos = egui::pos2(screen_vec.x, screen_vec.y);
 
-            painter.circle_filled(screen_pos, *radius, *color);
-            painter.circle_stroke(
-                screen_pos,
-                *radius,
-                egui::Stroke::new(1.5, egui::Color32::WHITE),
-            );
+            if self.current_marker == Some(*idx) {
+                current_screen = Some((screen_pos, *radius, *color));
+            } else {
+                painter.circle_filled(screen_pos, *radius, *color);
+                painter.circle_stroke(
+                    screen_pos,
+                    *radius,
+                    egui::Stroke::new(1.5, egui::Color32::WHITE),
+                );
+            }
 
             // Tooltip Detection
             if let Some(h_pos) = hover_pos
@@ -721,9 +936,46 @@ impl Plugin for GpsMarkersPlugin {
             }
         }
 
+        // The current image's marker goes on top, ringed black and white so it stands
+        // out whatever the coloring
+        if let Some((screen_pos, radius, color)) = current_screen {
+            painter.circle_filled(screen_pos, radius, color);
+            painter.circle_stroke(
+                screen_pos,
+                radius + 1.0,
+                egui::Stroke::new(3.0, egui::Color32::WHITE),
+            );
+            painter.circle_stroke(
+                screen_pos,
+                radius + 3.0,
+                egui::Stroke::new(1.5, egui::Color32::BLACK),
+            );
+        }
+
         // Apply Selection
         if closest_idx >= 0 && closest_dist < 50.0 {
             self.clicked_idx.store(closest_idx, std::sync::atomic::Ordering::Relaxed);
+        } else if let Some(c_pos) = click_pos
+            && let Ok(mut picked) = self.picked_out.lock()
+        {
+            // Empty map area: remember the point so it can be pinned as a location
+            *picked = Some(projector.unproject(c_pos.to_vec2()));
+        }
+
+        // --- DRAW PICKED POINT ---
+        if let Some(pos) = self.picked_point {
+            let p = projector.project(pos);
+            let center = egui::pos2(p.x, p.y);
+            let stroke = egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 0, 160));
+            painter.line_segment(
+                [center - egui::vec2(8.0, 0.0), center + egui::vec2(8.0, 0.0)],
+                stroke,
+            );
+            painter.line_segment(
+                [center - egui::vec2(0.0, 8.0), center + egui::vec2(0.0, 8.0)],
+                stroke,
+            );
+            painter.circle_stroke(center, 5.0, stroke);
         }
 
         if let Some((m_pos, m_name)) = hovered_marker {
@@ -746,8 +998,8 @@ impl Plugin for GpsMarkersPlugin {
                     let (dist, bearing) = crate::position::distance_and_bearing(p1, p2);
 
                     ui.separator();
-                    ui.label(format!("Distance: {}", format_distance(dist)));
-                    ui.label(format!("Bearing: {}", format_bearing(bearing)));
+                    ui.label(format!("Distance: {}", self.distance_format.distance(dist)));
+                    ui.label(format!("Bearing: {}", self.distance_format.bearing(bearing)));
                 }
             });
         }
@@ -783,8 +1035,8 @@ pub fn render_gps_map(
                 let (dist, bearing) = crate::position::distance_and_bearing(last, pos);
                 state.move_text = Some(format!(
                     "Moved {} into direction {}",
-                    format_distance(dist),
-                    format_bearing(bearing)
+                    state.distance_format.distance(dist),
+                    state.distance_format.bearing(bearing)
                 ));
                 state.last_pos = Some(pos);
             }
@@ -832,13 +1084,18 @@ pub fn render_gps_map(
     // Get position of current image for distance comparison
     let current_image_pos =
         current_path.and_then(|p| state.get_marker_by_path(p)).map(|m| m.position());
+    let (colors, legend) = marker_colors(&state.markers, state.coloring);
+    let current_marker = current_path.and_then(|p| state.path_to_marker.get(p)).copied();
     let markers_data: Vec<_> = state
         .markers
         .iter()
         .enumerate()
         .map(|(idx, marker)| {
-            let is_current = current_path.map(|p| p == marker.path).unwrap_or(false);
-            let color = if is_current { egui::Color32::GREEN } else { egui::Color32::GRAY };
+            let is_current = current_marker == Some(idx);
+            let color = match state.coloring {
+                MarkerColoring::Plain if is_current => egui::Color32::GREEN,
+                _ => colors[idx],
+            };
             let radius = if is_current { 8.0 } else { 5.0 };
 
             let name = marker.path.file_name().unwrap_or_default().to_string_lossy().to_string();
@@ -851,9 +1108,12 @@ pub fn render_gps_map(
 
     // Shared atomic to communicate clicked marker from plugin
     let clicked_idx = std::sync::Arc::new(std::sync::atomic::AtomicI32::new(-1));
+    // Shared slot for a click on empty map area
+    let picked_out = std::sync::Arc::new(std::sync::Mutex::new(None));
 
     // Get the available rect for the map before adding it
     let map_rect = ui.available_rect_before_wrap();
+    state.last_map_size = map_rect.size();
 
     // Get sun position for current marker if available
     let current_sun = current_path
@@ -864,39 +1124,50 @@ pub fn render_gps_map(
             _ => None,
         });
 
-    if let Some(ref mut tiles) = state.tiles {
-        let markers_plugin = GpsMarkersPlugin {
-            markers: markers_data,
-            clicked_idx: clicked_idx.clone(),
-            current_sun,
-            map_rect,
-            draw_lines: state.show_path_lines,
-            current_image_pos,
-        };
-        let map =
-            Map::new(Some(tiles), &mut state.map_memory, my_position).with_plugin(markers_plugin);
-        ui.add(map);
-
-        // Check if a marker was clicked
-        let idx = clicked_idx.load(std::sync::atomic::Ordering::Relaxed);
-        if idx >= 0 {
-            let idx = idx as usize;
-            if idx < state.markers.len() {
-                state.selected_marker = Some(idx);
-                clicked_path = Some(state.markers[idx].path.clone());
-            }
+    let markers_plugin = GpsMarkersPlugin {
+        markers: markers_data,
+        clicked_idx: clicked_idx.clone(),
+        current_sun,
+        map_rect,
+        draw_lines: state.show_path_lines,
+        current_image_pos,
+        picked_point: state.picked_point,
+        picked_out: picked_out.clone(),
+        current_marker,
+        distance_format: state.distance_format,
+    };
+    let tiles = state.tiles.as_mut().map(|t| t as &mut dyn walkers::Tiles);
+    let map = Map::new(tiles, &mut state.map_memory, my_position).with_plugin(markers_plugin);
+    ui.add(map);
+    state.tiles_in_flight = state.tiles.as_ref().map_or(0, |t| t.stats().in_progress);
+
+    // Check if a marker was clicked
+    let idx = clicked_idx.load(std::sync::atomic::Ordering::Relaxed);
+    if idx >= 0 {
+        let idx = idx as usize;
+        if idx < state.markers.len() {
+            state.selected_marker = Some(idx);
+            clicked_path = Some(state.markers[idx].path.clone());
         }
+    } else if let Ok(mut picked) = picked_out.lock()
+        && let Some(pos) = picked.take()
+    {
+        state.picked_point = Some(pos);
+    }
 
-        // Draw attribution at bottom right of the map area
-        let attribution_text = format!("© {}", state.provider_name);
-        ui.painter().text(
-            map_rect.max - egui::vec2(5.0, 5.0),
-            egui::Align2::RIGHT_BOTTOM,
-            attribution_text,
-            egui::FontId::proportional(10.0),
-            egui::Color32::from_black_alpha(150),
-        );
+    if !legend.is_empty() {
+        draw_legend(ui, map_rect, &legend);
     }
+
+    // Draw attribution at bottom right of the map area
+    let attribution_text = format!("© {}", state.provider_name);
+    ui.painter().text(
+        map_rect.max - egui::vec2(5.0, 5.0),
+        egui::Align2::RIGHT_BOTTOM,
+        attribution_text,
+        egui::FontId::proportional(10.0),
+        egui::Color32::from_black_alpha(150),
+    );
     clicked_path
 }
 
@@ -1014,37 +1285,91 @@ fn draw_sun_indicator(
     painter.text(text_pos, egui::Align2::LEFT_TOP, elev_text, font_id, text_color);
 }
 
-/// >= 1000m: show as km with 2 decimal places
-pub fn format_distance(meters: f64) -> String {
-    if meters < 1000.0 { format!("{:.0} m", meters) } else { format!("{:.2} km", meters / 1000.0) }
+/// Distance and bearing display settings (`distance_units`, `distance_decimals`,
+/// `bearing_style` in `[gui]`). The default gives "1919.99 km @ 88.00° E".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistanceFormat {
+    pub units: DistanceUnit,
+    pub decimals: usize,
+    pub bearing: BearingStyle,
 }
 
-/// Format bearing for display (compass direction)
-pub fn format_bearing(degrees: f64) -> String {
-    let directions = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
-    let idx = ((degrees + 22.5) / 45.0) as usize % 8;
-    format!("{:.2}° {}", degrees, directions[idx])
+impl Default for DistanceFormat {
+    fn default() -> Self {
+        Self { units: DistanceUnit::Km, decimals: 2, bearing: BearingStyle::Cardinal }
+    }
 }
 
-/// Get distance and bearing string between two points
-/// Returns None if either point is invalid
-#[allow(dead_code)]
-pub fn get_distance_bearing_string(
-    from_lat: f64,
-    from_lon: f64,
-    to_lat: f64,
-    to_lon: f64,
-) -> Option<String> {
-    if !(-90.0..=90.0).contains(&from_lat)
-        || !(-180.0..=180.0).contains(&from_lon)
-        || !(-90.0..=90.0).contains(&to_lat)
-        || !(-180.0..=180.0).contains(&to_lon)
-    {
-        return None;
+impl DistanceFormat {
+    pub fn from_gui_config(gui: &GuiConfig) -> Self {
+        Self {
+            units: gui.distance_units.unwrap_or_default(),
+            decimals: gui.distance_decimals.unwrap_or(2).min(9),
+            bearing: gui.bearing_style.unwrap_or_default(),
+        }
     }
 
-    let (distance, bearing) =
-        crate::position::distance_and_bearing((from_lat, from_lon), (to_lat, to_lon));
+    /// Short distances in whole metres (feet for miles), longer ones in the unit
+    pub fn distance(&self, meters: f64) -> String {
+        let d = self.decimals;
+        match self.units {
+            DistanceUnit::Km if meters < 1000.0 => format!("{:.0} m", meters),
+            DistanceUnit::Km => format!("{:.*} km", d, meters / 1000.0),
+            DistanceUnit::Mi if meters < 1609.344 => format!("{:.0} ft", meters / 0.3048),
+            DistanceUnit::Mi => format!("{:.*} mi", d, meters / 1609.344),
+            DistanceUnit::Nmi => format!("{:.*} nmi", d, meters / 1852.0),
+        }
+    }
 
-    Some(format!("{} @ {}", format_distance(distance), format_bearing(bearing)))
+    /// Bearing in degrees, with the compass direction unless `bearing_style = "degrees"`
+    pub fn bearing(&self, degrees: f64) -> String {
+        match self.bearing {
+            BearingStyle::Cardinal => {
+                let directions = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+                let idx = ((degrees + 22.5) / 45.0) as usize % 8;
+                format!("{:.*}° {}", self.decimals, degrees, directions[idx])
+            }
+            BearingStyle::Degrees => format!("{:.*}°", self.decimals, degrees),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(units: DistanceUnit, bearing: BearingStyle) -> DistanceFormat {
+        DistanceFormat { units, decimals: 2, bearing }
+    }
+
+    #[test]
+    fn test_distance_units() {
+        let km = format(DistanceUnit::Km, BearingStyle::Cardinal);
+        assert_eq!(km.distance(999.4), "999 m");
+        assert_eq!(km.distance(1919990.0), "1919.99 km");
+
+        let mi = format(DistanceUnit::Mi, BearingStyle::Cardinal);
+        assert_eq!(mi.distance(304.8), "1000 ft");
+        assert_eq!(mi.distance(1609.344), "1.00 mi");
+        assert_eq!(mi.distance(16093.44), "10.00 mi");
+
+        let nmi = format(DistanceUnit::Nmi, BearingStyle::Cardinal);
+        assert_eq!(nmi.distance(926.0), "0.50 nmi");
+        assert_eq!(nmi.distance(18520.0), "10.00 nmi");
+
+        let whole = DistanceFormat { decimals: 0, ..km };
+        assert_eq!(whole.distance(1500.0), "2 km");
+    }
+
+    #[test]
+    fn test_bearing_styles() {
+        let cardinal = format(DistanceUnit::Km, BearingStyle::Cardinal);
+        assert_eq!(cardinal.bearing(88.0), "88.00° E");
+        assert_eq!(cardinal.bearing(359.0), "359.00° N");
+        assert_eq!(cardinal.bearing(22.4), "22.40° N");
+        assert_eq!(cardinal.bearing(22.6), "22.60° NE");
+
+        let degrees = format(DistanceUnit::Km, BearingStyle::Degrees);
+        assert_eq!(degrees.bearing(88.0), "88.00°");
+    }
 }
@@ -0,0 +1,372 @@
+//! Group review: steps through every pair of files in a duplicate group and shows
+//! a pixel-difference heatmap next to the metadata that differs, so a group can be
+//! confirmed to really hold the same picture before anything is deleted from it.
+//!
+//! Both files of a pair are decoded upright at heatmap size (RAWs via their embedded
+//! preview) in a background thread; the second is scaled to the first when their
+//! sizes differ. Groups confirmed this way are remembered for the session.
+
+use crossbeam_channel::{Receiver, unbounded};
+use eframe::egui;
+use image::RgbImage;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use super::app::GuiApp;
+use crate::FileMetadata;
+use crate::hamminghash::HammingHash;
+
+/// Longest edge the files are decoded at for the heatmap
+const HEATMAP_SIZE: u32 = 512;
+/// Channel differences up to this are treated as encoder noise, not change
+const DIFF_NOISE: u8 = 4;
+/// Channel difference at which the heat colour saturates
+const DIFF_FULL: f32 = 64.0;
+
+/// Pixel comparison of one pair.
+struct PairDiff {
+    heatmap: egui::ColorImage,
+    /// Mean squared error over all channels (0-255 scale)
+    mse: f32,
+    max_diff: u8,
+    /// Fraction of pixels differing by more than DIFF_NOISE in some channel
+    changed: f32,
+    /// The second file had another size and was scaled to the first
+    rescaled: bool,
+}
+
+/// State of the review window for one group.
+pub(super) struct GroupReview {
+    group_idx: usize,
+    /// Indices into the group, first < second
+    pairs: Vec<(usize, usize)>,
+    pair_idx: usize,
+    /// Pairs shown so far, to tell when all have been looked at
+    seen: HashSet<usize>,
+    diff: Option<Result<PairDiff, String>>,
+    texture: Option<egui::TextureHandle>,
+    diff_rx: Option<Receiver<(usize, Result<PairDiff, String>)>>,
+}
+
+impl GroupReview {
+    /// Review of `group_idx`, or None when the group has fewer than two files.
+    pub(super) fn new(group_idx: usize, len: usize) -> Option<Self> {
+        let pairs: Vec<(usize, usize)> =
+            (0..len).flat_map(|a| ((a + 1)..len).map(move |b| (a, b))).collect();
+        if pairs.is_empty() {
+            return None;
+        }
+        Some(Self {
+            group_idx,
+            pairs,
+            pair_idx: 0,
+            seen: HashSet::new(),
+            diff: None,
+            texture: None,
+            diff_rx: None,
+        })
+    }
+
+    fn go_to(&mut self, pair_idx: usize) {
+        if pair_idx != self.pair_idx {
+            self.pair_idx = pair_idx;
+            self.diff = None;
+            self.texture = None;
+            self.diff_rx = None;
+        }
+    }
+
+    /// Start diffing the current pair unless it is done or underway.
+    fn request_diff(&mut self, a: PathBuf, b: PathBuf) {
+        if self.diff.is_some() || self.diff_rx.is_some() {
+            return;
+        }
+        let (tx, rx) = unbounded();
+        let pair_idx = self.pair_idx;
+        thread::spawn(move || {
+            let _ = tx.send((pair_idx, diff_pair(&a, &b)));
+        });
+        self.diff_rx = Some(rx);
+    }
+
+    fn poll_diff(&mut self, ctx: &egui::Context) {
+        let Some((pair_idx, result)) = self.diff_rx.as_ref().and_then(|rx| rx.try_recv().ok())
+        else {
+            return;
+        };
+        self.diff_rx = None;
+        if pair_idx != self.pair_idx {
+            return;
+        }
+        if let Ok(diff) = &result {
+            self.texture = Some(ctx.load_texture(
+                "group_review_heatmap",
+                diff.heatmap.clone(),
+                egui::TextureOptions::LINEAR,
+            ));
+        }
+        self.diff = Some(result);
+    }
+}
+
+/// Identity of a group for the reviewed set: its files' unique ids, sorted.
+/// Deleting or adding a member makes it a different group that needs a new review.
+pub(super) fn group_key(group: &[FileMetadata]) -> Vec<u128> {
+    let mut key: Vec<u128> = group.iter().map(|f| f.unique_file_id).collect();
+    key.sort_unstable();
+    key
+}
+
+fn diff_pair(a: &Path, b: &Path) -> Result<PairDiff, String> {
+    let load = |path: &Path| {
+        super::contact_sheet::load_thumbnail(path, HEATMAP_SIZE)
+            .ok_or_else(|| format!("can't decode {}", path.display()))
+    };
+    let img_a = load(a)?;
+    let mut img_b = load(b)?;
+    let rescaled = img_a.dimensions() != img_b.dimensions();
+    if rescaled {
+        img_b = image::imageops::resize(
+            &img_b,
+            img_a.width(),
+            img_a.height(),
+            image::imageops::FilterType::Triangle,
+        );
+    }
+    Ok(heatmap(&img_a, &img_b, rescaled))
+}
+
+/// Dimmed grayscale of `a` with the per-pixel difference to `b` painted over it,
+/// from red (small) to yellow (DIFF_FULL and up).
+fn heatmap(a: &RgbImage, b: &RgbImage, rescaled: bool) -> PairDiff {
+    let mut sq_sum = 0u64;
+    let mut max_diff = 0u8;
+    let mut changed = 0usize;
+    let pixels: Vec<egui::Color32> = a
+        .pixels()
+        .zip(b.pixels())
+        .map(|(pa, pb)| {
+            let mut d = 0u8;
+            for ch in 0..3 {
+                let cd = pa.0[ch].abs_diff(pb.0[ch]);
+                sq_sum += cd as u64 * cd as u64;
+                d = d.max(cd);
+            }
+            max_diff = max_diff.max(d);
+            let luma = (pa.0[0] as u32 * 3 + pa.0[1] as u32 * 6 + pa.0[2] as u32) / 10;
+            let base = (luma * 2 / 5) as f32;
+            if d <= DIFF_NOISE {
+                let g = base as u8;
+                return egui::Color32::from_rgb(g, g, g);
+            }
+            changed += 1;
+            let t = (d as f32 / DIFF_FULL).min(1.0);
+            let heat = [255.0, 255.0 * t, 0.0];
+            let alpha = 0.5 + 0.5 * t;
+            let mix = |h: f32| (base + (h - base) * alpha) as u8;
+            egui::Color32::from_rgb(mix(heat[0]), mix(heat[1]), mix(heat[2]))
+        })
+        .collect();
+    let count = pixels.len().max(1);
+    PairDiff {
+        heatmap: egui::ColorImage {
+            size: [a.width() as usize, a.height() as usize],
+            pixels,
+            source_size: egui::vec2(a.width() as f32, a.height() as f32),
+        },
+        mse: sq_sum as f32 / (count * 3) as f32,
+        max_diff,
+        changed: changed as f32 / count as f32,
+        rescaled,
+    }
+}
+
+/// (label, value of the first file, value of the second) for every metadata field
+/// the review lists.
+fn metadata_rows(a: &FileMetadata, b: &FileMetadata) -> Vec<(&'static str, String, String)> {
+    let name =
+        |f: &FileMetadata| f.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let resolution = |f: &FileMetadata| {
+        f.resolution.map(|(w, h)| format!("{}x{}", w, h)).unwrap_or_else(|| "?".to_string())
+    };
+    let exif_date = |f: &FileMetadata| {
+        f.exif_timestamp
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let gps = |f: &FileMetadata| {
+        f.gps_pos.map(|p| format!("{:.5}, {:.5}", p.y(), p.x())).unwrap_or_else(|| "-".to_string())
+    };
+    let pixels_equal = match (a.subgroup_pixel_hash(), b.subgroup_pixel_hash()) {
+        (Some(pa), Some(pb)) => {
+            if pa == pb {
+                "identical"
+            } else {
+                "differ"
+            }
+        }
+        _ => "not hashed",
+    };
+    let content = if a.content_hash == b.content_hash { "bit-identical" } else { "differ" };
+    let pdq = match (a.pdqhash, b.pdqhash) {
+        (Some(ha), Some(hb)) => ha.hamming_distance(&hb).to_string(),
+        _ => "-".to_string(),
+    };
+    vec![
+        ("Name", name(a), name(b)),
+        ("Size", crate::format_size(a.size), crate::format_size(b.size)),
+        ("Resolution", resolution(a), resolution(b)),
+        (
+            "Modified",
+            a.modified.format("%Y-%m-%d %H:%M:%S").to_string(),
+            b.modified.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ),
+        ("EXIF date", exif_date(a), exif_date(b)),
+        ("Orientation", a.orientation.to_string(), b.orientation.to_string()),
+        ("GPS", gps(a), gps(b)),
+        ("File content", content.to_string(), String::new()),
+        ("Pixels", pixels_equal.to_string(), String::new()),
+        ("PDQ distance", pdq, String::new()),
+    ]
+}
+
+/// The review window. Left/Right step through the pairs, Enter marks the group as
+/// reviewed and closes, Esc closes without marking.
+pub(super) fn show_group_review(app: &mut GuiApp, ctx: &egui::Context) {
+    let Some(review) = app.group_review.as_mut() else { return };
+    let Some(group) = app.state.groups.get(review.group_idx) else {
+        app.group_review = None;
+        return;
+    };
+    let Some(&(ia, ib)) = review.pairs.get(review.pair_idx) else {
+        app.group_review = None;
+        return;
+    };
+    let (Some(fa), Some(fb)) = (group.get(ia), group.get(ib)) else {
+        // The group shrank under the review (files deleted)
+        app.group_review = None;
+        return;
+    };
+
+    review.seen.insert(review.pair_idx);
+    review.request_diff(fa.path.clone(), fb.path.clone());
+    review.poll_diff(ctx);
+    if review.diff_rx.is_some() {
+        ctx.request_repaint_after(std::time::Duration::from_millis(50));
+    }
+
+    let key = group_key(group);
+    let reviewed = app.reviewed_groups.contains(&key);
+    let rows = metadata_rows(fa, fb);
+    let pair_count = review.pairs.len();
+    let mut next = ctx.input(|i| i.key_pressed(egui::Key::ArrowRight));
+    let mut prev = ctx.input(|i| i.key_pressed(egui::Key::ArrowLeft));
+    let mut verify = ctx.input(|i| i.key_pressed(egui::Key::Enter));
+    let mut unverify = false;
+    let mut close = false;
+
+    egui::Window::new(format!("Review group {}", review.group_idx + 1))
+        .collapsible(false)
+        .default_width(760.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Pair {}/{} (file {} vs {}), {} of {} viewed",
+                    review.pair_idx + 1,
+                    pair_count,
+                    ia + 1,
+                    ib + 1,
+                    review.seen.len(),
+                    pair_count
+                ));
+                if reviewed {
+                    ui.colored_label(egui::Color32::GREEN, "✔ reviewed");
+                }
+            });
+            ui.separator();
+
+            match (&review.diff, &review.texture) {
+                (Some(Ok(diff)), Some(texture)) => {
+                    ui.label(format!(
+                        "MSE {:.2}, max channel difference {}, {:.2}% of pixels changed{}",
+                        diff.mse,
+                        diff.max_diff,
+                        diff.changed * 100.0,
+                        if diff.rescaled { " (second file scaled to the first)" } else { "" }
+                    ));
+                    let size = texture.size_vec2();
+                    let scale = (ui.available_width() / size.x).min(480.0 / size.y).min(1.0);
+                    ui.image((texture.id(), size * scale));
+                }
+                (Some(Err(e)), _) => {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+                _ => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Comparing pixels...");
+                    });
+                }
+            }
+            ui.separator();
+
+            egui::Grid::new("group_review_meta").striped(true).show(ui, |ui| {
+                ui.strong("");
+                ui.strong(format!("File {}", ia + 1));
+                ui.strong(format!("File {}", ib + 1));
+                ui.end_row();
+                for (label, va, vb) in &rows {
+                    ui.label(*label);
+                    // Single-value rows compare the pair as a whole
+                    let differs = !vb.is_empty() && va != vb;
+                    let color =
+                        if differs { egui::Color32::YELLOW } else { ui.visuals().text_color() };
+                    ui.colored_label(color, va);
+                    ui.colored_label(color, vb);
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.add_enabled(review.pair_idx > 0, egui::Button::new("◀ Prev (←)")).clicked()
+                {
+                    prev = true;
+                }
+                if ui
+                    .add_enabled(review.pair_idx + 1 < pair_count, egui::Button::new("Next (→) ▶"))
+                    .clicked()
+                {
+                    next = true;
+                }
+                if ui.button("Mark reviewed (Enter)").clicked() {
+                    verify = true;
+                }
+                if reviewed && ui.button("Unmark").clicked() {
+                    unverify = true;
+                }
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if next && review.pair_idx + 1 < pair_count {
+        review.go_to(review.pair_idx + 1);
+    } else if prev && review.pair_idx > 0 {
+        review.go_to(review.pair_idx - 1);
+    }
+    if unverify {
+        app.reviewed_groups.remove(&key);
+    }
+    if verify {
+        let group_idx = review.group_idx;
+        app.reviewed_groups.insert(key);
+        app.group_review = None;
+        app.set_status(format!("Group {} marked as reviewed", group_idx + 1), false);
+    } else if close {
+        app.group_review = None;
+    }
+}
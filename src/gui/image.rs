@@ -1,3 +1,4 @@
+use crate::debug_log;
 use crate::scanner::is_raw_ext;
 use crossbeam_channel::{Receiver, Sender, unbounded};
 use eframe::egui;
@@ -7,6 +8,7 @@ use fast_image_resize::{PixelType, ResizeOptions, Resizer};
 use image::GenericImageView;
 use oklab::{LinearRgb, Oklab, linear_srgb_to_oklab, oklab_to_linear_srgb};
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::f32::consts::PI;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -16,6 +18,7 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use super::app::GuiApp;
+use crate::db::{ImageFilter, ViewerBackground};
 use crate::exif_types::{
     ExifValue, TAG_DERIVED_TIMESTAMP, TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE, TAG_ORIENTATION,
 };
@@ -26,10 +29,16 @@ use crate::scanner;
 
 pub const MAX_TEXTURE_SIDE: usize = 8192;
 
+/// Aspect ratios for the crop guide overlay (label, width, height)
+pub(super) const CROP_GUIDES: [(&str, f32, f32); 3] =
+    [("1:1", 1.0, 1.0), ("4:5", 4.0, 5.0), ("16:9", 16.0, 9.0)];
+
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub(super) enum ViewMode {
     #[default]
     FitWindow,
+    /// Like FitWindow, but small images stay at native size instead of being upscaled
+    FitWindowNoUpscale,
     FitWidth,
     FitHeight,
     ManualZoom(f32),
@@ -112,6 +121,9 @@ pub enum ImageLoadResult {
         content_hash: [u8; 32],
         exif_timestamp: Option<i64>,
     },
+    /// Embedded EXIF thumbnail sent ahead of the full decode, with the orientation
+    /// to display it in. The full result for the same path follows.
+    Placeholder(egui::ColorImage, u8),
     Failed(String), // Failure with error message
 }
 
@@ -141,10 +153,6 @@ impl Default for GroupViewState {
 // is an `*Srgb` format, so no hardware transfer function is applied on either
 // read or write). The fragment shader is therefore a straight pass-through.
 
-/// Above this on-screen magnification, switch to nearest-neighbour sampling so
-/// individual pixels stay crisp when pixel-peeping. Below it, linear.
-const NEAREST_ZOOM_THRESHOLD: f32 = 2.0;
-
 const IMAGE_SHADER_WGSL: &str = r#"
 struct Uniforms {
     // The image rect in normalized device coordinates: (x0, y0, x1, y1), with
@@ -285,6 +293,172 @@ impl ImageSource {
     }
 }
 
+/// White balance for the RAW preview (Shift+W). `Camera` is what the normal decode uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub(super) enum RawWhiteBalance {
+    #[default]
+    Camera,
+    Auto,
+    Daylight,
+}
+
+impl RawWhiteBalance {
+    fn next(self) -> Self {
+        match self {
+            Self::Camera => Self::Auto,
+            Self::Auto => Self::Daylight,
+            Self::Daylight => Self::Camera,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Camera => "camera",
+            Self::Auto => "auto",
+            Self::Daylight => "daylight",
+        }
+    }
+}
+
+type RawWbResult = (PathBuf, RawWhiteBalance, Result<egui::ColorImage, String>);
+
+/// RAW white-balance preview for the file on screen: one texture per non-camera mode,
+/// decoded on demand. Moving to another file drops it and returns to `Camera`.
+#[derive(Default)]
+pub(super) struct RawWbPreview {
+    mode: RawWhiteBalance,
+    path: Option<PathBuf>,
+    textures: HashMap<RawWhiteBalance, egui::TextureHandle>,
+    pending: HashMap<RawWhiteBalance, Receiver<RawWbResult>>,
+}
+
+impl RawWbPreview {
+    /// A white balance decode is still running.
+    pub(super) fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+/// Shift+W: switch the current RAW file to the next white balance mode, decoding it
+/// in the background the first time.
+pub(super) fn cycle_raw_wb(app: &mut GuiApp) {
+    let Some(path) = app.state.get_current_image_path().cloned() else {
+        return;
+    };
+    if !is_raw_ext(&path) {
+        app.set_status("White balance preview is only for RAW files".to_string(), false);
+        return;
+    }
+    if app.raw_thumbnails {
+        app.set_status("White balance preview needs full RAW decode".to_string(), true);
+        return;
+    }
+    if app.raw_wb.path.as_ref() != Some(&path) {
+        app.raw_wb = RawWbPreview { path: Some(path.clone()), ..Default::default() };
+    }
+    let mode = app.raw_wb.mode.next();
+    app.raw_wb.mode = mode;
+
+    if mode == RawWhiteBalance::Camera || app.raw_wb.textures.contains_key(&mode) {
+        app.set_status(format!("White balance: {}", mode.name()), false);
+        return;
+    }
+    app.set_status(format!("White balance: {} (decoding...)", mode.name()), false);
+    if app.raw_wb.pending.contains_key(&mode) {
+        return;
+    }
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    thread::spawn(move || {
+        let result = decode_raw_with_wb(&path, mode);
+        let _ = tx.send((path, mode, result));
+    });
+    app.raw_wb.pending.insert(mode, rx);
+}
+
+/// Pick up a finished white balance decode and turn it into a texture.
+pub(super) fn poll_raw_wb(app: &mut GuiApp, ctx: &egui::Context) {
+    let finished: Vec<RawWbResult> =
+        app.raw_wb.pending.values().filter_map(|rx| rx.try_recv().ok()).collect();
+    for (path, mode, result) in finished {
+        app.raw_wb.pending.remove(&mode);
+        if app.raw_wb.path.as_ref() != Some(&path) {
+            continue;
+        }
+        match result {
+            Ok(image) => {
+                let name = format!("wb_{}_{}", mode.name(), path.display());
+                let texture = ctx.load_texture(name, image, Default::default());
+                app.raw_wb.textures.insert(mode, texture);
+                if app.raw_wb.mode == mode {
+                    app.set_status(format!("White balance: {}", mode.name()), false);
+                }
+                ctx.request_repaint();
+            }
+            Err(e) => {
+                if app.raw_wb.mode == mode {
+                    app.raw_wb.mode = RawWhiteBalance::Camera;
+                }
+                app.set_status(format!("White balance {} failed: {}", mode.name(), e), true);
+            }
+        }
+    }
+}
+
+/// Texture to show instead of the normal decode while a non-camera white balance is
+/// selected for `path`. Resets the preview when another file is on screen.
+pub(super) fn raw_wb_source(app: &mut GuiApp, path: &Path) -> Option<ImageSource> {
+    if app.raw_wb.path.as_deref() != Some(path) {
+        if app.raw_wb.path.is_some() {
+            app.raw_wb = RawWbPreview::default();
+        }
+        return None;
+    }
+    app.raw_wb
+        .textures
+        .get(&app.raw_wb.mode)
+        .map(|texture| ImageSource::Egui { id: texture.id(), size: texture.size_vec2() })
+}
+
+/// Full 8-bit RAW decode with the given white balance, for the preview.
+fn decode_raw_with_wb(path: &Path, wb: RawWhiteBalance) -> Result<egui::ColorImage, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut raw =
+        rsraw::RawImage::open(&bytes).map_err(|e| format!("Failed to open RAW file: {}", e))?;
+    let dims = (raw.width(), raw.height());
+    match wb {
+        RawWhiteBalance::Camera => raw.set_use_camera_wb(true),
+        RawWhiteBalance::Auto => raw.set_use_auto_wb(true),
+        // Neither camera nor auto: LibRaw falls back to its daylight multipliers
+        RawWhiteBalance::Daylight => raw.set_use_camera_wb(false),
+    }
+    raw.unpack().map_err(|e| format!("Failed to unpack RAW: {}", e))?;
+    let processed = raw
+        .process::<{ rsraw::BIT_DEPTH_8 }>()
+        .map_err(|e| format!("Failed to process RAW: {}", e))?;
+    let img =
+        raw8_to_colorimage(processed.width() as usize, processed.height() as usize, &processed)?;
+    Ok(maybe_resize_image(img, dims, 1, path).0)
+}
+
+/// LibRaw 8-bit output (gray or RGB, chosen by length) as a `ColorImage`.
+fn raw8_to_colorimage(w: usize, h: usize, data: &[u8]) -> Result<egui::ColorImage, String> {
+    let total_pixels = w * h;
+    if data.len() == total_pixels {
+        // Monochrome: 1 byte per pixel
+        Ok(egui::ColorImage::from_gray([w, h], data))
+    } else if data.len() == total_pixels * 3 {
+        // RGB: 3 bytes per pixel
+        Ok(egui::ColorImage::from_rgb([w, h], data))
+    } else {
+        Err(format!(
+            "RAW size mismatch: expected {} (Mono) or {} (RGB) bytes, got {}",
+            total_pixels,
+            total_pixels * 3,
+            data.len()
+        ))
+    }
+}
+
 /// Build the 10-bit blit pipeline. Call once, from the `eframe` creation
 /// closure, and only when `target_format` is actually 10-bit.
 ///
@@ -510,7 +684,7 @@ pub(super) fn upload_gpu_image(
     );
 
     if gpu_debug_mode() > 0 {
-        eprintln!(
+        debug_log!(
             "[GPU-DBG] upload   {} {}x{} stride={}B bytes={}",
             label,
             width,
@@ -707,6 +881,7 @@ pub(super) fn spawn_image_loader_pool(
     content_key: [u8; 32],
     palette_config: crate::db::PaletteConfig,
     hdr_config: crate::db::HdrConfig,
+    decode_config: crate::db::DecodeConfig,
     histogram_enabled: Arc<AtomicBool>,
     deep_caps: Arc<DeepColorCaps>,
 ) -> (Sender<(PathBuf, usize, usize)>, Receiver<((PathBuf, usize, usize), ImageLoadResult)>) {
@@ -714,8 +889,20 @@ pub(super) fn spawn_image_loader_pool(
     let (result_tx, result_rx) = unbounded();
 
     let num_threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(8);
+    let decode_pool = decode_config.timeout.map(|timeout| {
+        let pool = spawn_decode_pool(
+            num_threads,
+            use_thumbnails,
+            content_key,
+            hdr_config,
+            decode_config,
+            Arc::clone(&deep_caps),
+        );
+        (pool, timeout)
+    });
 
     for _ in 0..num_threads {
+        let decode_pool = decode_pool.clone();
         let rx_clone = rx.clone();
         let tx_clone = result_tx.clone();
         let hist_flag = Arc::clone(&histogram_enabled);
@@ -723,13 +910,15 @@ pub(super) fn spawn_image_loader_pool(
 
         let pcfg = palette_config;
         let hcfg = hdr_config;
+        let dcfg = decode_config;
         thread::spawn(move || {
             while let Ok((path, g_idx, f_idx)) = rx_clone.recv() {
                 // Load & Process (Resize + Orientation)
                 // Note: We removed the "active window" check here because it caused race conditions
                 // where images would fail to load. The cache eviction handles cleanup instead.
 
-                // Check for animated WebP/GIF before standard loading
+                // Check for animated WebP/GIF/JXL before standard loading;
+                // still images stay on the single-frame path
                 let ext_lower = path
                     .extension()
                     .and_then(|e| e.to_str())
@@ -737,14 +926,19 @@ pub(super) fn spawn_image_loader_pool(
                     .unwrap_or_default();
                 let is_webp = ext_lower == "webp";
                 let is_gif = ext_lower == "gif";
+                let is_jxl = ext_lower == "jxl";
 
-                if (is_webp || is_gif)
+                if (is_webp || is_gif || is_jxl)
                     && let Ok(bytes) = std::fs::read(&path)
                 {
                     let animated_result = if is_webp && is_animated_webp(&bytes) {
                         Some(decode_animated_webp_frames(&path, &bytes))
                     } else if is_gif && is_animated_gif(&bytes) {
                         Some(decode_animated_gif_frames(&path, &bytes))
+                    } else if is_jxl
+                        && crate::scanner::jxl_info(&bytes).is_some_and(|info| info.animated())
+                    {
+                        Some(decode_animated_jxl_frames(&path, &bytes))
                     } else {
                         None
                     };
@@ -780,13 +974,25 @@ pub(super) fn spawn_image_loader_pool(
                     }
                 }
 
-                let result = match load_and_process_image_with_hash(
-                    &path,
-                    use_thumbnails,
-                    &content_key,
-                    hcfg,
-                    &caps,
-                ) {
+                if dcfg.exif_placeholder
+                    && let Some((thumb, orientation)) = load_exif_thumbnail(&path)
+                {
+                    let placeholder = ImageLoadResult::Placeholder(thumb, orientation);
+                    let _ = tx_clone.send(((path.clone(), g_idx, f_idx), placeholder));
+                }
+
+                let loaded = match &decode_pool {
+                    Some((pool, timeout)) => load_with_timeout(&path, pool, *timeout),
+                    None => load_and_process_image_with_hash(
+                        &path,
+                        use_thumbnails,
+                        &content_key,
+                        hcfg,
+                        dcfg,
+                        &caps,
+                    ),
+                };
+                let result = match loaded {
                     Ok((decoded, dims, orientation, content_hash, exif_timestamp)) => {
                         // Only compute histogram + palette when the overlay is enabled;
                         // the disk-based fallback in render_histogram handles cache misses
@@ -879,15 +1085,85 @@ fn dynamic_image_to_egui(img: image::DynamicImage) -> egui::ColorImage {
     }
 }
 
+type HashedLoad = (DecodedImage, (u32, u32), u8, [u8; 32], Option<i64>);
+
+/// A decode for the pool: the file, a flag set when its loader stopped waiting,
+/// and where the result goes.
+type DecodeJob = (PathBuf, Arc<AtomicBool>, Sender<Result<HashedLoad, String>>);
+
+/// Fixed set of threads running `load_and_process_image_with_hash` for
+/// `load_with_timeout`. A decoder that never returns holds one of them for good
+/// instead of leaking a thread per decode; jobs whose loader gave up are skipped.
+fn spawn_decode_pool(
+    num_threads: usize,
+    use_thumbnails: bool,
+    content_key: [u8; 32],
+    hdr_config: crate::db::HdrConfig,
+    decode_config: crate::db::DecodeConfig,
+    caps: Arc<DeepColorCaps>,
+) -> Sender<DecodeJob> {
+    let (job_tx, job_rx) = unbounded::<DecodeJob>();
+    for _ in 0..num_threads {
+        let job_rx = job_rx.clone();
+        let caps = Arc::clone(&caps);
+        thread::spawn(move || {
+            while let Ok((path, abandoned, done_tx)) = job_rx.recv() {
+                if abandoned.load(Ordering::Relaxed) {
+                    continue;
+                }
+                let result = load_and_process_image_with_hash(
+                    &path,
+                    use_thumbnails,
+                    &content_key,
+                    hdr_config,
+                    decode_config,
+                    &caps,
+                );
+                let _ = done_tx.send(result);
+            }
+        });
+    }
+    job_tx
+}
+
+/// `load_and_process_image_with_hash` behind a watchdog. The decode runs in the
+/// decode pool; if it has not finished within `timeout` (`decode_timeout_secs`), the loader
+/// reports a failure and moves on. A hung decoder can't be killed, so it keeps its
+/// pool thread, but the loader pool stays available.
+fn load_with_timeout(
+    path: &Path,
+    pool: &Sender<DecodeJob>,
+    timeout: Duration,
+) -> Result<HashedLoad, String> {
+    let (done_tx, done_rx) = crossbeam_channel::bounded(1);
+    let abandoned = Arc::new(AtomicBool::new(false));
+    if pool.send((path.to_path_buf(), Arc::clone(&abandoned), done_tx)).is_err() {
+        return Err("Decoder pool stopped".to_string());
+    }
+
+    match done_rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
+            abandoned.store(true, Ordering::Relaxed);
+            eprintln!("[DECODE] {:?}: abandoned after {}s", path, timeout.as_secs());
+            Err(format!("Decode timed out after {}s", timeout.as_secs()))
+        }
+        Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
+            Err("Decoder thread panicked".to_string())
+        }
+    }
+}
+
 fn load_and_process_image_with_hash(
     path: &Path,
     use_thumbnails: bool,
     content_key: &[u8; 32],
     hdr_config: crate::db::HdrConfig,
+    decode_config: crate::db::DecodeConfig,
     caps: &DeepColorCaps,
-) -> Result<(DecodedImage, (u32, u32), u8, [u8; 32], Option<i64>), String> {
-    // Read file once for both hashing and image processing
-    let bytes = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+) -> Result<HashedLoad, String> {
+    // Read file once for both hashing and image processing (ZIP entries included)
+    let bytes = crate::archive::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
 
     // Compute content_hash using BLAKE3
     let content_hash = {
@@ -911,8 +1187,14 @@ fn load_and_process_image_with_hash(
         });
 
     // Process the image using existing logic
-    let (img, dims, orientation) =
-        load_and_process_image_from_bytes(path, &bytes, use_thumbnails, hdr_config, caps)?;
+    let (img, dims, orientation) = load_and_process_image_from_bytes(
+        path,
+        &bytes,
+        use_thumbnails,
+        hdr_config,
+        decode_config,
+        caps,
+    )?;
 
     Ok((img, dims, orientation, content_hash, exif_timestamp))
 }
@@ -924,7 +1206,7 @@ fn srgb8_result(
     orientation: u8,
     path: &Path,
 ) -> (DecodedImage, (u32, u32), u8) {
-    eprintln!("[DEBUG-HDR] {:?}: srgb8_result (8-bit sRGB path)", path);
+    debug_log!("[DEBUG-HDR] {:?}: srgb8_result (8-bit sRGB path)", path);
     let (img, dims, orient) = maybe_resize_image(color_image, real_dims, orientation, path);
     (DecodedImage::Srgb8(img), dims, orient)
 }
@@ -942,7 +1224,7 @@ fn maybe_downscale_dynamic(img: image::DynamicImage, path: &Path) -> image::Dyna
     let scale = MAX_TEXTURE_SIDE as f32 / w.max(h) as f32;
     let new_w = ((w as f32 * scale).round() as u32).max(1);
     let new_h = ((h as f32 * scale).round() as u32).max(1);
-    eprintln!("[DEBUG] Downscaled (deep) {:?} from {}x{} to {}x{}", path, w, h, new_w, new_h);
+    debug_log!("[DEBUG] Downscaled (deep) {:?} from {}x{} to {}x{}", path, w, h, new_w, new_h);
     img.resize_exact(new_w, new_h, image::imageops::FilterType::Lanczos3)
 }
 
@@ -1062,7 +1344,7 @@ fn maybe_resize_image(
         };
 
         if let Some(dst_image) = resized {
-            eprintln!("[DEBUG] Fast-Resized {:?} from {}x{} to {}x{}", path, w, h, new_w, new_h);
+            debug_log!("[DEBUG] Fast-Resized {:?} from {}x{} to {}x{}", path, w, h, new_w, new_h);
             // The resized bytes are already premultiplied Color32 values, so
             // rebuild them verbatim: going through from_rgba_unmultiplied
             // here would premultiply a second time and darken semi-
@@ -1083,6 +1365,62 @@ fn maybe_resize_image(
     (color_image, real_dims, orientation)
 }
 
+/// Decode the small JPEG thumbnail from the EXIF thumbnail IFD (IFD1).
+/// Returns it with the primary image's EXIF orientation, which the thumbnail shares;
+/// for HEIC that is read straight from EXIF since the thumbnail is not pre-rotated.
+pub(super) fn load_exif_thumbnail(path: &Path) -> Option<(egui::ColorImage, u8)> {
+    let exif = crate::exif_extract::read_exif_data(path, None)?;
+    let jpeg_bytes = crate::helper_exif::thumbnail_jpeg(&exif)?;
+    let img = image::load_from_memory_with_format(jpeg_bytes, image::ImageFormat::Jpeg).ok()?;
+
+    let orientation = exif
+        .get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+        .and_then(|f| f.value.get_uint(0))
+        .filter(|v| (1..=8).contains(v))
+        .unwrap_or(1) as u8;
+    Some((dynamic_image_to_egui(img), orientation))
+}
+
+/// The JPEG's EXIF thumbnail as a preview when its longest edge reaches `max`, plus the
+/// full image's dimensions. The thumbnail is stored unrotated, so the caller applies
+/// the primary image's orientation to it as usual. Letterboxed thumbnails (aspect
+/// ratio off by more than 2%) are skipped.
+fn exif_thumbnail_preview(bytes: &[u8], max: u32) -> Option<(image::DynamicImage, (u32, u32))> {
+    fn jpeg(data: &[u8]) -> image::ImageReader<std::io::Cursor<&[u8]>> {
+        image::ImageReader::with_format(std::io::Cursor::new(data), image::ImageFormat::Jpeg)
+    }
+    let thumb = crate::helper_exif::extract_thumbnail(bytes)?;
+    let (tw, th) = jpeg(&thumb[..]).into_dimensions().ok()?;
+    if tw.max(th) < max {
+        return None;
+    }
+    let (w, h) = jpeg(bytes).into_dimensions().ok()?;
+    let aspect = |a: u32, b: u32| a as f64 / b.max(1) as f64;
+    if (aspect(tw, th) / aspect(w, h) - 1.0).abs() > 0.02 {
+        return None;
+    }
+    let img = jpeg(&thumb[..]).decode().ok()?;
+    Some((img, (w, h)))
+}
+
+/// Preview of an image over max_decode_pixels: a DCT-scaled decode for
+/// JPEG, else the embedded EXIF thumbnail. Dimensions stay those of the header.
+fn oversized_preview(
+    path: &Path,
+    bytes: &[u8],
+    orientation: u8,
+    caps: &DeepColorCaps,
+) -> Option<(DecodedImage, (u32, u32), u8)> {
+    const PREVIEW_EDGE: u32 = 4096;
+    if let Some((dyn_img, dims)) = crate::scanner::load_jpeg_scaled(path, bytes, PREVIEW_EDGE) {
+        return Some(finish_dynamic(dyn_img, dims, orientation, caps, path));
+    }
+    let dims = crate::scanner::get_resolution(path, Some(bytes))?;
+    let thumb = crate::helper_exif::extract_thumbnail(bytes)?;
+    let dyn_img = image::load_from_memory_with_format(&thumb, image::ImageFormat::Jpeg).ok()?;
+    Some(finish_dynamic(dyn_img, dims, orientation, caps, path))
+}
+
 /// Fallback: Manually carve out the largest embedded JPEG (PreviewImage)
 /// using EXIF/TIFF tags when the RAW decoder completely fails to open the file.
 fn extract_biggest_exif_preview(path: &Path, bytes: &[u8]) -> Option<(egui::ColorImage, u8)> {
@@ -1133,13 +1471,18 @@ fn extract_biggest_exif_preview(path: &Path, bytes: &[u8]) -> Option<(egui::Colo
     let rgb = img.to_rgb8();
     let (width, height) = rgb.dimensions();
 
-    eprintln!(
+    debug_log!(
         "[DEBUG] EXIF Fallback extracted thumbnail for {:?}:\n  \
          - IFD Source: {:?}\n  \
          - Byte Offset: {}\n  \
          - File Size: {} bytes\n  \
          - Resolution: {}x{}",
-        path, best_ifd, best_offset, max_length, width, height
+        path,
+        best_ifd,
+        best_offset,
+        max_length,
+        width,
+        height
     );
 
     Some((egui::ColorImage::from_rgb([width as usize, height as usize], rgb.as_raw()), orientation))
@@ -1284,7 +1627,7 @@ fn decode_animated_webp_frames(
 
     let (frames, durations) = convert_animation_frames(&raw_frames);
 
-    eprintln!(
+    debug_log!(
         "[DEBUG] Decoded animated WebP {:?}: {} frames, dims={}x{}",
         path.file_name().unwrap_or_default(),
         frames.len(),
@@ -1323,7 +1666,7 @@ fn decode_animated_gif_frames(
 
     let (frames, durations) = convert_animation_frames(&raw_frames);
 
-    eprintln!(
+    debug_log!(
         "[DEBUG] Decoded animated GIF {:?}: {} frames, dims={}x{}",
         path.file_name().unwrap_or_default(),
         frames.len(),
@@ -1334,6 +1677,68 @@ fn decode_animated_gif_frames(
     Ok((frames, durations, dims, orientation))
 }
 
+/// Decode all displayed frames of an animated JPEG XL with jxl-oxide.
+/// The renderer applies the header orientation, so 1 is returned for it.
+fn decode_animated_jxl_frames(
+    path: &Path,
+    bytes: &[u8],
+) -> Result<(Vec<egui::ColorImage>, Vec<Duration>, (u32, u32), u8), String> {
+    let image = jxl_oxide::JxlImage::builder()
+        .read(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("Failed to parse JXL: {:?}", e))?;
+    let Some(animation) = image.image_header().metadata.animation.as_ref() else {
+        return Err("JXL is not animated".to_string());
+    };
+    // Frame durations count ticks of tps_denominator / tps_numerator seconds
+    let tick_ms = 1000.0 * animation.tps_denominator as f64 / animation.tps_numerator.max(1) as f64;
+    let dims = (image.width(), image.height());
+
+    let mut raw_frames = Vec::with_capacity(image.num_loaded_keyframes());
+    for idx in 0..image.num_loaded_keyframes() {
+        let render = image
+            .render_frame(idx)
+            .map_err(|e| format!("Failed to render JXL frame {}: {:?}", idx, e))?;
+        let mut stream = render.stream();
+        let (w, h) = (stream.width(), stream.height());
+        let channels = stream.channels() as usize;
+        let mut samples = vec![0u8; w as usize * h as usize * channels];
+        stream.write_to_buffer(&mut samples);
+
+        let rgba: Vec<u8> = match channels {
+            1 => samples.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+            2 => samples.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            3 => samples.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+            _ => samples.chunks_exact(channels).flat_map(|p| [p[0], p[1], p[2], p[3]]).collect(),
+        };
+        let Some(buffer) = image::RgbaImage::from_raw(w, h, rgba) else {
+            return Err(format!("JXL frame {} has an unexpected size", idx));
+        };
+        let ms = (render.duration() as f64 * tick_ms).round() as u32;
+        raw_frames.push(image::Frame::from_parts(
+            buffer,
+            0,
+            0,
+            image::Delay::from_numer_denom_ms(ms, 1),
+        ));
+    }
+
+    if raw_frames.is_empty() {
+        return Err("Animated JXL has no frames".to_string());
+    }
+
+    let (frames, durations) = convert_animation_frames(&raw_frames);
+
+    debug_log!(
+        "[DEBUG] Decoded animated JXL {:?}: {} frames, dims={}x{}",
+        path.file_name().unwrap_or_default(),
+        frames.len(),
+        dims.0,
+        dims.1
+    );
+
+    Ok((frames, durations, dims, 1))
+}
+
 /// Wrap a 16-bit LibRaw full decode as a `DynamicImage` so it can go through the
 /// same `finish_dynamic` path as every other deep-colour source.
 ///
@@ -1366,6 +1771,7 @@ fn load_and_process_image_from_bytes(
     bytes: &[u8],
     use_thumbnails: bool,
     hdr_config: crate::db::HdrConfig,
+    decode_config: crate::db::DecodeConfig,
     caps: &DeepColorCaps,
 ) -> Result<(DecodedImage, (u32, u32), u8), String> {
     // ---------------------------------------------------------------------
@@ -1387,8 +1793,34 @@ fn load_and_process_image_from_bytes(
                     return Ok(srgb8_result(thumb, dims, actual_orientation, path));
                 }
 
-                // If the fallback fails or we aren't using thumbnails, return the original error
-                return Err(format!("Failed to open RAW file (and EXIF fallback failed): {}", e));
+                // Last resort regardless of thumbnail mode: the JPEG preview
+                // stored in the container itself (e.g. compressed Fuji RAF).
+                if let Some(jpeg) = crate::raw_exif::extract_embedded_jpeg(bytes)
+                    && let Ok(img) = image::load_from_memory(jpeg)
+                {
+                    eprintln!(
+                        "[RAW-FALLBACK] {}: rsraw failed ({}), showing embedded JPEG preview",
+                        path.display(),
+                        e
+                    );
+                    let jpeg_orient =
+                        crate::exif_extract::get_orientation(Path::new(""), Some(jpeg));
+                    let actual_orientation =
+                        if jpeg_orient != 1 { jpeg_orient } else { exif_orientation };
+                    let rgb = img.to_rgb8();
+                    let dims = rgb.dimensions();
+                    let thumb = egui::ColorImage::from_rgb(
+                        [dims.0 as usize, dims.1 as usize],
+                        rgb.as_raw(),
+                    );
+                    return Ok(srgb8_result(thumb, dims, actual_orientation, path));
+                }
+
+                // No usable preview anywhere, return the original error
+                return Err(format!(
+                    "Failed to open RAW file (and preview fallback failed): {}",
+                    e
+                ));
             }
         };
 
@@ -1410,6 +1842,16 @@ fn load_and_process_image_from_bytes(
             return Ok(srgb8_result(thumb, dims, actual_orientation, path));
         }
 
+        // No full decode past max_decode_pixels; the embedded preview stands in
+        if let Some(reason) = crate::scanner::decode_size_error(dims.0, dims.1) {
+            if let Some((thumb, thumb_orient)) = extract_best_thumbnail(&mut raw) {
+                let actual_orientation =
+                    if thumb_orient != 1 { thumb_orient } else { raw_fallback_orientation };
+                return Ok(srgb8_result(thumb, dims, actual_orientation, path));
+            }
+            return Err(format!("Not decoded: {}", reason));
+        }
+
         // 2. Full RAW decode mode
         raw.set_use_camera_wb(true);
         // Decode at 16 bits only when a deep texture can actually show them.
@@ -1441,22 +1883,7 @@ fn load_and_process_image_from_bytes(
                         .and_then(|processed| {
                             let w = processed.width() as usize;
                             let h = processed.height() as usize;
-                            let total_pixels = w * h;
-                            // Determine channels dynamically
-                            if processed.len() == total_pixels {
-                                // Monochrome: 1 byte per pixel
-                                Ok(egui::ColorImage::from_gray([w, h], &processed))
-                            } else if processed.len() == total_pixels * 3 {
-                                // RGB: 3 bytes per pixel
-                                Ok(egui::ColorImage::from_rgb([w, h], &processed))
-                            } else {
-                                Err(format!(
-                                    "RAW size mismatch: expected {} (Mono) or {} (RGB) bytes, got {}",
-                                    total_pixels,
-                                    total_pixels * 3,
-                                    processed.len()
-                                ))
-                            }
+                            raw8_to_colorimage(w, h, &processed)
                         })
                         .map(|img| DecodedImage::Srgb8(maybe_resize_image(img, dims, 1, path).0))
                 }
@@ -1499,23 +1926,49 @@ fn load_and_process_image_from_bytes(
         crate::exif_extract::get_orientation(path, Some(bytes))
     };
 
+    // Decompression-bomb guard (max_decode_pixels): an oversized image is shown from
+    // a preview, never decoded in full
+    if let Err(reason) = crate::scanner::check_decode_size(path, bytes) {
+        eprintln!("[DECODE-LIMIT] {}: {}", path.display(), reason);
+        return oversized_preview(path, bytes, orientation, caps)
+            .ok_or_else(|| format!("Not decoded: {}", reason));
+    }
+
     // ---------------------------------------------------------------------
     // JXL / PDF / JPEG / TIFF FAST PATH
     // ---------------------------------------------------------------------
     if matches!(ext.as_str(), "jpg" | "jpeg" | "jxl" | "pdf" | "tif" | "tiff") {
-        eprintln!("[DEBUG-GUI] attempting scanner decode for {:?}", path);
+        // Small preview cap: the embedded EXIF thumbnail, when it already reaches the
+        // cap, saves decoding the full image at all
+        if matches!(ext.as_str(), "jpg" | "jpeg")
+            && let Some(max) = decode_config.jpeg_preview_max
+            && let Some((dyn_img, dims)) = exif_thumbnail_preview(bytes, max)
+        {
+            debug_log!("[DEBUG-LOAD] {:?} -> EXIF thumbnail preview", path.file_name());
+            return Ok(finish_dynamic(dyn_img, dims, orientation, caps, path));
+        }
+
+        // JPEG preview cap: decode near the target size straight from the DCT
+        if matches!(ext.as_str(), "jpg" | "jpeg")
+            && let Some(max) = decode_config.jpeg_preview_max
+            && let Some((dyn_img, dims)) = crate::scanner::load_jpeg_scaled(path, bytes, max)
+        {
+            return Ok(finish_dynamic(dyn_img, dims, orientation, caps, path));
+        }
+
+        debug_log!("[DEBUG-GUI] attempting scanner decode for {:?}", path);
 
         match crate::scanner::load_image_fast(path, bytes) {
             Ok(dyn_img) => {
                 let (w, h) = dyn_img.dimensions();
 
-                eprintln!("[DEBUG-GUI] scanner decode SUCCESS for {:?}", path);
+                debug_log!("[DEBUG-GUI] scanner decode SUCCESS for {:?}", path);
                 // 16-bit TIFF and JXL reach this branch, so let finish_dynamic
                 // decide whether they are worth keeping at 10 bits.
                 return Ok(finish_dynamic(dyn_img, (w, h), orientation, caps, path));
             }
             Err(err_msg) => {
-                eprintln!("[DEBUG-GUI] scanner decode FAILED for {:?}: {}", path, err_msg);
+                debug_log!("[DEBUG-GUI] scanner decode FAILED for {:?}: {}", path, err_msg);
                 return Err(format!("Failed to decode {} file: {}", ext.to_uppercase(), err_msg));
             }
         }
@@ -1541,7 +1994,7 @@ fn load_and_process_image_from_bytes(
     // cICP in still images; AVIF/HEIC signal their color space through
     // ISOBMFF container metadata.
     let cicp = crate::hdr::detect_cicp(bytes);
-    eprintln!("[DEBUG-IMAGE] Final detected cICP: {:?}", cicp);
+    debug_log!("[DEBUG-IMAGE] Final detected cICP: {:?}", cicp);
 
     let dyn_img =
         reader.decode().map_err(|e| format!("Failed to decode {}: {}", format_name, e))?;
@@ -1553,7 +2006,7 @@ fn load_and_process_image_from_bytes(
     let needs_hdr_processing = cicp.map(|c| c.is_hdr()).unwrap_or(false);
     if needs_hdr_processing {
         let cicp = cicp.unwrap();
-        eprintln!(
+        debug_log!(
             "[DEBUG-HDR] {:?}: cICP primaries={} transfer={} matrix={} full_range={}",
             path,
             cicp.color_primaries,
@@ -1570,14 +2023,14 @@ fn load_and_process_image_from_bytes(
             let dyn_img = maybe_downscale_dynamic(dyn_img, path);
             let peak = hdr_config.sdr_peak_nits;
             let (w, h, pixels) = if wide {
-                eprintln!(
+                debug_log!(
                     "[DEBUG-HDR] {:?}: process_hdr_to_rgba16 (HDR -> deep Rgba16Unorm)",
                     path
                 );
                 let (w, h, data) = crate::hdr::process_hdr_to_rgba16(&dyn_img, cicp, peak);
                 (w, h, DeepPixels::Rgba16(data))
             } else {
-                eprintln!(
+                debug_log!(
                     "[DEBUG-HDR] {:?}: process_hdr_to_rgb10a2 (HDR -> deep Rgb10a2Unorm)",
                     path
                 );
@@ -1589,7 +2042,7 @@ fn load_and_process_image_from_bytes(
 
         // Downscale while still 16-bit
         let dyn_img = maybe_downscale_dynamic(dyn_img, path);
-        eprintln!("[DEBUG-HDR] {:?}: process_hdr_to_sdr (HDR tone-mapped to 8-bit sRGB)", path);
+        debug_log!("[DEBUG-HDR] {:?}: process_hdr_to_sdr (HDR tone-mapped to 8-bit sRGB)", path);
         let rgba = crate::hdr::process_hdr_to_sdr(&dyn_img, cicp, hdr_config.sdr_peak_nits);
         let img = egui::ColorImage::from_rgba_unmultiplied(
             [rgba.width() as usize, rgba.height() as usize],
@@ -1704,7 +2157,7 @@ pub(super) fn update_file_metadata(
                 features,
             ) {
                 let _ = db_tx.send(update);
-                eprintln!(
+                debug_log!(
                     "[DEBUG-UPDATE]   Sent DB update for {:?}: resolution={}x{}, orientation={}, exif_ts={:?}",
                     path.file_name().unwrap_or_default(),
                     w,
@@ -1715,7 +2168,7 @@ pub(super) fn update_file_metadata(
             }
         }
     } else {
-        eprintln!(
+        debug_log!(
             "[DEBUG-UPDATE]   FILE {:?} NOT FOUND in any group",
             path.file_name().unwrap_or_default()
         );
@@ -1723,7 +2176,7 @@ pub(super) fn update_file_metadata(
 }
 
 /// Extract the best (largest) thumbnail from a RAW file
-fn extract_best_thumbnail(raw: &mut rsraw::RawImage) -> Option<(egui::ColorImage, u8)> {
+pub(super) fn extract_best_thumbnail(raw: &mut rsraw::RawImage) -> Option<(egui::ColorImage, u8)> {
     let thumbs = raw.extract_thumbs().ok()?;
 
     // Find the largest JPEG thumbnail
@@ -1780,6 +2233,58 @@ fn image_uv_transform(steps: u32, flip_h: bool, flip_v: bool) -> ([f32; 4], [f32
 }
 
 // Helper to render an image with pan/zoom logic, from either backing store.
+/// Decompose an EXIF orientation into quarter-turns (clockwise) plus
+/// mirrors. The mirror applies in texture space before the rotation on
+/// both render paths, matching the EXIF convention: 2 = mirror H,
+/// 3 = rotate 180, 4 = mirror V, 5 = mirror H + rotate 270 CW,
+/// 6 = rotate 90 CW, 7 = mirror H + rotate 90 CW, 8 = rotate 270 CW.
+fn exif_orientation_steps(orientation: u8) -> (u8, bool, bool) {
+    match orientation {
+        2 => (0, true, false),
+        3 => (2, false, false),
+        4 => (0, false, true),
+        5 => (3, true, false),
+        6 => (1, false, false),
+        7 => (1, true, false),
+        8 => (3, false, false),
+        _ => (0, false, false),
+    }
+}
+
+/// Draw the EXIF thumbnail placeholder fitted to the panel, with the file's
+/// manual rotation/flips, until the full decode replaces it.
+pub(super) fn render_placeholder(
+    app: &GuiApp,
+    ui: &mut egui::Ui,
+    texture: &egui::TextureHandle,
+    orientation: u8,
+    available_rect: egui::Rect,
+) {
+    let file_transform = app.state.get_current_file_transform();
+    let (exif_steps, exif_flip_h, exif_flip_v) = exif_orientation_steps(orientation);
+    let total_steps = (exif_steps + file_transform.rotation % 4) % 4;
+    let flip_h = exif_flip_h ^ file_transform.flip_horizontal;
+    let flip_v = exif_flip_v ^ file_transform.flip_vertical;
+
+    let texture_size = texture.size_vec2();
+    let visual_size = if total_steps % 2 == 1 {
+        egui::vec2(texture_size.y, texture_size.x)
+    } else {
+        texture_size
+    };
+    let scale =
+        (available_rect.width() / visual_size.x).min(available_rect.height() / visual_size.y);
+    let paint_rect = egui::Rect::from_center_size(available_rect.center(), texture_size * scale);
+
+    let (u_min, u_max) = if flip_h { (1.0, 0.0) } else { (0.0, 1.0) };
+    let (v_min, v_max) = if flip_v { (1.0, 0.0) } else { (0.0, 1.0) };
+    let uv = egui::Rect::from_min_max(egui::pos2(u_min, v_min), egui::pos2(u_max, v_max));
+    egui::Image::from_texture((texture.id(), texture_size))
+        .uv(uv)
+        .rotate(total_steps as f32 * (PI / 2.0), egui::Vec2::splat(0.5))
+        .paint_at(ui, paint_rect);
+}
+
 pub(super) fn render_image_texture(
     app: &mut GuiApp,
     ui: &mut egui::Ui,
@@ -1801,21 +2306,7 @@ pub(super) fn render_image_texture(
     // Use per-file rotation instead of global manual_rotation
     let manual_rot = file_transform.rotation % 4;
 
-    // Decompose the EXIF orientation into quarter-turns (clockwise) plus
-    // mirrors. The mirror applies in texture space before the rotation on
-    // both render paths, matching the EXIF convention: 2 = mirror H,
-    // 3 = rotate 180, 4 = mirror V, 5 = mirror H + rotate 270 CW,
-    // 6 = rotate 90 CW, 7 = mirror H + rotate 90 CW, 8 = rotate 270 CW.
-    let (exif_steps, exif_flip_h, exif_flip_v) = match orientation {
-        2 => (0, true, false),
-        3 => (2, false, false),
-        4 => (0, false, true),
-        5 => (3, true, false),
-        6 => (1, false, false),
-        7 => (1, true, false),
-        8 => (3, false, false),
-        _ => (0, false, false),
-    };
+    let (exif_steps, exif_flip_h, exif_flip_v) = exif_orientation_steps(orientation);
 
     let manual_angle = manual_rot as f32 * (PI / 2.0);
     let total_angle = exif_steps as f32 * (PI / 2.0) + manual_angle;
@@ -1838,6 +2329,13 @@ pub(super) fn render_image_texture(
 
     let zoom_factor = match view_state.mode {
         ViewMode::FitWindow => (screen_w / visual_size.x).min(screen_h / visual_size.y).min(2.0),
+        ViewMode::FitWindowNoUpscale => {
+            // 1.0 means one image pixel per physical screen pixel
+            let native = 1.0 / ui.ctx().pixels_per_point();
+            let fit = (screen_w / visual_size.x).min(screen_h / visual_size.y);
+            app.fit_capped = fit > native;
+            fit.min(native)
+        }
         ViewMode::FitWidth => screen_w / visual_size.x,
         ViewMode::FitHeight => screen_h / visual_size.y,
         ViewMode::ManualZoom(z) => {
@@ -1865,6 +2363,15 @@ pub(super) fn render_image_texture(
         }
     };
 
+    // Image pixels per physical screen pixel, for picking the sampler
+    let magnification = zoom_factor * ui.ctx().pixels_per_point();
+    let manual_zoom = matches!(view_state.mode, ViewMode::ManualZoom(_));
+    let nearest = match app.image_filter {
+        ImageFilter::Smooth => false,
+        ImageFilter::Pixel => true,
+        ImageFilter::Auto => manual_zoom && magnification >= 1.0,
+    };
+
     // Size of the image on screen (visually)
     let virtual_visual_size = visual_size * zoom_factor;
 
@@ -1899,6 +2406,8 @@ pub(super) fn render_image_texture(
     // Allocate space for the WHOLE available area to catch mouse events everywhere
     let response = ui.allocate_rect(available_rect, egui::Sense::click_and_drag());
 
+    paint_viewer_background(app, ui, target_rect);
+
     // NOTE: do NOT clip to available_rect here. `available_rect` comes from
     // `ui.available_rect_before_wrap()`, which is the panel rect minus the
     // CentralPanel frame's inner margin (8 points by default). `Image::paint_at`
@@ -1909,6 +2418,8 @@ pub(super) fn render_image_texture(
 
     match source {
         ImageSource::Egui { id, size } => {
+            let id = if nearest { nearest_texture(app, id) } else { id };
+
             // Calculate UV coordinates for flipping (EXIF mirror + manual)
             let (u_min, u_max) = if flip_h { (1.0, 0.0) } else { (0.0, 1.0) };
             let (v_min, v_max) = if flip_v { (1.0, 0.0) } else { (0.0, 1.0) };
@@ -1961,18 +2472,31 @@ pub(super) fn render_image_texture(
                 let (uv_mat, uv_off) = image_uv_transform(total_steps as u32, flip_h, flip_v);
                 painter.add(egui_wgpu::Callback::new_paint_callback(
                     draw_rect,
-                    ImageCallback {
-                        bind_group,
-                        target_rect,
-                        uv_mat,
-                        uv_off,
-                        nearest: zoom_factor >= NEAREST_ZOOM_THRESHOLD,
-                    },
+                    ImageCallback { bind_group, target_rect, uv_mat, uv_off, nearest },
                 ));
             }
         }
     }
 
+    // Ghost overlay: the previous file at half opacity, same geometry as this one
+    if app.ghost_overlay
+        && let Some(ghost) = ghost_texture(app, orientation, texture_size)
+    {
+        let (u_min, u_max) = if flip_h { (1.0, 0.0) } else { (0.0, 1.0) };
+        let (v_min, v_max) = if flip_v { (1.0, 0.0) } else { (0.0, 1.0) };
+        let uv = egui::Rect::from_min_max(egui::pos2(u_min, v_min), egui::pos2(u_max, v_max));
+        egui::Image::from_texture((ghost.id(), ghost.size_vec2()))
+            .uv(uv)
+            .rotate(total_angle, egui::Vec2::splat(0.5))
+            .tint(egui::Color32::from_white_alpha(128))
+            .paint_at(ui, paint_rect);
+    }
+
+    // Crop guide overlay, aligned to the rendered (rotated) image rect
+    if let Some(&(label, aw, ah)) = app.crop_guide.checked_sub(1).and_then(|i| CROP_GUIDES.get(i)) {
+        draw_crop_guide(ui.painter(), target_rect, aw / ah, label);
+    }
+
     // --- 7. Interaction ---
     if response.dragged() {
         let d = response.drag_delta();
@@ -1987,6 +2511,137 @@ pub(super) fn render_image_texture(
     }
 }
 
+/// Cached texture of the file listed before the current one, if it can be laid over
+/// it: same EXIF orientation and nearly the same aspect ratio, at most 4x apart in
+/// size. Anything else (a crop, a rotated shot, a thumbnail) is not overlaid.
+fn ghost_texture(
+    app: &GuiApp,
+    orientation: u8,
+    texture_size: egui::Vec2,
+) -> Option<egui::TextureHandle> {
+    let group = app.state.groups.get(app.state.current_group_idx)?;
+    let prev = group.get(app.state.current_file_idx.checked_sub(1)?)?;
+    if prev.orientation != orientation {
+        return None;
+    }
+    let texture = app.raw_cache.get(&prev.path)?;
+    let size = texture.size_vec2();
+    let aspect = |s: egui::Vec2| s.x / s.y.max(1.0);
+    let scale = size.x / texture_size.x.max(1.0);
+    if (aspect(size) / aspect(texture_size) - 1.0).abs() > 0.02 || !(0.25..=4.0).contains(&scale) {
+        return None;
+    }
+    Some(texture.clone())
+}
+
+/// A nearest-sampled view of the linear egui texture `id`, for pixel-peeping. It
+/// shares the GPU texture, so switching samplers needs no decode or upload. One
+/// view is kept, for the texture painted last; `id` itself is the fallback before
+/// egui has uploaded it, or without a wgpu renderer.
+fn nearest_texture(app: &mut GuiApp, id: egui::TextureId) -> egui::TextureId {
+    if let Some((tex, view)) = app.nearest_view
+        && tex == id
+    {
+        return view;
+    }
+    let Some(rs) = app.render_state.as_ref() else { return id };
+    let mut renderer = rs.renderer.write();
+    let Some(view) = renderer
+        .texture(&id)
+        .and_then(|t| t.texture.as_ref())
+        .map(|t| t.create_view(&wgpu::TextureViewDescriptor::default()))
+    else {
+        return id;
+    };
+    let nearest = renderer.register_native_texture(&rs.device, &view, wgpu::FilterMode::Nearest);
+    if let Some((_, old)) = app.nearest_view.replace((id, nearest)) {
+        renderer.free_texture(&old);
+    }
+    nearest
+}
+
+/// Size of one checkerboard square, in points
+const CHECKER_CELL: f32 = 8.0;
+
+/// Fill behind the image per `viewer_background`. Solid colors cover the whole
+/// panel; the checkerboard only covers the image rect, so it shows through the
+/// transparent pixels only.
+fn paint_viewer_background(app: &mut GuiApp, ui: &egui::Ui, image_rect: egui::Rect) {
+    let painter = ui.painter();
+    match app.viewer_background {
+        ViewerBackground::Panel => {}
+        ViewerBackground::Black => {
+            painter.rect_filled(painter.clip_rect(), 0.0, egui::Color32::BLACK);
+        }
+        ViewerBackground::White => {
+            painter.rect_filled(painter.clip_rect(), 0.0, egui::Color32::WHITE);
+        }
+        ViewerBackground::Checkerboard => {
+            let visible = image_rect.intersect(painter.clip_rect());
+            if !visible.is_positive() {
+                return;
+            }
+            // 2x2 texels repeated with nearest sampling: one texel per square
+            let texture = app.checker_texture.get_or_insert_with(|| {
+                ui.ctx().load_texture(
+                    "viewer_checkerboard",
+                    egui::ColorImage::from_gray([2, 2], &[204, 153, 153, 204]),
+                    egui::TextureOptions::NEAREST_REPEAT,
+                )
+            });
+            // UVs are anchored at the image corner so the pattern pans with the image
+            let period = 2.0 * CHECKER_CELL;
+            let uv = egui::Rect::from_min_max(
+                ((visible.min - image_rect.min) / period).to_pos2(),
+                ((visible.max - image_rect.min) / period).to_pos2(),
+            );
+            painter.image(texture.id(), visible, uv, egui::Color32::WHITE);
+        }
+    }
+}
+
+/// Largest rect of the given aspect ratio centered in `image_rect`; the area
+/// outside it is dimmed.
+fn draw_crop_guide(painter: &egui::Painter, image_rect: egui::Rect, aspect: f32, label: &str) {
+    let (w, h) = if image_rect.width() / image_rect.height() > aspect {
+        (image_rect.height() * aspect, image_rect.height())
+    } else {
+        (image_rect.width(), image_rect.width() / aspect)
+    };
+    let crop = egui::Rect::from_center_size(image_rect.center(), egui::vec2(w, h));
+
+    let shade = egui::Color32::from_black_alpha(140);
+    for r in [
+        egui::Rect::from_min_max(image_rect.min, egui::pos2(image_rect.max.x, crop.min.y)),
+        egui::Rect::from_min_max(egui::pos2(image_rect.min.x, crop.max.y), image_rect.max),
+        egui::Rect::from_min_max(
+            egui::pos2(image_rect.min.x, crop.min.y),
+            egui::pos2(crop.min.x, crop.max.y),
+        ),
+        egui::Rect::from_min_max(
+            egui::pos2(crop.max.x, crop.min.y),
+            egui::pos2(image_rect.max.x, crop.max.y),
+        ),
+    ] {
+        if r.is_positive() {
+            painter.rect_filled(r, 0.0, shade);
+        }
+    }
+    painter.rect_stroke(
+        crop,
+        0.0,
+        egui::Stroke::new(1.5, egui::Color32::WHITE),
+        egui::StrokeKind::Middle,
+    );
+    painter.text(
+        crop.left_top() + egui::vec2(6.0, 4.0),
+        egui::Align2::LEFT_TOP,
+        label,
+        egui::FontId::proportional(14.0),
+        egui::Color32::WHITE,
+    );
+}
+
 #[inline(always)]
 fn srgb_to_linear(c: f32) -> f32 {
     if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
@@ -2778,24 +3433,33 @@ fn compute_histogram_from_image(
                 }
                 Err(e) => {
                     // Log the actual error that bubbled up and explicitly return None
-                    eprintln!(
+                    debug_log!(
                         "[DEBUG-HISTOGRAM] scanner::load_image_fast failed for {:?}: {}",
-                        path, e
+                        path,
+                        e
                     );
                     return None;
                 }
             }
         } else {
-            eprintln!("[DEBUG-HISTOGRAM] Failed to read bytes for {:?}", path);
+            debug_log!("[DEBUG-HISTOGRAM] Failed to read bytes for {:?}", path);
             return None;
         }
     }
 
+    // JPEG: the histogram is taken from 128x128 anyway, so decode at 1/8 scale if possible
+    if matches!(ext.as_str(), "jpg" | "jpeg")
+        && let Ok(bytes) = std::fs::read(path)
+        && let Some((img, _)) = crate::scanner::load_jpeg_scaled(path, &bytes, 256)
+    {
+        return Some(compute_histogram_from_dynamic_image(&img, palette_config));
+    }
+
     // Standard fallback using the `image` crate
     let img = match image::open(path) {
         Ok(i) => i,
         Err(e) => {
-            eprintln!("[DEBUG-HISTOGRAM] image::open failed for {:?}: {}", path, e);
+            debug_log!("[DEBUG-HISTOGRAM] image::open failed for {:?}: {}", path, e);
             return None;
         }
     };
@@ -2811,7 +3475,7 @@ fn compute_histogram_from_raw(
     let data = match fs::read(path) {
         Ok(d) => d,
         Err(e) => {
-            eprintln!("[DEBUG-HISTOGRAM] Failed to read RAW file {:?}: {}", path, e);
+            debug_log!("[DEBUG-HISTOGRAM] Failed to read RAW file {:?}: {}", path, e);
             return None;
         }
     };
@@ -2819,16 +3483,17 @@ fn compute_histogram_from_raw(
     let mut raw = match rsraw::RawImage::open(&data) {
         Ok(r) => r,
         Err(e) => {
-            eprintln!(
+            debug_log!(
                 "[DEBUG-HISTOGRAM] rsraw failed to open {:?}: {}. Trying EXIF fallback...",
-                path, e
+                path,
+                e
             );
             // Re-use the fallback from the main loader!
             if let Some((color_img, _)) = extract_biggest_exif_preview(path, &data) {
-                eprintln!("[DEBUG-HISTOGRAM] EXIF fallback success for {:?}", path);
+                debug_log!("[DEBUG-HISTOGRAM] EXIF fallback success for {:?}", path);
                 return Some(compute_histogram_from_colorimage(&color_img, palette_config, false));
             } else {
-                eprintln!("[DEBUG-HISTOGRAM] EXIF fallback also failed for {:?}", path);
+                debug_log!("[DEBUG-HISTOGRAM] EXIF fallback also failed for {:?}", path);
                 return None;
             }
         }
@@ -2846,25 +3511,26 @@ fn compute_histogram_from_raw(
                     Ok(img) => {
                         return Some(compute_histogram_from_dynamic_image(&img, palette_config));
                     }
-                    Err(e) => eprintln!(
+                    Err(e) => debug_log!(
                         "[DEBUG-HISTOGRAM] image::load_from_memory failed on rsraw thumb for {:?}: {}",
-                        path, e
+                        path,
+                        e
                     ),
                 }
             } else {
-                eprintln!(
+                debug_log!(
                     "[DEBUG-HISTOGRAM] No suitable JPEG thumbnail found by rsraw for {:?}",
                     path
                 );
             }
         }
-        Err(e) => eprintln!("[DEBUG-HISTOGRAM] rsraw.extract_thumbs failed for {:?}: {}", path, e),
+        Err(e) => debug_log!("[DEBUG-HISTOGRAM] rsraw.extract_thumbs failed for {:?}: {}", path, e),
     }
 
     // Fallback: process the full RAW (slower)
-    eprintln!("[DEBUG-HISTOGRAM] Falling back to full RAW decode for {:?}", path);
+    debug_log!("[DEBUG-HISTOGRAM] Falling back to full RAW decode for {:?}", path);
     if let Err(e) = raw.unpack() {
-        eprintln!("[DEBUG-HISTOGRAM] rsraw.unpack failed for {:?}: {}", path, e);
+        debug_log!("[DEBUG-HISTOGRAM] rsraw.unpack failed for {:?}: {}", path, e);
         return None;
     }
 
@@ -2884,7 +3550,10 @@ fn compute_histogram_from_raw(
                         palette_config,
                     ));
                 } else {
-                    eprintln!("[DEBUG-HISTOGRAM] Failed to create GrayImage buffer for {:?}", path);
+                    debug_log!(
+                        "[DEBUG-HISTOGRAM] Failed to create GrayImage buffer for {:?}",
+                        path
+                    );
                 }
             } else if processed.len() == w * h * 3 {
                 // RGB: construct DynamicImage
@@ -2896,10 +3565,10 @@ fn compute_histogram_from_raw(
                         palette_config,
                     ));
                 } else {
-                    eprintln!("[DEBUG-HISTOGRAM] Failed to create RgbImage buffer for {:?}", path);
+                    debug_log!("[DEBUG-HISTOGRAM] Failed to create RgbImage buffer for {:?}", path);
                 }
             } else {
-                eprintln!(
+                debug_log!(
                     "[DEBUG-HISTOGRAM] RAW size mismatch for {:?}: w={}, h={}, len={}",
                     path,
                     w,
@@ -2909,7 +3578,7 @@ fn compute_histogram_from_raw(
             }
         }
         Err(e) => {
-            eprintln!("[DEBUG-HISTOGRAM] rsraw.process failed for {:?}: {}", path, e);
+            debug_log!("[DEBUG-HISTOGRAM] rsraw.process failed for {:?}: {}", path, e);
         }
     }
 
@@ -2923,7 +3592,7 @@ pub(super) fn render_histogram(
     available_rect: egui::Rect,
     path: &Path,
 ) {
-    let palette_config = crate::db::PaletteConfig::from_gui_config(&app.ctx.gui_config);
+    let palette_config = crate::db::PaletteConfig::from_gui_config(&app.gui_config);
     let histogram_mode = app.histogram_mode;
 
     let window_width = ui.ctx().input(|i| {
@@ -3224,6 +3893,27 @@ fn draw_histogram(
     }
 }
 
+/// EXIF overlay lines: the configured tags, plus how a JPEG XL was coded.
+/// Capture times are shown in `display_tz`.
+fn overlay_tags(
+    path: &Path,
+    exif_tags: &[String],
+    decimal_coords: bool,
+    use_gps_utc: bool,
+    display_tz: crate::exif_extract::DisplayTz,
+) -> Vec<(String, String)> {
+    let mut tags = scanner::get_exif_tags(path, exif_tags, decimal_coords, use_gps_utc);
+    if tags.iter().any(|(k, _)| k.to_ascii_lowercase().starts_with("datetime"))
+        && let Some(exif) = crate::exif_extract::read_exif_data(path, None)
+    {
+        crate::exif_extract::apply_display_tz(&exif, &mut tags, display_tz);
+    }
+    if let Some(info) = scanner::jxl_info_from_file(path) {
+        tags.push(("JXL".to_string(), info.describe()));
+    }
+    tags
+}
+
 /// Render EXIF information overlay, using cached data if available
 /// Position: to the right of histogram if shown, otherwise bottom-left corner
 pub(super) fn render_exif(
@@ -3232,13 +3922,14 @@ pub(super) fn render_exif(
     available_rect: egui::Rect,
     path: &Path,
 ) {
-    let exif_tags = &app.ctx.gui_config.exif_tags;
+    let exif_tags = &app.gui_config.exif_tags;
     if exif_tags.is_empty() {
         return;
     }
 
-    let decimal_mode = &app.ctx.gui_config.decimal_coords.unwrap_or(false);
+    let decimal_mode = &app.gui_config.decimal_coords.unwrap_or(false);
     let use_gps = app.state.use_gps_utc;
+    let display_tz = app.display_tz();
 
     // Check cache first
     let tags = if let Some((cached_path, cached_tags)) = &app.cached_exif {
@@ -3246,7 +3937,7 @@ pub(super) fn render_exif(
             cached_tags.clone()
         } else {
             // Cache miss (or invalidated by 'G')
-            let new_tags = scanner::get_exif_tags(path, exif_tags, *decimal_mode, use_gps);
+            let new_tags = overlay_tags(path, exif_tags, *decimal_mode, use_gps, display_tz);
             app.cached_exif = Some((path.to_path_buf(), new_tags.clone()));
             // Check fallback warning during load
             if use_gps && !crate::exif_extract::has_gps_time(path) {
@@ -3261,7 +3952,7 @@ pub(super) fn render_exif(
             new_tags
         }
     } else {
-        let new_tags = scanner::get_exif_tags(path, exif_tags, *decimal_mode, use_gps);
+        let new_tags = overlay_tags(path, exif_tags, *decimal_mode, use_gps, display_tz);
         app.cached_exif = Some((path.to_path_buf(), new_tags.clone()));
         new_tags
     };
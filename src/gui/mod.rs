@@ -1,8 +1,14 @@
 mod app;
+mod cache_panel;
+mod contact_sheet;
+mod cull;
 mod dialogs;
+mod fill_metadata;
 mod fonts;
 pub mod gps_map;
+mod group_review;
 mod image;
+mod web_export;
 
 pub use app::GuiApp;
 
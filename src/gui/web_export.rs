@@ -0,0 +1,262 @@
+//! Web export (Ctrl+Shift+E): the marked files (or the current group) written as
+//! JPEGs ready for sharing. Each copy is turned upright, scaled down to fit the
+//! chosen size and encoded without any metadata, so GPS positions, camera serials
+//! and the orientation tag are all left behind. Only the color profile is kept.
+//! The originals are only read.
+
+use crossbeam_channel::{Receiver, TryRecvError, unbounded};
+use eframe::egui;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{ImageEncoder, Rgb};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use super::app::GuiApp;
+use crate::{format_size, scanner};
+
+/// Transparent areas come out white, as on most web pages
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+
+#[derive(Clone, Copy)]
+pub(super) struct WebExportOptions {
+    /// Longest edge of the exported images in pixels; smaller images keep their size
+    pub max_dim: u32,
+    /// JPEG quality, 1-100
+    pub quality: u8,
+}
+
+/// Outcome for one source file: the written JPEG and its size, or why it failed.
+pub(super) type ExportResult = (PathBuf, Result<(PathBuf, u64), String>);
+
+/// Write every file of `files` into `out_dir` (created if needed) as a JPEG per
+/// `opts`. Output names are the source stems with `.jpg`, numbered when two sources
+/// share a stem; existing files are never overwritten. `done` counts finished files.
+pub(super) fn export_web(
+    files: &[PathBuf],
+    opts: WebExportOptions,
+    out_dir: &Path,
+    done: &AtomicUsize,
+) -> Vec<ExportResult> {
+    if let Err(e) = fs::create_dir_all(out_dir) {
+        let msg = format!("cannot create {}: {}", out_dir.display(), e);
+        return files.iter().map(|f| (f.clone(), Err(msg.clone()))).collect();
+    }
+    // Names are picked up front so the parallel writers never race for one
+    let mut taken = HashSet::new();
+    let targets: Vec<PathBuf> = files.iter().map(|f| output_name(out_dir, f, &mut taken)).collect();
+
+    files
+        .par_iter()
+        .zip(targets)
+        .map(|(src, dst)| {
+            let result = export_one(src, &dst, opts).map(|size| (dst, size));
+            done.fetch_add(1, Ordering::Relaxed);
+            (src.clone(), result)
+        })
+        .collect()
+}
+
+/// `out_dir/<stem>.jpg`, or `<stem>_2.jpg` and up when that exists or is taken.
+fn output_name(out_dir: &Path, src: &Path, taken: &mut HashSet<PathBuf>) -> PathBuf {
+    let stem = src.file_stem().unwrap_or_default().to_string_lossy();
+    let mut candidate = out_dir.join(format!("{}.jpg", stem));
+    let mut n = 2;
+    while taken.contains(&candidate) || candidate.exists() {
+        candidate = out_dir.join(format!("{}_{}.jpg", stem, n));
+        n += 1;
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
+
+/// The source's ICC profile, to embed in the export so wide-gamut files (Display P3
+/// HEICs, Adobe RGB JPEGs) keep their colors. None for RAWs, whose embedded
+/// previews are sRGB.
+fn source_icc(path: &Path, bytes: &[u8]) -> Option<Vec<u8>> {
+    if scanner::is_raw_ext(path) {
+        return None;
+    }
+    if scanner::is_heif_ext(path) {
+        let ctx = libheif_rs::HeifContext::read_from_bytes(bytes).ok()?;
+        return ctx.primary_image_handle().ok()?.color_profile_raw().map(|p| p.data);
+    }
+    crate::hdr::extract_icc_profile(bytes)
+}
+
+fn export_one(src: &Path, dst: &Path, opts: WebExportOptions) -> Result<u64, String> {
+    let bytes = crate::archive::read(src).map_err(|e| e.to_string())?;
+    let img = super::contact_sheet::decode_upright_from(src, &bytes, opts.max_dim)
+        .ok_or_else(|| "cannot decode".to_string())?;
+    let icc = source_icc(src, &bytes);
+    drop(bytes);
+    let img = if img.width().max(img.height()) > opts.max_dim {
+        img.resize(opts.max_dim, opts.max_dim, FilterType::Lanczos3)
+    } else {
+        img
+    };
+    let rgb = super::contact_sheet::flatten_alpha(&img, BACKGROUND);
+
+    let mut data = Vec::new();
+    let mut encoder = JpegEncoder::new_with_quality(&mut data, opts.quality.clamp(1, 100));
+    if let Some(icc) = icc {
+        encoder.set_icc_profile(icc).map_err(|e| e.to_string())?;
+    }
+    encoder
+        .write_image(rgb.as_raw(), rgb.width(), rgb.height(), image::ExtendedColorType::Rgb8)
+        .map_err(|e| e.to_string())?;
+    let mut file = fs::File::create_new(dst).map_err(|e| e.to_string())?;
+    if let Err(e) = file.write_all(&data) {
+        drop(file);
+        let _ = fs::remove_file(dst);
+        return Err(e.to_string());
+    }
+    Ok(data.len() as u64)
+}
+
+/// State of the open export window.
+pub(super) struct WebExport {
+    files: Vec<PathBuf>,
+    out_dir: String,
+    opts: WebExportOptions,
+    /// Files finished so far and the final results, while the export runs
+    running: Option<(Arc<AtomicUsize>, Receiver<Vec<ExportResult>>)>,
+    report: Option<Vec<ExportResult>>,
+}
+
+impl WebExport {
+    pub(super) fn open(app: &GuiApp) -> Self {
+        let cfg = &app.gui_config;
+        let out_dir = match &cfg.web_export_dir {
+            Some(dir) => dir.to_string_lossy().into_owned(),
+            None => format!("web_export_{}", chrono::Local::now().format("%Y%m%d_%H%M%S")),
+        };
+        Self {
+            files: app.marked_or_group_files(),
+            out_dir,
+            opts: WebExportOptions {
+                max_dim: cfg.web_export_max.unwrap_or(2048).clamp(64, 16384),
+                quality: cfg.web_export_quality.unwrap_or(85).clamp(1, 100),
+            },
+            running: None,
+            report: None,
+        }
+    }
+
+    /// Whether an export is in progress; the window stays open until it ends.
+    pub(super) fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+}
+
+/// The export window: settings, then progress, then the per-file report.
+pub(super) fn show(app: &mut GuiApp, ctx: &egui::Context) {
+    let Some(export) = app.web_export.as_mut() else { return };
+    if let Some((_, rx)) = &export.running {
+        match rx.try_recv() {
+            Ok(results) => {
+                export.running = None;
+                export.report = Some(results);
+            }
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint_after(std::time::Duration::from_millis(100))
+            }
+            // The worker panicked: what it wrote is unknown, so report every file
+            Err(TryRecvError::Disconnected) => {
+                export.running = None;
+                let msg = "export stopped unexpectedly".to_string();
+                export.report =
+                    Some(export.files.iter().map(|f| (f.clone(), Err(msg.clone()))).collect());
+            }
+        }
+    }
+    let mut start = false;
+    let mut close = false;
+
+    egui::Window::new("Export for web").collapsible(false).resizable(false).show(ctx, |ui| {
+        if let Some((done, _)) = &export.running {
+            let done = done.load(Ordering::Relaxed);
+            let total = export.files.len();
+            ui.label(format!("Exporting to {}", export.out_dir));
+            ui.add(
+                egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                    .text(format!("{} / {}", done, total)),
+            );
+        } else if let Some(report) = &export.report {
+            let failed: Vec<_> =
+                report.iter().filter_map(|(src, r)| r.as_ref().err().map(|e| (src, e))).collect();
+            let bytes: u64 = report.iter().filter_map(|(_, r)| r.as_ref().ok()).map(|r| r.1).sum();
+            ui.label(format!(
+                "{} of {} files written to {} ({})",
+                report.len() - failed.len(),
+                report.len(),
+                export.out_dir,
+                format_size(bytes)
+            ));
+            if !failed.is_empty() {
+                ui.colored_label(egui::Color32::RED, format!("{} failed:", failed.len()));
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for (src, e) in failed {
+                        ui.label(format!("{}: {}", src.display(), e));
+                    }
+                });
+            }
+            close = ui.button("Close").clicked();
+        } else {
+            ui.label(format!(
+                "{} files as upright JPEGs without metadata (GPS included); \
+                 the originals are not changed.",
+                export.files.len()
+            ));
+            egui::Grid::new("web_export_opts").num_columns(2).show(ui, |ui| {
+                ui.label("Output folder");
+                ui.text_edit_singleline(&mut export.out_dir);
+                ui.end_row();
+                ui.label("Longest edge");
+                ui.add(
+                    egui::DragValue::new(&mut export.opts.max_dim).range(64..=16384).suffix(" px"),
+                );
+                ui.end_row();
+                ui.label("Quality");
+                ui.add(egui::Slider::new(&mut export.opts.quality, 1..=100));
+                ui.end_row();
+            });
+            ui.horizontal(|ui| {
+                start = ui
+                    .add_enabled(
+                        !export.files.is_empty() && !export.out_dir.trim().is_empty(),
+                        egui::Button::new("Export"),
+                    )
+                    .clicked();
+                close = ui.button("Cancel").clicked();
+            });
+        }
+    });
+
+    if start {
+        let files = export.files.clone();
+        let opts = export.opts;
+        let out_dir = PathBuf::from(export.out_dir.trim());
+        let done = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = unbounded();
+        let counter = done.clone();
+        thread::spawn(move || {
+            let _ = tx.send(export_web(&files, opts, &out_dir, &counter));
+        });
+        export.running = Some((done, rx));
+    }
+    if close {
+        if let Some(report) = &export.report {
+            let failed = report.iter().filter(|(_, r)| r.is_err()).count();
+            let msg = format!("Web export: {} written, {} failed", report.len() - failed, failed);
+            app.set_status(msg, failed > 0);
+        }
+        app.web_export = None;
+    }
+}
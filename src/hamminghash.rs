@@ -1,7 +1,7 @@
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 
 // 15 bits for 64-bit hash (approx 23% difference)
-#[allow(unused)]
 pub const MAX_SIMILARITY_64: u32 = 15;
 // 63 bits for 256-bit hash
 // Note: If you want strictly "near duplicates", use 30.
@@ -11,7 +11,6 @@ pub const MAX_SIMILARITY_256: u32 = 63;
 pub trait HammingHash: Copy + Send + Sync + 'static {
     const NUM_CHUNKS: usize;
     const NUM_BUCKETS: usize;
-    #[allow(dead_code)]
     const MAX_DIST: u32;
 
     fn get_chunk(&self, chunk_idx: usize) -> u16;
@@ -19,7 +18,7 @@ pub trait HammingHash: Copy + Send + Sync + 'static {
     fn bit_width_per_chunk() -> usize;
 }
 
-// --- Implementation for 64-bit pHash ---
+// --- Implementation for 64-bit pHash / whash ---
 impl HammingHash for u64 {
     const NUM_CHUNKS: usize = 8;
     const NUM_BUCKETS: usize = 256;
@@ -148,6 +147,71 @@ impl<H: HammingHash> MIHIndex<H> {
     }
 }
 
+// --- Growable Index (streaming) ---
+
+/// A multi-index hash table that grows one hash at a time, for grouping while a
+/// scan still runs. Same chunks and probing as `MIHIndex`/`find_groups`, but the
+/// buckets live in a map instead of the compact CSR layout.
+pub struct GrowableMIH<H: HammingHash> {
+    hashes: Vec<H>,
+    buckets: HashMap<usize, Vec<DenseId>>,
+}
+
+impl<H: HammingHash> GrowableMIH<H> {
+    pub fn new() -> Self {
+        Self { hashes: Vec::new(), buckets: HashMap::new() }
+    }
+
+    pub fn insert(&mut self, hash: H) -> DenseId {
+        let dense = DenseId(self.hashes.len() as u32);
+        for k in 0..H::NUM_CHUNKS {
+            let flat = k * H::NUM_BUCKETS + hash.get_chunk(k) as usize;
+            self.buckets.entry(flat).or_default().push(dense);
+        }
+        self.hashes.push(hash);
+        dense
+    }
+
+    #[inline(always)]
+    pub fn hash(&self, id: DenseId) -> &H {
+        &self.hashes[id.index()]
+    }
+
+    /// Indexed hashes within `max_dist` of `query`, found the way `find_groups`
+    /// finds them.
+    pub fn neighbors(&self, query: &H, max_dist: u32) -> Vec<DenseId> {
+        let chunk_tolerance = max_dist / H::NUM_CHUNKS as u32;
+        let mut seen = HashSet::new();
+        let mut results = Vec::new();
+        for k in 0..H::NUM_CHUNKS {
+            let q_chunk = query.get_chunk(k);
+            let mut probe = |val: u16| {
+                let flat = k * H::NUM_BUCKETS + val as usize;
+                for &dense in self.buckets.get(&flat).into_iter().flatten() {
+                    if seen.insert(dense.0)
+                        && query.hamming_distance(&self.hashes[dense.index()]) <= max_dist
+                    {
+                        results.push(dense);
+                    }
+                }
+            };
+            probe(q_chunk);
+            if chunk_tolerance >= 1 {
+                for bit in 0..H::bit_width_per_chunk() {
+                    probe(q_chunk ^ (1 << bit));
+                }
+            }
+        }
+        results
+    }
+}
+
+impl<H: HammingHash> Default for GrowableMIH<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // --- Helper: Sparse BitSet ---
 pub struct SparseBitSet {
     data: Vec<u64>,
@@ -411,6 +475,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn growable_index_matches_neighbors() {
+        let mut index = GrowableMIH::<u64>::new();
+        let target = 0xABCD_1234_5678_90EFu64;
+        let near = index.insert(target ^ 1);
+        let far = index.insert(!target);
+        let also_near = index.insert(target ^ 0x8001);
+
+        let mut found = index.neighbors(&target, 8);
+        found.sort_by_key(|d| d.index());
+        assert_eq!(found, vec![near, also_near]);
+        assert!(!index.neighbors(&target, 8).contains(&far));
+        assert!(index.neighbors(&target, 0).is_empty());
+    }
+
     // --- TEST 3: Dihedral (Rotation/Flip) Robustness ---
     #[test]
     fn test_pdq_dihedral_robustness() {
@@ -211,29 +211,60 @@ pub fn detect_cicp_png(bytes: &[u8]) -> Option<Cicp> {
     None
 }
 
-/// Detect cICP from an embedded ICC profile by asking the image crate's
-/// decoders to extract it. `ImageDecoder::icc_profile` handles the format-
-/// specific packaging for free — JPEG APP2 `ICC_PROFILE` segment reassembly,
-/// PNG `iCCP` zlib decompression, WebP `ICCP` chunks, TIFF tag 34675, etc.
+/// Detect cICP from an embedded ICC profile (see `extract_icc_profile`).
 /// Returns `None` if the format isn't supported, the file has no ICC
 /// profile, or the profile lacks a v4.4 `cicp` tag.
 fn detect_cicp_from_icc_profile(bytes: &[u8]) -> Option<Cicp> {
+    detect_cicp_icc(&extract_icc_profile(bytes)?)
+}
+
+/// The embedded ICC profile of a JPEG, PNG, WebP or TIFF file, by asking the
+/// image crate's decoders for it. `ImageDecoder::icc_profile` handles the format-
+/// specific packaging for free — JPEG APP2 `ICC_PROFILE` segment reassembly,
+/// PNG `iCCP` zlib decompression, WebP `ICCP` chunks, TIFF tag 34675, etc.
+/// Returns `None` for other formats and for files without a profile.
+pub(crate) fn extract_icc_profile(bytes: &[u8]) -> Option<Vec<u8>> {
     // Dispatch to a concrete decoder based on magic bytes. Using the
     // specific decoder types (instead of the generic `ImageReader` path)
     // avoids any lifetime gymnastics around borrowed byte slices and keeps
     // the set of supported formats explicit.
-    let icc: Vec<u8> = match image::guess_format(bytes).ok()? {
+    let cursor = std::io::Cursor::new(bytes);
+    match image::guess_format(bytes).ok()? {
         image::ImageFormat::Jpeg => {
-            let mut d = image::codecs::jpeg::JpegDecoder::new(std::io::Cursor::new(bytes)).ok()?;
-            d.icc_profile().ok().flatten()?
+            image::codecs::jpeg::JpegDecoder::new(cursor).ok()?.icc_profile().ok().flatten()
         }
         image::ImageFormat::Png => {
-            let mut d = image::codecs::png::PngDecoder::new(std::io::Cursor::new(bytes)).ok()?;
-            d.icc_profile().ok().flatten()?
+            image::codecs::png::PngDecoder::new(cursor).ok()?.icc_profile().ok().flatten()
         }
-        _ => return None,
+        image::ImageFormat::WebP => {
+            image::codecs::webp::WebPDecoder::new(cursor).ok()?.icc_profile().ok().flatten()
+        }
+        image::ImageFormat::Tiff => {
+            image::codecs::tiff::TiffDecoder::new(cursor).ok()?.icc_profile().ok().flatten()
+        }
+        _ => None,
+    }
+}
+
+/// Body of the tag `sig` of an ICC profile. The layout is a 128-byte header,
+/// then a u32 tag count, then (tag_count * 12) bytes of tag table entries:
+/// signature, offset and size. None when the tag is missing or out of bounds.
+fn icc_tag<'a>(icc: &'a [u8], sig: &[u8; 4]) -> Option<&'a [u8]> {
+    let be32 = |at: usize| -> Option<usize> {
+        Some(u32::from_be_bytes(icc.get(at..at + 4)?.try_into().ok()?) as usize)
     };
-    detect_cicp_icc(&icc)
+    let tag_count = be32(128)?;
+    let tag_table_start = 132usize;
+    let tag_table_end = tag_table_start.checked_add(tag_count.checked_mul(12)?)?;
+    if tag_table_end > icc.len() {
+        return None;
+    }
+    let entry = (0..tag_count)
+        .map(|idx| tag_table_start + idx * 12)
+        .find(|&entry| &icc[entry..entry + 4] == sig)?;
+    let offset = be32(entry + 4)?;
+    let end = offset.checked_add(be32(entry + 8)?)?;
+    icc.get(offset..end)
 }
 
 /// Extract cICP values from a reassembled ICC profile buffer. Looks up the
@@ -248,45 +279,48 @@ fn detect_cicp_from_icc_profile(bytes: &[u8]) -> Option<Cicp> {
 ///   1 byte    VideoFullRangeFlag
 /// ```
 fn detect_cicp_icc(icc: &[u8]) -> Option<Cicp> {
-    // ICC layout: 128-byte header, then u32 tag count, then (tag_count * 12)
-    // bytes of tag table entries.
-    if icc.len() < 132 {
-        return None;
-    }
-    let tag_count = u32::from_be_bytes([icc[128], icc[129], icc[130], icc[131]]) as usize;
-    let tag_table_start = 132usize;
-    let tag_table_end = tag_table_start.checked_add(tag_count.checked_mul(12)?)?;
-    if tag_table_end > icc.len() {
+    let data = icc_tag(icc, b"cicp")?;
+    if data.len() < 12 || &data[0..4] != b"cicp" {
         return None;
     }
+    Some(Cicp {
+        color_primaries: data[8],
+        transfer_characteristics: data[9],
+        matrix_coefficients: data[10],
+        full_range: data[11] != 0,
+    })
+}
 
-    for idx in 0..tag_count {
-        let entry = tag_table_start + idx * 12;
-        if &icc[entry..entry + 4] != b"cicp" {
-            continue;
-        }
-        let offset =
-            u32::from_be_bytes([icc[entry + 4], icc[entry + 5], icc[entry + 6], icc[entry + 7]])
-                as usize;
-        let size =
-            u32::from_be_bytes([icc[entry + 8], icc[entry + 9], icc[entry + 10], icc[entry + 11]])
-                as usize;
-        let end = offset.checked_add(size)?;
-        if end > icc.len() || size < 12 {
-            return None;
+/// The profile description of an ICC profile ("sRGB IEC61966-2.1", "Adobe RGB
+/// (1998)"), from its `desc` tag: ASCII `desc` type in v2 profiles, the first
+/// record of a UTF-16 `mluc` in v4 ones.
+pub(crate) fn icc_description(icc: &[u8]) -> Option<String> {
+    let data = icc_tag(icc, b"desc")?;
+    let be32 = |at: usize| -> Option<usize> {
+        Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize)
+    };
+    let text = match data.get(0..4)? {
+        b"desc" => {
+            let len = be32(8)?;
+            let ascii = data.get(12..12usize.checked_add(len)?)?;
+            String::from_utf8_lossy(ascii).into_owned()
         }
-        let data = &icc[offset..end];
-        if &data[0..4] != b"cicp" {
-            return None;
+        b"mluc" => {
+            if be32(8)? == 0 {
+                return None;
+            }
+            let (len, offset) = (be32(20)?, be32(24)?);
+            let utf16: Vec<u16> = data
+                .get(offset..offset.checked_add(len)?)?
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            String::from_utf16_lossy(&utf16)
         }
-        return Some(Cicp {
-            color_primaries: data[8],
-            transfer_characteristics: data[9],
-            matrix_coefficients: data[10],
-            full_range: data[11] != 0,
-        });
-    }
-    None
+        _ => return None,
+    };
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
 }
 
 // ---------------------------------------------------------------------------
@@ -852,4 +886,39 @@ mod tests {
         assert!(c.full_range);
         assert!(c.is_hdr());
     }
+
+    /// Minimal ICC profile holding a single tag with the given body.
+    fn icc_with_tag(sig: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut icc = vec![0u8; 128];
+        icc.extend_from_slice(&1u32.to_be_bytes());
+        icc.extend_from_slice(sig);
+        icc.extend_from_slice(&(128u32 + 4 + 12).to_be_bytes());
+        icc.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        icc.extend_from_slice(body);
+        icc
+    }
+
+    #[test]
+    fn icc_description_v2_and_v4() {
+        let text = b"sRGB IEC61966-2.1\0";
+        let mut desc = b"desc\0\0\0\0".to_vec();
+        desc.extend_from_slice(&(text.len() as u32).to_be_bytes());
+        desc.extend_from_slice(text);
+        assert_eq!(
+            icc_description(&icc_with_tag(b"desc", &desc)).as_deref(),
+            Some("sRGB IEC61966-2.1")
+        );
+
+        let utf16: Vec<u8> = "Display P3".encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+        let mut mluc = b"mluc\0\0\0\0".to_vec();
+        mluc.extend_from_slice(&1u32.to_be_bytes()); // records
+        mluc.extend_from_slice(&12u32.to_be_bytes()); // record size
+        mluc.extend_from_slice(b"enUS");
+        mluc.extend_from_slice(&(utf16.len() as u32).to_be_bytes());
+        mluc.extend_from_slice(&28u32.to_be_bytes()); // offset from the tag start
+        mluc.extend_from_slice(&utf16);
+        assert_eq!(icc_description(&icc_with_tag(b"desc", &mluc)).as_deref(), Some("Display P3"));
+
+        assert_eq!(icc_description(&icc_with_tag(b"cprt", &desc)), None);
+    }
 }
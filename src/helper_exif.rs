@@ -131,3 +131,19 @@ pub fn get_altitude(exif: &exif::Exif) -> Option<f64> {
     }
     None
 }
+
+/// JPEG bytes of the EXIF thumbnail (IFD1), borrowed from the parsed EXIF buffer.
+pub fn thumbnail_jpeg(exif: &exif::Exif) -> Option<&[u8]> {
+    let uint = |tag| exif.get_field(tag, In::THUMBNAIL).and_then(|f| f.value.get_uint(0));
+    let offset = uint(Tag::JPEGInterchangeFormat)? as usize;
+    let length = uint(Tag::JPEGInterchangeFormatLength)? as usize;
+    // Offsets are relative to the TIFF header, which is where buf() starts
+    exif.buf().get(offset..offset.checked_add(length)?)
+}
+
+/// Pulls the embedded EXIF thumbnail JPEG out of a JPEG/TIFF/HEIF container, for
+/// previews that don't need the full image decoded.
+pub fn extract_thumbnail(bytes: &[u8]) -> Option<Vec<u8>> {
+    let exif = exif::Reader::new().read_from_container(&mut std::io::Cursor::new(bytes)).ok()?;
+    thumbnail_jpeg(&exif).map(<[u8]>::to_vec)
+}
@@ -2,7 +2,8 @@
 // Uses BTreeMap for flexible EXIF tag storage with postcard serialization.
 
 use crate::exif_types::{
-    ExifValue, TAG_DERIVED_TIMESTAMP, TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE, TAG_ORIENTATION,
+    ExifValue, TAG_DERIVED_SUBSEC_NANOS, TAG_DERIVED_TIMESTAMP, TAG_DERIVED_TZ_OFFSET,
+    TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE, TAG_ORIENTATION,
 };
 use geo::Point;
 use serde::{Deserialize, Serialize};
@@ -75,6 +76,22 @@ impl ImageFeatures {
         })
     }
 
+    /// Sub-second part of the EXIF timestamp in nanoseconds (SubSecTimeOriginal)
+    pub fn exif_subsec_nanos(&self) -> Option<u32> {
+        self.tags.get(&TAG_DERIVED_SUBSEC_NANOS).and_then(|v| match v {
+            ExifValue::Long(n) => Some(*n),
+            _ => None,
+        })
+    }
+
+    /// EXIF timezone offset in seconds east of UTC (OffsetTimeOriginal)
+    pub fn exif_tz_offset(&self) -> Option<i32> {
+        self.tags.get(&TAG_DERIVED_TZ_OFFSET).and_then(|v| match v {
+            ExifValue::Signed(o) => Some(*o),
+            _ => None,
+        })
+    }
+
     /// Get a tag value by ID
     #[allow(dead_code)]
     pub fn get_tag(&self, tag_id: u16) -> Option<&ExifValue> {
@@ -148,5 +165,20 @@ mod tests {
         assert_eq!(features.orientation(), 1);
         assert!(features.gps_pos().is_none());
         assert!(features.exif_timestamp().is_none());
+        assert!(features.exif_subsec_nanos().is_none());
+        assert!(features.exif_tz_offset().is_none());
+    }
+
+    #[test]
+    fn test_time_refinement_roundtrip() {
+        let mut features = ImageFeatures::new(10, 10);
+        features.insert_tag(TAG_DERIVED_TIMESTAMP, ExifValue::Long64(1_700_000_000));
+        features.insert_tag(TAG_DERIVED_SUBSEC_NANOS, ExifValue::Long(120_000_000));
+        features.insert_tag(TAG_DERIVED_TZ_OFFSET, ExifValue::Signed(-5 * 3600));
+
+        let restored = ImageFeatures::from_bytes(&features.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.exif_timestamp(), Some(1_700_000_000));
+        assert_eq!(restored.exif_subsec_nanos(), Some(120_000_000));
+        assert_eq!(restored.exif_tz_offset(), Some(-5 * 3600));
     }
 }
@@ -1,18 +1,20 @@
 use crate::db::{AppContext, HashAlgorithm};
-use crate::scanner::ScanConfig;
-use crate::state::get_bit_identical_counts;
+use crate::debug_log;
+use crate::fileops::DeleteMode;
+use crate::scanner::{NameMatch, ScanConfig};
+use crate::state::{KeepPolicy, KeepRule, get_bit_identical_counts, get_smaller_copies};
 use chrono::{DateTime, Utc};
 use clap::Parser;
 use geo::Point;
 use jiff::Timestamp;
 use libheif_rs::integration::image::{register_heic_decoding_hook, register_heif_decoding_hook};
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::ffi::CStr;
 use std::fs;
 use std::io::{self, Write};
 use std::os::raw::c_char;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[cfg(not(target_os = "windows"))]
 use jemallocator::Jemalloc;
@@ -30,9 +32,11 @@ struct DepInfo {
     source: Option<String>,
 }
 
+mod archive;
 mod db;
 mod exif_extract;
 mod exif_types;
+mod exif_write;
 mod fileops;
 mod gui;
 mod hamminghash;
@@ -43,10 +47,14 @@ mod image_features;
 mod pdqhash;
 mod position;
 mod raw_exif;
+mod reference;
 mod scanner;
 mod search_index;
+mod similar;
 mod state;
+mod thumbnails;
 mod ui;
+mod whash;
 
 #[derive(Debug, Clone)]
 pub struct FileMetadata {
@@ -57,10 +65,122 @@ pub struct FileMetadata {
     pub resolution: Option<(u32, u32)>,
     pub content_hash: [u8; 32],
     pub pixel_hash: Option<[u8; 32]>,
-    pub orientation: u8, // Added: EXIF orientation (1-8)
+    pub oriented_pixel_hash: Option<[u8; 32]>, // Pixel hash after applying EXIF orientation
+    pub near_identical: Option<u128>, // Near-identical subgroup (first member's unique_file_id)
+    pub luma_variant: Option<u128>,   // Grayscale/color variant subgroup (same key scheme)
+    pub icc_profile: Option<IccProfile>, // Embedded ICC profile (`icc_profiles` only)
+    pub orientation: u8,              // Added: EXIF orientation (1-8)
     pub gps_pos: Option<Point<f64>>,
-    pub unique_file_id: u128,        // Always has dev+inode
+    pub unique_file_id: u128,           // Always has dev+inode
     pub exif_timestamp: Option<i64>, // EXIF DateTimeOriginal or DateTimeDigitized (Unix epoch seconds)
+    pub exif_subsec_nanos: Option<u32>, // SubSecTimeOriginal as nanoseconds
+    pub exif_tz_offset: Option<i32>, // OffsetTimeOriginal, seconds east of UTC
+}
+
+impl FileMetadata {
+    /// Pixel hash used for content subgroup (C1/C2) detection.
+    /// Prefers the orientation-normalized hash when it was computed; see
+    /// `unify_pixel_hashes` for keeping that consistent within a group.
+    pub fn subgroup_pixel_hash(&self) -> Option<[u8; 32]> {
+        self.oriented_pixel_hash.or(self.pixel_hash)
+    }
+
+    /// Drops the oriented pixel hashes of a group unless every member has one,
+    /// so `subgroup_pixel_hash` compares the same kind of hash across the group.
+    /// An oriented hash never equals a plain one of a rotated file, and a plain
+    /// hash may equal the oriented hash of a different file.
+    pub fn unify_pixel_hashes(group: &mut [FileMetadata]) {
+        if group.iter().any(|f| f.oriented_pixel_hash.is_none()) {
+            for f in group {
+                f.oriented_pixel_hash = None;
+            }
+        }
+    }
+
+    /// Resolution as displayed: with `oriented`, W and H are swapped for EXIF
+    /// orientations 5-8 (90°/270° rotations) so the numbers match the screen.
+    pub fn display_resolution(&self, oriented: bool) -> Option<(u32, u32)> {
+        self.resolution.map(|(w, h)| {
+            if oriented && (5..=8).contains(&self.orientation) { (h, w) } else { (w, h) }
+        })
+    }
+
+    /// Width divided by height as displayed (EXIF rotation applied): below 1 for
+    /// portrait, above 2 for most panoramas. None without a known resolution.
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        self.display_resolution(true).filter(|&(_, h)| h > 0).map(|(w, h)| w as f64 / h as f64)
+    }
+
+    /// Sort key for "exif-date" ordering: (UTC seconds, nanoseconds).
+    /// `exif_timestamp` is the naive local time read as UTC; when the camera
+    /// recorded an offset it is removed so mixed-timezone sets order correctly.
+    pub fn exif_sort_key(&self) -> Option<(i64, u32)> {
+        let ts = self.exif_timestamp?;
+        let utc = ts - self.exif_tz_offset.unwrap_or(0) as i64;
+        Some((utc, self.exif_subsec_nanos.unwrap_or(0)))
+    }
+}
+
+/// An embedded ICC profile: a hash of its bytes, to tell profiles apart, and its
+/// description for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IccProfile {
+    pub hash: [u8; 8],
+    pub description: Option<String>,
+}
+
+impl IccProfile {
+    pub fn from_bytes(icc: &[u8]) -> Self {
+        let mut hash = [0u8; 8];
+        hash.copy_from_slice(&blake3::hash(icc).as_bytes()[..8]);
+        Self { hash, description: hdr::icc_description(icc) }
+    }
+
+    /// The profile stored in cached features: Some(None) for a file without one,
+    /// None when the features predate `icc_profiles` and it was never looked at.
+    pub fn from_features(features: &image_features::ImageFeatures) -> Option<Option<Self>> {
+        use crate::exif_types::{ExifValue, TAG_DERIVED_ICC_DESCRIPTION, TAG_DERIVED_ICC_HASH};
+        let ExifValue::Bytes(hash) = features.get_tag(TAG_DERIVED_ICC_HASH)? else {
+            return None;
+        };
+        let Ok(hash) = <[u8; 8]>::try_from(hash.as_slice()) else {
+            return Some(None);
+        };
+        let description = features.get_tag_string(TAG_DERIVED_ICC_DESCRIPTION);
+        Some(Some(Self { hash, description }))
+    }
+
+    /// Record `profile` (None = the file has none) in features for `from_features`.
+    pub fn insert_tags(profile: Option<&Self>, features: &mut image_features::ImageFeatures) {
+        use crate::exif_types::{ExifValue, TAG_DERIVED_ICC_DESCRIPTION, TAG_DERIVED_ICC_HASH};
+        let hash = profile.map_or(Vec::new(), |p| p.hash.to_vec());
+        features.insert_tag(TAG_DERIVED_ICC_HASH, ExifValue::Bytes(hash));
+        if let Some(description) = profile.and_then(|p| p.description.clone()) {
+            features.insert_tag(TAG_DERIVED_ICC_DESCRIPTION, ExifValue::String(description));
+        }
+    }
+
+    /// Short name for list labels: the common RGB spaces by their usual names,
+    /// other profiles by their description (or hash when they have none).
+    pub fn label(&self) -> String {
+        let Some(desc) = &self.description else {
+            return format!("ICC {}", hex::encode(&self.hash[..4]));
+        };
+        let lower = desc.to_lowercase();
+        let known = [
+            ("srgb", "sRGB"),
+            ("adobe rgb", "AdobeRGB"),
+            ("display p3", "Display P3"),
+            ("prophoto", "ProPhoto"),
+        ];
+        match known.iter().find(|(key, _)| lower.contains(key)) {
+            Some((_, name)) => name.to_string(),
+            None if desc.chars().count() > 20 => {
+                format!("{}…", desc.chars().take(19).collect::<String>())
+            }
+            None => desc.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -76,6 +196,136 @@ pub struct GroupInfo {
     pub status: GroupStatus,
 }
 
+/// Totals shown once after a duplicate scan.
+#[derive(Debug, Clone, Default)]
+pub struct ScanSummary {
+    pub groups: usize,
+    pub files: usize,
+    pub identical_groups: usize,
+    /// Bytes freed by keeping only the largest file of every AllIdentical group
+    pub reclaimable_bytes: u64,
+}
+
+impl ScanSummary {
+    pub fn from_groups(groups: &[Vec<FileMetadata>], infos: &[GroupInfo]) -> Self {
+        let mut summary = Self {
+            groups: groups.len(),
+            files: groups.iter().map(|g| g.len()).sum(),
+            ..Default::default()
+        };
+        for (group, info) in groups.iter().zip(infos) {
+            if info.status != GroupStatus::AllIdentical {
+                continue;
+            }
+            summary.identical_groups += 1;
+            // Hard links share storage, count each inode once
+            let mut seen = HashSet::new();
+            let sizes: Vec<u64> =
+                group.iter().filter(|f| seen.insert(f.unique_file_id)).map(|f| f.size).collect();
+            let largest = sizes.iter().copied().max().unwrap_or(0);
+            summary.reclaimable_bytes += sizes.iter().sum::<u64>() - largest;
+        }
+        summary
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} groups, {} files. {} fully identical groups, {} reclaimable by keeping one file each.",
+            self.groups,
+            self.files,
+            self.identical_groups,
+            format_size(self.reclaimable_bytes)
+        )
+    }
+}
+
+/// `--changes` output: difference between the current scan and the stored baseline.
+#[derive(Debug, Default, Serialize)]
+struct ChangeReport {
+    first_run: bool,
+    /// When the baseline being compared against was saved (RFC 3339)
+    baseline_time: Option<String>,
+    files: usize,
+    new: Vec<String>,
+    removed: Vec<String>,
+    modified: Vec<String>,
+}
+
+impl ChangeReport {
+    fn compare(baseline: &db::ScanBaseline, current: &[(String, [u8; 32])]) -> Self {
+        let old: HashMap<&str, &[u8; 32]> =
+            baseline.files.iter().map(|(p, h)| (p.as_str(), h)).collect();
+        let now: HashSet<&str> = current.iter().map(|(p, _)| p.as_str()).collect();
+        let mut report = Self {
+            baseline_time: chrono::DateTime::<Utc>::from_timestamp(baseline.timestamp as i64, 0)
+                .map(|dt| dt.to_rfc3339()),
+            files: current.len(),
+            ..Default::default()
+        };
+        for (path, hash) in current {
+            match old.get(path.as_str()) {
+                None => report.new.push(path.clone()),
+                Some(h) if *h != hash => report.modified.push(path.clone()),
+                _ => {}
+            }
+        }
+        report.removed = baseline
+            .files
+            .iter()
+            .filter(|(p, _)| !now.contains(p.as_str()))
+            .map(|(p, _)| p.clone())
+            .collect();
+        report
+    }
+}
+
+/// `--json` output: one entry per duplicate group.
+#[derive(Debug, Serialize)]
+struct GroupReport {
+    /// "all-identical", "some-identical" or "none" (bit-identical files in the group)
+    status: &'static str,
+    max_dist: u32,
+    files: Vec<FileReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct FileReport {
+    path: String,
+    size: u64,
+    modified: String,
+    resolution: Option<(u32, u32)>,
+    content_hash: String,
+    /// Path of the group member this file is a lower-resolution copy of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    smaller_copy_of: Option<String>,
+}
+
+impl GroupReport {
+    fn new(group: &[FileMetadata], info: &GroupInfo, smaller_copy_ratio: f32) -> Self {
+        let smaller_copies = get_smaller_copies(group, smaller_copy_ratio);
+        let status = match info.status {
+            GroupStatus::AllIdentical => "all-identical",
+            GroupStatus::SomeIdentical => "some-identical",
+            GroupStatus::None => "none",
+        };
+        let files = group
+            .iter()
+            .enumerate()
+            .map(|(i, f)| FileReport {
+                path: f.path.to_string_lossy().into_owned(),
+                size: f.size,
+                modified: f.modified.to_rfc3339(),
+                resolution: f.resolution,
+                content_hash: hex::encode(f.content_hash),
+                smaller_copy_of: smaller_copies
+                    .get(&i)
+                    .map(|&j| group[j].path.to_string_lossy().into_owned()),
+            })
+            .collect();
+        Self { status, max_dist: info.max_dist, files }
+    }
+}
+
 // --- Runtime Version Checking for dav1d and heif ---
 // If "staticbuild" is enabled, link statically
 #[cfg_attr(feature = "staticbuild", link(name = "dav1d", kind = "static"))]
@@ -169,6 +419,16 @@ pub fn format_relative_time(ts: Timestamp) -> String {
     parts.into_iter().take(3).collect::<Vec<_>>().join(" ")
 }
 
+/// Short blake3 prefix shown in place of the resolution when no image was decoded
+/// (e.g. --name-dupes), so same-named files can be told apart by content.
+pub fn short_content_hash(file: &FileMetadata) -> String {
+    if file.content_hash == [0u8; 32] {
+        "?".to_string()
+    } else {
+        format!("b3:{}", hex::encode(&file.content_hash[..4]))
+    }
+}
+
 // --- Analysis Logic ---
 pub fn analyze_group(
     files: &mut Vec<FileMetadata>,
@@ -184,25 +444,46 @@ pub fn analyze_group(
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Finds visually similar images.", long_about = None)]
 struct Cli {
-    #[arg(required_unless_present = "show_build_info")]
-    #[arg(required_unless_present = "show_exif_tags")]
-    #[arg(required_unless_present = "prune")]
-    #[arg(required_unless_present = "show_ignored")]
-    #[arg(required_unless_present = "unignore")]
+    /// Directories or files to scan; in duplicate mode defaults to watch_dirs under
+    /// [grouping] in the config
     paths: Vec<String>,
 
+    /// Print debug output (cache hits, config and reload tracing); also PHDUPES_VERBOSE=1
+    #[arg(long)]
+    verbose: bool,
     #[arg(long)]
     rehash: bool,
     #[arg(long)]
     rehash_only: bool,
-    /// Similarity threshold (default: 40 for PDQ hash)
+    /// Re-decode unchanged files instead of trusting cached PDQ data (content hash is kept)
+    #[arg(long)]
+    no_fast_resume: bool,
+    /// Perceptual hash: pdq, or whash (64-bit wavelet hash, more tolerant of heavy resizing)
+    #[arg(long, default_value = "pdq")]
+    hash: String,
+    /// Similarity threshold (default: 40 for PDQ hash, 8 for whash)
     #[arg(long)]
     similarity: Option<u32>,
     /// Calculate hash of raw pixel data to find content-identical files (e.g. PNG vs JPG)
     #[arg(long)]
     pixel_hash: bool,
-
-    /// Sort order with --view: name, name-desc, name-natural, name-natural-desc, date, date-desc, size, size-desc, random, exif-date, exif-date-desc, location
+    /// Also hash pixels after applying EXIF orientation, so rotated copies match (implies --pixel-hash)
+    #[arg(long)]
+    oriented_pixel_hash: bool,
+    /// Only group files whose long and short edges each differ by at most this fraction (e.g. 0.05)
+    #[arg(long, value_name = "FRACTION")]
+    resolution_tolerance: Option<f32>,
+    /// Group files by identical name across directories instead of image content: exact, natural
+    #[arg(long, value_name = "MODE", num_args(0..=1), default_missing_value = "exact")]
+    name_dupes: Option<String>,
+    /// With --name-dupes: hash only the first KB of each file and read it whole only when
+    /// another same-size file shares that prefix (e.g. 64)
+    #[arg(long, value_name = "KB")]
+    prefix_hash: Option<usize>,
+
+    /// Sort order with --view: name, name-desc, name-natural, name-natural-desc,
+    /// name-natural-ci, name-natural-ci-desc (natural, ignoring case), date, date-desc,
+    /// size, size-desc, random, exif-date, exif-date-desc, aspect, aspect-desc, location
     #[arg(long, default_value = "name")]
     sort: String,
 
@@ -218,9 +499,28 @@ struct Cli {
     #[arg(long)]
     relative_times: bool,
 
-    #[arg(long)]
+    #[arg(long, alias = "trash")]
     use_trash: bool,
 
+    /// Headless cleanup: in every bit-identical group keep one file (see --keep) and delete
+    /// the others (--use-trash to trash them). Only prints the plan unless --confirm is given
+    #[arg(long)]
+    clean: bool,
+
+    /// With --clean: file to keep per group: best (resolution, extension, size), newest,
+    /// oldest, shortest-path
+    #[arg(long, value_name = "POLICY", default_value = "best")]
+    keep: String,
+
+    /// With --clean: really delete instead of the default dry run
+    #[arg(long)]
+    confirm: bool,
+
+    /// Move files from a staging folder (gui.staging_dir/YYYY-MM-DD) or the review folder
+    /// (gui.review_dir) back to where they came from
+    #[arg(long, value_name = "DIR")]
+    restore_staged: Option<PathBuf>,
+
     /// View mode: browse images without similarity checking
     #[arg(long)]
     view: bool,
@@ -229,6 +529,15 @@ struct Cli {
     #[arg(long)]
     view_flatten: bool,
 
+    /// Descend at most this many directory levels below each path (0 = no subdirectories)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Keep files under overlapping paths once per path: they are not grouped with
+    /// themselves, and the root-relative path display (P) shows each root's path
+    #[arg(long)]
+    keep_root_aliases: bool,
+
     /// Shuffle images randomly (implies --view)
     #[arg(long)]
     shuffle: bool,
@@ -241,6 +550,10 @@ struct Cli {
     #[arg(long, value_name = "DIR")]
     move_marked: Option<PathBuf>,
 
+    /// View mode: badge files already present (bit- or pixel-identical) in this folder
+    #[arg(long, value_name = "DIR")]
+    reference: Option<PathBuf>,
+
     /// Use embedded thumbnails from RAW files instead of processing
     #[arg(long)]
     raw_thumbnails: bool,
@@ -261,6 +574,24 @@ struct Cli {
     #[arg(long)]
     show_ignored: bool,
 
+    /// Print a JSON report of new, removed and modified files since the previous --changes run
+    /// for the same paths, then store the current state as the new baseline
+    #[arg(long)]
+    changes: bool,
+
+    /// Print the duplicate groups as JSON instead of text
+    #[arg(long)]
+    json: bool,
+
+    /// Re-hash files that have a cached content hash and report entries that no longer
+    /// match the file data (e.g. edited in place with the mtime restored)
+    #[arg(long)]
+    verify: bool,
+
+    /// With --verify: remove mismatching cache entries so the next scan re-hashes them
+    #[arg(long)]
+    purge_stale: bool,
+
     /// Remove file(s) from ignore list by filename(s), group UUID, or PDQ hash
     #[arg(long, value_name = "VALUE", num_args(1..))]
     unignore: Vec<String>,
@@ -268,14 +599,24 @@ struct Cli {
 
 impl Cli {
     fn validate(&self) -> Result<(), String> {
+        if HashAlgorithm::parse(&self.hash.to_lowercase()).is_none() {
+            return Err(format!("Invalid --hash '{}'. Use one of: pdq, whash", self.hash));
+        }
+
         // Validate similarity based on hash algorithm
-        let max_similarity = crate::hamminghash::MAX_SIMILARITY_256;
+        let algorithm = self.hash_algorithm();
+        let max_similarity = match algorithm {
+            HashAlgorithm::PdqHash => crate::hamminghash::MAX_SIMILARITY_256,
+            HashAlgorithm::WHash => crate::hamminghash::MAX_SIMILARITY_64,
+        };
 
         let similarity = self.get_similarity();
         if similarity > max_similarity {
             return Err(format!(
-                "Similarity must be 0-{} for PDQ hash. Got {}.",
-                max_similarity, similarity
+                "Similarity must be 0-{} for {}. Got {}.",
+                max_similarity,
+                algorithm.name(),
+                similarity
             ));
         }
 
@@ -284,6 +625,8 @@ impl Cli {
             "name-desc",
             "name-natural",
             "name-natural-desc",
+            "name-natural-ci",
+            "name-natural-ci-desc",
             "date",
             "date-desc",
             "size",
@@ -291,6 +634,8 @@ impl Cli {
             "random",
             "exif-date",
             "exif-date-desc",
+            "aspect",
+            "aspect-desc",
             "location",
         ];
         let sort_lower = self.sort.to_lowercase();
@@ -302,6 +647,48 @@ impl Cli {
             ));
         }
 
+        if let Some(ref mode) = self.name_dupes
+            && NameMatch::parse(&mode.to_lowercase()).is_none()
+        {
+            return Err(format!(
+                "Invalid --name-dupes mode '{}'. Use one of: exact, natural",
+                mode
+            ));
+        }
+
+        if KeepPolicy::parse(&self.keep.to_lowercase()).is_none() {
+            return Err(format!(
+                "Invalid --keep '{}'. Use one of: best, newest, oldest, shortest-path",
+                self.keep
+            ));
+        }
+
+        if self.confirm && !self.clean {
+            return Err("--confirm requires --clean".to_string());
+        }
+
+        if self.clean && (self.use_gui || self.use_tui || self.delete || self.is_view_mode()) {
+            return Err(
+                "--clean can't be combined with --use-gui, --use-tui, --delete or view mode"
+                    .to_string(),
+            );
+        }
+
+        if self.json && (self.use_gui || self.use_tui || self.delete || self.is_view_mode()) {
+            return Err(
+                "--json can't be combined with --use-gui, --use-tui, --delete or view mode"
+                    .to_string(),
+            );
+        }
+
+        if self.purge_stale && !self.verify {
+            return Err("--purge-stale requires --verify".to_string());
+        }
+
+        if self.paths.is_empty() && self.is_view_mode() {
+            return Err("View mode needs a directory or file to open".to_string());
+        }
+
         if self.use_tui && self.use_gui {
             return Err("Cannot use both --use-tui and --use-gui".to_string());
         }
@@ -321,6 +708,12 @@ impl Cli {
             return Err("Slideshow interval must be positive".to_string());
         }
 
+        if let Some(tol) = self.resolution_tolerance
+            && (tol.is_nan() || tol < 0.0)
+        {
+            return Err("Resolution tolerance must be 0 or greater".to_string());
+        }
+
         Ok(())
     }
 
@@ -331,12 +724,15 @@ impl Cli {
 
     /// Get the hash algorithm based on CLI flags
     fn hash_algorithm(&self) -> HashAlgorithm {
-        HashAlgorithm::PdqHash
+        HashAlgorithm::parse(&self.hash.to_lowercase()).unwrap_or_default()
     }
 
     /// Get similarity threshold with algorithm-specific defaults
     fn get_similarity(&self) -> u32 {
-        self.similarity.unwrap_or(40)
+        self.similarity.unwrap_or(match self.hash_algorithm() {
+            HashAlgorithm::PdqHash => 40,
+            HashAlgorithm::WHash => 8,
+        })
     }
 }
 
@@ -357,7 +753,7 @@ fn relative_time_str(modified: &DateTime<Utc>) -> String {
         .unwrap_or_else(|| modified.format("%Y-%m-%d %H:%M:%S").to_string())
 }
 
-fn format_size(bytes: u64) -> String {
+pub fn format_size(bytes: u64) -> String {
     if bytes < 1024 {
         return format!("{} B", bytes);
     }
@@ -373,11 +769,86 @@ fn format_size(bytes: u64) -> String {
     format!("{:.2} GB", gb)
 }
 
+/// Configured watch directories that exist, as scan paths; missing ones are
+/// reported and skipped so one unplugged drive doesn't abort the whole run.
+fn existing_watch_dirs(dirs: &[PathBuf]) -> Vec<String> {
+    dirs.iter()
+        .filter(|d| {
+            let ok = d.is_dir();
+            if !ok {
+                eprintln!("Warning: watch directory {} not found, skipping", d.display());
+            }
+            ok
+        })
+        .map(|d| d.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// --clean: keep one file of every bit-identical group (chosen by `policy`) and delete
+/// the rest. `state` holds only `AllIdentical` groups; perceptual matches are never
+/// touched, nor are groups of protected content. Without `confirm` this is a dry run
+/// that only prints the plan and the bytes it would free.
+fn run_clean(
+    state: &mut state::AppState,
+    policy: KeepPolicy,
+    confirm: bool,
+    staging_dir: Option<&Path>,
+) {
+    let verb = DeleteMode::new(state.use_trash, staging_dir).verb();
+
+    let mut to_delete = Vec::new();
+    let mut group_count = 0;
+    for group in state.groups.iter().filter(|g| g.len() > 1) {
+        if group.iter().any(|f| state.protected.contains(&f.unique_file_id)) {
+            println!("\nskip   {} (protected)", group[0].path.display());
+            continue;
+        }
+        let keeper = state.keeper_index_by(group, policy);
+        println!("\nkeep   {}", group[keeper].path.display());
+        for (_, file) in group.iter().enumerate().filter(|&(idx, _)| idx != keeper) {
+            println!("{:<6} {}", verb, file.path.display());
+            to_delete.push(file.path.clone());
+        }
+        group_count += 1;
+    }
+
+    state.marked_for_deletion = to_delete.clone();
+    let reclaimable = state.marked_reclaimable_bytes();
+    if !confirm {
+        println!(
+            "\nDry run: would {} {} files in {} groups, reclaiming {}. Add --confirm to apply.",
+            verb,
+            to_delete.len(),
+            group_count,
+            format_size(reclaimable)
+        );
+        return;
+    }
+
+    let mut removed = Vec::new();
+    for path in &to_delete {
+        match fileops::remove_file(path, state.use_trash, staging_dir) {
+            Ok(()) => removed.push(path.clone()),
+            Err(e) => eprintln!("Failed to {} {}: {}", verb, path.display(), e),
+        }
+    }
+    state.marked_for_deletion = removed.clone();
+    println!(
+        "\nDone: {} {} of {} files in {} groups, reclaimed {}.",
+        verb,
+        removed.len(),
+        to_delete.len(),
+        group_count,
+        format_size(state.marked_reclaimable_bytes())
+    );
+}
+
 fn run_interactive_cli_delete(
     groups: Vec<Vec<FileMetadata>>,
     group_infos: Vec<GroupInfo>,
     show_relative_times: bool,
     use_trash: bool,
+    staging_dir: Option<&Path>,
 ) {
     let mut input_buf = String::new();
     let stdin = io::stdin();
@@ -434,7 +905,12 @@ fn run_interactive_cli_delete(
             );
         }
 
-        let action_verb = if use_trash { "TRASH" } else { "PERMANENTLY delete" };
+        let mode = DeleteMode::new(use_trash, staging_dir);
+        let action_verb = match mode {
+            DeleteMode::Stage => "move to STAGING",
+            DeleteMode::Trash => "TRASH",
+            DeleteMode::Permanent => "PERMANENTLY delete",
+        };
         print!("\nEnter numbers to {} (e.g. '1 3'), or ENTER to skip: ", action_verb);
         stdout.flush().ok();
 
@@ -463,15 +939,14 @@ fn run_interactive_cli_delete(
                 let file = &group[idx];
                 print!(
                     "{} {:?} ... ",
-                    if use_trash { "Trashing" } else { "Deleting" },
+                    match mode {
+                        DeleteMode::Stage => "Staging",
+                        DeleteMode::Trash => "Trashing",
+                        DeleteMode::Permanent => "Deleting",
+                    },
                     file.path.file_name().unwrap_or_default()
                 );
-                let res = if use_trash {
-                    trash::delete(&file.path).map_err(|e| io::Error::other(e.to_string()))
-                } else {
-                    fs::remove_file(&file.path)
-                };
-                match res {
+                match fileops::remove_file(&file.path, use_trash, staging_dir) {
                     Ok(_) => println!("OK"),
                     Err(e) => println!("FAILED ({})", e),
                 }
@@ -531,6 +1006,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     register_heic_decoding_hook();
     image_extras::register();
     let args = Cli::parse();
+    if args.verbose || scanner::verbose_from_env() {
+        scanner::set_verbose(true);
+    }
 
     // Handle --show-exif-tags early, before validation
     if args.show_exif_tags {
@@ -576,6 +1054,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let is_view_mode = args.is_view_mode();
     let hash_algorithm = args.hash_algorithm();
 
+    // --- RESTORE STAGED MODE ---
+    if let Some(dir) = &args.restore_staged {
+        let results = fileops::restore_staged(dir)?;
+        let mut failed = 0;
+        for r in &results {
+            match &r.outcome {
+                Ok(()) => println!("Restored {}", r.destination.display()),
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("Failed to restore {}: {}", r.source.display(), e);
+                }
+            }
+        }
+        println!("Restored {} of {} files.", results.len() - failed, results.len());
+        return Ok(());
+    }
+
     // --- PRUNE MODE ---
     if let Some(seconds) = args.prune {
         let ctx = AppContext::with_algorithm(hash_algorithm)?;
@@ -665,11 +1160,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         let ctx = AppContext::with_algorithm(hash_algorithm)?;
 
         for value in &args.unignore {
-            eprintln!("[DEBUG-UNIGNORE] Processing: {}", value);
+            debug_log!("[DEBUG-UNIGNORE] Processing: {}", value);
 
             // Try 1: Parse as UUID (xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx or 32 hex chars)
             if let Some(uuid) = AppContext::parse_uuid(value) {
-                eprintln!("[DEBUG-UNIGNORE] Parsed as UUID: {}", AppContext::format_uuid(&uuid));
+                debug_log!("[DEBUG-UNIGNORE] Parsed as UUID: {}", AppContext::format_uuid(&uuid));
                 match ctx.remove_ignored_by_uuid(&uuid) {
                     Ok(count) => {
                         println!(
@@ -677,7 +1172,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             count,
                             AppContext::format_uuid(&uuid)
                         );
-                        eprintln!(
+                        debug_log!(
                             "[DEBUG-UNIGNORE] Removed {} entries for UUID {}",
                             count,
                             AppContext::format_uuid(&uuid)
@@ -694,13 +1189,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 && let Ok(bytes) = hex::decode(value)
                 && let Ok(pdqhash) = <[u8; 32]>::try_from(bytes.as_slice())
             {
-                eprintln!("[DEBUG-UNIGNORE] Parsed as PDQ hash: {}", hex::encode(pdqhash));
+                debug_log!("[DEBUG-UNIGNORE] Parsed as PDQ hash: {}", hex::encode(pdqhash));
                 match ctx.remove_ignored_by_pdqhash(&pdqhash) {
                     Ok(count) => {
                         println!("Removed {} ignored entries matching PDQ hash {}", count, value);
-                        eprintln!(
+                        debug_log!(
                             "[DEBUG-UNIGNORE] Removed {} entries for pdqhash {}",
-                            count, value
+                            count,
+                            value
                         );
                     }
                     Err(e) => eprintln!("Failed to unignore by PDQ hash: {}", e),
@@ -711,11 +1207,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Try 3: Treat as filename — compute keyed blake3 and look up
             let file_path = std::path::Path::new(value);
             if file_path.exists() {
-                eprintln!("[DEBUG-UNIGNORE] Treating as filename: {}", value);
+                debug_log!("[DEBUG-UNIGNORE] Treating as filename: {}", value);
                 match fs::read(file_path) {
                     Ok(data) => {
                         let content_hash = *blake3::keyed_hash(&ctx.content_key, &data).as_bytes();
-                        eprintln!(
+                        debug_log!(
                             "[DEBUG-UNIGNORE] Computed keyed blake3: {}",
                             hex::encode(content_hash)
                         );
@@ -726,7 +1222,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     value,
                                     hex::encode(content_hash)
                                 );
-                                eprintln!(
+                                debug_log!(
                                     "[DEBUG-UNIGNORE] Successfully removed keyed blake3={}",
                                     hex::encode(content_hash)
                                 );
@@ -776,7 +1272,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             args.slideshow,
             args.raw_thumbnails,
             args.view_flatten,
-        );
+            args.max_depth,
+        )
+        .with_reference_dir(args.reference.clone());
         if let Err(e) = app.run() {
             eprintln!("GUI Error: {}", e);
         }
@@ -786,19 +1284,139 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Duplicate detection modes require AppContext with selected algorithm
     let ctx = AppContext::with_algorithm(hash_algorithm)?;
 
+    // No paths on the command line: scan the configured watch list together
+    let paths = if args.paths.is_empty() {
+        let dirs = existing_watch_dirs(&ctx.grouping_config.watch_dirs);
+        if dirs.is_empty() {
+            return Err(
+                "No paths given and no existing watch_dirs under [grouping] in the config".into()
+            );
+        }
+        println!("Scanning {} watched directories: {}", dirs.len(), dirs.join(", "));
+        dirs
+    } else {
+        args.paths.clone()
+    };
+
     let similarity = args.get_similarity();
+    let max_similarity = match hash_algorithm {
+        HashAlgorithm::PdqHash => crate::hamminghash::MAX_SIMILARITY_256,
+        HashAlgorithm::WHash => crate::hamminghash::MAX_SIMILARITY_64,
+    };
+    let similarity_by_ext: HashMap<String, u32> = ctx
+        .grouping_config
+        .similarity_by_ext
+        .iter()
+        .map(|(ext, &t)| (ext.trim_start_matches('.').to_lowercase(), t))
+        .collect();
+    if let Some((ext, t)) = similarity_by_ext.iter().find(|(_, t)| **t > max_similarity) {
+        return Err(format!(
+            "similarity_by_ext: {} = {} exceeds the {} maximum of {}",
+            ext,
+            t,
+            hash_algorithm.name(),
+            max_similarity
+        )
+        .into());
+    }
+    let keep_rules = KeepRule::parse_all(&ctx.grouping_config.keep_rules)?;
     let scan_config = ScanConfig {
-        paths: args.paths.clone(),
+        paths,
         rehash: args.rehash,
         similarity,
         group_by: sort_order.clone(),
         extensions: ctx.grouping_config.extensions.clone(),
         ignore_same_stem: ctx.grouping_config.ignore_same_stem,
-        calc_pixel_hash: args.pixel_hash,
+        calc_pixel_hash: args.pixel_hash || args.oriented_pixel_hash,
+        calc_oriented_pixel_hash: args.oriented_pixel_hash,
+        fast_resume: !args.no_fast_resume,
+        name_dupes: args.name_dupes.as_deref().and_then(|m| NameMatch::parse(&m.to_lowercase())),
+        resolution_tolerance: args.resolution_tolerance,
+        live_photo_exts: ctx.grouping_config.live_photo_exts.clone(),
+        max_depth: args.max_depth,
+        prefix_hash_bytes: args.prefix_hash.filter(|&kb| kb > 0).map(|kb| kb * 1024),
+        near_identical_mse: Some(ctx.grouping_config.near_identical_mse).filter(|&m| m > 0.0),
+        dedupe_scan_roots: !args.keep_root_aliases,
+        similarity_by_ext,
+        cluster_split_dist: ctx.grouping_config.cluster_split_dist,
+        screenshots: ctx.grouping_config.screenshots,
+        os_thumbnails: ctx.grouping_config.os_thumbnails,
+        scan_archives: ctx.grouping_config.scan_archives,
+        icc_profiles: ctx.grouping_config.icc_profiles,
+        luma_variant_mse: Some(ctx.grouping_config.luma_variant_mse).filter(|&m| m > 0.0),
     };
 
     if args.rehash_only {
-        let _ = scanner::scan_and_group(&scan_config, &ctx, None);
+        let _ = scanner::scan_and_group(&scan_config, &ctx, None, None);
+        return Ok(());
+    }
+
+    // --- VERIFY CACHED HASHES ---
+    if args.verify {
+        let report = scanner::verify_content_hashes(&scan_config, &ctx);
+        for (path, _) in &report.mismatched {
+            println!("MISMATCH {}", path.display());
+        }
+        let purged = if args.purge_stale && !report.mismatched.is_empty() {
+            let keys: Vec<[u8; 32]> = report.mismatched.iter().map(|(_, k)| *k).collect();
+            match ctx.remove_meta_entries(&keys) {
+                Ok(n) => n,
+                Err(e) => {
+                    eprintln!("Failed to purge stale entries: {}", e);
+                    0
+                }
+            }
+        } else {
+            0
+        };
+        println!(
+            "Verified {} cached files: {} mismatched, {} purged ({} not in cache).",
+            report.verified + report.mismatched.len(),
+            report.mismatched.len(),
+            purged,
+            report.uncached
+        );
+        return Ok(());
+    }
+
+    // --- CHANGES SINCE LAST RUN ---
+    if args.changes {
+        let roots: Vec<String> = scan_config
+            .paths
+            .iter()
+            .map(|p| {
+                fs::canonicalize(p)
+                    .map(|c| c.to_string_lossy().into_owned())
+                    .unwrap_or_else(|_| p.clone())
+            })
+            .collect();
+        let current: Vec<(String, [u8; 32])> = scanner::scan_content_hashes(&scan_config, &ctx)
+            .into_iter()
+            .map(|(p, h)| (p.to_string_lossy().into_owned(), h))
+            .collect();
+
+        let report = match ctx.get_scan_baseline(&roots) {
+            Ok(Some(baseline)) => ChangeReport::compare(&baseline, &current),
+            Ok(None) => {
+                eprintln!("No baseline stored for these paths (first run); saving current state.");
+                ChangeReport { first_run: true, files: current.len(), ..Default::default() }
+            }
+            Err(e) => {
+                eprintln!("Failed to read scan baseline: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let baseline = db::ScanBaseline { timestamp, files: current };
+        if let Err(e) = ctx.save_scan_baseline(&roots, &baseline) {
+            eprintln!("Failed to save scan baseline: {}", e);
+        }
+
+        println!("{}", serde_json::to_string_pretty(&report)?);
         return Ok(());
     }
 
@@ -812,7 +1430,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .map(|(i, e)| (e.to_lowercase(), i))
             .collect();
 
-        println!("Launching GUI with PDQ hash algorithm (similarity: {})...", similarity);
+        println!(
+            "Launching GUI with {} algorithm (similarity: {})...",
+            hash_algorithm.name(),
+            similarity
+        );
         let app = gui::GuiApp::new(
             ctx,
             scan_config,
@@ -831,8 +1453,55 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // For non-GUI modes, scan first then display results
-    let (final_groups, final_infos) = scanner::scan_and_group(&scan_config, &ctx, None);
-    println!("Found {} duplicate groups using PDQ hash.", final_groups.len());
+    let (final_groups, final_infos) = scanner::scan_and_group(&scan_config, &ctx, None, None);
+    let smaller_copy_ratio = ctx.grouping_config.smaller_copy_ratio;
+    if args.json {
+        let report: Vec<GroupReport> = final_groups
+            .iter()
+            .zip(&final_infos)
+            .map(|(group, info)| GroupReport::new(group, info, smaller_copy_ratio))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    if scan_config.name_dupes.is_some() {
+        println!("Found {} groups of identically named files.", final_groups.len());
+    } else {
+        println!("Found {} duplicate groups using {}.", final_groups.len(), hash_algorithm.name());
+    }
+    println!("{}", ScanSummary::from_groups(&final_groups, &final_infos).describe());
+
+    if args.clean {
+        let ext_priorities: HashMap<String, usize> = ctx
+            .grouping_config
+            .extensions
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.to_lowercase(), i))
+            .collect();
+        // Only bit-identical groups are safe to thin out without looking at them
+        let (groups, infos): (Vec<_>, Vec<_>) = final_groups
+            .into_iter()
+            .zip(final_infos)
+            .filter(|(group, info)| {
+                info.status == GroupStatus::AllIdentical
+                    && group.iter().all(|f| f.content_hash != [0u8; 32])
+            })
+            .unzip();
+        let mut state = state::AppState::new(
+            groups,
+            infos,
+            args.relative_times,
+            args.use_trash,
+            sort_order,
+            ext_priorities,
+        );
+        state.keep_rules = keep_rules;
+        state.sync_protected(|ch| ctx.is_protected(ch));
+        let policy = KeepPolicy::parse(&args.keep.to_lowercase()).unwrap_or(KeepPolicy::Best);
+        run_clean(&mut state, policy, args.confirm, ctx.gui_config.staging_dir.as_deref());
+        return Ok(());
+    }
 
     if args.use_tui {
         let ext_priorities: HashMap<String, usize> = ctx
@@ -852,12 +1521,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             ext_priorities,
         );
         state.move_target = args.move_marked.clone();
+        state.staging_dir = ctx.gui_config.staging_dir.clone();
+        state.review_dir = ctx.gui_config.review_dir.clone();
+        state.nav_wrap = ctx.gui_config.nav_wrap.unwrap_or(false);
+        state.live_photo_exts = ctx.grouping_config.live_photo_exts.clone();
+        state.keep_rules = keep_rules;
+        state.scan_root = state::common_scan_root(&scan_config.paths);
+        if !scan_config.dedupe_scan_roots {
+            state.scan_roots = state::scan_root_dirs(&scan_config.paths);
+        }
+        state.index_live_siblings();
+        state.sync_protected(|ch| ctx.is_protected(ch));
 
         println!("Launching TUI...");
         let mut app = ui::TuiApp::new(state);
         app.run()?;
     } else if args.delete {
-        run_interactive_cli_delete(final_groups, final_infos, args.relative_times, args.use_trash);
+        run_interactive_cli_delete(
+            final_groups,
+            final_infos,
+            args.relative_times,
+            args.use_trash,
+            ctx.gui_config.staging_dir.as_deref(),
+        );
     } else {
         let green = "\x1b[32m";
         let reset = "\x1b[0m";
@@ -881,26 +1567,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
 
             let counts = get_bit_identical_counts(group);
+            let smaller_copies = get_smaller_copies(group, smaller_copy_ratio);
 
-            for file in group {
+            for (f_idx, file) in group.iter().enumerate() {
                 let time_str = if args.relative_times {
                     relative_time_str(&file.modified)
                 } else {
                     file.modified.format("%Y-%m-%d %H:%M:%S.%f").to_string()
                 };
-                let res_str =
-                    file.resolution.map(|(w, h)| format!("{}x{}", w, h)).unwrap_or("?".to_string());
+                let res_str = file
+                    .resolution
+                    .map(|(w, h)| format!("{}x{}", w, h))
+                    .unwrap_or_else(|| short_content_hash(file));
                 let is_identical = *counts.get(&file.content_hash).unwrap_or(&0) > 1;
                 let (color_start, color_end, marker) =
                     if is_identical { (green, reset, "*") } else { ("", "", " ") };
+                let smaller_copy = smaller_copies
+                    .get(&f_idx)
+                    .map(|&j| format!(" (smaller copy of {})", group[j].path.display()))
+                    .unwrap_or_default();
                 println!(
-                    "  {}[{}] {} | {} | {} | {}{}",
+                    "  {}[{}] {} | {} | {} | {}{}{}",
                     color_start,
                     marker,
                     time_str,
                     format_size(file.size),
                     res_str,
                     file.path.display(),
+                    smaller_copy,
                     color_end
                 );
             }
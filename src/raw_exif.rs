@@ -300,6 +300,97 @@ pub fn merge_raw_info_into_features(features: &mut ImageFeatures, raw: &RawImage
     }
 }
 
+/// Locate an embedded JPEG preview directly in a RAW container, without LibRaw.
+///
+/// Last-resort fallback for files rsraw refuses to open (e.g. some compressed
+/// Fujifilm RAF variants). RAF headers point straight at a full-size JPEG;
+/// for any other container the largest well-formed SOI..EOI run is returned.
+pub fn extract_embedded_jpeg(bytes: &[u8]) -> Option<&[u8]> {
+    raf_embedded_jpeg(bytes).or_else(|| largest_jpeg_run(bytes))
+}
+
+/// RAF header: magic at 0, big-endian JPEG offset/length at 84/88.
+fn raf_embedded_jpeg(bytes: &[u8]) -> Option<&[u8]> {
+    if !bytes.starts_with(b"FUJIFILMCCD-RAW") {
+        return None;
+    }
+    let be32 = |at: usize| -> Option<usize> {
+        Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?) as usize)
+    };
+    let offset = be32(84)?;
+    let length = be32(88)?;
+    let jpeg = bytes.get(offset..offset.checked_add(length)?)?;
+    jpeg.starts_with(&[0xFF, 0xD8, 0xFF]).then_some(jpeg)
+}
+
+/// Scan for every SOI marker and walk its segments to the matching EOI,
+/// keeping the longest complete stream. Thumbnails nested inside a preview's
+/// APP1 segment are skipped because whole segments are stepped over.
+fn largest_jpeg_run(bytes: &[u8]) -> Option<&[u8]> {
+    let mut best: Option<&[u8]> = None;
+    let mut pos = 0;
+    while pos + 3 <= bytes.len() {
+        if bytes[pos..pos + 3] != [0xFF, 0xD8, 0xFF] {
+            pos += 1;
+            continue;
+        }
+        match jpeg_stream_end(bytes, pos) {
+            Some(end) => {
+                if best.is_none_or(|b| end - pos > b.len()) {
+                    best = Some(&bytes[pos..end]);
+                }
+                pos = end;
+            }
+            None => pos += 1,
+        }
+    }
+    best
+}
+
+/// Returns the offset just past the EOI of the JPEG stream starting at `start`.
+fn jpeg_stream_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 2;
+    // Marker segments up to and including SOS.
+    loop {
+        if *bytes.get(i)? != 0xFF {
+            return None;
+        }
+        let marker = *bytes.get(i + 1)?;
+        match marker {
+            0xFF => i += 1, // fill byte
+            0xD9 => return Some(i + 2),
+            0x01 | 0xD0..=0xD7 => i += 2,
+            _ => {
+                let len = u16::from_be_bytes([*bytes.get(i + 2)?, *bytes.get(i + 3)?]) as usize;
+                if len < 2 {
+                    return None;
+                }
+                i += 2 + len;
+                if marker == 0xDA {
+                    break;
+                }
+            }
+        }
+    }
+    // Entropy-coded data: stuffed 0xFF00 and restart markers continue the scan,
+    // any other marker starts a new segment (progressive) or ends the image.
+    loop {
+        let rel = bytes.get(i..)?.iter().position(|&b| b == 0xFF)?;
+        i += rel;
+        match *bytes.get(i + 1)? {
+            0x00 | 0xD0..=0xD7 | 0xFF => i += 1,
+            0xD9 => return Some(i + 2),
+            _ => {
+                let len = u16::from_be_bytes([*bytes.get(i + 2)?, *bytes.get(i + 3)?]) as usize;
+                if len < 2 {
+                    return None;
+                }
+                i += 2 + len;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -345,4 +436,44 @@ mod tests {
         assert_eq!(libraw_flip_to_exif_orientation(-1), 1);
         assert_eq!(libraw_flip_to_exif_orientation(42), 1);
     }
+
+    /// Minimal baseline JPEG skeleton: SOI, APP0, SOS, two entropy bytes, EOI.
+    fn fake_jpeg(payload: usize) -> Vec<u8> {
+        let mut j = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00];
+        j.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02]);
+        j.extend(std::iter::repeat_n(0x12, payload));
+        j.extend_from_slice(&[0xFF, 0x00, 0xFF, 0xD3, 0x34]);
+        j.extend_from_slice(&[0xFF, 0xD9]);
+        j
+    }
+
+    #[test]
+    fn test_extract_embedded_jpeg_raf_header() {
+        let jpeg = fake_jpeg(10);
+        let mut raf = b"FUJIFILMCCD-RAW 0201FF383501".to_vec();
+        raf.resize(100, 0);
+        raf[84..88].copy_from_slice(&100u32.to_be_bytes());
+        raf[88..92].copy_from_slice(&(jpeg.len() as u32).to_be_bytes());
+        raf.extend_from_slice(&jpeg);
+        // Trailing sensor data containing a larger stray JPEG must not win.
+        raf.extend_from_slice(&fake_jpeg(50));
+        assert_eq!(extract_embedded_jpeg(&raf), Some(&jpeg[..]));
+
+        // Header pointing past the end of the file.
+        raf[88..92].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(raf_embedded_jpeg(&raf), None);
+    }
+
+    #[test]
+    fn test_extract_embedded_jpeg_picks_largest() {
+        let small = fake_jpeg(4);
+        let large = fake_jpeg(40);
+        let mut raw = b"II*\0 junk".to_vec();
+        raw.extend_from_slice(&small);
+        raw.extend_from_slice(&[0x00, 0xFF, 0xD8, 0xFF, 0x00]); // truncated SOI
+        raw.extend_from_slice(&large);
+        raw.extend_from_slice(b"tail");
+        assert_eq!(extract_embedded_jpeg(&raw), Some(&large[..]));
+        assert_eq!(extract_embedded_jpeg(b"no jpeg here"), None);
+    }
 }
@@ -0,0 +1,158 @@
+// Reference folder membership (e.g. a "selects" folder) for the cull assistant.
+// Only hashes are kept: a file is "in the reference" when its content hash or
+// pixel hash appears in the folder, no grouping involved.
+
+use crate::db::{AppContext, compute_meta_key_from_metadata};
+use crate::fileops::get_file_key;
+use crate::scanner::{collect_image_files, compute_pixel_hash, is_raw_ext, load_image_fast};
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Streamed from the reference scan as files are hashed.
+pub enum ReferenceUpdate {
+    Total(usize),
+    Hashes { content_hash: [u8; 32], pixel_hash: Option<[u8; 32]> },
+    Done,
+}
+
+/// How a file matched the reference folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceMatch {
+    BitIdentical,
+    PixelIdentical,
+}
+
+#[derive(Default)]
+pub struct ReferenceSet {
+    pub root: PathBuf,
+    pub content: HashSet<[u8; 32]>,
+    pub pixel: HashSet<[u8; 32]>,
+    pub loaded: usize,
+    pub total: usize,
+    pub done: bool,
+    /// Pixel hashes of browsed files (by unique_file_id); None = not decodable
+    pub browsed_pixel: HashMap<u128, Option<[u8; 32]>>,
+    /// Pixel hash requests sent to the hasher and not answered yet
+    pub pixel_pending: usize,
+}
+
+impl ReferenceSet {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root, ..Default::default() }
+    }
+
+    pub fn apply(&mut self, update: ReferenceUpdate) {
+        match update {
+            ReferenceUpdate::Total(n) => self.total = n,
+            ReferenceUpdate::Hashes { content_hash, pixel_hash } => {
+                if content_hash != [0u8; 32] {
+                    self.content.insert(content_hash);
+                }
+                if let Some(ph) = pixel_hash.filter(|ph| *ph != [0u8; 32]) {
+                    self.pixel.insert(ph);
+                }
+                self.loaded += 1;
+            }
+            ReferenceUpdate::Done => self.done = true,
+        }
+    }
+
+    /// Match a browsed file by content hash first, then by its pixel hash if known.
+    /// A file not hashed yet (zero content hash) matches nothing.
+    pub fn lookup(&self, content_hash: &[u8; 32], unique_file_id: u128) -> Option<ReferenceMatch> {
+        if *content_hash == [0u8; 32] {
+            return None;
+        }
+        if self.content.contains(content_hash) {
+            return Some(ReferenceMatch::BitIdentical);
+        }
+        match self.browsed_pixel.get(&unique_file_id) {
+            Some(Some(ph)) if *ph != [0u8; 32] && self.pixel.contains(ph) => {
+                Some(ReferenceMatch::PixelIdentical)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Pixel hash of a file: the cached value when a duplicate scan stored one,
+/// otherwise decoded and hashed. RAW files are skipped (bit-identical only).
+pub fn pixel_hash_for_file(
+    ctx: &AppContext,
+    path: &Path,
+    bytes: &[u8],
+    content_hash: &[u8; 32],
+) -> Option<[u8; 32]> {
+    if *content_hash != [0u8; 32]
+        && let Ok(Some(ph)) = ctx.get_pixel_hash(content_hash)
+    {
+        return Some(ph);
+    }
+    if is_raw_ext(path) {
+        return None;
+    }
+    load_image_fast(path, bytes).ok().map(|img| compute_pixel_hash(&img))
+}
+
+/// Hash every image under `root` in the background, streaming the hashes to `tx`.
+/// Unchanged files take their content hash from the meta_key cache.
+pub fn spawn_reference_scan(root: PathBuf, ctx: Arc<AppContext>, tx: Sender<ReferenceUpdate>) {
+    std::thread::spawn(move || {
+        let files = collect_image_files(&[root.to_string_lossy().into_owned()], &[], None, true);
+        eprintln!("[REFERENCE] Hashing {} files in {:?}", files.len(), root);
+        let _ = tx.send(ReferenceUpdate::Total(files.len()));
+
+        let (db_tx, db_rx) = unbounded();
+        let db_handle = ctx.start_db_writer(db_rx);
+
+        files.par_iter().for_each(|path| {
+            let Ok(bytes) = fs::read(path) else { return };
+            let cached = fs::metadata(path).ok().zip(get_file_key(path)).map(|(md, id)| {
+                let meta_key = compute_meta_key_from_metadata(&ctx.meta_key, &md, id);
+                (meta_key, ctx.get_content_hash(&meta_key).ok().flatten())
+            });
+            let content_hash = match cached {
+                Some((_, Some(ch))) => ch,
+                _ => {
+                    let ch = *blake3::keyed_hash(&ctx.content_key, &bytes).as_bytes();
+                    if let Some((meta_key, None)) = cached {
+                        let _ = db_tx.send((Some((meta_key, ch)), None, None, None, None, None));
+                    }
+                    ch
+                }
+            };
+            let pixel_hash = pixel_hash_for_file(&ctx, path, &bytes, &content_hash);
+            let _ = tx.send(ReferenceUpdate::Hashes { content_hash, pixel_hash });
+        });
+
+        drop(db_tx);
+        let _ = db_handle.join();
+        eprintln!("[REFERENCE] Done: {}", root.display());
+        let _ = tx.send(ReferenceUpdate::Done);
+    });
+}
+
+/// Worker that computes pixel hashes of browsed files on request, for
+/// pixel-identical matches against the reference folder.
+pub fn spawn_pixel_hasher(
+    ctx: Arc<AppContext>,
+) -> (Sender<(u128, PathBuf)>, Receiver<(u128, Option<[u8; 32]>)>) {
+    let (req_tx, req_rx) = unbounded::<(u128, PathBuf)>();
+    let (res_tx, res_rx) = unbounded();
+    std::thread::spawn(move || {
+        while let Ok((unique_file_id, path)) = req_rx.recv() {
+            let pixel_hash = fs::read(&path).ok().and_then(|bytes| {
+                let content_hash = *blake3::keyed_hash(&ctx.content_key, &bytes).as_bytes();
+                pixel_hash_for_file(&ctx, &path, &bytes, &content_hash)
+            });
+            if res_tx.send((unique_file_id, pixel_hash)).is_err() {
+                break;
+            }
+        }
+    });
+    (req_tx, res_rx)
+}
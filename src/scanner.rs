@@ -2,8 +2,9 @@ use bytemuck::cast_slice;
 use chrono::{DateTime, Utc};
 use codes_iso_3166::part_1::CountryCode;
 use codes_iso_3166::part_2::SubdivisionCode;
-use crossbeam_channel::{Sender, unbounded};
+use crossbeam_channel::{Receiver, Sender, unbounded};
 use geo::Point;
+use image::metadata::Orientation;
 use image::{DynamicImage, GenericImageView};
 use jpeg_decoder::Decoder as Tier2Decoder;
 use libheif_rs::HeifContext;
@@ -14,28 +15,30 @@ use std::fs;
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::time::UNIX_EPOCH;
 use walkdir::WalkDir;
 use zune_jpeg::JpegDecoder as ZuneDecoder;
 
 use crate::db::{
-    AppContext, CachedCoefficients, DbUpdate, EnrichmentResult, HashValue, compute_meta_key,
-    create_feature_update,
+    AppContext, CachedCoefficients, DbUpdate, EnrichmentResult, HashAlgorithm, HashValue, JpegTier,
+    ScreenshotGrouping, compute_meta_key, create_feature_update,
 };
-use crate::exif_extract::extract_gps_lat_lon;
+use crate::exif_extract::{extract_gps_lat_lon, get_exif_time_refinement, normalize_lens_model};
 use crate::exif_types::{
-    ExifValue, TAG_DERIVED_TIMESTAMP, TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE, TAG_ORIENTATION,
+    ExifValue, TAG_DERIVED_DECODE_SKIPPED, TAG_DERIVED_DEPTH_MAP, TAG_DERIVED_FRAMES,
+    TAG_DERIVED_LOSSLESS, TAG_DERIVED_SUBSEC_NANOS, TAG_DERIVED_TIMESTAMP, TAG_DERIVED_TZ_OFFSET,
+    TAG_GPS_LATITUDE, TAG_GPS_LONGITUDE, TAG_ORIENTATION,
 };
 use crate::fileops;
 use crate::fileops::get_file_key;
-use crate::hamminghash::{HammingHash, MIHIndex, SparseBitSet};
+use crate::hamminghash::{GrowableMIH, HammingHash, MIHIndex, SparseBitSet};
 use crate::helper_exif::{get_altitude, get_date_str, get_exif_timestamp, parse_gps_coordinate};
 use crate::image_features::ImageFeatures;
 use crate::position;
 use crate::raw_exif;
-use crate::{FileMetadata, GroupInfo, GroupStatus};
-use std::sync::OnceLock;
+use crate::{FileMetadata, GroupInfo, GroupStatus, IccProfile};
+use std::sync::{OnceLock, RwLock};
 use sysinfo::System;
 
 pub const RAW_EXTS: &[&str] = &[
@@ -54,6 +57,143 @@ macro_rules! img_debug {
     };
 }
 
+/// Debug output that is too noisy for normal runs (cache hits, config and reload
+/// tracing). Enabled with `--verbose` or `PHDUPES_VERBOSE=1`.
+#[macro_export]
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        if $crate::scanner::verbose() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbose(on: bool) {
+    VERBOSE.store(on, Ordering::Relaxed);
+}
+
+pub fn verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Whether `PHDUPES_VERBOSE` asks for verbose output: anything but unset, empty,
+/// `0`, `false`, `no` or `off`.
+pub fn verbose_from_env() -> bool {
+    std::env::var("PHDUPES_VERBOSE").is_ok_and(|v| env_flag(&v))
+}
+
+fn env_flag(value: &str) -> bool {
+    let value = value.trim();
+    !(value.is_empty()
+        || value == "0"
+        || ["false", "no", "off"].iter().any(|off| value.eq_ignore_ascii_case(off)))
+}
+
+static RESOLVE_SYMLINKS: AtomicBool = AtomicBool::new(true);
+
+/// `resolve_symlinks` under `[grouping]`: false keeps symlinked folders in listed paths.
+pub fn set_resolve_symlinks(on: bool) {
+    RESOLVE_SYMLINKS.store(on, Ordering::Relaxed);
+}
+
+/// Default for `max_decode_pixels` under `[grouping]`: 500 MP, 2 GB as RGBA8
+pub const DEFAULT_MAX_DECODE_PIXELS: u64 = 500_000_000;
+
+static MAX_DECODE_PIXELS: AtomicU64 = AtomicU64::new(DEFAULT_MAX_DECODE_PIXELS);
+
+/// `max_decode_pixels` under `[grouping]`; 0 removes the limit.
+pub fn set_max_decode_pixels(max: u64) {
+    MAX_DECODE_PIXELS.store(max, Ordering::Relaxed);
+}
+
+/// Why an image of `w` x `h` must not be decoded in full, or None when it may.
+pub fn decode_size_error(w: u32, h: u32) -> Option<String> {
+    let max = MAX_DECODE_PIXELS.load(Ordering::Relaxed);
+    let pixels = w as u64 * h as u64;
+    (max > 0 && pixels > max).then(|| {
+        format!(
+            "{}x{} ({} MP) exceeds max_decode_pixels ({} MP)",
+            w,
+            h,
+            pixels / 1_000_000,
+            max / 1_000_000
+        )
+    })
+}
+
+/// Decompression-bomb guard: refuse a full decode when the header announces more
+/// pixels than `max_decode_pixels`. Files whose size can't be read from the header
+/// pass; the decoders' own allocation limits still apply to them.
+pub fn check_decode_size(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    match get_resolution(path, Some(bytes)).and_then(|(w, h)| decode_size_error(w, h)) {
+        Some(reason) => Err(reason),
+        None => Ok(()),
+    }
+}
+
+/// Files of the last duplicate scan, and how many of them came from the cache unread
+static LAST_SCAN_FILES: AtomicUsize = AtomicUsize::new(0);
+static LAST_SCAN_CACHED: AtomicUsize = AtomicUsize::new(0);
+
+/// (files, cached) of the last duplicate scan in this process, None before the first.
+pub fn last_scan_cache() -> Option<(usize, usize)> {
+    let files = LAST_SCAN_FILES.load(Ordering::Relaxed);
+    (files > 0).then(|| (files, LAST_SCAN_CACHED.load(Ordering::Relaxed)))
+}
+
+static KEEP_UNRESOLVED: AtomicBool = AtomicBool::new(true);
+
+/// `keep_unresolved` under `[grouping]`: false drops paths `canonicalize` fails on.
+pub fn set_keep_unresolved(on: bool) {
+    KEEP_UNRESOLVED.store(on, Ordering::Relaxed);
+}
+
+/// `canonicalize`, except that with `keep_unresolved` a path it fails on (offline
+/// mount, network share hiccup) that still exists per `symlink_metadata` comes back
+/// as its absolute path, with a warning, instead of vanishing from the listing.
+pub fn canonicalize(path: &Path) -> std::io::Result<std::path::PathBuf> {
+    match path.canonicalize() {
+        Ok(canonical) => Ok(canonical),
+        Err(e) if KEEP_UNRESOLVED.load(Ordering::Relaxed) && fs::symlink_metadata(path).is_ok() => {
+            let absolute = std::path::absolute(path)?;
+            eprintln!("[WARN-PATH] {}: cannot resolve ({}), listed as is", absolute.display(), e);
+            Ok(absolute)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// The path a file or folder is listed under, once `canonical` has identified it
+/// (callers dedupe by the canonical path, so two links to one file list it once).
+/// By default that is `canonical` itself; with `resolve_symlinks = false` it is the
+/// absolute path as reached, with `.` and `..` removed lexically.
+pub fn listed_path(path: &Path, canonical: std::path::PathBuf) -> std::path::PathBuf {
+    if RESOLVE_SYMLINKS.load(Ordering::Relaxed) {
+        return canonical;
+    }
+    let Ok(absolute) = std::path::absolute(path) else { return canonical };
+    let mut logical = std::path::PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                logical.pop();
+            }
+            other => logical.push(other),
+        }
+    }
+    logical
+}
+
+/// `canonicalize`, or the logical path per `listed_path`. Fails like `canonicalize`
+/// when the path doesn't exist.
+pub fn resolve_path(path: &Path) -> std::io::Result<std::path::PathBuf> {
+    let canonical = canonicalize(path)?;
+    Ok(listed_path(path, canonical))
+}
+
 const BUDGET_PER_THREAD_BYTES: u64 = 1_500 * 1024 * 1024;
 // Create an empty lock to hold our calculated thread count and byte limit.
 static SMART_LIMITS: OnceLock<(usize, u64)> = OnceLock::new();
@@ -119,12 +259,22 @@ pub fn read_exif_data(path: &Path, preloaded_bytes: Option<&[u8]>) -> Option<exi
 /// Orientation tag must NOT be applied again by the viewer, or the image gets
 /// rotated twice.
 pub(crate) fn orientation_baked_into_pixels(path: &Path) -> bool {
+    is_heif_ext(path)
+}
+
+/// HEIC/HEIF container (decoded through libheif)
+pub(crate) fn is_heif_ext(path: &Path) -> bool {
     matches!(
         path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref(),
         Some("heic") | Some("heif")
     )
 }
 
+/// JPEG XL (decoded through jxl-oxide)
+pub(crate) fn is_jxl_ext(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("jxl"))
+}
+
 pub fn get_orientation(path: &Path, preloaded_bytes: Option<&[u8]>) -> u8 {
     // HEIC/HEIF pixels come out of libheif already upright; report neutral 1.
     if orientation_baked_into_pixels(path) {
@@ -166,7 +316,7 @@ pub fn get_exif_tags(
     let exif_data = read_exif_data(path, None);
     let is_raw = is_raw_ext(path);
 
-    eprintln!(
+    debug_log!(
         "[DEBUG-GET-EXIF-TAGS] path='{}', is_raw={}, kamadak_exif_ok={}",
         path.file_name().unwrap_or_default().to_string_lossy(),
         is_raw,
@@ -238,9 +388,14 @@ fn get_exif_tags_from_rsraw(
     let info = raw.full_info();
     let mut results = Vec::new();
 
-    eprintln!(
+    debug_log!(
         "[DEBUG-GET-EXIF-TAGS-RSRAW] make='{}', model='{}', iso={}, shutter={}, aperture={}, focal={}",
-        info.make, info.model, info.iso_speed, info.shutter, info.aperture, info.focal_len
+        info.make,
+        info.model,
+        info.iso_speed,
+        info.shutter,
+        info.aperture,
+        info.focal_len
     );
 
     for tag_name in tag_names {
@@ -319,6 +474,7 @@ fn get_exif_tags_from_rsraw(
                     None
                 }
             }
+            "derivedlens" => normalize_lens_model(&info.lens_info.lens_name),
             "lensmake" => {
                 if !info.lens_info.lens_make.is_empty() {
                     Some(info.lens_info.lens_make.clone())
@@ -384,7 +540,7 @@ fn get_exif_tags_from_rsraw(
         }
     }
 
-    eprintln!("[DEBUG-GET-EXIF-TAGS-RSRAW]   Returning {} tags", results.len());
+    debug_log!("[DEBUG-GET-EXIF-TAGS-RSRAW]   Returning {} tags", results.len());
     results
 }
 
@@ -415,6 +571,14 @@ fn get_derived_value(
             let val = derive_country(lat, lon)?;
             Some(vec![("Country".to_string(), val)])
         }
+        "derivedlens" => {
+            let field = exif_data.get_field(exif::Tag::LensModel, exif::In::PRIMARY)?;
+            let exif::Value::Ascii(ref strings) = field.value else {
+                return None;
+            };
+            let val = normalize_lens_model(&String::from_utf8_lossy(strings.first()?))?;
+            Some(vec![("Lens".to_string(), val)])
+        }
         "derivedsunposition" => {
             let (lat, lon) = gps_coords?;
             let alt_m = sun_inputs.as_ref()?.unwrap_or(0.0);
@@ -467,85 +631,244 @@ pub fn load_image_fast(path: &Path, bytes: &[u8]) -> Result<image::DynamicImage,
         return Err("RAW formats handled elsewhere".to_string());
     }
 
-    match ext.as_str() {
-        "jpg" | "jpeg" => {
-            // TIER 1: Zune-JPEG
-            let mut zune = ZuneDecoder::new(std::io::Cursor::new(bytes));
-            if let Ok(pixels) = zune.decode()
-                && let Some(info) = zune.info()
-            {
-                let w = info.width as u32;
-                let h = info.height as u32;
-                let len = pixels.len();
-                let wh = w as usize * h as usize;
-
-                // Robustly handle Grayscale vs RGB based on buffer size
-                if len == wh {
-                    if let Some(buf) =
-                        image::ImageBuffer::<image::Luma<u8>, _>::from_raw(w, h, pixels)
-                    {
-                        eprintln!(
-                            "[DEBUG-LOAD] {:?} -> Zune-JPEG (Grayscale)",
-                            path.file_name().unwrap_or_default()
-                        );
-                        return Ok(image::DynamicImage::ImageLuma8(buf));
-                    }
-                } else if len == wh * 3 {
-                    if let Some(buf) =
-                        image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(w, h, pixels)
-                    {
-                        eprintln!(
-                            "[DEBUG-LOAD] {:?} -> Zune-JPEG (RGB)",
-                            path.file_name().unwrap_or_default()
-                        );
-                        return Ok(image::DynamicImage::ImageRgb8(buf));
-                    }
-                } else if len == wh * 4 {
-                    // CMYK or RGBA (Zune might output RGBA for CMYK)
-                    if let Some(buf) =
-                        image::ImageBuffer::<image::Rgba<u8>, _>::from_raw(w, h, pixels)
-                    {
-                        eprintln!(
-                            "[DEBUG-LOAD] {:?} -> Zune-JPEG (RGBA/CMYK)",
-                            path.file_name().unwrap_or_default()
-                        );
-                        return Ok(image::DynamicImage::ImageRgba8(buf));
-                    }
-                }
+    check_decode_size(path, bytes)?;
+    decode_image(path, bytes, &ext)
+}
+
+/// Decode without the `max_decode_pixels` check, for callers that ran it already.
+fn decode_image(path: &Path, bytes: &[u8], ext: &str) -> Result<image::DynamicImage, String> {
+    // Extension first (fast path); only sniff the content when that fails
+    let result = decode_by_ext(path, bytes, ext);
+    if result.is_err()
+        && let Some(real) = sniff_mismatched_format(path, bytes, &ext)
+    {
+        return decode_by_ext(path, bytes, real);
+    }
+    result
+}
+
+/// Decode a JPEG at 1/2, 1/4 or 1/8 size straight from the DCT coefficients, using
+/// the smallest scale whose longest edge still reaches `target`. Far cheaper than a
+/// full decode + resize for previews. Returns the image plus the full-size dimensions,
+/// or None when no downscaling applies (small image, CMYK, decode error) so the
+/// caller falls back to `load_image_fast`.
+pub fn load_jpeg_scaled(
+    path: &Path,
+    bytes: &[u8],
+    target: u32,
+) -> Option<(image::DynamicImage, (u32, u32))> {
+    let mut decoder = Tier2Decoder::new(std::io::Cursor::new(bytes));
+    decoder.read_info().ok()?;
+    let info = decoder.info()?;
+    let (w, h) = (info.width as u32, info.height as u32);
+    let longest = w.max(h);
+    // Below 2x the target even the 1/2 scale would undershoot
+    if target == 0 || longest < target * 2 {
+        return None;
+    }
+
+    let req_w = (w as u64 * target as u64).div_ceil(longest as u64) as u16;
+    let req_h = (h as u64 * target as u64).div_ceil(longest as u64) as u16;
+    let (sw, sh) = decoder.scale(req_w, req_h).ok()?;
+    let pixels = decoder.decode().ok()?;
+    let (sw, sh) = (sw as u32, sh as u32);
+
+    let img = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => {
+            image::DynamicImage::ImageLuma8(image::ImageBuffer::from_raw(sw, sh, pixels)?)
+        }
+        jpeg_decoder::PixelFormat::RGB24 => {
+            image::DynamicImage::ImageRgb8(image::ImageBuffer::from_raw(sw, sh, pixels)?)
+        }
+        _ => return None,
+    };
+    debug_log!(
+        "[DEBUG-LOAD] {:?} -> jpeg-decoder DCT scaled {}x{} -> {}x{}",
+        path.file_name().unwrap_or_default(),
+        w,
+        h,
+        sw,
+        sh
+    );
+    Some((img, (w, h)))
+}
+
+/// Configured JPEG decoder order; empty means the default order
+static JPEG_TIERS: RwLock<Vec<JpegTier>> = RwLock::new(Vec::new());
+
+/// Set the JPEG decoder order from the config, at startup and on a config reload.
+/// An empty list restores the default order.
+pub fn set_jpeg_tiers(tiers: &[JpegTier]) {
+    if let Ok(mut current) = JPEG_TIERS.write()
+        && current.as_slice() != tiers
+    {
+        *current = tiers.to_vec();
+        if !tiers.is_empty() {
+            eprintln!("[SYSTEM] JPEG decoder order: {:?}", tiers);
+        }
+    }
+}
+
+fn jpeg_tiers() -> Vec<JpegTier> {
+    match JPEG_TIERS.read() {
+        Ok(tiers) if !tiers.is_empty() => tiers.clone(),
+        _ => vec![JpegTier::Zune, JpegTier::JpegDecoder, JpegTier::Image],
+    }
+}
+
+/// True when the first SOF marker is a progressive one (SOF2/6/10/14).
+fn jpeg_is_progressive(bytes: &[u8]) -> bool {
+    let mut i = 2;
+    while i + 4 <= bytes.len() {
+        if bytes[i] != 0xFF {
+            return false;
+        }
+        let marker = bytes[i + 1];
+        match marker {
+            // Fill bytes before a marker
+            0xFF => i += 1,
+            // Standalone markers without a length
+            0x01 | 0xD0..=0xD7 => i += 2,
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return matches!(marker, 0xC2 | 0xC6 | 0xCA | 0xCE);
             }
+            // Start of scan before any SOF: malformed
+            0xD9 | 0xDA => return false,
+            _ => i += 2 + u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize,
+        }
+    }
+    false
+}
 
-            // TIER 2: jpeg-decoder (Fallback)
-            let mut decoder = Tier2Decoder::new(std::io::Cursor::new(bytes));
-            if let Ok(pixels) = decoder.decode()
-                && let Some(info) = decoder.info()
-            {
-                let w = info.width as u32;
-                let h = info.height as u32;
-                let len = pixels.len();
-                // Multiply in usize to avoid u32 overflow on very large images
-                let wh = w as usize * h as usize;
-
-                if len == wh {
-                    if let Some(buf) =
-                        image::ImageBuffer::<image::Luma<u8>, _>::from_raw(w, h, pixels)
-                    {
-                        eprintln!(
-                            "[DEBUG-LOAD] {:?} -> jpeg-decoder (Fallback Grayscale)",
-                            path.file_name().unwrap_or_default()
-                        );
-                        return Ok(image::DynamicImage::ImageLuma8(buf));
-                    }
-                } else if len == wh * 3
-                    && let Some(buf) =
-                        image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(w, h, pixels)
-                {
-                    eprintln!(
-                        "[DEBUG-LOAD] {:?} -> jpeg-decoder (Fallback RGB)",
-                        path.file_name().unwrap_or_default()
+/// Zune-JPEG decode, returning the image and a label for the debug log.
+fn decode_jpeg_zune(bytes: &[u8]) -> Option<(image::DynamicImage, &'static str)> {
+    let mut zune = ZuneDecoder::new(std::io::Cursor::new(bytes));
+    let pixels = zune.decode().ok()?;
+    let info = zune.info()?;
+    let w = info.width as u32;
+    let h = info.height as u32;
+    let len = pixels.len();
+    let wh = w as usize * h as usize;
+
+    // Robustly handle Grayscale vs RGB based on buffer size
+    if len == wh {
+        let buf = image::ImageBuffer::<image::Luma<u8>, _>::from_raw(w, h, pixels)?;
+        Some((image::DynamicImage::ImageLuma8(buf), "Zune-JPEG (Grayscale)"))
+    } else if len == wh * 3 {
+        let buf = image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(w, h, pixels)?;
+        Some((image::DynamicImage::ImageRgb8(buf), "Zune-JPEG (RGB)"))
+    } else if len == wh * 4 {
+        // CMYK or RGBA (Zune might output RGBA for CMYK)
+        let buf = image::ImageBuffer::<image::Rgba<u8>, _>::from_raw(w, h, pixels)?;
+        Some((image::DynamicImage::ImageRgba8(buf), "Zune-JPEG (RGBA/CMYK)"))
+    } else {
+        None
+    }
+}
+
+/// jpeg-decoder decode, returning the image and a label for the debug log.
+fn decode_jpeg_tier2(bytes: &[u8]) -> Option<(image::DynamicImage, &'static str)> {
+    let mut decoder = Tier2Decoder::new(std::io::Cursor::new(bytes));
+    let pixels = decoder.decode().ok()?;
+    let info = decoder.info()?;
+    let w = info.width as u32;
+    let h = info.height as u32;
+    let len = pixels.len();
+    // Multiply in usize to avoid u32 overflow on very large images
+    let wh = w as usize * h as usize;
+
+    if len == wh {
+        let buf = image::ImageBuffer::<image::Luma<u8>, _>::from_raw(w, h, pixels)?;
+        Some((image::DynamicImage::ImageLuma8(buf), "jpeg-decoder (Grayscale)"))
+    } else if len == wh * 3 {
+        let buf = image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(w, h, pixels)?;
+        Some((image::DynamicImage::ImageRgb8(buf), "jpeg-decoder (RGB)"))
+    } else {
+        None
+    }
+}
+
+/// image crate JPEG decode, returning the image and a label for the debug log.
+fn decode_jpeg_image(bytes: &[u8]) -> Option<(image::DynamicImage, &'static str)> {
+    let mut reader =
+        image::ImageReader::with_format(std::io::Cursor::new(bytes), image::ImageFormat::Jpeg);
+    let mut custom_limits = image::Limits::default();
+    custom_limits.max_alloc = Some(get_image_memory_limit());
+    reader.limits(custom_limits);
+    reader.decode().ok().map(|img| (img, "image crate"))
+}
+
+/// Identify the image format from its leading magic bytes.
+/// Returns the canonical extension the decoders dispatch on.
+fn sniff_image_format(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+        Some("tiff")
+    } else if bytes.starts_with(&[0xFF, 0x0A]) || bytes.starts_with(b"\0\0\0\x0cJXL \r\n\x87\n") {
+        Some("jxl")
+    } else if bytes.starts_with(b"%PDF") {
+        Some("pdf")
+    } else if bytes.starts_with(b"BM") {
+        Some("bmp")
+    } else {
+        // ISO BMFF: "ftyp" box at offset 4, major brand right after it
+        match bytes.get(4..12) {
+            Some(b) if &b[..4] == b"ftyp" => match &b[4..] {
+                b"avif" | b"avis" => Some("avif"),
+                b"heic" | b"heix" | b"hevc" | b"hevx" | b"mif1" | b"msf1" => Some("heic"),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// When `ext` does not match the content, log the mismatch and return the real format.
+fn sniff_mismatched_format(path: &Path, bytes: &[u8], ext: &str) -> Option<&'static str> {
+    let real = sniff_image_format(bytes)?;
+    let same =
+        real == ext || matches!((real, ext), ("jpg", "jpeg") | ("tiff", "tif") | ("heic", "heif"));
+    if same {
+        return None;
+    }
+    eprintln!(
+        "[WARN-FORMAT] {:?} has extension .{} but contains {}; decoding as {}",
+        path.display(),
+        ext,
+        real.to_uppercase(),
+        real
+    );
+    Some(real)
+}
+
+fn decode_by_ext(path: &Path, bytes: &[u8], ext: &str) -> Result<image::DynamicImage, String> {
+    match ext {
+        "jpg" | "jpeg" => {
+            let progressive = jpeg_is_progressive(bytes);
+            for tier in jpeg_tiers() {
+                let decoded = match tier {
+                    JpegTier::Zune => decode_jpeg_zune(bytes),
+                    JpegTier::JpegDecoder => decode_jpeg_tier2(bytes),
+                    JpegTier::Image => decode_jpeg_image(bytes),
+                };
+                if let Some((img, label)) = decoded {
+                    debug_log!(
+                        "[DEBUG-LOAD] {:?} -> {} ({})",
+                        path.file_name().unwrap_or_default(),
+                        label,
+                        if progressive { "progressive" } else { "baseline" }
                     );
-                    return Ok(image::DynamicImage::ImageRgb8(buf));
+                    return Ok(img);
                 }
             }
+            return Err("No configured JPEG decoder could decode the file".to_string());
         }
 
         "jxl" => {
@@ -626,7 +949,7 @@ pub fn load_image_fast(path: &Path, bytes: &[u8]) -> Result<image::DynamicImage,
         "tif" | "tiff" => {
             use std::io::Cursor;
 
-            eprintln!(
+            debug_log!(
                 "[DEBUG-TIFF] Attempting image crate wrapper for {:?}",
                 path.file_name().unwrap_or_default()
             );
@@ -648,7 +971,7 @@ pub fn load_image_fast(path: &Path, bytes: &[u8]) -> Result<image::DynamicImage,
                     }
                 }
                 Err(e) => {
-                    eprintln!(
+                    debug_log!(
                         "[DEBUG-TIFF] Wrapper failed: {}. Falling back to native tiff crate...",
                         e
                     );
@@ -668,7 +991,7 @@ pub fn load_image_fast(path: &Path, bytes: &[u8]) -> Result<image::DynamicImage,
 
                             if data.len() == expected_rgb_len {
                                 if is_ycbcr {
-                                    eprintln!(
+                                    debug_log!(
                                         "[DEBUG-TIFF] Converting raw YCbCr bytes to RGB in-place..."
                                     );
                                     for chunk in data.chunks_exact_mut(3) {
@@ -687,19 +1010,19 @@ pub fn load_image_fast(path: &Path, bytes: &[u8]) -> Result<image::DynamicImage,
                                 if let Some(buf) =
                                     image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(w, h, data)
                                 {
-                                    eprintln!("[DEBUG-TIFF] Native bypass SUCCESS: RGB8");
+                                    debug_log!("[DEBUG-TIFF] Native bypass SUCCESS: RGB8");
                                     return Ok(image::DynamicImage::ImageRgb8(buf));
                                 }
                             } else if data.len() == expected_rgba_len
                                 && let Some(buf) =
                                     image::ImageBuffer::<image::Rgba<u8>, _>::from_raw(w, h, data)
                             {
-                                eprintln!("[DEBUG-TIFF] Native bypass SUCCESS: RGBA8");
+                                debug_log!("[DEBUG-TIFF] Native bypass SUCCESS: RGBA8");
                                 return Ok(image::DynamicImage::ImageRgba8(buf));
                             }
                         }
                     } else {
-                        eprintln!("[DEBUG-TIFF] Native tiff decoder also rejected the file.");
+                        debug_log!("[DEBUG-TIFF] Native tiff decoder also rejected the file.");
                     }
                 }
             }
@@ -709,7 +1032,7 @@ pub fn load_image_fast(path: &Path, bytes: &[u8]) -> Result<image::DynamicImage,
     }
 
     // This handles AVIF, WebP, PCX etc (image-extras) and corrupted files.
-    eprintln!("[DEBUG-LOAD] {:?} -> Fallback (image crate)", path.file_name().unwrap_or_default());
+    debug_log!("[DEBUG-LOAD] {:?} -> Fallback (image crate)", path.file_name().unwrap_or_default());
 
     // Chain with_guessed_format(). If it fails (IO error), fallback to a fresh reader.
     let mut reader = image::ImageReader::new(std::io::Cursor::new(bytes))
@@ -718,7 +1041,7 @@ pub fn load_image_fast(path: &Path, bytes: &[u8]) -> Result<image::DynamicImage,
 
     // Fallback to extension if format is still unknown
     if reader.format().is_none()
-        && let Ok(fmt) = image::ImageFormat::from_path(path)
+        && let Some(fmt) = image::ImageFormat::from_extension(ext)
     {
         reader.set_format(fmt);
     }
@@ -729,7 +1052,166 @@ pub fn load_image_fast(path: &Path, bytes: &[u8]) -> Result<image::DynamicImage,
     reader.limits(custom_limits);
 
     // Capture the image crate's format string and map the internal error out
-    reader.decode().map_err(|e| e.to_string())
+    let is_avif = reader.format() == Some(image::ImageFormat::Avif);
+    let img = reader.decode().map_err(|e| e.to_string())?;
+    if is_avif && img.color().has_alpha() && avif_alpha_premultiplied(bytes) {
+        debug_log!(
+            "[DEBUG-LOAD] {:?} -> premultiplied AVIF alpha",
+            path.file_name().unwrap_or_default()
+        );
+        return Ok(unpremultiply_alpha(img));
+    }
+    Ok(img)
+}
+
+/// Payload of the first ISOBMFF box of type `kind` among the boxes in `data`.
+fn find_isobmff_box<'a>(mut data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    while data.len() >= 8 {
+        let (header, size) = match u32::from_be_bytes(data[0..4].try_into().ok()?) {
+            0 => (8, data.len()), // box runs to the end of its parent
+            1 => (16, u64::from_be_bytes(data.get(8..16)?.try_into().ok()?) as usize),
+            size => (8, size as usize),
+        };
+        if size < header || size > data.len() {
+            return None;
+        }
+        if &data[4..8] == kind {
+            return Some(&data[header..size]);
+        }
+        data = &data[size..];
+    }
+    None
+}
+
+/// True when an AVIF stores its colour premultiplied by alpha, signalled by a
+/// 'prem' item reference (meta -> iref). The image crate copies the alpha plane
+/// as stored, which would leave semi-transparent edges dark.
+fn avif_alpha_premultiplied(bytes: &[u8]) -> bool {
+    // meta and iref are full boxes: skip their 4-byte version/flags
+    find_isobmff_box(bytes, b"meta")
+        .and_then(|meta| find_isobmff_box(meta.get(4..)?, b"iref"))
+        .and_then(|iref| find_isobmff_box(iref.get(4..)?, b"prem"))
+        .is_some()
+}
+
+/// Convert premultiplied colour back to straight alpha. Fully transparent pixels
+/// become transparent black instead of whatever the encoder left there.
+fn unpremultiply_alpha(img: DynamicImage) -> DynamicImage {
+    match img {
+        DynamicImage::ImageRgba8(mut buf) => {
+            for p in buf.pixels_mut() {
+                let a = p[3] as u32;
+                for c in &mut p.0[..3] {
+                    *c = if a == 0 { 0 } else { ((*c as u32 * 255 + a / 2) / a).min(255) as u8 };
+                }
+            }
+            DynamicImage::ImageRgba8(buf)
+        }
+        DynamicImage::ImageRgba16(mut buf) => {
+            for p in buf.pixels_mut() {
+                let a = p[3] as u64;
+                for c in &mut p.0[..3] {
+                    *c = if a == 0 {
+                        0
+                    } else {
+                        ((*c as u64 * 65535 + a / 2) / a).min(65535) as u16
+                    };
+                }
+            }
+            DynamicImage::ImageRgba16(buf)
+        }
+        other => other,
+    }
+}
+
+/// Whether the HEIC/HEIF in `bytes` carries a depth map next to its primary image
+/// (e.g. portrait mode shots). Only the container is parsed.
+fn heif_has_depth_map(bytes: &[u8]) -> bool {
+    HeifContext::read_from_bytes(bytes)
+        .ok()
+        .and_then(|ctx| ctx.primary_image_handle().ok())
+        .is_some_and(|handle| handle.has_depth_image())
+}
+
+/// Store whether a HEIC/HEIF has a depth map in its features, for the list's "D".
+fn insert_heif_tags(features: &mut ImageFeatures, path: &Path, bytes: &[u8]) {
+    if is_heif_ext(path) {
+        let depth_map = heif_has_depth_map(bytes);
+        features.insert_tag(TAG_DERIVED_DEPTH_MAP, ExifValue::Short(depth_map as u16));
+    }
+}
+
+/// How a JPEG XL was coded, from its headers (nothing is rendered).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JxlInfo {
+    /// No XYB colour transform, which is how encoders write lossless JXL (lossy
+    /// modular without XYB exists but is rare). The encoder effort is not stored
+    /// in the bitstream, so it can't be shown.
+    pub lossless: bool,
+    /// Displayed frames (keyframes); more than one means animated
+    pub frames: usize,
+}
+
+impl JxlInfo {
+    /// The flags `insert_jxl_tags` stored in a file's features; None when it has none
+    pub fn from_features(features: &ImageFeatures) -> Option<Self> {
+        let lossless = matches!(features.get_tag(TAG_DERIVED_LOSSLESS)?, ExifValue::Short(1));
+        let frames = match features.get_tag(TAG_DERIVED_FRAMES)? {
+            ExifValue::Long(n) => *n as usize,
+            _ => return None,
+        };
+        Some(JxlInfo { lossless, frames })
+    }
+
+    pub fn animated(&self) -> bool {
+        self.frames > 1
+    }
+
+    /// Short list marker: "LL" for lossless, "A" for animated
+    pub fn list_label(&self) -> Option<&'static str> {
+        match (self.lossless, self.animated()) {
+            (true, true) => Some("LL A"),
+            (true, false) => Some("LL"),
+            (false, true) => Some("A"),
+            (false, false) => None,
+        }
+    }
+
+    /// Overlay text, e.g. "lossless, animated (24 frames)"
+    pub fn describe(&self) -> String {
+        let coding = if self.lossless { "lossless" } else { "lossy" };
+        if self.animated() {
+            format!("{}, animated ({} frames)", coding, self.frames)
+        } else {
+            coding.to_string()
+        }
+    }
+}
+
+/// Parse the JPEG XL headers in `bytes`; None when it isn't a readable JXL.
+pub fn jxl_info(bytes: &[u8]) -> Option<JxlInfo> {
+    let image = jxl_oxide::JxlImage::builder().read(std::io::Cursor::new(bytes)).ok()?;
+    let metadata = &image.image_header().metadata;
+    let frames = if metadata.animation.is_some() { image.num_loaded_keyframes() } else { 1 };
+    Some(JxlInfo { lossless: !metadata.xyb_encoded, frames })
+}
+
+/// `jxl_info` for a file with a .jxl extension; None for other formats.
+pub fn jxl_info_from_file(path: &Path) -> Option<JxlInfo> {
+    if !is_jxl_ext(path) {
+        return None;
+    }
+    jxl_info(&fs::read(path).ok()?)
+}
+
+/// Store the lossless flag and frame count of a JPEG XL in its features.
+fn insert_jxl_tags(features: &mut ImageFeatures, path: &Path, bytes: &[u8]) {
+    if is_jxl_ext(path)
+        && let Some(info) = jxl_info(bytes)
+    {
+        features.insert_tag(TAG_DERIVED_LOSSLESS, ExifValue::Short(info.lossless as u16));
+        features.insert_tag(TAG_DERIVED_FRAMES, ExifValue::Long(info.frames as u32));
+    }
 }
 
 /// Derive country name from GPS coordinates using country-boundaries
@@ -873,6 +1355,7 @@ pub fn get_supported_exif_tags() -> Vec<(&'static str, &'static str)> {
         ("GPSAltitude", "GPS altitude"),
         // Derived values (computed from other EXIF data)
         ("DerivedCountry", "Country name derived from GPS coordinates"),
+        ("DerivedLens", "LensModel normalized so spellings of the same lens match"),
         ("DerivedSunPosition", "Sun Altitude and Azimuth calculated from time & location"),
     ]
 }
@@ -1021,7 +1504,30 @@ fn clean_exif_string(s: &str) -> String {
     cleaned.to_string()
 }
 
-fn get_resolution(path: &Path, bytes: Option<&[u8]>) -> Option<(u32, u32)> {
+pub(crate) fn get_resolution(path: &Path, bytes: Option<&[u8]>) -> Option<(u32, u32)> {
+    let ext =
+        path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
+    if let Some(dims) = get_resolution_by_ext(path, bytes, &ext) {
+        return Some(dims);
+    }
+    if is_raw_ext(path) {
+        return None;
+    }
+
+    // Extension-based probing failed: check whether the content is another format
+    let data_cow;
+    let data_slice = match bytes {
+        Some(b) => b,
+        None => {
+            data_cow = fs::read(path).ok()?;
+            &data_cow
+        }
+    };
+    let real = sniff_mismatched_format(path, data_slice, &ext)?;
+    get_resolution_by_ext(path, Some(data_slice), real)
+}
+
+fn get_resolution_by_ext(path: &Path, bytes: Option<&[u8]>, ext: &str) -> Option<(u32, u32)> {
     // 1. Handle RAW images
     if is_raw_ext(path) {
         let data_cow;
@@ -1033,43 +1539,55 @@ fn get_resolution(path: &Path, bytes: Option<&[u8]>) -> Option<(u32, u32)> {
             }
         };
 
-        if let Ok(raw) = rsraw::RawImage::open(data_slice) {
-            return Some((raw.width(), raw.height()));
+        match rsraw::RawImage::open(data_slice) {
+            Ok(raw) => return Some((raw.width(), raw.height())),
+            Err(e) => {
+                // Last resort: dimensions of the embedded JPEG preview.
+                let jpeg = crate::raw_exif::extract_embedded_jpeg(data_slice)?;
+                let dims = image::ImageReader::new(std::io::Cursor::new(jpeg))
+                    .with_guessed_format()
+                    .ok()?
+                    .into_dimensions()
+                    .ok()?;
+                eprintln!(
+                    "[RAW-FALLBACK] {}: rsraw failed ({}), using embedded JPEG size {}x{}",
+                    path.display(),
+                    e,
+                    dims.0,
+                    dims.1
+                );
+                return Some(dims);
+            }
         }
-        return None;
     }
 
     // 2. Handle HEIC/HEIF specifically
-    if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-        let ext = ext.to_lowercase();
-        if ext == "heic" || ext == "heif" {
-            let ctx = match bytes {
-                Some(b) => HeifContext::read_from_bytes(b).ok()?,
-                None => HeifContext::read_from_file(path.to_str()?).ok()?,
-            };
+    if ext == "heic" || ext == "heif" {
+        let ctx = match bytes {
+            Some(b) => HeifContext::read_from_bytes(b).ok()?,
+            None => HeifContext::read_from_file(path.to_str()?).ok()?,
+        };
 
-            if let Ok(handle) = ctx.primary_image_handle() {
-                return Some((handle.width(), handle.height()));
-            }
+        if let Ok(handle) = ctx.primary_image_handle() {
+            return Some((handle.width(), handle.height()));
         }
+    }
 
-        // 2.5 Handle TIFF specifically to bypass ImageReader color space limits
-        if ext == "tif" || ext == "tiff" {
-            let data_cow;
-            let data_slice = match bytes {
-                Some(b) => b,
-                None => {
-                    data_cow = fs::read(path).ok()?;
-                    &data_cow
-                }
-            };
-
-            if let Ok(decoder) =
-                image::codecs::tiff::TiffDecoder::new(std::io::Cursor::new(data_slice))
-            {
-                use image::ImageDecoder;
-                return Some(decoder.dimensions());
+    // 2.5 Handle TIFF specifically to bypass ImageReader color space limits
+    if ext == "tif" || ext == "tiff" {
+        let data_cow;
+        let data_slice = match bytes {
+            Some(b) => b,
+            None => {
+                data_cow = fs::read(path).ok()?;
+                &data_cow
             }
+        };
+
+        if let Ok(decoder) = image::codecs::tiff::TiffDecoder::new(std::io::Cursor::new(data_slice))
+        {
+            use image::ImageDecoder;
+            return Some(decoder.dimensions());
         }
     }
 
@@ -1093,6 +1611,14 @@ fn get_resolution(path: &Path, bytes: Option<&[u8]>) -> Option<(u32, u32)> {
     None
 }
 
+/// Blake3 of the image converted to 16-bit RGBA (content-identical check).
+/// This ensures 16-bit PNGs != 8-bit PNGs unless the extra bits are purely padding.
+pub(crate) fn compute_pixel_hash(img: &DynamicImage) -> [u8; 32] {
+    let rgba16 = img.to_rgba16();
+    let raw_bytes: &[u8] = cast_slice(rgba16.as_raw());
+    *blake3::hash(raw_bytes).as_bytes()
+}
+
 #[derive(Clone)]
 pub struct ScanConfig {
     pub paths: Vec<String>,
@@ -1103,78 +1629,389 @@ pub struct ScanConfig {
     #[allow(unused)]
     pub ignore_same_stem: bool,
     pub calc_pixel_hash: bool,
+    pub calc_oriented_pixel_hash: bool,
+    /// Trust cached PDQ hash + coefficients for unchanged files and skip opening them.
+    /// When off, files are re-decoded (features refreshed) but the content hash is still reused.
+    pub fast_resume: bool,
+    /// Group by file name instead of perceptual hash (skips image decoding)
+    pub name_dupes: Option<NameMatch>,
+    /// Only link files whose long and short edges each differ by at most this fraction
+    /// (0.05 = 5%).
+    /// Keeps thumbnails apart from their originals. Unknown resolutions are not restricted.
+    pub resolution_tolerance: Option<f32>,
+    /// Live photo companion extensions; companions are folded into their primary image
+    pub live_photo_exts: Vec<String>,
+    /// Directory levels to descend below each root path; 0 = the root's own files only
+    pub max_depth: Option<usize>,
+    /// Name-dedup mode: bucket by size + hash of this many leading bytes first and read
+    /// the whole file only when another file could still be identical
+    pub prefix_hash_bytes: Option<usize>,
+    /// Largest downsampled MSE still counted as near-identical; None skips the check
+    pub near_identical_mse: Option<f32>,
+    /// List a file under overlapping scan roots once. When off it is listed once per
+    /// root; grouping still sees it as one file (see `collapse_root_aliases`).
+    pub dedupe_scan_roots: bool,
+    /// Lowercase extension -> similarity threshold, overriding `similarity` for files
+    /// of that type (see `similarity_for`)
+    pub similarity_by_ext: HashMap<String, u32>,
+    /// Split groups whose members are further apart than this (pairwise) into tighter
+    /// subclusters; None keeps plain transitive grouping
+    pub cluster_split_dist: Option<u32>,
+    /// Whether screenshots are grouped with other files, left out, or kept apart
+    pub screenshots: ScreenshotGrouping,
+    /// Write freedesktop.org thumbnails for every file decoded during the scan
+    pub os_thumbnails: bool,
+    /// Also scan the images inside .zip archives (see `crate::archive`)
+    pub scan_archives: bool,
+    /// Read the embedded ICC profile of every decoded file, cached in its features
+    pub icc_profiles: bool,
+    /// Largest downsampled luminance MSE between a grayscale file and a color one still
+    /// linked as its black-and-white version; None skips the check (see
+    /// `mark_luma_variants`)
+    pub luma_variant_mse: Option<f32>,
 }
 
-#[derive(Clone)]
-struct ScannedFile {
-    pub path: std::path::PathBuf,
-    pub size: u64,
-    pub modified: DateTime<Utc>,
-    pub resolution: Option<(u32, u32)>,
-    pub content_hash: [u8; 32],
-    pub orientation: u8,
-    pub gps_pos: Option<Point<f64>>,
-    pub unique_file_id: u128,
-    pub pdqhash: Option<[u8; 32]>,
-    pub pdq_features: Option<Arc<crate::pdqhash::PdqFeatures>>,
-    pub pixel_hash: Option<[u8; 32]>,
-    pub exif_timestamp: Option<i64>,
-}
+impl ScanConfig {
+    /// Similarity threshold for one file: its extension's override, else the global one.
+    /// An edge between two files uses the stricter (lower) of their two thresholds, so
+    /// a loose JPEG threshold never links a PNG more loosely than the PNG allows.
+    pub fn similarity_for(&self, path: &Path) -> u32 {
+        path.extension()
+            .and_then(|e| self.similarity_by_ext.get(&e.to_string_lossy().to_lowercase()))
+            .copied()
+            .unwrap_or(self.similarity)
+    }
 
-impl ScannedFile {
-    fn to_file_metadata(&self) -> FileMetadata {
-        FileMetadata {
-            path: self.path.clone(),
-            size: self.size,
-            modified: self.modified,
-            pdqhash: self.pdqhash,
-            resolution: self.resolution,
-            content_hash: self.content_hash,
-            orientation: self.orientation,
-            gps_pos: self.gps_pos,
-            unique_file_id: self.unique_file_id,
-            pixel_hash: self.pixel_hash,
-            exif_timestamp: self.exif_timestamp,
-        }
+    /// Largest threshold any file can get; the hash index is searched this far.
+    fn max_similarity(&self) -> u32 {
+        self.similarity_by_ext.values().copied().fold(self.similarity, u32::max)
     }
 }
 
-pub fn scan_and_group(
+/// Exact screen sizes (long edge, short edge) of common monitors and phones
+const SCREEN_SIZES: &[(u32, u32)] = &[
+    (1280, 720),
+    (1280, 800),
+    (1280, 1024),
+    (1366, 768),
+    (1440, 900),
+    (1536, 864),
+    (1600, 900),
+    (1680, 1050),
+    (1920, 1080),
+    (1920, 1200),
+    (2560, 1080),
+    (2560, 1440),
+    (2560, 1600),
+    (2880, 1800),
+    (3024, 1964),
+    (3440, 1440),
+    (3456, 2234),
+    (3840, 2160),
+    (5120, 2880),
+    (1334, 750),
+    (1792, 828),
+    (2340, 1080),
+    (2400, 1080),
+    (2436, 1125),
+    (2532, 1170),
+    (2556, 1179),
+    (2688, 1242),
+    (2778, 1284),
+    (2796, 1290),
+    (3088, 1440),
+    (3200, 1440),
+];
+
+/// Software tag fragments written by screenshot tools
+const SCREENSHOT_SOFTWARE: &[&str] = &[
+    "screenshot",
+    "screen shot",
+    "snipping",
+    "spectacle",
+    "flameshot",
+    "sharex",
+    "greenshot",
+    "ksnip",
+    "scrot",
+    "grim",
+];
+
+/// Screenshot heuristic over a file's metadata. A screenshot tool named in the
+/// `Software` tag decides on its own; otherwise a file without camera Make/Model
+/// counts when it exactly matches a common screen size. The format alone says
+/// nothing: exported and edited photos are PNGs without camera tags too.
+pub fn is_screenshot(
+    resolution: Option<(u32, u32)>,
+    software: Option<&str>,
+    has_camera: bool,
+) -> bool {
+    if let Some(sw) = software {
+        let sw = sw.to_lowercase();
+        if SCREENSHOT_SOFTWARE.iter().any(|s| sw.contains(s)) {
+            return true;
+        }
+    }
+    if has_camera {
+        return false;
+    }
+    resolution.is_some_and(|(w, h)| SCREEN_SIZES.contains(&(w.max(h), w.min(h))))
+}
+
+/// `is_screenshot` for a file whose cached EXIF features are at hand.
+pub fn is_screenshot_features(features: &ImageFeatures) -> bool {
+    use crate::exif_types::{TAG_MAKE, TAG_MODEL, TAG_SOFTWARE};
+    is_screenshot(
+        features.resolution(),
+        features.get_tag_string(TAG_SOFTWARE).as_deref(),
+        features.has_tag(TAG_MAKE) || features.has_tag(TAG_MODEL),
+    )
+}
+
+/// How file names are compared in name-dedup mode (--name-dupes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameMatch {
+    /// Byte-for-byte identical file names
+    Exact,
+    /// Case-insensitive, with leading zeros in digit runs ignored (IMG_0001 == img_1)
+    Natural,
+}
+
+impl NameMatch {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "exact" => Some(NameMatch::Exact),
+            "natural" => Some(NameMatch::Natural),
+            _ => None,
+        }
+    }
+
+    fn key(self, path: &Path) -> String {
+        let name = path.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        match self {
+            NameMatch::Exact => name,
+            NameMatch::Natural => normalize_name_natural(&name),
+        }
+    }
+}
+
+/// Lowercase the name and drop leading zeros from every run of digits,
+/// so "IMG_0001.JPG" and "img_1.jpg" compare equal.
+fn normalize_name_natural(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let mut digits = String::from(c);
+            while let Some(&d) = chars.peek()
+                && d.is_ascii_digit()
+            {
+                digits.push(d);
+                chars.next();
+            }
+            let trimmed = digits.trim_start_matches('0');
+            out.push_str(if trimmed.is_empty() { "0" } else { trimmed });
+        } else {
+            out.extend(c.to_lowercase());
+        }
+    }
+    out
+}
+
+#[derive(Clone)]
+struct ScannedFile {
+    pub path: std::path::PathBuf,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+    pub resolution: Option<(u32, u32)>,
+    pub content_hash: [u8; 32],
+    pub orientation: u8,
+    pub gps_pos: Option<Point<f64>>,
+    pub unique_file_id: u128,
+    pub pdqhash: Option<[u8; 32]>,
+    pub pdq_features: Option<Arc<crate::pdqhash::PdqFeatures>>,
+    pub whash: Option<u64>, // Only with HashAlgorithm::WHash
+    pub pixel_hash: Option<[u8; 32]>,
+    pub oriented_pixel_hash: Option<[u8; 32]>,
+    pub icc_profile: Option<IccProfile>,
+    pub exif_timestamp: Option<i64>,
+    pub exif_subsec_nanos: Option<u32>,
+    pub exif_tz_offset: Option<i32>,
+    pub screenshot: bool,
+}
+
+impl ScannedFile {
+    fn to_file_metadata(&self) -> FileMetadata {
+        FileMetadata {
+            path: self.path.clone(),
+            size: self.size,
+            modified: self.modified,
+            pdqhash: self.pdqhash,
+            resolution: self.resolution,
+            content_hash: self.content_hash,
+            orientation: self.orientation,
+            gps_pos: self.gps_pos,
+            unique_file_id: self.unique_file_id,
+            pixel_hash: self.pixel_hash,
+            oriented_pixel_hash: self.oriented_pixel_hash,
+            near_identical: None,
+            luma_variant: None,
+            icc_profile: self.icc_profile.clone(),
+            exif_timestamp: self.exif_timestamp,
+            exif_subsec_nanos: self.exif_subsec_nanos,
+            exif_tz_offset: self.exif_tz_offset,
+        }
+    }
+}
+
+/// How long the preview grouper collects hashed files before sending the groups
+/// they formed or grew
+const PREVIEW_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Feeds hashed files to the preview grouper thread (see `stream_preview_groups`).
+enum PreviewFeed {
+    Pdq(Sender<([u8; 32], FileMetadata)>),
+    WHash(Sender<(u64, FileMetadata)>),
+}
+
+impl PreviewFeed {
+    fn send(&self, file: &ScannedFile) {
+        match self {
+            PreviewFeed::Pdq(tx) => {
+                if let Some(hash) = file.pdqhash {
+                    let _ = tx.send((hash, file.to_file_metadata()));
+                }
+            }
+            PreviewFeed::WHash(tx) => {
+                if let Some(hash) = file.whash {
+                    let _ = tx.send((hash, file.to_file_metadata()));
+                }
+            }
+        }
+    }
+}
+
+/// Preliminary grouping while the scan runs: every hashed file goes into a growable
+/// index and is joined with the files already there within the global `similarity`
+/// threshold. Each batch sends the groups that formed or grew, whole, on
+/// `preview_tx`; a receiver drops earlier groups sharing a file with a new one.
+/// Per-extension thresholds, resolution tolerance, cluster splitting and the other
+/// refinements only apply to the final result, which replaces the preview.
+fn stream_preview_groups<H: HammingHash>(
+    rx: Receiver<(H, FileMetadata)>,
+    preview_tx: Sender<Vec<FileMetadata>>,
+    max_dist: u32,
+) {
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while root != parent[root] {
+            root = parent[root];
+        }
+        let mut curr = i;
+        while curr != root {
+            let next = parent[curr];
+            parent[curr] = root;
+            curr = next;
+        }
+        root
+    }
+
+    let mut index = GrowableMIH::new();
+    let mut files: Vec<FileMetadata> = Vec::new();
+    let mut parent: Vec<usize> = Vec::new();
+    // Members of every group of two or more files, by root
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+
+    while let Ok(first) = rx.recv() {
+        let deadline = std::time::Instant::now() + PREVIEW_INTERVAL;
+        let mut batch = vec![first];
+        while let Ok(item) = rx.recv_deadline(deadline) {
+            batch.push(item);
+        }
+
+        let mut touched = HashSet::new();
+        for (hash, file) in batch {
+            let neighbors = index.neighbors(&hash, max_dist);
+            let id = index.insert(hash).index();
+            files.push(file);
+            parent.push(id);
+            for n in neighbors {
+                let (a, b) = (find(&mut parent, id), find(&mut parent, n.index()));
+                if a == b {
+                    continue;
+                }
+                // The larger group absorbs the smaller one
+                let mut group_a = members.remove(&a).unwrap_or_else(|| vec![a]);
+                let mut group_b = members.remove(&b).unwrap_or_else(|| vec![b]);
+                let (root, child) = if group_a.len() >= group_b.len() { (a, b) } else { (b, a) };
+                parent[child] = root;
+                group_a.append(&mut group_b);
+                members.insert(root, group_a);
+                touched.insert(root);
+            }
+        }
+
+        let roots: HashSet<usize> = touched.into_iter().map(|r| find(&mut parent, r)).collect();
+        for root in roots {
+            let mut group: Vec<FileMetadata> =
+                members[&root].iter().map(|&i| files[i].clone()).collect();
+            FileMetadata::unify_pixel_hashes(&mut group);
+            if preview_tx.send(group).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Hash all files under `config.paths` and group them. With `preview_tx`, groups
+/// found so far are streamed while the files are still being hashed (see
+/// `stream_preview_groups`); the returned groups are the final word.
+pub fn scan_and_group(
     config: &ScanConfig,
     ctx: &AppContext,
     progress_tx: Option<Sender<(usize, usize)>>,
+    preview_tx: Option<Sender<Vec<FileMetadata>>>,
+) -> (Vec<Vec<FileMetadata>>, Vec<GroupInfo>) {
+    scan_and_group_counted(config, ctx, progress_tx, preview_tx, &AtomicUsize::new(0))
+}
+
+/// `scan_and_group`, adding the number of files it had to open and hash to `read_count`.
+fn scan_and_group_counted(
+    config: &ScanConfig,
+    ctx: &AppContext,
+    progress_tx: Option<Sender<(usize, usize)>>,
+    preview_tx: Option<Sender<Vec<FileMetadata>>>,
+    read_count: &AtomicUsize,
 ) -> (Vec<Vec<FileMetadata>>, Vec<GroupInfo>) {
     use std::time::Instant;
 
     let ctx_ref = ctx;
     let force_rehash = config.rehash;
+    let use_whash = ctx.algorithm == HashAlgorithm::WHash;
 
-    let mut all_files = Vec::new();
-    let mut seen_paths = HashSet::new();
-    for path_str in &config.paths {
-        let path = Path::new(path_str);
-        if path.is_dir() {
-            for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-                if is_image_ext(entry.path())
-                    && let Ok(canonical) = entry.path().canonicalize()
-                    && seen_paths.insert(canonical.clone())
-                {
-                    all_files.push(canonical);
-                }
-            }
-        } else if path.is_file()
-            && is_image_ext(path)
-            && let Ok(canonical) = path.canonicalize()
-            && seen_paths.insert(canonical.clone())
-        {
-            all_files.push(canonical);
-        }
+    let mut all_files = collect_image_files(
+        &config.paths,
+        &config.live_photo_exts,
+        config.max_depth,
+        config.dedupe_scan_roots,
+    );
+    let mut root_aliases = HashMap::new();
+    if !config.dedupe_scan_roots {
+        (all_files, root_aliases) = collapse_root_aliases(all_files);
+    }
+    if config.scan_archives {
+        all_files.extend(collect_archive_entries(&config.paths, config.max_depth));
     }
-
     if all_files.is_empty() {
         return (Vec::new(), Vec::new());
     }
 
+    if let Some(mode) = config.name_dupes {
+        let (mut groups, infos) = group_by_file_name(all_files, mode, config, ctx, progress_tx);
+        expand_root_aliases(&mut groups, &root_aliases);
+        return (groups, infos);
+    }
+
     let total_files = all_files.len();
     if let Some(tx) = &progress_tx {
         let _ = tx.send((0, total_files));
@@ -1192,7 +2029,28 @@ pub fn scan_and_group(
     let hash_start = Instant::now();
     let (tx, rx) = unbounded();
     let db_handle = ctx.start_db_writer(rx);
+    let (preview, preview_handle) = match preview_tx {
+        Some(preview_tx) if use_whash => {
+            let (feed, rx) = unbounded();
+            let max_dist = config.similarity;
+            let handle =
+                std::thread::spawn(move || stream_preview_groups(rx, preview_tx, max_dist));
+            (Some(PreviewFeed::WHash(feed)), Some(handle))
+        }
+        Some(preview_tx) => {
+            let (feed, rx) = unbounded();
+            let max_dist = config.similarity;
+            let handle =
+                std::thread::spawn(move || stream_preview_groups(rx, preview_tx, max_dist));
+            (Some(PreviewFeed::Pdq(feed)), Some(handle))
+        }
+        None => (None, None),
+    };
+    // Left out of grouping, so not previewed either
+    let preview_screenshots = config.screenshots != ScreenshotGrouping::Exclude;
     let processed_count = AtomicUsize::new(0);
+    let cache_full_count = AtomicUsize::new(0);
+    let cache_partial_count = AtomicUsize::new(0);
 
     // 3. Run the heavy parsing inside the constrained pool
     let mut valid_files: Vec<ScannedFile> = pool.install(|| {
@@ -1206,17 +2064,21 @@ pub fn scan_and_group(
                     }
                 }
 
-                let metadata = fs::metadata(path).ok()?;
-                let size = metadata.len();
-                let mtime = metadata.modified().ok().unwrap_or(UNIX_EPOCH);
+                let in_archive = config.scan_archives && crate::archive::is_archive_entry(path);
+                let (size, mtime, unique_file_id) = if in_archive {
+                    crate::archive::entry_stat(path)?
+                } else {
+                    let metadata = fs::metadata(path).ok()?;
+                    let mtime = metadata.modified().ok().unwrap_or(UNIX_EPOCH);
+                    (metadata.len(), mtime, fileops::get_file_key(path)?)
+                };
                 let mtime_utc: DateTime<Utc> = DateTime::from(mtime);
                 let mtime_ns =
                     mtime.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64;
-                let unique_file_id = fileops::get_file_key(path)?;
 
                 let meta_key = compute_meta_key(&ctx_ref.meta_key, mtime_ns, size, unique_file_id);
                 if false {
-                    eprintln!(
+                    debug_log!(
                         "[DEBUG] mtime_ns/size/unique_file_id {} = {} {} {}",
                         path.display(),
                         mtime_ns,
@@ -1226,6 +2088,7 @@ pub fn scan_and_group(
                 }
                 let mut pdqhash: Option<[u8; 32]> = None;
                 let mut pdq_features: Option<Arc<crate::pdqhash::PdqFeatures>> = None;
+                let mut whash: Option<u64> = None;
                 // IMPORTANT: new_meta tracks updates to the file_metadata DB.
                 // Even if we hit the cache, we MUST set this to refresh the timestamp.
                 let mut new_meta = None;
@@ -1238,9 +2101,21 @@ pub fn scan_and_group(
                 let mut orientation = 1;
                 let mut gps_pos = None;
                 let mut exif_timestamp: Option<i64> = None;
+                let mut exif_subsec_nanos: Option<u32> = None;
+                let mut exif_tz_offset: Option<i32> = None;
+                let mut screenshot = false;
                 let mut cache_hit_full = false;
                 let mut pixel_hash: Option<[u8; 32]> = None; // Init
                 let mut new_pixel = None; // For DB update
+                let mut oriented_pixel_hash: Option<[u8; 32]> = None;
+                let mut new_oriented_pixel = None;
+                let mut icc_profile: Option<IccProfile> = None;
+                // RAW pixels come from the embedded preview, so the profile says nothing
+                let mut icc_read = !config.icc_profiles || is_raw_ext(path);
+                let mut new_icc = false;
+                // Features from the cache, updated when only the ICC profile is new
+                let mut cached_features = None;
+                let mut decode_skipped = None;
 
                 let mut metadata_hit = false;
                 if !force_rehash && let Ok(Some(ch)) = ctx_ref.get_content_hash(&meta_key) {
@@ -1248,25 +2123,48 @@ pub fn scan_and_group(
                     ck = ch;
                     // Refresh timestamp
                     new_meta = Some((meta_key, ck));
-                    if let Ok(Some(h)) = ctx_ref.get_pdqhash(&ch) {
-                        pdqhash = Some(h);
-                        if let Ok(Some(feats)) = ctx_ref.get_features(&ch) {
-                            resolution = Some((feats.width, feats.height));
-                            orientation = feats.orientation();
-                            gps_pos = feats.gps_pos();
-
-                            // Get coefficients from separate db
-                            if let Ok(Some(coeff_vec)) = ctx_ref.get_coefficients(&ch)
-                                && coeff_vec.len() == 256
-                            {
-                                let mut coeffs = [0.0; 256];
-                                coeffs.copy_from_slice(&coeff_vec);
-                                pdq_features = Some(Arc::new(crate::pdqhash::PdqFeatures {
-                                    coefficients: coeffs,
-                                }));
-                                cache_hit_full = true;
-                            }
+                    // In whash mode hash_db holds the wavelet hash instead of PDQ
+                    if config.fast_resume {
+                        if use_whash {
+                            whash = ctx_ref.get_whash(&ch).ok().flatten();
+                        } else {
+                            pdqhash = ctx_ref.get_pdqhash(&ch).ok().flatten();
+                        }
+                    }
+                    if (pdqhash.is_some() || whash.is_some())
+                        && let Ok(Some(feats)) = ctx_ref.get_features(&ch)
+                    {
+                        resolution = Some((feats.width, feats.height));
+                        orientation = feats.orientation();
+                        gps_pos = feats.gps_pos();
+                        exif_timestamp = feats.exif_timestamp();
+                        exif_subsec_nanos = feats.exif_subsec_nanos();
+                        exif_tz_offset = feats.exif_tz_offset();
+                        screenshot = is_screenshot_features(&feats);
+                        if config.icc_profiles
+                            && let Some(profile) = IccProfile::from_features(&feats)
+                        {
+                            icc_profile = profile;
+                            icc_read = true;
+                        }
+
+                        // Get coefficients from separate db (whash needs none)
+                        if use_whash {
+                            cache_hit_full = true;
+                        } else if let Ok(Some(coeff_vec)) = ctx_ref.get_coefficients(&ch)
+                            && coeff_vec.len() == 256
+                        {
+                            let mut coeffs = [0.0; 256];
+                            coeffs.copy_from_slice(&coeff_vec);
+                            pdq_features = Some(Arc::new(crate::pdqhash::PdqFeatures {
+                                coefficients: coeffs,
+                            }));
+                            cache_hit_full = true;
                         }
+                        cached_features = Some(feats);
+                    }
+                    if !icc_read {
+                        cache_hit_full = false;
                     }
                     // If user wants pixel hash, try to fetch it from DB.
                     if config.calc_pixel_hash {
@@ -1277,21 +2175,47 @@ pub fn scan_and_group(
                             cache_hit_full = false;
                         }
                     }
+                    if config.calc_oriented_pixel_hash {
+                        if let Ok(Some(oph)) = ctx_ref.get_oriented_pixel_hash(&ch) {
+                            oriented_pixel_hash = Some(oph);
+                        } else if cache_hit_full
+                            && orientation == 1
+                            && let Some(ph) = pixel_hash
+                        {
+                            // Upright image: the oriented hash equals the plain one,
+                            // no need to decode just to store it.
+                            oriented_pixel_hash = Some(ph);
+                            new_oriented_pixel = Some((ch, ph));
+                        } else {
+                            cache_hit_full = false;
+                        }
+                    }
                     if cache_hit_full {
-                        eprintln!("[CACHE-FULL] {:?}", path.display());
+                        cache_full_count.fetch_add(1, Ordering::Relaxed);
+                        debug_log!("[CACHE-FULL] {:?}", path.display());
                     } else {
-                        eprintln!(
+                        cache_partial_count.fetch_add(1, Ordering::Relaxed);
+                        debug_log!(
                             "[CACHE-PARTIAL] Metadata found, but features missing for {:?}",
                             path.display()
                         );
                     }
                 }
 
+                // The only place a file's bytes are read: a full cache hit is rebuilt
+                // from the DB above and never opens the file.
                 if !cache_hit_full {
                     if !metadata_hit {
-                        eprintln!("[CACHE-MISS] New file: {:?}", path.display());
+                        debug_log!("[CACHE-MISS] New file: {:?}", path.display());
+                    }
+                    let bytes = if in_archive {
+                        crate::archive::read(path).ok()
+                    } else {
+                        fs::read(path).ok()
+                    };
+                    if bytes.is_some() {
+                        read_count.fetch_add(1, Ordering::Relaxed);
                     }
-                    let bytes = fs::read(path).ok();
 
                     if let Some(ref b) = bytes {
                         // 1. PRE-PARSE rsraw if it's a RAW file to avoid doing it multiple times
@@ -1319,6 +2243,7 @@ pub fn scan_and_group(
                                 orientation = 1;
                             }
                             exif_timestamp = get_exif_timestamp(exif);
+                            (exif_subsec_nanos, exif_tz_offset) = get_exif_time_refinement(exif);
                         } else if is_raw {
                             // kamadak-exif failed on RAW file - try rsraw as fallback
                             if let Some(ref raw) = parsed_raw {
@@ -1347,7 +2272,23 @@ pub fn scan_and_group(
                         if is_raw {
                             // RAW FILE: Extract Largest JPEG Thumbnail
                             // We need the image for PDQ even if pixel_hash is disabled.
-                            if let Some(mut raw) = parsed_raw
+                            if parsed_raw.is_none()
+                                && let Some(jpeg) = raw_exif::extract_embedded_jpeg(b)
+                            {
+                                // rsraw can't open it (e.g. compressed RAF): hash the
+                                // container's own JPEG preview instead.
+                                img_for_hashing =
+                                    load_image_fast(Path::new("raw_preview.jpg"), jpeg).ok();
+                                if let Some(img) = &img_for_hashing {
+                                    eprintln!(
+                                        "[RAW-FALLBACK] {}: rsraw failed, hashing embedded JPEG preview",
+                                        path.display()
+                                    );
+                                    if resolution.is_none() {
+                                        resolution = Some(img.dimensions());
+                                    }
+                                }
+                            } else if let Some(mut raw) = parsed_raw
                                 && let Ok(thumbs) = raw.extract_thumbs()
                             {
                                 // Find largest JPEG thumbnail
@@ -1373,8 +2314,23 @@ pub fn scan_and_group(
                                 resolution = get_resolution(path, Some(b));
                             }
                         } else {
-                            // STANDARD IMAGE: Use fast loader directly
-                            img_for_hashing = load_image_fast(path, b).ok();
+                            // STANDARD IMAGE: the header is read once, for the size
+                            // check and as the resolution of a skipped file
+                            let header = get_resolution(path, Some(b));
+                            if let Some(reason) = header.and_then(|(w, h)| decode_size_error(w, h))
+                            {
+                                // Too large to decode safely: the file is left ungrouped
+                                eprintln!("[DECODE-LIMIT] {}: {}", path.display(), reason);
+                                resolution = resolution.or(header);
+                                decode_skipped = Some(reason);
+                            } else {
+                                let ext = path
+                                    .extension()
+                                    .and_then(|e| e.to_str())
+                                    .map(|e| e.to_lowercase())
+                                    .unwrap_or_default();
+                                img_for_hashing = decode_image(path, b, &ext).ok();
+                            }
                         }
 
                         if let Some(img) = &img_for_hashing {
@@ -1385,12 +2341,8 @@ pub fn scan_and_group(
 
                             // 4. Calculate Pixel Hash of 16bit RGBA (Content Identical Check)
                             if config.calc_pixel_hash && pixel_hash.is_none() {
-                                // This ensures 16-bit PNGs != 8-bit PNGs unless the extra bits are purely padding.
-                                let rgba16 = img.to_rgba16();
-                                let raw_u16 = rgba16.as_raw();
-                                let raw_bytes: &[u8] = cast_slice(raw_u16);
-                                let ph = *blake3::hash(raw_bytes).as_bytes();
-                                eprintln!(
+                                let ph = compute_pixel_hash(img);
+                                debug_log!(
                                     "[DEBUG-PIXEL_HASH 16BIT] {:?} : {}",
                                     path.file_name().unwrap_or_default(),
                                     hex::encode(ph)
@@ -1399,8 +2351,60 @@ pub fn scan_and_group(
                                 new_pixel = Some((ck, ph));
                             }
 
-                            // Use 'img' directly - do NOT call load_from_memory again
-                            if let Some((features, _)) = crate::pdqhash::generate_pdq_features(img)
+                            // 4b. Same hash after applying EXIF orientation, so a copy with
+                            // baked rotation matches an orientation-tagged original.
+                            if config.calc_oriented_pixel_hash && oriented_pixel_hash.is_none() {
+                                let oph = if orientation == 1 {
+                                    // Nothing to apply, so reuse the plain pixel hash.
+                                    pixel_hash.unwrap_or_else(|| compute_pixel_hash(img))
+                                } else {
+                                    let mut oriented = img.clone();
+                                    if let Some(o) = Orientation::from_exif(orientation) {
+                                        oriented.apply_orientation(o);
+                                    }
+                                    compute_pixel_hash(&oriented)
+                                };
+                                debug_log!(
+                                    "[DEBUG-PIXEL_HASH ORIENTED] {:?} : {}",
+                                    path.file_name().unwrap_or_default(),
+                                    hex::encode(oph)
+                                );
+                                oriented_pixel_hash = Some(oph);
+                                new_oriented_pixel = Some((ck, oph));
+                            }
+
+                            // 4c. Embedded ICC profile, so files with the same pixels but
+                            // another color profile are told apart
+                            if !icc_read {
+                                icc_profile = crate::hdr::extract_icc_profile(b)
+                                    .map(|icc| IccProfile::from_bytes(&icc));
+                                icc_read = true;
+                                new_icc = true;
+                            }
+
+                            // 4d. Freedesktop thumbnails from the decode we already have
+                            if config.os_thumbnails && !in_archive {
+                                crate::thumbnails::write_freedesktop(
+                                    path,
+                                    img,
+                                    orientation,
+                                    mtime_utc.timestamp(),
+                                    size,
+                                );
+                            }
+
+                            if use_whash && whash.is_none() {
+                                let wh = crate::whash::compute_whash(img);
+                                whash = Some(wh);
+                                new_hash = Some((ck, HashValue::WHash(wh)));
+                            }
+
+                            // Use 'img' directly - do NOT call load_from_memory again.
+                            // Skipped when PDQ came from the cache and we only decoded
+                            // to fill in a missing pixel hash.
+                            if pdq_features.is_none()
+                                && let Some((features, _)) =
+                                    crate::pdqhash::generate_pdq_features(img)
                             {
                                 let hash = features.to_hash();
                                 pdqhash = Some(hash);
@@ -1420,6 +2424,8 @@ pub fn scan_and_group(
                                     } else {
                                         ImageFeatures::new(w, h)
                                     };
+                                insert_jxl_tags(&mut img_features, path, b);
+                                insert_heif_tags(&mut img_features, path, b);
 
                                 // Persist orientation. build_image_features copies the raw
                                 // EXIF Orientation tag, so for formats whose decoder bakes
@@ -1447,14 +2453,25 @@ pub fn scan_and_group(
                                     img_features
                                         .insert_tag(TAG_DERIVED_TIMESTAMP, ExifValue::Long64(ts));
                                 }
+                                if let Some(nanos) = exif_subsec_nanos {
+                                    img_features.insert_tag(
+                                        TAG_DERIVED_SUBSEC_NANOS,
+                                        ExifValue::Long(nanos),
+                                    );
+                                }
+                                if let Some(off) = exif_tz_offset {
+                                    img_features
+                                        .insert_tag(TAG_DERIVED_TZ_OFFSET, ExifValue::Signed(off));
+                                }
 
                                 let cached_coeffs = CachedCoefficients {
                                     coefficients: features.coefficients.to_vec(),
                                 };
 
-                                if new_hash.is_none() {
+                                if new_hash.is_none() && !use_whash {
                                     new_hash = Some((ck, HashValue::PdqHash(hash)));
                                 }
+                                screenshot = is_screenshot_features(&img_features);
                                 new_features = Some((ck, img_features));
                                 new_coeffs = Some((ck, cached_coeffs));
                             }
@@ -1464,6 +2481,19 @@ pub fn scan_and_group(
                             if resolution.is_none() {
                                 resolution = get_resolution(path, Some(b));
                             }
+                            // Kept with the features, so the reason shows in the EXIF
+                            // panel and can be searched for
+                            if let Some(reason) = decode_skipped.take() {
+                                let (w, h) = resolution.unwrap_or((0, 0));
+                                let mut features = cached_features
+                                    .take()
+                                    .unwrap_or_else(|| ImageFeatures::new(w, h));
+                                features.insert_tag(
+                                    TAG_DERIVED_DECODE_SKIPPED,
+                                    ExifValue::String(reason),
+                                );
+                                new_features = Some((ck, features));
+                            }
                         }
                     } else if ck == [0u8; 32] {
                         // bytes is None and ck is still zero (unreadable file)
@@ -1472,16 +2502,33 @@ pub fn scan_and_group(
                     }
                 }
 
+                if new_icc {
+                    if let Some((_, features)) = &mut new_features {
+                        IccProfile::insert_tags(icc_profile.as_ref(), features);
+                    } else if let Some(mut features) = cached_features {
+                        IccProfile::insert_tags(icc_profile.as_ref(), &mut features);
+                        new_features = Some((ck, features));
+                    }
+                }
+
                 if new_meta.is_some()
                     || new_hash.is_some()
                     || new_features.is_some()
                     || new_coeffs.is_some()
                     || new_pixel.is_some()
+                    || new_oriented_pixel.is_some()
                 {
-                    let _ = tx.send((new_meta, new_hash, new_features, new_coeffs, new_pixel));
+                    let _ = tx.send((
+                        new_meta,
+                        new_hash,
+                        new_features,
+                        new_coeffs,
+                        new_pixel,
+                        new_oriented_pixel,
+                    ));
                 }
 
-                Some(ScannedFile {
+                let scanned = ScannedFile {
                     path: path.clone(),
                     size,
                     modified: mtime_utc,
@@ -1492,15 +2539,32 @@ pub fn scan_and_group(
                     unique_file_id,
                     pdqhash,
                     pdq_features,
+                    whash,
                     pixel_hash,
+                    oriented_pixel_hash,
+                    icc_profile,
                     exif_timestamp,
-                })
+                    exif_subsec_nanos,
+                    exif_tz_offset,
+                    screenshot,
+                };
+                if let Some(preview) = &preview
+                    && (preview_screenshots || !screenshot)
+                {
+                    preview.send(&scanned);
+                }
+                Some(scanned)
             })
             .collect()
     });
 
     drop(tx);
     db_handle.join().expect("DB writer thread panicked");
+    // The last preview batch goes out before the final grouping starts
+    drop(preview);
+    if let Some(handle) = preview_handle {
+        let _ = handle.join();
+    }
 
     // Deduplicate PDQ Features for Hardlinks
     let mut feature_cache: HashMap<u128, Arc<crate::pdqhash::PdqFeatures>> = HashMap::new();
@@ -1518,40 +2582,653 @@ pub fn scan_and_group(
         }
     }
 
-    let hash_elapsed = hash_start.elapsed();
-    eprintln!(
-        "[DEBUG] PDQ hashes loaded: {} in {:.3}s",
-        valid_files.len(),
-        hash_elapsed.as_secs_f64()
-    );
-
-    let group_start = Instant::now();
-    let (processed_groups, processed_infos, comparison_count) =
-        group_with_pdqhash(&valid_files, config);
-    let group_elapsed = group_start.elapsed();
-
-    eprintln!(
-        "[DEBUG] Grouping: {} groups found in {:.2}s ({} comparisons)",
-        processed_groups.len(),
-        group_elapsed.as_secs_f64(),
-        comparison_count
-    );
+    let hash_elapsed = hash_start.elapsed();
+    debug_log!(
+        "[DEBUG] PDQ hashes loaded: {} in {:.3}s",
+        valid_files.len(),
+        hash_elapsed.as_secs_f64()
+    );
+    let full_hits = cache_full_count.load(Ordering::Relaxed);
+    let partial_hits = cache_partial_count.load(Ordering::Relaxed);
+    debug_log!(
+        "[DEBUG] Cache: {} full, {} partial, {} miss of {} files ({:.1}% skipped decoding) in {:.3}s",
+        full_hits,
+        partial_hits,
+        total_files.saturating_sub(full_hits + partial_hits),
+        total_files,
+        full_hits as f64 * 100.0 / total_files as f64,
+        hash_elapsed.as_secs_f64()
+    );
+    let read = read_count.load(Ordering::Relaxed);
+    eprintln!(
+        "[SCAN] Hashed {} of {} files in {:.2}s ({} unchanged files taken from the cache unread)",
+        read,
+        total_files,
+        hash_elapsed.as_secs_f64(),
+        full_hits
+    );
+    LAST_SCAN_CACHED.store(full_hits, Ordering::Relaxed);
+    LAST_SCAN_FILES.store(total_files, Ordering::Relaxed);
+
+    let group_start = Instant::now();
+    let group = |files: &[ScannedFile]| match ctx.algorithm {
+        HashAlgorithm::PdqHash => group_with_pdqhash(files, config),
+        HashAlgorithm::WHash => group_with_whash(files, config),
+    };
+    let (mut processed_groups, processed_infos, comparison_count) = match config.screenshots {
+        ScreenshotGrouping::Mixed => group(&valid_files),
+        mode => {
+            let (shots, others): (Vec<_>, Vec<_>) =
+                valid_files.into_iter().partition(|f| f.screenshot);
+            eprintln!(
+                "[SCREENSHOTS] {} of {} files look like screenshots ({})",
+                shots.len(),
+                shots.len() + others.len(),
+                if mode == ScreenshotGrouping::Exclude {
+                    "left out of grouping"
+                } else {
+                    "grouped separately"
+                }
+            );
+            let (mut groups, mut infos, mut count) = group(&others);
+            if mode == ScreenshotGrouping::Separate {
+                let (g, i, c) = group(&shots);
+                groups.extend(g);
+                infos.extend(i);
+                count += c;
+            }
+            (groups, infos, count)
+        }
+    };
+    let group_elapsed = group_start.elapsed();
+
+    debug_log!(
+        "[DEBUG] Grouping: {} groups found in {:.2}s ({} comparisons)",
+        processed_groups.len(),
+        group_elapsed.as_secs_f64(),
+        comparison_count
+    );
+
+    expand_root_aliases(&mut processed_groups, &root_aliases);
+
+    let mut combined: Vec<_> = processed_groups.into_iter().zip(processed_infos).collect();
+    combined.sort_by(|(g1, info1), (g2, info2)| {
+        let has_ident1 = info1.status != GroupStatus::None;
+        let has_ident2 = info2.status != GroupStatus::None;
+        if has_ident1 != has_ident2 {
+            return has_ident2.cmp(&has_ident1);
+        }
+        if info1.max_dist != info2.max_dist {
+            return info1.max_dist.cmp(&info2.max_dist);
+        }
+        let s1 = g1.first().map(|f| f.size).unwrap_or(0);
+        let s2 = g2.first().map(|f| f.size).unwrap_or(0);
+        s2.cmp(&s1)
+    });
+
+    combined.into_iter().unzip()
+}
+
+/// Walker over `root`, descending at most `max_depth` directory levels below it
+/// (0 = only the files directly in `root`).
+fn walk_dir(root: &Path, max_depth: Option<usize>) -> WalkDir {
+    match max_depth {
+        // WalkDir counts the root itself as depth 0 and its entries as depth 1
+        Some(depth) => WalkDir::new(root).max_depth(depth.saturating_add(1)),
+        None => WalkDir::new(root),
+    }
+}
+
+/// Recursively collect the canonical paths of all images under `paths`. With
+/// `dedupe_roots` every file is listed once; without it once per scan root it is under.
+pub(crate) fn collect_image_files(
+    paths: &[String],
+    live_photo_exts: &[String],
+    max_depth: Option<usize>,
+    dedupe_roots: bool,
+) -> Vec<std::path::PathBuf> {
+    let mut all_files = Vec::new();
+    let mut seen_paths = HashSet::new();
+    for (root_idx, path_str) in paths.iter().enumerate() {
+        let root = if dedupe_roots { 0 } else { root_idx };
+        let path = Path::new(path_str);
+        if path.is_dir() {
+            for entry in walk_dir(path, max_depth).into_iter().filter_map(|e| e.ok()) {
+                if is_image_ext(entry.path())
+                    && let Ok(canonical) = canonicalize(entry.path())
+                    && seen_paths.insert((root, canonical.clone()))
+                {
+                    all_files.push(listed_path(entry.path(), canonical));
+                }
+            }
+        } else if path.is_file()
+            && is_image_ext(path)
+            && let Ok(canonical) = canonicalize(path)
+            && seen_paths.insert((root, canonical.clone()))
+        {
+            all_files.push(listed_path(path, canonical));
+        }
+    }
+
+    // Live photo companions move through grouping with their primary image, so drop
+    // every companion that has a same-stem primary next to it
+    if !live_photo_exts.is_empty() {
+        let primaries: HashSet<std::path::PathBuf> = all_files
+            .iter()
+            .filter(|p| !is_live_companion_ext(p, live_photo_exts))
+            .map(|p| p.with_extension(""))
+            .collect();
+        let before = all_files.len();
+        all_files.retain(|p| {
+            !is_live_companion_ext(p, live_photo_exts) || !primaries.contains(&p.with_extension(""))
+        });
+        debug_log!("[LIVE] Folded {} companion files into their primary", before - all_files.len());
+    }
+    all_files
+}
+
+/// Virtual paths of the image entries of every .zip archive under the scan roots
+/// (`scan_archives`), limited to entries that fit the per-image memory budget.
+fn collect_archive_entries(paths: &[String], max_depth: Option<usize>) -> Vec<std::path::PathBuf> {
+    let mut archives = Vec::new();
+    for path_str in paths {
+        let path = Path::new(path_str);
+        if path.is_dir() {
+            archives.extend(
+                walk_dir(path, max_depth)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file() && crate::archive::is_zip(e.path()))
+                    .map(|e| e.into_path()),
+            );
+        } else if path.is_file() && crate::archive::is_zip(path) {
+            archives.push(path.to_path_buf());
+        }
+    }
+    archives.sort();
+    archives.dedup();
+
+    let max_size = get_image_memory_limit();
+    let entries: Vec<std::path::PathBuf> =
+        archives.iter().flat_map(|a| crate::archive::image_entries(a, max_size)).collect();
+    if !archives.is_empty() {
+        eprintln!("[ZIP] {} images in {} archives", entries.len(), archives.len());
+    }
+    entries
+}
+
+/// Take the extra listings of files reached through several overlapping scan roots
+/// out of hashing and grouping. Like hardlinks the aliases share a unique_file_id,
+/// but they also resolve to the same canonical path: one file, which must not form
+/// a duplicate group with itself. Real hardlinks (different canonical paths) are
+/// kept. Returns the files to hash and, per kept file, its aliases for
+/// `expand_root_aliases`.
+fn collapse_root_aliases(
+    files: Vec<std::path::PathBuf>,
+) -> (Vec<std::path::PathBuf>, HashMap<std::path::PathBuf, Vec<std::path::PathBuf>>) {
+    let mut first: HashMap<(u128, std::path::PathBuf), std::path::PathBuf> = HashMap::new();
+    let mut aliases: HashMap<std::path::PathBuf, Vec<std::path::PathBuf>> = HashMap::new();
+    let mut kept = Vec::with_capacity(files.len());
+    for path in files {
+        let Some(unique_file_id) = get_file_key(&path) else {
+            kept.push(path);
+            continue;
+        };
+        let canonical = canonicalize(&path).unwrap_or_else(|_| path.clone());
+        match first.entry((unique_file_id, canonical)) {
+            std::collections::hash_map::Entry::Occupied(e) => {
+                aliases.entry(e.get().clone()).or_default().push(path);
+            }
+            std::collections::hash_map::Entry::Vacant(e) => {
+                e.insert(path.clone());
+                kept.push(path);
+            }
+        }
+    }
+    if !aliases.is_empty() {
+        eprintln!(
+            "[ROOTS] {} files listed under several scan roots are hashed once",
+            aliases.len()
+        );
+    }
+    (kept, aliases)
+}
+
+/// List the aliases taken out by `collapse_root_aliases` again, each right after the
+/// grouped file it stands for, so every scan root keeps its entry. They share its
+/// unique_file_id and show up like hardlinks of it.
+fn expand_root_aliases(
+    groups: &mut [Vec<FileMetadata>],
+    aliases: &HashMap<std::path::PathBuf, Vec<std::path::PathBuf>>,
+) {
+    if aliases.is_empty() {
+        return;
+    }
+    for group in groups {
+        let mut expanded = Vec::with_capacity(group.len());
+        for file in group.drain(..) {
+            let extra = aliases.get(&file.path);
+            expanded.push(file);
+            for alias in extra.into_iter().flatten() {
+                let mut copy = expanded[expanded.len() - 1].clone();
+                copy.path = alias.clone();
+                expanded.push(copy);
+            }
+        }
+        *group = expanded;
+    }
+}
+
+fn is_live_companion_ext(path: &Path, live_photo_exts: &[String]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| live_photo_exts.iter().any(|c| c.eq_ignore_ascii_case(e)))
+}
+
+/// Same-stem live photo companions (e.g. IMG_1234.MOV for IMG_1234.HEIC) of each of
+/// `paths`. Every directory is listed once instead of probing each extension per
+/// file, so upper- and lowercase companion extensions are found as they are, and a
+/// companion reached under two names (case-insensitive file systems) counts once.
+pub fn live_photo_sibling_index<'a>(
+    paths: impl IntoIterator<Item = &'a Path>,
+    live_photo_exts: &[String],
+) -> HashMap<std::path::PathBuf, Vec<std::path::PathBuf>> {
+    let mut index = HashMap::new();
+    if live_photo_exts.is_empty() {
+        return index;
+    }
+    let mut by_dir: HashMap<&Path, Vec<&Path>> = HashMap::new();
+    for path in paths {
+        if !is_live_companion_ext(path, live_photo_exts)
+            && let Some(dir) = path.parent()
+        {
+            by_dir.entry(dir).or_default().push(path);
+        }
+    }
+    for (dir, files) in by_dir {
+        let Ok(entries) = fs::read_dir(dir) else { continue };
+        let mut seen = HashSet::new();
+        let mut companions: HashMap<std::ffi::OsString, Vec<std::path::PathBuf>> = HashMap::new();
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if is_live_companion_ext(&path, live_photo_exts)
+                && path.is_file()
+                && get_file_key(&path).is_none_or(|key| seen.insert(key))
+                && let Some(stem) = path.file_stem()
+            {
+                companions.entry(stem.to_os_string()).or_default().push(path.clone());
+            }
+        }
+        for file in files {
+            if let Some(found) = file.file_stem().and_then(|stem| companions.get(stem)) {
+                index.insert(file.to_path_buf(), found.clone());
+            }
+        }
+    }
+    index
+}
+
+/// Content hash of every image under the scan roots, for `--changes`.
+/// Like name-dedup mode nothing is decoded: unchanged files come straight from
+/// the meta_key cache, everything else is read and hashed (and cached).
+pub fn scan_content_hashes(
+    config: &ScanConfig,
+    ctx: &AppContext,
+) -> Vec<(std::path::PathBuf, [u8; 32])> {
+    let all_files = collect_image_files(&config.paths, &[], config.max_depth, true);
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(get_safe_thread_count())
+        .build()
+        .expect("Failed to build smart thread pool");
+
+    let (tx, rx) = unbounded();
+    let db_handle = ctx.start_db_writer(rx);
+
+    let mut hashes: Vec<(std::path::PathBuf, [u8; 32])> = pool.install(|| {
+        all_files
+            .into_par_iter()
+            .filter_map(|path| {
+                let file = NameDupeFile::stat(&path, config.rehash, ctx)?;
+                let content_hash = match file.content_hash {
+                    Some(ch) => ch,
+                    None => hash_file(&path, &ctx.content_key)?,
+                };
+                let _ =
+                    tx.send((Some((file.meta_key, content_hash)), None, None, None, None, None));
+                Some((path, content_hash))
+            })
+            .collect()
+    });
+
+    drop(tx);
+    db_handle.join().expect("DB writer thread panicked");
+
+    hashes.sort_by(|a, b| a.0.cmp(&b.0));
+    hashes
+}
+
+/// `--verify` result: cached content hashes checked against the data on disk.
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// Files whose cached hash still matches
+    pub verified: usize,
+    /// Files without a cache entry (never scanned, or changed so the meta key differs)
+    pub uncached: usize,
+    /// Files whose cache entry is unreadable or disagrees with the data, with their meta key
+    pub mismatched: Vec<(std::path::PathBuf, [u8; 32])>,
+}
+
+/// Re-hash every file that has a cached content hash and compare. Unlike a scan with
+/// `--rehash`, nothing is written back; the caller decides whether to purge.
+pub fn verify_content_hashes(config: &ScanConfig, ctx: &AppContext) -> VerifyReport {
+    let all_files = collect_image_files(&config.paths, &[], config.max_depth, true);
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(get_safe_thread_count())
+        .build()
+        .expect("Failed to build smart thread pool");
+
+    let uncached = AtomicUsize::new(0);
+    let verified = AtomicUsize::new(0);
+    let mut mismatched: Vec<(std::path::PathBuf, [u8; 32])> = pool.install(|| {
+        all_files
+            .into_par_iter()
+            .filter_map(|path| {
+                let metadata = fs::metadata(&path).ok()?;
+                let unique_file_id = get_file_key(&path)?;
+                let meta_key = crate::db::compute_meta_key_from_metadata(
+                    &ctx.meta_key,
+                    &metadata,
+                    unique_file_id,
+                );
+                let cached = match ctx.get_content_hash(&meta_key) {
+                    Ok(Some(ch)) => Some(ch),
+                    Ok(None) => {
+                        uncached.fetch_add(1, Ordering::Relaxed);
+                        return None;
+                    }
+                    Err(_) => None,
+                };
+                let actual = hash_file(&path, &ctx.content_key)?;
+                if cached == Some(actual) {
+                    verified.fetch_add(1, Ordering::Relaxed);
+                    None
+                } else {
+                    Some((path, meta_key))
+                }
+            })
+            .collect()
+    });
+
+    mismatched.sort_by(|a, b| a.0.cmp(&b.0));
+    VerifyReport { verified: verified.into_inner(), uncached: uncached.into_inner(), mismatched }
+}
+
+/// `(meta_key, prefix length, prefix hash)` to be cached in the prefix DB
+type PrefixEntry = ([u8; 32], u32, [u8; 32]);
+
+/// A name-dedup (or `--changes`) candidate before its content hash is settled
+struct NameDupeFile {
+    path: std::path::PathBuf,
+    metadata: fs::Metadata,
+    unique_file_id: u128,
+    meta_key: [u8; 32],
+    /// Full keyed blake3, when cached or already known
+    content_hash: Option<[u8; 32]>,
+    /// Keyed blake3 of the first `--prefix-hash` bytes (files without a full hash only)
+    prefix: Option<[u8; 32]>,
+}
+
+impl NameDupeFile {
+    /// Stat `path` and look up its cached content hash, unless `rehash` is set.
+    /// None when the file can't be stat'ed.
+    fn stat(path: &Path, rehash: bool, ctx: &AppContext) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let unique_file_id = get_file_key(path)?;
+        let meta_key =
+            crate::db::compute_meta_key_from_metadata(&ctx.meta_key, &metadata, unique_file_id);
+        let content_hash = match ctx.get_content_hash(&meta_key) {
+            Ok(Some(ch)) if !rehash => Some(ch),
+            _ => None,
+        };
+        Some(Self {
+            path: path.to_path_buf(),
+            metadata,
+            unique_file_id,
+            meta_key,
+            content_hash,
+            prefix: None,
+        })
+    }
+}
+
+/// Keyed blake3 of the whole file.
+fn hash_file(path: &Path, key: &[u8; 32]) -> Option<[u8; 32]> {
+    let bytes = fs::read(path).ok()?;
+    Some(*blake3::keyed_hash(key, &bytes).as_bytes())
+}
+
+/// Keyed blake3 of the first `len` bytes of the file.
+fn hash_file_prefix(path: &Path, len: usize, key: &[u8; 32]) -> Option<[u8; 32]> {
+    use std::io::Read;
+    let mut buf = Vec::with_capacity(len);
+    fs::File::open(path).ok()?.take(len as u64).read_to_end(&mut buf).ok()?;
+    Some(*blake3::keyed_hash(key, &buf).as_bytes())
+}
+
+/// Give every file without a full hash its prefix hash, from the cache or by reading
+/// `len` bytes. A file no longer than the prefix is read whole, so its prefix hash is
+/// its content hash. Returns the newly computed entries for the prefix DB.
+fn fill_prefix_hashes(
+    files: &mut [NameDupeFile],
+    len: usize,
+    ctx: &AppContext,
+) -> Vec<PrefixEntry> {
+    let len32 = len.min(u32::MAX as usize) as u32;
+    let mut computed = Vec::new();
+    for f in files.iter_mut().filter(|f| f.content_hash.is_none()) {
+        let prefix = match ctx.get_prefix_hash(&f.meta_key, len32) {
+            Some(h) => Some(h),
+            None => {
+                let h = hash_file_prefix(&f.path, len, &ctx.content_key);
+                if let Some(h) = h {
+                    computed.push((f.meta_key, len32, h));
+                }
+                h
+            }
+        };
+        if f.metadata.len() <= len as u64 {
+            f.content_hash = prefix;
+        } else {
+            f.prefix = prefix;
+        }
+    }
+    computed
+}
+
+/// Whether `files[i]` might be bit-identical to another file: same size, and either
+/// the other's prefix matches or the other only has a full hash to compare with.
+fn has_possible_twin(files: &[NameDupeFile], i: usize) -> bool {
+    let me = &files[i];
+    files.iter().enumerate().any(|(j, other)| {
+        j != i
+            && other.metadata.len() == me.metadata.len()
+            && (other.content_hash.is_some() || other.prefix.is_none() || other.prefix == me.prefix)
+    })
+}
+
+/// Name-dedup mode: group files sharing a file name, regardless of content.
+/// No image is decoded; only the keyed blake3 content hash is computed (or taken
+/// from the metadata cache) so bit-identical files can be told apart in the UI.
+fn group_by_file_name(
+    all_files: Vec<std::path::PathBuf>,
+    mode: NameMatch,
+    config: &ScanConfig,
+    ctx: &AppContext,
+    progress_tx: Option<Sender<(usize, usize)>>,
+) -> (Vec<Vec<FileMetadata>>, Vec<GroupInfo>) {
+    use std::time::Instant;
+
+    let start = Instant::now();
+
+    // Only files whose name collides are worth hashing
+    let mut by_name: HashMap<String, Vec<std::path::PathBuf>> = HashMap::new();
+    for path in all_files {
+        by_name.entry(mode.key(&path)).or_default().push(path);
+    }
+    let candidates: Vec<Vec<std::path::PathBuf>> =
+        by_name.into_values().filter(|g| g.len() > 1).collect();
+
+    let total_files: usize = candidates.iter().map(|g| g.len()).sum();
+    if let Some(tx) = &progress_tx {
+        let _ = tx.send((0, total_files));
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(get_safe_thread_count())
+        .build()
+        .expect("Failed to build smart thread pool");
+
+    let (tx, rx) = unbounded();
+    let db_handle = ctx.start_db_writer(rx);
+    let processed_count = AtomicUsize::new(0);
+
+    let prefix_len = config.prefix_hash_bytes;
+    let results: Vec<(Vec<FileMetadata>, Vec<PrefixEntry>)> = pool.install(|| {
+        candidates
+            .par_iter()
+            .map(|paths| {
+                let mut files: Vec<NameDupeFile> = paths
+                    .iter()
+                    .filter_map(|path| {
+                        if let Some(prog_tx) = &progress_tx {
+                            let current = processed_count.fetch_add(1, Ordering::Relaxed) + 1;
+                            if current.is_multiple_of(10) || current == total_files {
+                                let _ = prog_tx.send((current, total_files));
+                            }
+                        }
+
+                        NameDupeFile::stat(path, config.rehash, ctx)
+                    })
+                    .collect();
+
+                let mut new_prefixes = Vec::new();
+                if let Some(len) = prefix_len {
+                    new_prefixes = fill_prefix_hashes(&mut files, len, ctx);
+                }
+
+                let group: Vec<FileMetadata> = (0..files.len())
+                    .filter_map(|i| {
+                        let f = &files[i];
+                        let content_hash = match f.content_hash {
+                            Some(ch) => ch,
+                            // No file of the same size could be identical: skip the full read
+                            None if f.prefix.is_some() && !has_possible_twin(&files, i) => {
+                                [0u8; 32]
+                            }
+                            None => hash_file(&f.path, &ctx.content_key)?,
+                        };
+                        if content_hash != [0u8; 32] {
+                            // Refresh the metadata timestamp (or insert the new entry)
+                            let _ = tx.send((
+                                Some((f.meta_key, content_hash)),
+                                None,
+                                None,
+                                None,
+                                None,
+                                None,
+                            ));
+                        }
+
+                        let mtime = f.metadata.modified().ok().unwrap_or(UNIX_EPOCH);
+                        Some(FileMetadata {
+                            path: f.path.clone(),
+                            size: f.metadata.len(),
+                            modified: DateTime::from(mtime),
+                            pdqhash: None,
+                            resolution: None,
+                            content_hash,
+                            pixel_hash: None,
+                            oriented_pixel_hash: None,
+                            near_identical: None,
+                            luma_variant: None,
+                            icc_profile: None,
+                            orientation: 1,
+                            gps_pos: None,
+                            unique_file_id: f.unique_file_id,
+                            exif_timestamp: None,
+                            exif_subsec_nanos: None,
+                            exif_tz_offset: None,
+                        })
+                    })
+                    .collect();
+                (group, new_prefixes)
+            })
+            .collect()
+    });
+
+    drop(tx);
+    db_handle.join().expect("DB writer thread panicked");
+
+    let mut groups = Vec::with_capacity(results.len());
+    let mut new_prefixes = Vec::new();
+    for (group, prefixes) in results {
+        new_prefixes.extend(prefixes);
+        if group.len() > 1 {
+            groups.push(group);
+        }
+    }
+    if let Err(e) = ctx.put_prefix_hashes(&new_prefixes) {
+        eprintln!("[NAME-DUPES] Failed to store prefix hashes: {}", e);
+    }
+
+    let mut combined: Vec<(Vec<FileMetadata>, GroupInfo)> = groups
+        .into_iter()
+        .map(|mut group| {
+            // Files left unhashed by --prefix-hash (zero hash) are unique by construction
+            let mut counts: HashMap<[u8; 32], usize> = HashMap::new();
+            for f in group.iter().filter(|f| f.content_hash != [0u8; 32]) {
+                *counts.entry(f.content_hash).or_insert(0) += 1;
+            }
+            let status = if counts.len() == 1 {
+                GroupStatus::AllIdentical
+            } else if counts.values().any(|&c| c > 1) {
+                GroupStatus::SomeIdentical
+            } else {
+                GroupStatus::None
+            };
+            // Keep identical copies next to each other
+            sort_files(&mut group, &config.group_by);
+            group.sort_by_key(|f| {
+                (
+                    std::cmp::Reverse(counts.get(&f.content_hash).copied().unwrap_or(1)),
+                    f.content_hash,
+                )
+            });
+            (group, GroupInfo { max_dist: 0, status })
+        })
+        .collect();
 
-    let mut combined: Vec<_> = processed_groups.into_iter().zip(processed_infos).collect();
+    // Same ordering as perceptual groups: identical first, then larger files first
     combined.sort_by(|(g1, info1), (g2, info2)| {
         let has_ident1 = info1.status != GroupStatus::None;
         let has_ident2 = info2.status != GroupStatus::None;
         if has_ident1 != has_ident2 {
             return has_ident2.cmp(&has_ident1);
         }
-        if info1.max_dist != info2.max_dist {
-            return info1.max_dist.cmp(&info2.max_dist);
-        }
         let s1 = g1.first().map(|f| f.size).unwrap_or(0);
         let s2 = g2.first().map(|f| f.size).unwrap_or(0);
         s2.cmp(&s1)
     });
 
+    debug_log!(
+        "[DEBUG] Name grouping ({:?}): {} groups from {} files in {:.2}s",
+        mode,
+        combined.len(),
+        total_files,
+        start.elapsed().as_secs_f64()
+    );
+
     combined.into_iter().unzip()
 }
 
@@ -1591,7 +3268,40 @@ impl GroupingStrategy<[u8; 32]> for PdqStrategy {
     }
 }
 
+struct WHashStrategy;
+impl GroupingStrategy<u64> for WHashStrategy {
+    #[inline(always)]
+    fn extract_hash(&self, file: &ScannedFile) -> Option<u64> {
+        file.whash
+    }
+
+    #[inline(always)]
+    fn generate_variants(&self, _file: &ScannedFile, hash: u64, out: &mut [u64; 8]) -> usize {
+        *out = crate::whash::dihedral_variants(hash);
+        8
+    }
+}
+
+/// Whether two files may be linked under `resolution_tolerance`.
+/// Compares the long edges and the short edges, so a rotated copy still counts as
+/// the same size but a crop to a different aspect ratio does not.
+fn resolution_compatible(
+    a: Option<(u32, u32)>,
+    b: Option<(u32, u32)>,
+    tolerance: Option<f32>,
+) -> bool {
+    let (Some(tol), Some((aw, ah)), Some((bw, bh))) = (tolerance, a, b) else {
+        return true;
+    };
+    if aw.min(ah) == 0 || bw.min(bh) == 0 {
+        return true;
+    }
+    let within = |x: u32, y: u32| x.max(y) as f32 / x.min(y) as f32 - 1.0 <= tol;
+    within(aw.max(ah), bw.max(bh)) && within(aw.min(ah), bw.min(bh))
+}
+
 // --- 2. Optimized Generic Grouping ---
+
 fn group_files_generic<H, S>(
     valid_files: &[ScannedFile],
     config: &ScanConfig,
@@ -1601,10 +3311,11 @@ where
     H: HammingHash + std::fmt::Debug + Clone + Copy + Default,
     S: GroupingStrategy<H>,
 {
-    // The current MIH implementation only guarantees 100% recall up to distance 63 (R=3).
-    let maxsim = crate::hamminghash::MAX_SIMILARITY_256;
+    // The current MIH implementation only guarantees 100% recall up to R=3 bit flips per chunk.
+    let maxsim = H::MAX_DIST;
+    let radius = config.max_similarity();
     assert!(
-        config.similarity <= maxsim,
+        radius <= maxsim,
         "Similarity distances above {} require R=4 bit-flip checks, which are not implemented.",
         maxsim
     );
@@ -1625,6 +3336,7 @@ where
 
     let mih = MIHIndex::new(hashes);
     let n = valid_files.len();
+    let thresholds: Vec<u32> = valid_files.iter().map(|f| config.similarity_for(&f.path)).collect();
 
     const CHUNK_SIZE: usize = 2000;
 
@@ -1665,7 +3377,13 @@ where
                                         }
 
                                         let cand_hash = mih.hash(*dense);
-                                        if variant.hamming_distance(cand_hash) <= config.similarity
+                                        if variant.hamming_distance(cand_hash)
+                                            <= thresholds[i].min(thresholds[cand_idx])
+                                            && resolution_compatible(
+                                                file.resolution,
+                                                valid_files[cand_idx].resolution,
+                                                config.resolution_tolerance,
+                                            )
                                         {
                                             edges.push((i as u32, cand_idx as u32));
                                         }
@@ -1676,14 +3394,14 @@ where
                             check_bucket(q_chunk, visited, local_edges);
 
                             // R=1: 1-bit flips (exhaustive up to dist 31 for 16 chunks)
-                            if config.similarity >= H::NUM_CHUNKS as u32 {
+                            if radius >= H::NUM_CHUNKS as u32 {
                                 for i_bit in 0..bits {
                                     check_bucket(q_chunk ^ (1 << i_bit), visited, local_edges);
                                 }
                             }
 
                             // R=2: 2-bit flips (exhaustive up to dist 47 for 16 chunks)
-                            if config.similarity >= (H::NUM_CHUNKS * 2) as u32 {
+                            if radius >= (H::NUM_CHUNKS * 2) as u32 {
                                 for i_bit in 0..bits {
                                     for j_bit in (i_bit + 1)..bits {
                                         check_bucket(
@@ -1696,7 +3414,7 @@ where
                             }
 
                             // R=3: 3-bit flips (exhaustive up to dist 63 - safely covers 60)
-                            if config.similarity >= (H::NUM_CHUNKS * 3) as u32 {
+                            if radius >= (H::NUM_CHUNKS * 3) as u32 {
                                 for i_bit in 0..bits {
                                     for j_bit in (i_bit + 1)..bits {
                                         for m_bit in (j_bit + 1)..bits {
@@ -1761,7 +3479,26 @@ where
         }
     }
 
-    let raw_groups: Vec<Vec<u32>> = groups_map.into_values().filter(|g| g.len() > 1).collect();
+    let mut raw_groups: Vec<Vec<u32>> = groups_map.into_values().filter(|g| g.len() > 1).collect();
+
+    if let Some(max_dist) = config.cluster_split_dist {
+        let before = raw_groups.len();
+        let results: Vec<(Vec<Vec<u32>>, bool)> = raw_groups
+            .into_par_iter()
+            .map(|group| split_chained_group(group, valid_files, &strategy, max_dist))
+            .collect();
+        let split = results.iter().filter(|(_, was_split)| *was_split).count();
+        raw_groups = results.into_iter().flat_map(|(parts, _)| parts).collect();
+        if split > 0 {
+            eprintln!(
+                "[CLUSTER-SPLIT] {} of {} groups exceeded distance {} and were split ({} groups now)",
+                split,
+                before,
+                max_dist,
+                raw_groups.len()
+            );
+        }
+    }
 
     let groups = merge_groups_by_stem(raw_groups, valid_files);
     let (groups, info) = process_raw_groups(groups, valid_files, config);
@@ -1769,6 +3506,80 @@ where
     (groups, info, comparison_count)
 }
 
+/// Splits one union-find group whose members chain further apart than `max_dist`.
+/// Distances are taken over the dihedral variants, like the linking edges. Returns the
+/// resulting groups and whether the group had to be split.
+fn split_chained_group<H, S>(
+    group: Vec<u32>,
+    valid_files: &[ScannedFile],
+    strategy: &S,
+    max_dist: u32,
+) -> (Vec<Vec<u32>>, bool)
+where
+    H: HammingHash + Default,
+    S: GroupingStrategy<H>,
+{
+    let members: Vec<([H; 8], usize, H)> = group
+        .iter()
+        .filter_map(|&i| {
+            let file = &valid_files[i as usize];
+            let hash = strategy.extract_hash(file)?;
+            let mut variants = [H::default(); 8];
+            let count = strategy.generate_variants(file, hash, &mut variants);
+            Some((variants, count, hash))
+        })
+        .collect();
+    if members.len() != group.len() {
+        return (vec![group], false);
+    }
+    let dist = |a: usize, b: usize| {
+        let (variants, count, _) = &members[a];
+        let target = &members[b].2;
+        variants[..*count].iter().map(|v| v.hamming_distance(target)).min().unwrap_or(u32::MAX)
+    };
+    let clusters = split_chains(members.len(), dist, max_dist);
+    if clusters.len() == 1 && clusters[0].len() == group.len() {
+        return (vec![group], false);
+    }
+    let parts = clusters.into_iter().map(|c| c.into_iter().map(|k| group[k]).collect()).collect();
+    (parts, true)
+}
+
+/// Greedy complete-linkage clustering of `n` items: members are visited best-connected
+/// first (most neighbours within `max_dist`), and each joins the first cluster whose
+/// members are all within `max_dist` of it, or starts a new one. A member left on its
+/// own then joins the cluster holding its closest item, so no file drops out of the
+/// group; every other cluster has a pairwise diameter of at most `max_dist`.
+fn split_chains(n: usize, dist: impl Fn(usize, usize) -> u32, max_dist: u32) -> Vec<Vec<usize>> {
+    let within = |a: usize, b: usize| dist(a, b) <= max_dist || dist(b, a) <= max_dist;
+    let mut order: Vec<(usize, usize)> =
+        (0..n).map(|a| ((0..n).filter(|&b| b != a && within(a, b)).count(), a)).collect();
+    order.sort_by_key(|&(degree, a)| (std::cmp::Reverse(degree), a));
+
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+    for (_, a) in order {
+        match clusters.iter_mut().find(|c| c.iter().all(|&b| within(a, b))) {
+            Some(cluster) => cluster.push(a),
+            None => clusters.push(vec![a]),
+        }
+    }
+    let (mut clusters, leftovers): (Vec<_>, Vec<_>) =
+        clusters.into_iter().partition(|c| c.len() > 1);
+    if clusters.is_empty() {
+        return vec![(0..n).collect()];
+    }
+    for a in leftovers.into_iter().flatten() {
+        let closest = (0..clusters.len())
+            .min_by_key(|&c| clusters[c].iter().map(|&b| dist(a, b).min(dist(b, a))).min())
+            .unwrap_or(0);
+        clusters[closest].push(a);
+    }
+    for c in &mut clusters {
+        c.sort_unstable();
+    }
+    clusters
+}
+
 // --- 3. Wrapper Functions ---
 
 fn group_with_pdqhash(
@@ -1778,6 +3589,13 @@ fn group_with_pdqhash(
     group_files_generic(valid_files, config, PdqStrategy)
 }
 
+fn group_with_whash(
+    valid_files: &[ScannedFile],
+    config: &ScanConfig,
+) -> (Vec<Vec<FileMetadata>>, Vec<GroupInfo>, usize) {
+    group_files_generic(valid_files, config, WHashStrategy)
+}
+
 pub fn analyze_group(
     files: &mut Vec<FileMetadata>,
     sort_order: &str,
@@ -1793,10 +3611,10 @@ pub fn analyze_group(
         *bit_counts.entry(f.content_hash).or_insert(0) += 1;
     }
 
-    // 2. Count Pixel-Identical (Pixel Hash)
+    // 2. Count Pixel-Identical (Pixel Hash, orientation-normalized if computed)
     let mut pixel_counts = HashMap::new();
     for f in files.iter() {
-        if let Some(ph) = f.pixel_hash {
+        if let Some(ph) = f.subgroup_pixel_hash() {
             *pixel_counts.entry(ph).or_insert(0) += 1;
         }
     }
@@ -1805,14 +3623,18 @@ pub fn analyze_group(
     let (mut duplicates, mut unique): (Vec<FileMetadata>, Vec<FileMetadata>) =
         files.drain(..).partition(|f| {
             let is_bit_dupe = *bit_counts.get(&f.content_hash).unwrap_or(&0) > 1;
-            let is_pixel_dupe =
-                f.pixel_hash.map(|ph| *pixel_counts.get(&ph).unwrap_or(&0) > 1).unwrap_or(false);
-            is_bit_dupe || is_pixel_dupe
+            let is_pixel_dupe = f
+                .subgroup_pixel_hash()
+                .map(|ph| *pixel_counts.get(&ph).unwrap_or(&0) > 1)
+                .unwrap_or(false);
+            is_bit_dupe || is_pixel_dupe || f.near_identical.is_some() || f.luma_variant.is_some()
         });
 
     duplicates.sort_by_cached_key(|f| {
         (
-            f.pixel_hash,
+            f.near_identical,
+            f.luma_variant,
+            f.subgroup_pixel_hash(),
             f.content_hash,
             f.path.file_name().unwrap_or_default().to_string_lossy().to_string(),
         )
@@ -1940,10 +3762,14 @@ fn process_raw_groups(
 
     // Build read-only lookup map
     let mut features_map = HashMap::new();
+    let mut whash_map = HashMap::new();
     for vf in valid_files {
         if let Some(feats) = &vf.pdq_features {
             features_map.insert(&vf.path, &**feats);
         }
+        if let Some(wh) = vf.whash {
+            whash_map.insert(&vf.path, wh);
+        }
     }
 
     // Process groups in parallel using Rayon
@@ -1954,13 +3780,38 @@ fn process_raw_groups(
                 .iter()
                 .map(|&idx| valid_files[idx as usize].to_file_metadata())
                 .collect();
+            FileMetadata::unify_pixel_hashes(&mut group_data);
+
+            if config.near_identical_mse.is_some() || config.luma_variant_mse.is_some() {
+                // Bit-identical copies decode the same, so each distinct content is decoded once
+                let mut decoded: HashMap<[u8; 32], Option<Downsampled>> = HashMap::new();
+                for f in &group_data {
+                    decoded.entry(f.content_hash).or_insert_with(|| near_identical_pixels(f));
+                }
+                if let Some(max_mse) = config.near_identical_mse {
+                    mark_near_identical(&mut group_data, max_mse, &decoded);
+                }
+                if let Some(max_mse) = config.luma_variant_mse {
+                    mark_luma_variants(&mut group_data, max_mse, &decoded);
+                }
+            }
 
-            let info = analyze_group_with_features(
+            let mut info = analyze_group_with_features(
                 &mut group_data,
                 &features_map,
                 &config.group_by.to_lowercase(),
                 &ext_priorities,
             );
+            // whash groups: distance from the pivot in wavelet-hash bits instead of PDQ
+            if let Some(pivot) = group_data.first().and_then(|f| whash_map.get(&f.path)) {
+                let variants = crate::whash::dihedral_variants(*pivot);
+                info.max_dist = group_data
+                    .iter()
+                    .filter_map(|f| whash_map.get(&f.path))
+                    .map(|h| variants.iter().map(|v| v.hamming_distance(h)).min().unwrap_or(64))
+                    .max()
+                    .unwrap_or(0);
+            }
             (group_data, info)
         })
         .collect();
@@ -1968,6 +3819,145 @@ fn process_raw_groups(
     results.into_iter().unzip()
 }
 
+/// Edge of the downsample compared by the near-identical and luma variant checks
+const NEAR_IDENTICAL_SIZE: u32 = 64;
+
+/// Largest per-pixel channel spread of a downsample still counted as grayscale;
+/// leaves room for the chroma noise of a JPEG-compressed black-and-white image
+const GRAY_MAX_SPREAD: u8 = 6;
+
+/// NEAR_IDENTICAL_SIZE² luminance pixels of a file after EXIF orientation
+struct Downsampled {
+    luma: Vec<u8>,
+    /// Every pixel has (nearly) equal RGB channels
+    gray: bool,
+}
+
+impl Downsampled {
+    fn new(img: &DynamicImage) -> Self {
+        let small = img
+            .resize_exact(
+                NEAR_IDENTICAL_SIZE,
+                NEAR_IDENTICAL_SIZE,
+                image::imageops::FilterType::Triangle,
+            )
+            .to_rgb8();
+        let gray = small.pixels().all(|p| {
+            let [r, g, b] = p.0;
+            r.max(g).max(b) - r.min(g).min(b) <= GRAY_MAX_SPREAD
+        });
+        let luma = DynamicImage::ImageRgb8(small).to_luma8().into_raw();
+        Self { luma, gray }
+    }
+}
+
+/// The downsample of a file, or None for RAW files and decode errors.
+fn near_identical_pixels(f: &FileMetadata) -> Option<Downsampled> {
+    if is_raw_ext(&f.path) {
+        return None;
+    }
+    let bytes = crate::archive::read(&f.path).ok()?;
+    let mut img = match load_jpeg_scaled(&f.path, &bytes, NEAR_IDENTICAL_SIZE * 4) {
+        Some((img, _)) => img,
+        None => load_image_fast(&f.path, &bytes).ok()?,
+    };
+    if let Some(o) = Orientation::from_exif(f.orientation) {
+        img.apply_orientation(o);
+    }
+    Some(Downsampled::new(&img))
+}
+
+fn mean_squared_error(a: &[u8], b: &[u8]) -> f32 {
+    let sum: u64 = a
+        .iter()
+        .zip(b)
+        .map(|(&x, &y)| {
+            let d = x.abs_diff(y) as u64;
+            d * d
+        })
+        .sum();
+    sum as f32 / a.len().max(1) as f32
+}
+
+/// Link files of one similarity group whose downsampled pixels are within
+/// `max_mse`, e.g. the same picture saved with different JPEG quantization.
+/// Every member of a cluster stores the same unique_file_id of one of them as
+/// the cluster key (see `link_subgroups`).
+fn mark_near_identical(
+    files: &mut [FileMetadata],
+    max_mse: f32,
+    decoded: &HashMap<[u8; 32], Option<Downsampled>>,
+) {
+    let clusters = link_subgroups(files, |a, b| {
+        match (&decoded[&a.content_hash], &decoded[&b.content_hash]) {
+            (Some(a), Some(b)) => mean_squared_error(&a.luma, &b.luma) <= max_mse,
+            _ => false,
+        }
+    });
+    for (key, members) in clusters {
+        for i in members {
+            files[i].near_identical = Some(key);
+        }
+    }
+}
+
+/// Link a grayscale file of one similarity group to a color one whose downsampled
+/// luminance is within `max_mse`: a black-and-white conversion and its original,
+/// even after re-encoding or a different gray formula. Keyed like `mark_near_identical`.
+fn mark_luma_variants(
+    files: &mut [FileMetadata],
+    max_mse: f32,
+    decoded: &HashMap<[u8; 32], Option<Downsampled>>,
+) {
+    let clusters = link_subgroups(files, |a, b| {
+        match (&decoded[&a.content_hash], &decoded[&b.content_hash]) {
+            (Some(a), Some(b)) => {
+                a.gray != b.gray && mean_squared_error(&a.luma, &b.luma) <= max_mse
+            }
+            _ => false,
+        }
+    });
+    for (key, members) in clusters {
+        for i in members {
+            files[i].luma_variant = Some(key);
+        }
+    }
+}
+
+/// Clusters of `files` formed by `linked` pairs, whole pixel-identical subgroups
+/// joining together. Only clusters spanning more than one pixel-identical subgroup
+/// are returned, as (unique_file_id of one member, member indices).
+fn link_subgroups(
+    files: &[FileMetadata],
+    linked: impl Fn(&FileMetadata, &FileMetadata) -> bool,
+) -> Vec<(u128, Vec<usize>)> {
+    let identity = |f: &FileMetadata| f.subgroup_pixel_hash().unwrap_or(f.content_hash);
+
+    let n = files.len();
+    let mut cluster: Vec<usize> = (0..n).collect();
+    for (i, j) in (0..n).flat_map(|i| ((i + 1)..n).map(move |j| (i, j))) {
+        if cluster[i] == cluster[j] {
+            continue;
+        }
+        if identity(&files[i]) == identity(&files[j]) || linked(&files[i], &files[j]) {
+            let (from, to) = (cluster[j], cluster[i]);
+            for c in cluster.iter_mut().filter(|c| **c == from) {
+                *c = to;
+            }
+        }
+    }
+
+    let mut clusters = Vec::new();
+    for root in 0..n {
+        let members: Vec<usize> = (0..n).filter(|&i| cluster[i] == root).collect();
+        let identities: HashSet<[u8; 32]> = members.iter().map(|&i| identity(&files[i])).collect();
+        if identities.len() > 1 {
+            clusters.push((files[root].unique_file_id, members));
+        }
+    }
+    clusters
+}
+
 // Helper struct to force natural sort comparison
 #[derive(PartialEq, Eq)]
 struct NaturalSortKey(String);
@@ -1984,6 +3974,64 @@ impl Ord for NaturalSortKey {
     }
 }
 
+/// Case-insensitive natural comparison used by the `name-natural-ci` sorts.
+/// Unlike natord (`name-natural`), letters compare without case, so `img_2` and
+/// `IMG_10` interleave. Digit runs compare by value, which also orders
+/// `2024-01-05` like a date and `v1.2.10` like a version. Equal values with
+/// different zero padding put the shorter run first; a full tie falls back to
+/// the exact string so the order stays total.
+fn natural_cmp_ci(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    fn take_digits(it: &mut std::iter::Peekable<std::str::Chars>) -> String {
+        let mut s = String::new();
+        while let Some(c) = it.next_if(|c| c.is_ascii_digit()) {
+            s.push(c);
+        }
+        s
+    }
+
+    let (mut ai, mut bi) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ord = match (ai.peek().copied(), bi.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (da, db) = (take_digits(&mut ai), take_digits(&mut bi));
+                let (ta, tb) = (da.trim_start_matches('0'), db.trim_start_matches('0'));
+                ta.len()
+                    .cmp(&tb.len())
+                    .then_with(|| ta.cmp(tb))
+                    .then_with(|| da.len().cmp(&db.len()))
+            }
+            (Some(x), Some(y)) => {
+                ai.next();
+                bi.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            }
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+#[derive(PartialEq, Eq)]
+struct NaturalCiSortKey(String);
+
+impl PartialOrd for NaturalCiSortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NaturalCiSortKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        natural_cmp_ci(&self.0, &other.0)
+    }
+}
+
 pub fn sort_files(files: &mut [FileMetadata], sort_order: &str) {
     use rand::seq::SliceRandom;
     match sort_order {
@@ -2015,6 +4063,21 @@ pub fn sort_files(files: &mut [FileMetadata], sort_order: &str) {
             });
             files.reverse();
         }
+        "name-natural-ci" => {
+            files.sort_by_cached_key(|f| {
+                NaturalCiSortKey(
+                    f.path.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                )
+            });
+        }
+        "name-natural-ci-desc" => {
+            files.sort_by_cached_key(|f| {
+                NaturalCiSortKey(
+                    f.path.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                )
+            });
+            files.reverse();
+        }
         "date" => files.sort_by_key(|a| a.modified),
         "date-desc" => files.sort_by(|a, b| b.modified.cmp(&a.modified)),
         "size" => files.sort_by_key(|a| a.size),
@@ -2022,7 +4085,8 @@ pub fn sort_files(files: &mut [FileMetadata], sort_order: &str) {
         "exif-date" => {
             // Sort by EXIF timestamp (oldest first).
             // Files with EXIF timestamps come first, then files without (sorted by mtime).
-            files.sort_by(|a, b| match (a.exif_timestamp, b.exif_timestamp) {
+            // Sub-second and timezone offset tags refine the order when present.
+            files.sort_by(|a, b| match (a.exif_sort_key(), b.exif_sort_key()) {
                 (Some(ta), Some(tb)) => ta.cmp(&tb),
                 (Some(_), None) => std::cmp::Ordering::Less,
                 (None, Some(_)) => std::cmp::Ordering::Greater,
@@ -2032,13 +4096,25 @@ pub fn sort_files(files: &mut [FileMetadata], sort_order: &str) {
         "exif-date-desc" => {
             // Sort by EXIF timestamp (newest first).
             // Files with EXIF timestamps come first, then files without (sorted by mtime desc).
-            files.sort_by(|a, b| match (a.exif_timestamp, b.exif_timestamp) {
+            files.sort_by(|a, b| match (a.exif_sort_key(), b.exif_sort_key()) {
                 (Some(ta), Some(tb)) => tb.cmp(&ta),
                 (Some(_), None) => std::cmp::Ordering::Less,
                 (None, Some(_)) => std::cmp::Ordering::Greater,
                 (None, None) => b.modified.cmp(&a.modified),
             });
         }
+        "aspect" | "aspect-desc" => {
+            // Narrowest (portrait) first, or widest (panoramas) first for -desc;
+            // files without a known resolution last either way
+            let desc = sort_order == "aspect-desc";
+            files.sort_by(|a, b| match (a.aspect_ratio(), b.aspect_ratio()) {
+                (Some(ra), Some(rb)) if desc => rb.total_cmp(&ra),
+                (Some(ra), Some(rb)) => ra.total_cmp(&rb),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            });
+        }
         "random" => {
             let mut rng = rand::rng();
             files.shuffle(&mut rng);
@@ -2085,6 +4161,21 @@ pub fn sort_directories(dirs: &mut [std::path::PathBuf], sort_order: &str) {
             });
             dirs.reverse();
         }
+        "name-natural-ci" => {
+            dirs.sort_by_cached_key(|d| {
+                NaturalCiSortKey(
+                    d.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                )
+            });
+        }
+        "name-natural-ci-desc" => {
+            dirs.sort_by_cached_key(|d| {
+                NaturalCiSortKey(
+                    d.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                )
+            });
+            dirs.reverse();
+        }
         "date" => {
             dirs.sort_by_cached_key(|d| {
                 fs::metadata(d).ok().and_then(|m| m.modified().ok()).unwrap_or(UNIX_EPOCH)
@@ -2251,24 +4342,24 @@ pub fn scan_for_view(
                 for entry in entries.filter_map(|e| e.ok()) {
                     let entry_path = entry.path();
                     if entry_path.is_dir() {
-                        if let Ok(canonical) = entry_path.canonicalize() {
-                            subdirs.push(canonical);
+                        if let Ok(canonical) = canonicalize(&entry_path) {
+                            subdirs.push(listed_path(&entry_path, canonical));
                         }
                     } else if entry_path.is_file()
                         && is_image_ext(&entry_path)
-                        && let Ok(canonical) = entry_path.canonicalize()
+                        && let Ok(canonical) = canonicalize(&entry_path)
                         && seen_paths.insert(canonical.clone())
                     {
-                        raw_paths.push(canonical);
+                        raw_paths.push(listed_path(&entry_path, canonical));
                     }
                 }
             }
         } else if path.is_file()
             && is_image_ext(path)
-            && let Ok(canonical) = path.canonicalize()
+            && let Ok(canonical) = canonicalize(path)
             && seen_paths.insert(canonical.clone())
         {
-            raw_paths.push(canonical);
+            raw_paths.push(listed_path(path, canonical));
         }
     }
     // Apply same sort order to directories as files
@@ -2317,18 +4408,20 @@ pub fn scan_for_view(
 
                     let mut gps_pos = None;
                     let mut exif_timestamp = None;
+                    let (mut exif_subsec_nanos, mut exif_tz_offset) = (None, None);
                     if let Some(exif) = read_exif_data(path, None) {
                         if let Some((lat, lon)) = extract_gps_lat_lon(&exif) {
                             gps_pos = Some(Point::new(lon, lat));
                         }
                         exif_timestamp = get_exif_timestamp(&exif);
+                        (exif_subsec_nanos, exif_tz_offset) = get_exif_time_refinement(&exif);
                     }
                     // Required for RAWs to look correct immediately.
                     // Streaming (batch_tx) ensures the UI is still responsive.
                     // Note: For RAW files, the actual orientation used depends on whether thumbnails
                     // or full decode is used. The image loader will return the correct value.
                     let orientation = get_orientation(path, None);
-                    eprintln!(
+                    debug_log!(
                         "[DEBUG-SCAN] scan_for_view get_orientation={} for {:?}",
                         orientation,
                         path.file_name().unwrap_or_default()
@@ -2344,10 +4437,16 @@ pub fn scan_for_view(
                         resolution: None,
                         content_hash: [0u8; 32],
                         pixel_hash: None,
+                        oriented_pixel_hash: None,
+                        near_identical: None,
+                        luma_variant: None,
+                        icc_profile: None,
                         orientation,
                         gps_pos,
                         unique_file_id,
                         exif_timestamp,
+                        exif_subsec_nanos,
+                        exif_tz_offset,
                     })
                 })
                 .collect()
@@ -2370,7 +4469,7 @@ pub fn scan_for_view(
 }
 
 /// Scan for view mode with recursive directory traversal (flatten mode).
-/// Unlike scan_for_view, this recursively walks all subdirectories.
+/// Unlike scan_for_view, this recursively walks subdirectories (down to `max_depth`).
 /// Uses database cache for metadata like spawn_background_dir_scan.
 /// Returns (file_count) synchronously for immediate UI setup.
 pub fn spawn_background_flatten_scan(
@@ -2379,6 +4478,7 @@ pub fn spawn_background_flatten_scan(
     ctx: &crate::db::AppContext,
     batch_tx: Sender<Vec<FileMetadata>>,
     progress_tx: Option<Sender<(usize, usize)>>,
+    max_depth: Option<usize>,
 ) -> usize {
     let mut seen_paths = HashSet::new();
     let mut entries: Vec<DirEntry> = Vec::new();
@@ -2387,17 +4487,17 @@ pub fn spawn_background_flatten_scan(
     for path_str in paths {
         let path = Path::new(path_str);
         if path.is_dir() {
-            for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+            for entry in walk_dir(path, max_depth).into_iter().filter_map(|e| e.ok()) {
                 let entry_path = entry.path();
                 if entry_path.is_file()
                     && is_image_ext(entry_path)
-                    && let Ok(canonical) = entry_path.canonicalize()
+                    && let Ok(canonical) = canonicalize(entry_path)
                     && seen_paths.insert(canonical.clone())
                     && let Ok(meta) = fs::metadata(&canonical)
                     && let Some(unique_file_id) = get_file_key(&canonical)
                 {
                     entries.push(DirEntry {
-                        path: canonical,
+                        path: listed_path(entry_path, canonical),
                         size: meta.len(),
                         modified: meta.modified().unwrap_or(UNIX_EPOCH).into(),
                         unique_file_id,
@@ -2406,13 +4506,13 @@ pub fn spawn_background_flatten_scan(
             }
         } else if path.is_file()
             && is_image_ext(path)
-            && let Ok(canonical) = path.canonicalize()
+            && let Ok(canonical) = canonicalize(path)
             && seen_paths.insert(canonical.clone())
             && let Ok(meta) = fs::metadata(&canonical)
             && let Some(unique_file_id) = get_file_key(&canonical)
         {
             entries.push(DirEntry {
-                path: canonical,
+                path: listed_path(path, canonical),
                 size: meta.len(),
                 modified: meta.modified().unwrap_or(UNIX_EPOCH).into(),
                 unique_file_id,
@@ -2453,17 +4553,20 @@ pub fn spawn_background_flatten_scan(
             .into_iter()
             .map(|e| {
                 // Extract fields from ImageFeatures if cached
-                let (resolution, orientation, gps_pos, exif_timestamp) =
-                    if let Some(feats) = cached.get(&e.unique_file_id) {
-                        (
-                            feats.resolution(),
-                            feats.orientation(),
-                            feats.gps_pos(),
-                            feats.exif_timestamp(),
-                        )
-                    } else {
-                        (None, 1, None, None)
-                    };
+                let feats = cached.get(&e.unique_file_id);
+                let (resolution, orientation, gps_pos, exif_timestamp) = if let Some(feats) = feats
+                {
+                    (
+                        feats.resolution(),
+                        feats.orientation(),
+                        feats.gps_pos(),
+                        feats.exif_timestamp(),
+                    )
+                } else {
+                    (None, 1, None, None)
+                };
+                let exif_subsec_nanos = feats.and_then(|f| f.exif_subsec_nanos());
+                let exif_tz_offset = feats.and_then(|f| f.exif_tz_offset());
 
                 FileMetadata {
                     path: e.path,
@@ -2473,10 +4576,16 @@ pub fn spawn_background_flatten_scan(
                     resolution,
                     content_hash: [0u8; 32],
                     pixel_hash: None,
+                    oriented_pixel_hash: None,
+                    near_identical: None,
+                    luma_variant: None,
+                    icc_profile: None,
                     orientation,
                     gps_pos,
                     unique_file_id: e.unique_file_id,
                     exif_timestamp,
+                    exif_subsec_nanos,
+                    exif_tz_offset,
                 }
             })
             .collect();
@@ -2493,12 +4602,61 @@ pub fn spawn_background_flatten_scan(
     file_count
 }
 
+/// Spawn a background thread filling in resolutions the view-mode dir scan couldn't
+/// take from the cache (files not cached yet, or cached before the resolution was known).
+/// Only image headers are read. Cached features missing a resolution are rewritten via
+/// db_tx so the next visit finds it; results go to result_tx as (unique_file_id, (w, h)).
+pub fn spawn_resolution_fill(
+    files: Vec<(std::path::PathBuf, u128)>, // (path, unique_file_id)
+    ctx: Arc<AppContext>,
+    db_tx: Option<Sender<DbUpdate>>,
+    result_tx: Sender<(u128, (u32, u32))>,
+) {
+    if files.is_empty() {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let cancelled = AtomicBool::new(false);
+        files.par_iter().for_each(|(path, unique_file_id)| {
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+            let Some(dims) = get_resolution(path, None) else {
+                return;
+            };
+
+            if let Some(tx) = &db_tx
+                && let Ok(metadata) = fs::metadata(path)
+                && let Ok(Some(content_hash)) =
+                    ctx.get_content_hash(&crate::db::compute_meta_key_from_metadata(
+                        &ctx.meta_key,
+                        &metadata,
+                        *unique_file_id,
+                    ))
+                && let Ok(Some(mut features)) = ctx.get_features(&content_hash)
+                && features.resolution().is_none()
+            {
+                features.width = dims.0;
+                features.height = dims.1;
+                let _ = tx.send((None, None, Some((content_hash, features)), None, None, None));
+            }
+
+            // GUI moved on (directory change): stop reading headers
+            if result_tx.send((*unique_file_id, dims)).is_err() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        });
+    });
+}
+
 /// Spawn a background thread to enrich files with content_hash and GPS data.
 /// This function handles:
 /// - Computing blake3 content_hash (parallel with rayon)
 /// - Reading GPS coordinates from EXIF
 /// - Writing results to database via db_tx channel
 /// - Sending EnrichmentResult back to GUI via result_tx channel
+/// - Reporting (enriched, total) via progress_tx, like the scan progress
 ///
 /// The GUI can then use unique_file_id for O(1) lookup to update FileMetadata.
 pub fn spawn_background_enrichment(
@@ -2507,16 +4665,30 @@ pub fn spawn_background_enrichment(
     meta_key_secret: [u8; 32],
     db_tx: Option<Sender<DbUpdate>>,
     result_tx: Sender<EnrichmentResult>,
+    progress_tx: Option<Sender<(usize, usize)>>,
 ) {
     if files_to_enrich.is_empty() {
         return;
     }
 
     std::thread::spawn(move || {
+        let total_files = files_to_enrich.len();
+        let processed_count = AtomicUsize::new(0);
+        if let Some(tx) = &progress_tx {
+            let _ = tx.send((0, total_files));
+        }
+
         // Process files in parallel using rayon
         // Thread Safety: Each file is processed independently, no shared mutable state
         // between iterations. The db_tx and result_tx channels are thread-safe.
         files_to_enrich.par_iter().for_each(|(path, unique_file_id, resolution, _orientation)| {
+            if let Some(prog_tx) = &progress_tx {
+                let current = processed_count.fetch_add(1, Ordering::Relaxed) + 1;
+                if current.is_multiple_of(10) || current == total_files {
+                    let _ = prog_tx.send((current, total_files));
+                }
+            }
+
             if let Ok(data) = std::fs::read(path) {
                 // Compute content_hash
                 let mut hasher = blake3::Hasher::new_keyed(&content_key);
@@ -2566,7 +4738,8 @@ pub fn spawn_background_enrichment(
                 // --- 1. BUILD FEATURES (Unified Path) ---
                 // We build the features object now so it can be used for BOTH the database
                 // and the immediate GUI update (fixing the race condition).
-                let (w, h) = resolution.unwrap_or((0, 0));
+                // Fall back to the header so this DB write never clears a resolution.
+                let (w, h) = resolution.or_else(|| get_resolution(path, Some(&data))).unwrap_or((0, 0));
 
                 // Initialize: Use rich EXIF data if available, or blank slate
                 // For RAW files where kamadak-exif failed, use rsraw data
@@ -2579,6 +4752,8 @@ pub fn spawn_background_enrichment(
                 } else {
                     crate::image_features::ImageFeatures::new(w, h)
                 };
+                insert_jxl_tags(&mut features, path, &data);
+                insert_heif_tags(&mut features, path, &data);
 
                 // If we have kamadak-exif data and this is a RAW file, also merge rsraw data
                 // (rsraw might have data that kamadak-exif missed, like lens info)
@@ -2588,7 +4763,7 @@ pub fn spawn_background_enrichment(
                     }
 
                 if false {
-                    eprintln!("[DEBUG-ENRICH]   Final features for '{}': tag_count={}, has_make={}, has_model={}, has_iso={}",
+                    debug_log!("[DEBUG-ENRICH]   Final features for '{}': tag_count={}, has_make={}, has_model={}, has_iso={}",
                         path.file_name().unwrap_or_default().to_string_lossy(),
                         features.tag_count(),
                         features.has_tag(crate::exif_types::TAG_MAKE),
@@ -2689,6 +4864,7 @@ pub fn spawn_background_dir_scan(
     sort_order: String,
     ctx: &crate::db::AppContext,
     batch_tx: Sender<Vec<FileMetadata>>,
+    preread_exif: bool,
 ) -> (Vec<std::path::PathBuf>, usize) {
     let mut subdirs = Vec::new();
     let mut entries: Vec<DirEntry> = Vec::new();
@@ -2697,15 +4873,15 @@ pub fn spawn_background_dir_scan(
     if let Ok(dir_entries) = fs::read_dir(&dir) {
         for entry in dir_entries.flatten() {
             let entry_path = entry.path();
-            if let Ok(canonical) = entry_path.canonicalize() {
+            if let Ok(canonical) = canonicalize(&entry_path) {
                 if canonical.is_dir() {
-                    subdirs.push(canonical);
+                    subdirs.push(listed_path(&entry_path, canonical));
                 } else if is_image_ext(&canonical)
                     && let Ok(meta) = entry.metadata()
                     && let Some(unique_file_id) = get_file_key(&canonical)
                 {
                     entries.push(DirEntry {
-                        path: canonical,
+                        path: listed_path(&entry_path, canonical),
                         size: meta.len(),
                         modified: meta.modified().unwrap_or(UNIX_EPOCH).into(),
                         unique_file_id,
@@ -2740,13 +4916,15 @@ pub fn spawn_background_dir_scan(
     std::thread::spawn(move || {
         const BATCH_SIZE: usize = 500;
 
-        // Convert entries to FileMetadata using cached data
+        // Convert entries to FileMetadata using cached data.
+        // Parallel because uncached files may need an EXIF read (preread_exif).
         let mut files: Vec<FileMetadata> = entries
-            .into_iter()
+            .into_par_iter()
             .map(|e| {
                 // Extract fields from ImageFeatures if cached
-                let (resolution, orientation, gps_pos, exif_timestamp) =
-                    if let Some(feats) = cached.get(&e.unique_file_id) {
+                let feats = cached.get(&e.unique_file_id);
+                let (resolution, orientation, mut gps_pos, mut exif_timestamp) =
+                    if let Some(feats) = feats {
                         (
                             feats.resolution(),
                             feats.orientation(),
@@ -2756,6 +4934,21 @@ pub fn spawn_background_dir_scan(
                     } else {
                         (None, 1, None, None)
                     };
+                let mut exif_subsec_nanos = feats.and_then(|f| f.exif_subsec_nanos());
+                let mut exif_tz_offset = feats.and_then(|f| f.exif_tz_offset());
+
+                // Cheap pre-read for files the cache doesn't know yet: GPS and date only.
+                // Orientation, resolution and features are still left to enrichment.
+                if preread_exif
+                    && feats.is_none()
+                    && let Some(exif) = read_exif_data(&e.path, None)
+                {
+                    if let Some((lat, lon)) = extract_gps_lat_lon(&exif) {
+                        gps_pos = Some(Point::new(lon, lat));
+                    }
+                    exif_timestamp = get_exif_timestamp(&exif);
+                    (exif_subsec_nanos, exif_tz_offset) = get_exif_time_refinement(&exif);
+                }
 
                 FileMetadata {
                     path: e.path,
@@ -2765,10 +4958,16 @@ pub fn spawn_background_dir_scan(
                     resolution,
                     content_hash: [0u8; 32],
                     pixel_hash: None,
+                    oriented_pixel_hash: None,
+                    near_identical: None,
+                    luma_variant: None,
+                    icc_profile: None,
                     orientation,
                     gps_pos,
                     unique_file_id: e.unique_file_id,
                     exif_timestamp,
+                    exif_subsec_nanos,
+                    exif_tz_offset,
                 }
             })
             .collect();
@@ -2797,4 +4996,214 @@ mod tests {
         let result = derive_country(lat, lon);
         assert_eq!(result, Some("Florida, United States of America (the)".to_string()));
     }
+
+    #[test]
+    fn test_resolution_compatible() {
+        let big = Some((4000, 3000));
+        assert!(resolution_compatible(big, Some((640, 480)), None));
+        assert!(!resolution_compatible(big, Some((640, 480)), Some(0.05)));
+        assert!(resolution_compatible(big, Some((3000, 3900)), Some(0.05)));
+        assert!(!resolution_compatible(big, Some((4000, 1000)), Some(0.05)));
+        assert!(resolution_compatible(big, None, Some(0.0)));
+    }
+
+    #[test]
+    fn test_sniff_image_format() {
+        assert_eq!(sniff_image_format(&[0xFF, 0xD8, 0xFF, 0xE1]), Some("jpg"));
+        assert_eq!(sniff_image_format(b"\x89PNG\r\n\x1a\n...."), Some("png"));
+        assert_eq!(sniff_image_format(b"\0\0\0\x18ftypheic\0\0\0\0"), Some("heic"));
+        assert_eq!(sniff_image_format(b"\0\0\0\x1cftypavif"), Some("avif"));
+        assert_eq!(sniff_image_format(b"hello"), None);
+        let p = Path::new("x.png");
+        assert_eq!(sniff_mismatched_format(p, &[0xFF, 0xD8, 0xFF], "png"), Some("jpg"));
+        assert_eq!(sniff_mismatched_format(p, &[0xFF, 0xD8, 0xFF], "jpeg"), None);
+    }
+
+    #[test]
+    fn test_natural_cmp_ci() {
+        use std::cmp::Ordering;
+        assert_eq!(natural_cmp_ci("img_2.jpg", "IMG_10.jpg"), Ordering::Less);
+        assert_eq!(natural_cmp_ci("v1.2.10", "v1.2.9"), Ordering::Greater);
+        assert_eq!(natural_cmp_ci("2024-01-05", "2024-1-06"), Ordering::Less);
+        assert_eq!(natural_cmp_ci("a7", "a07"), Ordering::Less);
+        assert_eq!(natural_cmp_ci("Beach", "apple"), Ordering::Greater);
+        assert_eq!(natural_cmp_ci("same", "same"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_avif_alpha_premultiplied() {
+        fn isobmff(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+            let mut b = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+            b.extend_from_slice(kind);
+            b.extend_from_slice(payload);
+            b
+        }
+        let full = |kind, children: &[u8]| isobmff(kind, &[&[0u8; 4][..], children].concat());
+        let ftyp = isobmff(b"ftyp", b"avif\0\0\0\0");
+        let prem = isobmff(b"prem", &[0, 1, 0, 1, 0, 2]);
+        let auxl = isobmff(b"auxl", &[0, 2, 0, 1, 0, 1]);
+        let with = [ftyp.clone(), full(b"meta", &full(b"iref", &[auxl.clone(), prem].concat()))];
+        assert!(avif_alpha_premultiplied(&with.concat()));
+        let without = [ftyp, full(b"meta", &full(b"iref", &auxl))];
+        assert!(!avif_alpha_premultiplied(&without.concat()));
+        assert!(!avif_alpha_premultiplied(b"\0\0\0\xffmeta"));
+    }
+
+    #[test]
+    fn test_jxl_info_labels() {
+        let still = JxlInfo { lossless: true, frames: 1 };
+        assert_eq!(still.list_label(), Some("LL"));
+        assert_eq!(still.describe(), "lossless");
+        let anim = JxlInfo { lossless: false, frames: 24 };
+        assert_eq!(anim.list_label(), Some("A"));
+        assert_eq!(anim.describe(), "lossy, animated (24 frames)");
+        assert_eq!(JxlInfo { lossless: false, frames: 1 }.list_label(), None);
+        assert_eq!(jxl_info(b"not a jxl"), None);
+
+        let mut features = ImageFeatures::new(8, 8);
+        assert_eq!(JxlInfo::from_features(&features), None);
+        insert_jxl_tags(&mut features, Path::new("a.png"), b"");
+        assert_eq!(JxlInfo::from_features(&features), None);
+        features.insert_tag(TAG_DERIVED_LOSSLESS, ExifValue::Short(0));
+        features.insert_tag(TAG_DERIVED_FRAMES, ExifValue::Long(24));
+        assert_eq!(JxlInfo::from_features(&features), Some(anim));
+    }
+
+    #[test]
+    fn test_mean_squared_error() {
+        assert_eq!(mean_squared_error(&[10, 20, 30, 40], &[10, 20, 30, 40]), 0.0);
+        assert_eq!(mean_squared_error(&[0, 0, 0, 0], &[2, 0, 0, 2]), 2.0);
+        assert_eq!(mean_squared_error(&[255], &[0]), 65025.0);
+    }
+
+    #[test]
+    fn test_downsampled_gray() {
+        let color = DynamicImage::ImageRgb8(image::RgbImage::from_fn(128, 128, |x, y| {
+            image::Rgb([(x * 2) as u8, (y * 2) as u8, 200])
+        }));
+        let gray = DynamicImage::ImageLuma8(color.to_luma8());
+        let (c, g) = (Downsampled::new(&color), Downsampled::new(&gray));
+        assert!(!c.gray);
+        assert!(g.gray);
+        assert!(mean_squared_error(&c.luma, &g.luma) <= 1.0);
+    }
+
+    #[test]
+    fn test_split_chains() {
+        // A chain 0-1-2-3-4 with unit steps: ends are 4 apart
+        let pos = [0i32, 1, 2, 3, 4];
+        let dist = |a: usize, b: usize| (pos[a] - pos[b]).unsigned_abs();
+        // The best-connected middle seeds the cluster; the ends don't fit it but
+        // join it as its closest items instead of dropping out
+        assert_eq!(split_chains(pos.len(), dist, 2), vec![vec![0, 1, 2, 3, 4]]);
+
+        // A longer chain splits into tight runs
+        let pos = [0i32, 1, 2, 3, 4, 5, 6];
+        let dist = |a: usize, b: usize| (pos[a] - pos[b]).unsigned_abs();
+        assert_eq!(split_chains(pos.len(), dist, 2), vec![vec![2, 3, 4], vec![0, 1], vec![5, 6]]);
+
+        // Already tight: one cluster with everything
+        assert_eq!(split_chains(pos.len(), dist, 4), vec![vec![0, 1, 2, 3, 4]]);
+
+        // Two far-apart pairs; the lone outlier joins the pair closest to it
+        let pos = [0i32, 1, 50, 51, 100];
+        let dist = |a: usize, b: usize| (pos[a] - pos[b]).unsigned_abs();
+        assert_eq!(split_chains(pos.len(), dist, 5), vec![vec![0, 1], vec![2, 3, 4]]);
+    }
+
+    #[test]
+    fn test_is_screenshot() {
+        // Screenshot tool in Software wins even with camera tags (phone screenshots)
+        assert!(is_screenshot(Some((4032, 3024)), Some("Screenshot"), true));
+        assert!(is_screenshot(None, Some("Spectacle 23.08"), false));
+        // Camera photos stay photos, whatever their size
+        assert!(!is_screenshot(Some((1920, 1080)), Some("Ver.1.00"), true));
+        assert!(!is_screenshot(Some((1920, 1080)), None, true));
+        // No camera EXIF: an exact screen size in either orientation
+        assert!(is_screenshot(Some((1920, 1080)), None, false));
+        assert!(is_screenshot(Some((1179, 2556)), Some("GIMP 2.10"), false));
+        assert!(!is_screenshot(Some((1920, 1081)), None, false));
+        assert!(!is_screenshot(None, None, false));
+        // An exported PNG is not a screenshot just for being a PNG
+        assert!(!is_screenshot(Some((800, 600)), None, false));
+    }
+
+    #[test]
+    fn test_env_flag() {
+        assert!(env_flag("1"));
+        assert!(env_flag("yes"));
+        assert!(!env_flag(""));
+        assert!(!env_flag("0"));
+        assert!(!env_flag("False"));
+        assert!(!env_flag(" off "));
+    }
+
+    /// A second scan of unchanged files must come entirely from the cache, without
+    /// opening any of them.
+    #[test]
+    fn test_rescan_reads_nothing() {
+        let root = std::env::temp_dir().join(format!("phdupes_rescan_{}", std::process::id()));
+        let images = root.join("images");
+        fs::create_dir_all(&images).unwrap();
+        for (i, name) in ["a.png", "b.png"].iter().enumerate() {
+            let img = image::RgbImage::from_fn(64, 48, |x, y| {
+                image::Rgb([(x * 4) as u8, (y * 5) as u8, (i * 100) as u8])
+            });
+            img.save(images.join(name)).unwrap();
+        }
+
+        let ctx = AppContext::with_dirs(
+            HashAlgorithm::PdqHash,
+            &root.join("config"),
+            &root.join("cache"),
+        )
+        .unwrap();
+        let config = ScanConfig {
+            paths: vec![images.to_string_lossy().into_owned()],
+            rehash: false,
+            similarity: 0,
+            group_by: "name".to_string(),
+            extensions: Vec::new(),
+            ignore_same_stem: false,
+            calc_pixel_hash: false,
+            calc_oriented_pixel_hash: false,
+            fast_resume: true,
+            name_dupes: None,
+            resolution_tolerance: None,
+            live_photo_exts: Vec::new(),
+            max_depth: None,
+            prefix_hash_bytes: None,
+            near_identical_mse: None,
+            dedupe_scan_roots: true,
+            similarity_by_ext: HashMap::new(),
+            cluster_split_dist: None,
+            screenshots: ScreenshotGrouping::Mixed,
+            os_thumbnails: false,
+            scan_archives: false,
+            icc_profiles: false,
+            luma_variant_mse: None,
+        };
+
+        let first = AtomicUsize::new(0);
+        scan_and_group_counted(&config, &ctx, None, None, &first);
+        assert_eq!(first.load(Ordering::Relaxed), 2);
+        let second = AtomicUsize::new(0);
+        scan_and_group_counted(&config, &ctx, None, None, &second);
+        assert_eq!(second.load(Ordering::Relaxed), 0);
+
+        drop(ctx);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_name_match_natural() {
+        let a = NameMatch::Natural.key(Path::new("/card1/IMG_0001.JPG"));
+        let b = NameMatch::Natural.key(Path::new("/card2/img_1.jpg"));
+        assert_eq!(a, b);
+        assert_eq!(normalize_name_natural("DSC_000.NEF"), "dsc_0.nef");
+        assert_ne!(
+            NameMatch::Exact.key(Path::new("/a/IMG_0001.JPG")),
+            NameMatch::Exact.key(Path::new("/b/img_0001.jpg"))
+        );
+    }
 }
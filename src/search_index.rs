@@ -330,6 +330,16 @@ impl SearchIndex {
                 }
             }
         }
+
+        // Features cached before DerivedLens existed only carry the raw LensModel
+        use crate::exif_types::{TAG_DERIVED_LENS, TAG_LENS_MODEL};
+        if !features.has_tag(TAG_DERIVED_LENS)
+            && let Some(ExifValue::String(lens)) = features.tags.get(&TAG_LENS_MODEL)
+            && let Some(normalized) = crate::exif_extract::normalize_lens_model(lens)
+        {
+            self.indexed_tags.insert(TAG_DERIVED_LENS);
+            self.insert_string(TAG_DERIVED_LENS, &normalized, file_idx);
+        }
     }
 
     /// Insert a string value into the exact index
@@ -784,6 +794,14 @@ fn parse_single_criterion(query: &str) -> Result<SearchCriterion, String> {
             }
         }
 
+        // Search the normalized lens name with a normalized query ("EF24-70" finds
+        // "Canon EF 24-70mm f/2.8L"), the same way it was stored
+        if tag_id == crate::exif_types::TAG_DERIVED_LENS
+            && let Some(normalized) = crate::exif_extract::normalize_lens_model(value)
+        {
+            return Ok(SearchCriterion::new(tag_id, SearchOp::Contains, normalized));
+        }
+
         // Fallback to standard parse or contains
         let op = if value.parse::<f32>().is_ok() { SearchOp::Equals } else { SearchOp::Contains };
         return Ok(SearchCriterion::new(tag_id, op, value.to_string()));
@@ -887,7 +905,8 @@ fn resolve_tag_name(name: &str) -> Option<u16> {
         "focal35" | "focallength35" | "focallength35mm" | "fl35" => Some(TAG_FOCAL_LENGTH_35MM),
         "make" | "manufacturer" | "brand" => Some(TAG_MAKE),
         "model" | "camera" => Some(TAG_MODEL),
-        "lens" | "lensmodel" => Some(TAG_LENS_MODEL),
+        "lens" | "derivedlens" => Some(TAG_DERIVED_LENS),
+        "lensmodel" => Some(TAG_LENS_MODEL),
         "lensmake" => Some(TAG_LENS_MAKE),
         "date" | "datetime" | "datetimeoriginal" => Some(TAG_DATETIME_ORIGINAL),
         "software" | "app" => Some(TAG_SOFTWARE),
@@ -1106,4 +1125,35 @@ mod tests {
         assert_eq!(result.len(), 1);
         assert!(result.contains(1)); // Only the -5.0 one
     }
+
+    #[test]
+    fn test_derived_lens_search() {
+        use crate::exif_extract::normalize_lens_model;
+        use crate::exif_types::TAG_DERIVED_LENS;
+
+        let a = normalize_lens_model("EF24-70mm f/2.8L USM").unwrap();
+        let b = normalize_lens_model("Canon EF 24-70mm F2.8L").unwrap();
+        assert_eq!(a, "Canon EF 24-70mm f/2.8L");
+        assert_eq!(a, b);
+        assert_eq!(
+            normalize_lens_model("FE 24-70mm F2.8 GM II").as_deref(),
+            Some("Sony FE 24-70mm f/2.8 GM II")
+        );
+        assert_eq!(
+            normalize_lens_model("XF18-55mmF2.8-4 R LM OIS").as_deref(),
+            Some("Fujifilm XF 18-55mm f/2.8-4 R OIS")
+        );
+        assert_eq!(normalize_lens_model("----"), None);
+
+        let mut index = SearchIndex::new();
+        let mut features = ImageFeatures::new(1920, 1080);
+        features.insert_tag(TAG_DERIVED_LENS, ExifValue::String(a));
+        index.insert(1, &features);
+        index.finalize();
+
+        let criteria = parse_search_query("lens:EF24-70mm").unwrap();
+        assert_eq!(criteria[0].tag_id, TAG_DERIVED_LENS);
+        assert_eq!(criteria[0].op, SearchOp::Contains);
+        assert_eq!(index.search_contains(TAG_DERIVED_LENS, &criteria[0].value).len(), 1);
+    }
 }
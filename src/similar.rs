@@ -0,0 +1,176 @@
+// Near-duplicate hints for view mode, which doesn't group: every image of the
+// browsed directory gets a PDQ hash in the background, where a growing MIH index
+// also finds each file's nearest neighbour within the threshold. The GUI only
+// stores the results.
+
+use crate::db::{AppContext, HashAlgorithm, compute_meta_key_from_metadata};
+use crate::hamminghash::{GrowableMIH, HammingHash};
+use crate::scanner::{is_raw_ext, load_image_fast, load_jpeg_scaled};
+use crossbeam_channel::Sender;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Threads for the background pass, so the viewer's own decodes stay responsive
+const SIMILAR_THREADS: usize = 2;
+
+/// PDQ downsamples to 512 px anyway; JPEGs are decoded at a reduced scale near that
+const PDQ_DECODE_TARGET: u32 = 512;
+
+/// Streamed from the directory pass as files are hashed.
+pub enum SimilarUpdate {
+    Total(usize),
+    /// One more file processed, hashed or not (unreadable, RAW without a cached hash)
+    Hashed,
+    /// `unique_file_id`'s nearest similar file so far got closer
+    Nearest {
+        unique_file_id: u128,
+        other: u128,
+        dist: u32,
+    },
+    Done,
+}
+
+#[derive(Default)]
+pub struct SimilarIndex {
+    /// unique_file_id -> (nearest unique_file_id, distance), within the threshold only
+    pub nearest: HashMap<u128, (u128, u32)>,
+    pub loaded: usize,
+    pub total: usize,
+    pub done: bool,
+}
+
+impl SimilarIndex {
+    pub fn apply(&mut self, update: SimilarUpdate) {
+        match update {
+            SimilarUpdate::Total(n) => self.total = n,
+            SimilarUpdate::Hashed => self.loaded += 1,
+            SimilarUpdate::Nearest { unique_file_id, other, dist } => {
+                self.nearest.insert(unique_file_id, (other, dist));
+            }
+            SimilarUpdate::Done => self.done = true,
+        }
+    }
+}
+
+/// Nearest neighbours within `threshold` of the hashes inserted so far.
+struct NearestFinder {
+    threshold: u32,
+    index: GrowableMIH<[u8; 32]>,
+    /// unique_file_id of each indexed hash, by dense id
+    ids: Vec<u128>,
+    nearest: HashMap<u128, (u128, u32)>,
+}
+
+impl NearestFinder {
+    fn new(threshold: u32) -> Self {
+        // The index only finds every match up to this distance
+        let threshold = threshold.min(<[u8; 32] as HammingHash>::MAX_DIST);
+        Self { threshold, index: GrowableMIH::new(), ids: Vec::new(), nearest: HashMap::new() }
+    }
+
+    /// Index one hash; returns the files whose nearest neighbour changed, with it.
+    fn insert(&mut self, unique_file_id: u128, pdqhash: [u8; 32]) -> Vec<(u128, (u128, u32))> {
+        let mut changed = Vec::new();
+        for dense in self.index.neighbors(&pdqhash, self.threshold) {
+            let other = self.ids[dense.index()];
+            // Hard links share the id; they are the same file, not a neighbour
+            if other == unique_file_id {
+                continue;
+            }
+            let dist = pdqhash.hamming_distance(self.index.hash(dense));
+            for (a, b) in [(unique_file_id, other), (other, unique_file_id)] {
+                let entry = self.nearest.entry(a).or_insert((b, u32::MAX));
+                if dist < entry.1 {
+                    *entry = (b, dist);
+                    changed.push((a, *entry));
+                }
+            }
+        }
+        self.index.insert(pdqhash);
+        self.ids.push(unique_file_id);
+        changed
+    }
+}
+
+/// PDQ hash of a file: the cached value when a duplicate scan stored one,
+/// otherwise decoded and hashed. RAW files without a cached hash are skipped.
+fn pdqhash_for_file(ctx: &AppContext, path: &Path) -> Option<[u8; 32]> {
+    let bytes = fs::read(path).ok()?;
+    if ctx.algorithm == HashAlgorithm::PdqHash {
+        let content_hash = fs::metadata(path)
+            .ok()
+            .zip(crate::fileops::get_file_key(path))
+            .and_then(|(md, id)| {
+                let meta_key = compute_meta_key_from_metadata(&ctx.meta_key, &md, id);
+                ctx.get_content_hash(&meta_key).ok().flatten()
+            })
+            .unwrap_or_else(|| *blake3::keyed_hash(&ctx.content_key, &bytes).as_bytes());
+        if let Ok(Some(hash)) = ctx.get_pdqhash(&content_hash) {
+            return Some(hash);
+        }
+    }
+    if is_raw_ext(path) {
+        return None;
+    }
+    let img = match load_jpeg_scaled(path, &bytes, PDQ_DECODE_TARGET) {
+        Some((img, _)) => img,
+        None => load_image_fast(path, &bytes).ok()?,
+    };
+    crate::pdqhash::generate_pdq_features(&img).map(|(features, _)| features.to_hash())
+}
+
+/// Hash `files` in the background and stream the progress and nearest neighbours
+/// within `threshold` to `tx`. Stops early once the receiver is gone (e.g. the user
+/// changed directory).
+pub fn spawn_similar_scan(
+    files: Vec<(PathBuf, u128)>,
+    ctx: Arc<AppContext>,
+    threshold: u32,
+    tx: Sender<SimilarUpdate>,
+) {
+    std::thread::spawn(move || {
+        let _ = tx.send(SimilarUpdate::Total(files.len()));
+        let Ok(pool) = rayon::ThreadPoolBuilder::new().num_threads(SIMILAR_THREADS).build() else {
+            return;
+        };
+        let cancelled = AtomicBool::new(false);
+        let (hash_tx, hash_rx) = crossbeam_channel::unbounded();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                pool.install(|| {
+                    files.par_iter().for_each(|(path, unique_file_id)| {
+                        if cancelled.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let _ = hash_tx.send((*unique_file_id, pdqhash_for_file(&ctx, path)));
+                    });
+                });
+                drop(hash_tx);
+            });
+
+            // Neighbours are looked up here, one hash at a time, as the workers finish
+            let mut finder = NearestFinder::new(threshold);
+            for (unique_file_id, pdqhash) in hash_rx {
+                let changed = pdqhash.map(|h| finder.insert(unique_file_id, h)).unwrap_or_default();
+                let updates = changed
+                    .into_iter()
+                    .map(|(unique_file_id, (other, dist))| SimilarUpdate::Nearest {
+                        unique_file_id,
+                        other,
+                        dist,
+                    })
+                    .chain([SimilarUpdate::Hashed]);
+                for update in updates {
+                    if tx.send(update).is_err() {
+                        cancelled.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+        });
+        let _ = tx.send(SimilarUpdate::Done);
+    });
+}
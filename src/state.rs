@@ -4,7 +4,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::fileops;
-use crate::scanner::{analyze_group, sort_files};
+use crate::fileops::DeleteMode;
+use crate::scanner::{analyze_group, live_photo_sibling_index, sort_files};
 use crate::{FileMetadata, GroupInfo};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -53,6 +54,10 @@ pub enum InputIntent {
     IgnoreCurrent,      // Q key: ignore marked files or current file (duplicate mode)
     IgnoreGroup,        // Ctrl+Q: ignore all files in current group (duplicate mode)
     ConfirmIgnoreGroup, // Y on ignore group confirmation dialog
+    ToggleProtect,      // ! key: protect/unprotect current file's content
+    NextPeer,           // Shift+J: next file with the same pixel or content hash
+    NextCamera,         // ] key: next file from a different camera (Make/Model)
+    PrevCamera,         // [ key: previous file from a different camera
 }
 
 #[derive(Debug, Clone)]
@@ -94,11 +99,41 @@ pub fn format_path_depth(path: &Path, depth: usize) -> String {
     out.to_string_lossy().to_string()
 }
 
+/// Deepest directory containing every scan path (files count as their parent).
+/// None when the paths share no common directory (e.g. different drives).
+pub fn common_scan_root(paths: &[String]) -> Option<PathBuf> {
+    let mut root: Option<PathBuf> = None;
+    for p in paths {
+        let canonical = crate::scanner::resolve_path(Path::new(p)).ok()?;
+        let dir = if canonical.is_dir() { canonical } else { canonical.parent()?.to_path_buf() };
+        root = Some(match root {
+            None => dir,
+            Some(r) => r
+                .components()
+                .zip(dir.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    root.filter(|r| !r.as_os_str().is_empty())
+}
+
+/// Directory of each scan path (files count as their parent), for per-root path display.
+pub fn scan_root_dirs(paths: &[String]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .filter_map(|p| crate::scanner::resolve_path(Path::new(p)).ok())
+        .filter_map(|c| if c.is_dir() { Some(c) } else { c.parent().map(Path::to_path_buf) })
+        .collect()
+}
+
 /// Returns a map of content_hash -> count for a group of files.
 /// Used to detect and highlight bit-identical files in UIs.
 pub fn get_bit_identical_counts(group: &[FileMetadata]) -> HashMap<[u8; 32], usize> {
     let mut counts = HashMap::new();
-    for f in group {
+    // A zero hash was never computed; such files must not look identical to each other
+    for f in group.iter().filter(|f| f.content_hash != [0u8; 32]) {
         *counts.entry(f.content_hash).or_insert(0) += 1;
     }
     counts
@@ -107,7 +142,7 @@ pub fn get_bit_identical_counts(group: &[FileMetadata]) -> HashMap<[u8; 32], usi
 pub fn get_content_subgroups(group: &[FileMetadata]) -> HashMap<[u8; 32], usize> {
     let mut counts = HashMap::new();
     for f in group {
-        if let Some(ph) = f.pixel_hash {
+        if let Some(ph) = f.subgroup_pixel_hash() {
             *counts.entry(ph).or_insert(0) += 1;
         }
     }
@@ -117,7 +152,7 @@ pub fn get_content_subgroups(group: &[FileMetadata]) -> HashMap<[u8; 32], usize>
 
     // Assign IDs in order of appearance in the list to keep UI stable
     for f in group {
-        if let Some(ph) = f.pixel_hash {
+        if let Some(ph) = f.subgroup_pixel_hash() {
             // Only assign an ID if this hash appears more than once (is a duplicate)
             if *counts.get(&ph).unwrap_or(&0) > 1
                 && let std::collections::hash_map::Entry::Vacant(e) = ids.entry(ph)
@@ -130,6 +165,188 @@ pub fn get_content_subgroups(group: &[FileMetadata]) -> HashMap<[u8; 32], usize>
     ids
 }
 
+/// Pixel hashes of the content subgroups whose files don't all carry the same ICC
+/// profile (`[grouping] icc_profiles`): same pixels, but not the same colors when
+/// color-managed. A file without a profile counts as a profile of its own.
+pub fn get_mixed_profile_subgroups(group: &[FileMetadata]) -> HashSet<[u8; 32]> {
+    let mut profiles: HashMap<[u8; 32], HashSet<Option<[u8; 8]>>> = HashMap::new();
+    for f in group {
+        if let Some(ph) = f.subgroup_pixel_hash() {
+            profiles.entry(ph).or_default().insert(f.icc_profile.as_ref().map(|p| p.hash));
+        }
+    }
+    profiles.into_iter().filter(|(_, p)| p.len() > 1).map(|(ph, _)| ph).collect()
+}
+
+/// Label ids (N1, N2, ...) of the near-identical clusters in a group, keyed by
+/// `FileMetadata::near_identical`, in order of appearance. Clusters with a
+/// single file left (e.g. after deletions) get no id.
+pub fn get_near_identical_subgroups(group: &[FileMetadata]) -> HashMap<u128, usize> {
+    cluster_ids(group, |f| f.near_identical)
+}
+
+/// Label ids (L1, L2, ...) of the grayscale/color variant clusters in a group, keyed
+/// by `FileMetadata::luma_variant`, numbered like the near-identical ones.
+pub fn get_luma_variant_subgroups(group: &[FileMetadata]) -> HashMap<u128, usize> {
+    cluster_ids(group, |f| f.luma_variant)
+}
+
+fn cluster_ids(
+    group: &[FileMetadata],
+    key: impl Fn(&FileMetadata) -> Option<u128>,
+) -> HashMap<u128, usize> {
+    let mut counts = HashMap::new();
+    for k in group.iter().filter_map(&key) {
+        *counts.entry(k).or_insert(0) += 1;
+    }
+
+    let mut ids = HashMap::new();
+    for k in group.iter().filter_map(&key) {
+        if counts[&k] > 1 {
+            let next_id = ids.len() + 1;
+            ids.entry(k).or_insert(next_id);
+        }
+    }
+    ids
+}
+
+/// Lower-resolution copies in a group: files with the same aspect ratio (within 2%,
+/// after EXIF rotation) as another member that has at least `min_ratio` times their
+/// pixel count. Maps the index of each such file to the index of the largest copy.
+/// Empty when `min_ratio` is 1 or less (disabled).
+pub fn get_smaller_copies(group: &[FileMetadata], min_ratio: f32) -> HashMap<usize, usize> {
+    let mut smaller = HashMap::new();
+    if min_ratio <= 1.0 {
+        return smaller;
+    }
+    let sizes: Vec<Option<(f64, f64)>> = group
+        .iter()
+        .map(|f| {
+            f.display_resolution(true)
+                .filter(|&(w, h)| w > 0 && h > 0)
+                .map(|(w, h)| (w as f64 * h as f64, w as f64 / h as f64))
+        })
+        .collect();
+    for (i, size) in sizes.iter().enumerate() {
+        let Some((pixels, aspect)) = *size else { continue };
+        let largest = sizes
+            .iter()
+            .enumerate()
+            .filter_map(|(j, s)| s.map(|(p, a)| (j, p, a)))
+            .filter(|&(_, p, a)| p >= pixels * min_ratio as f64 && (a / aspect - 1.0).abs() <= 0.02)
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((j, _, _)) = largest {
+            smaller.insert(i, j);
+        }
+    }
+    smaller
+}
+
+/// Which file of a group of identical files `--clean` keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepPolicy {
+    /// The `AppState::keeper_index` choice, per `[grouping] keep_rules`
+    Best,
+    /// Latest modification time
+    Newest,
+    /// Earliest modification time
+    Oldest,
+    /// Shortest path, e.g. the copy outside nested backup folders
+    ShortestPath,
+}
+
+impl KeepPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "best" => Some(KeepPolicy::Best),
+            "newest" => Some(KeepPolicy::Newest),
+            "oldest" => Some(KeepPolicy::Oldest),
+            "shortest-path" => Some(KeepPolicy::ShortestPath),
+            _ => None,
+        }
+    }
+}
+
+/// What a keep rule compares (`[grouping] keep_rules`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepKey {
+    /// Position of the extension in `[grouping] extensions`, earlier is preferred
+    ExtPriority,
+    /// Pixel count
+    Resolution,
+    /// File size
+    Size,
+    /// Modification time
+    Mtime,
+    /// RAW file or not (RAW counts as greater)
+    Raw,
+    /// Length of the full path
+    PathLength,
+}
+
+/// One criterion of the keeper choice, e.g. "size desc": the file ranking first
+/// under the first rule is kept; later rules only break ties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepRule {
+    pub key: KeepKey,
+    /// Prefer the greatest value instead of the smallest
+    pub descending: bool,
+}
+
+impl KeepRule {
+    /// Rules matching the built-in choice: resolution, preferred extension, size.
+    pub const DEFAULTS: [KeepRule; 3] = [
+        KeepRule { key: KeepKey::Resolution, descending: true },
+        KeepRule { key: KeepKey::ExtPriority, descending: false },
+        KeepRule { key: KeepKey::Size, descending: true },
+    ];
+
+    /// Parse "criterion [asc|desc]". Without a direction each criterion uses its
+    /// natural one: preferred extension, RAW, highest resolution, largest size,
+    /// oldest mtime and shortest path first.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut words = s.split_whitespace();
+        let key = match words.next()? {
+            "ext_priority" => KeepKey::ExtPriority,
+            "resolution" => KeepKey::Resolution,
+            "size" => KeepKey::Size,
+            "mtime" => KeepKey::Mtime,
+            "raw" => KeepKey::Raw,
+            "path_length" => KeepKey::PathLength,
+            _ => return None,
+        };
+        let descending = match words.next() {
+            None => matches!(key, KeepKey::Resolution | KeepKey::Size | KeepKey::Raw),
+            Some("asc") => false,
+            Some("desc") => true,
+            Some(_) => return None,
+        };
+        if words.next().is_some() {
+            return None;
+        }
+        Some(Self { key, descending })
+    }
+
+    /// Parse the configured rules; an empty list means `DEFAULTS`.
+    pub fn parse_all(rules: &[String]) -> Result<Vec<Self>, String> {
+        if rules.is_empty() {
+            return Ok(Self::DEFAULTS.to_vec());
+        }
+        rules
+            .iter()
+            .map(|r| {
+                Self::parse(&r.to_lowercase()).ok_or_else(|| {
+                    format!(
+                        "Invalid keep rule '{}'. Use one of ext_priority, resolution, size, \
+                         mtime, raw, path_length, optionally followed by asc or desc",
+                        r
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
 // --- AppState ---
 
 pub struct AppState {
@@ -138,11 +355,38 @@ pub struct AppState {
     pub current_group_idx: usize,
     pub current_file_idx: usize,
     pub marked_for_deletion: Vec<PathBuf>,
+    /// Bumped on every change to `marked_for_deletion`, so figures derived from the
+    /// marks know when to recompute
+    pub marks_generation: u64,
+    /// Files hidden by the GUI list filters (badge, content subgroup, group size).
+    /// They stay in `groups`; the list, navigation and bulk actions skip them.
+    pub hidden: HashSet<PathBuf>,
     pub renaming: Option<RenameState>,
     pub show_relative_times: bool,
     pub use_trash: bool,
+    /// When set, "deleted" files are moved here (dated, with a manifest) instead of
+    /// trashed or removed. Takes precedence over `use_trash`.
+    pub staging_dir: Option<PathBuf>,
+    /// Review folder: marked files can be moved here, below their path relative to
+    /// `scan_root`, to look through them before deleting; restorable via its manifest
+    pub review_dir: Option<PathBuf>,
+    /// Live photo companion extensions (grouping config); empty disables pairing
+    pub live_photo_exts: Vec<String>,
+    /// Listed file -> its live photo companions, filled by `index_live_siblings`
+    pub live_siblings: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Delete companions together with their primary image
+    pub delete_live_siblings: bool,
+    /// Files (by `unique_file_id`) whose content is protected against deletion
+    pub protected: HashSet<u128>,
+    /// Set after the first confirm when deleting a protected file; a second one deletes
+    pub protected_delete_armed: bool,
     pub group_by: String,
     pub ext_priorities: HashMap<String, usize>,
+    /// How `keeper_index` picks the file to keep (`[grouping] keep_rules`)
+    pub keep_rules: Vec<KeepRule>,
+    /// Next on the last file goes to the first one and Prev on the first to the last
+    /// (`[gui] nav_wrap`); otherwise both stop there
+    pub nav_wrap: bool,
     pub status_message: Option<(String, bool)>,
     pub status_set_time: Option<std::time::Instant>,
     pub show_confirmation: bool,
@@ -163,6 +407,13 @@ pub struct AppState {
     pub last_file_count: usize,
     pub zoom_relative: bool,
     pub path_display_depth: usize,
+    /// Common directory of the scan paths, for root-relative path display
+    pub scan_root: Option<PathBuf>,
+    /// Show list paths relative to `scan_root` instead of the last components
+    pub path_relative_to_root: bool,
+    /// Each scan root, when files under overlapping roots are kept per root
+    /// (`dedupe_scan_roots = false`); such files show the path relative to every root
+    pub scan_roots: Vec<PathBuf>,
 
     // View mode features
     pub view_mode: bool,
@@ -197,11 +448,22 @@ impl AppState {
             current_group_idx: 0,
             current_file_idx: 0,
             marked_for_deletion: Vec::new(),
+            marks_generation: 0,
+            hidden: HashSet::new(),
             renaming: None,
             show_relative_times,
             use_trash,
+            staging_dir: None,
+            review_dir: None,
+            live_photo_exts: Vec::new(),
+            live_siblings: HashMap::new(),
+            delete_live_siblings: true,
+            protected: HashSet::new(),
+            protected_delete_armed: false,
             group_by,
             ext_priorities,
+            keep_rules: KeepRule::DEFAULTS.to_vec(),
+            nav_wrap: false,
             status_message: None,
             status_set_time: None,
             show_confirmation: false,
@@ -217,6 +479,9 @@ impl AppState {
             last_file_count: count,
             zoom_relative: false,
             path_display_depth: 0,
+            scan_root: None,
+            path_relative_to_root: false,
+            scan_roots: Vec::new(),
             view_mode: false,
             view_mode_flatten: false,
             move_target: None,
@@ -291,11 +556,18 @@ impl AppState {
         if self.show_delete_immediate_confirmation {
             match intent {
                 InputIntent::ConfirmDeleteImmediate => {
+                    if self.is_current_protected() && !self.protected_delete_armed {
+                        // Protected files need a second confirmation
+                        self.protected_delete_armed = true;
+                        return;
+                    }
                     self.show_delete_immediate_confirmation = false;
+                    self.protected_delete_armed = false;
                     self.perform_delete_immediate();
                 }
                 InputIntent::Cancel | InputIntent::Quit => {
                     self.show_delete_immediate_confirmation = false;
+                    self.protected_delete_armed = false;
                 }
                 _ => {}
             }
@@ -362,7 +634,7 @@ impl AppState {
             }
             InputIntent::ToggleMark => self.toggle_delete(),
             InputIntent::ExecuteDelete => {
-                if !self.marked_for_deletion.is_empty() {
+                if !self.listed_marks().is_empty() {
                     self.show_confirmation = true;
                 } else if self.get_current_image_path().is_some() {
                     // If nothing marked, delete current file
@@ -380,7 +652,7 @@ impl AppState {
             InputIntent::MoveMarked => {
                 if self.move_target.is_none() {
                     self.set_status("No move target set (use --move-marked)".to_string(), true);
-                } else if self.marked_for_deletion.is_empty() {
+                } else if self.listed_marks().is_empty() {
                     self.set_status("No files marked.".to_string(), false);
                 } else {
                     // Open the destination directory now (before the dialog) so
@@ -413,6 +685,10 @@ impl AppState {
             InputIntent::CycleViewMode | InputIntent::CycleZoom => {}
             InputIntent::StartRename => {
                 if let Some(path) = self.get_current_image_path().cloned() {
+                    if let Err(e) = fileops::ensure_not_archived(&path) {
+                        self.set_status(format!("Can't rename: {}", e), true);
+                        return;
+                    }
                     self.renaming = Some(RenameState {
                         group_idx: self.current_group_idx,
                         file_idx: self.current_file_idx,
@@ -427,9 +703,14 @@ impl AppState {
                 self.selection_changed = true;
             }
             InputIntent::TogglePathVisibility => {
+                // Cycles depth 0, 1, ... full path, then relative to the scan root
                 if let Some(path) = self.get_current_image_path() {
                     let total_components = path.components().count();
-                    if self.path_display_depth + 1 >= total_components {
+                    if self.path_relative_to_root {
+                        self.path_relative_to_root = false;
+                        self.path_display_depth = 0;
+                    } else if self.path_display_depth + 1 >= total_components {
+                        self.path_relative_to_root = self.scan_root.is_some();
                         self.path_display_depth = 0;
                     } else {
                         self.path_display_depth += 1;
@@ -500,16 +781,16 @@ impl AppState {
                 let current_dist = self.group_infos[self.current_group_idx].max_dist;
 
                 // Find next group index with strictly greater max_dist
-                if let Some(new_idx) = self
+                if let Some((new_idx, first)) = self
                     .group_infos
                     .iter()
                     .enumerate()
                     .skip(self.current_group_idx + 1)
-                    .find(|(_, info)| info.max_dist > current_dist)
-                    .map(|(i, _)| i)
+                    .filter(|(_, info)| info.max_dist > current_dist)
+                    .find_map(|(i, _)| self.listed_indices(i).first().map(|&f| (i, f)))
                 {
                     self.current_group_idx = new_idx;
-                    self.current_file_idx = 0;
+                    self.current_file_idx = first;
                     self.manual_rotation = 0;
                     self.selection_changed = true;
                     self.set_status(
@@ -527,15 +808,17 @@ impl AppState {
                 let current_dist = self.group_infos[self.current_group_idx].max_dist;
 
                 // Find the last group preceding current one that has strictly smaller max_dist
-                if let Some(new_idx) = self
+                if let Some((new_idx, first)) = self
                     .group_infos
                     .iter()
                     .enumerate()
                     .take(self.current_group_idx)
-                    .rposition(|(_, info)| info.max_dist < current_dist)
+                    .rev()
+                    .filter(|(_, info)| info.max_dist < current_dist)
+                    .find_map(|(i, _)| self.listed_indices(i).first().map(|&f| (i, f)))
                 {
                     self.current_group_idx = new_idx;
-                    self.current_file_idx = 0;
+                    self.current_file_idx = first;
                     self.manual_rotation = 0;
                     self.selection_changed = true;
                     self.set_status(
@@ -572,7 +855,242 @@ impl AppState {
                 }
             }
             InputIntent::ConfirmIgnoreGroup => {} // handled in dialogs.rs
+            InputIntent::ToggleProtect => {}      // handled in dialogs.rs (needs the DB)
+            InputIntent::NextPeer => self.next_peer(),
+            InputIntent::NextCamera | InputIntent::PrevCamera => {} // dialogs.rs (needs the DB)
+        }
+    }
+
+    /// Path as shown in the file list, following the `P` display mode.
+    pub fn display_path(&self, path: &Path) -> String {
+        if self.path_relative_to_root {
+            let per_root: Vec<String> = self
+                .scan_roots
+                .iter()
+                .filter_map(|r| path.strip_prefix(r).ok())
+                .map(|rel| rel.to_string_lossy().to_string())
+                .collect();
+            if per_root.len() > 1 {
+                return per_root.join(" | ");
+            }
+        }
+        if self.path_relative_to_root
+            && let Some(rel) = self.scan_root.as_deref().and_then(|r| path.strip_prefix(r).ok())
+        {
+            return rel.to_string_lossy().to_string();
+        }
+        format_path_depth(path, self.path_display_depth)
+    }
+
+    /// The currently selected file, if any.
+    pub fn current_file(&self) -> Option<&FileMetadata> {
+        self.groups.get(self.current_group_idx).and_then(|g| g.get(self.current_file_idx))
+    }
+
+    /// Select the file with this `unique_file_id` in the current group (after a re-sort).
+    /// Returns false and leaves the selection alone when it is not there.
+    pub fn select_file_id(&mut self, unique_file_id: u128) -> bool {
+        let Some(idx) = self
+            .groups
+            .get(self.current_group_idx)
+            .and_then(|g| g.iter().position(|f| f.unique_file_id == unique_file_id))
+        else {
+            return false;
+        };
+        self.current_file_idx = idx;
+        true
+    }
+
+    /// 1-based position of the current file counted across all groups, and the
+    /// number of files in all groups (the `[n/total]` of the status bar). Files
+    /// hidden by a list filter don't count.
+    pub fn file_position(&self) -> (usize, usize) {
+        if self.hidden.is_empty() {
+            let total = self.groups.iter().map(|g| g.len()).sum();
+            let before: usize =
+                self.groups.iter().take(self.current_group_idx).map(|g| g.len()).sum();
+            return (before + self.current_file_idx + 1, total);
+        }
+        let positions = self.listed_positions();
+        let current = (self.current_group_idx, self.current_file_idx);
+        (positions.iter().filter(|&&p| p < current).count() + 1, positions.len())
+    }
+
+    /// `(group_idx, file_idx)` of the `number`-th listed file (1-based) counted
+    /// across all groups, or None when out of range.
+    pub fn locate_file_number(&self, number: usize) -> Option<(usize, usize)> {
+        self.listed_positions().get(number.checked_sub(1)?).copied()
+    }
+
+    /// Whether the currently selected file is protected.
+    pub fn is_current_protected(&self) -> bool {
+        self.current_file().is_some_and(|f| self.protected.contains(&f.unique_file_id))
+    }
+
+    /// Whether the file at `path` is protected.
+    pub fn is_path_protected(&self, path: &Path) -> bool {
+        self.groups
+            .iter()
+            .flatten()
+            .any(|f| f.path == *path && self.protected.contains(&f.unique_file_id))
+    }
+
+    /// Index of the file to keep when marking a group: a protected file if any, else
+    /// the first by `keep_rules` (by default the highest resolution, then the preferred
+    /// extension from `[grouping] extensions`, then the largest file). Ties go to the
+    /// file listed first.
+    pub fn keeper_index(&self, group: &[FileMetadata]) -> usize {
+        let value = |f: &FileMetadata, key: KeepKey| -> u128 {
+            match key {
+                KeepKey::ExtPriority => {
+                    let ext = f
+                        .path
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .map(|e| e.to_lowercase())
+                        .unwrap_or_default();
+                    self.ext_priorities.get(&ext).copied().unwrap_or(usize::MAX) as u128
+                }
+                KeepKey::Resolution => {
+                    f.resolution.map(|(w, h)| w as u128 * h as u128).unwrap_or(0)
+                }
+                KeepKey::Size => f.size as u128,
+                KeepKey::Mtime => f.modified.timestamp_nanos_opt().unwrap_or(0).max(0) as u128,
+                KeepKey::Raw => crate::scanner::is_raw_ext(&f.path) as u128,
+                KeepKey::PathLength => f.path.as_os_str().len() as u128,
+            }
+        };
+        // Whether `a` ranks before `b`
+        let better = |a: &FileMetadata, b: &FileMetadata| {
+            let protected = |f: &FileMetadata| self.protected.contains(&f.unique_file_id);
+            if protected(a) != protected(b) {
+                return protected(a);
+            }
+            for rule in &self.keep_rules {
+                let (va, vb) = (value(a, rule.key), value(b, rule.key));
+                if va != vb {
+                    return (va > vb) == rule.descending;
+                }
+            }
+            false
+        };
+        let mut best = 0;
+        for (idx, f) in group.iter().enumerate().skip(1) {
+            if better(f, &group[best]) {
+                best = idx;
+            }
+        }
+        best
+    }
+
+    /// Index of the file to keep under `policy`. Ties go to the file listed first.
+    pub fn keeper_index_by(&self, group: &[FileMetadata], policy: KeepPolicy) -> usize {
+        let indexed = group.iter().enumerate();
+        let kept = match policy {
+            KeepPolicy::Best => return self.keeper_index(group),
+            KeepPolicy::Newest => {
+                indexed.min_by_key(|&(idx, f)| (std::cmp::Reverse(f.modified), idx))
+            }
+            KeepPolicy::Oldest => indexed.min_by_key(|&(idx, f)| (f.modified, idx)),
+            KeepPolicy::ShortestPath => {
+                indexed.min_by_key(|&(idx, f)| (f.path.as_os_str().len(), idx))
+            }
+        };
+        kept.map_or(0, |(idx, _)| idx)
+    }
+
+    /// Mark every listed file except each group's keeper (see `keeper_index`); keepers
+    /// that were marked by hand are unmarked. Protected files and entries inside ZIP
+    /// archives (read-only) are never marked.
+    /// Returns the number of files newly marked.
+    pub fn mark_all_but_keepers(&mut self) -> usize {
+        let mut keepers = HashSet::new();
+        let mut to_mark = Vec::new();
+        let listed_groups: Vec<Vec<FileMetadata>> = self
+            .groups
+            .iter()
+            .map(|g| g.iter().filter(|f| !self.hidden.contains(&f.path)).cloned().collect())
+            .collect();
+        for group in listed_groups.iter().filter(|g| g.len() > 1) {
+            let keeper = self.keeper_index(group);
+            for (idx, f) in group.iter().enumerate() {
+                if idx == keeper {
+                    keepers.insert(f.path.clone());
+                } else if !self.protected.contains(&f.unique_file_id)
+                    && !crate::archive::is_archive_entry(&f.path)
+                {
+                    to_mark.push(f.path.clone());
+                }
+            }
+        }
+        self.marked_for_deletion.retain(|p| !keepers.contains(p));
+        self.marks_generation += 1;
+        let mut marked: HashSet<PathBuf> = self.marked_for_deletion.iter().cloned().collect();
+        let mut added = 0;
+        for path in to_mark {
+            if marked.insert(path.clone()) {
+                self.marked_for_deletion.push(path);
+                self.marks_generation += 1;
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Bytes freed by deleting the listed marked files. A hard-linked file only counts
+    /// once, and only when every listed link to it is marked.
+    pub fn marked_reclaimable_bytes(&self) -> u64 {
+        let marks = self.listed_marks();
+        if marks.is_empty() {
+            return 0;
+        }
+        let marked: HashSet<&Path> = marks.iter().map(|p| p.as_path()).collect();
+        let mut inodes: HashMap<u128, u64> = HashMap::new();
+        for f in self.groups.iter().flatten() {
+            if marked.contains(f.path.as_path()) {
+                inodes.insert(f.unique_file_id, f.size);
+            }
+        }
+        for f in self.groups.iter().flatten() {
+            if !marked.contains(f.path.as_path()) {
+                inodes.remove(&f.unique_file_id);
+            }
+        }
+        inodes.values().sum()
+    }
+
+    /// Rebuild `protected` from the content hashes of all listed files.
+    pub fn sync_protected(&mut self, is_protected: impl Fn(&[u8; 32]) -> bool) {
+        self.protected = self
+            .groups
+            .iter()
+            .flatten()
+            .filter(|f| f.content_hash != [0u8; 32] && is_protected(&f.content_hash))
+            .map(|f| f.unique_file_id)
+            .collect();
+    }
+
+    /// Set protection for every listed file with this content, so identical
+    /// copies are covered too. Protected files are also unmarked.
+    /// Returns the number of files affected.
+    pub fn set_content_protected(&mut self, content_hash: &[u8; 32], protected: bool) -> usize {
+        let files: Vec<(u128, PathBuf)> = self
+            .groups
+            .iter()
+            .flatten()
+            .filter(|f| f.content_hash == *content_hash)
+            .map(|f| (f.unique_file_id, f.path.clone()))
+            .collect();
+        for (id, path) in &files {
+            if protected {
+                self.protected.insert(*id);
+                self.marked_for_deletion.retain(|p| p != path);
+                self.marks_generation += 1;
+            } else {
+                self.protected.remove(id);
+            }
         }
+        files.len()
     }
 
     pub fn set_status(&mut self, msg: String, is_error: bool) {
@@ -603,6 +1121,35 @@ impl AppState {
         }
     }
 
+    /// Paths of a group, one per line, for pasting into a report. With `details`
+    /// each line also carries the size in bytes and the resolution, tab-separated.
+    pub fn group_paths_text(&self, group_idx: usize, details: bool, oriented: bool) -> String {
+        let mut out = String::new();
+        for file in self.groups.get(group_idx).into_iter().flatten() {
+            out.push_str(&file.path.to_string_lossy());
+            if details {
+                let res = file
+                    .display_resolution(oriented)
+                    .map(|(w, h)| format!("{}x{}", w, h))
+                    .unwrap_or_else(|| "-".to_string());
+                out.push_str(&format!("\t{}\t{}", file.size, res));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Paths of all marked files across groups, in list order, one per line.
+    pub fn marked_paths_text(&self) -> String {
+        let marked: HashSet<&PathBuf> = self.marked_for_deletion.iter().collect();
+        let mut out = String::new();
+        for file in self.groups.iter().flatten().filter(|f| marked.contains(&f.path)) {
+            out.push_str(&file.path.to_string_lossy());
+            out.push('\n');
+        }
+        out
+    }
+
     /// Get the transform state for the current file (or default if none set)
     pub fn get_current_file_transform(&self) -> FileTransform {
         if let Some(group) = self.groups.get(self.current_group_idx)
@@ -623,6 +1170,10 @@ impl AppState {
                     Some(format!("Error: Destination already exists:\n{:?}", new_path));
                 return;
             }
+            if let Err(e) = fileops::ensure_not_archived(&rename_state.original_path) {
+                self.error_popup = Some(format!("Failed to rename:\n{}", e));
+                return;
+            }
 
             match fs::rename(&rename_state.original_path, &new_path) {
                 Ok(_) => {
@@ -641,25 +1192,16 @@ impl AppState {
         }
     }
 
-    fn perform_sort(&mut self, sort_order: String) {
-        // Capture current file path to preserve selection
-        let current_path = self.get_current_image_path().cloned();
+    pub fn perform_sort(&mut self, sort_order: String) {
+        // Capture current file to preserve selection
+        let current_id = self.current_file().map(|f| f.unique_file_id);
 
         for group in &mut self.groups {
             sort_files(group, &sort_order);
         }
 
-        // Restore selection
-        if let Some(path) = current_path {
-            if let Some(group) = self.groups.get(self.current_group_idx) {
-                if let Some(new_idx) = group.iter().position(|f| f.path == path) {
-                    self.current_file_idx = new_idx;
-                } else {
-                    // Fallback if file not found (unlikely unless list changed concurrently)
-                    self.current_file_idx = 0;
-                }
-            }
-        } else {
+        // Restore selection; fall back to the top if the file is gone
+        if !current_id.is_some_and(|id| self.select_file_id(id)) {
             self.current_file_idx = 0;
         }
 
@@ -667,141 +1209,274 @@ impl AppState {
         self.selection_changed = true;
     }
 
-    pub fn next_item(&mut self) {
-        if self.groups.is_empty() {
+    /// Whether the file at (`g_idx`, `f_idx`) exists and isn't hidden by a list filter.
+    pub fn is_listed(&self, g_idx: usize, f_idx: usize) -> bool {
+        self.groups
+            .get(g_idx)
+            .and_then(|g| g.get(f_idx))
+            .is_some_and(|f| self.hidden.is_empty() || !self.hidden.contains(&f.path))
+    }
+
+    /// Indices of the listed files of group `g_idx`, in list order.
+    pub fn listed_indices(&self, g_idx: usize) -> Vec<usize> {
+        let len = self.groups.get(g_idx).map_or(0, |g| g.len());
+        (0..len).filter(|&f| self.is_listed(g_idx, f)).collect()
+    }
+
+    /// (group, file) of every listed file, in list order.
+    fn listed_positions(&self) -> Vec<(usize, usize)> {
+        (0..self.groups.len())
+            .flat_map(|g| self.listed_indices(g).into_iter().map(move |f| (g, f)))
+            .collect()
+    }
+
+    /// Marked files that are listed; bulk delete and move leave hidden ones alone.
+    pub fn listed_marks(&self) -> Vec<PathBuf> {
+        self.marked_for_deletion.iter().filter(|p| !self.hidden.contains(*p)).cloned().collect()
+    }
+
+    /// After a filter change: move the selection off a hidden file, to the next
+    /// listed one or else the previous one.
+    pub fn select_listed(&mut self) {
+        if self.groups.is_empty() || self.is_listed(self.current_group_idx, self.current_file_idx) {
             return;
         }
-        self.manual_rotation = 0; // Reset rotation
-        let group_len = self.groups[self.current_group_idx].len();
-        if self.current_file_idx + 1 < group_len {
-            self.current_file_idx += 1;
-        } else if self.current_group_idx + 1 < self.groups.len() {
-            self.current_group_idx += 1;
-            self.current_file_idx = 0;
+        let positions = self.listed_positions();
+        let current = (self.current_group_idx, self.current_file_idx);
+        let next = positions.iter().find(|&&p| p > current).or(positions.last());
+        if let Some(&(g, f)) = next {
+            self.current_group_idx = g;
+            self.current_file_idx = f;
         }
     }
-    fn prev_item(&mut self) {
-        if self.groups.is_empty() {
+
+    /// Move the selection `forward` or back by one listed file, across groups.
+    fn step_item(&mut self, forward: bool) {
+        let positions = self.listed_positions();
+        let (Some(&first), Some(&last)) = (positions.first(), positions.last()) else {
             return;
-        }
+        };
         self.manual_rotation = 0; // Reset rotation
-        if self.current_file_idx > 0 {
-            self.current_file_idx -= 1;
-        } else if self.current_group_idx > 0 {
-            self.current_group_idx -= 1;
-            self.current_file_idx = self.groups[self.current_group_idx].len() - 1;
+        let current = (self.current_group_idx, self.current_file_idx);
+        let target = if forward {
+            positions.iter().find(|&&p| p > current).copied().or(self.nav_wrap.then_some(first))
+        } else {
+            positions
+                .iter()
+                .rev()
+                .find(|&&p| p < current)
+                .copied()
+                .or(self.nav_wrap.then_some(last))
+        };
+        if let Some((g, f)) = target {
+            self.current_group_idx = g;
+            self.current_file_idx = f;
         }
     }
-    fn next_group(&mut self) {
-        if self.groups.is_empty() {
+
+    pub fn next_item(&mut self) {
+        self.step_item(true);
+    }
+    fn prev_item(&mut self) {
+        self.step_item(false);
+    }
+    /// Select the next file of the current group that is pixel- or bit-identical
+    /// to the current one, wrapping around the group.
+    fn next_peer(&mut self) {
+        let Some(current) = self.current_file() else {
             return;
+        };
+        let (content_hash, pixel_hash) = (current.content_hash, current.subgroup_pixel_hash());
+        let is_peer = |f: &FileMetadata| {
+            (content_hash != [0u8; 32] && f.content_hash == content_hash)
+                || (pixel_hash.is_some() && f.subgroup_pixel_hash() == pixel_hash)
+        };
+        let group = &self.groups[self.current_group_idx];
+        let len = group.len();
+        let peer = (1..len)
+            .map(|k| (self.current_file_idx + k) % len)
+            .find(|&i| is_peer(&group[i]) && self.is_listed(self.current_group_idx, i));
+        match peer {
+            Some(idx) => {
+                self.current_file_idx = idx;
+                self.manual_rotation = 0;
+                self.selection_changed = true;
+            }
+            None => self.set_status("No identical peer in this group".to_string(), false),
         }
-        self.manual_rotation = 0;
-        self.current_group_idx = (self.current_group_idx + 1) % self.groups.len();
-        self.current_file_idx = 0;
     }
-    fn prev_group(&mut self) {
-        if self.groups.is_empty() {
+    /// Select the first listed file of the next (`forward`) or previous group that
+    /// has any, wrapping around.
+    fn step_group(&mut self, forward: bool) {
+        let n = self.groups.len();
+        if n == 0 {
             return;
         }
         self.manual_rotation = 0;
-        if self.current_group_idx == 0 {
-            self.current_group_idx = self.groups.len() - 1;
-        } else {
-            self.current_group_idx -= 1;
+        let target = (1..=n)
+            .map(|k| {
+                if forward {
+                    (self.current_group_idx + k) % n
+                } else {
+                    (self.current_group_idx + n - k) % n
+                }
+            })
+            .find_map(|g| self.listed_indices(g).first().map(|&f| (g, f)));
+        if let Some((g, f)) = target {
+            self.current_group_idx = g;
+            self.current_file_idx = f;
         }
-        self.current_file_idx = 0;
+    }
+    fn next_group(&mut self) {
+        self.step_group(true);
+    }
+    fn prev_group(&mut self) {
+        self.step_group(false);
     }
     fn go_home(&mut self) {
-        if !self.groups.is_empty() {
-            self.current_group_idx = 0;
-            self.current_file_idx = 0;
+        if let Some(&(g, f)) = self.listed_positions().first() {
+            self.current_group_idx = g;
+            self.current_file_idx = f;
             self.manual_rotation = 0;
         }
     }
     fn go_end(&mut self) {
-        if !self.groups.is_empty() {
-            self.current_group_idx = self.groups.len() - 1;
+        if let Some(&(g, f)) = self.listed_positions().last() {
+            self.current_group_idx = g;
+            self.current_file_idx = f;
             self.manual_rotation = 0;
-            if let Some(g) = self.groups.last() {
-                self.current_file_idx = g.len().saturating_sub(1);
-            }
         }
     }
 
+    /// Move by `view_size` rows of the list, counting a header row per group.
     pub fn move_page(&mut self, down: bool, view_size: usize) {
-        if self.groups.is_empty() {
-            return;
+        // Rows as in the list: (group, None) for a header, then its listed files
+        let mut rows: Vec<(usize, Option<usize>)> = Vec::new();
+        for g_idx in 0..self.groups.len() {
+            let listed = self.listed_indices(g_idx);
+            if !listed.is_empty() {
+                rows.push((g_idx, None));
+                rows.extend(listed.into_iter().map(|f| (g_idx, Some(f))));
+            }
         }
+        let current = (self.current_group_idx, Some(self.current_file_idx));
+        let Some(current_abs) = rows.iter().position(|&r| r == current) else {
+            self.go_home();
+            return;
+        };
         self.manual_rotation = 0;
-        let mut current_abs = 0;
-        for i in 0..self.current_group_idx {
-            current_abs += 1 + self.groups[i].len();
-        }
-        current_abs += 1 + self.current_file_idx;
-        let total_rows: usize = self.groups.iter().map(|g| 1 + g.len()).sum();
         let scroll_amount = view_size.max(1);
         let target_abs = if down {
-            current_abs.saturating_add(scroll_amount).min(total_rows - 1)
+            current_abs.saturating_add(scroll_amount).min(rows.len() - 1)
         } else {
             current_abs.saturating_sub(scroll_amount)
         };
-        let mut accum = 0;
-        for (g_idx, group) in self.groups.iter().enumerate() {
-            let g_len = 1 + group.len();
-            if target_abs < accum + g_len {
-                let offset = target_abs - accum;
-                if offset == 0 {
-                    if down {
-                        self.current_group_idx = g_idx;
-                        self.current_file_idx = 0;
-                    } else if g_idx > 0 {
-                        self.current_group_idx = g_idx - 1;
-                        self.current_file_idx = self.groups[g_idx - 1].len().saturating_sub(1);
-                    } else {
-                        self.current_group_idx = 0;
-                        self.current_file_idx = 0;
-                    }
-                } else {
-                    self.current_group_idx = g_idx;
-                    self.current_file_idx = offset - 1;
-                }
-                return;
+        let target = match rows[target_abs] {
+            (g, Some(f)) => (g, f),
+            // A header: down goes to its first file, up to the file above it
+            (g, None) if down || target_abs == 0 => (g, rows[target_abs + 1].1.unwrap_or(0)),
+            (_, None) => {
+                let (g, f) = rows[target_abs - 1];
+                (g, f.unwrap_or(0))
             }
-            accum += g_len;
-        }
+        };
+        (self.current_group_idx, self.current_file_idx) = target;
     }
 
     fn toggle_delete(&mut self) {
         if let Some(path) = self.get_current_image_path().cloned() {
             if self.marked_for_deletion.contains(&path) {
                 self.marked_for_deletion.retain(|p| p != &path);
+                self.marks_generation += 1;
+            } else if self.is_current_protected() {
+                self.set_status("File is protected (press ! to unprotect)".to_string(), true);
+            } else if crate::archive::is_archive_entry(&path) {
+                self.set_status("File is inside a ZIP archive (read-only)".to_string(), true);
             } else {
                 self.marked_for_deletion.push(path);
+                self.marks_generation += 1;
+            }
+        }
+    }
+
+    /// Look up the live photo companions of every listed file.
+    pub fn index_live_siblings(&mut self) {
+        self.live_siblings = live_photo_sibling_index(
+            self.groups.iter().flatten().map(|f| f.path.as_path()),
+            &self.live_photo_exts,
+        );
+    }
+
+    /// Number of companions that would go along with deleting `paths`.
+    pub fn live_sibling_count<'a>(&self, paths: impl IntoIterator<Item = &'a PathBuf>) -> usize {
+        paths.into_iter().filter_map(|p| self.live_siblings.get(p)).map(|s| s.len()).sum()
+    }
+
+    /// Delete the companions of an already deleted primary, if enabled.
+    /// Returns error lines for the ones that failed.
+    fn remove_live_siblings(&mut self, path: &Path) -> Vec<String> {
+        let Some(siblings) = self.live_siblings.remove(path) else {
+            return Vec::new();
+        };
+        if !self.delete_live_siblings {
+            return Vec::new();
+        }
+        let mut errors = Vec::new();
+        for sibling in siblings {
+            match self.remove_file(&sibling) {
+                Ok(_) => eprintln!(
+                    "[DELETE] {}{} (live photo)",
+                    self.delete_log_tag(),
+                    sibling.display()
+                ),
+                Err(e) => {
+                    errors.push(format!("• {:?}: {}", sibling.file_name().unwrap_or_default(), e))
+                }
             }
         }
+        errors
+    }
+
+    /// The active delete mode, for status lines and dialogs.
+    pub fn delete_mode(&self) -> DeleteMode {
+        DeleteMode::new(self.use_trash, self.staging_dir.as_deref())
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), String> {
+        fileops::remove_file(path, self.use_trash, self.staging_dir.as_deref())
+            .map_err(|e| e.to_string())
+    }
+
+    fn delete_log_tag(&self) -> &'static str {
+        match self.delete_mode() {
+            DeleteMode::Stage => "STAGED ",
+            DeleteMode::Trash => "TRASHED ",
+            DeleteMode::Permanent => "DELETED ",
+        }
     }
 
     fn perform_deletion(&mut self) {
-        if self.marked_for_deletion.is_empty() {
+        let marks = self.listed_marks();
+        if marks.is_empty() {
             return;
         }
+        // Never bulk-delete protected files, even if they were marked before protection
+        let protected_paths: Vec<PathBuf> =
+            marks.iter().filter(|p| self.is_path_protected(p)).cloned().collect();
+        self.marked_for_deletion.retain(|p| !protected_paths.contains(p));
+        self.marks_generation += 1;
         let mut success_count = 0;
         let mut failed_paths = HashSet::new();
-        let deleted_paths = self.marked_for_deletion.clone();
+        let deleted_paths: Vec<PathBuf> =
+            marks.into_iter().filter(|p| !protected_paths.contains(p)).collect();
         let mut error_details = Vec::new();
 
         for path in &deleted_paths {
-            let res = if self.use_trash {
-                trash::delete(path).map_err(|e| e.to_string())
-            } else {
-                fs::remove_file(path).map_err(|e| e.to_string())
-            };
-            match res {
+            match self.remove_file(path) {
                 Ok(_) => {
                     success_count += 1;
-                    let action = if self.use_trash { "TRASHED " } else { "DELETED " };
-                    eprintln!("[DELETE] {}{}", action, path.display());
+                    eprintln!("[DELETE] {}{}", self.delete_log_tag(), path.display());
+                    error_details.extend(self.remove_live_siblings(path));
                 }
                 Err(e) => {
                     error_details.push(format!(
@@ -813,15 +1488,22 @@ impl AppState {
                 }
             }
         }
-        self.marked_for_deletion.retain(|p| failed_paths.contains(p));
+        // Marks on files hidden by a list filter stay
+        self.marked_for_deletion.retain(|p| failed_paths.contains(p) || self.hidden.contains(p));
+        self.marks_generation += 1;
         if success_count > 0 {
             for group in &mut self.groups {
                 group
                     .retain(|f| !deleted_paths.contains(&f.path) || failed_paths.contains(&f.path));
             }
+            // In duplicate mode a group left with one file has nothing to compare against;
+            // drop it unless that file is still marked (its delete failed)
+            let min_len = if self.view_mode { 1 } else { 2 };
             let mut i = 0;
             while i < self.groups.len() {
-                if self.groups[i].is_empty() {
+                let still_marked =
+                    self.groups[i].iter().any(|f| self.marked_for_deletion.contains(&f.path));
+                if self.groups[i].len() < min_len && !still_marked {
                     self.groups.remove(i);
                     self.group_infos.remove(i);
                     if self.current_group_idx >= i && self.current_group_idx > 0 {
@@ -844,15 +1526,29 @@ impl AppState {
                     self.current_file_idx = self.groups[self.current_group_idx].len() - 1;
                 }
             }
+            self.select_listed();
             self.selection_changed = true;
         }
-        if failed_paths.is_empty() {
-            let action = if self.use_trash { "trashed" } else { "permanently deleted" };
-            self.set_status(format!("Successfully {} {} files.", action, success_count), false);
+        if error_details.is_empty() {
+            let action = match self.delete_mode() {
+                DeleteMode::Stage => "staged",
+                DeleteMode::Trash => "trashed",
+                DeleteMode::Permanent => "permanently deleted",
+            };
+            let skipped = if protected_paths.is_empty() {
+                String::new()
+            } else {
+                format!(" Skipped {} protected.", protected_paths.len())
+            };
+            self.set_status(
+                format!("Successfully {} {} files.{}", action, success_count, skipped),
+                false,
+            );
         } else {
-            let mut full_msg = format!("Failed to delete {} files:\n\n", failed_paths.len());
+            let failed_count = error_details.len();
+            let mut full_msg = format!("Failed to delete {} files:\n\n", failed_count);
             full_msg.push_str(&error_details.into_iter().take(5).collect::<Vec<_>>().join("\n"));
-            if failed_paths.len() > 5 {
+            if failed_count > 5 {
                 full_msg.push_str("\n...and others.");
             }
             full_msg.push_str("\n\n(Press any key to dismiss)");
@@ -865,17 +1561,17 @@ impl AppState {
             return;
         };
 
-        let res = if self.use_trash {
-            trash::delete(&path).map_err(|e| e.to_string())
-        } else {
-            fs::remove_file(&path).map_err(|e| e.to_string())
-        };
-
-        match res {
+        match self.remove_file(&path) {
             Ok(_) => {
                 let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
-                let action_log = if self.use_trash { "TRASHED " } else { "DELETED " };
-                eprintln!("[DELETE] {}{}", action_log, path.display());
+                eprintln!("[DELETE] {}{}", self.delete_log_tag(), path.display());
+                let sibling_errors = self.remove_live_siblings(&path);
+                if !sibling_errors.is_empty() {
+                    self.error_popup = Some(format!(
+                        "Failed to delete live photo companion:\n{}",
+                        sibling_errors.join("\n")
+                    ));
+                }
 
                 // Remove from current group
                 if let Some(group) = self.groups.get_mut(self.current_group_idx) {
@@ -904,8 +1600,13 @@ impl AppState {
 
                 // Also remove from marked list if it was there
                 self.marked_for_deletion.retain(|p| p != &path);
+                self.marks_generation += 1;
 
-                let action = if self.use_trash { "Trashed" } else { "Deleted" };
+                let action = match self.delete_mode() {
+                    DeleteMode::Stage => "Staged",
+                    DeleteMode::Trash => "Trashed",
+                    DeleteMode::Permanent => "Deleted",
+                };
                 self.set_status(format!("{}: {}", action, filename), false);
                 self.selection_changed = true;
             }
@@ -936,7 +1637,7 @@ impl AppState {
             for (f_idx, file) in group.iter().enumerate() {
                 let name = file.path.file_name().unwrap_or_default().to_string_lossy();
 
-                if re.is_match(&name) {
+                if re.is_match(&name) && !self.hidden.contains(&file.path) {
                     self.search_results.push((g_idx, f_idx, "Filename".to_string()));
                 }
             }
@@ -1001,14 +1702,15 @@ impl AppState {
         };
         let target_dir = dest.path.clone();
 
-        let paths_to_move = if self.marked_for_deletion.is_empty() {
+        let marks = self.listed_marks();
+        let paths_to_move = if marks.is_empty() {
             if let Some(p) = self.get_current_image_path() {
                 vec![p.clone()]
             } else {
                 return; // Should be handled by InputIntent check, but safety check here
             }
         } else {
-            self.marked_for_deletion.clone()
+            marks
         };
 
         if paths_to_move.is_empty() {
@@ -1038,7 +1740,8 @@ impl AppState {
                 }
             }
         }
-        self.marked_for_deletion.retain(|p| failed_paths.contains(p));
+        self.marked_for_deletion.retain(|p| failed_paths.contains(p) || self.hidden.contains(p));
+        self.marks_generation += 1;
 
         // Report what happened. Source dirs come from each input path's parent;
         // destination dir is `target_dir` for every entry. We list each unique
@@ -1071,42 +1774,7 @@ impl AppState {
         }
 
         if success_count > 0 {
-            // Remove moved files from groups
-            for group in &mut self.groups {
-                group
-                    .retain(|f| !paths_to_move.contains(&f.path) || failed_paths.contains(&f.path));
-            }
-
-            // Clean up empty groups
-            let mut i = 0;
-            while i < self.groups.len() {
-                if self.groups[i].is_empty() {
-                    self.groups.remove(i);
-                    self.group_infos.remove(i);
-                    if self.current_group_idx >= i && self.current_group_idx > 0 {
-                        self.current_group_idx -= 1;
-                    }
-                } else {
-                    self.group_infos[i] =
-                        analyze_group(&mut self.groups[i], &self.group_by, &self.ext_priorities);
-                    i += 1;
-                }
-            }
-
-            // Adjust indices
-            if self.groups.is_empty() {
-                self.current_group_idx = 0;
-                self.current_file_idx = 0;
-            } else {
-                if self.current_group_idx >= self.groups.len() {
-                    self.current_group_idx = self.groups.len() - 1;
-                }
-                if self.current_file_idx >= self.groups[self.current_group_idx].len() {
-                    self.current_file_idx =
-                        self.groups[self.current_group_idx].len().saturating_sub(1);
-                }
-            }
-            self.selection_changed = true;
+            self.drop_moved_files(&paths_to_move, &failed_paths);
         }
 
         if failed_paths.is_empty() {
@@ -1124,6 +1792,141 @@ impl AppState {
             self.error_popup = Some(full_msg);
         }
     }
+
+    /// Remove files that were moved away from the groups (all of `moved` except
+    /// `failed`), drop groups left empty and keep the selection in range.
+    fn drop_moved_files(&mut self, moved: &[PathBuf], failed: &HashSet<PathBuf>) {
+        for group in &mut self.groups {
+            group.retain(|f| !moved.contains(&f.path) || failed.contains(&f.path));
+        }
+
+        // Clean up empty groups
+        let mut i = 0;
+        while i < self.groups.len() {
+            if self.groups[i].is_empty() {
+                self.groups.remove(i);
+                self.group_infos.remove(i);
+                if self.current_group_idx >= i && self.current_group_idx > 0 {
+                    self.current_group_idx -= 1;
+                }
+            } else {
+                self.group_infos[i] =
+                    analyze_group(&mut self.groups[i], &self.group_by, &self.ext_priorities);
+                i += 1;
+            }
+        }
+
+        // Adjust indices
+        if self.groups.is_empty() {
+            self.current_group_idx = 0;
+            self.current_file_idx = 0;
+        } else {
+            if self.current_group_idx >= self.groups.len() {
+                self.current_group_idx = self.groups.len() - 1;
+            }
+            if self.current_file_idx >= self.groups[self.current_group_idx].len() {
+                self.current_file_idx = self.groups[self.current_group_idx].len().saturating_sub(1);
+            }
+        }
+        self.select_listed();
+        self.selection_changed = true;
+    }
+
+    /// Move every marked file into `review_dir`, keeping its path relative to the scan
+    /// root. Protected files are skipped, as for deletion.
+    pub fn move_marked_to_review(&mut self) {
+        let Some(review_dir) = self.review_dir.clone() else {
+            self.set_status("No review_dir configured".to_string(), true);
+            return;
+        };
+        let marks = self.listed_marks();
+        let protected: Vec<PathBuf> =
+            marks.iter().filter(|p| self.is_path_protected(p)).cloned().collect();
+        self.marked_for_deletion.retain(|p| !protected.contains(p));
+        self.marks_generation += 1;
+        let paths: Vec<PathBuf> = marks.into_iter().filter(|p| !protected.contains(p)).collect();
+        if paths.is_empty() {
+            self.set_status("No files marked.".to_string(), false);
+            return;
+        }
+
+        let mut failed = HashSet::new();
+        let mut error_details = Vec::new();
+        for path in &paths {
+            match fileops::move_to_review(&review_dir, self.scan_root.as_deref(), path) {
+                Ok(dest) => eprintln!("[REVIEW] {}  ->  {}", path.display(), dest.display()),
+                Err(e) => {
+                    error_details.push(format!(
+                        "• {:?}: {}",
+                        path.file_name().unwrap_or_default(),
+                        e
+                    ));
+                    failed.insert(path.clone());
+                }
+            }
+        }
+        let moved = paths.len() - failed.len();
+        self.marked_for_deletion.retain(|p| failed.contains(p) || self.hidden.contains(p));
+        self.marks_generation += 1;
+        if moved > 0 {
+            self.drop_moved_files(&paths, &failed);
+        }
+
+        if error_details.is_empty() {
+            let skipped = if protected.is_empty() {
+                String::new()
+            } else {
+                format!(" Skipped {} protected.", protected.len())
+            };
+            self.set_status(
+                format!("Moved {} files to review folder {:?}.{}", moved, review_dir, skipped),
+                false,
+            );
+        } else {
+            let mut full_msg =
+                format!("Failed to move {} files to the review folder:\n\n", error_details.len());
+            full_msg
+                .push_str(&error_details.iter().take(5).cloned().collect::<Vec<_>>().join("\n"));
+            if error_details.len() > 5 {
+                full_msg.push_str("\n...and others.");
+            }
+            full_msg.push_str("\n\n(Press any key to dismiss)");
+            self.error_popup = Some(full_msg);
+        }
+    }
+
+    /// Move everything in the review folder's manifest back where it came from.
+    /// Restored files reappear in the list after a rescan.
+    pub fn restore_review(&mut self) {
+        let Some(review_dir) = self.review_dir.clone() else {
+            self.set_status("No review_dir configured".to_string(), true);
+            return;
+        };
+        let results = match fileops::restore_staged(&review_dir) {
+            Ok(r) => r,
+            Err(e) => {
+                self.set_status(format!("Nothing to restore in {:?}: {}", review_dir, e), true);
+                return;
+            }
+        };
+        let failed: Vec<String> = results
+            .iter()
+            .filter_map(|r| {
+                r.outcome.as_ref().err().map(|e| format!("• {}: {}", r.source.display(), e))
+            })
+            .collect();
+        let restored = results.len() - failed.len();
+        if failed.is_empty() {
+            self.set_status(format!("Restored {} files (rescan to list them)", restored), false);
+        } else {
+            self.error_popup = Some(format!(
+                "Restored {} files, {} failed (still in the review folder):\n\n{}\n\n(Press any key to dismiss)",
+                restored,
+                failed.len(),
+                failed.join("\n")
+            ));
+        }
+    }
 }
 
 /// Returns a map of (dev, ino) -> Vec<&FileMetadata> for files that are hardlinked
@@ -0,0 +1,205 @@
+// Freedesktop.org thumbnails (~/.cache/thumbnails/{normal,large}) written while
+// scanning, so file managers find them ready. Per the Thumbnail Managing Standard
+// the file name is the MD5 of the file's URI, and the PNG carries Thumb::URI and
+// Thumb::MTime so a stale thumbnail can be told from a valid one.
+
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageFormat};
+use md5::{Digest, Md5};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Size directories, largest first so each size is scaled down from the previous one
+const SIZES: [(&str, u32); 2] = [("large", 256), ("normal", 128)];
+
+/// Write the missing or stale thumbnails of `path` from its decoded image.
+/// `mtime` is the file's modification time in Unix seconds. Errors are logged
+/// and otherwise ignored: thumbnails are a courtesy to other programs.
+pub fn write_freedesktop(path: &Path, img: &DynamicImage, orientation: u8, mtime: i64, size: u64) {
+    let Some(root) = dirs::cache_dir().map(|d| d.join("thumbnails")) else {
+        return;
+    };
+    let Ok(abs) = std::path::absolute(path) else {
+        return;
+    };
+    let uri = file_uri(&abs);
+    let name = format!("{}.png", hex::encode(Md5::digest(uri.as_bytes())));
+
+    let mut source: Option<DynamicImage> = None;
+    for (dir, edge) in SIZES {
+        let target = root.join(dir).join(&name);
+        if is_current(&target, &uri, mtime) {
+            continue;
+        }
+        // Never upscale: a small image is stored at its own size
+        let base = source.as_ref().unwrap_or(img);
+        let mut thumb = if base.width() > edge || base.height() > edge {
+            base.thumbnail(edge, edge)
+        } else {
+            base.clone()
+        };
+        if source.is_none()
+            && let Some(o) = Orientation::from_exif(orientation)
+        {
+            thumb.apply_orientation(o);
+        }
+        let thumb = DynamicImage::ImageRgba8(thumb.to_rgba8());
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        if let Err(e) = thumb.write_to(&mut png, ImageFormat::Png) {
+            eprintln!("[THUMBNAIL] {}: encoding failed: {}", path.display(), e);
+            return;
+        }
+        let png = with_text_chunks(
+            png.into_inner(),
+            &[
+                ("Thumb::URI", &uri),
+                ("Thumb::MTime", &mtime.to_string()),
+                ("Thumb::Size", &size.to_string()),
+                ("Software", "rupphash"),
+            ],
+        );
+        if let Err(e) = write_private(&target, &png) {
+            eprintln!("[THUMBNAIL] {}: {}", target.display(), e);
+            return;
+        }
+        source = Some(thumb);
+    }
+}
+
+/// Whether `target` is a thumbnail of `uri` at modification time `mtime`.
+fn is_current(target: &Path, uri: &str, mtime: i64) -> bool {
+    let Ok(png) = fs::read(target) else {
+        return false;
+    };
+    let texts = text_chunks(&png);
+    let get = |key: &[u8]| texts.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+    get(b"Thumb::URI") == Some(uri.as_bytes())
+        && get(b"Thumb::MTime") == Some(mtime.to_string().as_bytes())
+}
+
+/// Write through a temporary file and rename, so readers never see half a PNG.
+/// The directory is 0700 and the file 0600, as the spec asks.
+fn write_private(target: &Path, data: &[u8]) -> std::io::Result<()> {
+    let dir = target.parent().unwrap_or(Path::new("."));
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+
+    let tmp: PathBuf = dir.join(format!(
+        ".{}.{}.tmp",
+        target.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id()
+    ));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let result = options.open(&tmp).and_then(|mut f| f.write_all(data));
+    match result.and_then(|()| fs::rename(&tmp, target)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// `file://` URI of an absolute path, percent-encoding everything but the
+/// unreserved characters and `/`.
+fn file_uri(path: &Path) -> String {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let bytes = path.to_string_lossy().replace('\\', "/").into_bytes();
+
+    let mut uri = String::from("file://");
+    for b in bytes {
+        if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+            uri.push(b as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", b));
+        }
+    }
+    uri
+}
+
+/// Key/value pairs of the tEXt chunks of a PNG (empty if it isn't one).
+fn text_chunks(png: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut out = Vec::new();
+    if !png.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return out;
+    }
+    let mut pos = 8;
+    while let Some(header) = png.get(pos..pos + 8) {
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = &header[4..8];
+        let Some(body) = png.get(pos + 8..pos + 8 + len) else {
+            break;
+        };
+        if kind == b"IEND" {
+            break;
+        }
+        if kind == b"tEXt"
+            && let Some(nul) = body.iter().position(|&b| b == 0)
+        {
+            out.push((&body[..nul], &body[nul + 1..]));
+        }
+        pos += len + 12;
+    }
+    out
+}
+
+/// Insert tEXt chunks just before the trailing IEND chunk of an encoded PNG.
+fn with_text_chunks(mut png: Vec<u8>, texts: &[(&str, &str)]) -> Vec<u8> {
+    let Some(iend) = png.len().checked_sub(12).filter(|&i| &png[i + 4..i + 8] == b"IEND") else {
+        return png;
+    };
+    let tail = png.split_off(iend);
+    for (key, value) in texts {
+        let mut body = Vec::with_capacity(key.len() + 1 + value.len());
+        body.extend_from_slice(key.as_bytes());
+        body.push(0);
+        body.extend_from_slice(value.as_bytes());
+
+        let mut crc = flate2::Crc::new();
+        crc.update(b"tEXt");
+        crc.update(&body);
+        png.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        png.extend_from_slice(b"tEXt");
+        png.extend_from_slice(&body);
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+    }
+    png.extend_from_slice(&tail);
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(file_uri(Path::new("/home/a b/ä.jpg")), "file:///home/a%20b/%C3%A4.jpg");
+    }
+
+    #[test]
+    fn test_text_chunks_round_trip() {
+        let img = DynamicImage::new_rgba8(2, 2);
+        let mut png = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut png, ImageFormat::Png).unwrap();
+        let png = with_text_chunks(
+            png.into_inner(),
+            &[("Thumb::URI", "file:///x"), ("Thumb::MTime", "7")],
+        );
+        assert_eq!(
+            text_chunks(&png),
+            vec![(&b"Thumb::URI"[..], &b"file:///x"[..]), (&b"Thumb::MTime"[..], &b"7"[..])]
+        );
+        // Still decodes as a PNG
+        assert!(image::load_from_memory(&png).is_ok());
+    }
+}
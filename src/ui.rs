@@ -14,10 +14,9 @@ use ratatui::{
 };
 
 use crate::GroupStatus;
+use crate::fileops::DeleteMode;
 use crate::format_relative_time;
-use crate::state::{
-    AppState, InputIntent, format_path_depth, get_bit_identical_counts, get_hardlink_groups,
-};
+use crate::state::{AppState, InputIntent, get_bit_identical_counts, get_hardlink_groups};
 
 pub struct TuiApp {
     state: AppState,
@@ -263,6 +262,9 @@ impl TuiApp {
                 KeyCode::Char('y') | KeyCode::Char('Y') => {
                     self.state.handle_input(InputIntent::ConfirmDelete)
                 }
+                KeyCode::Char('l') | KeyCode::Char('L') => {
+                    self.state.delete_live_siblings = !self.state.delete_live_siblings
+                }
                 KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
                     self.state.handle_input(InputIntent::Cancel)
                 }
@@ -418,6 +420,7 @@ impl TuiApp {
             KeyCode::Char('h') => Some(InputIntent::ToggleRelativeTime),
             KeyCode::Char('p') => Some(InputIntent::TogglePathVisibility),
             KeyCode::Char('x') => Some(InputIntent::ToggleZoomRelative),
+            KeyCode::Char('j') => Some(InputIntent::NextPeer),
             _ => None,
         };
 
@@ -449,7 +452,11 @@ impl TuiApp {
             let color = if *is_error { Color::Red } else { Color::Green };
             Paragraph::new(Span::styled(msg, Style::default().fg(color)))
         } else {
-            let mode = if self.state.use_trash { "Trash" } else { "Perm" };
+            let mode = match self.state.delete_mode() {
+                DeleteMode::Stage => "Stage",
+                DeleteMode::Trash => "Trash",
+                DeleteMode::Permanent => "Perm",
+            };
             let time_mode = if self.state.show_relative_times { "Rel" } else { "Abs" };
             Paragraph::new(Span::raw(format!(
                 "Mode: {} | Time: {} | [Space]: Mark | [d]: Delete | [m]: Move | [r]: Rename | [s]: Sort | [q]: Quit",
@@ -506,14 +513,27 @@ impl TuiApp {
                     Style::default()
                 };
 
-                let marker = if is_marked { "*" } else { " " };
+                let marker = match (is_marked, self.state.live_siblings.contains_key(&file.path)) {
+                    (true, _) => "*",
+                    _ if self.state.protected.contains(&file.unique_file_id) => "!",
+                    (false, true) => "P",
+                    (false, false) => " ",
+                };
                 let marker_style = if is_marked {
                     Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
                 };
 
-                let path_display = format_path_depth(&file.path, self.state.path_display_depth);
+                // Keep the end of long root-relative paths (list width minus border,
+                // highlight symbol and marker)
+                let mut path_display = self.state.display_path(&file.path);
+                let max_chars = (main_layout[0].width as usize).saturating_sub(7).max(8);
+                let chars = path_display.chars().count();
+                if self.state.path_relative_to_root && chars > max_chars {
+                    let tail: String = path_display.chars().skip(chars - max_chars + 1).collect();
+                    path_display = format!("…{}", tail);
+                }
 
                 let size_kb = file.size / 1024;
 
@@ -532,7 +552,7 @@ impl TuiApp {
                 let res_str = if let Some((w, h)) = file.resolution {
                     format!("{}x{}", w, h)
                 } else {
-                    "???x???".to_string()
+                    crate::short_content_hash(file)
                 };
 
                 let line1 = Line::from(vec![
@@ -559,11 +579,23 @@ impl TuiApp {
 
         // 1. Confirmation Popups
         if self.state.show_confirmation {
-            let action = if self.state.use_trash { "trash" } else { "delete" };
+            let action = self.state.delete_mode().verb();
+            let live_count = self.state.live_sibling_count(&self.state.marked_for_deletion);
+            let live_line = if live_count == 0 {
+                String::new()
+            } else {
+                format!(
+                    "\n[{}] (l) Also {} {} live photo companion files",
+                    if self.state.delete_live_siblings { "x" } else { " " },
+                    action,
+                    live_count
+                )
+            };
             let text = format!(
-                "Are you sure you want to {} {} files?\n\n(y) Yes / (n) No",
+                "Are you sure you want to {} {} files?{}\n\n(y) Yes / (n) No",
                 action,
-                self.state.marked_for_deletion.len()
+                self.state.marked_for_deletion.len(),
+                live_line
             );
             render_popup(frame, "Confirm Deletion", &text, 60, 20, Color::Red);
         }
@@ -573,8 +605,16 @@ impl TuiApp {
             let name = path
                 .map(|p| p.file_name().unwrap_or_default().to_string_lossy())
                 .unwrap_or_default();
-            let action = if self.state.use_trash { "trash" } else { "delete" };
-            let text = format!("{} current file?\n{}\n\n(y) Yes / (n) No", action, name);
+            let action = self.state.delete_mode().verb();
+            let protected = if !self.state.is_current_protected() {
+                ""
+            } else if self.state.protected_delete_armed {
+                "\nPROTECTED - press y again to delete anyway"
+            } else {
+                "\nPROTECTED"
+            };
+            let text =
+                format!("{} current file?\n{}{}\n\n(y) Yes / (n) No", action, name, protected);
             render_popup(frame, "Confirm Delete", &text, 60, 20, Color::Red);
         }
 
@@ -0,0 +1,170 @@
+// Haar wavelet hash (whash), 64 bits.
+// The image is reduced to 64x64 grayscale, decomposed with three levels of the
+// 2D Haar transform, and the remaining 8x8 low-frequency band is thresholded
+// against its median. Coarser than PDQ, but the LL band barely moves under
+// heavy recompression or resizing, which is exactly where PDQ starts to drift.
+
+use image::DynamicImage;
+use image::imageops::FilterType;
+
+const IMAGE_SCALE: usize = 64;
+const HASH_SIZE: usize = 8;
+
+/// Compute the 64-bit wavelet hash. Bit `y * 8 + x` belongs to cell (x, y) of the LL band.
+pub fn compute_whash(img: &DynamicImage) -> u64 {
+    let small =
+        img.resize_exact(IMAGE_SCALE as u32, IMAGE_SCALE as u32, FilterType::Triangle).to_luma32f();
+    let mut data: Vec<f32> = small.into_raw();
+
+    // Every level halves the LL band: 64 -> 32 -> 16 -> 8
+    let mut size = IMAGE_SCALE;
+    while size > HASH_SIZE {
+        haar_step(&mut data, IMAGE_SCALE, size);
+        size /= 2;
+    }
+
+    // The global DC term is irrelevant here: thresholding at the median is shift-invariant
+    let ll: Vec<f32> = (0..HASH_SIZE)
+        .flat_map(|y| data[y * IMAGE_SCALE..y * IMAGE_SCALE + HASH_SIZE].to_vec())
+        .collect();
+    let mut sorted = ll.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = (sorted[31] + sorted[32]) / 2.0;
+
+    let mut hash = 0u64;
+    for (i, v) in ll.iter().enumerate() {
+        if *v > median {
+            hash |= 1 << i;
+        }
+    }
+    hash
+}
+
+/// One level of the orthonormal 2D Haar transform on the top-left `size` x `size`
+/// block of a `stride`-wide buffer: rows first, then columns. Averages end up in
+/// the first half of each axis, details in the second.
+fn haar_step(data: &mut [f32], stride: usize, size: usize) {
+    let half = size / 2;
+    let mut tmp = vec![0.0f32; size];
+    let s = std::f32::consts::FRAC_1_SQRT_2;
+
+    for y in 0..size {
+        let row = &mut data[y * stride..y * stride + size];
+        let (lo, hi) = tmp.split_at_mut(half);
+        for ((l, h), pair) in lo.iter_mut().zip(hi.iter_mut()).zip(row.chunks_exact(2)) {
+            *l = (pair[0] + pair[1]) * s;
+            *h = (pair[0] - pair[1]) * s;
+        }
+        row.copy_from_slice(&tmp);
+    }
+    for x in 0..size {
+        let (lo, hi) = tmp.split_at_mut(half);
+        for (i, (l, h)) in lo.iter_mut().zip(hi.iter_mut()).enumerate() {
+            let a = data[2 * i * stride + x];
+            let b = data[(2 * i + 1) * stride + x];
+            *l = (a + b) * s;
+            *h = (a - b) * s;
+        }
+        for (i, v) in tmp.iter().enumerate() {
+            data[i * stride + x] = *v;
+        }
+    }
+}
+
+/// The hash of the image under all 8 rotations/flips. Each bit is one LL cell,
+/// so transforming the 8x8 bit grid gives the exact result without re-decoding.
+pub fn dihedral_variants(hash: u64) -> [u64; 8] {
+    let n = HASH_SIZE - 1;
+    let transforms: [fn(usize, usize, usize) -> (usize, usize); 8] = [
+        |x, y, _| (x, y),
+        |x, y, n| (n - y, x),     // rotate 90
+        |x, y, n| (n - x, n - y), // rotate 180
+        |x, y, n| (y, n - x),     // rotate 270
+        |x, y, n| (n - x, y),     // flip horizontal
+        |x, y, n| (x, n - y),     // flip vertical
+        |x, y, _| (y, x),         // transpose
+        |x, y, n| (n - y, n - x), // anti-transpose
+    ];
+    let mut out = [0u64; 8];
+    for (t, transform) in transforms.iter().enumerate() {
+        for y in 0..HASH_SIZE {
+            for x in 0..HASH_SIZE {
+                if hash & (1 << (y * HASH_SIZE + x)) != 0 {
+                    let (nx, ny) = transform(x, y, n);
+                    out[t] |= 1 << (ny * HASH_SIZE + nx);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hamminghash::HammingHash;
+    use image::{ImageBuffer, Rgb};
+
+    fn test_image(seed: f32) -> DynamicImage {
+        let img = ImageBuffer::from_fn(512, 384, |x, y| {
+            let (fx, fy) = (x as f32, y as f32);
+            let v = ((fx / (37.0 + seed)).sin() + (fy / (53.0 - seed)).cos()) * 60.0 + 128.0;
+            let w = ((fx + fy) / 512.0 * 255.0) as u8;
+            Rgb([v as u8, ((v + w as f32) / 2.0) as u8, (255.0 - v) as u8])
+        });
+        DynamicImage::ImageRgb8(img)
+    }
+
+    fn pdq(img: &DynamicImage) -> [u8; 32] {
+        crate::pdqhash::generate_pdq_features(img).expect("PDQ failed").0.to_hash()
+    }
+
+    #[test]
+    fn test_dihedral_variants_roundtrip() {
+        let h = 0x0123_4567_89ab_cdef;
+        let v = dihedral_variants(h);
+        assert_eq!(v[0], h);
+        assert_eq!(dihedral_variants(v[2])[2], h); // 180 twice
+        assert_eq!(dihedral_variants(v[1])[3], h); // 90 then 270
+        assert_eq!(v.iter().map(|x| x.count_ones()).max(), Some(h.count_ones()));
+    }
+
+    #[test]
+    fn test_whash_rotation_matches_variant() {
+        let img = test_image(0.0);
+        let rotated = img.rotate90();
+        assert!(
+            compute_whash(&rotated).hamming_distance(&dihedral_variants(compute_whash(&img))[1])
+                <= 2
+        );
+    }
+
+    /// Resize chain 512 -> 256 -> 128 -> 64 -> back up to 512. Both hashes must stay
+    /// within their own match threshold at every step.
+    #[test]
+    fn test_resize_chain_pdq_vs_whash() {
+        use crate::hamminghash::{MAX_SIMILARITY_64, MAX_SIMILARITY_256};
+
+        let original = test_image(0.0);
+        let (w0, p0) = (compute_whash(&original), pdq(&original));
+
+        let mut current = original.clone();
+        for size in [256, 128, 64] {
+            current = current.resize_exact(size, size * 3 / 4, FilterType::Triangle);
+            let dw = w0.hamming_distance(&compute_whash(&current));
+            let dp = p0.hamming_distance(&pdq(&current));
+            assert!(dw <= MAX_SIMILARITY_64, "whash drifted to {}/64 at {}px", dw, size);
+            assert!(dp <= MAX_SIMILARITY_256, "pdq drifted to {}/256 at {}px", dp, size);
+        }
+
+        let upscaled = current.resize_exact(512, 384, FilterType::Triangle);
+        let dw = w0.hamming_distance(&compute_whash(&upscaled));
+        let dp = p0.hamming_distance(&pdq(&upscaled));
+        assert!(dw <= MAX_SIMILARITY_64, "whash drifted to {}/64 after upscaling", dw);
+        assert!(dp <= MAX_SIMILARITY_256, "pdq drifted to {}/256 after upscaling", dp);
+
+        // A different image must stay well apart
+        let other = compute_whash(&test_image(17.0));
+        assert!(w0.hamming_distance(&other) > 8);
+    }
+}